@@ -0,0 +1,171 @@
+use crate::cli::Args;
+use anyhow::{bail, Result};
+use clap::CommandFactory;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+/// A shortcut for a command with its arguments (`st = "status -q"`), or a macro of several
+/// commands that run one after another (`sync = ["fetch", "pull -s"]`)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Alias {
+    Command(String),
+    Macro(Vec<String>),
+}
+
+impl Alias {
+    fn commands(&self) -> Vec<&str> {
+        match self {
+            Alias::Command(command) => vec![command.as_str()],
+            Alias::Macro(commands) => commands.iter().map(|c| c.as_str()).collect(),
+        }
+    }
+}
+
+/// Expand the alias in a command line into the command lines to run
+///
+/// The global arguments before the alias are kept and the arguments after it are appended to
+/// every command of a macro. Aliases can refer to other aliases, but not shadow built-in
+/// commands.
+pub fn expand(
+    args: Vec<OsString>,
+    aliases: &BTreeMap<String, Alias>,
+) -> Result<Vec<Vec<OsString>>> {
+    expand_with(args, aliases, &mut vec![])
+}
+
+fn expand_with(
+    args: Vec<OsString>,
+    aliases: &BTreeMap<String, Alias>,
+    expanding: &mut Vec<String>,
+) -> Result<Vec<Vec<OsString>>> {
+    let command = Args::command();
+    let position = match command_position(&command, &args) {
+        Some(position) => position,
+        None => return Ok(vec![args]),
+    };
+    let name = args[position].to_string_lossy().to_string();
+    let alias = match aliases.get(&name) {
+        Some(alias) if command.find_subcommand(&name).is_none() => alias,
+        _ => return Ok(vec![args]),
+    };
+
+    if expanding.contains(&name) {
+        bail!(
+            "The alias {} refers to itself through {}",
+            name,
+            expanding.join(" -> ")
+        );
+    }
+    expanding.push(name);
+
+    let mut result = vec![];
+    for line in alias.commands() {
+        let mut expanded = args[..position].to_vec();
+        expanded.extend(split(line)?.into_iter().map(OsString::from));
+        expanded.extend(args[position + 1..].iter().cloned());
+        result.extend(expand_with(expanded, aliases, expanding)?);
+    }
+
+    expanding.pop();
+    Ok(result)
+}
+
+/// Index of the command name, after the program name and the global arguments
+fn command_position(command: &clap::Command, args: &[OsString]) -> Option<usize> {
+    let takes_value: Vec<String> = command
+        .get_arguments()
+        .filter(|a| !a.is_positional() && a.get_action().takes_values())
+        .filter_map(|a| a.get_long().map(|l| format!("--{}", l)))
+        .collect();
+
+    let mut position = 1;
+    while let Some(token) = args.get(position) {
+        let token = token.to_string_lossy();
+        if !token.starts_with('-') {
+            return Some(position);
+        }
+        position += if takes_value.iter().any(|t| *t == token) {
+            2
+        } else {
+            1
+        };
+    }
+    None
+}
+
+/// Split a command line into words, with single or double quotes around words with spaces
+fn split(line: &str) -> Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        bail!("The alias {:?} has an unterminated quote", line);
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_str(args: &[&str], aliases: &str) -> Result<Vec<Vec<String>>> {
+        let aliases: BTreeMap<String, Alias> = toml::from_str(aliases).unwrap();
+        let args = args.iter().map(OsString::from).collect();
+        Ok(expand(args, &aliases)?
+            .into_iter()
+            .map(|l| l.iter().map(|a| a.to_string_lossy().to_string()).collect())
+            .collect())
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let aliases = r#"
+            st = "status -q"
+            campaign = "create branch --push -b main"
+            msg = "commit -m 'Update the readme'"
+            sync = ["fetch", "st"]
+            status = "pull"
+            loop = ["sync", "loop"]
+        "#;
+
+        assert_eq!(
+            expand_str(&["gut", "--format", "json", "st", "-o", "divvun"], aliases).unwrap(),
+            [["gut", "--format", "json", "status", "-q", "-o", "divvun"]]
+        );
+        assert_eq!(
+            expand_str(&["gut", "msg"], aliases).unwrap(),
+            [["gut", "commit", "-m", "Update the readme"]]
+        );
+        assert_eq!(
+            expand_str(&["gut", "sync", "-r", "^lang-"], aliases).unwrap(),
+            [
+                vec!["gut", "fetch", "-r", "^lang-"],
+                vec!["gut", "status", "-q", "-r", "^lang-"]
+            ]
+        );
+        // Built-in commands are not shadowed
+        assert_eq!(
+            expand_str(&["gut", "status"], aliases).unwrap(),
+            [["gut", "status"]]
+        );
+        assert!(expand_str(&["gut", "loop"], aliases).is_err());
+        assert!(split("commit -m 'Update").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The branch to work on per repository, for the --branch-map of pull, push and checkout
+///
+/// It is a toml file with the branch of each repository, keyed by `owner/name` or by the name
+/// alone for the repositories of every owner:
+///
+/// ```toml
+/// lang-sme = "develop"
+/// "giellalt/lang-fin" = "gut/bump-version"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BranchMap {
+    branches: BTreeMap<String, String>,
+}
+
+impl BranchMap {
+    pub fn load(path: &Path) -> Result<BranchMap> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read the branch map from {:?}", path))?;
+        BranchMap::parse(&content)
+            .with_context(|| format!("Cannot read the branch map from {:?}", path))
+    }
+
+    fn parse(content: &str) -> Result<BranchMap> {
+        Ok(BranchMap {
+            branches: toml::from_str(content)?,
+        })
+    }
+
+    /// The branch of a repository, an entry with the owner goes before one with the name alone
+    pub fn get(&self, owner: &str, name: &str) -> Option<&str> {
+        self.branches
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&format!("{}/{}", owner, name)))
+            .or_else(|| {
+                self.branches
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            })
+            .map(|(_, branch)| branch.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let map = BranchMap::parse(
+            "lang-sme = \"develop\"\n\"giellalt/lang-sme\" = \"main\"\nlang-fin = \"gut/bump\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(map.get("giellalt", "lang-sme"), Some("main"));
+        assert_eq!(map.get("divvun", "lang-sme"), Some("develop"));
+        assert_eq!(map.get("giellalt", "lang-fin"), Some("gut/bump"));
+        assert_eq!(map.get("giellalt", "lang-smj"), None);
+        assert!(BranchMap::parse("lang-sme = 1").is_err());
+    }
+}
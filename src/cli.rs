@@ -1,10 +1,21 @@
 use crate::commands::{
-    AddArgs, ApplyArgs, BranchArgs, CheckoutArgs, CiArgs, CleanArgs, CloneArgs, CommitArgs,
-    CreateArgs, FetchArgs, HookArgs, InitArgs, InviteArgs, MakeArgs, MergeArgs, PullArgs, PushArgs,
-    RemoveArgs, RenameArgs, SetArgs, ShowArgs, StatusArgs, TemplateArgs, TopicArgs, TransferArgs,
-    WorkflowArgs,
+    AddArgs, AffectedArgs, AmendArgs, ApplyArgs, BlameFileArgs, BranchArgs, CacheArgs,
+    ChangelogArgs, CheckArgs, CheckoutArgs, CiArgs, CleanArgs, CloneArgs, CommitArgs, ConvertArgs,
+    CreateArgs, DepsArgs, DiffArgs, EncodeArgs, EnvironmentArgs, FetchArgs, GuardArgs, HookArgs,
+    InitArgs, InviteArgs, IssueArgs, LfsArgs, LockArgs, MailmapArgs, MakeArgs, MergeArgs,
+    NormalizeArgs, NotificationsArgs, OpenArgs, PackagesArgs, PagesArgs, PrArgs, ProjectArgs,
+    PruneBranchesArgs, PullArgs, PurgeFileArgs, PushArgs, ReleaseArgs, RemoveArgs, RenameArgs,
+    RepairArgs, ReviewArgs, RunnerArgs, SearchArgs, SecretArgs, SetArgs, ShowArgs, SnapshotArgs,
+    StarArgs, StateArgs, StatsArgs, StatusArgs, TemplateArgs, TopicArgs, TransferArgs, TriageArgs,
+    UnlockArgs, UpstreamArgs, VerifyArgs, VersionArgs, WatchArgs, WhoamiArgs, WorkflowArgs,
+    WorkspaceArgs,
 };
-use clap::{Parser, ValueEnum, Subcommand};
+#[cfg(feature = "tui")]
+use crate::commands::TuiArgs;
+use crate::filter;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, Command, CommandFactory, FromArgMatches, Parser, ValueEnum, Subcommand};
+use std::ffi::{OsStr, OsString};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum OutputFormat {
@@ -14,23 +25,206 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    /// Emit a json line on stderr when the work on a repository starts and finishes
+    Json,
+}
+
 #[derive(Debug, Parser)]
 #[command(name = "gut", about = "git multirepo maintenance tool")]
 pub struct Args {
     #[arg(long, value_enum, default_value = "table")]
     pub format: Option<OutputFormat>,
+    #[arg(long, global = true)]
+    /// Select the repositories of a saved workspace instead of the organisation and regex
+    /// arguments of the command
+    pub workspace: Option<String>,
+    #[arg(long, value_enum, global = true)]
+    /// Report the progress of commands that work on many repositories
+    pub progress: Option<ProgressFormat>,
+    #[arg(long, global = true)]
+    /// Wait for another gut run that changes repositories in the root directory to finish,
+    /// instead of stopping
+    pub wait: bool,
+    #[arg(long, global = true, conflicts_with = "wait")]
+    /// Run without the lock that keeps runs in the same root directory apart
+    pub no_lock: bool,
+    #[arg(long, global = true)]
+    /// Send the output of the command by email, to the recipients of the [email] config
+    pub email: bool,
+    #[arg(long, global = true)]
+    /// Refuse to run anything that writes to the GitHub api, pushes or changes local
+    /// repositories, also set with read_only in the config or GUT_READ_ONLY
+    pub read_only: bool,
+    #[arg(skip)]
+    /// Whether the organisation or the regex of the command is given on the command line,
+    /// which selects the repositories instead of the workspace chosen with `gut workspace use`
+    pub selects_repos: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Args {
+    /// Parse the command line arguments, using the command defaults from the config file
+    /// instead of the built-in defaults of the arguments
+    pub fn parse_with_defaults(args: Vec<OsString>, defaults: &toml::Table) -> Args {
+        let command = with_defaults(Args::command(), defaults);
+        let args = join_repeated_regex(&command, args);
+        let matches = command.get_matches_from(args);
+        let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        args.selects_repos = selects_repos(&matches);
+        args
+    }
+}
+
+/// Whether the organisation or regex argument of the subcommand is on the command line
+fn selects_repos(matches: &ArgMatches) -> bool {
+    let given = |id: &str| {
+        matches.ids().any(|i| i == id) && matches.value_source(id) == Some(ValueSource::CommandLine)
+    };
+    if given("organisation") || given("regex") {
+        return true;
+    }
+    matches
+        .subcommand()
+        .map(|(_, sub)| selects_repos(sub))
+        .unwrap_or(false)
+}
+
+/// Join the values of a repeated regex argument into one
+///
+/// The regex argument can be repeated to select repositories of several owners
+/// (`-r giellalt:^lang-s -r divvun:^divvun-gramcheck$`), but it only holds one `Filter`.
+/// The values are joined into the first one with `filter::SEPARATOR`, which a value cannot
+/// contain, so that a single value is always one pattern.
+fn join_repeated_regex(command: &Command, args: Vec<OsString>) -> Vec<OsString> {
+    let appending = with_appending_regex(command.clone());
+    let matches = match appending.clone().try_get_matches_from(&args) {
+        Ok(matches) => matches,
+        Err(_) => return args,
+    };
+
+    let (mut leaf, mut leaf_matches) = (&appending, &matches);
+    while let Some((name, sub_matches)) = leaf_matches.subcommand() {
+        match leaf.find_subcommand(name) {
+            Some(sub) => (leaf, leaf_matches) = (sub, sub_matches),
+            None => return args,
+        }
+    }
+
+    let values: Vec<&OsStr> = match leaf_matches.try_get_raw("regex") {
+        Ok(Some(values)) => values.collect(),
+        _ => return args,
+    };
+    let regex = match leaf.get_arguments().find(|a| a.get_id() == "regex") {
+        Some(regex) if values.len() > 1 => regex,
+        _ => return args,
+    };
+
+    let is_flag = |token: &OsStr| {
+        regex.get_short().map(|s| format!("-{}", s)).as_deref() == token.to_str()
+            || regex.get_long().map(|l| format!("--{}", l)).as_deref() == token.to_str()
+    };
+    let joined = values.join(OsStr::new(&filter::SEPARATOR.to_string()));
+
+    let mut result = vec![];
+    let mut found = 0;
+    let mut tokens = args.iter();
+    while let Some(token) = tokens.next() {
+        if is_flag(token) {
+            tokens.next();
+            found += 1;
+            if found == 1 {
+                result.push(token.clone());
+                result.push(joined.clone());
+            }
+        } else {
+            result.push(token.clone());
+        }
+    }
+
+    // Other forms such as --regex=value are left to clap to complain about
+    if found == values.len() {
+        result
+    } else {
+        args
+    }
+}
+
+fn with_appending_regex(mut command: Command) -> Command {
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|c| c.get_name().to_string())
+        .collect();
+    for name in names {
+        command = command.mut_subcommand(name, with_appending_regex);
+    }
+    if command.get_arguments().any(|a| a.get_id() == "regex") {
+        command = command.mut_arg("regex", |a| a.action(ArgAction::Append));
+    }
+    command
+}
+
+/// Set the default values of the arguments of a command and its subcommands
+///
+/// Tables are matched with subcommands (`[defaults.ci.export]`), other values with the
+/// arguments of the command, by the name of the argument (`use_https` or `use-https`).
+fn with_defaults(mut command: Command, defaults: &toml::Table) -> Command {
+    for (key, value) in defaults {
+        if let toml::Value::Table(table) = value {
+            if command.find_subcommand(key).is_some() {
+                command = command.mut_subcommand(key, |c| with_defaults(c, table));
+                continue;
+            }
+        }
+
+        let id = key.replace('-', "_");
+        if command.get_arguments().any(|a| a.get_id() == id.as_str()) {
+            let values = match value {
+                toml::Value::String(s) => vec![s.clone()],
+                toml::Value::Array(array) => array.iter().map(value_to_string).collect(),
+                v => vec![value_to_string(v)],
+            };
+            command = command.mut_arg(id, |a| a.default_values(values));
+        } else {
+            log::warn!(
+                "Ignoring unknown default {:?} for command {:?} in the config file",
+                key,
+                command.get_name()
+            );
+        }
+    }
+    command
+}
+
+fn value_to_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     #[command(name = "add")]
     Add(AddArgs),
+    #[command(name = "affected")]
+    Affected(AffectedArgs),
+    #[command(name = "amend")]
+    Amend(AmendArgs),
     #[command(name = "apply", aliases = &["ap"])]
     Apply(ApplyArgs),
+    #[command(name = "blame-file")]
+    BlameFile(BlameFileArgs),
     #[command(name = "branch", aliases = &["br"])]
     Branch(BranchArgs),
+    #[command(name = "cache")]
+    Cache(CacheArgs),
+    #[command(name = "changelog")]
+    Changelog(ChangelogArgs),
+    #[command(name = "check")]
+    Check(CheckArgs),
     #[command(name = "checkout", aliases = &["co"])]
     Checkout(CheckoutArgs),
     #[command(name = "ci")]
@@ -41,32 +235,90 @@ pub enum Commands {
     Clean(CleanArgs),
     #[command(name = "commit")]
     Commit(CommitArgs),
+    #[command(name = "convert")]
+    Convert(ConvertArgs),
     #[command(name = "create", aliases = &["cr"])]
     Create(CreateArgs),
+    #[command(name = "deps")]
+    Deps(DepsArgs),
+    #[command(name = "diff")]
+    Diff(DiffArgs),
+    #[command(name = "encode")]
+    Encode(EncodeArgs),
+    #[command(name = "environment", aliases = &["env"])]
+    Environment(EnvironmentArgs),
     #[command(name = "fetch")]
     Fetch(FetchArgs),
+    #[command(name = "guard")]
+    Guard(GuardArgs),
     #[command(name = "hook")]
     Hook(HookArgs),
     #[command(name = "init")]
     Init(InitArgs),
     #[command(name = "invite")]
     Invite(InviteArgs),
+    #[command(name = "issue")]
+    Issue(IssueArgs),
+    #[command(name = "lfs")]
+    Lfs(LfsArgs),
+    #[command(name = "lock")]
+    Lock(LockArgs),
+    #[command(name = "mailmap")]
+    Mailmap(MailmapArgs),
     #[command(name = "make")]
     Make(MakeArgs),
     #[command(name = "merge")]
     Merge(MergeArgs),
+    #[command(name = "normalize")]
+    Normalize(NormalizeArgs),
+    #[command(name = "notifications")]
+    Notifications(NotificationsArgs),
+    #[command(name = "open")]
+    Open(OpenArgs),
+    #[command(name = "packages")]
+    Packages(PackagesArgs),
+    #[command(name = "pages")]
+    Pages(PagesArgs),
+    #[command(name = "pr")]
+    Pr(PrArgs),
+    #[command(name = "project")]
+    Project(ProjectArgs),
+    #[command(name = "prune-branches")]
+    PruneBranches(PruneBranchesArgs),
     #[command(name = "pull")]
     Pull(PullArgs),
+    #[command(name = "purge-file")]
+    PurgeFile(PurgeFileArgs),
     #[command(name = "push")]
     Push(PushArgs),
+    #[command(name = "release")]
+    Release(ReleaseArgs),
     #[command(name = "remove")]
     Remove(RemoveArgs),
     #[command(name = "rename")]
     Rename(RenameArgs),
+    #[command(name = "repair")]
+    Repair(RepairArgs),
+    #[command(name = "review")]
+    Review(ReviewArgs),
+    #[command(name = "runner")]
+    Runner(RunnerArgs),
+    #[command(name = "search")]
+    Search(SearchArgs),
+    #[command(name = "secret")]
+    Secret(SecretArgs),
     #[command(name = "set")]
     Set(SetArgs),
     #[command(name = "show")]
     Show(ShowArgs),
+    #[command(name = "snapshot")]
+    Snapshot(SnapshotArgs),
+    #[command(name = "star")]
+    Star(StarArgs),
+    #[command(name = "state")]
+    State(StateArgs),
+    #[command(name = "stats")]
+    Stats(StatsArgs),
     #[command(name = "status")]
     Status(StatusArgs),
     #[command(name = "template")]
@@ -75,6 +327,155 @@ pub enum Commands {
     Topic(TopicArgs),
     #[command(name = "transfer")]
     Transfer(TransferArgs),
+    #[command(name = "triage")]
+    Triage(TriageArgs),
+    #[cfg(feature = "tui")]
+    #[command(name = "tui")]
+    Tui(TuiArgs),
+    #[command(name = "unlock")]
+    Unlock(UnlockArgs),
+    #[command(name = "upstream")]
+    Upstream(UpstreamArgs),
+    #[command(name = "verify")]
+    Verify(VerifyArgs),
+    #[command(name = "version")]
+    Version(VersionArgs),
+    #[command(name = "watch")]
+    Watch(WatchArgs),
+    #[command(name = "whoami")]
+    Whoami(WhoamiArgs),
     #[command(name = "workflow")]
     Workflow(WorkflowArgs),
+    #[command(name = "workspace", aliases = &["ws"])]
+    Workspace(WorkspaceArgs),
+}
+
+impl Commands {
+    /// Whether the command may run in read-only mode
+    ///
+    /// Clone and fetch only bring the remote state in. Api writes and pushes of the other
+    /// commands are refused where they are made.
+    pub fn allowed_read_only(&self) -> bool {
+        matches!(self, Commands::Clone(_) | Commands::Fetch(_)) || !self.changes_local_repos()
+    }
+
+    /// Whether the command changes local repositories, and must not run while another run does
+    pub fn changes_local_repos(&self) -> bool {
+        #[cfg(feature = "tui")]
+        if let Commands::Tui(_) = self {
+            return true;
+        }
+        matches!(
+            self,
+            Commands::Amend(_)
+                | Commands::Apply(_)
+                | Commands::Branch(_)
+                | Commands::Changelog(_)
+                | Commands::Checkout(_)
+                | Commands::Ci(_)
+                | Commands::Clean(_)
+                | Commands::Clone(_)
+                | Commands::Commit(_)
+                | Commands::Convert(_)
+                | Commands::Create(_)
+                | Commands::Fetch(_)
+                | Commands::Hook(_)
+                | Commands::Lfs(_)
+                | Commands::Mailmap(_)
+                | Commands::Merge(_)
+                | Commands::Normalize(_)
+                | Commands::PruneBranches(_)
+                | Commands::Pull(_)
+                | Commands::PurgeFile(_)
+                | Commands::Push(_)
+                | Commands::Repair(_)
+                | Commands::Snapshot(_)
+                | Commands::Template(_)
+                | Commands::Upstream(_)
+                | Commands::Version(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(defaults: &str, args: &[&str]) -> Args {
+        let defaults: toml::Table = toml::from_str(defaults).unwrap();
+        let matches = with_defaults(Args::command(), &defaults)
+            .try_get_matches_from(args)
+            .unwrap();
+        Args::from_arg_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn test_command_defaults() {
+        let defaults = r#"
+            format = "json"
+            [pull]
+            stash = true
+            organisation = "giellalt"
+        "#;
+
+        let args = parse(defaults, &["gut", "pull"]);
+        assert_eq!(args.format, Some(OutputFormat::Json));
+        match args.command {
+            Commands::Pull(pull) => {
+                assert!(pull.stash);
+                assert!(!pull.merge);
+                assert_eq!(pull.organisation.as_deref(), Some("giellalt"));
+            }
+            _ => panic!("Expected the pull command"),
+        }
+
+        let args = parse(
+            defaults,
+            &["gut", "--format", "table", "pull", "-o", "divvun"],
+        );
+        assert_eq!(args.format, Some(OutputFormat::Table));
+        match args.command {
+            Commands::Pull(pull) => assert_eq!(pull.organisation.as_deref(), Some("divvun")),
+            _ => panic!("Expected the pull command"),
+        }
+    }
+
+    #[test]
+    fn test_selects_repos() {
+        let matches = |args: &[&str]| Args::command().try_get_matches_from(args).unwrap();
+        assert!(selects_repos(&matches(&["gut", "pull", "-o", "divvun"])));
+        assert!(selects_repos(&matches(&["gut", "pull", "-r", "^lang-"])));
+        assert!(!selects_repos(&matches(&["gut", "pull"])));
+
+        let defaults: toml::Table = toml::from_str("[pull]\norganisation = \"divvun\"").unwrap();
+        let matches = with_defaults(Args::command(), &defaults)
+            .try_get_matches_from(["gut", "pull"])
+            .unwrap();
+        assert!(!selects_repos(&matches));
+    }
+    #[test]
+    fn test_repeated_regex() {
+        let command = Args::command();
+        let args = [
+            "gut",
+            "pull",
+            "-r",
+            "giellalt:^lang-s",
+            "-s",
+            "--regex",
+            "divvun:^kbd",
+        ];
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let joined = join_repeated_regex(&command, args);
+        assert_eq!(
+            joined,
+            ["gut", "pull", "-r", "giellalt:^lang-s\0divvun:^kbd", "-s"]
+        );
+
+        let args: Vec<OsString> = ["gut", "pull", "-r", "^lang-"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(join_repeated_regex(&command, args.clone()), args);
+    }
 }
@@ -1,4 +1,5 @@
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
 use crate::cli::Args as CommonArgs;
 use crate::filter::Filter;
 use crate::github;
@@ -97,25 +98,22 @@ impl Status {
     fn status(&self) -> Cell {
         match &self.result {
             Ok(_) => cell!(Fgr -> "Success"),
-            Err(_) => cell!(Frr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
     fn has_error(&self) -> bool {
-        matches!(self.result, Err(_))
+        self.result.is_err()
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let Err(e) = &self.result {
             e
         } else {
             panic!("This should have an error here");
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo.name), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo.name, e)
     }
 }
 
@@ -151,13 +149,8 @@ fn summarize(statuses: &[Status], team_slug: &str) {
         let msg = format!("There {} errors when executing the command:", errors.len());
         println!("\n{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
 
@@ -0,0 +1,247 @@
+use super::common;
+use super::deps_graph::{self, Graph};
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show which repositories have to be rebuilt after the changes since a ref or a date
+///
+/// The repositories that match the regex and have commits since the ref or the date are
+/// changed, and every local repository that depends on a changed one, directly or indirectly,
+/// is affected. The dependencies are the ones of deps graph, declared in .gut/build.toml. The
+/// affected repositories are listed in build order.
+pub struct AffectedArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to select the repositories whose changes are looked at
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Tag, branch or commit, or a date as YYYY-MM-DD, after which changes count
+    pub since: Since,
+    #[arg(long)]
+    /// Only print the names of the affected repositories in build order, one per line
+    pub names: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    Date(NaiveDate),
+    Rev(String),
+}
+
+impl std::str::FromStr for Since {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(date) => Since::Date(date),
+            Err(_) => Since::Rev(s.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for Since {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Since::Date(date) => write!(f, "{}", date),
+            Since::Rev(rev) => write!(f, "{}", rev),
+        }
+    }
+}
+
+impl AffectedArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let all_dirs = common::read_dirs_for_org(&organisation, &root, None)?;
+        let (graph, mut reports) = deps_graph::read_graph(&all_dirs, &organisation);
+
+        let results: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|dir| {
+                let repo = deps_graph::local_full_name(dir, &organisation);
+                (repo, has_changed(dir, &self.since))
+            })
+            .collect();
+
+        let mut changed = BTreeSet::new();
+        for (repo, result) in results {
+            match result {
+                Ok(true) => {
+                    changed.insert(repo);
+                }
+                Ok(false) => {}
+                Err(e) => reports.push(ErrorReport::from_message(&repo, &format!("{:?}", e))),
+            }
+        }
+
+        let affected = affected(&graph, &changed);
+        let order = build_order(&graph, &affected);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            let errors: BTreeMap<_, _> = reports.iter().map(|r| (&r.repo, &r.message)).collect();
+            println!(
+                "{}",
+                json!({ "changed": changed, "affected": order, "errors": errors })
+            );
+            return Ok(());
+        }
+
+        if self.names {
+            for repo in &order {
+                println!("{}", repo);
+            }
+        } else if order.is_empty() {
+            println!("There is no changes since {}", self.since);
+        } else {
+            to_table(&graph, &changed, &order).printstd();
+            println!(
+                "\n{} repos changed since {}, {} repos have to be rebuilt",
+                changed.len(),
+                self.since,
+                order.len()
+            );
+        }
+
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking for changes:", reports.len());
+            eprintln!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether HEAD has commits after the ref or the date
+fn has_changed(dir: &PathBuf, since: &Since) -> Result<bool> {
+    let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+    let changed = match since {
+        Since::Rev(rev) => !git::first_parent_log(&git_repo, Some(rev), "HEAD")?.is_empty(),
+        Since::Date(date) => {
+            let start = date
+                .and_hms_opt(0, 0, 0)
+                .map(|d| d.and_utc().timestamp())
+                .unwrap_or_default();
+            git::first_parent_log(&git_repo, None, "HEAD")?
+                .iter()
+                .any(|e| e.time >= start)
+        }
+    };
+    Ok(changed)
+}
+
+/// The changed repositories and the ones that depend on them, directly or indirectly
+fn affected(graph: &Graph, changed: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut affected = changed.clone();
+    for repo in changed {
+        affected.extend(deps_graph::dependents(graph, repo));
+    }
+    affected
+}
+
+/// The affected repositories in build order, the ones in cycles last
+fn build_order(graph: &Graph, affected: &BTreeSet<String>) -> Vec<String> {
+    let (levels, blocked) = deps_graph::levels(graph);
+    levels
+        .into_iter()
+        .flatten()
+        .chain(blocked)
+        .filter(|repo| affected.contains(repo))
+        .collect()
+}
+
+fn to_table(graph: &Graph, changed: &BTreeSet<String>, order: &[String]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["#", "Repo", "Reason"]);
+    let affected: BTreeSet<_> = order.iter().collect();
+    for (i, repo) in order.iter().enumerate() {
+        let reason = if changed.contains(repo) {
+            "Changed".to_string()
+        } else {
+            let depends: Vec<_> = graph
+                .get(repo)
+                .into_iter()
+                .flatten()
+                .filter(|d| affected.contains(d))
+                .map(String::as_str)
+                .collect();
+            format!("Depends on {}", depends.join(", "))
+        };
+        table.add_row(row![r -> i + 1, b -> repo, reason]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since() {
+        assert_eq!(
+            "2024-05-01".parse::<Since>().unwrap(),
+            Since::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+        );
+        assert_eq!(
+            "v1.2.0".parse::<Since>().unwrap(),
+            Since::Rev("v1.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_affected() {
+        let graph: Graph = [
+            ("keyboard-sme", &["lang-sme"][..]),
+            ("lang-sme", &["giella-core"]),
+            ("lang-sma", &["giella-core"]),
+            ("giella-core", &[]),
+            ("divvunspell", &[]),
+        ]
+        .iter()
+        .map(|(repo, depends)| {
+            let depends = depends.iter().map(|d| d.to_string()).collect();
+            (repo.to_string(), depends)
+        })
+        .collect();
+
+        let changed: BTreeSet<_> = ["lang-sme".to_string()].into();
+        assert_eq!(
+            build_order(&graph, &affected(&graph, &changed)),
+            ["lang-sme", "keyboard-sme"]
+        );
+
+        let changed: BTreeSet<_> = ["giella-core".to_string()].into();
+        assert_eq!(
+            build_order(&graph, &affected(&graph, &changed)),
+            ["giella-core", "lang-sma", "lang-sme", "keyboard-sme"]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::path::dir_name;
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::{ArgGroup, Parser};
+use colored::*;
+use git2::{BranchType, Oid, Repository, Signature};
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("change").required(true).multiple(true).args(["message", "author"])))]
+/// Amend the last commit of the current branch of local repositories that match a regex
+///
+/// This fixes the message or the author of a commit that was just made in bulk. The files of
+/// the commit are not changed. With --push origin is fetched before amending, and the amend is
+/// refused when origin has commits that the local branch does not. The branch is then
+/// force-pushed with the commit that origin had as lease, so that commits of others are never
+/// overwritten.
+pub struct AmendArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// New commit message
+    pub message: Option<String>,
+    #[arg(long, short)]
+    /// New author, as "Name <email>"
+    pub author: Option<String>,
+    #[arg(long)]
+    /// Force-push the amended branch with lease
+    pub push: bool,
+}
+
+impl AmendArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let author = self.author.as_deref().map(parse_author).transpose()?;
+
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        if !confirm(sub_dirs.len(), self.push)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || self.amend(d, author.as_ref(), &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, self.push);
+
+        Ok(())
+    }
+
+    fn amend(&self, dir: &PathBuf, author: Option<&(String, String)>, user: &User) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let amend = || -> Result<Amend> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if git_repo.head_detached()? {
+                return Err(anyhow!("HEAD is detached, check out a branch first"));
+            }
+            let branch = git::head_shorthand(&git_repo)?;
+
+            let old = git_repo.head()?.peel_to_commit()?;
+            let cred = GitCredential::from(user);
+            let lease = if self.push {
+                Some(lease(&git_repo, &branch, old.id(), &cred)?)
+            } else {
+                None
+            };
+
+            let author = author
+                .map(|(name, email)| Signature::now(name, email))
+                .transpose()?;
+            let committer = git_repo.signature()?;
+            let new = old.amend(
+                Some("HEAD"),
+                author.as_ref(),
+                Some(&committer),
+                None,
+                self.message.as_deref(),
+                None,
+            )?;
+
+            if let Some(expected) = lease {
+                git::force_push_with_lease(
+                    &git_repo,
+                    &[(branch.clone(), expected)],
+                    &[],
+                    Some(&cred),
+                )?;
+            }
+
+            Ok(Amend {
+                branch,
+                old: old.id().to_string(),
+                new: new.to_string(),
+                pushed: lease.is_some(),
+            })
+        };
+
+        let result = amend().map_err(|e| format!("{:?}", e));
+        Status { repo, result }
+    }
+}
+
+/// The commit that origin has, to force-push the amended branch with as lease
+///
+/// It is fetched before amending, and has to be the commit that is amended or one of its
+/// ancestors. Otherwise somebody pushed on top of it, and the push would lose their commits.
+fn lease(git_repo: &Repository, branch: &str, old: Oid, cred: &GitCredential) -> Result<Oid> {
+    git::fetch(git_repo, "origin", Some(cred.clone()))?;
+
+    let remote = git_repo
+        .find_branch(&format!("origin/{}", branch), BranchType::Remote)
+        .map_err(|_| anyhow!("Branch {} is not on origin, push it with gut push", branch))?;
+    let remote = remote
+        .get()
+        .target()
+        .ok_or_else(|| anyhow!("origin/{} has no commit", branch))?;
+
+    if remote != old && !git_repo.graph_descendant_of(old, remote)? {
+        return Err(anyhow!(
+            "origin/{} has commits that are not in the local branch, pull them first",
+            branch
+        ));
+    }
+    Ok(remote)
+}
+
+/// Split an author written as "Name <email>"
+fn parse_author(author: &str) -> Result<(String, String)> {
+    let invalid = || anyhow!("Invalid author {:?}, expected \"Name <email>\"", author);
+    let (name, email) = author.trim().split_once('<').ok_or_else(invalid)?;
+    let email = email.strip_suffix('>').ok_or_else(invalid)?;
+    let (name, email) = (name.trim(), email.trim());
+    if name.is_empty() || email.is_empty() {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), email.to_string()))
+}
+
+#[derive(Debug, Serialize)]
+struct Amend {
+    branch: String,
+    old: String,
+    new: String,
+    pushed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Amend, String>,
+}
+
+impl Status {
+    fn to_row(&self, push: bool) -> Row {
+        let mut row = match &self.result {
+            Ok(amend) => row![
+                b -> self.repo,
+                amend.branch,
+                &amend.old[..7],
+                Fg -> &amend.new[..7]
+            ],
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+            ]),
+        };
+        if push {
+            row.add_cell(match &self.result {
+                Ok(amend) if amend.pushed => cell!(Fgr -> "Success"),
+                _ => cell!(r -> "-"),
+            });
+        }
+        row
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status], push: bool) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    if push {
+        table.set_titles(row!["Repo", "Branch", "Old", "New", "Push"]);
+    } else {
+        table.set_titles(row!["Repo", "Branch", "Old", "New"]);
+    }
+    for status in statuses {
+        table.add_row(status.to_row(push));
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when amending:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, push: bool) -> Result<bool> {
+    let key = "YES";
+    let push = if push { " and force-push it" } else { "" };
+    common::confirm(
+        &format!(
+            "Are you sure you want to amend the last commit of {} repo(s){}?\nEnter {} to continue",
+            count, push, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_author() {
+        assert_eq!(
+            parse_author("Børre Gaup <borre@example.com>").unwrap(),
+            ("Børre Gaup".to_string(), "borre@example.com".to_string())
+        );
+        assert!(parse_author("borre@example.com").is_err());
+        assert!(parse_author("<borre@example.com>").is_err());
+    }
+}
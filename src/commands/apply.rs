@@ -1,5 +1,7 @@
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
 use super::models::Script;
+use super::progress::{self, Tracked};
 use crate::filter::Filter;
 use crate::cli::Args as CommonArgs;
 use crate::path;
@@ -56,9 +58,10 @@ impl ApplyArgs {
             .to_str()
             .expect("gut only supports UTF-8 paths now!");
 
+        progress::begin(sub_dirs.len());
         let statuses: Vec<_> = sub_dirs
             .par_iter()
-            .map(|r| apply_script(r, script_path))
+            .map(|r| progress::track_dir(r, || apply_script(r, script_path)))
             .collect();
 
         summarize(&statuses);
@@ -94,7 +97,7 @@ impl Status {
     fn status(&self) -> Cell {
         match &self.result {
             Ok(_) => cell!(Fgr -> "Success"),
-            Err(_) => cell!(Frr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
@@ -114,20 +117,23 @@ impl Status {
     }
 
     fn has_error(&self) -> bool {
-        matches!(self.result, Err(_))
+        self.result.is_err()
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let Err(e) = &self.result {
             e
         } else {
             panic!("This should have an error here");
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
     }
 }
 
@@ -174,12 +180,7 @@ fn summarize(statuses: &[Status]) {
         );
         println!("\n{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
@@ -0,0 +1,207 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::LastChange;
+use crate::path::dir_name;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show the last commit that changed a file in every local repository that matches a regex
+///
+/// The repositories are listed from the oldest change to the newest. The version column is the
+/// id of the file content, so repositories with the same version have identical copies.
+pub struct BlameFileArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    /// Path of the file or directory in the repositories, e.g. tools/grammarcheckers/pipespec.xml
+    pub path: String,
+    #[arg(long)]
+    /// Also list the repositories that do not have the file
+    pub missing: bool,
+}
+
+impl BlameFileArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| blame(d, &self.path))
+            .filter(|s| self.missing || !matches!(s.result, Ok(None)))
+            .collect();
+        statuses.sort_by_key(|s| match &s.result {
+            Ok(Some(change)) => change.time,
+            _ => i64::MIN,
+        });
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, &self.path);
+        }
+
+        Ok(())
+    }
+}
+
+fn blame(dir: &PathBuf, path: &str) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let blame = || -> Result<Option<LastChange>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        Ok(git::last_change(&git_repo, path)?)
+    };
+
+    Status {
+        repo,
+        result: blame().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Option<LastChange>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(Some(change)) => row![
+                b -> self.repo,
+                format!("{} <{}>", change.author, change.email),
+                date(change.time),
+                &change.sha[..7],
+                &change.content[..7]
+            ],
+            Ok(None) => row![b -> self.repo, "not present", "", "", ""],
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+}
+
+fn date(time: i64) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Number of repositories per version of the file, the most common first
+fn versions(statuses: &[Status]) -> Vec<(&str, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for status in statuses {
+        if let Ok(Some(change)) = &status.result {
+            *counts.entry(change.content.as_str()).or_default() += 1;
+        }
+    }
+    let mut versions: Vec<_> = counts.into_iter().collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.1));
+    versions
+}
+
+fn summarize(statuses: &[Status], path: &str) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Author", "Date", "Commit", "Version"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let versions = versions(statuses);
+    let copies: usize = versions.iter().map(|(_, count)| count).sum();
+    println!(
+        "\n{} repositories have {} in {} versions",
+        copies,
+        path,
+        versions.len()
+    );
+    if let Some((content, count)) = versions.first() {
+        if versions.len() > 1 {
+            let msg = format!(
+                "{} repositories do not have the most common version {}",
+                copies - count,
+                &content[..7]
+            );
+            println!("{}", msg.yellow());
+        }
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading the history:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(repo: &str, content: Option<&str>) -> Status {
+        Status {
+            repo: repo.to_string(),
+            result: Ok(content.map(|c| LastChange {
+                sha: "0123456789".into(),
+                author: "Sjur".into(),
+                email: "sjur@example.com".into(),
+                time: 0,
+                content: c.into(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let statuses = vec![
+            status("lang-sme", Some("aaaaaaaa")),
+            status("lang-sma", Some("bbbbbbbb")),
+            status("lang-smj", Some("bbbbbbbb")),
+            status("lang-fin", None),
+        ];
+        assert_eq!(versions(&statuses), vec![("bbbbbbbb", 2), ("aaaaaaaa", 1)]);
+    }
+}
@@ -1,4 +1,5 @@
 use super::branch_default::*;
+use super::branch_ff::*;
 use super::branch_protect::*;
 use super::branch_unprotect::*;
 use anyhow::Result;
@@ -6,7 +7,7 @@ use clap::Parser;
 use crate::cli::Args as CommonArgs;
 
 #[derive(Debug, Parser)]
-/// Set default, set protected branch
+/// Set default, set protected branch, fast-forward local branches
 pub struct BranchArgs {
     #[command(subcommand)]
     command: BranchCommand,
@@ -22,6 +23,8 @@ impl BranchArgs {
 pub enum BranchCommand {
     #[command(name = "default")]
     Default(DefaultBranchArgs),
+    #[command(name = "ff")]
+    Ff(BranchFfArgs),
     #[command(name = "protect")]
     Protect(ProtectedBranchArgs),
     #[command(name = "unprotect")]
@@ -32,6 +35,7 @@ impl BranchCommand {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         match self {
             BranchCommand::Default(args) => args.set_default_branch(common_args),
+            BranchCommand::Ff(args) => args.run(common_args),
             BranchCommand::Protect(args) => args.set_protected_branch(common_args),
             BranchCommand::Unprotect(args) => args.set_unprotected_branch(common_args),
         }
@@ -0,0 +1,183 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git::{self, FastForward, GitCredential};
+use crate::path::dir_name;
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::BranchType;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Fast-forward a local branch to its upstream in all local repositories that match a regex
+///
+/// The branch is moved without checking it out, so the working trees are not touched. The
+/// remote of the upstream is fetched first. Branches that have diverged from their upstream
+/// are refused, and a branch that is checked out has to be pulled instead.
+pub struct BranchFfArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Name of the local branch
+    pub branch: String,
+    #[arg(long)]
+    /// Do not fetch before fast-forwarding, use the remote-tracking branches as they are
+    pub no_fetch: bool,
+}
+
+impl BranchFfArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || self.fast_forward(d, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, &self.branch);
+
+        Ok(())
+    }
+
+    fn fast_forward(&self, dir: &PathBuf, user: &User) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let fast_forward = || -> Result<Option<FastForward>> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if git_repo
+                .find_branch(&self.branch, BranchType::Local)
+                .is_err()
+            {
+                return Ok(None);
+            }
+            if !self.no_fetch {
+                let remote = git::upstream_remote(&git_repo, &self.branch)
+                    .ok_or_else(|| anyhow!("Branch {} has no upstream", self.branch))?;
+                git::fetch_quiet(&git_repo, &remote, Some(GitCredential::from(user)))?;
+            }
+            git::fast_forward_branch(&git_repo, &self.branch).map(Some)
+        };
+
+        Status {
+            repo,
+            result: fast_forward().map_err(|e| format!("{:?}", e)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    /// None when the repository has no such branch
+    result: Result<Option<FastForward>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.to_cell()])
+    }
+
+    fn to_cell(&self) -> Cell {
+        match &self.result {
+            Ok(None) => cell!(r -> "No branch"),
+            Ok(Some(FastForward::UpToDate)) => cell!(r -> "Up to date"),
+            Ok(Some(FastForward::Ahead { commits })) => cell!(Fy -> format!("{} ahead", commits)),
+            Ok(Some(FastForward::Forwarded { commits, .. })) => {
+                cell!(Fgr -> format!("{} new commits", commits))
+            }
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn forwarded(&self) -> bool {
+        matches!(self.result, Ok(Some(FastForward::Forwarded { .. })))
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Fast-forward"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status], branch: &str) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let forwarded = statuses.iter().filter(|s| s.forwarded()).count();
+
+    if forwarded > 0 {
+        let msg = format!(
+            "\nFast-forwarded branch {} of {} repos successfully!",
+            branch, forwarded
+        );
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when fast-forwarding:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
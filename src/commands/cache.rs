@@ -0,0 +1,35 @@
+use super::cache_list::*;
+use super::cache_refresh::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Keep the names of organisations, repositories, topics and teams for shell completion
+pub struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+impl CacheArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum CacheCommand {
+    #[command(name = "list")]
+    List(CacheListArgs),
+    #[command(name = "refresh")]
+    Refresh(CacheRefreshArgs),
+}
+
+impl CacheCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::List(args) => args.run(common_args),
+            Self::Refresh(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::completion_cache::{CompletionCache, Kind};
+use anyhow::Result;
+use chrono::Duration;
+use clap::Parser;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Parser)]
+/// Print cached names one per line, for shell completion scripts
+///
+/// This never waits for GitHub. When the names of the organisation are missing or older
+/// than --max-age, `gut cache refresh` is started in the background and the next completion
+/// gets the fresh names.
+pub struct CacheListArgs {
+    #[arg(value_enum)]
+    /// What kind of names to print
+    pub kind: Kind,
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command. Without
+    /// any, the names of all cached organisations are printed.
+    pub organisation: Option<String>,
+    #[arg(long, value_name = "HOURS", default_value_t = 24)]
+    /// Age after which the names are refreshed in the background
+    pub max_age: i64,
+    #[arg(long)]
+    /// Do not refresh stale names in the background
+    pub no_refresh: bool,
+}
+
+impl CacheListArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let owner = common::organisation(self.organisation.as_deref()).ok();
+
+        // A broken cache must not break completion, it is replaced by the next refresh
+        let cache = CompletionCache::load().unwrap_or_else(|e| {
+            log::debug!("Cannot load the completion cache: {:?}", e);
+            CompletionCache::default()
+        });
+
+        for name in cache.names(self.kind, owner.as_deref()) {
+            println!("{}", name);
+        }
+
+        let stale = owner
+            .as_deref()
+            .filter(|o| !self.no_refresh && cache.is_stale(o, Duration::hours(self.max_age)));
+        if let Some(owner) = stale {
+            refresh_in_background(owner);
+        }
+
+        Ok(())
+    }
+}
+
+/// Start `gut cache refresh` for an owner without waiting for it
+fn refresh_in_background(owner: &str) {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            log::debug!("Cannot find the gut executable: {:?}", e);
+            return;
+        }
+    };
+
+    let result = Command::new(exe)
+        .args(["cache", "refresh", "--quiet", "--organisation", owner])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = result {
+        log::debug!("Cannot refresh the completion cache of {}: {:?}", owner, e);
+    }
+}
@@ -0,0 +1,126 @@
+use super::common;
+use super::topic_helper;
+use crate::cli::Args as CommonArgs;
+use crate::completion_cache::{CompletionCache, OwnerNames};
+use crate::github;
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Parser;
+use colored::*;
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Parser)]
+/// Fetch the names of organisations, repositories, topics and teams for shell completion
+///
+/// The names are kept in completion-cache.json in the config directory. `gut cache list`
+/// starts this command in the background when the names it prints are stale.
+pub struct CacheRefreshArgs {
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Refresh the names of all organisations of the token
+    pub all_orgs: bool,
+    #[arg(long, value_name = "HOURS")]
+    /// Only refresh the owners whose names are older than this
+    pub if_older_than: Option<i64>,
+    #[arg(long, short)]
+    /// Do not print anything
+    pub quiet: bool,
+}
+
+impl CacheRefreshArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let token = common::user_token()?;
+
+        let owners = if self.all_orgs {
+            github::get_user_orgs(&token)?
+        } else {
+            vec![common::organisation(self.organisation.as_deref())?]
+        };
+
+        let cache = CompletionCache::load()?;
+        let stale: Vec<_> = owners
+            .into_iter()
+            .filter(|owner| match self.if_older_than {
+                Some(hours) => cache.is_stale(owner, Duration::hours(hours)),
+                None => true,
+            })
+            .collect();
+
+        if stale.is_empty() {
+            if !self.quiet {
+                println!("The completion cache is up to date");
+            }
+            return Ok(());
+        }
+
+        let orgs = github::get_user_orgs(&token)?;
+        let fetched: Vec<_> = stale
+            .par_iter()
+            .map(|owner| (owner, fetch_names(owner, &token)))
+            .collect();
+
+        // Another refresh may have saved other owners in the meantime, so only the owners
+        // fetched here replace what is in the file now
+        let mut cache = CompletionCache::load()?;
+        cache.orgs = orgs;
+        let mut failed = 0;
+        for (owner, names) in fetched {
+            match names {
+                Ok(names) => {
+                    if !self.quiet {
+                        println!(
+                            "Cached {} repositories, {} topics and {} teams of {}",
+                            names.repos.len(),
+                            names.topics.len(),
+                            names.teams.len(),
+                            owner
+                        );
+                    }
+                    cache.owners.insert(owner.clone(), names);
+                }
+                Err(e) => {
+                    failed += 1;
+                    if !self.quiet {
+                        let msg = format!("Cannot refresh the names of {}: {:?}", owner, e);
+                        println!("{}", msg.red());
+                    }
+                }
+            }
+        }
+        cache.save()?;
+
+        if failed > 0 {
+            anyhow::bail!("Cannot refresh the names of {} owners", failed);
+        }
+        Ok(())
+    }
+}
+
+fn fetch_names(owner: &str, token: &str) -> Result<OwnerNames> {
+    let repos = topic_helper::query_repositories_with_topics(owner, token)?;
+    let topics: BTreeSet<_> = repos
+        .iter()
+        .flat_map(|r| r.topics.iter().cloned())
+        .collect();
+
+    // Users have no teams, and tokens without read:org cannot see those of an organisation
+    let teams = match github::get_teams(owner, token) {
+        Ok(teams) => teams.into_iter().map(|t| t.slug).collect(),
+        Err(e) => {
+            log::debug!("Cannot fetch the teams of {}: {:?}", owner, e);
+            vec![]
+        }
+    };
+
+    Ok(OwnerNames {
+        refreshed_at: Utc::now(),
+        repos: repos.into_iter().map(|r| r.repo.name).collect(),
+        topics: topics.into_iter().collect(),
+        teams,
+    })
+}
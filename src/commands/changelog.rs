@@ -0,0 +1,707 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::{GitCredential, LogEntry};
+use crate::github::{self, RemoteRepo};
+use crate::path::{dir_name, owner_name};
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use clap::Parser;
+use colored::*;
+use git2::{Repository, Status as FileStatus};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static CHANGELOG: &str = "CHANGELOG.md";
+
+#[derive(Debug, Parser)]
+/// Generate changelogs from the commits between two tags or dates
+///
+/// Commits are grouped by their conventional commit type (feat, fix, ...) and linked to their
+/// pull requests. The release notes of all matched repositories are printed, or written to a
+/// file with --output. With --write the changelog is added to the CHANGELOG.md of every
+/// repository and pushed, or with --pr pushed to a new branch with a pull request.
+pub struct ChangelogArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Tag or commit after which the changelog starts [default: the previous tag]
+    pub from: Option<String>,
+    #[arg(long, default_value = "HEAD")]
+    /// Tag or commit with which the changelog ends
+    pub to: String,
+    #[arg(long)]
+    /// Only commits made on or after this date, as YYYY-MM-DD
+    pub since: Option<NaiveDate>,
+    #[arg(long)]
+    /// Only commits made on or before this date, as YYYY-MM-DD
+    pub until: Option<NaiveDate>,
+    #[arg(long)]
+    /// Title of the release [default: the --to tag, or Unreleased]
+    pub title: Option<String>,
+    #[arg(long)]
+    /// Write the release notes of all repositories to this file instead of printing them
+    pub output: Option<PathBuf>,
+    #[arg(long, short)]
+    /// Add the changelog to the CHANGELOG.md of every repository and push it
+    pub write: bool,
+    #[arg(long, requires = "write")]
+    /// Open a pull request instead of pushing to the current branch
+    pub pr: bool,
+    #[arg(long, short, default_value = "gut/changelog")]
+    /// Name of the branch for the pull request
+    pub branch: String,
+    #[arg(long, short, default_value = "Update CHANGELOG.md")]
+    /// Commit message
+    pub message: String,
+}
+
+impl ChangelogArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let title = self.title.clone().unwrap_or_else(|| {
+            if self.to == "HEAD" {
+                "Unreleased".to_string()
+            } else {
+                self.to.clone()
+            }
+        });
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| self.collect(d, &organisation))
+            .collect();
+
+        if self.write {
+            return self.write_all(&sub_dirs, &statuses, &title, common_args);
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        let notes = release_notes(&statuses, &title);
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, notes)
+                    .with_context(|| format!("Cannot write release notes to {:?}", path))?;
+                println!("Wrote the release notes to {}", path.display());
+            }
+            None => print!("{}", notes),
+        }
+
+        print_errors(&statuses);
+        Ok(())
+    }
+
+    fn collect(&self, dir: &PathBuf, organisation: &str) -> Status {
+        let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+        let collect = || -> Result<Changelog> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            let from = match &self.from {
+                Some(from) => Some(from.clone()),
+                None if self.since.is_none() => git::previous_tag(&git_repo, &self.to),
+                None => None,
+            };
+            let to_time = git_repo
+                .revparse_single(&self.to)?
+                .peel_to_commit()?
+                .time()
+                .seconds();
+
+            let since = self.since.map(start_of_day);
+            let until = self
+                .until
+                .and_then(|d| d.checked_add_days(Days::new(1)))
+                .map(start_of_day);
+            let entries = git::first_parent_log(&git_repo, from.as_deref(), &self.to)?
+                .iter()
+                .filter(|e| since.map(|s| e.time >= s).unwrap_or(true))
+                .filter(|e| until.map(|u| e.time < u).unwrap_or(true))
+                .filter_map(parse_entry)
+                .collect();
+
+            Ok(Changelog {
+                from,
+                to: self.to.clone(),
+                date: DateTime::<Utc>::from_timestamp(to_time, 0)
+                    .map(|d| d.date_naive())
+                    .unwrap_or_default(),
+                entries,
+            })
+        };
+
+        Status {
+            owner,
+            name,
+            result: collect().map_err(|e| format!("{:?}", e)),
+        }
+    }
+
+    fn write_all(
+        &self,
+        sub_dirs: &[PathBuf],
+        statuses: &[Status],
+        title: &str,
+        common_args: &CommonArgs,
+    ) -> Result<()> {
+        let user = common::user()?;
+
+        let changed = statuses
+            .iter()
+            .filter(|s| matches!(&s.result, Ok(c) if !c.entries.is_empty()))
+            .count();
+        if !confirm(changed, self.pr)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        // Sequential, because it pushes and opens pull requests
+        progress::begin(sub_dirs.len());
+        let results: Vec<_> = sub_dirs
+            .iter()
+            .zip(statuses)
+            .map(|(d, s)| progress::track_dir(d, || self.write(d, s, title, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(results));
+            return Ok(());
+        }
+
+        summarize(&results);
+        Ok(())
+    }
+
+    fn write(&self, dir: &PathBuf, status: &Status, title: &str, user: &User) -> WriteStatus {
+        let write = || -> Result<Rollout> {
+            let changelog = status.result.as_ref().map_err(|e| anyhow!("{}", e))?;
+            if changelog.entries.is_empty() {
+                return Ok(Rollout::NoChanges);
+            }
+
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if !git_repo.head()?.is_branch() {
+                return Err(anyhow!("HEAD is detached, check out a branch first"));
+            }
+            let current_branch = git::head_shorthand(&git_repo)?;
+            let head = git_repo.head()?.peel_to_commit()?;
+            let head_tree = head.tree()?;
+
+            let existing = match head_tree.get_path(Path::new(CHANGELOG)) {
+                Ok(entry) => {
+                    let blob = git_repo.find_blob(entry.id())?;
+                    Some(String::from_utf8_lossy(blob.content()).to_string())
+                }
+                Err(_) => None,
+            };
+            let section = format!(
+                "## {} ({})\n\n{}",
+                title,
+                changelog.date,
+                render(changelog, &status.owner, &status.name, 3)
+            );
+            let content = update_changelog(existing.as_deref(), title, &section);
+            if existing.as_deref() == Some(content.as_str()) {
+                return Ok(Rollout::UpToDate);
+            }
+
+            let blob = git_repo.blob(content.as_bytes())?;
+            let mut builder = git_repo.treebuilder(Some(&head_tree))?;
+            builder.insert(CHANGELOG, blob, 0o100644)?;
+            let tree = git_repo.find_tree(builder.write()?)?;
+            let sig = git_repo.signature()?;
+            let cred = GitCredential::from(user);
+
+            if self.pr {
+                let oid = git_repo.commit(None, &sig, &sig, &self.message, &tree, &[&head])?;
+                git_repo.branch(&self.branch, &git_repo.find_commit(oid)?, true)?;
+                git::push::push_branch(&git_repo, &self.branch, "origin", Some(cred))?;
+
+                let remote_repo = RemoteRepo {
+                    name: status.name.clone(),
+                    owner: status.owner.clone(),
+                    ssh_url: String::new(),
+                    https_url: String::new(),
+                };
+                let pr = github::create_pull_request(
+                    &remote_repo,
+                    &self.branch,
+                    &current_branch,
+                    &self.message,
+                    &section,
+                    &user.token,
+                )?;
+                return Ok(Rollout::PullRequest(pr.html_url));
+            }
+
+            check_unmodified(&git_repo)?;
+            git_repo.commit(Some("HEAD"), &sig, &sig, &self.message, &tree, &[&head])?;
+
+            // Bring the working directory and the index up to date with the new commit
+            let workdir = git_repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Cannot commit to a bare repository"))?;
+            std::fs::write(workdir.join(CHANGELOG), &content)?;
+            let mut index = git_repo.index()?;
+            index.add_path(Path::new(CHANGELOG))?;
+            index.write()?;
+
+            git::push::push_branch(&git_repo, &current_branch, "origin", Some(cred))?;
+            Ok(Rollout::Pushed(current_branch))
+        };
+
+        WriteStatus {
+            repo: status.name.clone(),
+            commits: status.result.as_ref().map(|c| c.entries.len()).unwrap_or(0),
+            result: write().map_err(|e| format!("{:?}", e)),
+        }
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .map(|d| d.and_utc().timestamp())
+        .unwrap_or_default()
+}
+
+/// A CHANGELOG.md with uncommitted changes would be overwritten
+fn check_unmodified(git_repo: &Repository) -> Result<()> {
+    match git_repo.status_file(Path::new(CHANGELOG)) {
+        Ok(status) if status != FileStatus::CURRENT => Err(anyhow!(
+            "{} has uncommitted changes, commit or discard them first",
+            CHANGELOG
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+enum Section {
+    Breaking,
+    Features,
+    Fixes,
+    Performance,
+    Reverts,
+    Documentation,
+    Refactoring,
+    Maintenance,
+    Other,
+}
+
+impl Section {
+    fn of_type(kind: &str) -> Section {
+        match kind {
+            "feat" => Section::Features,
+            "fix" => Section::Fixes,
+            "perf" => Section::Performance,
+            "revert" => Section::Reverts,
+            "docs" => Section::Documentation,
+            "refactor" => Section::Refactoring,
+            "build" | "chore" | "ci" | "style" | "test" => Section::Maintenance,
+            _ => Section::Other,
+        }
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Section::Breaking => "Breaking Changes",
+            Section::Features => "Features",
+            Section::Fixes => "Bug Fixes",
+            Section::Performance => "Performance",
+            Section::Reverts => "Reverts",
+            Section::Documentation => "Documentation",
+            Section::Refactoring => "Refactoring",
+            Section::Maintenance => "Maintenance",
+            Section::Other => "Other Changes",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    section: Section,
+    scope: Option<String>,
+    description: String,
+    sha: String,
+    pr: Option<u64>,
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid regex"))
+}
+
+/// Read a commit as a conventional commit, `type(scope)!: description`
+///
+/// Merge commits of pull requests are read from the pull request title in their body, other
+/// merge commits are left out.
+fn parse_entry(entry: &LogEntry) -> Option<Entry> {
+    static MERGE_PR: OnceLock<Regex> = OnceLock::new();
+    static PR_SUFFIX: OnceLock<Regex> = OnceLock::new();
+    static CONVENTIONAL: OnceLock<Regex> = OnceLock::new();
+
+    let (message, pr) = if entry.is_merge {
+        let captures = regex(&MERGE_PR, r"^Merge pull request #(\d+)").captures(&entry.summary)?;
+        let title = entry
+            .body
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or(&entry.summary);
+        (title.to_string(), captures[1].parse().ok())
+    } else {
+        match regex(&PR_SUFFIX, r"\s*\(#(\d+)\)$").captures(&entry.summary) {
+            Some(captures) => (
+                entry.summary[..captures.get(0)?.start()].to_string(),
+                captures[1].parse().ok(),
+            ),
+            None => (entry.summary.clone(), None),
+        }
+    };
+
+    let breaking_footer =
+        entry.body.contains("BREAKING CHANGE:") || entry.body.contains("BREAKING-CHANGE:");
+    let conventional = regex(&CONVENTIONAL, r"^(\w+)(?:\(([^)]*)\))?(!)?:\s*(.+)$");
+    let (section, scope, description) = match conventional.captures(&message) {
+        Some(captures) => {
+            let section = if captures.get(3).is_some() || breaking_footer {
+                Section::Breaking
+            } else {
+                Section::of_type(&captures[1].to_lowercase())
+            };
+            let scope = captures
+                .get(2)
+                .map(|s| s.as_str().to_string())
+                .filter(|s| !s.is_empty());
+            (section, scope, captures[4].to_string())
+        }
+        None if breaking_footer => (Section::Breaking, None, message),
+        None => (Section::Other, None, message),
+    };
+
+    Some(Entry {
+        section,
+        scope,
+        description,
+        sha: entry.sha.clone(),
+        pr,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct Changelog {
+    from: Option<String>,
+    to: String,
+    date: NaiveDate,
+    entries: Vec<Entry>,
+}
+
+/// The entries of a changelog in markdown, with a heading of the given level for each section
+fn render(changelog: &Changelog, owner: &str, name: &str, level: usize) -> String {
+    let base = format!("https://github.com/{}/{}", owner, name);
+    let mut sections: Vec<_> = changelog.entries.iter().map(|e| e.section).collect();
+    sections.sort();
+    sections.dedup();
+
+    let mut text = String::new();
+    for section in sections {
+        text += &format!("{} {}\n\n", "#".repeat(level), section.title());
+        for entry in changelog.entries.iter().filter(|e| e.section == section) {
+            let scope = entry
+                .scope
+                .as_ref()
+                .map(|s| format!("**{}:** ", s))
+                .unwrap_or_default();
+            let link = match entry.pr {
+                Some(pr) => format!("[#{}]({}/pull/{})", pr, base, pr),
+                None => format!("[`{}`]({}/commit/{})", &entry.sha[..7], base, entry.sha),
+            };
+            text += &format!("- {}{} ({})\n", scope, entry.description, link);
+        }
+        text += "\n";
+    }
+    text
+}
+
+/// Put the section of a release above the earlier releases of a changelog, or in place of the
+/// section with the same title when the changelog is generated again
+fn update_changelog(existing: Option<&str>, title: &str, section: &str) -> String {
+    let existing = existing.unwrap_or("# Changelog\n");
+    let lines: Vec<_> = existing.lines().collect();
+    let heading = format!("## {}", title);
+    let is_release = |l: &&str| l.starts_with("## ");
+
+    let (start, end) = match lines
+        .iter()
+        .position(|l| *l == heading || l.starts_with(&format!("{} (", heading)))
+    {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(is_release)
+                .map(|i| start + 1 + i)
+                .unwrap_or(lines.len());
+            (start, end)
+        }
+        None => {
+            let start = lines.iter().position(is_release).unwrap_or(lines.len());
+            (start, start)
+        }
+    };
+
+    let before = lines[..start].join("\n");
+    let after = lines[end..].join("\n");
+    let mut content = String::new();
+    if !before.trim().is_empty() {
+        content += before.trim_end();
+        content += "\n\n";
+    }
+    content += section.trim_end();
+    content += "\n";
+    if !after.trim().is_empty() {
+        content += "\n";
+        content += after.trim_end();
+        content += "\n";
+    }
+    content
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    owner: String,
+    name: String,
+    result: Result<Changelog, String>,
+}
+
+impl Status {
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.name, e)
+    }
+}
+
+/// The changelogs of all repositories with changes in one document
+fn release_notes(statuses: &[Status], title: &str) -> String {
+    let mut notes = format!("# {}\n\n", title);
+    let mut unchanged = vec![];
+    for status in statuses {
+        match &status.result {
+            Ok(changelog) if changelog.entries.is_empty() => unchanged.push(status.name.as_str()),
+            Ok(changelog) => {
+                notes += &format!("## {}/{}\n\n", status.owner, status.name);
+                notes += &render(changelog, &status.owner, &status.name, 3);
+            }
+            Err(_) => {}
+        }
+    }
+    if !unchanged.is_empty() {
+        notes += &format!("No changes in {}.\n", unchanged.join(", "));
+    }
+    notes
+}
+
+fn print_errors(statuses: &[Status]) {
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when reading the history:", errors.len());
+        eprintln!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum Rollout {
+    NoChanges,
+    UpToDate,
+    Pushed(String),
+    PullRequest(String),
+}
+
+#[derive(Debug, Serialize)]
+struct WriteStatus {
+    repo: String,
+    commits: usize,
+    result: Result<Rollout, String>,
+}
+
+impl WriteStatus {
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(r -> self.commits),
+            self.result_cell(),
+        ])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Rollout::NoChanges) => cell!("No changes"),
+            Ok(Rollout::UpToDate) => cell!("Up to date"),
+            Ok(Rollout::Pushed(branch)) => cell!(Fg -> format!("Pushed to {}", branch)),
+            Ok(Rollout::PullRequest(url)) => cell!(Fg -> url),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for WriteStatus {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[WriteStatus]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Entries", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when writing the changelogs:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, pr: bool) -> Result<bool> {
+    let key = "YES";
+    let action = if pr {
+        "open a pull request with the CHANGELOG.md in"
+    } else {
+        "commit and push the CHANGELOG.md to"
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to {} {} repo(s)?\nEnter {} to continue",
+            action, count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_entry(summary: &str, body: &str, is_merge: bool) -> LogEntry {
+        LogEntry {
+            sha: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            summary: summary.to_string(),
+            body: body.to_string(),
+            time: 0,
+            is_merge,
+        }
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = parse_entry(&log_entry(
+            "feat(speller): Add a suggestion (#12)",
+            "",
+            false,
+        ))
+        .unwrap();
+        assert_eq!(entry.section, Section::Features);
+        assert_eq!(entry.scope.as_deref(), Some("speller"));
+        assert_eq!(entry.description, "Add a suggestion");
+        assert_eq!(entry.pr, Some(12));
+
+        let entry = parse_entry(&log_entry(
+            "Merge pull request #7 from giellalt/fix-tags",
+            "fix!: Rename the tags",
+            true,
+        ))
+        .unwrap();
+        assert_eq!(entry.section, Section::Breaking);
+        assert_eq!(entry.description, "Rename the tags");
+        assert_eq!(entry.pr, Some(7));
+
+        assert!(parse_entry(&log_entry("Merge branch 'main'", "", true)).is_none());
+        let entry = parse_entry(&log_entry("Update the readme", "", false)).unwrap();
+        assert_eq!(entry.section, Section::Other);
+        assert_eq!(entry.pr, None);
+    }
+
+    #[test]
+    fn test_update_changelog() {
+        let first = update_changelog(None, "v1.0", "## v1.0 (2026-01-01)\n\n- One\n");
+        assert_eq!(first, "# Changelog\n\n## v1.0 (2026-01-01)\n\n- One\n");
+
+        let second = update_changelog(Some(&first), "v1.1", "## v1.1 (2026-02-01)\n\n- Two\n");
+        assert_eq!(
+            second,
+            "# Changelog\n\n## v1.1 (2026-02-01)\n\n- Two\n\n## v1.0 (2026-01-01)\n\n- One\n"
+        );
+
+        let again = update_changelog(Some(&second), "v1.1", "## v1.1 (2026-02-02)\n\n- Three\n");
+        assert_eq!(
+            again,
+            "# Changelog\n\n## v1.1 (2026-02-02)\n\n- Three\n\n## v1.0 (2026-01-01)\n\n- One\n"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use super::check_docs::*;
+use super::check_links::*;
+use super::check_syntax::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Check the content of local repositories for problems
+pub struct CheckArgs {
+    #[command(subcommand)]
+    command: CheckCommand,
+}
+
+impl CheckArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum CheckCommand {
+    #[command(name = "docs")]
+    Docs(CheckDocsArgs),
+    #[command(name = "links")]
+    Links(CheckLinksArgs),
+    #[command(name = "syntax")]
+    Syntax(CheckSyntaxArgs),
+}
+
+impl CheckCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Docs(args) => args.run(common_args),
+            Self::Links(args) => args.run(common_args),
+            Self::Syntax(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::Config;
+use crate::doc_check::{DocsPolicy, File, Violation};
+use crate::encoding;
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Check the documentation files of all local repositories that match a regex
+///
+/// Markdown files are linted, and with a term list in `[docs.<owner>]` of the config file the
+/// documentation is checked for spellings that should be replaced. `encoding = true` there also
+/// checks the encoding and line endings of all text files. Only files that git tracks are
+/// checked.
+pub struct CheckDocsArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Fail when there is a violation, to use it in CI
+    pub check: bool,
+}
+
+impl CheckDocsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let config = Config::load()?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| check(d, &config, &organisation))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
+        }
+
+        let violations: usize = statuses.iter().map(|s| s.violations().len()).sum();
+        if self.check && violations > 0 {
+            bail!("There are {} violations in the documentation", violations);
+        }
+
+        Ok(())
+    }
+}
+
+fn check(dir: &PathBuf, config: &Config, organisation: &str) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+    let policy = DocsPolicy::for_owner(config, &owner);
+
+    let check = || -> Result<Vec<FileViolation>> {
+        let checkers = policy.checkers()?;
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let mut violations = vec![];
+        for file in git::tracked_files(&git_repo)? {
+            let applying: Vec<_> = checkers.iter().filter(|c| c.applies_to(&file)).collect();
+            if applying.is_empty() || encoding::is_marked_binary(&git_repo, &file) {
+                continue;
+            }
+            let bytes = match std::fs::read(dir.join(&file)) {
+                Ok(bytes) => bytes,
+                // Deleted in the working directory
+                Err(_) => continue,
+            };
+            let checked = File {
+                bytes: &bytes,
+                wants_lf: encoding::wants_lf(&git_repo, &file),
+            };
+            for checker in applying {
+                for violation in checker.check_file(&checked) {
+                    if !policy.skips(violation.rule) {
+                        violations.push(FileViolation {
+                            file: file.clone(),
+                            violation,
+                        });
+                    }
+                }
+            }
+        }
+        violations.sort_by(|a, b| (&a.file, a.violation.line).cmp(&(&b.file, b.violation.line)));
+        Ok(violations)
+    };
+
+    Status {
+        repo,
+        result: check().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileViolation {
+    file: String,
+    #[serde(flatten)]
+    violation: Violation,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<FileViolation>, String>,
+}
+
+impl Status {
+    fn violations(&self) -> &[FileViolation] {
+        match &self.result {
+            Ok(violations) => violations,
+            Err(_) => &[],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "File", "Rule", "Problem"]);
+    for status in statuses {
+        for (i, v) in status.violations().iter().enumerate() {
+            table.add_row(row![
+                b -> if i == 0 { status.repo.as_str() } else { "" },
+                format!("{}:{}", v.file, v.violation.line),
+                v.violation.rule,
+                Fr -> v.violation.message
+            ]);
+        }
+    }
+
+    let violations: usize = statuses.iter().map(|s| s.violations().len()).sum();
+    if violations == 0 {
+        println!("\nThere is no violation in the documentation");
+    } else {
+        table.printstd();
+        let repos = statuses
+            .iter()
+            .filter(|s| !s.violations().is_empty())
+            .count();
+        let msg = format!("\n{} violations in {} repositories", violations, repos);
+        println!("{}", msg.red());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when checking:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,346 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::markdown::{self, Link};
+use crate::path::dir_name;
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::Repository;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use reqwest::blocking as req;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+/// Find dead links in the markdown files of all local repositories that match a regex
+///
+/// Relative links have to point to a file or directory in the repository, web links have to
+/// answer without an error. Every url is checked only once, however many repositories link to
+/// it. Anchors are not checked.
+pub struct CheckLinksArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, default_value_t = 8)]
+    /// Number of urls to check at the same time
+    pub jobs: usize,
+    #[arg(long, default_value_t = 15)]
+    /// Seconds to wait for an answer before a url counts as dead
+    pub timeout: u64,
+    #[arg(long)]
+    /// Only check the relative links, without going online
+    pub offline: bool,
+}
+
+impl CheckLinksArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let scans: Vec<_> = sub_dirs.par_iter().map(scan).collect();
+
+        let urls: BTreeSet<_> = scans
+            .iter()
+            .filter_map(|(_, result)| result.as_ref().ok())
+            .flatten()
+            .filter_map(|found| match &found.target {
+                Target::External(url) if !self.offline => Some(url.as_str()),
+                _ => None,
+            })
+            .collect();
+        let problems = self.check_urls(&urls)?;
+
+        let statuses: Vec<_> = scans
+            .into_iter()
+            .map(|(repo, result)| Status::new(repo, result, &problems))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    /// The problem of every url that is dead
+    fn check_urls(&self, urls: &BTreeSet<&str>) -> Result<BTreeMap<String, String>> {
+        if urls.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        let client = req::Client::builder()
+            .timeout(Duration::from_secs(self.timeout))
+            .user_agent(crate::github::USER_AGENT)
+            .build()?;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs.max(1))
+            .build()?;
+
+        println!("Checking {} urls", urls.len());
+        let problems = pool.install(|| {
+            urls.par_iter()
+                .filter_map(|url| check_url(&client, url).map(|p| (url.to_string(), p)))
+                .collect()
+        });
+        Ok(problems)
+    }
+}
+
+/// Why the url is dead, None when it answers
+///
+/// Many servers refuse HEAD requests, so a failed HEAD is tried again with GET. A server that
+/// limits the rate cannot tell, so that counts as alive.
+fn check_url(client: &req::Client, url: &str) -> Option<String> {
+    let response = match client.head(url).send() {
+        Ok(response) if response.status().is_success() => Ok(response),
+        _ => client.get(url).send(),
+    };
+    match response {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => None,
+        Ok(response) => Some(response.status().to_string()),
+        Err(e) if e.is_timeout() => Some("Timed out".to_string()),
+        Err(e) if e.is_connect() => Some("Cannot connect".to_string()),
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+/// Where a link points to
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    /// A url on the web, without the anchor
+    External(String),
+    /// A path in the repository that does not exist
+    Missing,
+    /// A path that exists, an anchor in the same file or a scheme that is not checked
+    Fine,
+}
+
+#[derive(Debug)]
+struct Found {
+    file: String,
+    link: Link,
+    target: Target,
+}
+
+fn scan(dir: &PathBuf) -> (String, Result<Vec<Found>, String>) {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let scan = || -> Result<Vec<Found>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let mut found = vec![];
+        for file in markdown_files(&git_repo)? {
+            let path = dir.join(&file);
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                // Deleted in the working directory, or not utf-8
+                Err(_) => continue,
+            };
+            for link in markdown::links(&content) {
+                let target = target(dir, &path, &link.target);
+                found.push(Found {
+                    file: file.clone(),
+                    link,
+                    target,
+                });
+            }
+        }
+        Ok(found)
+    };
+
+    (repo, scan().map_err(|e| format!("{:?}", e)))
+}
+
+/// The markdown files that git tracks, which leaves out build results and dependencies
+fn markdown_files(git_repo: &Repository) -> Result<Vec<String>> {
+    Ok(git::tracked_files(git_repo)?
+        .into_iter()
+        .filter(|path| is_markdown(path))
+        .collect())
+}
+
+fn is_markdown(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".md") || path.ends_with(".markdown")
+}
+
+fn target(repo_dir: &Path, file: &Path, link: &str) -> Target {
+    let without_anchor = link.split(['#', '?']).next().unwrap_or_default();
+
+    if link.starts_with("http://") || link.starts_with("https://") {
+        return Target::External(link.split('#').next().unwrap_or(link).to_string());
+    }
+    if without_anchor.is_empty() || link.contains(':') {
+        return Target::Fine;
+    }
+
+    let relative = without_anchor.replace("%20", " ");
+    let path = match relative.strip_prefix('/') {
+        Some(from_root) => repo_dir.join(from_root),
+        None => file.parent().unwrap_or(repo_dir).join(&relative),
+    };
+    if path.exists() {
+        Target::Fine
+    } else {
+        Target::Missing
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DeadLink {
+    file: String,
+    line: usize,
+    link: String,
+    problem: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    links: usize,
+    result: Result<Vec<DeadLink>, String>,
+}
+
+impl Status {
+    fn new(
+        repo: String,
+        result: Result<Vec<Found>, String>,
+        problems: &BTreeMap<String, String>,
+    ) -> Status {
+        let found = match result {
+            Ok(found) => found,
+            Err(e) => {
+                return Status {
+                    repo,
+                    links: 0,
+                    result: Err(e),
+                }
+            }
+        };
+
+        let links = found.len();
+        let dead = found
+            .into_iter()
+            .filter_map(|found| {
+                let problem = match &found.target {
+                    Target::Missing => "No such file".to_string(),
+                    Target::External(url) => problems.get(url)?.clone(),
+                    Target::Fine => return None,
+                };
+                Some(DeadLink {
+                    file: found.file,
+                    line: found.link.line,
+                    link: found.link.target,
+                    problem,
+                })
+            })
+            .collect();
+
+        Status {
+            repo,
+            links,
+            result: Ok(dead),
+        }
+    }
+
+    fn dead(&self) -> &[DeadLink] {
+        match &self.result {
+            Ok(dead) => dead,
+            Err(_) => &[],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "File", "Link", "Problem"]);
+    for status in statuses {
+        for (i, dead) in status.dead().iter().enumerate() {
+            table.add_row(row![
+                b -> if i == 0 { status.repo.as_str() } else { "" },
+                format!("{}:{}", dead.file, dead.line),
+                dead.link,
+                Fr -> dead.problem
+            ]);
+        }
+    }
+
+    let dead: usize = statuses.iter().map(|s| s.dead().len()).sum();
+    let links: usize = statuses.iter().map(|s| s.links).sum();
+    if dead == 0 {
+        println!("\nThere is no dead link in {} links", links);
+    } else {
+        table.printstd();
+        let repos = statuses.iter().filter(|s| !s.dead().is_empty()).count();
+        let msg = format!(
+            "\n{} of {} links are dead, in {} repositories",
+            dead, links, repos
+        );
+        println!("{}", msg.red());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading the files:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/setup.md"), "").unwrap();
+        let file = dir.path().join("docs/README.md");
+
+        assert_eq!(target(dir.path(), &file, "setup.md#install"), Target::Fine);
+        assert_eq!(target(dir.path(), &file, "/docs/setup.md"), Target::Fine);
+        assert_eq!(target(dir.path(), &file, "../setup.md"), Target::Missing);
+        assert_eq!(target(dir.path(), &file, "#usage"), Target::Fine);
+        assert_eq!(target(dir.path(), &file, "mailto:feedback@divvun.no"), Target::Fine);
+        assert_eq!(
+            target(dir.path(), &file, "https://giellalt.github.io/#top"),
+            Target::External("https://giellalt.github.io/".to_string())
+        );
+    }
+}
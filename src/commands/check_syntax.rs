@@ -0,0 +1,194 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::dir_name;
+use crate::syntax::{self, ParseError, Syntax};
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Find XML, YAML and TOML files with syntax errors in all local repositories that match a regex
+///
+/// The format of a file is known by its extension: .xml, .xsl, .xslt and .xsd are XML, .yml and
+/// .yaml are YAML and .toml is TOML. Only files that git tracks are checked.
+pub struct CheckSyntaxArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long = "type", short, value_delimiter = ',')]
+    /// Formats to check, all of them by default
+    pub types: Vec<Syntax>,
+    #[arg(long)]
+    /// Fail when a file has a syntax error, to use it in CI
+    pub check: bool,
+}
+
+impl CheckSyntaxArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs.par_iter().map(|d| self.check(d)).collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
+        }
+
+        let broken: usize = statuses.iter().map(|s| s.broken().len()).sum();
+        if self.check && broken > 0 {
+            bail!("{} files have syntax errors", broken);
+        }
+
+        Ok(())
+    }
+
+    fn checks(&self, syntax: Syntax) -> bool {
+        self.types.is_empty() || self.types.contains(&syntax)
+    }
+
+    fn check(&self, dir: &PathBuf) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let check = || -> Result<(usize, Vec<BrokenFile>)> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+            let mut checked = 0;
+            let mut broken = vec![];
+            for file in git::tracked_files(&git_repo)? {
+                let syntax = match Syntax::of(&file) {
+                    Some(syntax) if self.checks(syntax) => syntax,
+                    _ => continue,
+                };
+                let bytes = match std::fs::read(dir.join(&file)) {
+                    Ok(bytes) => bytes,
+                    // Deleted in the working directory
+                    Err(_) => continue,
+                };
+                checked += 1;
+                let error = match String::from_utf8(bytes) {
+                    Ok(content) => syntax::check(syntax, &content),
+                    Err(e) => Some(ParseError {
+                        line: 1,
+                        message: format!("Not UTF-8: {}", e.utf8_error()),
+                    }),
+                };
+                if let Some(error) = error {
+                    broken.push(BrokenFile {
+                        file,
+                        syntax,
+                        error,
+                    });
+                }
+            }
+            Ok((checked, broken))
+        };
+
+        match check() {
+            Ok((files, broken)) => Status {
+                repo,
+                files,
+                result: Ok(broken),
+            },
+            Err(e) => Status {
+                repo,
+                files: 0,
+                result: Err(format!("{:?}", e)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BrokenFile {
+    file: String,
+    syntax: Syntax,
+    #[serde(flatten)]
+    error: ParseError,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    /// Number of files that are checked
+    files: usize,
+    result: Result<Vec<BrokenFile>, String>,
+}
+
+impl Status {
+    fn broken(&self) -> &[BrokenFile] {
+        match &self.result {
+            Ok(broken) => broken,
+            Err(_) => &[],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "File", "Format", "Error"]);
+    for status in statuses {
+        for (i, file) in status.broken().iter().enumerate() {
+            table.add_row(row![
+                b -> if i == 0 { status.repo.as_str() } else { "" },
+                format!("{}:{}", file.file, file.error.line),
+                file.syntax,
+                Fr -> file.error.message
+            ]);
+        }
+    }
+
+    let broken: usize = statuses.iter().map(|s| s.broken().len()).sum();
+    let files: usize = statuses.iter().map(|s| s.files).sum();
+    if broken == 0 {
+        println!("\nAll {} files parse without errors", files);
+    } else {
+        table.printstd();
+        let repos = statuses.iter().filter(|s| !s.broken().is_empty()).count();
+        let msg = format!(
+            "\n{} of {} files have syntax errors, in {} repositories",
+            broken, files, repos
+        );
+        println!("{}", msg.red());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when checking:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
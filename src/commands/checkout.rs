@@ -1,4 +1,5 @@
 use super::common;
+use crate::branch_map::BranchMap;
 use crate::cli::Args as CommonArgs;
 use crate::git;
 use crate::user::User;
@@ -8,8 +9,10 @@ use anyhow::{anyhow, Result};
 
 use crate::filter::Filter;
 use clap::Parser;
-use git2::BranchType;
+use git2::{BranchType, Repository};
+use std::path::PathBuf;
 
+use super::snapshot_create::Snapshot;
 use crate::commands::topic_helper;
 use crate::convert::try_from_one;
 use crate::github::RemoteRepo;
@@ -21,6 +24,9 @@ use crate::github::RemoteRepo;
 /// This command is able to checkout a local branch as well as a remote branch
 ///
 /// This command is able to clone a repository if it is not on the root directory
+///
+/// With --detach or --manifest a tag or commit is checked out without a branch, to reproduce
+/// the same state in every repository.
 pub struct CheckoutArgs {
     #[arg(long, short)]
     /// Target organisation name
@@ -33,9 +39,22 @@ pub struct CheckoutArgs {
     #[arg(long, required_unless_present("regex"))]
     /// topic to filter
     pub topic: Option<String>,
-    #[arg(long, short)]
+    #[arg(long, short, required_unless_present_any(["detach", "manifest", "branch_map"]))]
     /// branch name to checkout
-    pub branch: String,
+    pub branch: Option<String>,
+    #[arg(long, conflicts_with_all(["branch", "manifest"]))]
+    /// Tag or commit to checkout in detached mode
+    pub detach: Option<String>,
+    #[arg(long, conflicts_with("branch"))]
+    /// Snapshot file with the commit of every repository, as written by snapshot create
+    ///
+    /// Repositories that are not in the snapshot are skipped.
+    pub manifest: Option<PathBuf>,
+    #[arg(long, conflicts_with_all(["detach", "manifest"]))]
+    /// Toml file with the branch to checkout per repository
+    ///
+    /// Repositories that are not in it checkout --branch, or are skipped without it.
+    pub branch_map: Option<PathBuf>,
     #[arg(long)]
     /// Use this option to checkout a remote banch
     ///
@@ -51,6 +70,12 @@ impl CheckoutArgs {
     pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
         let user = common::user()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
+        let snapshot = self.manifest.as_deref().map(Snapshot::load).transpose()?;
+        let branch_map = self
+            .branch_map
+            .as_deref()
+            .map(BranchMap::load)
+            .transpose()?;
 
         let all_repos = topic_helper::query_repositories_with_topics(&organisation, &user.token)?;
 
@@ -69,21 +94,37 @@ impl CheckoutArgs {
         }
 
         for repo in filtered_repos {
-            match checkout_branch(
-                &repo,
-                &self.branch,
-                &user,
-                "origin",
-                self.remote,
-                self.use_https,
-            ) {
-                Ok(_) => println!(
-                    "Checkout branch {} of repo {:?} successfully",
-                    &self.branch, repo.name
-                ),
+            let mapped = branch_map
+                .as_ref()
+                .and_then(|m| m.get(&repo.owner, &repo.name));
+            let target = match (mapped.or(self.branch.as_deref()), &self.detach, &snapshot) {
+                (Some(branch), _, _) => Target::Branch(branch),
+                (None, None, None) if branch_map.is_some() => {
+                    println!(
+                        "Skip repo {:?} because it is not in the branch map",
+                        repo.name
+                    );
+                    continue;
+                }
+                (_, Some(rev), _) => Target::Detached(rev),
+                (_, _, Some(snapshot)) => match snapshot.get(&repo.owner, &repo.name) {
+                    Some(entry) => Target::Detached(&entry.sha),
+                    None => {
+                        println!(
+                            "Skip repo {:?} because it is not in the manifest",
+                            repo.name
+                        );
+                        continue;
+                    }
+                },
+                _ => unreachable!("clap requires a branch, --detach, --manifest or --branch-map"),
+            };
+
+            match checkout(&repo, &target, &user, "origin", self.remote, self.use_https) {
+                Ok(_) => println!("Checkout {} of repo {:?} successfully", target, repo.name),
                 Err(e) => println!(
-                    "Failed to checkout branch {} of repo {:?} because {:?}",
-                    &self.branch, repo.name, e
+                    "Failed to checkout {} of repo {:?} because {:?}",
+                    target, repo.name, e
                 ),
             }
         }
@@ -92,9 +133,23 @@ impl CheckoutArgs {
     }
 }
 
-fn checkout_branch(
+enum Target<'a> {
+    Branch(&'a str),
+    Detached(&'a str),
+}
+
+impl std::fmt::Display for Target<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Branch(branch) => write!(f, "branch {}", branch),
+            Target::Detached(rev) => write!(f, "{} in detached mode", rev),
+        }
+    }
+}
+
+fn checkout(
     repo: &RemoteRepo,
-    branch: &str,
+    target: &Target,
     user: &User,
     remote_name: &str,
     remote: bool,
@@ -103,11 +158,29 @@ fn checkout_branch(
     let git_repo = try_from_one(repo.clone(), user, use_https)?;
     let git_repo = git_repo.open()?;
 
+    match target {
+        Target::Branch(branch) => checkout_branch(&git_repo, branch, user, remote_name, remote),
+        Target::Detached(rev) => {
+            let cred = GitCredential::from(user);
+            git::checkout_detached(&git_repo, rev, remote_name, Some(cred))?;
+            Ok(())
+        }
+    }
+}
+
+/// Checkout a local branch, or the branch of the remote when `remote` is set
+pub fn checkout_branch(
+    git_repo: &Repository,
+    branch: &str,
+    user: &User,
+    remote_name: &str,
+    remote: bool,
+) -> Result<()> {
     if git_repo.find_branch(branch, BranchType::Local).is_ok() {
-        git::checkout_local_branch(&git_repo, branch)?;
+        git::checkout_local_branch(git_repo, branch)?;
     } else if remote {
         let cred = GitCredential::from(user);
-        git::checkout_remote_branch(&git_repo, branch, remote_name, Some(cred))?;
+        git::checkout_remote_branch(git_repo, branch, remote_name, Some(cred))?;
     } else {
         return Err(anyhow!("There is no local branch with name: {}.\n You can use `--remote` option to checkout a remote branch.", branch));
     };
@@ -0,0 +1,412 @@
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::commands::common;
+use crate::commands::errors::{self, ErrorCategory, ErrorReport};
+use crate::commands::progress::{self, Tracked};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::github::{self, RemoteRepo};
+use crate::path::{dir_name, owner_name};
+use crate::user::User;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{Repository, Status as FileStatus, Tree};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+static README: &str = "README.md";
+static WORKFLOWS: &str = ".github/workflows";
+static BEGIN_MARKER: &str = "<!-- gut:ci-badge -->";
+static END_MARKER: &str = "<!-- /gut:ci-badge -->";
+
+/// Workflows that are taken for the badge when a repository has several
+static PREFERRED_WORKFLOWS: &[&str] = &["ci", "build", "main"];
+
+#[derive(Debug, Parser)]
+/// Insert or update a CI status badge in the README.md of all local repositories that match a regex
+///
+/// The badge is kept between `<!-- gut:ci-badge -->` and `<!-- /gut:ci-badge -->`, which are
+/// added below the first heading when they are missing, so running it again after a workflow
+/// was renamed updates the badge. It is committed on top of the current branch and pushed, or
+/// with --pr pushed to a new branch with a pull request.
+pub struct BadgeArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// File name of the workflow in .github/workflows, e.g. ci.yml
+    ///
+    /// By default it is the only workflow of a repository, or the one named ci, build or main.
+    pub workflow: Option<String>,
+    #[arg(long, short, default_value = "Update the CI badge")]
+    /// Commit message
+    pub message: String,
+    #[arg(long)]
+    /// Open a pull request instead of pushing to the current branch
+    pub pr: bool,
+    #[arg(long, short, default_value = "gut/ci-badge")]
+    /// Name of the branch for the pull request
+    pub branch: String,
+}
+
+impl BadgeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        if !confirm(sub_dirs.len(), self.pr)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        // Sequential, because it pushes and opens pull requests
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .iter()
+            .map(|d| progress::track_dir(d, || self.apply(d, &organisation, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn apply(&self, dir: &PathBuf, organisation: &str, user: &User) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+        let apply = || -> Result<Rollout> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if !git_repo.head()?.is_branch() {
+                return Err(anyhow!("HEAD is detached, check out a branch first"));
+            }
+            let current_branch = git::head_shorthand(&git_repo)?;
+            let head = git_repo.head()?.peel_to_commit()?;
+            let head_tree = head.tree()?;
+
+            let workflows = workflows(&git_repo, &head_tree)?;
+            let workflow = choose_workflow(&workflows, self.workflow.as_deref())?;
+
+            let entry = head_tree
+                .get_path(Path::new(README))
+                .map_err(|_| anyhow!("There is no {}", README))?;
+            let blob = git_repo.find_blob(entry.id())?;
+            let readme = std::str::from_utf8(blob.content())
+                .with_context(|| format!("{} is not UTF-8", README))?;
+
+            let content = with_badge(readme, &badge(&owner, &repo, workflow));
+            if content == readme {
+                return Ok(Rollout::UpToDate);
+            }
+
+            let blob = git_repo.blob(content.as_bytes())?;
+            let mut builder = git_repo.treebuilder(Some(&head_tree))?;
+            builder.insert(README, blob, entry.filemode())?;
+            let tree = git_repo.find_tree(builder.write()?)?;
+            let sig = git_repo.signature()?;
+            let cred = GitCredential::from(user);
+
+            if self.pr {
+                let oid = git_repo.commit(None, &sig, &sig, &self.message, &tree, &[&head])?;
+                git_repo.branch(&self.branch, &git_repo.find_commit(oid)?, true)?;
+                git::push::push_branch(&git_repo, &self.branch, "origin", Some(cred))?;
+
+                let remote_repo = RemoteRepo {
+                    name: repo.clone(),
+                    owner: owner.clone(),
+                    ssh_url: String::new(),
+                    https_url: String::new(),
+                };
+                let pr = github::create_pull_request(
+                    &remote_repo,
+                    &self.branch,
+                    &current_branch,
+                    &self.message,
+                    &format!(
+                        "This shows the status of the {} workflow in the README.",
+                        workflow
+                    ),
+                    &user.token,
+                )?;
+                return Ok(Rollout::PullRequest(pr.html_url));
+            }
+
+            check_unmodified(&git_repo)?;
+            git_repo.commit(Some("HEAD"), &sig, &sig, &self.message, &tree, &[&head])?;
+
+            // Bring the working directory and the index up to date with the new commit
+            let workdir = git_repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Cannot commit to a bare repository"))?;
+            std::fs::write(workdir.join(README), &content)?;
+            let mut index = git_repo.index()?;
+            index.add_path(Path::new(README))?;
+            index.write()?;
+
+            git::push::push_branch(&git_repo, &current_branch, "origin", Some(cred))?;
+            Ok(Rollout::Pushed(current_branch))
+        };
+
+        let result = apply().map_err(|e| format!("{:?}", e));
+        Status { repo, result }
+    }
+}
+
+/// The file names of the committed workflows
+fn workflows(git_repo: &Repository, head_tree: &Tree) -> Result<Vec<String>> {
+    let Ok(entry) = head_tree.get_path(Path::new(WORKFLOWS)) else {
+        return Ok(vec![]);
+    };
+    let tree = git_repo.find_tree(entry.id())?;
+    Ok(tree
+        .iter()
+        .filter_map(|e| e.name().map(str::to_string))
+        .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+        .collect())
+}
+
+/// The workflow that the badge shows the status of
+fn choose_workflow<'a>(workflows: &'a [String], wanted: Option<&str>) -> Result<&'a str> {
+    if let Some(wanted) = wanted {
+        return workflows
+            .iter()
+            .find(|w| *w == wanted)
+            .map(|w| w.as_str())
+            .ok_or_else(|| anyhow!("There is no workflow {} in {}", wanted, WORKFLOWS));
+    }
+
+    match workflows {
+        [] => bail!("There are no workflows in {}", WORKFLOWS),
+        [workflow] => Ok(workflow.as_str()),
+        _ => {
+            let stem = |w: &str| w.rsplit_once('.').map(|(stem, _)| stem.to_lowercase());
+            PREFERRED_WORKFLOWS
+                .iter()
+                .find_map(|preferred| {
+                    workflows
+                        .iter()
+                        .find(|w| stem(w.as_str()).as_deref() == Some(*preferred))
+                })
+                .map(|w| w.as_str())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "There are several workflows ({}), choose one with --workflow",
+                        workflows.join(", ")
+                    )
+                })
+        }
+    }
+}
+
+/// The markdown of a badge that links to the runs of a workflow
+fn badge(owner: &str, repo: &str, workflow: &str) -> String {
+    let url = format!(
+        "{}/{}/{}/actions/workflows/{}",
+        github::web_url(),
+        owner,
+        repo,
+        workflow
+    );
+    format!("[![CI]({}/badge.svg)]({})", url, url)
+}
+
+/// The README with the badge between the markers, which are added below the first heading, or
+/// at the top when there is none, when they are missing
+fn with_badge(readme: &str, badge: &str) -> String {
+    let block = format!("{}\n{}\n{}", BEGIN_MARKER, badge, END_MARKER);
+
+    if let (Some(begin), Some(end)) = (readme.find(BEGIN_MARKER), readme.find(END_MARKER)) {
+        if begin < end {
+            let end = end + END_MARKER.len();
+            return format!("{}{}{}", &readme[..begin], block, &readme[end..]);
+        }
+    }
+
+    let mut lines: Vec<&str> = readme.lines().collect();
+    let at = match lines.iter().position(|l| l.starts_with("# ")) {
+        Some(heading) => heading + 1,
+        None => 0,
+    };
+    let inserted = if at == 0 {
+        vec![block.as_str(), ""]
+    } else {
+        vec!["", block.as_str()]
+    };
+    lines.splice(at..at, inserted);
+
+    let mut content = lines.join("\n");
+    if readme.ends_with('\n') || readme.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+/// A README with uncommitted changes would be overwritten
+fn check_unmodified(git_repo: &Repository) -> Result<()> {
+    match git_repo.status_file(Path::new(README)) {
+        Ok(status) if status != FileStatus::CURRENT => Err(anyhow!(
+            "{} has uncommitted changes, commit or discard them first",
+            README
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum Rollout {
+    UpToDate,
+    Pushed(String),
+    PullRequest(String),
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Rollout, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.result_cell()])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Rollout::UpToDate) => cell!("Up to date"),
+            Ok(Rollout::Pushed(branch)) => cell!(Fg -> format!("Pushed to {}", branch)),
+            Ok(Rollout::PullRequest(url)) => cell!(Fg -> url),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when updating the badges:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, pr: bool) -> Result<bool> {
+    let key = "YES";
+    let action = if pr {
+        "open a pull request with the CI badge in"
+    } else {
+        "commit and push the CI badge to"
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to {} {} repo(s)?\nEnter {} to continue",
+            action, count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_badge() {
+        let badge = "[![CI](a/badge.svg)](a)";
+        let readme = "# lang-sme\n\nNorthern Sami\n";
+        let expected = format!(
+            "# lang-sme\n\n{}\n{}\n{}\n\nNorthern Sami\n",
+            BEGIN_MARKER, badge, END_MARKER
+        );
+        assert_eq!(with_badge(readme, badge), expected);
+
+        // Updating replaces only the badge
+        let updated = with_badge(&expected, "[![CI](b/badge.svg)](b)");
+        assert_eq!(
+            updated,
+            format!(
+                "# lang-sme\n\n{}\n[![CI](b/badge.svg)](b)\n{}\n\nNorthern Sami\n",
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+        assert_eq!(with_badge(&updated, "[![CI](b/badge.svg)](b)"), updated);
+
+        assert_eq!(
+            with_badge("Northern Sami", badge),
+            format!(
+                "{}\n{}\n{}\n\nNorthern Sami",
+                BEGIN_MARKER, badge, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn test_choose_workflow() {
+        let workflows = ["release.yml".to_string(), "CI.yaml".to_string()];
+        assert_eq!(choose_workflow(&workflows, None).unwrap(), "CI.yaml");
+        assert_eq!(
+            choose_workflow(&workflows, Some("release.yml")).unwrap(),
+            "release.yml"
+        );
+        assert!(choose_workflow(&workflows, Some("build.yml")).is_err());
+        assert!(choose_workflow(&workflows[..1], None).is_ok());
+        assert!(choose_workflow(&["a.yml".to_string(), "b.yml".to_string()], None).is_err());
+        assert!(choose_workflow(&[], None).is_err());
+    }
+}
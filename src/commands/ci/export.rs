@@ -6,13 +6,14 @@ use crate::commands::models::Script;
 use crate::commands::topic_helper;
 use crate::convert::try_from_one;
 use crate::filter::Filter;
+use crate::git;
 use crate::github::RemoteRepo;
 use crate::user::User;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use serde::Deserialize;
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Parser)]
 /// export data file for ci generate command
@@ -34,6 +35,19 @@ pub struct ExportArgs {
     /// use https to clone repositories if needed
     #[arg(long, short)]
     pub use_https: bool,
+    /// Format of the exported file
+    #[arg(long, value_enum, default_value = "data")]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Data file for the ci generate command
+    Data,
+    /// Build manifest for external build servers as json
+    Json,
+    /// Build manifest for external build servers as yaml
+    Yaml,
 }
 
 impl ExportArgs {
@@ -45,14 +59,31 @@ impl ExportArgs {
         let filtered_repos =
             topic_helper::filter_repos(&all_repos, self.topic.as_ref(), self.regex.as_ref());
 
-        let repos: Result<BTreeMap<String, RepoData>> = filtered_repos
-            .iter()
-            .map(|r| get_repo_data(&r.repo, &self.script, &user, self.use_https))
-            .collect();
+        let output = Path::new(&self.output).to_path_buf();
 
-        let repos = repos?;
+        let result = match self.format {
+            ExportFormat::Data => {
+                let repos: Result<BTreeMap<String, RepoData>> = filtered_repos
+                    .iter()
+                    .map(|r| get_repo_data(&r.repo, &self.script, &user, self.use_https))
+                    .collect();
+                save(&repos?, &output)
+            }
+            ExportFormat::Json | ExportFormat::Yaml => {
+                let repos: Result<Vec<BuildEntry>> = filtered_repos
+                    .iter()
+                    .map(|r| get_build_entry(&r.repo, &self.script, &user, self.use_https))
+                    .collect();
+                let manifest = BuildManifest { repos: repos? };
+                if self.format == ExportFormat::Json {
+                    manifest.save_json(&output)
+                } else {
+                    manifest.save_yaml(&output)
+                }
+            }
+        };
 
-        match save(&repos, &Path::new(&self.output).to_path_buf()) {
+        match result {
             Ok(_) => println!("Save repos data successfully at {:?}", self.output),
             Err(e) => println!("Failed to export data because {:?}", e),
         }
@@ -60,6 +91,36 @@ impl ExportArgs {
     }
 }
 
+/// Build targets and artifacts are read from the `.gut/manifest.toml` of the repository
+/// when it has one
+fn get_build_entry(
+    repo: &RemoteRepo,
+    script: &Script,
+    user: &User,
+    use_https: bool,
+) -> Result<BuildEntry> {
+    let (_, data) = get_repo_data(repo, script, user, use_https)?;
+    let git_repo = try_from_one(repo.clone(), user, use_https)?;
+    let branch = git::head_shorthand(&git_repo.open()?)?;
+
+    let manifest_path: PathBuf = git_repo.local_path.join(".gut/manifest.toml");
+    let (targets, artifacts) = if manifest_path.exists() {
+        let manifest = Manifest::get_from_file(&manifest_path)?;
+        (manifest.targets(), manifest.artifacts())
+    } else {
+        (vec![], vec![])
+    };
+
+    Ok(BuildEntry {
+        repo: repo.full_name(),
+        url: repo.https_url.clone(),
+        branch,
+        version: data.package["__VERSION__"].clone(),
+        targets,
+        artifacts,
+    })
+}
+
 fn get_repo_data(
     repo: &RemoteRepo,
     script: &Script,
@@ -1,9 +1,11 @@
+pub mod badge;
 pub mod export;
 pub mod generate;
 pub mod models;
 
 use crate::cli::Args as CommonArgs;
 use anyhow::Result;
+use badge::*;
 use clap::Parser;
 use export::*;
 use generate::*;
@@ -22,6 +24,8 @@ impl CiArgs {
 
 #[derive(Debug, Parser)]
 pub enum CiCommand {
+    #[command(name = "badge")]
+    Badge(BadgeArgs),
     #[command(name = "export")]
     Export(ExportArgs),
     #[command(name = "generate")]
@@ -31,6 +35,7 @@ pub enum CiCommand {
 impl CiCommand {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         match self {
+            Self::Badge(args) => args.run(common_args),
             Self::Export(args) => args.run(common_args),
             Self::Generate(args) => args.run(common_args),
         }
@@ -1,7 +1,8 @@
 use crate::toml::{from_string, read_file, write_to_file};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::write;
 use std::path::PathBuf;
 
 pub fn save(map: &BTreeMap<String, RepoData>, path: &PathBuf) -> Result<()> {
@@ -35,7 +36,6 @@ impl Manifest {
         toml::to_string(self).context("Manifest serialize error")
     }
 
-    #[allow(dead_code)]
     pub fn get_from_file(path: &PathBuf) -> Result<Manifest> {
         read_file(path)
     }
@@ -44,6 +44,45 @@ impl Manifest {
     pub fn get_from_content(content: &str) -> Result<Manifest> {
         from_string(content)
     }
+
+    /// Platforms that the bundles of this manifest are built for
+    pub fn targets(&self) -> Vec<String> {
+        let targets: BTreeSet<_> = self.bundles.values().map(|b| b.platform.clone()).collect();
+        targets.into_iter().collect()
+    }
+
+    /// Files produced by the spellers of this manifest
+    pub fn artifacts(&self) -> Vec<String> {
+        self.spellers.values().map(|s| s.filename.clone()).collect()
+    }
+}
+
+/// A manifest for external build servers (buildbot, TeamCity)
+#[derive(Serialize, Debug, Clone)]
+pub struct BuildManifest {
+    pub repos: Vec<BuildEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BuildEntry {
+    pub repo: String,
+    pub url: String,
+    pub branch: String,
+    pub version: String,
+    pub targets: Vec<String>,
+    pub artifacts: Vec<String>,
+}
+
+impl BuildManifest {
+    pub fn save_json(&self, path: &PathBuf) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Serialize error")?;
+        write(path, content).context("Cannot write the manifest file")
+    }
+
+    pub fn save_yaml(&self, path: &PathBuf) -> Result<()> {
+        let content = serde_yaml::to_string(self).context("Serialize error")?;
+        write(path, content).context("Cannot write the manifest file")
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
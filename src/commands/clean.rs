@@ -1,3 +1,4 @@
+use super::clean_remote_gone::*;
 use super::common;
 use crate::cli::Args as CommonArgs;
 use crate::filter::Filter;
@@ -8,8 +9,11 @@ use clap::Parser;
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 /// Do git clean -f for all local repositories that match a pattern
 pub struct CleanArgs {
+    #[command(subcommand)]
+    command: Option<CleanCommand>,
     #[arg(long, short)]
     /// Target organisation name
     ///
@@ -21,7 +25,11 @@ pub struct CleanArgs {
 }
 
 impl CleanArgs {
-    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        if let Some(command) = &self.command {
+            return command.run(common_args);
+        }
+
         let root = common::root()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
         let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
@@ -35,6 +43,20 @@ impl CleanArgs {
     }
 }
 
+#[derive(Debug, Parser)]
+pub enum CleanCommand {
+    #[command(name = "remote-gone")]
+    RemoteGone(RemoteGoneArgs),
+}
+
+impl CleanCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::RemoteGone(args) => args.run(common_args),
+        }
+    }
+}
+
 fn clean(dir: &PathBuf) -> Result<()> {
     println!("Cleaning {:?}", dir);
     let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
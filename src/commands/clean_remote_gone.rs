@@ -0,0 +1,493 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::convert::RemoteUrl;
+use crate::filter::Filter;
+use crate::git;
+use crate::github;
+use crate::path::{dir_name, owner_name};
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Parser)]
+/// Find local repositories whose origin does not exist on GitHub anymore
+///
+/// Repositories that are gone are moved into a quarantine directory, or deleted with --delete.
+/// Repositories with local changes or unpushed commits are never deleted, only quarantined.
+/// Repositories that were transferred or renamed get their new origin url and are moved to the
+/// directory of their new owner and name.
+pub struct RemoteGoneArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Directory to move gone repositories into [default: <root>/.quarantine]
+    pub quarantine: Option<PathBuf>,
+    #[arg(long, conflicts_with = "quarantine")]
+    /// Delete gone repositories instead of moving them into quarantine
+    ///
+    /// Repositories with local changes or unpushed commits are still moved into quarantine.
+    pub delete: bool,
+    #[arg(long, short = 'n')]
+    /// Only show what would be done
+    pub dry_run: bool,
+}
+
+impl RemoteGoneArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let owners = readable_owners(&user_token)?;
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| check(d, &organisation, &owners, &user_token))
+            .collect();
+
+        let quarantine = self
+            .quarantine
+            .clone()
+            .unwrap_or_else(|| Path::new(&root).join(".quarantine"));
+        let actions: Vec<_> = statuses
+            .iter()
+            .filter_map(|s| s.action(&root, &quarantine, self.delete))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            if self.dry_run || actions.is_empty() {
+                println!("{}", json!(statuses));
+                return Ok(());
+            }
+        } else {
+            summarize(&statuses, &actions);
+        }
+
+        if self.dry_run || actions.is_empty() {
+            return Ok(());
+        }
+
+        if !confirm(&actions, self.delete)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        let results: Vec<_> = actions
+            .iter()
+            .map(|a| (a, a.apply().map_err(|e| format!("{:?}", e))))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(results));
+            return Ok(());
+        }
+
+        let failed: Vec<_> = results
+            .iter()
+            .filter_map(|(action, result)| {
+                result
+                    .as_ref()
+                    .err()
+                    .map(|e| ErrorReport::from_message(&action.repo, e))
+            })
+            .collect();
+        println!(
+            "\nCleaned up {} repositories",
+            (results.len() - failed.len()).to_string().green()
+        );
+        if !failed.is_empty() {
+            let msg = format!("There {} errors when cleaning up:", failed.len());
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&failed);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum RemoteState {
+    /// The origin is not a GitHub url, it cannot be checked
+    NotOnGitHub,
+    Exists,
+    /// Transferred or renamed, with the new `owner/name`
+    Moved(String),
+    Gone,
+    /// Not found, but the token may not be able to see the private repositories of the owner
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+struct Remote {
+    url: String,
+    state: RemoteState,
+    dirty: bool,
+    /// Commits of local branches that are on no remote-tracking branch
+    unpushed: usize,
+}
+
+impl Remote {
+    /// Whether deleting the repository would lose work that exists nowhere else
+    fn has_local_work(&self) -> bool {
+        self.dirty || self.unpushed > 0
+    }
+}
+
+/// The owners whose private repositories the token can see: the user of the token and the
+/// organisations they are a member of, when the token has the repo scope
+///
+/// GitHub answers 404 for private repositories that the token cannot see, so a missing
+/// repository is only gone when the token can see all repositories of its owner. Membership
+/// alone does not give that, and fine-grained tokens do not list their scopes, so without the
+/// repo scope no owner is confirmed.
+fn readable_owners(token: &str) -> Result<Vec<String>> {
+    let info = github::get_token_info(token)?;
+    if !has_repo_scope(info.scopes.as_deref()) {
+        log::info!("The token has no repo scope, repositories that are not found are unknown");
+        return Ok(vec![]);
+    }
+    let mut owners = vec![info.login];
+    // Without the read:org scope the organisations are unknown, and their repositories are
+    // never taken for gone
+    owners.extend(github::get_user_orgs(token).unwrap_or_default());
+    Ok(owners)
+}
+
+fn has_repo_scope(scopes: Option<&[String]>) -> bool {
+    scopes.is_some_and(|scopes| scopes.iter().any(|s| s == "repo"))
+}
+
+fn check(dir: &PathBuf, organisation: &str, owners: &[String], token: &str) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let check = || -> Result<Remote> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let origin = git_repo
+            .find_remote("origin")
+            .context("There is no origin remote")?;
+        let url = origin
+            .url()
+            .ok_or_else(|| anyhow!("The origin url is not valid utf-8"))?
+            .to_string();
+        let status = git::status(&git_repo, true)?;
+        let dirty = !status.is_empty();
+        let unpushed = git::unpushed_count(&git_repo)?;
+
+        let state = match RemoteUrl::parse(&url) {
+            Some(remote) if remote.host == "github.com" => {
+                let current = github::get_current_full_name(&remote.owner, &remote.name, token)?;
+                match current {
+                    None if owners.iter().any(|o| o.eq_ignore_ascii_case(&remote.owner)) => {
+                        RemoteState::Gone
+                    }
+                    None => RemoteState::Unknown,
+                    Some(full_name)
+                        if full_name
+                            .eq_ignore_ascii_case(&format!("{}/{}", remote.owner, remote.name)) =>
+                    {
+                        RemoteState::Exists
+                    }
+                    Some(full_name) => RemoteState::Moved(full_name),
+                }
+            }
+            _ => RemoteState::NotOnGitHub,
+        };
+
+        Ok(Remote {
+            url,
+            state,
+            dirty,
+            unpushed,
+        })
+    };
+
+    Status {
+        repo: format!("{}/{}", owner, name),
+        dir: dir.clone(),
+        result: check().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Action {
+    repo: String,
+    kind: ActionKind,
+}
+
+#[derive(Debug, Serialize)]
+enum ActionKind {
+    /// Point origin to the new url and move the directory
+    Relocate {
+        from: PathBuf,
+        to: PathBuf,
+        url: String,
+    },
+    Quarantine {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Delete(PathBuf),
+}
+
+impl Action {
+    fn apply(&self) -> Result<()> {
+        match &self.kind {
+            ActionKind::Relocate { from, to, url } => {
+                let git_repo = git::open(from)?;
+                git_repo.remote_set_url("origin", url)?;
+                if from != to {
+                    move_dir(from, to)?;
+                }
+            }
+            ActionKind::Quarantine { from, to } => move_dir(from, to)?,
+            ActionKind::Delete(dir) => {
+                std::fs::remove_dir_all(dir).with_context(|| format!("Cannot delete {:?}", dir))?
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        match &self.kind {
+            ActionKind::Relocate { from, to, url } if from == to => {
+                format!("set origin to {}", url)
+            }
+            ActionKind::Relocate { to, url, .. } => {
+                format!("set origin to {}, move to {}", url, to.display())
+            }
+            ActionKind::Quarantine { to, .. } => format!("move to {}", to.display()),
+            ActionKind::Delete(_) => "delete".to_string(),
+        }
+    }
+}
+
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        return Err(anyhow!("{:?} already exists", to));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(from, to).with_context(|| format!("Cannot move {:?} to {:?}", from, to))
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    result: Result<Remote, String>,
+}
+
+impl Status {
+    fn action(&self, root: &str, quarantine: &Path, delete: bool) -> Option<Action> {
+        let remote = self.result.as_ref().ok()?;
+        let kind = match &remote.state {
+            RemoteState::NotOnGitHub | RemoteState::Exists | RemoteState::Unknown => return None,
+            RemoteState::Moved(full_name) => {
+                let (new_owner, new_name) = full_name.split_once('/')?;
+                let mut url = RemoteUrl::parse(&remote.url)?;
+                let owner_changed = !url.owner.eq_ignore_ascii_case(new_owner);
+                url.owner = new_owner.to_string();
+                url.name = new_name.to_string();
+                // A rename keeps the repository in its group directory
+                let to = if owner_changed {
+                    Path::new(root).join(new_owner).join(new_name)
+                } else {
+                    self.dir.with_file_name(new_name)
+                };
+                ActionKind::Relocate {
+                    from: self.dir.clone(),
+                    to,
+                    url: url.to_url(false),
+                }
+            }
+            RemoteState::Gone if delete && !remote.has_local_work() => {
+                ActionKind::Delete(self.dir.clone())
+            }
+            RemoteState::Gone => ActionKind::Quarantine {
+                from: self.dir.clone(),
+                to: quarantine.join(&self.repo),
+            },
+        };
+        Some(Action {
+            repo: self.repo.clone(),
+            kind,
+        })
+    }
+
+    fn state_cell(&self) -> Cell {
+        match &self.result {
+            Ok(remote) => match &remote.state {
+                RemoteState::NotOnGitHub => cell!("not on GitHub"),
+                RemoteState::Exists => cell!(Fg -> "exists"),
+                RemoteState::Moved(full_name) => cell!(Fy -> format!("moved to {}", full_name)),
+                RemoteState::Gone => cell!(Fr -> "gone"),
+                RemoteState::Unknown => cell!(Fy -> "not found, may be private"),
+            },
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status], actions: &[Action]) {
+    let existing = statuses
+        .iter()
+        .filter(|s| matches!(&s.result, Ok(r) if matches!(r.state, RemoteState::Exists)))
+        .count();
+    println!(
+        "{} of {} repositories still exist on GitHub",
+        existing,
+        statuses.len()
+    );
+
+    let shown: Vec<_> = statuses
+        .iter()
+        .filter(|s| !matches!(&s.result, Ok(r) if matches!(r.state, RemoteState::Exists)))
+        .collect();
+    if !shown.is_empty() {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Repo", "Origin", "State", "Local changes", "Action"]);
+        for status in shown {
+            let (url, changes) = match &status.result {
+                Ok(remote) => (remote.url.as_str(), local_work_cell(remote)),
+                Err(_) => ("", cell!("")),
+            };
+            let action = actions
+                .iter()
+                .find(|a| a.repo == status.repo)
+                .map(|a| a.describe())
+                .unwrap_or_default();
+            table.add_row(Row::new(vec![
+                cell!(b -> &status.repo),
+                cell!(url),
+                status.state_cell(),
+                changes,
+                cell!(action),
+            ]));
+        }
+        table.printstd();
+    }
+
+    if actions.is_empty() {
+        println!("\n{}", "Nothing to clean up".green());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when checking remotes:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn local_work_cell(remote: &Remote) -> Cell {
+    let mut work = vec![];
+    if remote.dirty {
+        work.push("uncommitted".to_string());
+    }
+    if remote.unpushed > 0 {
+        work.push(format!("{} unpushed", remote.unpushed));
+    }
+    if work.is_empty() {
+        cell!("")
+    } else {
+        cell!(Fr -> work.join(", "))
+    }
+}
+
+fn confirm(actions: &[Action], delete: bool) -> Result<bool> {
+    let (key, verb) = if delete {
+        ("DELETE", "delete or relocate")
+    } else {
+        ("YES", "quarantine or relocate")
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to {} {} local repositories?\nEnter {} to continue",
+            verb,
+            actions.len(),
+            key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gone(dirty: bool, unpushed: usize) -> Status {
+        Status {
+            repo: "giellalt/lang-xyz".to_string(),
+            dir: PathBuf::from("/gut/giellalt/lang-xyz"),
+            result: Ok(Remote {
+                url: "git@github.com:giellalt/lang-xyz.git".to_string(),
+                state: RemoteState::Gone,
+                dirty,
+                unpushed,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_has_repo_scope() {
+        let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(has_repo_scope(Some(&scopes(&["read:org", "repo"]))));
+        assert!(!has_repo_scope(Some(&scopes(&["read:org", "public_repo"]))));
+        assert!(!has_repo_scope(None));
+    }
+
+    #[test]
+    fn test_delete_keeps_local_work() {
+        let quarantine = Path::new("/gut/.quarantine");
+        let kind = |status: Status| status.action("/gut", quarantine, true).unwrap().kind;
+
+        assert!(matches!(kind(gone(false, 0)), ActionKind::Delete(_)));
+        assert!(matches!(kind(gone(true, 0)), ActionKind::Quarantine { .. }));
+        assert!(matches!(
+            kind(gone(false, 2)),
+            ActionKind::Quarantine { .. }
+        ));
+    }
+}
@@ -1,18 +1,25 @@
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::topic_helper;
 
-use crate::github::RemoteRepo;
+use crate::github::{self, RemoteRepo};
 use crate::cli::Args as CommonArgs;
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 
 use crate::convert::try_from_one;
 use crate::filter::Filter;
+use crate::git;
 use crate::git::models::GitRepo;
 use crate::git::Clonable;
 use crate::user::User;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use colored::*;
 use prettytable::{cell, format, row, Cell, Row, Table};
 use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::thread;
+use std::time::Duration;
 
 #[derive(Debug, Parser)]
 /// Clone all repositories that matches a pattern
@@ -28,6 +35,32 @@ pub struct CloneArgs {
     #[arg(long, short)]
     /// Option to use https instead of ssh when clone repositories
     pub use_https: bool,
+    #[arg(long)]
+    /// Option to leave Git LFS files as pointer files
+    ///
+    /// Use gut lfs fetch later to download them for the repositories that need them.
+    pub no_lfs: bool,
+    #[arg(long, value_enum, default_value_t = OrgStructure::Flat)]
+    /// How to lay out the repositories in the organisation directory
+    pub org_structure: OrgStructure,
+    #[arg(long, required_if_eq("org_structure", "topics"))]
+    /// Prefix of the topic that names the subdirectory of a repository, like langfam-
+    pub topic_prefix: Option<String>,
+    #[arg(long, value_name = "OWNER/REPO")]
+    /// Also clone the fork of an external repository, and fork it into the organisation first
+    /// when there is no fork yet
+    ///
+    /// The fork is the origin remote of the clone and the external repository its upstream
+    /// remote. Can be given more than once. Without --regex only the forks are cloned.
+    pub include_forks_of: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OrgStructure {
+    /// Every repository directly in <root>/<organisation>
+    Flat,
+    /// Repositories in <root>/<organisation>/<topic>, by the first topic with --topic-prefix
+    Topics,
 }
 
 impl CloneArgs {
@@ -39,10 +72,13 @@ impl CloneArgs {
             false => common::use_https()?,
         };
 
-        let filtered_repos =
-            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user.token)?;
+        let filtered_repos = if self.regex.is_none() && !self.include_forks_of.is_empty() {
+            vec![]
+        } else {
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user.token)?
+        };
 
-        if filtered_repos.is_empty() {
+        if filtered_repos.is_empty() && self.include_forks_of.is_empty() {
             println!(
                 "There is no repositories in organisation {} matches pattern {:?}",
                 &organisation, self.regex
@@ -50,20 +86,169 @@ impl CloneArgs {
             return Ok(());
         }
 
-        let statuses: Vec<_> = filtered_repos
+        let groups = match (self.org_structure, &self.topic_prefix) {
+            (OrgStructure::Topics, Some(prefix)) => topic_groups(&filtered_repos, prefix, &user)?,
+            _ => BTreeMap::new(),
+        };
+
+        progress::begin(filtered_repos.len() + self.include_forks_of.len());
+        let mut statuses: Vec<_> = filtered_repos
             .par_iter()
-            .map(|r| clone(r, &user, use_https))
+            .map(|r| {
+                progress::track(&r.full_name(), || {
+                    let group = groups.get(&r.full_name()).map(String::as_str);
+                    clone(r, &user, use_https, self.no_lfs, group)
+                })
+            })
             .collect();
 
+        for upstream in &self.include_forks_of {
+            let status = progress::track(upstream, || {
+                match fork_of(upstream, &organisation, &user.token) {
+                    Ok(fork) => clone_fork(&fork, &user, use_https, self.no_lfs),
+                    Err(e) => Status {
+                        repo: RemoteRepo {
+                            name: upstream.clone(),
+                            owner: organisation.clone(),
+                            ssh_url: String::new(),
+                            https_url: String::new(),
+                        },
+                        result: Err(e),
+                        lfs: LfsStatus::No,
+                    },
+                }
+            });
+            statuses.push(status);
+        }
+
         summarize(&statuses);
 
         Ok(())
     }
 }
 
-fn clone(repo: &RemoteRepo, user: &User, use_https: bool) -> Status {
+/// The subdirectory of every repository that has a topic with the prefix
+fn topic_groups(
+    repos: &[RemoteRepo],
+    prefix: &str,
+    user: &User,
+) -> Result<BTreeMap<String, String>> {
+    let owners: BTreeSet<_> = repos.iter().map(|r| r.owner.as_str()).collect();
+
+    let mut groups = BTreeMap::new();
+    for owner in owners {
+        for repo in topic_helper::query_repositories_with_topics(owner, &user.token)? {
+            let mut topics: Vec<_> = repo
+                .topics
+                .iter()
+                .filter(|t| t.starts_with(prefix))
+                .collect();
+            topics.sort();
+            if let Some(topic) = topics.first() {
+                groups.insert(repo.repo.full_name(), topic.to_string());
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// A fork in the organisation and the repository it is forked from
+struct Fork {
+    fork: RemoteRepo,
+    upstream: RemoteRepo,
+    /// The fork was made just now
+    created: bool,
+}
+
+/// The fork of `upstream`, written as owner/name, in the organisation, which is made when there
+/// is none
+fn fork_of(upstream: &str, organisation: &str, token: &str) -> Result<Fork> {
+    let (owner, name) = upstream
+        .split_once('/')
+        .ok_or_else(|| anyhow!("{} is not written as owner/name", upstream))?;
+    let upstream = github::get_fork_info(owner, name, token)?
+        .ok_or_else(|| anyhow!("There is no repository {}", upstream))?
+        .remote_repo();
+
+    match github::get_fork_info(organisation, &upstream.name, token)? {
+        Some(fork) if is_fork_of(&fork, &upstream) => Ok(Fork {
+            fork: fork.remote_repo(),
+            upstream,
+            created: false,
+        }),
+        Some(_) => bail!(
+            "{}/{} exists and is not a fork of {}",
+            organisation,
+            upstream.name,
+            upstream.full_name()
+        ),
+        None => Ok(Fork {
+            fork: github::create_fork(&upstream, organisation, token)?.remote_repo(),
+            upstream,
+            created: true,
+        }),
+    }
+}
+
+fn is_fork_of(fork: &github::ForkInfo, upstream: &RemoteRepo) -> bool {
+    fork.fork
+        && fork
+            .parent
+            .as_ref()
+            .is_some_and(|p| p.full_name.eq_ignore_ascii_case(&upstream.full_name()))
+}
+
+/// Clone a fork and add the repository it is forked from as the upstream remote
+///
+/// GitHub copies a new fork in the background, so cloning it is retried for a while.
+fn clone_fork(fork: &Fork, user: &User, use_https: bool, no_lfs: bool) -> Status {
+    let local_path = try_from_one(fork.fork.clone(), user, use_https).map(|r| r.local_path);
+    let existed = local_path.as_ref().map(|p| p.exists()).unwrap_or(true);
+
+    let mut status = clone(&fork.fork, user, use_https, no_lfs, None);
+    for _ in 0..6 {
+        if status.result.is_ok() || !fork.created || existed {
+            break;
+        }
+        if let Ok(path) = &local_path {
+            let _ = std::fs::remove_dir_all(path);
+        }
+        thread::sleep(Duration::from_secs(5));
+        status = clone(&fork.fork, user, use_https, no_lfs, None);
+    }
+
+    if let Ok(git_repo) = &status.result {
+        let url = if use_https {
+            format!("{}.git", fork.upstream.https_url)
+        } else {
+            fork.upstream.ssh_url.clone()
+        };
+        let add = || -> Result<(), git2::Error> {
+            let repo = git_repo.open()?;
+            repo.remote("upstream", &url)?;
+            Ok(())
+        };
+        if let Err(e) = add() {
+            status.result = Err(Error::from(e).context("Cannot add the upstream remote"));
+        }
+    }
+    status
+}
+
+fn clone(
+    repo: &RemoteRepo,
+    user: &User,
+    use_https: bool,
+    no_lfs: bool,
+    group: Option<&str>,
+) -> Status {
     let cl = || -> Result<GitRepo> {
-        let git_repo = try_from_one(repo.clone(), user, use_https)?;
+        let mut git_repo = try_from_one(repo.clone(), user, use_https)?;
+        if let (Some(group), false) = (group, git_repo.local_path.exists()) {
+            if let Some(org_dir) = git_repo.local_path.parent() {
+                git_repo.local_path = org_dir.join(group).join(&repo.name);
+            }
+        }
         if git_repo.local_path.exists() {
             return Err(anyhow!(
                 "Repository {} already exists at {:?}",
@@ -75,51 +260,97 @@ fn clone(repo: &RemoteRepo, user: &User, use_https: bool) -> Status {
         Ok(result)
     };
     let result = cl();
+
+    let lfs = match &result {
+        Ok(git_repo) => match git_repo.open().map_err(Error::from).and_then(|r| {
+            if no_lfs {
+                git::lfs_skip(&r)
+            } else {
+                git::lfs_pull(&r, git_repo.cred.as_ref())
+            }
+        }) {
+            Ok(true) if no_lfs => LfsStatus::Skipped,
+            Ok(true) => LfsStatus::Pulled,
+            Ok(false) => LfsStatus::No,
+            Err(e) => LfsStatus::Failed(e),
+        },
+        Err(_) => LfsStatus::No,
+    };
+
     Status {
         repo: repo.clone(),
         result,
+        lfs,
     }
 }
 
 struct Status {
     repo: RemoteRepo,
     result: Result<GitRepo, Error>,
+    lfs: LfsStatus,
+}
+
+enum LfsStatus {
+    No,
+    Pulled,
+    Skipped,
+    Failed(Error),
+}
+
+impl LfsStatus {
+    fn to_cell(&self) -> Cell {
+        match &self {
+            LfsStatus::No => cell!(r -> "-"),
+            LfsStatus::Pulled => cell!(Fgr -> "Pulled"),
+            LfsStatus::Skipped => cell!(Fyr -> "Skipped"),
+            LfsStatus::Failed(e) => ErrorCategory::of(e).failed_cell(),
+        }
+    }
 }
 
 impl Status {
     fn to_row(&self) -> Row {
-        Row::new(vec![cell!(b -> &self.repo.name), self.status()])
+        Row::new(vec![
+            cell!(b -> &self.repo.name),
+            self.status(),
+            self.lfs.to_cell(),
+        ])
     }
 
     fn status(&self) -> Cell {
         match &self.result {
             Ok(_) => cell!(Fgr -> "Success"),
-            Err(_) => cell!(Frr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
     fn has_error(&self) -> bool {
-        matches!(self.result, Err(_))
+        self.result.is_err() || matches!(self.lfs, LfsStatus::Failed(_))
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let Err(e) = &self.result {
             e
+        } else if let LfsStatus::Failed(e) = &self.lfs {
+            e
         } else {
             panic!("This should have an error here");
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo.name), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo.name, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
     }
 }
 
 fn to_table(statuses: &[Status]) -> Table {
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-    table.set_titles(row!["Repo", "Status"]);
+    table.set_titles(row!["Repo", "Status", "LFS"]);
     for status in statuses {
         table.add_row(status.to_row());
     }
@@ -131,7 +362,7 @@ fn summarize(statuses: &[Status]) {
     table.printstd();
 
     let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
-    let successes: Vec<_> = statuses.iter().filter(|s| !s.has_error()).collect();
+    let successes: Vec<_> = statuses.iter().filter(|s| s.result.is_ok()).collect();
 
     if !successes.is_empty() {
         let msg = format!("\nCloned {} repos successfully!", successes.len());
@@ -144,12 +375,7 @@ fn summarize(statuses: &[Status]) {
         let msg = format!("There {} errors when cloning:", errors.len());
         println!("\n{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
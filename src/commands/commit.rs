@@ -1,9 +1,13 @@
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::push::push_if_ahead;
 use crate::cli::Args as CommonArgs;
 use crate::filter::Filter;
 use crate::git;
 use anyhow::Result;
 use clap::Parser;
+use git2::Repository;
 use std::path::Path;
 
 use crate::commands::topic_helper;
@@ -34,6 +38,12 @@ pub struct CommitArgs {
     #[arg(long, short)]
     /// Option to use https instead of ssh when clone repositories
     pub use_https: bool,
+    #[arg(long)]
+    /// Push the current branch after committing
+    pub push: bool,
+    #[arg(long = "path")]
+    /// Only add the files that match this glob, e.g. src/fst/**, can be repeated
+    pub paths: Vec<String>,
 }
 
 impl CommitArgs {
@@ -57,56 +67,86 @@ impl CommitArgs {
             return Ok(());
         }
 
+        progress::begin(filtered_repos.len());
         let statuses: Vec<_> = filtered_repos
             .par_iter()
-            .map(|r| commit(r, &self.message, &user, self.use_https))
+            .map(|r| {
+                progress::track(&r.full_name(), || {
+                    commit(r, self, &user)
+                })
+            })
             .collect();
 
-        summarize(&statuses);
+        summarize(&statuses, self.push);
 
         Ok(())
     }
 }
 
-fn commit(repo: &RemoteRepo, msg: &str, user: &User, use_https: bool) -> Status {
-    let commit = || -> Result<CommitResult> {
-        let git_repo = try_from_one(repo.clone(), user, use_https)?;
-        let git_repo = git_repo.open()?;
-
-        let status = git::status(&git_repo, true)?;
-        //let current_branch = git::head_shorthand(&git_repo)?;
-
-        if !status.can_commit() {
-            return Ok(CommitResult::Conflict);
+fn commit(repo: &RemoteRepo, args: &CommitArgs, user: &User) -> Status {
+    let git_repo = || -> Result<Repository> {
+        let git_repo = try_from_one(repo.clone(), user, args.use_https)?;
+        Ok(git_repo.open()?)
+    };
+    let git_repo = match git_repo() {
+        Ok(git_repo) => git_repo,
+        Err(e) => {
+            return Status {
+                repo: repo.clone(),
+                result: Err(e),
+                push: None,
+            }
         }
+    };
 
-        if !status.should_commit() {
-            return Ok(CommitResult::NoChanges);
-        }
+    let result = commit_paths(&git_repo, &args.message, &args.paths);
+    let push = match &result {
+        Ok(CommitResult::Success | CommitResult::NoChanges) if args.push => Some(
+            git::head_shorthand(&git_repo)
+                .and_then(|branch| push_if_ahead(&git_repo, &branch, user, "origin")),
+        ),
+        _ => None,
+    };
+    Status {
+        repo: repo.clone(),
+        result,
+        push,
+    }
+}
 
-        let mut index = git_repo.index()?;
+/// Add the changes of the files that match one of the pathspecs and commit them
+///
+/// Without pathspecs all changes are added.
+pub fn commit_paths(git_repo: &Repository, msg: &str, paths: &[String]) -> Result<CommitResult> {
+    let status = git::status_in(git_repo, true, paths)?;
+    //let current_branch = git::head_shorthand(&git_repo)?;
 
-        let addable_list = status.addable_list();
-        for p in addable_list {
-            //log::debug!("addable file: {}", p);
-            let path = Path::new(&p);
-            index.add_path(path)?;
-        }
+    if !status.can_commit() {
+        return Ok(CommitResult::Conflict);
+    }
 
-        for p in status.deleted {
-            //log::debug!("removed file: {}", p);
-            let path = Path::new(&p);
-            index.remove_path(path)?;
-        }
+    if !status.should_commit() {
+        return Ok(CommitResult::NoChanges);
+    }
 
-        git::commit_index(&git_repo, &mut index, msg)?;
+    let mut index = git_repo.index()?;
 
-        Ok(CommitResult::Success)
-    };
-    Status {
-        repo: repo.clone(),
-        result: commit(),
+    let addable_list = status.addable_list();
+    for p in addable_list {
+        //log::debug!("addable file: {}", p);
+        let path = Path::new(&p);
+        index.add_path(path)?;
+    }
+
+    for p in status.deleted {
+        //log::debug!("removed file: {}", p);
+        let path = Path::new(&p);
+        index.remove_path(path)?;
     }
+
+    git::commit_index(git_repo, &mut index, msg)?;
+
+    Ok(CommitResult::Success)
 }
 
 pub enum CommitResult {
@@ -118,11 +158,25 @@ pub enum CommitResult {
 struct Status {
     repo: RemoteRepo,
     result: Result<CommitResult>,
+    /// Whether the branch was pushed, None when --push is not given or the commit failed
+    push: Option<Result<bool>>,
 }
 
 impl Status {
-    fn to_row(&self) -> Row {
-        Row::new(vec![cell!(b -> &self.repo.name), self.status()])
+    fn to_row(&self, push: bool) -> Row {
+        let mut cells = vec![cell!(b -> &self.repo.name), self.status()];
+        if push {
+            cells.push(self.push_cell());
+        }
+        Row::new(cells)
+    }
+
+    fn push_cell(&self) -> Cell {
+        match &self.push {
+            Some(Ok(true)) => cell!(Fgr -> "Success"),
+            Some(Err(e)) => ErrorCategory::of(e).failed_cell(),
+            _ => cell!(r -> "-"),
+        }
     }
 
     fn status(&self) -> Cell {
@@ -134,40 +188,46 @@ impl Status {
                 CommitResult::NoChanges => cell!(l -> "There is no changes."),
                 CommitResult::Success => cell!(Fgl -> "Success"),
             },
-            Err(_) => cell!(Frr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
     fn has_error(&self) -> bool {
-        matches!(self.result, Err(_))
+        self.result.is_err() || matches!(self.push, Some(Err(_)))
     }
 
-    fn to_error_row(&self) -> Row {
-        let e = if let Err(e) = &self.result {
-            e
-        } else {
-            panic!("This should have an error here");
+    fn to_error_report(&self) -> ErrorReport {
+        let e = match (&self.result, &self.push) {
+            (Err(e), _) | (_, Some(Err(e))) => e,
+            _ => panic!("This should have an error here"),
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo.name), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo.name, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
     }
 }
 
-fn to_table(statuses: &[Status]) -> Table {
+fn to_table(statuses: &[Status], push: bool) -> Table {
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-    table.set_titles(row!["Repo", "Status"]);
+    if push {
+        table.set_titles(row!["Repo", "Status", "Push"]);
+    } else {
+        table.set_titles(row!["Repo", "Status"]);
+    }
     for status in statuses {
-        table.add_row(status.to_row());
+        table.add_row(status.to_row(push));
     }
     table
 }
 
-fn summarize(statuses: &[Status]) {
-    let table = to_table(statuses);
+fn summarize(statuses: &[Status], push: bool) {
+    let table = to_table(statuses, push);
     table.printstd();
 
     let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
@@ -187,12 +247,7 @@ fn summarize(statuses: &[Status]) {
         );
         println!("\n{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
@@ -10,21 +10,83 @@ use crate::github::{NoReposFound, RemoteRepo, Unauthorized};
 
 use crate::filter::{Filter, Filterable};
 use crate::user::User;
+use crate::workspace::{self, Workspace};
+
+use super::topic_helper::query_repositories_with_topics;
 
 pub fn query_and_filter_repositories(
     org: &str,
     regex: Option<&Filter>,
     token: &str,
 ) -> Result<Vec<RemoteRepo>> {
-    let remote_repos = remote_repos(token, org)?;
-    let mut result = RemoteRepo::filter_with_option(remote_repos, regex);
+    if let Some((_, workspace)) = workspace::active() {
+        return query_workspace_repositories(workspace, token);
+    }
+
+    let mut result = vec![];
+    for owner in selected_owners(org, regex) {
+        let remote_repos = remote_repos(token, &owner)?;
+        result.extend(RemoteRepo::filter_with_option(remote_repos, regex));
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Every organisation of the token with `all_orgs`, and otherwise the organisation of the
+/// command with the owners named in the regex
+pub fn owners(
+    organisation: Option<&str>,
+    all_orgs: bool,
+    regex: Option<&Filter>,
+    token: &str,
+) -> Result<Vec<String>> {
+    if all_orgs {
+        return github::get_user_orgs(token);
+    }
+    let organisation = self::organisation(organisation)?;
+    Ok(selected_owners(&organisation, regex))
+}
+
+/// The organisation of the command, or the owners named in `owner:pattern` regexes
+pub fn selected_owners(org: &str, regex: Option<&Filter>) -> Vec<String> {
+    let mut owners = regex.map(|r| r.owners()).unwrap_or_default();
+    let uses_org = owners.is_empty() || regex.map(|r| r.has_unscoped()).unwrap_or(false);
+    if uses_org && !owners.iter().any(|o| o.eq_ignore_ascii_case(org)) {
+        owners.insert(0, org.to_string());
+    }
+    owners
+}
+
+fn query_workspace_repositories(workspace: &Workspace, token: &str) -> Result<Vec<RemoteRepo>> {
+    let mut result = vec![];
+    for selection in &workspace.selections {
+        let filter = selection.filter()?;
+        if selection.topic.is_some() {
+            let repos = query_repositories_with_topics(&selection.owner, token)?;
+            result.extend(
+                repos
+                    .into_iter()
+                    .filter(|r| selection.is_match(&r.repo.name, &r.topics, filter.as_ref()))
+                    .map(|r| r.repo),
+            );
+        } else {
+            let repos = remote_repos(token, &selection.owner)?;
+            result.extend(
+                repos
+                    .into_iter()
+                    .filter(|r| selection.is_match(&r.name, &[], filter.as_ref())),
+            );
+        }
+    }
     result.sort();
+    result.dedup();
     Ok(result)
 }
 
 pub fn user() -> Result<User> {
-    User::from_config()
-        .context("Cannot get user token from the config file. Run `gut init` with a valid token")
+    User::load().context(
+        "Cannot get user token from the config file. Run `gut init` with a valid token or set GUT_TOKEN",
+    )
 }
 
 pub fn root() -> Result<String> {
@@ -33,15 +95,23 @@ pub fn root() -> Result<String> {
 }
 
 pub fn user_token() -> Result<String> {
-    User::token()
-        .context("Cannot get user token from the config file. Run `gut init` with a valid token")
+    User::token().context(
+        "Cannot get user token from the config file. Run `gut init` with a valid token or set GUT_TOKEN",
+    )
 }
 
 pub fn organisation(opt: Option<&str>) -> Result<String> {
+    if let (None, Some((name, workspace))) = (opt, workspace::active()) {
+        return match workspace.selections.first() {
+            Some(selection) => Ok(selection.owner.clone()),
+            None => anyhow::bail!("Workspace {} is empty", name),
+        };
+    }
+
     match opt {
         Some(s) => Ok(s.to_string()),
         None => {
-            let config = Config::from_file()?;
+            let config = Config::load()?;
             match config.default_org {
                 Some(o) => Ok(o),
                 None => anyhow::bail!("You need to provide an organisation or set a default organisation with init/set default organisation command."),
@@ -51,7 +121,7 @@ pub fn organisation(opt: Option<&str>) -> Result<String> {
 }
 
 pub fn use_https() -> Result<bool> {
-    let config = Config::from_file()?;
+    let config = Config::load()?;
     Ok(config.use_https)
 }
 
@@ -71,6 +141,19 @@ fn remote_repos(token: &str, org: &str) -> Result<Vec<RemoteRepo>> {
 }
 
 pub fn read_dirs_for_org(org: &str, root: &str, filter: Option<&Filter>) -> Result<Vec<PathBuf>> {
+    if let Some((_, workspace)) = workspace::active() {
+        return read_workspace_dirs(workspace, root);
+    }
+
+    let mut result = vec![];
+    for owner in selected_owners(org, filter) {
+        result.extend(read_dirs_for_owner(&owner, root, filter)?);
+    }
+    result.sort();
+    Ok(result)
+}
+
+fn read_dirs_for_owner(org: &str, root: &str, filter: Option<&Filter>) -> Result<Vec<PathBuf>> {
     let target_dir = path::local_path_org(org, root)?;
 
     let result = match filter {
@@ -79,10 +162,7 @@ pub fn read_dirs_for_org(org: &str, root: &str, filter: Option<&Filter>) -> Resu
     };
 
     match result {
-        Ok(mut vec) => {
-            vec.sort();
-            Ok(vec)
-        }
+        Ok(vec) => Ok(vec),
         Err(e) => Err(anyhow!(
             "Cannot read sub directories for organisation {} \"{}\" because {:?}",
             target_dir.display(),
@@ -92,6 +172,35 @@ pub fn read_dirs_for_org(org: &str, root: &str, filter: Option<&Filter>) -> Resu
     }
 }
 
+fn read_workspace_dirs(workspace: &Workspace, root: &str) -> Result<Vec<PathBuf>> {
+    let mut result = vec![];
+    for selection in &workspace.selections {
+        let filter = selection.filter()?;
+        let topics = match selection.topic {
+            Some(_) => query_repositories_with_topics(&selection.owner, &user_token()?)?,
+            None => vec![],
+        };
+        let topics_of = |name: &str| {
+            topics
+                .iter()
+                .find(|r| r.repo.name == name)
+                .map(|r| r.topics.clone())
+                .unwrap_or_default()
+        };
+
+        let target_dir = path::local_path_org(&selection.owner, root)?;
+        let dirs = read_dirs(&target_dir)
+            .with_context(|| format!("Cannot read sub directories of {}", target_dir.display()))?;
+        result.extend(dirs.into_iter().filter(|d| {
+            let name = path::dir_name(d).unwrap_or_default();
+            selection.is_match(&name, &topics_of(&name), filter.as_ref())
+        }));
+    }
+    result.sort();
+    result.dedup();
+    Ok(result)
+}
+
 /// Filter directory's name by regex
 pub fn read_dirs_with_filter(path: &Path, filter: &Filter) -> Result<Vec<PathBuf>> {
     let dirs = read_dirs(path)?;
@@ -100,13 +209,24 @@ pub fn read_dirs_with_filter(path: &Path, filter: &Filter) -> Result<Vec<PathBuf
 
 /// Read all dirs inside a path
 /// Filter directories
+///
+/// Group directories made by `clone --org-structure topics` are replaced by the repositories
+/// inside them.
 fn read_dirs(path: &Path) -> Result<Vec<PathBuf>> {
     let entries = path.read_dir()?;
-    let dirs = entries
-        .filter_map(|x| x.ok())
-        .map(|x| x.path())
-        .filter(|x| x.is_dir())
-        .collect();
+    let mut dirs = vec![];
+    for dir in entries.filter_map(|x| x.ok()).map(|x| x.path()) {
+        if path::is_group_dir(&dir) {
+            dirs.extend(
+                dir.read_dir()?
+                    .filter_map(|x| x.ok())
+                    .map(|x| x.path())
+                    .filter(|x| x.is_dir()),
+            );
+        } else if dir.is_dir() {
+            dirs.push(dir);
+        }
+    }
     Ok(dirs)
 }
 
@@ -0,0 +1,31 @@
+use super::convert_remotes::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Convert settings of local repositories to a common form
+pub struct ConvertArgs {
+    #[command(subcommand)]
+    command: ConvertCommand,
+}
+
+impl ConvertArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum ConvertCommand {
+    #[command(name = "remotes")]
+    Remotes(ConvertRemotesArgs),
+}
+
+impl ConvertCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Remotes(args) => args.run(common_args),
+        }
+    }
+}
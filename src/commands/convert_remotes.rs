@@ -0,0 +1,267 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::convert::RemoteUrl;
+use crate::filter::Filter;
+use crate::git;
+use crate::github;
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Normalize the remote urls of all local repositories that match a regex
+///
+/// Urls lose their .git suffix and trailing slash, git:// urls become ssh or https urls, and
+/// repositories that were transferred or renamed on GitHub get their current owner and name.
+/// The changes are shown before anything is written.
+pub struct ConvertRemotesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short = 'n')]
+    /// Only show the changes
+    pub dry_run: bool,
+    #[arg(long)]
+    /// Do not ask GitHub about transferred or renamed repositories
+    pub local: bool,
+}
+
+impl ConvertRemotesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let use_https = common::use_https()?;
+        let token = if self.local {
+            None
+        } else {
+            Some(common::user_token()?)
+        };
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| plan(d, &organisation, use_https, token.as_deref()))
+            .collect();
+        let changes: Vec<_> = statuses
+            .iter()
+            .filter_map(|s| s.result.as_ref().ok())
+            .flatten()
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            if self.dry_run || changes.is_empty() {
+                println!("{}", json!(statuses));
+                return Ok(());
+            }
+        } else {
+            summarize(&statuses);
+        }
+
+        if self.dry_run || changes.is_empty() {
+            return Ok(());
+        }
+
+        if !confirm(changes.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        let applied: Vec<_> = changes.iter().map(|c| apply(c)).collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(applied));
+            return Ok(());
+        }
+
+        let failed: Vec<_> = applied
+            .iter()
+            .filter_map(|(change, result)| {
+                result
+                    .as_ref()
+                    .err()
+                    .map(|e| ErrorReport::from_message(&change.repo, e))
+            })
+            .collect();
+        println!(
+            "\nUpdated {} remotes",
+            (applied.len() - failed.len()).to_string().green()
+        );
+        if !failed.is_empty() {
+            let msg = format!("There {} errors when updating remotes:", failed.len());
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&failed);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Change {
+    repo: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    remote: String,
+    from: String,
+    to: String,
+}
+
+/// The remotes of a repository that are not in the normal form, with their normal url
+fn plan(dir: &PathBuf, organisation: &str, use_https: bool, token: Option<&str>) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+    let repo = format!("{}/{}", owner, name);
+
+    let plan = || -> Result<Vec<Change>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let mut changes = vec![];
+        for remote in git_repo.remotes()?.iter().flatten() {
+            let from = match git_repo.find_remote(remote)?.url() {
+                Some(url) => url.to_string(),
+                None => continue,
+            };
+            let mut url = match RemoteUrl::parse(&from) {
+                Some(url) => url,
+                None => continue,
+            };
+
+            if let (Some(token), true) = (token, url.host == "github.com") {
+                let full_name = github::get_current_full_name(&url.owner, &url.name, token)
+                    .with_context(|| format!("Cannot look up {}/{}", url.owner, url.name))?;
+                if let Some((owner, name)) = full_name.as_deref().and_then(|n| n.split_once('/')) {
+                    url.owner = owner.to_string();
+                    url.name = name.to_string();
+                }
+            }
+
+            let to = url.to_url(use_https);
+            if to != from {
+                changes.push(Change {
+                    repo: repo.clone(),
+                    dir: dir.clone(),
+                    remote: remote.to_string(),
+                    from,
+                    to,
+                });
+            }
+        }
+        Ok(changes)
+    };
+
+    Status {
+        repo: repo.clone(),
+        result: plan().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+fn apply(change: &Change) -> (&Change, Result<(), String>) {
+    let result = git::open(&change.dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|r| Ok(r.remote_set_url(&change.remote, &change.to)?))
+        .map_err(|e| format!("{:?}", e));
+    (change, result)
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<Change>, String>,
+}
+
+impl Status {
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+
+    fn to_rows(&self) -> Vec<Row> {
+        match &self.result {
+            Ok(changes) => changes
+                .iter()
+                .map(|c| {
+                    let diff = format!("- {}\n+ {}", c.from.red(), c.to.green());
+                    row![b -> self.repo, c.remote, diff]
+                })
+                .collect(),
+            Err(e) => vec![Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+            ])],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let rows: Vec<_> = statuses.iter().flat_map(|s| s.to_rows()).collect();
+    let changes = statuses
+        .iter()
+        .filter_map(|s| s.result.as_ref().ok())
+        .map(|c| c.len())
+        .sum::<usize>();
+
+    if changes == 0 {
+        println!("{}", "Every remote url is already normalized".green());
+    }
+    if !rows.is_empty() {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Repo", "Remote", "Url"]);
+        for row in rows {
+            table.add_row(row);
+        }
+        table.printstd();
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when reading remotes:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to update {} remote url(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
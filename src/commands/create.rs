@@ -1,6 +1,7 @@
 use crate::cli::Args as CommonArgs;
 use super::create_branch::*;
 use super::create_discussion::*;
+use super::create_keyboard::*;
 use super::create_repo::*;
 use super::create_team::*;
 use anyhow::Result;
@@ -28,6 +29,8 @@ pub enum CreateCommand {
     Branch(CreateBranchArgs),
     #[command(name = "repo", aliases = &["repository"])]
     Repo(CreateRepoArgs),
+    #[command(name = "keyboard")]
+    Keyboard(CreateKeyboardArgs),
 }
 
 impl CreateCommand {
@@ -37,6 +40,7 @@ impl CreateCommand {
             Self::Team(args) => args.create_team(common_args),
             Self::Branch(args) => args.run(common_args),
             Self::Repo(args) => args.run(common_args),
+            Self::Keyboard(args) => args.run(common_args),
         }
     }
 }
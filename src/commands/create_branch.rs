@@ -1,8 +1,11 @@
 use crate::cli::Args as CommonArgs;
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
 use crate::commands::topic_helper;
 use crate::convert::try_from_one;
 use crate::github::RemoteRepo;
+use crate::repo_state::RepoState;
 use crate::user::User;
 use anyhow::{anyhow, Error, Result};
 use colored::*;
@@ -19,7 +22,7 @@ use rayon::prelude::*;
 ///
 /// If regex is provided, this will fillter by repo name on the provided regex.
 /// If topic is provided, this will fillter if a repo contains that provided topic.
-/// The new branch will be based from a another branch (default is main).
+/// The new branch will be based on another branch (default is main), a tag or a commit.
 /// If a matched repository is not present in root dir yet, it will be cloned.
 pub struct CreateBranchArgs {
     #[arg(long, short)]
@@ -36,8 +39,11 @@ pub struct CreateBranchArgs {
     #[arg(long, short)]
     /// New branch name
     pub new_branch: String,
-    #[arg(long, short, default_value = "main")]
-    /// The base branch which new branch will based of
+    #[arg(long, short, default_value = "main", visible_alias = "base")]
+    /// The branch, tag or commit which the new branch will be based on
+    ///
+    /// Tags and commits that are not in the local repository are fetched from origin, e.g. to
+    /// cut maintenance branches from the last release tag.
     pub base_branch: String,
     #[arg(long, short)]
     /// Use https to clone repositories if needed
@@ -67,17 +73,20 @@ impl CreateBranchArgs {
             return Ok(());
         }
 
+        progress::begin(filtered_repos.len());
         let statuses: Vec<_> = filtered_repos
             .par_iter()
             .map(|r| {
-                create_branch(
-                    r,
-                    &self.new_branch,
-                    &self.base_branch,
-                    &user,
-                    self.use_https,
-                    self.push,
-                )
+                progress::track(&r.full_name(), || {
+                    create_branch(
+                        r,
+                        &self.new_branch,
+                        &self.base_branch,
+                        &user,
+                        self.use_https,
+                        self.push,
+                    )
+                })
             })
             .collect();
 
@@ -89,7 +98,7 @@ impl CreateBranchArgs {
 /// We need to do following steps
 /// 1. Check if the repository is already exist
 /// 2. if it is not exist we need to clone it
-/// 3. Check out the base branch
+/// 3. Find the base branch, tag or commit, fetch it if needed
 /// 4. Create new_branch
 /// 5. Push it to origin if needed
 fn create_branch(
@@ -124,7 +133,16 @@ fn create_branch(
             }
         };
 
-        branch::create_branch(&cloned_repo, new_branch, base_branch)?;
+        branch::create_branch(
+            &cloned_repo,
+            new_branch,
+            base_branch,
+            "origin",
+            git_repo.cred.clone(),
+        )?;
+        RepoState::record(&git_repo.local_path, |s| {
+            s.campaign_branch = Some(new_branch.to_string())
+        });
 
         push_status = if push {
             match push::push_branch(&cloned_repo, new_branch, "origin", git_repo.cred) {
@@ -176,13 +194,8 @@ fn summarize(statuses: &[Status], branch: &str) {
     } else {
         let msg = format!("There {} errors when process command:", errors.len());
         println!("\n{}\n", msg.red());
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
 
@@ -209,7 +222,7 @@ impl Status {
         ])
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let Err(e1) = &self.result {
             e1
         } else if let PushStatus::Failed(e2) = &self.push {
@@ -217,16 +230,13 @@ impl Status {
         } else {
             panic!("This should have an error here");
         };
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo.name), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo.name, e)
     }
 
     fn result_to_cell(&self) -> Cell {
-        match self.result {
+        match &self.result {
             Ok(_) => cell!(Fg -> "Success"),
-            Err(_) => cell!(Fr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
@@ -235,6 +245,12 @@ impl Status {
     }
 }
 
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
 enum PushStatus {
     Success,
     No,
@@ -246,7 +262,7 @@ impl PushStatus {
         match &self {
             PushStatus::Success => cell!(Fgr -> "Success"),
             PushStatus::No => cell!(r -> "-"),
-            PushStatus::Failed(_) => cell!(Frr -> "Failed"),
+            PushStatus::Failed(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
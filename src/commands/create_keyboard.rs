@@ -0,0 +1,166 @@
+use super::common;
+use super::template::generate::generate;
+use crate::cli::Args as CommonArgs;
+use crate::git::{self, GitCredential};
+use crate::github::{self, RemoteRepo};
+use crate::path;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+/// Pattern of the templates for the language code
+static LANGUAGE_PATTERN: &str = "__UND__";
+
+#[derive(Debug, Parser)]
+/// Create a new keyboard repository for a language from the keyboard template
+///
+/// This generates keyboard-<language> from the template, creates the repository on GitHub,
+/// pushes it, sets the keyboard and langfam-<family> topics, gives the teams access and protects
+/// the default branch.
+pub struct CreateKeyboardArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    /// Language code of the keyboard, e.g. sme
+    pub language: String,
+    #[arg(long, short)]
+    /// Language family, for the langfam-<family> topic, e.g. uralic
+    pub family: String,
+    #[arg(long, short, default_value = "template-keyboard")]
+    /// Directory of the template, or the name of a local repository of the organisation
+    pub template: String,
+    #[arg(long = "replace", value_parser = parse_replacement)]
+    /// Value of another pattern of the template as PATTERN=VALUE, missing ones are asked for
+    pub replacements: Vec<(String, String)>,
+    #[arg(long = "team", value_parser = parse_team)]
+    /// Team that gets access as TEAM=PERMISSION, or TEAM for push access
+    pub teams: Vec<(String, String)>,
+    #[arg(long)]
+    /// More topics besides keyboard and langfam-<family>
+    pub topic: Vec<String>,
+    #[arg(long, short)]
+    /// Create a public repository
+    pub public: bool,
+    #[arg(long)]
+    /// Do not protect the default branch
+    pub no_protect: bool,
+    #[arg(long, short)]
+    /// Option to use https instead of ssh for the remote
+    pub use_https: bool,
+}
+
+impl CreateKeyboardArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let use_https = self.use_https || common::use_https()?;
+
+        let name = format!("keyboard-{}", self.language);
+        let target_dir = path::local_path_repo(&organisation, &name, &root);
+        if target_dir.exists() {
+            bail!("{} already exists", target_dir.display());
+        }
+        let template_dir = self.template_dir(&organisation, &root)?;
+
+        let mut known: BTreeMap<_, _> = self.replacements.iter().cloned().collect();
+        known.insert(LANGUAGE_PATTERN.to_string(), self.language.clone());
+        create_dir_all(&target_dir).context("Cannot create target directory")?;
+        if let Err(e) = generate(&template_dir, &target_dir, false, &known) {
+            path::remove_path(&target_dir).ok();
+            return Err(e).with_context(|| format!("Cannot generate {} from the template", name));
+        }
+        done(&format!("Generated {} at {}", name, target_dir.display()));
+
+        let created = github::create_org_repo(&organisation, &name, self.public, &user.token)
+            .with_context(|| format!("Cannot create {}/{} on GitHub", organisation, name))?;
+        done(&format!("Created {}", created.html_url));
+        let repo = RemoteRepo {
+            name: name.clone(),
+            owner: organisation.clone(),
+            ssh_url: created.ssh_url,
+            https_url: created.clone_url,
+        };
+
+        let git_repo = git::open(&target_dir)?;
+        let remote_url = if use_https {
+            &repo.https_url
+        } else {
+            &repo.ssh_url
+        };
+        git_repo.remote("origin", remote_url)?;
+        let branch = git::head_shorthand(&git_repo)?;
+        git::push::push_branch(
+            &git_repo,
+            &branch,
+            "origin",
+            Some(GitCredential::from(&user)),
+        )
+        .with_context(|| format!("Cannot push {}", branch))?;
+        done(&format!("Pushed {}", branch));
+
+        let mut topics = vec!["keyboard".to_string(), format!("langfam-{}", self.family)];
+        topics.extend(self.topic.iter().cloned());
+        let topics =
+            github::set_topics(&repo, &topics, &user.token).context("Cannot set the topics")?;
+        done(&format!("Set topics {}", topics.join(", ")));
+
+        for (team, permission) in &self.teams {
+            github::add_repo_to_team(&repo, team, permission, &user.token)
+                .with_context(|| format!("Cannot give team {} access", team))?;
+            done(&format!("Gave team {} {} access", team, permission));
+        }
+
+        if !self.no_protect {
+            github::set_protected_branch(&repo, &branch, &user.token)
+                .with_context(|| format!("Cannot protect {}", branch))?;
+            done(&format!("Protected {}", branch));
+        }
+
+        println!("\n{}", format!("Keyboard {} is ready!", name).green());
+        Ok(())
+    }
+
+    fn template_dir(&self, organisation: &str, root: &str) -> Result<PathBuf> {
+        let dir = PathBuf::from(&self.template);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        let dir = path::local_path_repo(organisation, &self.template, root);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        bail!(
+            "Cannot find the template {}, clone it or give the directory of the template",
+            self.template
+        )
+    }
+}
+
+fn done(step: &str) {
+    println!("{} {}", "Done:".green(), step);
+}
+
+fn parse_replacement(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((pattern, value)) if !pattern.is_empty() => {
+            Ok((pattern.to_string(), value.to_string()))
+        }
+        _ => Err(format!("{:?} is not of the form PATTERN=VALUE", s)),
+    }
+}
+
+fn parse_team(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((team, permission)) if !team.is_empty() && !permission.is_empty() => {
+            Ok((team.to_string(), permission.to_string()))
+        }
+        Some(_) => Err(format!("{:?} is not of the form TEAM=PERMISSION", s)),
+        None => Ok((s.to_string(), "push".to_string())),
+    }
+}
@@ -0,0 +1,31 @@
+use super::deps_graph::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Work with the dependencies between repositories
+pub struct DepsArgs {
+    #[command(subcommand)]
+    command: DepsCommand,
+}
+
+impl DepsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum DepsCommand {
+    #[command(name = "graph")]
+    Graph(DepsGraphArgs),
+}
+
+impl DepsCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Graph(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::path::{dir_name, owner_name};
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+pub static BUILD_CONFIG: &str = ".gut/build.toml";
+
+/// Repositories and the repositories they depend on
+pub type Graph = BTreeMap<String, BTreeSet<String>>;
+
+#[derive(Debug, Parser)]
+/// Show the dependencies between local repositories in build order, and find cycles
+///
+/// A repository declares the repositories it needs in .gut/build.toml, e.g.
+/// `depends = ["lang-sme", "divvun/divvun-gramcheck"]`. Names without an owner are
+/// repositories of the same owner. The repositories are listed in levels: every repository
+/// depends only on repositories of earlier levels, so the repositories of a level can be built
+/// in parallel. The command fails when the dependencies have cycles.
+pub struct DepsGraphArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, value_name = "REPO")]
+    /// Only show the repositories that depend on this one, directly or indirectly
+    ///
+    /// This is what has to be rebuilt after a change of the repository.
+    pub impact: Option<String>,
+    #[arg(long)]
+    /// Print the graph in the DOT language of Graphviz
+    pub dot: bool,
+}
+
+impl DepsGraphArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let (mut graph, reports) = read_graph(&sub_dirs, &organisation);
+
+        if let Some(repo) = &self.impact {
+            let repo = full_name(repo, &organisation);
+            let mut keep = dependents(&graph, &repo);
+            keep.insert(repo);
+            graph.retain(|r, _| keep.contains(r));
+            for depends in graph.values_mut() {
+                depends.retain(|d| keep.contains(d));
+            }
+        }
+
+        let (levels, blocked) = levels(&graph);
+        let cycles = cycles(&graph);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!(
+                "{}",
+                json!({
+                    "dependencies": graph,
+                    "levels": levels,
+                    "cycles": cycles,
+                })
+            );
+        } else if self.dot {
+            print!("{}", to_dot(&graph, &cycles));
+        } else {
+            print_levels(&graph, &levels, &blocked);
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when reading {}:",
+                reports.len(),
+                BUILD_CONFIG
+            );
+            eprintln!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        if !cycles.is_empty() {
+            for cycle in &cycles {
+                eprintln!("{} {}", "Cycle between".red(), cycle.join(", "));
+            }
+            bail!("There are {} dependency cycles", cycles.len());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BuildConfig {
+    /// Repositories that have to be built before this one
+    depends: Vec<String>,
+}
+
+/// The dependencies that the local repositories declare, and the repositories whose
+/// declarations cannot be read
+pub fn read_graph(dirs: &[PathBuf], organisation: &str) -> (Graph, Vec<ErrorReport>) {
+    let results: Vec<_> = dirs
+        .par_iter()
+        .map(|d| declared_dependencies(d, organisation))
+        .collect();
+
+    let mut graph = Graph::new();
+    let mut reports = vec![];
+    for (repo, result) in results {
+        match result {
+            Ok(depends) => {
+                graph.insert(repo, depends);
+            }
+            Err(e) => reports.push(ErrorReport::from_message(&repo, &format!("{:?}", e))),
+        }
+    }
+    (complete(graph), reports)
+}
+
+/// `owner/name` of a local repository
+pub fn local_full_name(dir: &PathBuf, organisation: &str) -> String {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+    format!("{}/{}", owner, name)
+}
+
+fn declared_dependencies(dir: &PathBuf, organisation: &str) -> (String, Result<BTreeSet<String>>) {
+    let repo = local_full_name(dir, organisation);
+    let owner = repo.split('/').next().unwrap_or(organisation).to_string();
+
+    let path = dir.join(BUILD_CONFIG);
+    if !path.exists() {
+        return (repo, Ok(BTreeSet::new()));
+    }
+    let result = crate::toml::read_file(&path).map(|config: BuildConfig| {
+        config
+            .depends
+            .iter()
+            .map(|d| full_name(d, &owner))
+            .collect()
+    });
+    (repo, result)
+}
+
+/// `owner/name`, with the owner of the depending repository when it is left out
+pub fn full_name(name: &str, owner: &str) -> String {
+    if name.contains('/') {
+        name.to_string()
+    } else {
+        format!("{}/{}", owner, name)
+    }
+}
+
+/// The graph with a node for every dependency, also the ones that are not cloned
+fn complete(mut graph: Graph) -> Graph {
+    let missing: Vec<_> = graph
+        .values()
+        .flatten()
+        .filter(|d| !graph.contains_key(*d))
+        .cloned()
+        .collect();
+    for repo in missing {
+        graph.entry(repo).or_default();
+    }
+    graph
+}
+
+/// The repositories in build order, grouped in levels that only depend on earlier levels, and
+/// the repositories that cannot be ordered because they are in or behind a cycle
+pub fn levels(graph: &Graph) -> (Vec<Vec<String>>, Vec<String>) {
+    let mut placed: BTreeSet<&String> = BTreeSet::new();
+    let mut levels = vec![];
+    loop {
+        let level: Vec<_> = graph
+            .iter()
+            .filter(|(repo, depends)| {
+                !placed.contains(repo) && depends.iter().all(|d| placed.contains(d))
+            })
+            .map(|(repo, _)| repo)
+            .collect();
+        if level.is_empty() {
+            break;
+        }
+        placed.extend(level.iter().copied());
+        levels.push(level.into_iter().cloned().collect());
+    }
+    let blocked = graph
+        .keys()
+        .filter(|repo| !placed.contains(repo))
+        .cloned()
+        .collect();
+    (levels, blocked)
+}
+
+/// The cycles of the graph, as the repositories of every strongly connected component with
+/// more than one repository or a repository that depends on itself
+fn cycles(graph: &Graph) -> Vec<Vec<String>> {
+    struct Tarjan<'a> {
+        graph: &'a Graph,
+        index: BTreeMap<&'a str, usize>,
+        low: BTreeMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        components: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, repo: &'a str) {
+            let graph = self.graph;
+            let index = self.index.len();
+            self.index.insert(repo, index);
+            self.low.insert(repo, index);
+            self.stack.push(repo);
+            self.on_stack.insert(repo);
+
+            for dependency in graph.get(repo).into_iter().flatten() {
+                let dependency = dependency.as_str();
+                if !self.index.contains_key(dependency) {
+                    self.visit(dependency);
+                    let low = self.low[repo].min(self.low[dependency]);
+                    self.low.insert(repo, low);
+                } else if self.on_stack.contains(dependency) {
+                    let low = self.low[repo].min(self.index[dependency]);
+                    self.low.insert(repo, low);
+                }
+            }
+
+            if self.low[repo] == self.index[repo] {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == repo {
+                        break;
+                    }
+                }
+                let depends_on_itself = graph[repo].contains(repo);
+                if component.len() > 1 || depends_on_itself {
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        index: BTreeMap::new(),
+        low: BTreeMap::new(),
+        stack: vec![],
+        on_stack: BTreeSet::new(),
+        components: vec![],
+    };
+    for repo in graph.keys() {
+        if !tarjan.index.contains_key(repo.as_str()) {
+            tarjan.visit(repo);
+        }
+    }
+    tarjan.components
+}
+
+/// The repositories that depend on a repository, directly or indirectly
+pub fn dependents(graph: &Graph, repo: &str) -> BTreeSet<String> {
+    let mut found: BTreeSet<String> = BTreeSet::new();
+    let mut todo = vec![repo.to_string()];
+    while let Some(current) = todo.pop() {
+        for (dependent, depends) in graph {
+            if depends.contains(&current) && found.insert(dependent.clone()) {
+                todo.push(dependent.clone());
+            }
+        }
+    }
+    found.remove(repo);
+    found
+}
+
+fn to_dot(graph: &Graph, cycles: &[Vec<String>]) -> String {
+    let in_cycle: BTreeSet<&String> = cycles.iter().flatten().collect();
+    let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n");
+    for (repo, depends) in graph {
+        let style = if in_cycle.contains(repo) {
+            " [color=red]"
+        } else {
+            ""
+        };
+        dot.push_str(&format!("    \"{}\"{};\n", repo, style));
+        for dependency in depends {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", repo, dependency));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn print_levels(graph: &Graph, levels: &[Vec<String>], blocked: &[String]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Level", "Repo", "Depends on"]);
+    for (level, repos) in levels.iter().enumerate() {
+        for repo in repos {
+            let depends: Vec<_> = graph[repo].iter().map(String::as_str).collect();
+            table.add_row(row![r -> level + 1, b -> repo, depends.join(", ")]);
+        }
+    }
+    for repo in blocked {
+        let depends: Vec<_> = graph[repo].iter().map(String::as_str).collect();
+        table.add_row(row![r -> "-", b -> repo, depends.join(", ")]);
+    }
+    table.printstd();
+    if !blocked.is_empty() {
+        let msg = format!(
+            "{} repositories cannot be ordered because of cycles",
+            blocked.len()
+        );
+        println!("\n{}", msg.red());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> Graph {
+        complete(
+            edges
+                .iter()
+                .map(|(repo, depends)| {
+                    let depends = depends.iter().map(|d| d.to_string()).collect();
+                    (repo.to_string(), depends)
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_levels() {
+        let graph = graph(&[
+            ("keyboard-sme", &["lang-sme"]),
+            ("lang-sme", &["giella-core"]),
+            ("lang-sma", &["giella-core"]),
+        ]);
+        let (levels, blocked) = levels(&graph);
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0], ["giella-core"]);
+        assert_eq!(levels[1], ["lang-sma", "lang-sme"]);
+        assert_eq!(levels[2], ["keyboard-sme"]);
+        assert!(blocked.is_empty());
+        assert!(cycles(&graph).is_empty());
+
+        let impact: Vec<_> = dependents(&graph, "lang-sme").into_iter().collect();
+        assert_eq!(impact, ["keyboard-sme"]);
+        assert_eq!(dependents(&graph, "giella-core").len(), 3);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = graph(&[
+            ("a", &["b"]),
+            ("b", &["c"]),
+            ("c", &["a"]),
+            ("d", &["d"]),
+            ("e", &["a"]),
+            ("f", &[]),
+        ]);
+        let cycles = cycles(&graph);
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0], ["a", "b", "c"]);
+        assert_eq!(cycles[1], ["d"]);
+        let (levels, blocked) = levels(&graph);
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0], ["f"]);
+        assert_eq!(blocked, ["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_full_name() {
+        assert_eq!(full_name("lang-sme", "giellalt"), "giellalt/lang-sme");
+        assert_eq!(
+            full_name("divvun/divvunspell", "giellalt"),
+            "divvun/divvunspell"
+        );
+    }
+}
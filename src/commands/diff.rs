@@ -0,0 +1,203 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::diff::DiffStat;
+use crate::path;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show how local repositories differ between two refs, e.g. origin/main..main or v1.0..v1.1
+///
+/// By default this is a table with the number of changed files and lines of each repository,
+/// --patch shows the full diff of a single repository.
+pub struct DiffArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    /// The refs to compare as <from>..<to>, a single ref is compared with HEAD
+    pub range: String,
+    #[arg(long)]
+    /// Show the number of changed files and lines per repository, which is the default
+    pub stat: bool,
+    #[arg(long, conflicts_with = "stat")]
+    /// Show the full diff, the regex has to match exactly one repository
+    pub patch: bool,
+    #[arg(long = "path")]
+    /// Only compare the files that match this glob, e.g. src/fst/**, can be repeated
+    pub paths: Vec<String>,
+}
+
+impl DiffArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let (from, to) = parse_range(&self.range)?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        if self.patch {
+            if sub_dirs.len() > 1 {
+                bail!(
+                    "--patch shows the diff of one repository, but {} repositories match pattern {:?}",
+                    sub_dirs.len(),
+                    self.regex
+                );
+            }
+            let git_repo = git::open(&sub_dirs[0])
+                .with_context(|| format!("{:?} is not a git directory.", sub_dirs[0]))?;
+            let diff = git::diff::diff_revs(&git_repo, from, to, &self.paths)?;
+            git::diff::print_patch(&diff)?;
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| stat(d, from, to, &self.paths))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, &self.range);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_range(range: &str) -> Result<(&str, &str)> {
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) => (from, if to.is_empty() { "HEAD" } else { to }),
+        None => (range, "HEAD"),
+    };
+    if from.is_empty() || to.starts_with('.') {
+        bail!("{:?} is not a range of the form <from>..<to>", range);
+    }
+    Ok((from, to))
+}
+
+fn stat(dir: &PathBuf, from: &str, to: &str, paths: &[String]) -> Status {
+    let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let stat = || -> Result<DiffStat> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let diff = git::diff::diff_revs(&git_repo, from, to, paths)?;
+        Ok(git::diff::diff_stat(&diff)?)
+    };
+
+    Status {
+        repo,
+        result: stat().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<DiffStat, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(stat) if stat.files_changed == 0 => {
+                row![b -> &self.repo, r -> "-", r -> "-", r -> "-"]
+            }
+            Ok(stat) => row![
+                b -> &self.repo,
+                r -> stat.files_changed,
+                Fgr -> format!("+{}", stat.insertions),
+                Frr -> format!("-{}", stat.deletions)
+            ],
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn has_changes(&self) -> bool {
+        matches!(&self.result, Ok(stat) if stat.files_changed > 0)
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status], range: &str) {
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Files changed", "Insertions", "Deletions"]);
+    table.printstd();
+
+    let changed = statuses.iter().filter(|s| s.has_changes()).count();
+    println!(
+        "\n{} of {} repositories differ in {}",
+        changed,
+        statuses.len(),
+        range
+    );
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when comparing {}:", errors.len(), range);
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("origin/main..main").unwrap(),
+            ("origin/main", "main")
+        );
+        assert_eq!(parse_range("v1.0..").unwrap(), ("v1.0", "HEAD"));
+        assert_eq!(parse_range("v1.0").unwrap(), ("v1.0", "HEAD"));
+        assert!(parse_range("..main").is_err());
+        assert!(parse_range("v1.0...v1.1").is_err());
+    }
+}
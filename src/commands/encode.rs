@@ -0,0 +1,31 @@
+use super::encode_check::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Check the encoding of the files of local repositories
+pub struct EncodeArgs {
+    #[command(subcommand)]
+    command: EncodeCommand,
+}
+
+impl EncodeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum EncodeCommand {
+    #[command(name = "check")]
+    Check(EncodeCheckArgs),
+}
+
+impl EncodeCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Check(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::encoding::{self, EncodingProblem};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::dir_name;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Find text files that are not UTF-8, start with a BOM or have the wrong line endings
+///
+/// All files that git tracks are checked, except the ones that look binary or that
+/// .gitattributes marks as binary. CRLF line endings are only reported for files where
+/// .gitattributes asks for `eol=lf`.
+pub struct EncodeCheckArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Fail when a file has a problem, to use it in CI
+    pub check: bool,
+}
+
+impl EncodeCheckArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs.par_iter().map(check).collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
+        }
+
+        let files: usize = statuses.iter().map(|s| s.files().len()).sum();
+        if self.check && files > 0 {
+            bail!("{} files have encoding problems", files);
+        }
+
+        Ok(())
+    }
+}
+
+fn check(dir: &PathBuf) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let check = || -> Result<Vec<FileProblems>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let mut files = vec![];
+        for file in git::tracked_files(&git_repo)? {
+            if encoding::is_marked_binary(&git_repo, &file) {
+                continue;
+            }
+            let bytes = match std::fs::read(dir.join(&file)) {
+                Ok(bytes) => bytes,
+                // Deleted in the working directory
+                Err(_) => continue,
+            };
+            if encoding::is_binary(&bytes) {
+                continue;
+            }
+            let problems = encoding::check(&bytes, encoding::wants_lf(&git_repo, &file));
+            if !problems.is_empty() {
+                files.push(FileProblems { file, problems });
+            }
+        }
+        Ok(files)
+    };
+
+    Status {
+        repo,
+        result: check().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileProblems {
+    file: String,
+    problems: Vec<EncodingProblem>,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<FileProblems>, String>,
+}
+
+impl Status {
+    fn files(&self) -> &[FileProblems] {
+        match &self.result {
+            Ok(files) => files,
+            Err(_) => &[],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "File", "Problem"]);
+    for status in statuses {
+        let mut first = true;
+        for file in status.files() {
+            for problem in &file.problems {
+                table.add_row(row![
+                    b -> if first { status.repo.as_str() } else { "" },
+                    file.file,
+                    Fr -> problem
+                ]);
+                first = false;
+            }
+        }
+    }
+
+    let files: usize = statuses.iter().map(|s| s.files().len()).sum();
+    if files == 0 {
+        println!("\nAll text files are UTF-8 with the expected line endings");
+    } else {
+        table.printstd();
+        let repos = statuses.iter().filter(|s| !s.files().is_empty()).count();
+        let msg = format!("\n{} files in {} repositories have problems", files, repos);
+        println!("{}", msg.red());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when checking:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
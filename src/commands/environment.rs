@@ -0,0 +1,39 @@
+use super::environment_create::*;
+use super::environment_list::*;
+use super::environment_set_reviewers::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Create, list and configure deployment environments of repositories
+pub struct EnvironmentArgs {
+    #[command(subcommand)]
+    command: EnvironmentCommand,
+}
+
+impl EnvironmentArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum EnvironmentCommand {
+    #[command(name = "create")]
+    Create(EnvironmentCreateArgs),
+    #[command(name = "list")]
+    List(EnvironmentListArgs),
+    #[command(name = "set-reviewers")]
+    SetReviewers(EnvironmentSetReviewersArgs),
+}
+
+impl EnvironmentCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Create(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+            Self::SetReviewers(args) => args.run(common_args),
+        }
+    }
+}
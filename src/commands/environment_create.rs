@@ -0,0 +1,115 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use crate::github::EnvironmentReviewer;
+use anyhow::{Context, Result};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Create or update an environment for all repositories that match a regex
+///
+/// Existing environments with the same name keep their protection rules unless
+/// new ones are provided.
+pub struct EnvironmentCreateArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Name of the environment, e.g. production
+    pub name: String,
+    #[arg(long, short)]
+    /// Minutes to wait before a deployment to this environment can proceed (0 - 43200)
+    pub wait_timer: Option<u32>,
+    #[arg(long, short)]
+    /// Usernames of the required reviewers
+    pub users: Vec<String>,
+    #[arg(long, short)]
+    /// Slugs of the teams in the organisation that are required reviewers
+    pub teams: Vec<String>,
+}
+
+impl EnvironmentCreateArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let reviewers = if self.users.is_empty() && self.teams.is_empty() {
+            None
+        } else {
+            Some(get_reviewers(
+                &organisation,
+                &self.users,
+                &self.teams,
+                &user_token,
+            )?)
+        };
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        for repo in filtered_repos {
+            let result = github::set_environment(
+                &repo,
+                &self.name,
+                self.wait_timer,
+                reviewers.as_deref(),
+                &user_token,
+            );
+            match result {
+                Ok(_) => println!(
+                    "Created environment {} for repo {} successfully",
+                    self.name, repo.name
+                ),
+                Err(e) => println!(
+                    "Failed to create environment {} for repo {} because {:?}",
+                    self.name, repo.name, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolve usernames and team slugs to the ids that the environment api requires
+pub fn get_reviewers(
+    org: &str,
+    users: &[String],
+    teams: &[String],
+    token: &str,
+) -> Result<Vec<EnvironmentReviewer>> {
+    let mut reviewers = vec![];
+
+    for user in users {
+        let id = github::get_user_id(user, token)
+            .with_context(|| format!("Cannot find user {}", user))?;
+        reviewers.push(EnvironmentReviewer {
+            reviewer_type: "User".to_string(),
+            id,
+        });
+    }
+
+    for team in teams {
+        let id = github::get_team_id(org, team, token)
+            .with_context(|| format!("Cannot find team {} in organisation {}", team, org))?;
+        reviewers.push(EnvironmentReviewer {
+            reviewer_type: "Team".to_string(),
+            id,
+        });
+    }
+
+    Ok(reviewers)
+}
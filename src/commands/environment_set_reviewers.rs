@@ -0,0 +1,68 @@
+use super::common;
+use super::environment_create::get_reviewers;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Set the required reviewers of an environment for all repositories that match a regex
+///
+/// The environment is created if it does not exist. Providing no users and teams removes
+/// all required reviewers.
+pub struct EnvironmentSetReviewersArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Name of the environment, e.g. production
+    pub name: String,
+    #[arg(long, short)]
+    /// Usernames of the required reviewers
+    pub users: Vec<String>,
+    #[arg(long, short)]
+    /// Slugs of the teams in the organisation that are required reviewers
+    pub teams: Vec<String>,
+}
+
+impl EnvironmentSetReviewersArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let reviewers = get_reviewers(&organisation, &self.users, &self.teams, &user_token)?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        for repo in filtered_repos {
+            let result =
+                github::set_environment(&repo, &self.name, None, Some(&reviewers), &user_token);
+            match result {
+                Ok(_) => println!(
+                    "Set reviewers of environment {} for repo {} successfully",
+                    self.name, repo.name
+                ),
+                Err(e) => println!(
+                    "Failed to set reviewers of environment {} for repo {} because {:?}",
+                    self.name, repo.name, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+use crate::github::{MissingScope, SsoRequired, Unauthorized, Unsuccessful};
+use anyhow::Error;
+use colored::*;
+use git2::{ErrorClass, ErrorCode};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+
+/// Broad classes of the errors that commands report per repository, so that the final
+/// report can group them and suggest how to fix each group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    Auth,
+    Network,
+    NotFound,
+    Conflict,
+    DirtyWorktree,
+    Disk,
+    Other,
+}
+
+impl ErrorCategory {
+    pub fn of(error: &Error) -> ErrorCategory {
+        error
+            .chain()
+            .find_map(|cause| {
+                if cause.downcast_ref::<Unauthorized>().is_some()
+                    || cause.downcast_ref::<SsoRequired>().is_some()
+                    || cause.downcast_ref::<MissingScope>().is_some()
+                {
+                    return Some(ErrorCategory::Auth);
+                }
+                if let Some(Unsuccessful(status)) = cause.downcast_ref::<Unsuccessful>() {
+                    return Some(Self::from_status(*status));
+                }
+                if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                    return Some(match e.status() {
+                        Some(status) => Self::from_status(status),
+                        None => ErrorCategory::Network,
+                    });
+                }
+                if let Some(e) = cause.downcast_ref::<git2::Error>() {
+                    return Self::from_git(e);
+                }
+                if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                    return Some(match e.kind() {
+                        ErrorKind::NotFound => ErrorCategory::NotFound,
+                        ErrorKind::TimedOut | ErrorKind::ConnectionRefused => {
+                            ErrorCategory::Network
+                        }
+                        _ => ErrorCategory::Disk,
+                    });
+                }
+                None
+            })
+            .unwrap_or_else(|| Self::from_message(&format!("{:?}", error)))
+    }
+
+    /// Best effort classification for errors that only survived as text
+    pub fn from_message(message: &str) -> ErrorCategory {
+        let message = message.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+
+        if has(&[
+            "unauthorized",
+            "authentication",
+            "credential",
+            "permission to",
+        ]) {
+            ErrorCategory::Auth
+        } else if has(&[
+            "timed out",
+            "timeout",
+            "network",
+            "connection",
+            "resolve host",
+        ]) {
+            ErrorCategory::Network
+        } else if has(&["conflict"]) {
+            ErrorCategory::Conflict
+        } else if has(&["uncommitted", "unstaged", "dirty", "local changes"]) {
+            ErrorCategory::DirtyWorktree
+        } else if has(&[
+            "no space",
+            "read-only file system",
+            "permission denied",
+            "disk",
+        ]) {
+            ErrorCategory::Disk
+        } else if has(&["not found", "404", "does not exist", "is not exist"]) {
+            ErrorCategory::NotFound
+        } else {
+            ErrorCategory::Other
+        }
+    }
+
+    fn from_status(status: StatusCode) -> ErrorCategory {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCategory::Auth,
+            StatusCode::NOT_FOUND => ErrorCategory::NotFound,
+            StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY => ErrorCategory::Conflict,
+            s if s.is_server_error() => ErrorCategory::Network,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    fn from_git(error: &git2::Error) -> Option<ErrorCategory> {
+        // The repository exists locally, so a 404 from the remote is almost always a token
+        // that is not authorized for the organisation (SSO) or lacks the repo scope
+        if error.class() == ErrorClass::Http && error.message().contains("status code: 404") {
+            return Some(ErrorCategory::Auth);
+        }
+        match error.code() {
+            ErrorCode::Auth | ErrorCode::Certificate => return Some(ErrorCategory::Auth),
+            ErrorCode::NotFound => return Some(ErrorCategory::NotFound),
+            ErrorCode::Conflict | ErrorCode::MergeConflict | ErrorCode::Unmerged => {
+                return Some(ErrorCategory::Conflict)
+            }
+            ErrorCode::Uncommitted | ErrorCode::Modified | ErrorCode::IndexDirty => {
+                return Some(ErrorCategory::DirtyWorktree)
+            }
+            _ => {}
+        }
+        match error.class() {
+            ErrorClass::Net | ErrorClass::Ssh | ErrorClass::Http | ErrorClass::Ssl => {
+                Some(ErrorCategory::Network)
+            }
+            ErrorClass::Os | ErrorClass::Filesystem => Some(ErrorCategory::Disk),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCategory::Auth => "auth",
+            ErrorCategory::Network => "network",
+            ErrorCategory::NotFound => "not-found",
+            ErrorCategory::Conflict => "conflict",
+            ErrorCategory::DirtyWorktree => "dirty-worktree",
+            ErrorCategory::Disk => "disk",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            ErrorCategory::Auth => "Check that your token is valid, has the repo scope and is authorized for the SSO of the organisations named in the errors at https://github.com/settings/tokens (then run gut init)",
+            ErrorCategory::Network => "Check your connection or proxy settings and run the command again for these repositories",
+            ErrorCategory::NotFound => "Check the repository, branch or file names, or clone the missing repositories first",
+            ErrorCategory::Conflict => "Resolve the conflicts in these repositories, then run the command again",
+            ErrorCategory::DirtyWorktree => "Commit or stash the local changes first (e.g. gut pull --stash)",
+            ErrorCategory::Disk => "Check free disk space and the permissions of the root directory",
+            ErrorCategory::Other => "See the error messages below",
+        }
+    }
+
+    /// Status cell for a failed repository in summary tables
+    pub fn failed_cell(&self) -> Cell {
+        cell!(Frr -> format!("Failed ({})", self.name()))
+    }
+}
+
+/// One failed repository in the final error report of a command
+pub struct ErrorReport {
+    pub repo: String,
+    pub category: ErrorCategory,
+    pub message: String,
+}
+
+impl ErrorReport {
+    pub fn new(repo: &str, error: &Error) -> ErrorReport {
+        ErrorReport {
+            repo: repo.to_string(),
+            category: ErrorCategory::of(error),
+            message: format!("{:?}", error),
+        }
+    }
+
+    pub fn from_message(repo: &str, message: &str) -> ErrorReport {
+        ErrorReport {
+            repo: repo.to_string(),
+            category: ErrorCategory::from_message(message),
+            message: message.to_string(),
+        }
+    }
+
+    fn to_row(&self) -> Row {
+        let lines = super::common::sub_strings(self.message.as_str(), 80);
+        let lines = lines.join("\n");
+        row!(cell!(b -> &self.repo), cell!(Fr -> lines.as_str()))
+    }
+}
+
+/// Print the errors grouped by category with a count and a suggested fix for every category
+pub fn print_error_report(reports: &[ErrorReport]) {
+    let mut groups: BTreeMap<ErrorCategory, Vec<&ErrorReport>> = BTreeMap::new();
+    for report in reports {
+        groups.entry(report.category).or_default().push(report);
+    }
+
+    for (category, reports) in groups {
+        let msg = format!(
+            "{} ({}): {}",
+            category.name(),
+            reports.len(),
+            category.suggestion()
+        );
+        println!("{}\n", msg.yellow());
+
+        let mut error_table = Table::new();
+        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        error_table.set_titles(row!["Repo", "Error"]);
+        for report in reports {
+            error_table.add_row(report.to_row());
+        }
+        error_table.printstd();
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_category_of_typed_errors() {
+        let e: Error = Unauthorized.into();
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::Auth);
+
+        let e: Error = Unsuccessful(StatusCode::NOT_FOUND).into();
+        assert_eq!(
+            ErrorCategory::of(&e.context("Cannot get topics")),
+            ErrorCategory::NotFound
+        );
+
+        let e: Error = git2::Error::new(ErrorCode::Uncommitted, ErrorClass::None, "").into();
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::DirtyWorktree);
+
+        let e: Error = git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Http,
+            "unexpected http status code: 404",
+        )
+        .into();
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::Auth);
+
+        let e = git::common::explain_not_found(
+            Some("https://github.com/giellalt/lang-sme.git"),
+            git2::Error::new(
+                ErrorCode::GenericError,
+                ErrorClass::Http,
+                "unexpected http status code: 404",
+            ),
+        );
+        assert!(e
+            .message()
+            .contains("authorize your token for SSO in giellalt"));
+        assert_eq!(ErrorCategory::of(&e.into()), ErrorCategory::Auth);
+
+        let e: Error = SsoRequired {
+            org: "divvungiellatekno".to_string(),
+            url: None,
+        }
+        .into();
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::Auth);
+
+        let e: Error = std::io::Error::new(ErrorKind::PermissionDenied, "denied").into();
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::Disk);
+    }
+
+    #[test]
+    fn test_category_from_message() {
+        let e = anyhow!("Failed to connect: Connection refused");
+        assert_eq!(ErrorCategory::of(&e), ErrorCategory::Network);
+        assert_eq!(
+            ErrorCategory::from_message("There is a merge conflict"),
+            ErrorCategory::Conflict
+        );
+        assert_eq!(ErrorCategory::from_message("boom"), ErrorCategory::Other);
+    }
+}
@@ -1,12 +1,17 @@
-use crate::cli::Args as CommonArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
 use crate::filter::Filter;
 use crate::git;
-use crate::git::GitCredential;
+use crate::git::{GitCredential, RefUpdate};
 use crate::path;
 use crate::user::User;
 use anyhow::{Context, Result};
 use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use serde::Serialize;
+use serde_json::json;
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -22,32 +27,130 @@ pub struct FetchArgs {
     #[arg(long, short)]
     /// Optional regex to filter repositories
     pub regex: Option<Filter>,
+    #[arg(long, default_value = "origin")]
+    /// Remote to fetch, e.g. upstream for forks
+    pub remote: String,
+    #[arg(long, conflicts_with = "remote")]
+    /// Fetch every remote of the repositories
+    pub all_remotes: bool,
 }
 
 impl FetchArgs {
-    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         let user = common::user()?;
         let root = common::root()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
 
         let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
 
+        let mut statuses = vec![];
         for dir in sub_dirs {
-            fetch(&dir, &user)?;
+            statuses.extend(fetch(&dir, &user, &self.remote, self.all_remotes));
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
         }
         Ok(())
     }
 }
 
-fn fetch(dir: &PathBuf, user: &User) -> Result<()> {
-    let dir_name = path::dir_name(dir)?;
-    println!("Fetching for {}", dir_name);
+fn fetch(dir: &PathBuf, user: &User, remote: &str, all_remotes: bool) -> Vec<Status> {
+    let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let status = |remote: &str, result: Result<Vec<RefUpdate>>| Status {
+        repo: repo.clone(),
+        remote: remote.to_string(),
+        result: result.map_err(|e| format!("{:?}", e)),
+    };
+
+    let git_repo = match git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir)) {
+        Ok(git_repo) => git_repo,
+        Err(e) => return vec![status(remote, Err(e))],
+    };
 
-    let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+    let remotes = if all_remotes {
+        match git_repo.remotes() {
+            Ok(names) => names.iter().flatten().map(|n| n.to_string()).collect(),
+            Err(e) => return vec![status(remote, Err(e.into()))],
+        }
+    } else {
+        vec![remote.to_string()]
+    };
 
     let cred = GitCredential::from(user);
-    git::fetch(&git_repo, "origin", Some(cred))?;
+    let mut statuses = vec![];
+    for remote in remotes {
+        println!("Fetching {} for {}", remote, repo);
+        let result = git::fetch(&git_repo, &remote, Some(cred.clone()));
+        println!("===============");
+        statuses.push(status(&remote, result.map_err(|e| e.into())));
+    }
+    statuses
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    remote: String,
+    result: Result<Vec<RefUpdate>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(&self.remote),
+            self.updates_cell(),
+        ])
+    }
+
+    fn updates_cell(&self) -> Cell {
+        match &self.result {
+            Ok(updates) if updates.is_empty() => cell!("up to date"),
+            Ok(updates) => {
+                let lines: Vec<_> = updates
+                    .iter()
+                    .map(|u| match &u.old {
+                        Some(old) => format!("{} {}..{}", u.branch, &old[..7], &u.new[..7]),
+                        None => format!("{} (new)", u.branch),
+                    })
+                    .collect();
+                cell!(Fg -> lines.join("\n"))
+            }
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
 
-    println!("===============");
-    Ok(())
+        ErrorReport::from_message(&format!("{} ({})", self.repo, self.remote), e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Remote", "Updated branches"]);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when fetching:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
 }
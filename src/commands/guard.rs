@@ -0,0 +1,235 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::github::{self, CiState, RemoteRepo};
+use crate::path;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use clap::Parser;
+use colored::*;
+use git2::RepositoryState;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Check that local repositories are ready for a bulk change
+///
+/// This runs the preflight checks that push, merge and template apply run before they change
+/// anything: the working trees are clean, no merge or rebase is in progress, optionally CI
+/// passes on the default branches, and enough GitHub api requests are left.
+pub struct GuardArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Check that CI passes on the default branch of every repository
+    pub check_ci: bool,
+    #[arg(long, default_value = "0")]
+    /// GitHub api requests that the planned command makes per repository
+    pub requests: usize,
+}
+
+impl GuardArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let guard = Guard {
+            clean_worktree: true,
+            ci: self.check_ci,
+            requests_per_repo: self.requests,
+        };
+        let report = guard.report(&sub_dirs)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(report));
+        } else if report.passed() {
+            let msg = format!(
+                "All {} repositories passed the preflight checks",
+                sub_dirs.len()
+            );
+            println!("{}", msg.green());
+        } else {
+            report.print();
+        }
+
+        if !report.passed() {
+            bail!("The preflight checks failed");
+        }
+        Ok(())
+    }
+}
+
+/// What a bulk command needs from the repositories before it changes them
+pub struct Guard {
+    /// The working trees have to be clean
+    pub clean_worktree: bool,
+    /// CI has to pass on the default branches
+    pub ci: bool,
+    /// GitHub api requests the command makes per repository
+    pub requests_per_repo: usize,
+}
+
+impl Guard {
+    /// Check the repositories and stop with a report of the problems, unless `force` is set
+    pub fn check(&self, dirs: &[PathBuf], force: bool) -> Result<()> {
+        let report = self.report(dirs)?;
+        if report.passed() {
+            return Ok(());
+        }
+
+        report.print();
+        if force {
+            println!("{}\n", "Going on anyway because of --force".yellow());
+            Ok(())
+        } else {
+            bail!("The preflight checks failed, fix the problems or run again with --force")
+        }
+    }
+
+    fn report(&self, dirs: &[PathBuf]) -> Result<Report> {
+        let needs_api = self.ci || self.requests_per_repo > 0;
+        let token = if needs_api {
+            Some(common::user_token()?)
+        } else {
+            None
+        };
+
+        let repos: Vec<_> = dirs
+            .par_iter()
+            .map(|d| self.check_repo(d, token.as_deref()))
+            .filter(|r| !r.problems.is_empty())
+            .collect();
+
+        let rate_limit = match &token {
+            Some(token) => {
+                let needed = dirs.len() * (self.requests_per_repo + if self.ci { 2 } else { 0 });
+                let limit =
+                    github::get_rate_limit(token).context("Cannot get the GitHub rate limit")?;
+                (limit.remaining < needed).then(|| {
+                    let reset = DateTime::from_timestamp(limit.reset, 0)
+                        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+                        .unwrap_or_default();
+                    format!(
+                        "Only {} of {} GitHub api requests are left until {}, but about {} are needed",
+                        limit.remaining, limit.limit, reset, needed
+                    )
+                })
+            }
+            None => None,
+        };
+
+        Ok(Report { repos, rate_limit })
+    }
+
+    fn check_repo(&self, dir: &PathBuf, token: Option<&str>) -> RepoProblems {
+        let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let check = || -> Result<Vec<String>> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            let mut problems = vec![];
+
+            if let Some(operation) = operation_in_progress(git_repo.state()) {
+                problems.push(format!("{} in progress", operation));
+            }
+            if self.clean_worktree && git::status(&git_repo, false)?.is_dirty() {
+                problems.push("uncommitted changes".to_string());
+            }
+            if let (true, Some(token)) = (self.ci, token) {
+                let branch = match git::remote_head(&git_repo, "origin") {
+                    Some(branch) => branch,
+                    None => git::head_shorthand(&git_repo)?,
+                };
+                let remote = RemoteRepo {
+                    name: repo.clone(),
+                    owner: path::owner_name(dir)?,
+                    ssh_url: String::new(),
+                    https_url: String::new(),
+                };
+                match github::get_ci_state(&remote, &branch, token)? {
+                    CiState::Failure => problems.push(format!("CI fails on {}", branch)),
+                    CiState::Pending => problems.push(format!("CI is running on {}", branch)),
+                    CiState::Success | CiState::Unknown => {}
+                }
+            }
+
+            Ok(problems)
+        };
+
+        let problems = check().unwrap_or_else(|e| vec![format!("{:?}", e)]);
+        RepoProblems { repo, problems }
+    }
+}
+
+fn operation_in_progress(state: RepositoryState) -> Option<&'static str> {
+    match state {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => Some("rebase"),
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => Some("git am"),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RepoProblems {
+    repo: String,
+    problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    /// Only the repositories with problems
+    repos: Vec<RepoProblems>,
+    rate_limit: Option<String>,
+}
+
+impl Report {
+    fn passed(&self) -> bool {
+        self.repos.is_empty() && self.rate_limit.is_none()
+    }
+
+    fn print(&self) {
+        if !self.repos.is_empty() {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+            table.set_titles(row!["Repo", "Problems"]);
+            for repo in &self.repos {
+                table.add_row(row![b -> &repo.repo, Fr -> repo.problems.join("\n")]);
+            }
+            table.printstd();
+        }
+        if !self.repos.is_empty() {
+            let msg = format!("\n{} repos are not ready for the command", self.repos.len());
+            println!("{}", msg.red());
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            println!("\n{}", rate_limit.red());
+        }
+        println!();
+    }
+}
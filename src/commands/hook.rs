@@ -1,6 +1,9 @@
 use crate::cli::Args as CommonArgs;
 use super::hook_create::*;
 use super::hook_delete::*;
+use super::hook_install::*;
+use super::hook_run::*;
+use super::hook_uninstall::*;
 use anyhow::Result;
 use clap::Parser;
 
@@ -9,7 +12,7 @@ pub struct HookArgs {
     #[command(subcommand)]
     command: HookCommand,
 }
-/// Create, delete hooks for all repositories that match a pattern
+/// Create, delete webhooks or install policy git hooks for all repositories that match a pattern
 impl HookArgs {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         self.command.run(common_args)
@@ -22,6 +25,12 @@ pub enum HookCommand {
     Create(CreateArgs),
     #[command(name = "delete")]
     Delete(DeleteArgs),
+    #[command(name = "install")]
+    Install(HookInstallArgs),
+    #[command(name = "run")]
+    Run(HookRunArgs),
+    #[command(name = "uninstall")]
+    Uninstall(HookUninstallArgs),
 }
 
 impl HookCommand {
@@ -29,6 +38,9 @@ impl HookCommand {
         match self {
             Self::Create(args) => args.run(common_args),
             Self::Delete(args) => args.run(common_args),
+            Self::Install(args) => args.run(common_args),
+            Self::Run(args) => args.run(common_args),
+            Self::Uninstall(args) => args.run(common_args),
         }
     }
 }
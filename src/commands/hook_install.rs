@@ -0,0 +1,222 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use crate::policy::HookPolicy;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::Repository;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// Marks the hooks that gut wrote, so that they are updated and removed but other hooks are not
+pub static HOOK_MARKER: &str = "# Installed by gut hook install";
+
+/// The git hooks that run the built-in policy checks
+pub static HOOK_STAGES: [&str; 2] = ["pre-commit", "pre-push"];
+
+#[derive(Debug, Parser)]
+/// Install the built-in policy hooks in all local repositories that match a regex
+///
+/// The pre-commit hook refuses large files and file names that are not in Unicode NFC, the
+/// pre-push hook also checks the commit messages of the pushed commits. The rules are set per
+/// owner in the [hooks.<owner>] tables of the config file and are read when the hooks run, so
+/// changing them does not need a new install.
+pub struct HookInstallArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Replace hooks that were not installed by gut
+    pub force: bool,
+}
+
+impl HookInstallArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let config = Config::load()?;
+        let gut = std::env::current_exe().context("Cannot find the gut executable")?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| self.install(d, &organisation, &config, &gut))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn install(&self, dir: &PathBuf, organisation: &str, config: &Config, gut: &Path) -> Status {
+        let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+        let install = || -> Result<bool> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            let hooks_dir = hooks_dir(&git_repo)?;
+            std::fs::create_dir_all(&hooks_dir)?;
+
+            let mut changed = false;
+            for stage in HOOK_STAGES {
+                let path = hooks_dir.join(stage);
+                let script = hook_script(gut, stage, &owner);
+                match std::fs::read_to_string(&path) {
+                    Ok(current) if current == script => continue,
+                    Ok(current) if !current.contains(HOOK_MARKER) && !self.force => {
+                        return Err(anyhow!(
+                            "There is already a {} hook, use --force to replace it",
+                            stage
+                        ));
+                    }
+                    _ => {}
+                }
+                write_executable(&path, &script)?;
+                changed = true;
+            }
+            Ok(changed)
+        };
+
+        Status {
+            repo: format!("{}/{}", owner, name),
+            policy: HookPolicy::for_owner(config, &owner).describe(),
+            result: install().map_err(|e| format!("{:?}", e)),
+        }
+    }
+}
+
+/// The directory git runs hooks from, core.hooksPath is relative to the working directory
+pub fn hooks_dir(git_repo: &Repository) -> Result<PathBuf> {
+    let base = git_repo.workdir().unwrap_or_else(|| git_repo.path());
+    match git_repo.config()?.get_path("core.hooksPath") {
+        Ok(path) => Ok(base.join(path)),
+        Err(_) => Ok(git_repo.path().join("hooks")),
+    }
+}
+
+/// A small shell script that hands the hook over to `gut hook run`
+///
+/// The path of the gut that installed the hook is tried first, because hooks often run
+/// without the PATH of an interactive shell.
+fn hook_script(gut: &Path, stage: &str, owner: &str) -> String {
+    format!(
+        "#!/bin/sh\n\
+         {}, the rules are in [hooks.{}] of the gut config\n\
+         gut={}\n\
+         command -v \"$gut\" >/dev/null 2>&1 || gut=gut\n\
+         exec \"$gut\" hook run {} --owner {} \"$@\"\n",
+        HOOK_MARKER,
+        owner,
+        shell_quote(&gut.to_string_lossy()),
+        stage,
+        shell_quote(owner)
+    )
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn write_executable(path: &Path, content: &str) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::write(path, content)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_executable(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    policy: String,
+    /// Whether any hook was written
+    result: Result<bool, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            self.result_cell(),
+            cell!(&self.policy),
+        ])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(true) => cell!(Fg -> "Installed"),
+            Ok(false) => cell!("Up to date"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Hooks", "Policy"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when installing hooks:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
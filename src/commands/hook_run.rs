@@ -0,0 +1,148 @@
+use crate::cli::Args as CommonArgs;
+use crate::config::Config;
+use crate::policy::{HookPolicy, Violation};
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use colored::*;
+use git2::{Delta, Diff, FileMode, Oid, Repository};
+use std::io::BufRead;
+
+#[derive(Debug, Parser)]
+/// Run the checks of a policy hook in the current repository
+///
+/// This is what the hooks written by hook install call, git passes the arguments and input.
+pub struct HookRunArgs {
+    #[arg(value_enum)]
+    /// The hook that runs
+    pub stage: Stage,
+    #[arg(long)]
+    /// Owner whose rules apply
+    pub owner: String,
+    /// Arguments that git gives the hook
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Stage {
+    PreCommit,
+    PrePush,
+}
+
+impl HookRunArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        // Without a config file the defaults apply
+        let policy = Config::load()
+            .map(|c| HookPolicy::for_owner(&c, &self.owner))
+            .unwrap_or_default();
+        let git_repo = Repository::open_from_env()?;
+
+        let violations = match self.stage {
+            Stage::PreCommit => check_staged(&git_repo, &policy)?,
+            Stage::PrePush => {
+                let remote = self.args.first().map(String::as_str).unwrap_or("origin");
+                check_pushed(&git_repo, &policy, remote)?
+            }
+        };
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        eprintln!("{}", "The gut policy hook refused this:".red());
+        for violation in &violations {
+            eprintln!("  {}", violation);
+        }
+        eprintln!(
+            "\nThe rules are in [hooks.{}] of the gut config, git --no-verify skips the hook.",
+            self.owner
+        );
+        bail!("{} policy violation(s)", violations.len())
+    }
+}
+
+/// The files that are about to be committed
+fn check_staged(git_repo: &Repository, policy: &HookPolicy) -> Result<Vec<Violation>> {
+    let head = git_repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let index = git_repo.index()?;
+    let diff = git_repo.diff_tree_to_index(head.as_ref(), Some(&index), None)?;
+    check_diff(git_repo, policy, &diff)
+}
+
+/// The commits that are pushed and not on the remote yet
+///
+/// git gives one line per ref on stdin: `<local ref> <local sha> <remote ref> <remote sha>`.
+fn check_pushed(
+    git_repo: &Repository,
+    policy: &HookPolicy,
+    remote: &str,
+) -> Result<Vec<Violation>> {
+    let message_regex = policy.message_regex()?;
+    let mut violations = vec![];
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let parts: Vec<_> = line.split_whitespace().collect();
+        let (local, remote_oid) = match parts.as_slice() {
+            [_, local, _, remote_oid] => (Oid::from_str(local)?, Oid::from_str(remote_oid)?),
+            _ => continue,
+        };
+        if local.is_zero() {
+            // A deleted ref
+            continue;
+        }
+
+        let mut revwalk = git_repo.revwalk()?;
+        revwalk.push(local)?;
+        if !remote_oid.is_zero() && git_repo.find_commit(remote_oid).is_ok() {
+            revwalk.hide(remote_oid)?;
+        } else {
+            // A new branch, only the commits that the remote does not have yet count
+            revwalk.hide_glob(&format!("refs/remotes/{}/*", remote))?;
+        }
+
+        for oid in revwalk {
+            let commit = git_repo.find_commit(oid?)?;
+            let id = commit.id().to_string();
+
+            if let Some(regex) = &message_regex {
+                // Merge commits get their message from git
+                if commit.parent_count() < 2 {
+                    let message = commit.message().unwrap_or_default();
+                    violations.extend(HookPolicy::check_message(regex, &id, message));
+                }
+            }
+
+            let parent = commit.parents().next().map(|p| p.tree()).transpose()?;
+            let diff = git_repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), None)?;
+            for violation in check_diff(git_repo, policy, &diff)? {
+                if !violations.contains(&violation) {
+                    violations.push(violation);
+                }
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+fn check_diff(git_repo: &Repository, policy: &HookPolicy, diff: &Diff) -> Result<Vec<Violation>> {
+    let odb = git_repo.odb()?;
+    let mut violations = vec![];
+    for delta in diff.deltas() {
+        if matches!(delta.status(), Delta::Deleted) {
+            continue;
+        }
+        let file = delta.new_file();
+        if file.mode() == FileMode::Commit {
+            // A submodule, its files are checked in its own repository
+            continue;
+        }
+        let path = match file.path() {
+            Some(path) => path.to_string_lossy(),
+            None => continue,
+        };
+        let size = odb.read_header(file.id()).map(|(size, _)| size as u64)?;
+        violations.extend(policy.check_file(&path, size));
+    }
+    Ok(violations)
+}
@@ -0,0 +1,145 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::hook_install::{hooks_dir, HOOK_MARKER, HOOK_STAGES};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Remove the policy hooks installed by hook install from local repositories that match a regex
+///
+/// Hooks that were not installed by gut are left alone.
+pub struct HookUninstallArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl HookUninstallArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| uninstall(d, &organisation))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn uninstall(dir: &PathBuf, organisation: &str) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let uninstall = || -> Result<bool> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let hooks_dir = hooks_dir(&git_repo)?;
+
+        let mut removed = false;
+        for stage in HOOK_STAGES {
+            let path = hooks_dir.join(stage);
+            if let Ok(current) = std::fs::read_to_string(&path) {
+                if current.contains(HOOK_MARKER) {
+                    std::fs::remove_file(&path)?;
+                    removed = true;
+                }
+            }
+        }
+        Ok(removed)
+    };
+
+    Status {
+        repo: format!("{}/{}", owner, name),
+        result: uninstall().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    /// Whether any hook was removed
+    result: Result<bool, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.result_cell()])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(true) => cell!(Fg -> "Removed"),
+            Ok(false) => cell!("Not installed"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Hooks"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when removing hooks:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -1,9 +1,11 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::cli::Args as CommonArgs;
 use crate::config::Config;
 use crate::github;
 use crate::user::User;
+use anyhow::Context;
 use clap::Parser;
+use dialoguer::Password;
 
 fn validate_root(root: &str) -> Result<PathBuf, String> {
     let path = PathBuf::from(root);
@@ -12,17 +14,24 @@ fn validate_root(root: &str) -> Result<PathBuf, String> {
         return Err(format!("{root} is not an absolute path. Root must be an absolute path"));
     }
 
-    if path.exists() {
-        if path.is_dir() {
-            Ok(path)
-        } else {
-            Err(format!("{root} is a file. Root directory cannot be a file"))
-        }
-    } else {
-        std::fs::create_dir_all(&path)
-            .map_err(|source| format!("Cannot create root directory for path: {path:?} ({source})"))
-            .map(|_| path)
+    if path.exists() && !path.is_dir() {
+        return Err(format!("{root} is a file. Root directory cannot be a file"));
     }
+
+    Ok(path)
+}
+
+/// Create the root directory if it is missing and check that we can write to it
+fn prepare_root(path: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(path)
+        .with_context(|| format!("Cannot create root directory for path: {path:?}"))?;
+
+    let probe = path.join(".gut-write-test");
+    std::fs::write(&probe, b"")
+        .with_context(|| format!("Root directory {path:?} is not writable"))?;
+    std::fs::remove_file(&probe).with_context(|| format!("Cannot clean up {probe:?}"))?;
+
+    Ok(())
 }
 
 #[derive(Debug, Parser)]
@@ -38,32 +47,72 @@ pub struct InitArgs {
     ///
     /// All repositories will be cloned under this directory
     pub root: PathBuf,
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "token_stdin")]
     /// Github token. Gut needs github token to access your github data
-    pub token: String,
+    ///
+    /// You will be asked for the token if neither this nor --token-stdin is given.
+    pub token: Option<String>,
+    #[arg(long)]
+    /// Read the Github token from the first line of stdin
+    ///
+    /// This keeps the token out of the shell history and the process list in provisioning
+    /// scripts.
+    pub token_stdin: bool,
     /// Default organisation
-    #[arg(short, long)]
+    #[arg(short, long, visible_alias = "owner")]
     pub organisation: Option<String>,
     /// Default to https instead of ssh when cloning repositories
-    #[arg(short, long)]
+    #[arg(short, long, visible_alias = "https")]
     pub use_https: bool,
 }
 
 impl InitArgs {
     pub fn save_config(&self, _common_args: &CommonArgs) -> anyhow::Result<()> {
-        let user = match User::new(self.token.clone()) {
+        let user = match User::new(self.token()?) {
                 Ok(user) => { user },
                 Err(e) => match e.downcast_ref::<github::Unauthorized>() {
                     Some(_) => anyhow::bail!("Token is invalid. Check https://help.github.com/en/github/authenticating-to-github/creating-a-personal-access-token-for-the-command-line"),
                     _ => return Err(e)
                 }
             };
+        prepare_root(&self.root)?;
         user.save_user()?;
-        let config = Config::new(
+        let mut config = Config::new(
             self.root.to_str().unwrap().to_string(),
             self.organisation.clone(),
             self.use_https,
         );
+        // Keep the command defaults, aliases, network settings, mirrors, version files and workspaces of an earlier init
+        if let Ok(old) = Config::from_file() {
+            config.defaults = old.defaults;
+            config.alias = old.alias;
+            config.network = old.network;
+            config.mirrors = old.mirrors;
+            config.version_files = old.version_files;
+            config.email = old.email;
+            config.current_workspace = old.current_workspace;
+            config.workspaces = old.workspaces;
+        }
         config.save_config()
     }
+
+    fn token(&self) -> anyhow::Result<String> {
+        let token = if let Some(token) = &self.token {
+            token.clone()
+        } else if self.token_stdin {
+            let mut line = String::new();
+            std::io::stdin()
+                .read_line(&mut line)
+                .context("Cannot read the token from stdin")?;
+            line
+        } else {
+            Password::new().with_prompt("Github token").interact()?
+        };
+
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("The token is empty");
+        }
+        Ok(token)
+    }
 }
@@ -0,0 +1,39 @@
+use super::issue_close::*;
+use super::issue_create::*;
+use super::issue_list::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Work with the issues of repositories in bulk
+pub struct IssueArgs {
+    #[command(subcommand)]
+    command: IssueCommand,
+}
+
+impl IssueArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum IssueCommand {
+    #[command(name = "close")]
+    Close(IssueCloseArgs),
+    #[command(name = "create")]
+    Create(IssueCreateArgs),
+    #[command(name = "list", aliases = &["ls"])]
+    List(IssueListArgs),
+}
+
+impl IssueCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Close(args) => args.run(common_args),
+            Self::Create(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::{case_insensitive_regex, Filter};
+use crate::github::{self, OpenIssue, Pacer, RemoteRepo};
+use anyhow::Result;
+use clap::{ArgGroup, Parser};
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("filter").required(true).multiple(true).args(["title", "label"])))]
+/// Close the open issues that match a title regex or labels in all repositories that match a regex
+///
+/// This mass-closes obsolete issues, e.g. ones that a bot opened. The issues are listed and
+/// have to be confirmed before they are closed as not planned.
+pub struct IssueCloseArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, value_parser = case_insensitive_regex)]
+    /// Case insensitive regex that the titles of the issues match
+    pub title: Option<Regex>,
+    #[arg(long, short, value_delimiter = ',')]
+    /// Only close issues with all of these labels
+    pub label: Vec<String>,
+    #[arg(long, short)]
+    /// Comment on the issues before closing them
+    pub comment: Option<String>,
+    #[arg(long)]
+    /// Only show the issues that would be closed
+    pub dry_run: bool,
+}
+
+impl IssueCloseArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| {
+                (
+                    repo,
+                    github::get_open_issues(repo, &self.label, None, &user_token),
+                )
+            })
+            .collect();
+
+        let mut candidates = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(issues) => candidates.extend(
+                    issues
+                        .into_iter()
+                        .filter(|issue| self.is_match(issue))
+                        .map(|issue| (repo.clone(), issue)),
+                ),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking up issues:", reports.len());
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if candidates.is_empty() {
+            println!("There is no open issues to close");
+            return Ok(());
+        }
+
+        if self.dry_run {
+            if let Some(OutputFormat::Json) = common_args.format {
+                let issues: Vec<_> = candidates
+                    .iter()
+                    .map(|(repo, issue)| {
+                        json!({
+                            "repo": repo.full_name(),
+                            "number": issue.number,
+                            "title": issue.title,
+                            "url": issue.html_url,
+                        })
+                    })
+                    .collect();
+                println!("{}", json!(issues));
+            } else {
+                to_table(&candidates).printstd();
+                println!("\n{} issues would be closed", candidates.len());
+            }
+            return Ok(());
+        }
+
+        if common_args.format != Some(OutputFormat::Json) {
+            to_table(&candidates).printstd();
+        }
+        if !confirm(candidates.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        progress::begin(candidates.len());
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = candidates
+            .iter()
+            .map(|(repo, issue)| {
+                let name = format!("{}#{}", repo.full_name(), issue.number);
+                progress::track(&name, || {
+                    let result = self.close(repo, issue, &mut pacer, &user_token);
+                    Status {
+                        issue: name.clone(),
+                        title: issue.title.clone(),
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        println!();
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn is_match(&self, issue: &OpenIssue) -> bool {
+        self.title.as_ref().is_none_or(|t| t.is_match(&issue.title))
+    }
+
+    fn close(
+        &self,
+        repo: &RemoteRepo,
+        issue: &OpenIssue,
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<()> {
+        if let Some(comment) = &self.comment {
+            pacer.run(|| github::comment_on_issue(repo, issue.number, comment, token))?;
+        }
+        pacer.run(|| github::close_issue(repo, issue.number, token))
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    issue: String,
+    title: String,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let result = match &self.result {
+            Ok(()) => cell!(Fgr -> "Closed"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![cell!(b -> &self.issue), cell!(self.title), result])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.issue, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(candidates: &[(RemoteRepo, OpenIssue)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Author", "Labels"]);
+    for (repo, issue) in candidates {
+        let labels: Vec<_> = issue.labels.iter().map(|l| l.name.as_str()).collect();
+        table.add_row(row![
+            b -> repo.full_name(),
+            r -> issue.number,
+            issue.title,
+            issue.user.login,
+            labels.join(", ")
+        ]);
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Issue", "Title", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let closed = statuses.len() - errors.len();
+    if closed > 0 {
+        let msg = format!("\nClosed {} issues!", closed);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when closing issues:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to close {} issue(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::PullRequestUser;
+    use chrono::Utc;
+
+    fn issue(title: &str) -> OpenIssue {
+        OpenIssue {
+            number: 7,
+            node_id: "I_kwDOAbc".to_string(),
+            title: title.to_string(),
+            html_url: "https://github.com/giellalt/lang-sme/issues/7".to_string(),
+            user: PullRequestUser {
+                login: "divvunbot".to_string(),
+            },
+            assignees: vec![],
+            labels: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            pull_request: None,
+        }
+    }
+
+    #[test]
+    fn test_is_match() {
+        let args = IssueCloseArgs::parse_from(["close", "--title", "^Nightly build failed"]);
+        assert!(args.is_match(&issue("Nightly build failed on 2024-05-01")));
+        assert!(!args.is_match(&issue("Analyser crashes")));
+        assert!(!args.is_match(&issue("Windows build failed")));
+        assert!(args.is_match(&issue("nightly build failed")));
+
+        // Not an owner scope, the colon is part of the title
+        let args = IssueCloseArgs::parse_from(["close", "--title", "Bug: crash"]);
+        assert!(args.is_match(&issue("Bug: crash in hfst")));
+        assert!(!args.is_match(&issue("Analyser crashes")));
+
+        let args = IssueCloseArgs::parse_from(["close", "--label", "autogenerated"]);
+        assert!(args.is_match(&issue("Analyser crashes")));
+    }
+}
@@ -0,0 +1,220 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::topic_helper;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, Pacer, RemoteRepo};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Open the same issue in all repositories that match a regex or a topic
+///
+/// This announces a maintenance campaign in every repository it touches. Repositories that
+/// already have an open issue with the same title keep it, and the issues are opened one after
+/// another to keep below the rate limits of GitHub.
+pub struct IssueCreateArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short, required_unless_present("topic"))]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, required_unless_present("regex"))]
+    /// topic to filter
+    pub topic: Option<String>,
+    #[arg(long, short)]
+    /// Title of the issues
+    pub title: String,
+    #[arg(long, short)]
+    /// Markdown file with the description of the issues
+    pub body_file: Option<PathBuf>,
+    #[arg(long, short, value_delimiter = ',')]
+    /// Labels of the issues, e.g. maintenance,build
+    pub label: Vec<String>,
+    #[arg(long, short, value_delimiter = ',')]
+    /// Users the issues are assigned to
+    pub assignee: Vec<String>,
+}
+
+impl IssueCreateArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let body = match &self.body_file {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read body file {:?}", path))?,
+            None => String::new(),
+        };
+
+        let all_repos = topic_helper::query_repositories_with_topics(&organisation, &user_token)?;
+        let filtered_repos: Vec<_> =
+            topic_helper::filter_repos(&all_repos, self.topic.as_ref(), self.regex.as_ref())
+                .into_iter()
+                .map(|r| r.repo)
+                .collect();
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let existing: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| self.existing_issue(repo, &user_token))
+            .collect();
+
+        progress::begin(filtered_repos.len());
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = filtered_repos
+            .iter()
+            .zip(existing)
+            .map(|(repo, existing)| {
+                progress::track(&repo.full_name(), || {
+                    let result = existing.and_then(|existing| match existing {
+                        Some(url) => Ok(Outcome::Exists { url }),
+                        None => self.create(repo, &body, &mut pacer, &user_token),
+                    });
+                    Status {
+                        repo: repo.full_name(),
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, &self.title);
+
+        Ok(())
+    }
+
+    /// The url of the open issue with the same title, if there is one
+    fn existing_issue(&self, repo: &RemoteRepo, token: &str) -> Result<Option<String>> {
+        let issues = github::get_open_issues(repo, &[], None, token)?;
+        Ok(issues
+            .into_iter()
+            .find(|issue| issue.title == self.title)
+            .map(|issue| issue.html_url))
+    }
+
+    fn create(
+        &self,
+        repo: &RemoteRepo,
+        body: &str,
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<Outcome> {
+        let issue = pacer.run(|| {
+            github::create_issue(repo, &self.title, body, &self.label, &self.assignee, token)
+        })?;
+        Ok(Outcome::Created {
+            url: issue.html_url,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Outcome {
+    Created { url: String },
+    Exists { url: String },
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Outcome, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let url = match &self.result {
+            Ok(Outcome::Created { url } | Outcome::Exists { url }) => url.as_str(),
+            Err(_) => "",
+        };
+        Row::new(vec![cell!(b -> &self.repo), self.to_cell(), cell!(url)])
+    }
+
+    fn to_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Outcome::Created { .. }) => cell!(Fgr -> "Created"),
+            Ok(Outcome::Exists { .. }) => cell!(r -> "Already open"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn created(&self) -> bool {
+        matches!(self.result, Ok(Outcome::Created { .. }))
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Issue", "Link"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status], title: &str) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let created = statuses.iter().filter(|s| s.created()).count();
+
+    if created > 0 {
+        let msg = format!("\nOpened issue {:?} in {} repos!", title, created);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when opening issues:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,209 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use super::table::SortArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, OpenIssue, RemoteRepo};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Parser)]
+/// List the open issues of all repositories that match a regex
+///
+/// Every issue is shown with its author, assignees, labels and age, to triage an organisation
+/// without visiting every repository.
+pub struct IssueListArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, value_delimiter = ',')]
+    /// Only list issues with all of these labels, e.g. bug,help-wanted
+    pub label: Vec<String>,
+    #[arg(long, short)]
+    /// Only list issues assigned to this user, none for unassigned issues
+    pub assignee: Option<String>,
+    #[arg(long)]
+    /// Only list issues that were opened at least this many days ago
+    pub older_than: Option<i64>,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+impl IssueListArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| {
+                let issues = github::get_open_issues(
+                    repo,
+                    &self.label,
+                    self.assignee.as_deref(),
+                    &user_token,
+                );
+                (repo, issues)
+            })
+            .collect();
+
+        let mut issues = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(found) => issues.extend(
+                    found
+                        .into_iter()
+                        .map(|issue| Issue::new(repo, issue))
+                        .filter(|issue| self.is_old_enough(issue)),
+                ),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+        self.sort.sort(&mut issues, &titles(), Issue::to_row)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            let errors: BTreeMap<_, _> = reports.iter().map(|r| (&r.repo, &r.message)).collect();
+            println!("{}", json!({ "issues": issues, "errors": errors }));
+            return Ok(());
+        }
+
+        if issues.is_empty() {
+            println!("There is no open issues");
+        } else {
+            to_table(&issues).printstd();
+            let repos = issues
+                .iter()
+                .map(|i| &i.repo)
+                .collect::<std::collections::BTreeSet<_>>()
+                .len();
+            println!("\n{} open issues in {} repos", issues.len(), repos);
+        }
+
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking up issues:", reports.len());
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+
+    fn is_old_enough(&self, issue: &Issue) -> bool {
+        self.older_than.is_none_or(|days| issue.age() >= days)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Issue {
+    repo: String,
+    number: u64,
+    title: String,
+    author: String,
+    assignees: Vec<String>,
+    labels: Vec<String>,
+    created_at: DateTime<Utc>,
+    url: String,
+}
+
+impl Issue {
+    fn new(repo: &RemoteRepo, issue: OpenIssue) -> Issue {
+        Issue {
+            repo: repo.full_name(),
+            number: issue.number,
+            title: issue.title,
+            author: issue.user.login,
+            assignees: issue.assignees.into_iter().map(|u| u.login).collect(),
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            created_at: issue.created_at,
+            url: issue.html_url,
+        }
+    }
+
+    /// Days since the issue was opened
+    fn age(&self) -> i64 {
+        (Utc::now() - self.created_at).num_days()
+    }
+
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(r -> self.number),
+            cell!(self.title),
+            cell!(self.author),
+            cell!(self.assignees.join(", ")),
+            cell!(self.labels.join(", ")),
+            cell!(r -> format!("{}d", self.age())),
+        ])
+    }
+}
+
+fn titles() -> Row {
+    row!["Repo", "#", "Title", "Author", "Assignees", "Labels", "Age"]
+}
+
+fn to_table(issues: &[Issue]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles());
+    for issue in issues {
+        table.add_row(issue.to_row());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn issue(days: i64) -> Issue {
+        Issue {
+            repo: "giellalt/lang-sme".to_string(),
+            number: 1,
+            title: "Analyser crashes".to_string(),
+            author: "snomos".to_string(),
+            assignees: vec![],
+            labels: vec!["bug".to_string()],
+            created_at: Utc::now() - Duration::days(days),
+            url: "https://github.com/giellalt/lang-sme/issues/1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_older_than() {
+        let args = IssueListArgs::parse_from(["list", "--older-than", "30"]);
+        assert!(args.is_old_enough(&issue(45)));
+        assert!(args.is_old_enough(&issue(30)));
+        assert!(!args.is_old_enough(&issue(3)));
+
+        let args = IssueListArgs::parse_from(["list"]);
+        assert!(args.is_old_enough(&issue(3)));
+    }
+}
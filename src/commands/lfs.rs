@@ -0,0 +1,31 @@
+use super::lfs_fetch::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Manage Git LFS files of local repositories
+pub struct LfsArgs {
+    #[command(subcommand)]
+    command: LfsCommand,
+}
+
+impl LfsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum LfsCommand {
+    #[command(name = "fetch")]
+    Fetch(LfsFetchArgs),
+}
+
+impl LfsCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Fetch(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::path::dir_name;
+use crate::user::User;
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Download the Git LFS files of all local repositories that match a regex
+///
+/// This hydrates repositories that were cloned or pulled with --no-lfs. Only the files of
+/// the current branch are downloaded.
+pub struct LfsFetchArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl LfsFetchArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || fetch(d, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn fetch(dir: &PathBuf, user: &User) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let fetch = || -> Result<bool> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let cred = GitCredential::from(user);
+        git::lfs_fetch(&git_repo, Some(&cred))
+    };
+
+    Status {
+        repo,
+        result: fetch().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    /// Whether the repository uses LFS
+    result: Result<bool, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.to_cell()])
+    }
+
+    fn to_cell(&self) -> Cell {
+        match &self.result {
+            Ok(true) => cell!(Fgr -> "Fetched"),
+            Ok(false) => cell!(r -> "No LFS"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "LFS"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let fetched = statuses
+        .iter()
+        .filter(|s| matches!(s.result, Ok(true)))
+        .count();
+
+    if fetched > 0 {
+        let msg = format!("\nFetched LFS files of {} repos successfully!", fetched);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when fetching LFS files:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,225 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, RemoteRepo};
+use crate::path;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Freeze the default branch of all repositories that match a regex
+///
+/// The default branch is locked, so nobody can push to it or merge into it, admins included.
+/// The protection that the branch had before is saved, so that unlock can put it back as it was.
+pub struct LockArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl LockArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        let mut locks = Locks::load()?;
+        let (locked, to_lock): (Vec<_>, Vec<_>) = filtered_repos
+            .into_iter()
+            .partition(|r| locks.repos.contains_key(&r.full_name()));
+
+        for repo in &locked {
+            println!("{} is already locked", repo.full_name());
+        }
+
+        if to_lock.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?} left to lock",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        println!("The default branch of the following repos will be locked:");
+        for repo in &to_lock {
+            println!("{}", repo.full_name());
+        }
+
+        if !confirm(to_lock.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = to_lock
+            .par_iter()
+            .map(|repo| lock(repo, &user_token))
+            .collect();
+
+        for status in &statuses {
+            if let Ok(lock) = &status.result {
+                locks.repos.insert(status.repo.clone(), lock.clone());
+            }
+        }
+        locks.save()?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn lock(repo: &RemoteRepo, token: &str) -> Status {
+    let lock = || -> Result<Lock> {
+        let branch = github::get_default_branch(&repo.owner, &repo.name, token)?;
+        let protection = github::get_branch_protection(repo, &branch, token)?;
+        github::set_locked_branch(repo, &branch, token)?;
+
+        Ok(Lock {
+            branch,
+            protection,
+            locked_at: Utc::now(),
+        })
+    };
+
+    Status {
+        repo: repo.full_name(),
+        result: lock().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+/// Repositories that are locked, with the protection that their default branch had before
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Locks {
+    pub repos: BTreeMap<String, Lock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lock {
+    pub branch: String,
+    /// The protection as GitHub returned it, None when the branch was not protected
+    pub protection: Option<serde_json::Value>,
+    pub locked_at: DateTime<Utc>,
+}
+
+impl Locks {
+    fn path() -> Result<PathBuf> {
+        path::locks_path().ok_or_else(|| anyhow!("Cannot find the config directory"))
+    }
+
+    pub fn load() -> Result<Locks> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Locks::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read the saved locks from {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Cannot read the saved locks from {:?}", path))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Cannot save the locks to {:?}", path))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Lock, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(lock) => row![
+                b -> self.repo,
+                lock.branch,
+                protection_cell(&lock.protection),
+                Fg -> "Locked"
+            ],
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+            ]),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+pub fn protection_cell(protection: &Option<serde_json::Value>) -> Cell {
+    match protection {
+        Some(_) => cell!("Protected"),
+        None => cell!("Not protected"),
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Branch", "Before", "Status"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when locking:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Nobody will be able to push to or merge into {} repo(s) until they are unlocked. Are you sure?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,35 @@
+use super::mailmap_apply::*;
+use super::mailmap_report::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Find inconsistent commit authors and roll out a shared .mailmap file
+pub struct MailmapArgs {
+    #[command(subcommand)]
+    command: MailmapCommand,
+}
+
+impl MailmapArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum MailmapCommand {
+    #[command(name = "apply")]
+    Apply(MailmapApplyArgs),
+    #[command(name = "report")]
+    Report(MailmapReportArgs),
+}
+
+impl MailmapCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Apply(args) => args.run(common_args),
+            Self::Report(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::github::{self, RemoteRepo};
+use crate::path::{dir_name, owner_name};
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{Repository, Status as FileStatus};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+static MAILMAP: &str = ".mailmap";
+
+#[derive(Debug, Parser)]
+/// Commit a shared .mailmap file to all local repositories that match a regex
+///
+/// The file is committed on top of the current branch and pushed, or with --pr pushed to a
+/// new branch with a pull request. Other changes in the working directory are left alone.
+pub struct MailmapApplyArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// The .mailmap file to roll out, for example one written by mailmap report --output
+    pub file: PathBuf,
+    #[arg(long, short, default_value = "Update .mailmap")]
+    /// Commit message
+    pub message: String,
+    #[arg(long)]
+    /// Open a pull request instead of pushing to the current branch
+    pub pr: bool,
+    #[arg(long, short, default_value = "gut/mailmap")]
+    /// Name of the branch for the pull request
+    pub branch: String,
+}
+
+impl MailmapApplyArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let content = std::fs::read_to_string(&self.file)
+            .with_context(|| format!("Cannot read mailmap file {:?}", self.file))?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        if !confirm(sub_dirs.len(), self.pr)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        // Sequential, because it pushes and opens pull requests
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .iter()
+            .map(|d| progress::track_dir(d, || self.apply(d, &organisation, &content, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn apply(&self, dir: &PathBuf, organisation: &str, content: &str, user: &User) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+        let apply = || -> Result<Rollout> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if !git_repo.head()?.is_branch() {
+                return Err(anyhow!("HEAD is detached, check out a branch first"));
+            }
+            let current_branch = git::head_shorthand(&git_repo)?;
+            let head = git_repo.head()?.peel_to_commit()?;
+            let head_tree = head.tree()?;
+
+            if let Ok(entry) = head_tree.get_path(Path::new(MAILMAP)) {
+                let blob = git_repo.find_blob(entry.id())?;
+                if blob.content() == content.as_bytes() {
+                    return Ok(Rollout::UpToDate);
+                }
+            }
+
+            let blob = git_repo.blob(content.as_bytes())?;
+            let mut builder = git_repo.treebuilder(Some(&head_tree))?;
+            builder.insert(MAILMAP, blob, 0o100644)?;
+            let tree = git_repo.find_tree(builder.write()?)?;
+            let sig = git_repo.signature()?;
+            let cred = GitCredential::from(user);
+
+            if self.pr {
+                let oid = git_repo.commit(None, &sig, &sig, &self.message, &tree, &[&head])?;
+                git_repo.branch(&self.branch, &git_repo.find_commit(oid)?, true)?;
+                git::push::push_branch(&git_repo, &self.branch, "origin", Some(cred))?;
+
+                let remote_repo = RemoteRepo {
+                    name: repo.clone(),
+                    owner: owner.clone(),
+                    ssh_url: String::new(),
+                    https_url: String::new(),
+                };
+                let pr = github::create_pull_request(
+                    &remote_repo,
+                    &self.branch,
+                    &current_branch,
+                    &self.message,
+                    "This maps the names and emails that authors have committed under to one identity per author.",
+                    &user.token,
+                )?;
+                return Ok(Rollout::PullRequest(pr.html_url));
+            }
+
+            check_unmodified(&git_repo)?;
+            git_repo.commit(Some("HEAD"), &sig, &sig, &self.message, &tree, &[&head])?;
+
+            // Bring the working directory and the index up to date with the new commit
+            let workdir = git_repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Cannot commit to a bare repository"))?;
+            std::fs::write(workdir.join(MAILMAP), content)?;
+            let mut index = git_repo.index()?;
+            index.add_path(Path::new(MAILMAP))?;
+            index.write()?;
+
+            git::push::push_branch(&git_repo, &current_branch, "origin", Some(cred))?;
+            Ok(Rollout::Pushed(current_branch))
+        };
+
+        let result = apply().map_err(|e| format!("{:?}", e));
+        Status { repo, result }
+    }
+}
+
+/// A .mailmap with uncommitted changes would be overwritten
+fn check_unmodified(git_repo: &Repository) -> Result<()> {
+    match git_repo.status_file(Path::new(MAILMAP)) {
+        Ok(status) if status != FileStatus::CURRENT => Err(anyhow!(
+            "{} has uncommitted changes, commit or discard them first",
+            MAILMAP
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum Rollout {
+    UpToDate,
+    Pushed(String),
+    PullRequest(String),
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Rollout, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.result_cell()])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Rollout::UpToDate) => cell!("Up to date"),
+            Ok(Rollout::Pushed(branch)) => cell!(Fg -> format!("Pushed to {}", branch)),
+            Ok(Rollout::PullRequest(url)) => cell!(Fg -> url),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!(
+            "There {} errors when rolling out the .mailmap:",
+            errors.len()
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, pr: bool) -> Result<bool> {
+    let key = "YES";
+    let action = if pr {
+        "open a pull request with the .mailmap in"
+    } else {
+        "commit and push the .mailmap to"
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to {} {} repo(s)?\nEnter {} to continue",
+            action, count, key
+        ),
+        key,
+    )
+}
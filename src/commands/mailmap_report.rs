@@ -0,0 +1,314 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::dir_name;
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{Mailmap, Repository};
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Find commit authors that use several names or emails in local repositories
+///
+/// Identities that share an email, or a name apart from case, are grouped. The identity with
+/// the most commits in a group is taken as the right one, and the others can be written as a
+/// .mailmap file with --output.
+pub struct MailmapReportArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// A .mailmap file to apply before comparing authors, to see what it does not cover yet
+    pub mailmap: Option<PathBuf>,
+    #[arg(long)]
+    /// Write a .mailmap file that maps every inconsistent identity to the right one
+    pub output: Option<PathBuf>,
+}
+
+impl MailmapReportArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let mailmap = match &self.mailmap {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Cannot read mailmap file {:?}", path))?,
+            ),
+            None => None,
+        };
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| authors(d, mailmap.as_deref()))
+            .collect();
+
+        let mut identities: BTreeMap<Identity, Usage> = BTreeMap::new();
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(authors) => {
+                    for (identity, commits) in authors {
+                        let usage = identities.entry(identity).or_default();
+                        usage.commits += commits;
+                        usage.repos.insert(repo.clone());
+                    }
+                }
+                Err(e) => reports.push(ErrorReport::from_message(&repo, &format!("{:?}", e))),
+            }
+        }
+
+        let groups = group_identities(identities);
+
+        if let Some(path) = &self.output {
+            std::fs::write(path, to_mailmap(&groups))
+                .with_context(|| format!("Cannot write mailmap file {:?}", path))?;
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(groups));
+            return Ok(());
+        }
+
+        summarize(&groups, &reports);
+        if let Some(path) = &self.output {
+            println!("\nWrote the suggested .mailmap to {:?}", path);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Identity {
+    name: String,
+    email: String,
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Usage {
+    commits: usize,
+    repos: BTreeSet<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Group {
+    canonical: Identity,
+    /// The other identities, with the usage of each
+    variants: Vec<(Identity, Usage)>,
+    commits: usize,
+}
+
+fn authors(dir: &PathBuf, mailmap: Option<&str>) -> (String, Result<BTreeMap<Identity, usize>>) {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let authors = || -> Result<BTreeMap<Identity, usize>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let mailmap = match mailmap {
+            Some(content) => Mailmap::from_buffer(content)?,
+            None => Mailmap::new()?,
+        };
+        count_authors(&git_repo, &mailmap)
+    };
+
+    (repo, authors())
+}
+
+fn count_authors(git_repo: &Repository, mailmap: &Mailmap) -> Result<BTreeMap<Identity, usize>> {
+    let mut authors = BTreeMap::new();
+
+    let mut revwalk = git_repo.revwalk()?;
+    revwalk.push_head()?;
+    for oid in revwalk {
+        let commit = git_repo.find_commit(oid?)?;
+        let author = commit.author_with_mailmap(mailmap)?;
+        let identity = Identity {
+            name: author.name().unwrap_or_default().trim().to_string(),
+            email: author.email().unwrap_or_default().trim().to_string(),
+        };
+        *authors.entry(identity).or_insert(0) += 1;
+    }
+
+    Ok(authors)
+}
+
+/// Identities that share an email or a name (ignoring case) belong to the same person
+fn group_identities(identities: BTreeMap<Identity, Usage>) -> Vec<Group> {
+    let identities: Vec<_> = identities.into_iter().collect();
+    let mut parents: Vec<usize> = (0..identities.len()).collect();
+
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        parents[i] = root;
+        root
+    }
+
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    for (i, (identity, _)) in identities.iter().enumerate() {
+        let keys = [
+            format!("email:{}", identity.email.to_lowercase()),
+            format!("name:{}", identity.name.to_lowercase()),
+        ];
+        for key in keys {
+            if key.ends_with(':') {
+                continue;
+            }
+            match seen.get(&key) {
+                Some(&j) => {
+                    let (a, b) = (find(&mut parents, i), find(&mut parents, j));
+                    parents[a] = b;
+                }
+                None => {
+                    seen.insert(key, i);
+                }
+            }
+        }
+    }
+
+    let mut members: BTreeMap<usize, Vec<(Identity, Usage)>> = BTreeMap::new();
+    for (i, identity) in identities.into_iter().enumerate() {
+        let root = find(&mut parents, i);
+        members.entry(root).or_default().push(identity);
+    }
+
+    let mut groups: Vec<_> = members
+        .into_values()
+        .filter(|m| m.len() > 1)
+        .map(|mut m| {
+            m.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(&b.0)));
+            let commits = m.iter().map(|(_, u)| u.commits).sum();
+            let (canonical, _) = m.remove(0);
+            Group {
+                canonical,
+                variants: m,
+                commits,
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| Reverse(g.commits));
+    groups
+}
+
+fn to_mailmap(groups: &[Group]) -> String {
+    let mut lines = vec![];
+    for group in groups {
+        for (variant, _) in &group.variants {
+            lines.push(format!("{} {}", group.canonical, variant));
+        }
+    }
+    lines.sort();
+    lines.iter().map(|l| format!("{}\n", l)).collect()
+}
+
+fn summarize(groups: &[Group], reports: &[ErrorReport]) {
+    if groups.is_empty() {
+        println!("{}", "Every author uses one name and one email".green());
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Author", "Also committed as", "Commits", "Repos"]);
+        for group in groups {
+            for (i, (variant, usage)) in group.variants.iter().enumerate() {
+                let author = if i == 0 {
+                    group.canonical.to_string()
+                } else {
+                    String::new()
+                };
+                table.add_row(row![
+                    b -> author,
+                    Fy -> variant,
+                    r -> usage.commits,
+                    usage.repos.iter().cloned().collect::<Vec<_>>().join(", ")
+                ]);
+            }
+        }
+        table.printstd();
+
+        let msg = format!(
+            "\n{} authors have commits under several identities",
+            groups.len()
+        );
+        println!("{}", msg.yellow());
+    }
+
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading commits:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(commits: usize) -> Usage {
+        Usage {
+            commits,
+            repos: BTreeSet::new(),
+        }
+    }
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_group_identities() {
+        let identities = BTreeMap::from([
+            (identity("Ann Olsen", "ann@uit.no"), usage(40)),
+            (identity("ann olsen", "ann@laptop.local"), usage(3)),
+            (identity("Ann", "ann@uit.no"), usage(5)),
+            (identity("Per", "per@uit.no"), usage(10)),
+        ]);
+
+        let groups = group_identities(identities);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].canonical, identity("Ann Olsen", "ann@uit.no"));
+        assert_eq!(groups[0].commits, 48);
+        assert_eq!(
+            to_mailmap(&groups),
+            "Ann Olsen <ann@uit.no> Ann <ann@uit.no>\n\
+             Ann Olsen <ann@uit.no> ann olsen <ann@laptop.local>\n"
+        );
+    }
+}
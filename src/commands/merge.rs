@@ -1,5 +1,6 @@
 use crate::cli::Args as CommonArgs;
 use super::common;
+use super::guard::Guard;
 use crate::filter::Filter;
 use crate::git;
 use crate::git::MergeStatus;
@@ -24,6 +25,12 @@ pub struct MergeArgs {
     #[arg(long, short)]
     /// Option to abort merging process if there is a conflict
     pub abort_if_conflict: bool,
+    #[arg(long)]
+    /// Check that CI passes on the default branch of every repository before changing anything
+    pub check_ci: bool,
+    #[arg(long)]
+    /// Run even when the preflight checks fail
+    pub force: bool,
 }
 
 impl MergeArgs {
@@ -33,6 +40,13 @@ impl MergeArgs {
 
         let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
 
+        let guard = Guard {
+            clean_worktree: true,
+            ci: self.check_ci,
+            requests_per_repo: 0,
+        };
+        guard.check(&sub_dirs, self.force)?;
+
         for dir in sub_dirs {
             match merge(&dir, &self.branch, self.abort_if_conflict) {
                 Ok(status) => match status {
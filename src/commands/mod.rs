@@ -1,49 +1,153 @@
 pub mod add;
 pub mod add_repos;
 pub mod add_users;
+pub mod affected;
+pub mod amend;
 pub mod apply;
+pub mod blame_file;
 pub mod branch;
 pub mod branch_default;
+pub mod branch_ff;
 pub mod branch_protect;
 pub mod branch_unprotect;
+pub mod cache;
+pub mod cache_list;
+pub mod cache_refresh;
+pub mod changelog;
+pub mod check;
+pub mod check_docs;
+pub mod check_links;
+pub mod check_syntax;
 pub mod checkout;
 pub mod ci;
 pub mod clean;
+pub mod clean_remote_gone;
 pub mod clone;
 pub mod commit;
 pub mod common;
+pub mod convert;
+pub mod convert_remotes;
 pub mod create;
 pub mod create_branch;
 pub mod create_discussion;
+pub mod create_keyboard;
 pub mod create_repo;
 pub mod create_team;
+pub mod deps;
+pub mod deps_graph;
+pub mod diff;
+pub mod encode;
+pub mod encode_check;
+pub mod errors;
+pub mod environment;
+pub mod environment_create;
+pub mod environment_list;
+pub mod environment_set_reviewers;
 pub mod fetch;
+pub mod guard;
 pub mod hook;
 pub mod hook_create;
 pub mod hook_delete;
+pub mod hook_install;
+pub mod hook_run;
+pub mod hook_uninstall;
 pub mod init_config;
 pub mod invite;
 pub mod invite_users;
+pub mod issue;
+pub mod issue_close;
+pub mod issue_create;
+pub mod issue_list;
+pub mod lfs;
+pub mod lfs_fetch;
+pub mod lock;
+pub mod mailmap;
+pub mod mailmap_apply;
+pub mod mailmap_report;
 pub mod make;
 pub mod merge;
 pub mod models;
+pub mod normalize;
+pub mod normalize_newline;
+pub mod normalize_permissions;
+pub mod notifications;
+pub mod notifications_list;
+pub mod notifications_mark_read;
+pub mod open;
+pub mod packages;
+pub mod packages_list;
+pub mod packages_prune;
+pub mod pages;
+pub mod pages_disable;
+pub mod pages_enable;
+pub mod pages_set;
+pub mod pages_show;
 pub mod patterns;
+pub mod pr;
+pub mod pr_assign;
+pub mod pr_automerge;
+pub mod pr_create;
+pub mod pr_list;
+pub mod pr_merge;
+pub mod progress;
+pub mod project;
+pub mod project_add;
+pub mod project_fields;
+pub mod prune_branches;
 pub mod pull;
+pub mod purge_file;
 pub mod push;
+pub mod release;
+pub mod release_create;
+pub mod release_delete;
+pub mod release_list;
 pub mod remove;
 pub mod remove_repos;
 pub mod remove_users;
 pub mod rename;
+pub mod repair;
+pub mod review;
+pub mod runner;
+pub mod runner_labels;
+pub mod runner_list;
+pub mod runner_token;
+pub mod search;
+pub mod search_code;
+pub mod search_issues;
+pub mod secret;
+pub mod secret_rotate;
 pub mod set;
 pub mod set_default_organisation;
 pub mod set_info;
+pub mod set_org_defaults;
 pub mod set_secret;
 pub mod set_team_permission;
 pub mod show;
+pub mod show_ci_config;
+pub mod show_ci_config_drift;
+pub mod show_community_files;
 pub mod show_config;
+pub mod show_default_branches;
+pub mod show_dependencies;
+pub mod show_forks;
+pub mod show_inbox;
+pub mod show_org_settings;
 pub mod show_repos;
+pub mod show_size;
+pub mod show_url;
 pub mod show_users;
+pub mod show_vulnerabilities;
+pub mod snapshot;
+pub mod snapshot_checkout;
+pub mod snapshot_create;
+pub mod star;
+pub mod state;
+pub mod state_show;
+pub mod stats;
+pub mod stats_activity;
+pub mod stats_languages;
 pub mod status;
+pub mod table;
 pub mod template;
 pub mod topic;
 pub mod topic_add;
@@ -52,32 +156,97 @@ pub mod topic_get;
 pub mod topic_helper;
 pub mod topic_set;
 pub mod transfer;
+pub mod triage;
+pub mod triage_issues;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod unlock;
+pub mod upstream;
+pub mod upstream_merge;
+pub mod verify;
+pub mod verify_clone_integrity;
+pub mod verify_default_branch;
+pub mod verify_tags_signed;
+pub mod version;
+pub mod version_bump;
+pub mod watch;
+pub mod whoami;
 pub mod workflow;
 pub mod workflow_run;
+pub mod workflow_status;
+pub mod workspace;
+pub mod workspace_create;
+pub mod workspace_list;
+pub mod workspace_use;
 
 pub use add::*;
+pub use affected::*;
+pub use amend::*;
 pub use apply::*;
+pub use blame_file::*;
 pub use branch::*;
+pub use cache::*;
+pub use changelog::*;
+pub use check::*;
 pub use checkout::*;
 pub use ci::*;
 pub use clean::*;
 pub use clone::*;
 pub use commit::*;
+pub use convert::*;
 pub use create::*;
+pub use deps::*;
+pub use diff::*;
+pub use encode::*;
+pub use environment::*;
 pub use fetch::*;
+pub use guard::*;
 pub use hook::*;
 pub use init_config::*;
 pub use invite::*;
+pub use issue::*;
+pub use lfs::*;
+pub use lock::*;
+pub use mailmap::*;
 pub use make::*;
 pub use merge::*;
+pub use normalize::*;
+pub use notifications::*;
+pub use open::*;
+pub use packages::*;
+pub use pages::*;
+pub use pr::*;
+pub use project::*;
+pub use prune_branches::*;
 pub use pull::*;
+pub use purge_file::*;
 pub use push::*;
+pub use release::*;
 pub use remove::*;
 pub use rename::*;
+pub use repair::*;
+pub use review::*;
+pub use runner::*;
+pub use search::*;
+pub use secret::*;
 pub use set::*;
 pub use show::*;
+pub use snapshot::*;
+pub use star::*;
+pub use state::*;
+pub use stats::*;
 pub use status::*;
 pub use template::*;
 pub use topic::*;
 pub use transfer::*;
+pub use triage::*;
+#[cfg(feature = "tui")]
+pub use tui::*;
+pub use unlock::*;
+pub use upstream::*;
+pub use verify::*;
+pub use version::*;
+pub use watch::*;
+pub use whoami::*;
 pub use workflow::*;
+pub use workspace::*;
@@ -0,0 +1,277 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::normalize_newline::*;
+use super::normalize_permissions::*;
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::git;
+use crate::path::dir_name;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{Repository, Status as FileStatus};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Parser)]
+/// Fix the line endings, whitespace and permissions of the files of local repositories
+pub struct NormalizeArgs {
+    #[command(subcommand)]
+    command: NormalizeCommand,
+}
+
+impl NormalizeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum NormalizeCommand {
+    #[command(name = "newline")]
+    Newline(NormalizeNewlineArgs),
+    #[command(name = "permissions")]
+    Permissions(NormalizePermissionsArgs),
+}
+
+impl NormalizeCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Newline(args) => args.run(common_args),
+            Self::Permissions(args) => args.run(common_args),
+        }
+    }
+}
+
+/// A fix of the tracked files of a repository, as they are committed
+pub trait Fix: Sync {
+    /// The fixed content of a file, None when it does not change
+    fn content(&self, _repo: &Repository, _path: &str, _content: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// The fixed mode of a file, None when it does not change
+    fn mode(&self, _path: &str, _content: &[u8], _mode: u32) -> Option<u32> {
+        None
+    }
+}
+
+/// Fix the files of all directories and commit the fixes, or only find them with `dry_run`
+pub fn normalize_dirs(
+    dirs: &[PathBuf],
+    fix: &dyn Fix,
+    message: &str,
+    dry_run: bool,
+    common_args: &CommonArgs,
+) -> Result<()> {
+    if !dry_run && !confirm(dirs.len(), message)? {
+        println!("Command is aborted. Nothing change!");
+        return Ok(());
+    }
+
+    progress::begin(dirs.len());
+    let statuses: Vec<_> = dirs
+        .par_iter()
+        .map(|d| progress::track_dir(d, || normalize(d, fix, message, dry_run)))
+        .collect();
+
+    if let Some(OutputFormat::Json) = common_args.format {
+        println!("{}", json!(statuses));
+        return Ok(());
+    }
+
+    summarize(&statuses, dry_run);
+
+    Ok(())
+}
+
+fn normalize(dir: &PathBuf, fix: &dyn Fix, message: &str, dry_run: bool) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let normalize = || -> Result<Vec<String>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let mut index = git_repo.index()?;
+        if index.has_conflicts() {
+            bail!("There are conflicts, fix them first");
+        }
+
+        let mut fixed = vec![];
+        for mut entry in index.iter() {
+            // Only regular files, not symlinks or submodules
+            if entry.mode & 0o170000 != 0o100000 {
+                continue;
+            }
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            let blob = git_repo.find_blob(entry.id)?;
+            let content = fix.content(&git_repo, &path, blob.content());
+            let mode = fix.mode(&path, blob.content(), entry.mode);
+            if content.is_none() && mode.is_none() {
+                continue;
+            }
+
+            check_unmodified(
+                &git_repo,
+                fix,
+                &path,
+                content.as_deref().unwrap_or(blob.content()),
+            )?;
+            if let (Some(content), false) = (&content, dry_run) {
+                entry.id = git_repo.blob(content)?;
+                entry.file_size = content.len() as u32;
+            }
+            entry.mode = mode.unwrap_or(entry.mode);
+            fixed.push((path, entry));
+        }
+
+        if fixed.is_empty() || dry_run {
+            return Ok(fixed.into_iter().map(|(path, _)| path).collect());
+        }
+
+        let paths: BTreeSet<_> = fixed.iter().map(|(path, _)| path.as_str()).collect();
+        check_clean(&git_repo, &paths)?;
+        for (_, entry) in &fixed {
+            index.add(entry)?;
+        }
+        git::commit_index(&git_repo, &mut index, message)?;
+
+        Ok(fixed.into_iter().map(|(path, _)| path).collect())
+    };
+
+    Status {
+        repo,
+        result: normalize().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+/// A fixed file with uncommitted changes would lose them when the fix is checked out, unless
+/// the changes are only what the fix does, e.g. line endings that git converts on checkout
+fn check_unmodified(git_repo: &Repository, fix: &dyn Fix, path: &str, fixed: &[u8]) -> Result<()> {
+    match git_repo.status_file(Path::new(path)) {
+        Ok(FileStatus::CURRENT) | Err(_) => return Ok(()),
+        Ok(_) => {}
+    }
+    let workdir = git_repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot fix a bare repository"))?;
+    let content = std::fs::read(workdir.join(path)).unwrap_or_default();
+    let content = fix.content(git_repo, path, &content).unwrap_or(content);
+    if content != fixed {
+        bail!(
+            "{} has uncommitted changes, commit or discard them first",
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Other uncommitted changes would be committed with the fix or lost on checkout
+fn check_clean(git_repo: &Repository, fixed: &BTreeSet<&str>) -> Result<()> {
+    let status = git::status(git_repo, false)?;
+    let changed = status
+        .added
+        .iter()
+        .chain(&status.modified)
+        .chain(&status.deleted)
+        .chain(&status.renamed)
+        .chain(&status.typechanges)
+        .find(|path| !fixed.contains(path.as_str()));
+    match changed {
+        Some(path) => bail!(
+            "{} has uncommitted changes, commit or stash them first",
+            path
+        ),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    /// The files that are fixed
+    result: Result<Vec<String>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.result_cell()])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(files) if files.is_empty() => cell!(r -> "-"),
+            Ok(files) => cell!(Fyr -> files.len()),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status], dry_run: bool) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    let files = if dry_run {
+        "Files to fix"
+    } else {
+        "Fixed files"
+    };
+    table.set_titles(row!["Repo", files]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let fixed: usize = statuses
+        .iter()
+        .filter_map(|s| s.result.as_ref().ok())
+        .map(|files| files.len())
+        .sum();
+    if dry_run {
+        println!("\n{} files need a fix, nothing is changed", fixed);
+    } else {
+        println!("\n{} files are fixed and committed", fixed);
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when fixing:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, message: &str) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to fix and commit {:?} in {} repo(s)?\nEnter {} to continue",
+            message, count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,124 @@
+use super::common;
+use super::normalize::{normalize_dirs, Fix};
+use crate::cli::Args as CommonArgs;
+use crate::encoding;
+use crate::filter::Filter;
+use anyhow::Result;
+use clap::Parser;
+use git2::Repository;
+
+#[derive(Debug, Parser)]
+/// Store text files with LF line endings, as .gitattributes asks for, and commit them
+///
+/// Files that .gitattributes marks as `text`, `text=auto` or with an `eol` are committed with LF
+/// line endings, like `git add --renormalize`. The working directory gets the line endings of
+/// the `eol` attribute on checkout. Binary files are left alone.
+pub struct NormalizeNewlineArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Also remove the spaces and tabs at the end of lines of all text files
+    ///
+    /// Two spaces at the end of a markdown line are a line break, and are kept.
+    pub trailing_whitespace: bool,
+    #[arg(long, short, default_value = "Normalize line endings")]
+    /// Commit message
+    pub message: String,
+    #[arg(long)]
+    /// Only count the files that would be fixed
+    pub dry_run: bool,
+}
+
+impl NormalizeNewlineArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        normalize_dirs(&sub_dirs, self, &self.message, self.dry_run, common_args)
+    }
+}
+
+impl Fix for NormalizeNewlineArgs {
+    fn content(&self, repo: &Repository, path: &str, content: &[u8]) -> Option<Vec<u8>> {
+        if encoding::is_binary(content) || encoding::is_marked_binary(repo, path) {
+            return None;
+        }
+        let normalize = encoding::is_normalized(repo, path);
+        if !normalize && !self.trailing_whitespace {
+            return None;
+        }
+
+        let markdown = path.to_lowercase().ends_with(".md");
+        let fixed = fix_lines(content, normalize, self.trailing_whitespace, markdown);
+        (fixed != content).then_some(fixed)
+    }
+}
+
+/// The content with LF line endings when `lf`, and without whitespace at the end of lines when
+/// `trim`
+fn fix_lines(content: &[u8], lf: bool, trim: bool, markdown: bool) -> Vec<u8> {
+    let mut fixed = Vec::with_capacity(content.len());
+    let mut lines = content.split(|b| *b == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let (mut text, cr) = match line.strip_suffix(b"\r") {
+            Some(text) => (text, true),
+            None => (line, false),
+        };
+        if trim {
+            let end = text
+                .iter()
+                .rposition(|b| *b != b' ' && *b != b'\t')
+                .map_or(0, |i| i + 1);
+            let line_break = markdown && end > 0 && &text[end..] == b"  ";
+            if !line_break {
+                text = &text[..end];
+            }
+        }
+        fixed.extend_from_slice(text);
+        if cr && !lf {
+            fixed.push(b'\r');
+        }
+        if lines.peek().is_some() {
+            fixed.push(b'\n');
+        }
+    }
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_lines() {
+        let content = b"a \r\nb\t\n  \r\nline  \nbreak";
+        assert_eq!(
+            fix_lines(content, true, false, false),
+            b"a \nb\t\n  \nline  \nbreak"
+        );
+        assert_eq!(
+            fix_lines(content, false, true, false),
+            b"a\r\nb\n\r\nline\nbreak"
+        );
+        assert_eq!(
+            fix_lines(content, true, true, true),
+            b"a\nb\n\nline  \nbreak"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use super::common;
+use super::normalize::{normalize_dirs, Fix};
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use anyhow::Result;
+use clap::Parser;
+
+/// Extensions of files that are run, and keep their executable bit
+static PROGRAMS: &[&str] = &["sh", "bash", "zsh", "py", "pl", "rb", "exe", "bat", "cmd"];
+
+#[derive(Debug, Parser)]
+/// Remove the executable bit from data files, and commit them
+///
+/// A file keeps the bit when it starts with #!, is a compiled program, or has the extension of a
+/// script. All other files, like lexicons, xml and documentation, are committed as not
+/// executable.
+pub struct NormalizePermissionsArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(
+        long,
+        short,
+        default_value = "Remove the executable bit from data files"
+    )]
+    /// Commit message
+    pub message: String,
+    #[arg(long)]
+    /// Only count the files that would be fixed
+    pub dry_run: bool,
+}
+
+impl NormalizePermissionsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        normalize_dirs(&sub_dirs, self, &self.message, self.dry_run, common_args)
+    }
+}
+
+impl Fix for NormalizePermissionsArgs {
+    fn mode(&self, path: &str, content: &[u8], mode: u32) -> Option<u32> {
+        (mode == 0o100755 && !is_program(path, content)).then_some(0o100644)
+    }
+}
+
+fn is_program(path: &str, content: &[u8]) -> bool {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, e)| e.to_lowercase())
+        .unwrap_or_default();
+    content.starts_with(b"#!")
+        // ELF, Mach-O and Windows executables
+        || content.starts_with(b"\x7fELF")
+        || content.starts_with(&[0xcf, 0xfa, 0xed, 0xfe])
+        || content.starts_with(b"MZ")
+        || PROGRAMS.contains(&extension.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_program() {
+        assert!(is_program("autogen", b"#!/bin/sh\n"));
+        assert!(is_program("tools/build.sh", b"make\n"));
+        assert!(is_program("bin/hfst-lookup", b"\x7fELF\x02"));
+        assert!(!is_program("src/fst/stems/nouns.lexc", b"LEXICON Root\n"));
+        assert!(!is_program("README.md", b"# Title\n"));
+    }
+}
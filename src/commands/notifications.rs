@@ -0,0 +1,141 @@
+use super::common;
+use super::notifications_list::*;
+use super::notifications_mark_read::*;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github::{self, Notification};
+use anyhow::Result;
+use chrono::Utc;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// List and mark read the GitHub notifications from the repositories of organisations
+pub struct NotificationsArgs {
+    #[command(subcommand)]
+    command: NotificationsCommand,
+}
+
+impl NotificationsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum NotificationsCommand {
+    #[command(name = "list")]
+    List(NotificationsListArgs),
+    #[command(name = "mark-read")]
+    MarkRead(NotificationsMarkReadArgs),
+}
+
+impl NotificationsCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::List(args) => args.run(common_args),
+            Self::MarkRead(args) => args.run(common_args),
+        }
+    }
+}
+
+/// Which unread notifications a command works on
+pub struct Selection<'a> {
+    pub organisation: Option<&'a str>,
+    pub all_orgs: bool,
+    pub regex: Option<&'a Filter>,
+    pub reasons: &'a [String],
+    /// Days since the last update
+    pub older_than: Option<i64>,
+}
+
+impl Selection<'_> {
+    /// The unread notifications of the token that are selected, newest first
+    pub fn notifications(&self, token: &str) -> Result<Vec<Notification>> {
+        let owners = common::owners(self.organisation, self.all_orgs, self.regex, token)?;
+        Ok(github::get_notifications(token)?
+            .into_iter()
+            .filter(|n| self.matches(n, &owners))
+            .collect())
+    }
+
+    fn matches(&self, notification: &Notification, owners: &[String]) -> bool {
+        let repo = &notification.repository;
+        let owner = &repo.owner.login;
+        owners.iter().any(|o| o.eq_ignore_ascii_case(owner))
+            && self
+                .regex
+                .map(|r| r.is_match_in(owner, &repo.name))
+                .unwrap_or(true)
+            && (self.reasons.is_empty() || self.reasons.contains(&notification.reason))
+            && self
+                .older_than
+                .map(|days| age(notification) >= days)
+                .unwrap_or(true)
+    }
+}
+
+/// Days since the notification was updated
+pub fn age(notification: &Notification) -> i64 {
+    (Utc::now() - notification.updated_at).num_days()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{NotificationOwner, NotificationRepository, NotificationSubject};
+    use chrono::Duration;
+
+    fn notification(owner: &str, name: &str, reason: &str, days: i64) -> Notification {
+        Notification {
+            id: "1".to_string(),
+            reason: reason.to_string(),
+            updated_at: Utc::now() - Duration::days(days),
+            subject: NotificationSubject {
+                title: "Update the analyser".to_string(),
+                kind: "PullRequest".to_string(),
+            },
+            repository: NotificationRepository {
+                name: name.to_string(),
+                full_name: format!("{}/{}", owner, name),
+                owner: NotificationOwner {
+                    login: owner.to_string(),
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let regex: Filter = "^lang-".parse().unwrap();
+        let reasons = ["subscribed".to_string()];
+        let selection = Selection {
+            organisation: None,
+            all_orgs: false,
+            regex: Some(&regex),
+            reasons: &reasons,
+            older_than: Some(30),
+        };
+        let owners = ["giellalt".to_string()];
+
+        assert!(selection.matches(
+            &notification("GiellaLT", "lang-sme", "subscribed", 40),
+            &owners
+        ));
+        assert!(!selection.matches(
+            &notification("divvun", "lang-sme", "subscribed", 40),
+            &owners
+        ));
+        assert!(!selection.matches(
+            &notification("giellalt", "giella-core", "subscribed", 40),
+            &owners
+        ));
+        assert!(!selection.matches(
+            &notification("giellalt", "lang-sme", "mention", 40),
+            &owners
+        ));
+        assert!(!selection.matches(
+            &notification("giellalt", "lang-sme", "subscribed", 3),
+            &owners
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use super::common;
+use super::notifications::{age, Selection};
+use super::table::{GroupBy, OwnerTable};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::Notification;
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{cell, row, Row};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Parser)]
+/// List the unread notifications from the repositories of organisations
+///
+/// Only notifications from repositories of the organisation, or of the owners in the regex,
+/// are listed, newest first.
+pub struct NotificationsListArgs {
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// List notifications from every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, value_delimiter = ',')]
+    /// Only list notifications for these reasons, e.g. review_requested,mention
+    pub reason: Vec<String>,
+    #[arg(long)]
+    /// Only list notifications that were not updated for this many days
+    pub older_than: Option<i64>,
+    #[arg(long, value_enum, default_value_t = GroupBy::Flat)]
+    /// Print a table per owner, or one table with an Owner column
+    pub group_by: GroupBy,
+}
+
+impl NotificationsListArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let selection = Selection {
+            organisation: self.organisation.as_deref(),
+            all_orgs: self.all_orgs,
+            regex: self.regex.as_ref(),
+            reasons: &self.reason,
+            older_than: self.older_than,
+        };
+        let notifications = selection.notifications(&user_token)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(notifications));
+            return Ok(());
+        }
+
+        summarize(&notifications, self.group_by);
+
+        Ok(())
+    }
+}
+
+fn to_row(notification: &Notification) -> Row {
+    let reason = match notification.reason.as_str() {
+        "review_requested" | "mention" | "assign" => cell!(Fy -> notification.reason),
+        reason => cell!(reason),
+    };
+    Row::new(vec![
+        cell!(b -> &notification.repository.name),
+        reason,
+        cell!(notification.subject.kind),
+        cell!(notification.subject.title),
+        cell!(r -> format!("{}d", age(notification))),
+        cell!(notification.id),
+    ])
+}
+
+fn summarize(notifications: &[Notification], group_by: GroupBy) {
+    if notifications.is_empty() {
+        println!("There is no unread notification");
+        return;
+    }
+
+    let mut table = OwnerTable::new(row!["Repo", "Reason", "Kind", "Title", "Age", "Thread"]);
+    for notification in notifications {
+        table.add_row(&notification.repository.owner.login, to_row(notification));
+    }
+    table.print(group_by);
+
+    let mut reasons: BTreeMap<&str, usize> = BTreeMap::new();
+    for notification in notifications {
+        *reasons.entry(notification.reason.as_str()).or_default() += 1;
+    }
+    let reasons: Vec<_> = reasons
+        .iter()
+        .map(|(reason, count)| format!("{} {}", count, reason))
+        .collect();
+    println!(
+        "\n{} unread notifications: {}",
+        notifications.len(),
+        reasons.join(", ")
+    );
+}
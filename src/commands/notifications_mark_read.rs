@@ -0,0 +1,137 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use super::notifications::Selection;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, Notification};
+use anyhow::Result;
+use clap::Parser;
+use colored::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Mark the unread notifications from the repositories of organisations as read, or as done
+///
+/// The notifications are selected like in notifications list, or by their thread ids.
+pub struct NotificationsMarkReadArgs {
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Mark notifications from every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, value_delimiter = ',')]
+    /// Only mark notifications for these reasons, e.g. subscribed,ci_activity
+    pub reason: Vec<String>,
+    #[arg(long)]
+    /// Only mark notifications that were not updated for this many days
+    pub older_than: Option<i64>,
+    #[arg(long, value_delimiter = ',')]
+    /// Only mark the threads with these ids, as shown by notifications list
+    pub thread: Vec<String>,
+    #[arg(long)]
+    /// Mark the notifications as done, which removes them from the inbox
+    pub done: bool,
+}
+
+impl NotificationsMarkReadArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let selection = Selection {
+            organisation: self.organisation.as_deref(),
+            all_orgs: self.all_orgs,
+            regex: self.regex.as_ref(),
+            reasons: &self.reason,
+            older_than: self.older_than,
+        };
+        let notifications: Vec<_> = selection
+            .notifications(&user_token)?
+            .into_iter()
+            .filter(|n| self.thread.is_empty() || self.thread.contains(&n.id))
+            .collect();
+
+        if notifications.is_empty() {
+            println!("There is no unread notification to mark");
+            return Ok(());
+        }
+
+        if !self.confirm(notifications.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = notifications
+            .iter()
+            .map(|n| self.mark(n, &user_token))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, self.done);
+
+        Ok(())
+    }
+
+    fn mark(&self, notification: &Notification, token: &str) -> Status {
+        let result = if self.done {
+            github::mark_notification_done(&notification.id, token)
+        } else {
+            github::mark_notification_read(&notification.id, token)
+        };
+        Status {
+            thread: notification.id.clone(),
+            repo: notification.repository.full_name.clone(),
+            title: notification.subject.title.clone(),
+            result: result.map_err(|e| format!("{:?}", e)),
+        }
+    }
+
+    fn confirm(&self, count: usize) -> Result<bool> {
+        let key = "YES";
+        let mark = if self.done { "done" } else { "read" };
+        common::confirm(
+            &format!(
+                "Are you sure you want to mark {} notification(s) as {}?\nEnter {} to continue",
+                count, mark, key
+            ),
+            key,
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    thread: String,
+    repo: String,
+    title: String,
+    result: Result<(), String>,
+}
+
+fn summarize(statuses: &[Status], done: bool) {
+    let marked = statuses.iter().filter(|s| s.result.is_ok()).count();
+    let mark = if done { "done" } else { "read" };
+    println!("Marked {} notifications as {}", marked, mark);
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when marking:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,136 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use std::process::Command;
+
+#[derive(Debug, Parser)]
+/// Open the GitHub page of repositories in the browser
+///
+/// Repositories can be given by name, or matched with a regex.
+pub struct OpenArgs {
+    #[arg(required_unless_present = "regex")]
+    /// Names of the repositories to open
+    pub repos: Vec<String>,
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, group = "page")]
+    /// Open the issues page
+    pub issues: bool,
+    #[arg(long, short, group = "page")]
+    /// Open the actions page
+    pub actions: bool,
+    #[arg(long, short, group = "page")]
+    /// Open the settings page
+    pub settings: bool,
+    #[arg(long, short)]
+    /// Only print the urls instead of opening them
+    pub print: bool,
+    #[arg(long, short, default_value = "5")]
+    /// Ask for confirmation before opening more pages than this
+    pub max: usize,
+}
+
+impl OpenArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let mut urls: Vec<_> = self
+            .repos
+            .iter()
+            .map(|name| format!("{}/{}/{}", github::web_url(), organisation, name))
+            .collect();
+
+        if self.regex.is_some() {
+            let user_token = common::user_token()?;
+            let filtered_repos = common::query_and_filter_repositories(
+                &organisation,
+                self.regex.as_ref(),
+                &user_token,
+            )?;
+            urls.extend(filtered_repos.into_iter().map(|r| r.https_url));
+        }
+
+        if urls.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let urls: Vec<_> = urls
+            .iter()
+            .map(|u| format!("{}{}", u, self.page()))
+            .collect();
+
+        if self.print {
+            for url in &urls {
+                println!("{}", url);
+            }
+            return Ok(());
+        }
+
+        if urls.len() > self.max && !confirm(urls.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        for url in &urls {
+            if let Err(e) = open(url) {
+                println!("Failed to open {} because {:?}", url, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn page(&self) -> &str {
+        if self.issues {
+            "/issues"
+        } else if self.actions {
+            "/actions"
+        } else if self.settings {
+            "/settings"
+        } else {
+            ""
+        }
+    }
+}
+
+fn open(url: &str) -> Result<()> {
+    let status = if cfg!(target_os = "windows") {
+        Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .status()?
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg(url).status()?
+    } else {
+        Command::new("xdg-open").arg(url).status()?
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("the browser command exited with {}", status))
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to open {} pages in the browser?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,58 @@
+use super::packages_list::*;
+use super::packages_prune::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+
+#[derive(Debug, Parser)]
+/// List or prune packages published to GitHub Packages by an organisation
+pub struct PackagesArgs {
+    #[command(subcommand)]
+    command: PackagesCommand,
+}
+
+impl PackagesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum PackagesCommand {
+    #[command(name = "list", aliases = &["ls"])]
+    List(PackagesListArgs),
+    #[command(name = "prune")]
+    Prune(PackagesPruneArgs),
+}
+
+impl PackagesCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::List(args) => args.run(common_args),
+            Self::Prune(args) => args.run(common_args),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum PackageType {
+    Container,
+    Npm,
+    Maven,
+    Rubygems,
+    Docker,
+    Nuget,
+}
+
+impl PackageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Npm => "npm",
+            Self::Maven => "maven",
+            Self::Rubygems => "rubygems",
+            Self::Docker => "docker",
+            Self::Nuget => "nuget",
+        }
+    }
+}
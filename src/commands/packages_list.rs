@@ -0,0 +1,117 @@
+use super::common;
+use super::packages::PackageType;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use crate::github::{Package, PackageVersion};
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+
+#[derive(Debug, Parser)]
+/// List packages of an organisation with their versions
+///
+/// GitHub does not report the storage size of packages through its api, use the number of
+/// versions and the date of the oldest one to find the packages worth pruning.
+pub struct PackagesListArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter packages by name
+    pub regex: Option<Filter>,
+    #[arg(long, short, value_enum, default_value = "container")]
+    /// The type of packages
+    pub package_type: PackageType,
+}
+
+impl PackagesListArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let package_type = self.package_type.as_str();
+
+        let packages = github::get_org_packages(&organisation, package_type, &user_token)?;
+        let packages: Vec<_> = packages
+            .into_iter()
+            .filter(|p| self.regex.as_ref().is_none_or(|r| r.is_match(&p.name)))
+            .collect();
+
+        if packages.is_empty() {
+            println!(
+                "There is no {} packages in organisation {} that matches pattern {:?}",
+                package_type, organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = packages
+            .par_iter()
+            .map(|p| {
+                let versions =
+                    github::get_package_versions(&organisation, package_type, &p.name, &user_token);
+                (p, versions)
+            })
+            .collect();
+
+        to_table(&results).printstd();
+
+        Ok(())
+    }
+}
+
+fn to_table(results: &[(&Package, Result<Vec<PackageVersion>>)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row![
+        "Package",
+        "Repo",
+        "Visibility",
+        "Versions",
+        "Latest",
+        "Oldest"
+    ]);
+    for (package, versions) in results {
+        let repo = package
+            .repository
+            .as_ref()
+            .map(|r| r.name.as_str())
+            .unwrap_or("-");
+        match versions {
+            Ok(versions) => {
+                let latest = versions
+                    .first()
+                    .map(|v| format!("{} ({})", v.name, v.created_at.format("%Y-%m-%d")))
+                    .unwrap_or_else(|| "-".to_string());
+                let oldest = versions
+                    .last()
+                    .map(|v| v.created_at.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                table.add_row(row![
+                    b -> &package.name,
+                    repo,
+                    package.visibility,
+                    r -> versions.len(),
+                    latest,
+                    oldest
+                ]);
+            }
+            Err(e) => {
+                let msg = format!("{:?}", e);
+                let lines = common::sub_strings(msg.as_str(), 80).join("\n");
+                table.add_row(row![
+                    b -> &package.name,
+                    repo,
+                    package.visibility,
+                    r -> package.version_count,
+                    Fr -> "Failed",
+                    Fr -> lines
+                ]);
+            }
+        }
+    }
+    table
+}
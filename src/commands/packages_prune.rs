@@ -0,0 +1,211 @@
+use super::common;
+use super::packages::PackageType;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use crate::github::PackageVersion;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use prettytable::{format, row, Table};
+
+#[derive(Debug, Parser)]
+/// Delete old versions of packages of an organisation
+///
+/// A version is deleted when it is not one of the newest "--keep" versions and it is older
+/// than "--older-than" days. The newest version of a package is never deleted.
+pub struct PackagesPruneArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter packages by name
+    pub regex: Option<Filter>,
+    #[arg(long, short, value_enum, default_value = "container")]
+    /// The type of packages
+    pub package_type: PackageType,
+    #[arg(long, short)]
+    /// Number of newest versions to keep for every package
+    pub keep: Option<usize>,
+    #[arg(long)]
+    /// Only delete versions older than this number of days
+    pub older_than: Option<i64>,
+    #[arg(long)]
+    /// Only show the versions that would be deleted
+    pub dry_run: bool,
+}
+
+impl PackagesPruneArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        if self.keep.is_none() && self.older_than.is_none() {
+            return Err(anyhow!(
+                "You need to provide at least one of \"--keep\" and \"--older-than\""
+            ));
+        }
+
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let package_type = self.package_type.as_str();
+
+        let packages = github::get_org_packages(&organisation, package_type, &user_token)?;
+        let now = Utc::now();
+
+        let mut candidates = vec![];
+        for package in packages
+            .iter()
+            .filter(|p| self.regex.as_ref().is_none_or(|r| r.is_match(&p.name)))
+        {
+            match github::get_package_versions(
+                &organisation,
+                package_type,
+                &package.name,
+                &user_token,
+            ) {
+                Ok(versions) => {
+                    for version in versions_to_prune(versions, self.keep, self.older_than, now) {
+                        candidates.push((package.name.clone(), version));
+                    }
+                }
+                Err(e) => println!(
+                    "Failed to get versions of package {} because {:?}",
+                    package.name, e
+                ),
+            }
+        }
+
+        if candidates.is_empty() {
+            println!("There is no package versions to prune");
+            return Ok(());
+        }
+
+        to_table(&candidates).printstd();
+
+        if self.dry_run {
+            println!("\n{} versions would be deleted", candidates.len());
+            return Ok(());
+        }
+
+        if !confirm(candidates.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        for (name, version) in candidates {
+            let result = github::delete_package_version(
+                &organisation,
+                package_type,
+                &name,
+                version.id,
+                &user_token,
+            );
+            match result {
+                Ok(_) => println!("Deleted version {} of package {}", version.name, name),
+                Err(e) => println!(
+                    "Failed to delete version {} of package {} because {:?}",
+                    version.name, name, e
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `versions` are expected newest first, as returned by the api
+fn versions_to_prune(
+    versions: Vec<PackageVersion>,
+    keep: Option<usize>,
+    older_than: Option<i64>,
+    now: DateTime<Utc>,
+) -> Vec<PackageVersion> {
+    let keep = keep.unwrap_or(0).max(1);
+    versions
+        .into_iter()
+        .skip(keep)
+        .filter(|v| older_than.is_none_or(|days| now - v.created_at > Duration::days(days)))
+        .collect()
+}
+
+fn to_table(candidates: &[(String, PackageVersion)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Package", "Version", "Created"]);
+    for (name, version) in candidates {
+        table.add_row(row![
+            b -> name,
+            version.name,
+            version.created_at.format("%Y-%m-%d")
+        ]);
+    }
+    table
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to delete {} package version(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(id: i64, days_ago: i64, now: DateTime<Utc>) -> PackageVersion {
+        PackageVersion {
+            id,
+            name: format!("v{}", id),
+            created_at: now - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_versions_to_prune_keeps_newest() {
+        let now = Utc::now();
+        let versions = vec![
+            version(3, 1, now),
+            version(2, 10, now),
+            version(1, 100, now),
+        ];
+
+        let ids: Vec<_> = versions_to_prune(versions.clone(), Some(2), None, now)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![1]);
+
+        let ids: Vec<_> = versions_to_prune(versions, Some(0), None, now)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_versions_to_prune_by_age() {
+        let now = Utc::now();
+        let versions = vec![
+            version(3, 1, now),
+            version(2, 10, now),
+            version(1, 100, now),
+        ];
+
+        let ids: Vec<_> = versions_to_prune(versions.clone(), None, Some(30), now)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![1]);
+
+        let ids: Vec<_> = versions_to_prune(versions, Some(1), Some(5), now)
+            .iter()
+            .map(|v| v.id)
+            .collect();
+        assert_eq!(ids, vec![2, 1]);
+    }
+}
@@ -0,0 +1,60 @@
+use super::pages_disable::*;
+use super::pages_enable::*;
+use super::pages_set::*;
+use super::pages_show::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+
+#[derive(Debug, Parser)]
+/// Enable, disable, configure or show GitHub Pages sites of repositories
+pub struct PagesArgs {
+    #[command(subcommand)]
+    command: PagesCommand,
+}
+
+impl PagesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum PagesCommand {
+    #[command(name = "disable")]
+    Disable(PagesDisableArgs),
+    #[command(name = "enable")]
+    Enable(PagesEnableArgs),
+    #[command(name = "set")]
+    Set(PagesSetArgs),
+    #[command(name = "show")]
+    Show(PagesShowArgs),
+}
+
+impl PagesCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Disable(args) => args.run(common_args),
+            Self::Enable(args) => args.run(common_args),
+            Self::Set(args) => args.run(common_args),
+            Self::Show(args) => args.run(common_args),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum PagesBuildType {
+    /// Build the site from a branch
+    Legacy,
+    /// Build the site with a GitHub Actions workflow
+    Workflow,
+}
+
+impl PagesBuildType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Legacy => "legacy",
+            Self::Workflow => "workflow",
+        }
+    }
+}
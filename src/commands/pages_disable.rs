@@ -0,0 +1,71 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Disable GitHub Pages for all repositories that match a regex
+pub struct PagesDisableArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl PagesDisableArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        println!("GitHub Pages will be disabled for the following repos:");
+
+        for repo in &filtered_repos {
+            println!("{}", repo.full_name());
+        }
+
+        if !confirm(filtered_repos.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        for repo in filtered_repos {
+            let result = github::delete_pages(&repo, &user_token);
+            match result {
+                Ok(_) => println!("Disabled GitHub Pages for repo {} successfully", repo.name),
+                Err(e) => println!(
+                    "Failed to disable GitHub Pages for repo {} because {:?}",
+                    repo.name, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to disable GitHub Pages for {} repo(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
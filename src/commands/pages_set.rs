@@ -0,0 +1,78 @@
+use super::common;
+use super::pages::PagesBuildType;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use crate::github::PagesSource;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Change the source or the build type of GitHub Pages for all repositories that match a regex
+///
+/// GitHub Pages need to be enabled already for the repositories.
+pub struct PagesSetArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// The branch to publish the site from
+    pub branch: Option<String>,
+    #[arg(long, short, default_value = "/", value_parser = ["/", "/docs"])]
+    /// The directory in the branch to publish the site from
+    pub path: String,
+    #[arg(long, value_enum)]
+    /// How the site is built
+    pub build_type: Option<PagesBuildType>,
+}
+
+impl PagesSetArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        if self.branch.is_none() && self.build_type.is_none() {
+            return Err(anyhow!(
+                "You need to provide at least one of \"--branch\" and \"--build-type\""
+            ));
+        }
+
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let source = self.branch.as_ref().map(|branch| PagesSource {
+            branch: branch.clone(),
+            path: self.path.clone(),
+        });
+
+        for repo in filtered_repos {
+            let result = github::update_pages(
+                &repo,
+                self.build_type.map(|b| b.as_str()),
+                source.clone(),
+                &user_token,
+            );
+            match result {
+                Ok(_) => println!("Updated GitHub Pages for repo {} successfully", repo.name),
+                Err(e) => println!(
+                    "Failed to update GitHub Pages for repo {} because {:?}",
+                    repo.name, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use crate::github::{Pages, RemoteRepo};
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+
+#[derive(Debug, Parser)]
+/// Show GitHub Pages configuration and published url for all repositories that match a regex
+pub struct PagesShowArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl PagesShowArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|r| (r, github::get_pages(r, &user_token)))
+            .collect();
+
+        to_table(&results).printstd();
+
+        Ok(())
+    }
+}
+
+fn to_table(results: &[(&RemoteRepo, Result<Option<Pages>>)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Status", "Build type", "Source", "Url"]);
+    for (repo, result) in results {
+        match result {
+            Ok(None) => {
+                table.add_row(row![b -> &repo.name, "Disabled", "-", "-", "-"]);
+            }
+            Ok(Some(pages)) => {
+                let source = pages
+                    .source
+                    .as_ref()
+                    .map(|s| format!("{}:{}", s.branch, s.path))
+                    .unwrap_or_else(|| "-".to_string());
+                table.add_row(row![
+                    b -> &repo.name,
+                    Fg -> pages.status.as_deref().unwrap_or("enabled"),
+                    pages.build_type.as_deref().unwrap_or("-"),
+                    source,
+                    pages.html_url.as_deref().unwrap_or("-")
+                ]);
+            }
+            Err(e) => {
+                let msg = format!("{:?}", e);
+                let lines = common::sub_strings(msg.as_str(), 80).join("\n");
+                table.add_row(row![b -> &repo.name, Fr -> "Failed", "", "", Fr -> lines]);
+            }
+        }
+    }
+    table
+}
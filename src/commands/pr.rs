@@ -0,0 +1,47 @@
+use super::pr_assign::*;
+use super::pr_automerge::*;
+use super::pr_create::*;
+use super::pr_list::*;
+use super::pr_merge::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Work with the pull requests of repositories in bulk
+pub struct PrArgs {
+    #[command(subcommand)]
+    command: PrCommand,
+}
+
+impl PrArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum PrCommand {
+    #[command(name = "assign")]
+    Assign(PrAssignArgs),
+    #[command(name = "automerge")]
+    Automerge(PrAutomergeArgs),
+    #[command(name = "create")]
+    Create(PrCreateArgs),
+    #[command(name = "list", aliases = &["ls"])]
+    List(PrListArgs),
+    #[command(name = "merge")]
+    Merge(PrMergeArgs),
+}
+
+impl PrCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Assign(args) => args.run(common_args),
+            Self::Automerge(args) => args.run(common_args),
+            Self::Create(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+            Self::Merge(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, OpenPullRequest, Pacer, RemoteRepo};
+use anyhow::{bail, Result};
+use clap::{ArgGroup, Parser};
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("pool").required(true).args(["reviewers", "team"])))]
+/// Request reviewers for the open pull requests of a branch in a round-robin rotation
+///
+/// The reviewers take turns over the pull requests of all repositories that match a regex, and
+/// the ones with the fewest requested reviews of the branch go first, so the load of a campaign
+/// is balanced. Authors never review their own pull requests, and pull requests that already
+/// have enough reviewers of the rotation are left alone.
+pub struct PrAssignArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Head branch of the pull requests
+    pub head: String,
+    #[arg(long, value_delimiter = ',')]
+    /// Users of the rotation, e.g. snomos,flammie
+    pub reviewers: Vec<String>,
+    #[arg(long, short)]
+    /// Slug of the team whose members are the rotation
+    pub team: Option<String>,
+    #[arg(long, short, default_value_t = 1)]
+    /// Number of reviewers of the rotation per pull request
+    pub count: usize,
+    #[arg(long)]
+    /// Also assign the pull requests to the reviewers
+    pub assign: bool,
+    #[arg(long)]
+    /// Only show who would review which pull request
+    pub dry_run: bool,
+}
+
+impl PrAssignArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let pool = match &self.team {
+            Some(team) => github::get_team_members(&organisation, team, &user_token)?,
+            None => self.reviewers.clone(),
+        };
+        if pool.is_empty() {
+            bail!("There is nobody in the rotation");
+        }
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, github::get_open_pull_requests(repo, &user_token)))
+            .collect();
+
+        let mut pulls: Vec<(RemoteRepo, OpenPullRequest)> = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(found) => pulls.extend(
+                    found
+                        .into_iter()
+                        .filter(|pr| pr.head.branch == self.head && !pr.draft)
+                        .map(|pr| (repo.clone(), pr)),
+                ),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up pull requests:",
+                reports.len()
+            );
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        let requests: Vec<_> = pulls
+            .iter()
+            .map(|(_, pr)| Request {
+                author: pr.user.login.clone(),
+                requested: pr
+                    .requested_reviewers
+                    .iter()
+                    .map(|u| u.login.clone())
+                    .collect(),
+            })
+            .collect();
+        let rotation = rotate(&requests, &pool, self.count);
+
+        let planned: Vec<_> = pulls
+            .iter()
+            .zip(rotation)
+            .filter(|(_, reviewers)| !reviewers.is_empty())
+            .collect();
+
+        if planned.is_empty() {
+            println!(
+                "There is no pull requests of branch {} to assign",
+                self.head
+            );
+            return Ok(());
+        }
+
+        if self.dry_run {
+            if let Some(OutputFormat::Json) = common_args.format {
+                let planned: Vec<_> = planned
+                    .iter()
+                    .map(|((repo, pr), reviewers)| {
+                        json!({
+                            "repo": repo.full_name(),
+                            "number": pr.number,
+                            "reviewers": reviewers,
+                        })
+                    })
+                    .collect();
+                println!("{}", json!(planned));
+            } else {
+                to_table(&planned).printstd();
+                println!("\n{} pull requests would get reviewers", planned.len());
+            }
+            return Ok(());
+        }
+
+        progress::begin(planned.len());
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = planned
+            .iter()
+            .map(|((repo, pr), reviewers)| {
+                let name = format!("{}#{}", repo.full_name(), pr.number);
+                progress::track(&name, || {
+                    let result = self.request(repo, pr.number, reviewers, &mut pacer, &user_token);
+                    Status {
+                        pull_request: name.clone(),
+                        reviewers: reviewers.clone(),
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn request(
+        &self,
+        repo: &RemoteRepo,
+        number: u64,
+        reviewers: &[String],
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<()> {
+        pacer.run(|| github::request_reviewers(repo, number, reviewers, token))?;
+        if self.assign {
+            pacer.run(|| github::add_assignees(repo, number, reviewers, token))?;
+        }
+        Ok(())
+    }
+}
+
+/// A pull request as the rotation sees it
+struct Request {
+    author: String,
+    requested: Vec<String>,
+}
+
+/// The reviewers of the pool to request for every pull request, in order
+///
+/// Reviews that are already requested count for the load. The least loaded reviewers go first,
+/// and among equally loaded ones the turn passes on in the order of the pool.
+fn rotate(requests: &[Request], pool: &[String], count: usize) -> Vec<Vec<String>> {
+    let mut load: BTreeMap<&str, usize> = pool.iter().map(|r| (r.as_str(), 0)).collect();
+    for request in requests {
+        for reviewer in &request.requested {
+            if let Some(n) = load.get_mut(reviewer.as_str()) {
+                *n += 1;
+            }
+        }
+    }
+
+    let mut turn = 0;
+    requests
+        .iter()
+        .map(|request| {
+            let has = pool
+                .iter()
+                .filter(|r| request.requested.contains(r))
+                .count();
+            let mut candidates: Vec<_> = (0..pool.len())
+                .map(|i| (turn + i) % pool.len())
+                .filter(|&i| pool[i] != request.author && !request.requested.contains(&pool[i]))
+                .collect();
+            candidates.sort_by_key(|&i| load[pool[i].as_str()]);
+
+            let chosen: Vec<_> = candidates
+                .into_iter()
+                .take(count.saturating_sub(has))
+                .collect();
+            if let Some(&last) = chosen.last() {
+                turn = (last + 1) % pool.len();
+            }
+            chosen
+                .into_iter()
+                .map(|i| {
+                    *load.get_mut(pool[i].as_str()).unwrap() += 1;
+                    pool[i].clone()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct Status {
+    pull_request: String,
+    reviewers: Vec<String>,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let result = match &self.result {
+            Ok(()) => cell!(Fgr -> "Requested"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![
+            cell!(b -> &self.pull_request),
+            cell!(self.reviewers.join(", ")),
+            result,
+        ])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.pull_request, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(planned: &[(&(RemoteRepo, OpenPullRequest), Vec<String>)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Author", "Reviewers"]);
+    for ((repo, pr), reviewers) in planned {
+        table.add_row(row![
+            b -> repo.full_name(),
+            r -> pr.number,
+            pr.title,
+            pr.user.login,
+            reviewers.join(", ")
+        ]);
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Pull request", "Reviewers", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when requesting reviewers:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(author: &str, requested: &[&str]) -> Request {
+        Request {
+            author: author.to_string(),
+            requested: requested.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    fn pool(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rotate() {
+        let pool = pool(&["ana", "bo", "cai"]);
+        let requests = [
+            request("divvunbot", &[]),
+            request("divvunbot", &[]),
+            request("divvunbot", &[]),
+            request("divvunbot", &[]),
+        ];
+        let rotation = rotate(&requests, &pool, 1);
+        assert_eq!(rotation[0], ["ana"]);
+        assert_eq!(rotation[1], ["bo"]);
+        assert_eq!(rotation[2], ["cai"]);
+        assert_eq!(rotation[3], ["ana"]);
+    }
+
+    #[test]
+    fn test_rotate_skips_authors_and_balances_load() {
+        let pool = pool(&["ana", "bo", "cai"]);
+        let requests = [
+            request("divvunbot", &["ana"]),
+            request("bo", &[]),
+            request("divvunbot", &[]),
+        ];
+        let rotation = rotate(&requests, &pool, 1);
+        assert!(rotation[0].is_empty());
+        assert_eq!(rotation[1], ["cai"]);
+        assert_eq!(rotation[2], ["bo"]);
+
+        let rotation = rotate(&[request("ana", &["bo"])], &pool, 2);
+        assert_eq!(rotation[0], ["cai"]);
+    }
+}
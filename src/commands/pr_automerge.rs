@@ -0,0 +1,161 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github::{self, OpenPullRequest, RemoteRepo};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+
+#[derive(Debug, Parser)]
+/// Enable auto-merge on the open pull requests of a branch in all repositories that match a regex
+///
+/// GitHub merges the pull requests as soon as their required checks and reviews pass, so the
+/// pull requests of a campaign land without watching them. Auto-merge has to be allowed in the
+/// settings of the repositories, and needs branch protection with required checks.
+pub struct PrAutomergeArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Head branch of the pull requests
+    pub head: String,
+    #[arg(long, short, value_enum, default_value = "squash")]
+    /// How the pull requests are merged
+    pub merge_method: MergeMethod,
+    #[arg(long)]
+    /// Only show the pull requests that auto-merge would be enabled on
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum MergeMethod {
+    /// Add all commits to the base branch with a merge commit
+    Merge,
+    /// Combine all commits into one commit in the base branch
+    Squash,
+    /// Add all commits to the base branch individually
+    Rebase,
+}
+
+impl MergeMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Merge => "MERGE",
+            Self::Squash => "SQUASH",
+            Self::Rebase => "REBASE",
+        }
+    }
+
+    /// The name in the REST api
+    pub fn rest_name(&self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Squash => "squash",
+            Self::Rebase => "rebase",
+        }
+    }
+}
+
+impl PrAutomergeArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, github::get_open_pull_requests(repo, &user_token)))
+            .collect();
+
+        let mut candidates = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(pulls) => candidates.extend(
+                    pulls
+                        .into_iter()
+                        .filter(|pr| pr.head.branch == self.head)
+                        .map(|pr| (repo.clone(), pr)),
+                ),
+                Err(e) => {
+                    reports.push(ErrorReport::from_message(
+                        &repo.full_name(),
+                        &format!("{:?}", e),
+                    ));
+                }
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up pull requests:",
+                reports.len()
+            );
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if candidates.is_empty() {
+            println!("There is no open pull requests of branch {}", self.head);
+            return Ok(());
+        }
+
+        to_table(&candidates).printstd();
+
+        if self.dry_run {
+            println!(
+                "\nAuto-merge would be enabled on {} pull requests",
+                candidates.len()
+            );
+            return Ok(());
+        }
+
+        for (repo, pr) in candidates {
+            match github::enable_auto_merge(&pr.node_id, self.merge_method.as_str(), &user_token) {
+                Ok(_) => println!("Enabled auto-merge on {}#{}", repo.full_name(), pr.number),
+                Err(e) => println!(
+                    "Failed to enable auto-merge on {}#{} because {:?}",
+                    repo.full_name(),
+                    pr.number,
+                    e
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_table(candidates: &[(RemoteRepo, OpenPullRequest)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Author"]);
+    for (repo, pr) in candidates {
+        table.add_row(row![
+            b -> repo.full_name(),
+            r -> pr.number,
+            pr.title,
+            pr.user.login
+        ]);
+    }
+    table
+}
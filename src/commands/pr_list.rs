@@ -0,0 +1,260 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use super::table::SortArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, OpenPullRequest, PullRequestReview, RemoteRepo};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Parser)]
+/// List the open pull requests of all repositories that match a regex
+///
+/// Every pull request is shown with its author, head branch, age, review state and whether it
+/// is a draft.
+pub struct PrListArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+impl PrListArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, pull_requests(repo, &user_token)))
+            .collect();
+
+        let mut pulls = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(found) => pulls.extend(found),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+        self.sort.sort(&mut pulls, &titles(), PullRequest::to_row)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            let errors: BTreeMap<_, _> = reports.iter().map(|r| (&r.repo, &r.message)).collect();
+            println!("{}", json!({ "pull_requests": pulls, "errors": errors }));
+            return Ok(());
+        }
+
+        if pulls.is_empty() {
+            println!("There is no open pull requests");
+        } else {
+            to_table(&pulls).printstd();
+            println!("\n{} open pull requests", pulls.len());
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up pull requests:",
+                reports.len()
+            );
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+}
+
+fn pull_requests(repo: &RemoteRepo, token: &str) -> Result<Vec<PullRequest>> {
+    let pulls = github::get_open_pull_requests(repo, token)?;
+    pulls
+        .into_par_iter()
+        .map(|pr| {
+            let reviews = github::get_pull_request_reviews(repo, pr.number, token)?;
+            Ok(PullRequest::new(repo, pr, &reviews))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    /// Reviewers are requested and none has approved or requested changes yet
+    Requested,
+    None,
+}
+
+impl ReviewState {
+    /// The state from the latest approving or blocking review of every reviewer
+    pub fn of(reviews: &[PullRequestReview], requested: usize) -> ReviewState {
+        let mut latest: BTreeMap<Option<&str>, &str> = BTreeMap::new();
+        for review in reviews {
+            if matches!(
+                review.state.as_str(),
+                "APPROVED" | "CHANGES_REQUESTED" | "DISMISSED"
+            ) {
+                let reviewer = review.user.as_ref().map(|u| u.login.as_str());
+                latest.insert(reviewer, review.state.as_str());
+            }
+        }
+
+        if latest.values().any(|s| *s == "CHANGES_REQUESTED") {
+            ReviewState::ChangesRequested
+        } else if latest.values().any(|s| *s == "APPROVED") {
+            ReviewState::Approved
+        } else if requested > 0 {
+            ReviewState::Requested
+        } else {
+            ReviewState::None
+        }
+    }
+
+    fn to_cell(self) -> Cell {
+        match self {
+            ReviewState::Approved => cell!(Fg -> "Approved"),
+            ReviewState::ChangesRequested => cell!(Fr -> "Changes requested"),
+            ReviewState::Requested => cell!(Fy -> "Requested"),
+            ReviewState::None => cell!("-"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PullRequest {
+    repo: String,
+    number: u64,
+    title: String,
+    author: String,
+    branch: String,
+    created_at: DateTime<Utc>,
+    review: ReviewState,
+    draft: bool,
+    url: String,
+}
+
+impl PullRequest {
+    fn new(repo: &RemoteRepo, pr: OpenPullRequest, reviews: &[PullRequestReview]) -> PullRequest {
+        PullRequest {
+            repo: repo.full_name(),
+            number: pr.number,
+            review: ReviewState::of(reviews, pr.requested_reviewers.len()),
+            title: pr.title,
+            author: pr.user.login,
+            branch: pr.head.branch,
+            created_at: pr.created_at,
+            draft: pr.draft,
+            url: pr.html_url,
+        }
+    }
+
+    fn to_row(&self) -> Row {
+        let age = (Utc::now() - self.created_at).num_days();
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(r -> self.number),
+            cell!(self.title),
+            cell!(self.author),
+            cell!(self.branch),
+            cell!(r -> format!("{}d", age)),
+            self.review.to_cell(),
+            cell!(if self.draft { "Draft" } else { "" }),
+        ])
+    }
+}
+
+fn titles() -> Row {
+    row!["Repo", "#", "Title", "Author", "Branch", "Age", "Review", "Draft"]
+}
+
+fn to_table(pulls: &[PullRequest]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles());
+    for pr in pulls {
+        table.add_row(pr.to_row());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::PullRequestUser;
+
+    fn review(login: &str, state: &str) -> PullRequestReview {
+        PullRequestReview {
+            user: Some(PullRequestUser {
+                login: login.to_string(),
+            }),
+            state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_review_state() {
+        assert_eq!(ReviewState::of(&[], 0), ReviewState::None);
+        assert_eq!(ReviewState::of(&[], 1), ReviewState::Requested);
+        assert_eq!(
+            ReviewState::of(&[review("snomos", "COMMENTED")], 1),
+            ReviewState::Requested
+        );
+        assert_eq!(
+            ReviewState::of(
+                &[
+                    review("snomos", "CHANGES_REQUESTED"),
+                    review("flammie", "APPROVED"),
+                    review("snomos", "APPROVED"),
+                ],
+                0
+            ),
+            ReviewState::Approved
+        );
+        assert_eq!(
+            ReviewState::of(
+                &[
+                    review("snomos", "APPROVED"),
+                    review("flammie", "CHANGES_REQUESTED"),
+                    review("flammie", "COMMENTED"),
+                ],
+                0
+            ),
+            ReviewState::ChangesRequested
+        );
+        assert_eq!(
+            ReviewState::of(
+                &[review("snomos", "APPROVED"), review("snomos", "DISMISSED")],
+                0
+            ),
+            ReviewState::None
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::pr_automerge::MergeMethod;
+use super::pr_list::ReviewState;
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, CiState, OpenPullRequest, Pacer, PullRequestDetails, RemoteRepo};
+use anyhow::Result;
+use clap::{ArgGroup, Parser};
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("method").multiple(false).args(["squash", "rebase", "merge"])))]
+/// Merge the open pull requests of a branch in all repositories that match a regex
+///
+/// A pull request is only merged when it is approved, its status checks are green and GitHub
+/// can merge it without conflicts. The others are skipped and listed with the reason. The
+/// pull requests are merged one after another after a confirmation.
+pub struct PrMergeArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Head branch of the pull requests
+    pub branch: String,
+    #[arg(long)]
+    /// Squash the commits into one commit, the default
+    pub squash: bool,
+    #[arg(long)]
+    /// Rebase the commits onto the base branch
+    pub rebase: bool,
+    #[arg(long)]
+    /// Add the commits to the base branch with a merge commit
+    pub merge: bool,
+    #[arg(long)]
+    /// Only show which pull requests would be merged or skipped
+    pub dry_run: bool,
+}
+
+impl PrMergeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let checked: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, self.check(repo, &user_token)))
+            .collect();
+
+        let mut ready = vec![];
+        let mut statuses = vec![];
+        for (repo, result) in checked {
+            match result {
+                Ok(pulls) => {
+                    for (pr, skip) in pulls {
+                        match skip {
+                            Some(reason) => statuses.push(Status::skipped(repo, &pr, reason)),
+                            None => ready.push((repo.clone(), pr)),
+                        }
+                    }
+                }
+                Err(e) => statuses.push(Status {
+                    repo: repo.full_name(),
+                    number: None,
+                    result: Err(format!("{:?}", e)),
+                }),
+            }
+        }
+
+        if ready.is_empty() && statuses.is_empty() {
+            println!("There is no open pull requests of branch {}", self.branch);
+            return Ok(());
+        }
+
+        if !ready.is_empty() && common_args.format != Some(OutputFormat::Json) {
+            to_candidate_table(&ready).printstd();
+        }
+
+        if self.dry_run {
+            if let Some(OutputFormat::Json) = common_args.format {
+                let ready: Vec<_> = ready
+                    .iter()
+                    .map(|(repo, pr)| json!({ "repo": repo.full_name(), "number": pr.number }))
+                    .collect();
+                println!("{}", json!({ "ready": ready, "skipped": statuses }));
+            } else {
+                println!("\n{} pull requests would be merged", ready.len());
+                summarize(&statuses);
+            }
+            return Ok(());
+        }
+
+        if !ready.is_empty() {
+            if !confirm(ready.len())? {
+                println!("Command is aborted. Nothing change!");
+                return Ok(());
+            }
+
+            progress::begin(ready.len());
+            let mut pacer = Pacer::new();
+            for (repo, pr) in &ready {
+                let status = progress::track(&repo.full_name(), || {
+                    let result = pacer.run(|| {
+                        github::merge_pull_request(
+                            repo,
+                            pr.number,
+                            &pr.head.sha,
+                            self.method().rest_name(),
+                            &user_token,
+                        )
+                    });
+                    Status {
+                        repo: repo.full_name(),
+                        number: Some(pr.number),
+                        result: result
+                            .map(|_| Outcome::Merged)
+                            .map_err(|e| format!("{:?}", e)),
+                    }
+                });
+                statuses.push(status);
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        println!();
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn method(&self) -> MergeMethod {
+        if self.rebase {
+            MergeMethod::Rebase
+        } else if self.merge {
+            MergeMethod::Merge
+        } else {
+            MergeMethod::Squash
+        }
+    }
+
+    /// The open pull requests of the branch with the reason to skip each of them
+    fn check(
+        &self,
+        repo: &RemoteRepo,
+        token: &str,
+    ) -> Result<Vec<(OpenPullRequest, Option<String>)>> {
+        let pulls: Vec<_> = github::get_open_pull_requests(repo, token)?
+            .into_iter()
+            .filter(|pr| pr.head.branch == self.branch)
+            .collect();
+
+        let mut checked = vec![];
+        for pr in pulls {
+            let reason = if pr.draft {
+                Some("Draft".to_string())
+            } else {
+                let reviews = github::get_pull_request_reviews(repo, pr.number, token)?;
+                let review = ReviewState::of(&reviews, pr.requested_reviewers.len());
+                let ci = github::get_ci_state(repo, &pr.head.sha, token)?;
+                let details = mergeability(repo, pr.number, token)?;
+                skip_reason(review, ci, &details)
+            };
+            checked.push((pr, reason));
+        }
+        Ok(checked)
+    }
+}
+
+/// GitHub computes the mergeability in the background, so it is asked again while it is unknown
+fn mergeability(repo: &RemoteRepo, number: u64, token: &str) -> Result<PullRequestDetails> {
+    let mut details = github::get_pull_request(repo, number, token)?;
+    for _ in 0..5 {
+        if details.mergeable.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_secs(2));
+        details = github::get_pull_request(repo, number, token)?;
+    }
+    Ok(details)
+}
+
+/// Why a pull request cannot be merged, None when it can
+fn skip_reason(review: ReviewState, ci: CiState, details: &PullRequestDetails) -> Option<String> {
+    let reason = match review {
+        ReviewState::Approved => None,
+        ReviewState::ChangesRequested => Some("Changes requested"),
+        ReviewState::Requested | ReviewState::None => Some("Not approved"),
+    }
+    .or(match ci {
+        CiState::Success => None,
+        CiState::Pending => Some("Checks pending"),
+        CiState::Failure => Some("Checks failed"),
+        CiState::Unknown => Some("No status checks"),
+    })
+    .or(
+        match (details.mergeable, details.mergeable_state.as_str()) {
+            (Some(false), _) | (_, "dirty") => Some("Conflicts"),
+            (None, _) => Some("Mergeability unknown"),
+            (_, "blocked") => Some("Blocked by branch protection"),
+            (_, "behind") => Some("Behind base branch"),
+            _ => None,
+        },
+    )?;
+    Some(reason.to_string())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "reason", rename_all = "snake_case")]
+enum Outcome {
+    Merged,
+    Skipped(String),
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    number: Option<u64>,
+    result: Result<Outcome, String>,
+}
+
+impl Status {
+    fn skipped(repo: &RemoteRepo, pr: &OpenPullRequest, reason: String) -> Status {
+        Status {
+            repo: repo.full_name(),
+            number: Some(pr.number),
+            result: Ok(Outcome::Skipped(reason)),
+        }
+    }
+
+    fn to_row(&self) -> Row {
+        let number = self.number.map(|n| n.to_string()).unwrap_or_default();
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(r -> number),
+            self.to_cell(),
+        ])
+    }
+
+    fn to_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Outcome::Merged) => cell!(Fgr -> "Merged"),
+            Ok(Outcome::Skipped(reason)) => cell!(Fy -> format!("Skipped: {}", reason)),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn merged(&self) -> bool {
+        matches!(self.result, Ok(Outcome::Merged))
+    }
+
+    fn to_error_report(&self) -> Option<ErrorReport> {
+        let repo = match self.number {
+            Some(n) => format!("{}#{}", self.repo, n),
+            None => self.repo.clone(),
+        };
+        match &self.result {
+            Ok(Outcome::Merged) => None,
+            Ok(Outcome::Skipped(reason)) => Some(ErrorReport::from_message(
+                &repo,
+                &format!("Skipped: {}", reason),
+            )),
+            Err(e) => Some(ErrorReport::from_message(&repo, e)),
+        }
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.result
+            .as_ref()
+            .err()
+            .map(|e| ErrorReport::from_message(&self.repo, e))
+    }
+}
+
+fn to_candidate_table(ready: &[(RemoteRepo, OpenPullRequest)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Author"]);
+    for (repo, pr) in ready {
+        table.add_row(row![
+            b -> repo.full_name(),
+            r -> pr.number,
+            pr.title,
+            pr.user.login
+        ]);
+    }
+    table
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    if statuses.is_empty() {
+        return;
+    }
+    to_table(statuses).printstd();
+
+    let merged = statuses.iter().filter(|s| s.merged()).count();
+    if merged > 0 {
+        let msg = format!("\nMerged {} pull requests!", merged);
+        println!("{}", msg.green());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| s.to_error_report())
+        .collect();
+    if reports.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!(
+            "There {} pull requests that are skipped or failed to merge:",
+            reports.len()
+        );
+        println!("\n{}\n", msg.red());
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to merge {} pull request(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(mergeable: Option<bool>, state: &str) -> PullRequestDetails {
+        PullRequestDetails {
+            mergeable,
+            mergeable_state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_skip_reason() {
+        let clean = details(Some(true), "clean");
+        assert_eq!(
+            skip_reason(ReviewState::Approved, CiState::Success, &clean),
+            None
+        );
+        assert_eq!(
+            skip_reason(ReviewState::Requested, CiState::Success, &clean).as_deref(),
+            Some("Not approved")
+        );
+        assert_eq!(
+            skip_reason(ReviewState::Approved, CiState::Pending, &clean).as_deref(),
+            Some("Checks pending")
+        );
+        assert_eq!(
+            skip_reason(ReviewState::Approved, CiState::Unknown, &clean).as_deref(),
+            Some("No status checks")
+        );
+        assert_eq!(
+            skip_reason(
+                ReviewState::Approved,
+                CiState::Success,
+                &details(Some(false), "dirty")
+            )
+            .as_deref(),
+            Some("Conflicts")
+        );
+        assert_eq!(
+            skip_reason(
+                ReviewState::Approved,
+                CiState::Success,
+                &details(Some(true), "blocked")
+            )
+            .as_deref(),
+            Some("Blocked by branch protection")
+        );
+        assert_eq!(
+            skip_reason(
+                ReviewState::ChangesRequested,
+                CiState::Failure,
+                &details(None, "unknown")
+            )
+            .as_deref(),
+            Some("Changes requested")
+        );
+    }
+}
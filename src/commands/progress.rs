@@ -0,0 +1,73 @@
+use super::errors::ErrorReport;
+use crate::cli::ProgressFormat;
+use crate::path::owner_name;
+use serde_json::json;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+static FORMAT: OnceLock<Option<ProgressFormat>> = OnceLock::new();
+
+pub fn init(format: Option<ProgressFormat>) {
+    let _ = FORMAT.set(format);
+}
+
+fn enabled() -> bool {
+    matches!(FORMAT.get(), Some(Some(ProgressFormat::Json)))
+}
+
+/// Result of the work on one repository, as far as progress events are concerned
+pub trait Tracked {
+    fn error_report(&self) -> Option<ErrorReport>;
+}
+
+/// Announce how many repositories a command is going to work on
+pub fn begin(total: usize) {
+    emit(json!({
+        "event": "begin",
+        "total": total,
+    }));
+}
+
+/// Emit start and finish events around the work on a repository
+pub fn track<S: Tracked>(repo: &str, work: impl FnOnce() -> S) -> S {
+    if !enabled() {
+        return work();
+    }
+
+    emit(json!({
+        "event": "start",
+        "repo": repo,
+    }));
+    let started = Instant::now();
+
+    let status = work();
+
+    let error = status.error_report();
+    emit(json!({
+        "event": "finish",
+        "repo": repo,
+        "status": if error.is_some() { "failed" } else { "success" },
+        "category": error.as_ref().map(|e| e.category),
+        "error": error.as_ref().map(|e| &e.message),
+        "elapsed_ms": started.elapsed().as_millis() as u64,
+    }));
+
+    status
+}
+
+/// Same as `track` for a local repository directory
+pub fn track_dir<S: Tracked>(dir: &Path, work: impl FnOnce() -> S) -> S {
+    let repo = match (owner_name(dir), dir.file_name()) {
+        (Ok(owner), Some(name)) => format!("{}/{}", owner, name.to_string_lossy()),
+        _ => dir.display().to_string(),
+    };
+    track(&repo, work)
+}
+
+/// Progress events go to stderr as json lines, so they don't mix with the results
+fn emit(event: serde_json::Value) {
+    if enabled() {
+        eprintln!("{}", event);
+    }
+}
@@ -0,0 +1,35 @@
+use super::project_add::*;
+use super::project_fields::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Track issues and pull requests of many repositories on a Projects board
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    command: ProjectCommand,
+}
+
+impl ProjectArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum ProjectCommand {
+    #[command(name = "add")]
+    Add(ProjectAddArgs),
+    #[command(name = "fields")]
+    Fields(ProjectFieldsArgs),
+}
+
+impl ProjectCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Add(args) => args.run(common_args),
+            Self::Fields(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::search_issues::Kind;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, ProjectField, RemoteRepo};
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Add the open issues and pull requests of all repositories that match a regex to a board
+///
+/// This puts the issues and pull requests of a campaign on one board, and can set a field of
+/// them, e.g. a Campaign field to "Campaign 2025". Issues and pull requests that are already on
+/// the board keep their item, only the field is set.
+pub struct ProjectAddArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Number of the project, as in the url of the board
+    pub project: u64,
+    #[arg(long, short, value_enum)]
+    /// Only add issues or only pull requests
+    pub kind: Option<Kind>,
+    #[arg(long, short, value_delimiter = ',')]
+    /// Only add issues with all of these labels
+    pub label: Vec<String>,
+    #[arg(long)]
+    /// Only add pull requests of this head branch
+    pub head: Option<String>,
+    #[arg(long, requires = "value")]
+    /// Name of the field to set on the items
+    pub field: Option<String>,
+    #[arg(long, requires = "field")]
+    /// Value of the field, the name of an option for single select fields
+    pub value: Option<String>,
+    #[arg(long)]
+    /// Only show what would be added
+    pub dry_run: bool,
+}
+
+impl ProjectAddArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let project = github::get_project(&organisation, self.project, &user_token)?;
+        let field = match (&self.field, &self.value) {
+            (Some(name), Some(value)) => {
+                let field = project
+                    .fields
+                    .iter()
+                    .find(|f| f.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| anyhow!("There is no field {:?} in {}", name, project.title))?;
+                Some((field.id.clone(), field_value(field, value)?))
+            }
+            _ => None,
+        };
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, self.items(repo, &user_token)))
+            .collect();
+
+        let mut items = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(found) => items.extend(found),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up issues and pull requests:",
+                reports.len()
+            );
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if items.is_empty() {
+            println!("There is no open issues or pull requests to add");
+            return Ok(());
+        }
+
+        if self.dry_run {
+            if let Some(OutputFormat::Json) = common_args.format {
+                println!("{}", json!(items));
+            } else {
+                to_table(&items).printstd();
+                println!(
+                    "\n{} items would be added to {}",
+                    items.len(),
+                    project.title
+                );
+            }
+            return Ok(());
+        }
+
+        progress::begin(items.len());
+        let statuses: Vec<_> = items
+            .into_iter()
+            .map(|item| {
+                progress::track(&item.name(), || {
+                    let result = github::add_project_item(&project.id, &item.node_id, &user_token)
+                        .and_then(|item_id| match &field {
+                            Some((field_id, value)) => github::set_project_field(
+                                &project.id,
+                                &item_id,
+                                field_id,
+                                value,
+                                &user_token,
+                            ),
+                            None => Ok(()),
+                        });
+                    Status {
+                        item,
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, &project.title);
+
+        Ok(())
+    }
+
+    /// The open issues and pull requests of a repository that match the filters
+    fn items(&self, repo: &RemoteRepo, token: &str) -> Result<Vec<Item>> {
+        let mut items = vec![];
+        if self.kind != Some(Kind::Pr) {
+            for issue in github::get_open_issues(repo, &self.label, None, token)? {
+                items.push(Item {
+                    repo: repo.full_name(),
+                    number: issue.number,
+                    kind: "Issue",
+                    title: issue.title,
+                    node_id: issue.node_id,
+                });
+            }
+        }
+        if self.kind != Some(Kind::Issue) {
+            for pr in github::get_open_pull_requests(repo, token)? {
+                if self.head.as_ref().is_some_and(|h| *h != pr.head.branch) {
+                    continue;
+                }
+                items.push(Item {
+                    repo: repo.full_name(),
+                    number: pr.number,
+                    kind: "PR",
+                    title: pr.title,
+                    node_id: pr.node_id,
+                });
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// The input of a field value, `value` is the name of an option for single select fields
+fn field_value(field: &ProjectField, value: &str) -> Result<serde_json::Value> {
+    Ok(match field.data_type.as_str() {
+        "TEXT" => json!({ "text": value }),
+        "NUMBER" => json!({ "number": value.parse::<f64>()? }),
+        "DATE" => json!({ "date": value }),
+        "SINGLE_SELECT" => {
+            let option = field
+                .options
+                .iter()
+                .find(|o| o.name.eq_ignore_ascii_case(value))
+                .ok_or_else(|| {
+                    let options: Vec<_> = field.options.iter().map(|o| o.name.as_str()).collect();
+                    anyhow!(
+                        "{:?} is not an option of field {}, which has {}",
+                        value,
+                        field.name,
+                        options.join(", ")
+                    )
+                })?;
+            json!({ "singleSelectOptionId": option.id })
+        }
+        data_type => bail!("Field {} of type {} cannot be set", field.name, data_type),
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct Item {
+    repo: String,
+    number: u64,
+    kind: &'static str,
+    title: String,
+    #[serde(skip)]
+    node_id: String,
+}
+
+impl Item {
+    fn name(&self) -> String {
+        format!("{}#{}", self.repo, self.number)
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    #[serde(flatten)]
+    item: Item,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let result = match &self.result {
+            Ok(()) => cell!(Fgr -> "Added"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![
+            cell!(b -> &self.item.repo),
+            cell!(r -> self.item.number),
+            cell!(self.item.kind),
+            cell!(self.item.title),
+            result,
+        ])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.item.name(), e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(items: &[Item]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Kind", "Title"]);
+    for item in items {
+        table.add_row(row![b -> item.repo, r -> item.number, item.kind, item.title]);
+    }
+    table
+}
+
+fn summarize(statuses: &[Status], project: &str) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Kind", "Title", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let added = statuses.len() - errors.len();
+    if added > 0 {
+        let msg = format!("\nAdded {} items to {}!", added, project);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when adding items:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::ProjectFieldOption;
+
+    fn field(data_type: &str, options: &[&str]) -> ProjectField {
+        ProjectField {
+            id: "PVTF_1".to_string(),
+            name: "Campaign".to_string(),
+            data_type: data_type.to_string(),
+            options: options
+                .iter()
+                .enumerate()
+                .map(|(i, name)| ProjectFieldOption {
+                    id: format!("option{}", i),
+                    name: name.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_field_value() {
+        assert_eq!(
+            field_value(&field("TEXT", &[]), "Campaign 2025").unwrap(),
+            json!({ "text": "Campaign 2025" })
+        );
+        assert_eq!(
+            field_value(&field("NUMBER", &[]), "3").unwrap(),
+            json!({ "number": 3.0 })
+        );
+        assert!(field_value(&field("NUMBER", &[]), "three").is_err());
+        assert_eq!(
+            field_value(
+                &field("SINGLE_SELECT", &["Campaign 2024", "Campaign 2025"]),
+                "campaign 2025"
+            )
+            .unwrap(),
+            json!({ "singleSelectOptionId": "option1" })
+        );
+        assert!(field_value(&field("SINGLE_SELECT", &["Campaign 2024"]), "Campaign 2025").is_err());
+        assert!(field_value(&field("ITERATION", &[]), "Sprint 1").is_err());
+    }
+}
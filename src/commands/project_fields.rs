@@ -0,0 +1,56 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{format, row, Table};
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Show the fields of a Projects board of an organisation and their options
+///
+/// These are the fields and values that project add can set.
+pub struct ProjectFieldsArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Number of the project, as in the url of the board
+    pub project: u64,
+}
+
+impl ProjectFieldsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let project = github::get_project(&organisation, self.project, &user_token)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            let fields: Vec<_> = project
+                .fields
+                .iter()
+                .map(|f| {
+                    let options: Vec<_> = f.options.iter().map(|o| &o.name).collect();
+                    json!({ "name": f.name, "type": f.data_type, "options": options })
+                })
+                .collect();
+            println!("{}", json!({ "title": project.title, "fields": fields }));
+            return Ok(());
+        }
+
+        println!("{}\n", project.title);
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Field", "Type", "Options"]);
+        for field in &project.fields {
+            let options: Vec<_> = field.options.iter().map(|o| o.name.as_str()).collect();
+            table.add_row(row![b -> field.name, field.data_type, options.join(", ")]);
+        }
+        table.printstd();
+
+        Ok(())
+    }
+}
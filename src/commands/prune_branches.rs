@@ -0,0 +1,330 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git::{self, GitCredential, MergedBranch};
+use crate::path::dir_name;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use clap::Parser;
+use colored::*;
+use git2::BranchType;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Delete local branches that are merged into the default branch
+///
+/// A branch is merged when all its commits are in origin's default branch, so run fetch first.
+/// The checked out branch is never deleted. With --remote, origin is fetched first and the
+/// branches on origin that the deleted branches track are deleted too, when they are merged as
+/// well and still point to the commit that was checked.
+pub struct PruneBranchesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Only delete branches whose last commit is older than this number of days
+    pub older_than: Option<i64>,
+    #[arg(long)]
+    /// Delete the branches on origin that the deleted branches track
+    pub remote: bool,
+    #[arg(long, short = 'n')]
+    /// Only show the branches that would be deleted
+    pub dry_run: bool,
+}
+
+impl PruneBranchesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let cred = if self.remote {
+            Some(GitCredential::from(&common::user()?))
+        } else {
+            None
+        };
+
+        let now = Utc::now();
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| self.find(d, now, cred.as_ref()))
+            .collect();
+        statuses.retain(|s| s.has_error() || s.branch_count() > 0);
+
+        let to_prune = statuses.iter().map(|s| s.branch_count()).sum::<usize>();
+        if to_prune > 0 && !self.dry_run {
+            summarize(&statuses, false);
+            if confirm(to_prune, self.remote)? {
+                statuses
+                    .par_iter_mut()
+                    .filter(|s| !s.has_error())
+                    .for_each(|s| s.prune(cred.as_ref()));
+            } else {
+                println!("Command is aborted. Nothing change!");
+                return Ok(());
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, !self.dry_run);
+        }
+
+        Ok(())
+    }
+
+    fn find(&self, dir: &PathBuf, now: DateTime<Utc>, cred: Option<&GitCredential>) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let find = || -> Result<Vec<Candidate>> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            if let Some(cred) = cred {
+                git::fetch_quiet(&git_repo, "origin", Some(cred.clone()))
+                    .context("Cannot fetch origin")?;
+            }
+            let default_branch = git::remote_head(&git_repo, "origin")
+                .ok_or_else(|| anyhow!("Cannot find the default branch of origin"))?;
+            let target = git_repo
+                .find_branch(&format!("origin/{}", default_branch), BranchType::Remote)?
+                .get()
+                .peel_to_commit()?
+                .id();
+
+            let mut candidates = vec![];
+            for branch in git::merged_branches(&git_repo, target)? {
+                if branch.name == default_branch {
+                    continue;
+                }
+                let last_commit = DateTime::from_timestamp(branch.time, 0).unwrap_or(now);
+                if let Some(days) = self.older_than {
+                    if now - last_commit <= Duration::days(days) {
+                        continue;
+                    }
+                }
+                let remote = if self.remote {
+                    remote_to_prune(&git_repo, &branch, &default_branch, target)
+                } else {
+                    None
+                };
+                candidates.push(Candidate {
+                    name: branch.name,
+                    last_commit,
+                    remote: remote.as_ref().map(|(name, _)| name.clone()),
+                    remote_tip: remote.map(|(_, tip)| tip),
+                });
+            }
+            Ok(candidates)
+        };
+
+        Status {
+            repo,
+            dir: dir.clone(),
+            result: find().map_err(|e| format!("{:?}", e)),
+            pruned: false,
+        }
+    }
+}
+
+/// The branch on origin that a merged branch tracks and its tip, when it is merged too
+fn remote_to_prune(
+    git_repo: &git2::Repository,
+    branch: &MergedBranch,
+    default_branch: &str,
+    target: git2::Oid,
+) -> Option<(String, git2::Oid)> {
+    let name = branch.upstream.as_deref()?.strip_prefix("origin/")?;
+    if name == default_branch {
+        return None;
+    }
+    let tip = git_repo
+        .find_branch(&format!("origin/{}", name), BranchType::Remote)
+        .ok()?
+        .get()
+        .target()?;
+    let merged = tip == target || git_repo.graph_descendant_of(target, tip).ok()?;
+    merged.then(|| (name.to_string(), tip))
+}
+
+#[derive(Debug, Serialize)]
+struct Candidate {
+    name: String,
+    last_commit: DateTime<Utc>,
+    /// The branch on origin that is deleted with it
+    remote: Option<String>,
+    /// The commit of the branch on origin that was checked, it is only deleted if it still has it
+    #[serde(skip)]
+    remote_tip: Option<git2::Oid>,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    result: Result<Vec<Candidate>, String>,
+    pruned: bool,
+}
+
+impl Status {
+    fn branch_count(&self) -> usize {
+        self.result.as_ref().map(|c| c.len()).unwrap_or(0)
+    }
+
+    fn prune(&mut self, cred: Option<&GitCredential>) {
+        let candidates = match &self.result {
+            Ok(candidates) => candidates,
+            Err(_) => return,
+        };
+
+        let prune = || -> Result<()> {
+            let git_repo = git::open(&self.dir)?;
+            for candidate in candidates {
+                if let (Some(remote), Some(tip)) = (&candidate.remote, candidate.remote_tip) {
+                    git::delete_with_lease(&git_repo, remote, tip, cred)
+                        .with_context(|| format!("Cannot delete origin/{}", remote))?;
+                }
+                git_repo
+                    .find_branch(&candidate.name, BranchType::Local)?
+                    .delete()
+                    .with_context(|| format!("Cannot delete {}", candidate.name))?;
+            }
+            Ok(())
+        };
+
+        match prune() {
+            Ok(()) => self.pruned = true,
+            Err(e) => self.result = Err(format!("{:?}", e)),
+        }
+    }
+
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(candidates) => {
+                let branches: Vec<_> = candidates
+                    .iter()
+                    .map(|c| match &c.remote {
+                        Some(remote) => format!("{} (+ origin/{})", c.name, remote),
+                        None => c.name.clone(),
+                    })
+                    .collect();
+                let last_commits: Vec<_> = candidates
+                    .iter()
+                    .map(|c| {
+                        c.last_commit
+                            .with_timezone(&Local)
+                            .format("%Y-%m-%d")
+                            .to_string()
+                    })
+                    .collect();
+                Row::new(vec![
+                    cell!(b -> &self.repo),
+                    cell!(branches.join("\n")),
+                    cell!(last_commits.join("\n")),
+                    self.pruned_cell(),
+                ])
+            }
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+            ]),
+        }
+    }
+
+    fn pruned_cell(&self) -> Cell {
+        if self.pruned {
+            cell!(Fg -> "Deleted")
+        } else {
+            cell!("-")
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status], done: bool) {
+    if statuses.is_empty() {
+        println!("{}", "There is no merged branches to delete".green());
+        return;
+    }
+
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Merged branches", "Last commit", "Result"]);
+    table.printstd();
+
+    if !done {
+        let count = statuses.iter().map(|s| s.branch_count()).sum::<usize>();
+        println!("\n{} merged branches can be deleted\n", count);
+        return;
+    }
+
+    let pruned = statuses
+        .iter()
+        .filter(|s| s.pruned)
+        .map(|s| s.branch_count())
+        .sum::<usize>();
+    if pruned > 0 {
+        let msg = format!("\nDeleted {} merged branches!", pruned);
+        println!("{}", msg.green());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when deleting branches:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, remote: bool) -> Result<bool> {
+    let place = if remote {
+        "local and on origin"
+    } else {
+        "local"
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to delete {} merged branches ({})?\nEnter YES to continue",
+            count, place
+        ),
+        "YES",
+    )
+}
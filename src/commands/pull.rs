@@ -1,12 +1,20 @@
+use super::checkout::checkout_branch;
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::table::SortArgs;
+use crate::branch_map::BranchMap;
 use crate::filter::Filter;
 use crate::cli::Args as CommonArgs;
 use crate::git;
 use crate::git::GitCredential;
 use crate::git::PullStatus;
 use crate::path;
+use crate::repo_state::RepoState;
 use crate::user::User;
 use anyhow::{Context, Error, Result};
+use chrono::Utc;
+use git2::Repository;
 use clap::Parser;
 use colored::*;
 use prettytable::{cell, format, row, Cell, Row, Table};
@@ -39,6 +47,24 @@ pub struct PullArgs {
     #[arg(long, short)]
     /// Option to create a merge commit instead of rebase
     pub merge: bool,
+    #[arg(long)]
+    /// Option to leave Git LFS files as pointer files
+    ///
+    /// Use gut lfs fetch later to download them for the repositories that need them.
+    pub no_lfs: bool,
+    #[arg(long, default_value = "origin")]
+    /// Remote to pull from, e.g. upstream for forks
+    pub remote: String,
+    #[arg(long)]
+    /// Fetch every other remote of the repositories as well before pulling
+    pub all_remotes: bool,
+    #[arg(long)]
+    /// Toml file with the branch to pull per repository, which is checked out first
+    ///
+    /// The repositories that are not in it pull their current branch.
+    pub branch_map: Option<PathBuf>,
+    #[command(flatten)]
+    pub sort: SortArgs,
 }
 
 impl PullArgs {
@@ -46,6 +72,11 @@ impl PullArgs {
         let user = common::user()?;
         let root = common::root()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
+        let branch_map = self
+            .branch_map
+            .as_deref()
+            .map(BranchMap::load)
+            .transpose()?;
 
         let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
 
@@ -57,10 +88,12 @@ impl PullArgs {
             return Ok(());
         }
 
-        let statuses: Vec<_> = sub_dirs
+        progress::begin(sub_dirs.len());
+        let mut statuses: Vec<_> = sub_dirs
             .par_iter()
-            .map(|d| pull(d, &user, self.stash, self.merge))
+            .map(|d| progress::track_dir(d, || pull(d, &user, self, branch_map.as_ref())))
             .collect();
+        self.sort.sort(&mut statuses, &titles(), |s| s.to_row())?;
 
         match common_args.format.unwrap() {
             OutputFormat::Json => println!("{}", json!(statuses)),
@@ -110,13 +143,8 @@ fn summarize(statuses: &[Status]) {
         let msg = format!("There {} errors when process command:", errors.len());
         println!("{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
 
@@ -124,14 +152,26 @@ fn to_table(statuses: &[Status]) -> Table {
     let rows: Vec<_> = statuses.par_iter().map(|s| s.to_row()).collect();
     let mut table = Table::init(rows);
     table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-    table.set_titles(row!["Repo", "Pull Status", "Repo Status", "Stash Status"]);
+    table.set_titles(titles());
     table
 }
 
-fn pull(dir: &PathBuf, user: &User, stash: bool, merge: bool) -> Status {
+fn titles() -> Row {
+    row![
+        "Repo",
+        "Remote",
+        "Pull Status",
+        "Repo Status",
+        "Stash Status",
+        "LFS"
+    ]
+}
+
+fn pull(dir: &PathBuf, user: &User, args: &PullArgs, branch_map: Option<&BranchMap>) -> Status {
     let mut dir_name = "".to_string();
     let mut repo_status = RepoStatus::Clean;
     let mut stash_status = StashStatus::No;
+    let mut lfs_status = LfsStatus::No;
 
     let mut pull = || -> Result<PullStatus> {
         dir_name = path::dir_name(dir)?;
@@ -140,28 +180,47 @@ fn pull(dir: &PathBuf, user: &User, stash: bool, merge: bool) -> Status {
         let mut git_repo =
             git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
 
+        if args.all_remotes {
+            fetch_other_remotes(&git_repo, user, &args.remote)?;
+        }
+        let mapped_branch = match branch_map {
+            Some(map) => map.get(&path::owner_name(dir)?, &dir_name),
+            None => None,
+        };
+
         let status = git::status(&git_repo, false)?;
 
         if !status.is_dirty() {
             stash_status = StashStatus::No;
             repo_status = RepoStatus::Clean;
+            if let Some(branch) = mapped_branch {
+                switch_branch(&git_repo, branch, user, &args.remote)?;
+            }
             // pull
             let cred = GitCredential::from(user);
-            let status = git::pull(&git_repo, "origin", Some(cred), merge)?;
+            let status = git::pull(&git_repo, &args.remote, Some(cred.clone()), args.merge)?;
+            RepoState::record(dir, |s| s.last_pull = Some(Utc::now()));
+            lfs_status = lfs_pull(&git_repo, &cred, &status, args.no_lfs);
             Ok(status)
         } else {
             if status.conflicted.is_empty() {
                 repo_status = RepoStatus::Dirty;
 
-                if stash {
+                if args.stash {
                     // do stash
                     stash_status = match git::stash(&mut git_repo, None) {
                         Ok(_) => StashStatus::Success,
                         Err(e) => StashStatus::Failed(Arc::new(e)),
                     };
+                    if let Some(branch) = mapped_branch {
+                        switch_branch(&git_repo, branch, user, &args.remote)?;
+                    }
                     // pull
                     let cred = GitCredential::from(user);
-                    let status = git::pull(&git_repo, "origin", Some(cred), merge)?;
+                    let status =
+                        git::pull(&git_repo, &args.remote, Some(cred.clone()), args.merge)?;
+                    RepoState::record(dir, |s| s.last_pull = Some(Utc::now()));
+                    lfs_status = lfs_pull(&git_repo, &cred, &status, args.no_lfs);
                     return Ok(status);
                 }
             } else {
@@ -177,9 +236,54 @@ fn pull(dir: &PathBuf, user: &User, stash: bool, merge: bool) -> Status {
 
     Status {
         repo: dir_name,
+        remote: args.remote.clone(),
         status,
         repo_status,
         stash_status,
+        lfs_status,
+    }
+}
+
+/// Check out the branch of the branch map, when another one is checked out
+fn switch_branch(git_repo: &Repository, branch: &str, user: &User, remote: &str) -> Result<()> {
+    if git::head_shorthand(git_repo)? == branch {
+        return Ok(());
+    }
+    checkout_branch(git_repo, branch, user, remote, true)
+        .with_context(|| format!("Cannot check out {} of the branch map", branch))
+}
+
+/// Fetch the remotes other than the one that is pulled from, which the pull fetches itself
+fn fetch_other_remotes(git_repo: &Repository, user: &User, pull_remote: &str) -> Result<()> {
+    let cred = GitCredential::from(user);
+    for remote in git_repo.remotes()?.iter().flatten() {
+        if remote != pull_remote {
+            git::fetch_quiet(git_repo, remote, Some(cred.clone()))
+                .with_context(|| format!("Cannot fetch {}", remote))?;
+        }
+    }
+    Ok(())
+}
+
+fn lfs_pull(
+    git_repo: &Repository,
+    cred: &GitCredential,
+    status: &PullStatus,
+    no_lfs: bool,
+) -> LfsStatus {
+    if matches!(status, PullStatus::WithConflict | PullStatus::SkipConflict) {
+        return LfsStatus::No;
+    }
+    let result = if no_lfs {
+        git::lfs_skip(git_repo)
+    } else {
+        git::lfs_pull(git_repo, Some(cred))
+    };
+    match result {
+        Ok(true) if no_lfs => LfsStatus::Skipped,
+        Ok(true) => LfsStatus::Pulled,
+        Ok(false) => LfsStatus::No,
+        Err(e) => LfsStatus::Failed(Arc::new(e)),
     }
 }
 
@@ -196,26 +300,30 @@ where
 #[derive(Debug, Clone, Serialize)]
 struct Status {
     repo: String,
+    remote: String,
     #[serde(serialize_with = "serialize_status")]
     status: Result<PullStatus, Arc<anyhow::Error>>,
     repo_status: RepoStatus,
     stash_status: StashStatus,
+    lfs_status: LfsStatus,
 }
 
 impl Status {
     fn to_row(&self) -> Row {
         Row::new(vec![
             cell!(b -> &self.repo),
+            cell!(&self.remote),
             self.status_to_cell(),
             self.repo_status.to_cell(),
             self.stash_status.to_cell(),
+            self.lfs_status.to_cell(),
         ])
     }
 
     fn status_to_cell(&self) -> Cell {
         match &self.status {
             Ok(s) => merge_status_to_cell(s),
-            Err(_) => cell!(Frr -> "Failed"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 
@@ -225,22 +333,29 @@ impl Status {
     }
 
     fn has_error(&self) -> bool {
-        self.status.is_err() || matches!(self.stash_status, StashStatus::Failed(_))
+        self.status.is_err()
+            || matches!(self.stash_status, StashStatus::Failed(_))
+            || matches!(self.lfs_status, LfsStatus::Failed(_))
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let StashStatus::Failed(e1) = &self.stash_status {
             e1
         } else if let Err(e2) = &self.status {
             e2
+        } else if let LfsStatus::Failed(e3) = &self.lfs_status {
+            e3
         } else {
             panic!("This should have an error here");
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
     }
 }
 
@@ -285,6 +400,26 @@ impl StashStatus {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+enum LfsStatus {
+    No,
+    Pulled,
+    Skipped,
+    #[serde(serialize_with = "serialize_error")]
+    Failed(Arc<Error>),
+}
+
+impl LfsStatus {
+    fn to_cell(&self) -> Cell {
+        match &self {
+            LfsStatus::No => cell!(r -> "-"),
+            LfsStatus::Pulled => cell!(Fgr -> "Pulled"),
+            LfsStatus::Skipped => cell!(Fyr -> "Skipped"),
+            LfsStatus::Failed(e) => ErrorCategory::of(e).failed_cell(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 enum RepoStatus {
     Clean,
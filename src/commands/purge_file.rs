@@ -0,0 +1,351 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::github::{self, RemoteRepo};
+use crate::path::{dir_name, owner_name};
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{BranchType, Oid, Repository};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Remove files from the whole history of local repositories that match a regex
+///
+/// This rewrites history with git filter-repo, which has to be installed, and force-pushes
+/// every local branch that has the same commit as on origin, and every tag on origin whose
+/// history contains the paths. The push is refused when somebody pushed in the meantime. An
+/// issue is opened in every rewritten repository to tell contributors to clone it again.
+pub struct PurgeFileArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long = "path", short, required = true)]
+    /// Path of a file or directory to remove, can be repeated
+    pub paths: Vec<String>,
+    #[arg(long)]
+    /// Do not open an issue about the rewrite
+    pub no_issue: bool,
+}
+
+impl PurgeFileArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        if !git::filter_repo_available() {
+            anyhow::bail!(
+                "git filter-repo is not installed, see https://github.com/newren/git-filter-repo"
+            );
+        }
+
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        let affected: Vec<_> = sub_dirs
+            .into_iter()
+            .filter(|d| {
+                git::open(d)
+                    .ok()
+                    .and_then(|r| git::history_contains(&r, &self.paths).ok())
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        if affected.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?} with {} in their history",
+                organisation,
+                self.regex,
+                self.paths.join(", ")
+            );
+            return Ok(());
+        }
+
+        println!("The history of the following repos will be rewritten and force-pushed:");
+        for dir in &affected {
+            println!("{}", dir.display());
+        }
+
+        if !confirm(affected.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        // One at a time, so that a problem can be stopped before it spreads
+        progress::begin(affected.len());
+        let statuses: Vec<_> = affected
+            .iter()
+            .map(|d| progress::track_dir(d, || self.purge(d, &organisation, &user)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn purge(&self, dir: &PathBuf, organisation: &str, user: &User) -> Status {
+        let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+        let cred = GitCredential::from(user);
+
+        let purge = || -> Result<Purge> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+            if git::status(&git_repo, true)?.is_dirty() {
+                return Err(anyhow!(
+                    "There are uncommitted changes, commit or stash them first"
+                ));
+            }
+
+            git::fetch(&git_repo, "origin", Some(cred.clone()))?;
+            let branches = branches_in_sync(&git_repo)?;
+            let tags = tags_in_sync(&git_repo, &self.paths, &cred)?;
+
+            git::purge_paths(&git_repo, &self.paths)?;
+            git::force_push_with_lease(&git_repo, &branches, &tags, Some(&cred))?;
+
+            let branches: Vec<_> = branches.into_iter().map(|(b, _)| b).collect();
+            let tags: Vec<_> = tags.into_iter().map(|(t, _)| t).collect();
+            let issue = if self.no_issue {
+                None
+            } else {
+                let remote_repo = RemoteRepo {
+                    name: repo.clone(),
+                    owner: owner.clone(),
+                    ssh_url: String::new(),
+                    https_url: String::new(),
+                };
+                let issue = github::create_issue(
+                    &remote_repo,
+                    "The history of this repository was rewritten",
+                    &issue_body(&self.paths, &branches, &tags),
+                    &[],
+                    &[],
+                    &user.token,
+                )?;
+                Some(issue.html_url)
+            };
+
+            Ok(Purge {
+                branches,
+                tags,
+                issue,
+            })
+        };
+
+        let result = purge().map_err(|e| format!("{:?}", e));
+        Status { repo, result }
+    }
+}
+
+/// Local branches with the commit that origin has, which are the ones that are safe to push
+///
+/// A branch that is ahead of or behind origin would lose commits when it is force-pushed
+/// after the rewrite, so that is an error.
+fn branches_in_sync(git_repo: &Repository) -> Result<Vec<(String, Oid)>> {
+    let mut branches = vec![];
+    for branch in git_repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let name = branch
+            .name()?
+            .ok_or_else(|| anyhow!("Branch name is not valid utf-8"))?
+            .to_string();
+        let remote = match git_repo.find_branch(&format!("origin/{}", name), BranchType::Remote) {
+            Ok(remote) => remote,
+            Err(_) => continue,
+        };
+
+        let (local, remote) = (branch.get().target(), remote.get().target());
+        match (local, remote) {
+            (Some(local), Some(remote)) if local == remote => branches.push((name, remote)),
+            _ => {
+                return Err(anyhow!(
+                    "Branch {} is not the same as on origin, pull or push it first",
+                    name
+                ))
+            }
+        }
+    }
+
+    if branches.is_empty() {
+        return Err(anyhow!("There is no branch that is also on origin"));
+    }
+    Ok(branches)
+}
+
+/// Tags on origin whose history contains the paths, which have to be force-pushed as well
+///
+/// Their old versions would keep the removed files on origin. A tag that differs from the local
+/// one would be replaced by a rewrite of the wrong commit, so that is an error.
+fn tags_in_sync(
+    git_repo: &Repository,
+    paths: &[String],
+    cred: &GitCredential,
+) -> Result<Vec<(String, Oid)>> {
+    let mut tags = vec![];
+    for (name, remote) in git::remote_tags(git_repo, Some(cred))? {
+        if !git::rev_contains(git_repo, &remote.to_string(), paths).unwrap_or(true) {
+            continue;
+        }
+        let local = git_repo
+            .find_reference(&format!("refs/tags/{}", name))
+            .ok()
+            .and_then(|r| r.target());
+        if local != Some(remote) {
+            return Err(anyhow!(
+                "Tag {} is not the same as on origin, fetch it first",
+                name
+            ));
+        }
+        tags.push((name, remote));
+    }
+    Ok(tags)
+}
+
+fn issue_body(paths: &[String], branches: &[String], tags: &[String]) -> String {
+    let paths: Vec<_> = paths.iter().map(|p| format!("- `{}`", p)).collect();
+    let quote = |names: &[String]| -> Vec<_> { names.iter().map(|n| format!("`{}`", n)).collect() };
+    let tags = if tags.is_empty() {
+        String::new()
+    } else {
+        format!(" and the tags {}", quote(tags).join(", "))
+    };
+    format!(
+        "These paths were removed from the whole history of the repository:\n\n{}\n\n\
+         The branches {}{} were force-pushed, so every commit in them has a new id. \
+         Please clone the repository again, and do not push from an old clone, since that \
+         would bring the removed files back. Work that was not pushed yet can be moved over \
+         with `git cherry-pick` or `git format-patch`.",
+        paths.join("\n"),
+        quote(branches).join(", "),
+        tags
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct Purge {
+    branches: Vec<String>,
+    tags: Vec<String>,
+    issue: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Purge, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(purge) => {
+                let pushed: Vec<_> = purge
+                    .branches
+                    .iter()
+                    .chain(&purge.tags)
+                    .map(|r| r.as_str())
+                    .collect();
+                row![
+                    b -> self.repo,
+                    Fg -> pushed.join("\n"),
+                    purge.issue.as_deref().unwrap_or("-")
+                ]
+            }
+            Err(e) => Row::new(vec![cell!(b -> &self.repo), self.error_cell(e), cell!("")]),
+        }
+    }
+
+    fn error_cell(&self, e: &str) -> Cell {
+        ErrorCategory::from_message(e).failed_cell()
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Force-pushed", "Issue"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when purging:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "REWRITE";
+    common::confirm(
+        &format!(
+            "This rewrites the history of {} repo(s) and cannot be undone. Everybody will have to clone them again.\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_body() {
+        let paths = ["secrets.txt".to_string()];
+        let branches = ["main".to_string()];
+        let body = issue_body(&paths, &branches, &[]);
+        assert!(body.contains("- `secrets.txt`"));
+        assert!(body.contains("The branches `main` were force-pushed"));
+
+        let tags = ["v1.0".to_string(), "v1.1".to_string()];
+        let body = issue_body(&paths, &branches, &tags);
+        assert!(body.contains("The branches `main` and the tags `v1.0`, `v1.1` were force-pushed"));
+    }
+}
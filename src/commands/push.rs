@@ -1,20 +1,29 @@
 use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::guard::Guard;
+use super::progress::{self, Tracked};
 use crate::user::User;
 use colored::*;
 use prettytable::{cell, format, row, Cell, Row, Table};
 
+use crate::branch_map::BranchMap;
 use crate::git;
+use crate::path;
 use anyhow::{Context, Error, Result};
 
 use crate::cli::Args as CommonArgs;
+use crate::config::Config;
 use crate::filter::Filter;
 use crate::git::push;
 use crate::git::GitCredential;
 use clap::Parser;
+use git2::Repository;
+use std::path::PathBuf;
 
 use crate::commands::topic_helper;
 use crate::convert::try_from_one;
 use crate::github::RemoteRepo;
+use crate::mirror::Mirror;
 use rayon::prelude::*;
 
 #[derive(Debug, Parser)]
@@ -22,6 +31,9 @@ use rayon::prelude::*;
 /// or a topic
 ///
 /// This command will do nothing if there is nothing to push
+///
+/// With --mirror-to all branches of origin and all tags are pushed to a secondary remote
+/// instead, as configured in `[mirrors.<owner>.<name>]` of the config file.
 pub struct PushArgs {
     #[arg(long, short)]
     /// Target organisation name
@@ -36,14 +48,33 @@ pub struct PushArgs {
     pub topic: Option<String>,
     #[arg(long, short, default_value = "main")]
     pub branch: String,
+    #[arg(long, conflicts_with = "branch_map")]
+    /// Name of a mirror to push all branches and tags to, after fetching origin
+    ///
+    /// Missing projects are created on the mirror when it has a GitLab api and a token.
+    pub mirror_to: Option<String>,
+    #[arg(long)]
+    /// Toml file with the branch to push per repository, the others push --branch
+    pub branch_map: Option<PathBuf>,
     #[arg(long, short)]
     pub use_https: bool,
+    #[arg(long)]
+    /// Check that CI passes on the default branch of every repository before changing anything
+    pub check_ci: bool,
+    #[arg(long)]
+    /// Run even when the preflight checks fail
+    pub force: bool,
 }
 
 impl PushArgs {
     pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
         let user = common::user()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
+        let branch_map = self
+            .branch_map
+            .as_deref()
+            .map(BranchMap::load)
+            .transpose()?;
 
         let all_repos = topic_helper::query_repositories_with_topics(&organisation, &user.token)?;
 
@@ -61,12 +92,53 @@ impl PushArgs {
             return Ok(());
         }
 
+        let root = common::root()?;
+        let dirs: Vec<_> = filtered_repos
+            .iter()
+            .map(|r| path::local_path_repo(&r.owner, &r.name, &root))
+            .filter(|d| d.exists())
+            .collect();
+        let guard = Guard {
+            clean_worktree: false,
+            ci: self.check_ci,
+            requests_per_repo: 0,
+        };
+        guard.check(&dirs, self.force)?;
+
+        if let Some(mirror) = &self.mirror_to {
+            let config = Config::load()?;
+            progress::begin(filtered_repos.len());
+            let statuses: Vec<_> = filtered_repos
+                .par_iter()
+                .map(|r| {
+                    progress::track(&r.full_name(), || {
+                        push_mirror(r, &config, mirror, &user, self.use_https)
+                    })
+                })
+                .collect();
+            summarize(&statuses, &format!("all refs to mirror {}", mirror));
+            return Ok(());
+        }
+
+        progress::begin(filtered_repos.len());
         let statuses: Vec<_> = filtered_repos
             .par_iter()
-            .map(|r| push_branch(r, &self.branch, &user, "origin", self.use_https))
+            .map(|r| {
+                let branch = branch_map
+                    .as_ref()
+                    .and_then(|m| m.get(&r.owner, &r.name))
+                    .unwrap_or(&self.branch);
+                progress::track(&r.full_name(), || {
+                    push_branch(r, branch, &user, "origin", self.use_https)
+                })
+            })
             .collect();
 
-        summarize(&statuses, &self.branch);
+        let branch = match branch_map {
+            Some(_) => "the branches of the branch map".to_string(),
+            None => format!("branch {}", self.branch),
+        };
+        summarize(&statuses, &branch);
 
         Ok(())
     }
@@ -80,11 +152,7 @@ fn summarize(statuses: &[Status], branch: &str) {
     let success_create: Vec<_> = statuses.iter().filter(|s| s.success()).collect();
 
     if !success_create.is_empty() {
-        let msg = format!(
-            "\nPushed branch {} for {} repos!",
-            branch,
-            success_create.len()
-        );
+        let msg = format!("\nPushed {} for {} repos!", branch, success_create.len());
         println!("{}", msg.green());
     }
 
@@ -94,13 +162,8 @@ fn summarize(statuses: &[Status], branch: &str) {
         let msg = format!("There {} errors when process command:", errors.len());
         println!("\n{}\n", msg.red());
 
-        let mut error_table = Table::new();
-        error_table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-        error_table.set_titles(row!["Repo", "Error"]);
-        for error in errors {
-            error_table.add_row(error.to_error_row());
-        }
-        error_table.printstd();
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
     }
 }
 
@@ -121,16 +184,9 @@ fn push_branch(
             .open()
             .with_context(|| format!("{:?} is not a git directory.", git_repo.local_path))?;
 
-        let status = git::status(&git_repo, false)?;
-
-        if !status.should_push() {
-            push_status = PushStatus::No;
-            return Ok(());
+        if push_if_ahead(&git_repo, branch, user, remote_name)? {
+            push_status = PushStatus::Success(());
         }
-
-        let cred = GitCredential::from(user);
-        push::push_branch(&git_repo, branch, remote_name, Some(cred))?;
-        push_status = PushStatus::Success(());
         Ok(())
     };
 
@@ -145,6 +201,61 @@ fn push_branch(
     }
 }
 
+fn push_mirror(
+    repo: &RemoteRepo,
+    config: &Config,
+    mirror_name: &str,
+    user: &User,
+    use_https: bool,
+) -> Status {
+    log::info!("Mirroring repo {}", repo.name);
+
+    let push = || -> Result<PushStatus> {
+        let mirror = Mirror::for_owner(config, &repo.owner, mirror_name)?;
+        let git_repo = try_from_one(repo.clone(), user, use_https)?;
+        let git_repo = git_repo
+            .open()
+            .with_context(|| format!("{:?} is not a git directory.", git_repo.local_path))?;
+
+        git::fetch_quiet(&git_repo, "origin", Some(GitCredential::from(user)))?;
+        let created = mirror.ensure_project(&repo.name)?;
+        push::push_mirror(
+            &git_repo,
+            "origin",
+            &mirror.url_for(&repo.name),
+            mirror.credential(),
+        )?;
+        Ok(if created {
+            PushStatus::Created
+        } else {
+            PushStatus::Success(())
+        })
+    };
+
+    Status {
+        repo: repo.clone(),
+        status: push().unwrap_or_else(PushStatus::Failed),
+    }
+}
+
+/// Push a branch when it has commits that the remote does not have, returns whether it pushed
+pub fn push_if_ahead(
+    git_repo: &Repository,
+    branch: &str,
+    user: &User,
+    remote_name: &str,
+) -> Result<bool> {
+    let status = git::status(git_repo, false)?;
+
+    if !status.should_push() {
+        return Ok(false);
+    }
+
+    let cred = GitCredential::from(user);
+    push::push_branch(git_repo, branch, remote_name, Some(cred))?;
+    Ok(true)
+}
+
 struct Status {
     repo: RemoteRepo,
     status: PushStatus,
@@ -160,26 +271,31 @@ impl Status {
     }
 
     fn success(&self) -> bool {
-        matches!(self.status, PushStatus::Success(_))
+        matches!(self.status, PushStatus::Success(_) | PushStatus::Created)
     }
 
-    fn to_error_row(&self) -> Row {
+    fn to_error_report(&self) -> ErrorReport {
         let e = if let PushStatus::Failed(e) = &self.status {
             e
         } else {
             panic!("This should have an error here");
         };
 
-        let msg = format!("{:?}", e);
-        let lines = common::sub_strings(msg.as_str(), 80);
-        let lines = lines.join("\n");
-        row!(cell!(b -> &self.repo.name), cell!(Fr -> lines.as_str()))
+        ErrorReport::new(&self.repo.name, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
     }
 }
 
 enum PushStatus {
     No,
     Success(()),
+    /// Pushed to a mirror project that had to be created first
+    Created,
     Failed(Error),
 }
 
@@ -188,7 +304,8 @@ impl PushStatus {
         match &self {
             PushStatus::No => cell!(r -> "-"),
             PushStatus::Success(_) => cell!(Fgr -> "Success"),
-            PushStatus::Failed(_) => cell!(Frr -> "Failed"),
+            PushStatus::Created => cell!(Fgr -> "Created"),
+            PushStatus::Failed(e) => ErrorCategory::of(e).failed_cell(),
         }
     }
 }
@@ -0,0 +1,39 @@
+use super::release_create::*;
+use super::release_delete::*;
+use super::release_list::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Create, list and delete GitHub releases of many repositories in lockstep
+pub struct ReleaseArgs {
+    #[command(subcommand)]
+    command: ReleaseCommand,
+}
+
+impl ReleaseArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum ReleaseCommand {
+    #[command(name = "create")]
+    Create(ReleaseCreateArgs),
+    #[command(name = "delete")]
+    Delete(ReleaseDeleteArgs),
+    #[command(name = "list", aliases = &["ls"])]
+    List(ReleaseListArgs),
+}
+
+impl ReleaseCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Create(args) => args.run(common_args),
+            Self::Delete(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::topic_helper;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, NewRelease, Pacer, RemoteRepo};
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Tag and create a GitHub release in all repositories that match a regex or a topic
+///
+/// The tag is made on the default branch, or on --target, when it does not exist yet.
+/// Repositories that already have a release of the tag keep it. The release notes are read
+/// from a file in which __REPO__, __OWNER__ and __TAG__ are replaced for every repository.
+pub struct ReleaseCreateArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short, required_unless_present("topic"))]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, required_unless_present("regex"))]
+    /// topic to filter
+    pub topic: Option<String>,
+    #[arg(long, short)]
+    /// Name of the tag, e.g. v1.2.0
+    pub tag: String,
+    #[arg(long)]
+    /// Branch or commit to make a new tag on, the default branch by default
+    pub target: Option<String>,
+    #[arg(long)]
+    /// Title of the releases, the tag by default
+    pub name: Option<String>,
+    #[arg(long, short)]
+    /// Markdown file with the release notes, with __REPO__, __OWNER__ and __TAG__ placeholders
+    pub notes: Option<PathBuf>,
+    #[arg(long)]
+    /// Create the releases as drafts
+    pub draft: bool,
+    #[arg(long)]
+    /// Mark the releases as prereleases
+    pub prerelease: bool,
+    #[arg(long, short)]
+    /// File to upload as an asset of every release, can be given more than once
+    pub asset: Vec<PathBuf>,
+}
+
+impl ReleaseCreateArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let notes = match &self.notes {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read release notes {:?}", path))?,
+            None => String::new(),
+        };
+        for asset in &self.asset {
+            if !asset.is_file() {
+                bail!("Asset {:?} is not a file", asset);
+            }
+        }
+
+        let all_repos = topic_helper::query_repositories_with_topics(&organisation, &user_token)?;
+        let filtered_repos: Vec<_> =
+            topic_helper::filter_repos(&all_repos, self.topic.as_ref(), self.regex.as_ref())
+                .into_iter()
+                .map(|r| r.repo)
+                .collect();
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let existing: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| self.existing_release(repo, &user_token))
+            .collect();
+
+        progress::begin(filtered_repos.len());
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = filtered_repos
+            .iter()
+            .zip(existing)
+            .map(|(repo, existing)| {
+                progress::track(&repo.full_name(), || {
+                    let result = existing.and_then(|existing| match existing {
+                        Some(url) => Ok(Outcome::Exists { url }),
+                        None => self.create(repo, &notes, &mut pacer, &user_token),
+                    });
+                    Status {
+                        repo: repo.full_name(),
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, &self.tag);
+
+        Ok(())
+    }
+
+    /// The url of the release of the tag, if there is one
+    fn existing_release(&self, repo: &RemoteRepo, token: &str) -> Result<Option<String>> {
+        let releases = github::get_releases(repo, token)?;
+        Ok(releases
+            .into_iter()
+            .find(|r| r.tag_name == self.tag)
+            .map(|r| r.html_url))
+    }
+
+    fn create(
+        &self,
+        repo: &RemoteRepo,
+        notes: &str,
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<Outcome> {
+        let release = NewRelease {
+            tag_name: self.tag.clone(),
+            target_commitish: self.target.clone(),
+            name: self.name.clone().unwrap_or_else(|| self.tag.clone()),
+            body: fill_notes(notes, repo, &self.tag),
+            draft: self.draft,
+            prerelease: self.prerelease,
+        };
+        let created = pacer.run(|| github::create_release(repo, &release, token))?;
+        for asset in &self.asset {
+            pacer
+                .run(|| github::upload_release_asset(&created, asset, token))
+                .with_context(|| format!("Cannot upload asset {:?}", asset))?;
+        }
+        Ok(Outcome::Created {
+            url: created.html_url,
+        })
+    }
+}
+
+fn fill_notes(notes: &str, repo: &RemoteRepo, tag: &str) -> String {
+    notes
+        .replace("__REPO__", &repo.name)
+        .replace("__OWNER__", &repo.owner)
+        .replace("__TAG__", tag)
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Outcome {
+    Created { url: String },
+    Exists { url: String },
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Outcome, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let url = match &self.result {
+            Ok(Outcome::Created { url } | Outcome::Exists { url }) => url.as_str(),
+            Err(_) => "",
+        };
+        Row::new(vec![cell!(b -> &self.repo), self.to_cell(), cell!(url)])
+    }
+
+    fn to_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Outcome::Created { .. }) => cell!(Fgr -> "Created"),
+            Ok(Outcome::Exists { .. }) => cell!(r -> "Already released"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn created(&self) -> bool {
+        matches!(self.result, Ok(Outcome::Created { .. }))
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Release", "Link"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status], tag: &str) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let created = statuses.iter().filter(|s| s.created()).count();
+
+    if created > 0 {
+        let msg = format!("\nReleased {} in {} repos!", tag, created);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when creating releases:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_notes() {
+        let repo = RemoteRepo {
+            name: "lang-sme".to_string(),
+            owner: "giellalt".to_string(),
+            ssh_url: String::new(),
+            https_url: String::new(),
+        };
+        assert_eq!(
+            fill_notes(
+                "Release __TAG__ of __REPO__, see https://github.com/__OWNER__/__REPO__",
+                &repo,
+                "v1.2.0"
+            ),
+            "Release v1.2.0 of lang-sme, see https://github.com/giellalt/lang-sme"
+        );
+    }
+}
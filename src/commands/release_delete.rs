@@ -0,0 +1,231 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, Pacer, Release, RemoteRepo};
+use anyhow::Result;
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Delete the release of a tag in all repositories that match a regex
+///
+/// The releases are listed and have to be confirmed before they are deleted. The tag itself is
+/// kept unless --delete-tag is given.
+pub struct ReleaseDeleteArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Tag of the releases
+    pub tag: String,
+    #[arg(long)]
+    /// Also delete the tag
+    pub delete_tag: bool,
+    #[arg(long)]
+    /// Only show the releases that would be deleted
+    pub dry_run: bool,
+}
+
+impl ReleaseDeleteArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, github::get_releases(repo, &user_token)))
+            .collect();
+
+        let mut candidates = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(releases) => candidates.extend(
+                    releases
+                        .into_iter()
+                        .filter(|r| r.tag_name == self.tag)
+                        .map(|r| (repo.clone(), r)),
+                ),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking up releases:", reports.len());
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if candidates.is_empty() {
+            println!("There is no release of {}", self.tag);
+            return Ok(());
+        }
+
+        if common_args.format != Some(OutputFormat::Json) {
+            to_table(&candidates).printstd();
+        }
+
+        if self.dry_run {
+            if let Some(OutputFormat::Json) = common_args.format {
+                let releases: Vec<_> = candidates.iter().map(|(_, r)| r).collect();
+                println!("{}", json!(releases));
+            } else {
+                println!("\n{} releases would be deleted", candidates.len());
+            }
+            return Ok(());
+        }
+
+        if !confirm(candidates.len(), &self.tag)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        progress::begin(candidates.len());
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = candidates
+            .iter()
+            .map(|(repo, release)| {
+                progress::track(&repo.full_name(), || {
+                    let result = self.delete(repo, release, &mut pacer, &user_token);
+                    Status {
+                        repo: repo.full_name(),
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        println!();
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    fn delete(
+        &self,
+        repo: &RemoteRepo,
+        release: &Release,
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<()> {
+        pacer.run(|| github::delete_release(repo, release.id, token))?;
+        if self.delete_tag {
+            pacer.run(|| github::delete_tag(repo, &self.tag, token))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let result = match &self.result {
+            Ok(()) => cell!(Fgr -> "Deleted"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![cell!(b -> &self.repo), result])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(candidates: &[(RemoteRepo, Release)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Tag", "Name", "Link"]);
+    for (repo, release) in candidates {
+        table.add_row(row![
+            b -> repo.full_name(),
+            release.tag_name,
+            release.name.as_deref().unwrap_or_default(),
+            release.html_url
+        ]);
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when deleting releases:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, tag: &str) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to delete {} release(s) of {}?\nEnter {} to continue",
+            count, tag, key
+        ),
+        key,
+    )
+}
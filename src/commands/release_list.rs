@@ -0,0 +1,151 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, Release, RemoteRepo};
+use anyhow::Result;
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Show the latest release, or the release of a tag, of all repositories that match a regex
+pub struct ReleaseListArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Show the release of this tag instead of the latest one
+    pub tag: Option<String>,
+}
+
+impl ReleaseListArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| self.release(repo, &user_token))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        to_table(&statuses).printstd();
+
+        let released = statuses
+            .iter()
+            .filter(|s| matches!(s.release, Ok(Some(_))))
+            .count();
+        match &self.tag {
+            Some(tag) => println!(
+                "\n{} of {} repos have released {}",
+                released,
+                statuses.len(),
+                tag
+            ),
+            None => println!("\n{} of {} repos have releases", released, statuses.len()),
+        }
+
+        let reports: Vec<_> = statuses
+            .iter()
+            .filter_map(|s| s.to_error_report())
+            .collect();
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking up releases:", reports.len());
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+
+    fn release(&self, repo: &RemoteRepo, token: &str) -> Status {
+        let release = github::get_releases(repo, token).map(|releases| {
+            releases
+                .into_iter()
+                .find(|r| self.tag.as_ref().is_none_or(|t| *t == r.tag_name))
+        });
+        Status {
+            repo: repo.full_name(),
+            release: release.map_err(|e| format!("{:?}", e)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    release: Result<Option<Release>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let release = match &self.release {
+            Ok(Some(release)) => release,
+            Ok(None) => return Row::new(vec![cell!(b -> &self.repo), cell!("-")]),
+            Err(e) => {
+                return Row::new(vec![
+                    cell!(b -> &self.repo),
+                    ErrorCategory::from_message(e).failed_cell(),
+                ])
+            }
+        };
+        let state = if release.draft {
+            cell!(Fy -> "Draft")
+        } else if release.prerelease {
+            cell!(Fc -> "Prerelease")
+        } else {
+            cell!(Fg -> "Published")
+        };
+        let published = release
+            .published_at
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(release.tag_name),
+            cell!(release.name.as_deref().unwrap_or_default()),
+            state,
+            cell!(published),
+            cell!(r -> release.assets.len()),
+        ])
+    }
+
+    fn to_error_report(&self) -> Option<ErrorReport> {
+        let e = self.release.as_ref().err()?;
+        Some(ErrorReport::from_message(&self.repo, e))
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Tag", "Name", "State", "Published", "Assets"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
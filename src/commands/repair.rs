@@ -0,0 +1,236 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::dir_name;
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+/// Find and fix local repositories that are stuck after an interrupted git command
+///
+/// This finds repositories in the middle of a merge, rebase, cherry-pick, revert or bisect,
+/// and stale lock files that are left behind when git is killed. Without --abort or
+/// --remove-locks it only reports them.
+pub struct RepairArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Abort the merge, rebase, cherry-pick, revert or bisect that is in progress
+    pub abort: bool,
+    #[arg(long)]
+    /// Remove stale lock files
+    pub remove_locks: bool,
+    #[arg(long, default_value = "10")]
+    /// Minutes after which a lock file is considered stale
+    pub lock_age: u64,
+}
+
+impl RepairArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+        let lock_age = Duration::from_secs(self.lock_age * 60);
+
+        let mut statuses: Vec<_> = sub_dirs.iter().map(|d| inspect(d, lock_age)).collect();
+        statuses.retain(|s| s.needs_repair() || s.has_error());
+
+        let to_repair: Vec<_> = statuses
+            .iter_mut()
+            .filter(|s| {
+                (self.abort && s.operation.is_some()) || (self.remove_locks && !s.locks.is_empty())
+            })
+            .collect();
+
+        if !to_repair.is_empty() {
+            println!("The following repos will be repaired:");
+            for status in &to_repair {
+                println!("{}", status.repo);
+            }
+
+            if confirm(to_repair.len())? {
+                for status in to_repair {
+                    status.repair(self.abort, self.remove_locks);
+                }
+            } else {
+                println!("Command is aborted. Nothing change!");
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn inspect(dir: &PathBuf, lock_age: Duration) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let mut status = Status {
+        repo,
+        dir: dir.clone(),
+        operation: None,
+        locks: vec![],
+        result: Ok(Repair::None),
+    };
+
+    match git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir)) {
+        Ok(git_repo) => {
+            status.operation = git::operation_in_progress(&git_repo);
+            status.locks = git::stale_locks(&git_repo, lock_age);
+        }
+        Err(e) => status.result = Err(format!("{:?}", e)),
+    }
+    status
+}
+
+#[derive(Debug, Serialize)]
+enum Repair {
+    None,
+    Repaired,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    operation: Option<&'static str>,
+    locks: Vec<PathBuf>,
+    result: Result<Repair, String>,
+}
+
+impl Status {
+    fn needs_repair(&self) -> bool {
+        self.operation.is_some() || !self.locks.is_empty()
+    }
+
+    /// Locks go first, git refuses to abort anything while the index is locked
+    fn repair(&mut self, abort: bool, remove_locks: bool) {
+        let mut repair = || -> Result<()> {
+            if remove_locks {
+                for lock in &self.locks {
+                    std::fs::remove_file(lock)
+                        .with_context(|| format!("Cannot remove {:?}", lock))?;
+                }
+                self.locks.clear();
+            }
+            if abort && self.operation.is_some() {
+                let git_repo = git::open(&self.dir)?;
+                git::abort_operation(&git_repo)?;
+                self.operation = None;
+            }
+            Ok(())
+        };
+        self.result = repair()
+            .map(|_| Repair::Repaired)
+            .map_err(|e| format!("{:?}", e));
+    }
+
+    fn to_row(&self) -> Row {
+        let locks: Vec<_> = self
+            .locks
+            .iter()
+            .filter_map(|l| l.file_name())
+            .map(|l| l.to_string_lossy())
+            .collect();
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            match self.operation {
+                Some(o) => cell!(Fy -> o),
+                None => cell!("-"),
+            },
+            cell!(Fy -> locks.join("\n")),
+            self.result_cell(),
+        ])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Repair::Repaired) => cell!(Fg -> "Repaired"),
+            Ok(Repair::None) => cell!("-"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    if statuses.is_empty() {
+        println!(
+            "{}",
+            "There is no repositories that need to be repaired".green()
+        );
+        return;
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "In progress", "Stale locks", "Repair"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let remaining = statuses.iter().filter(|s| s.needs_repair()).count();
+    if remaining > 0 {
+        let msg = format!(
+            "{} repos still need to be repaired, use --abort and/or --remove-locks",
+            remaining
+        );
+        println!("\n{}", msg.yellow());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when repairing:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to repair {} repo(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,229 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github::{self, CiState, OpenPullRequest, RemoteRepo};
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+
+#[derive(Debug, Parser)]
+/// Approve or request changes on the open pull requests of all repositories that match a regex
+///
+/// Pull requests are selected by their author, the name of their head branch and the state of
+/// their CI, e.g. the pull requests that a template campaign opened. Draft pull requests are
+/// skipped. The selected pull requests are always listed and confirmed before they are reviewed.
+pub struct ReviewArgs {
+    #[arg(value_enum)]
+    /// How to review the pull requests
+    pub action: ReviewAction,
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Only review pull requests opened by this user, e.g. an automation account
+    pub author: Option<String>,
+    #[arg(long)]
+    /// Only review pull requests whose head branch matches this regex
+    pub head: Option<Regex>,
+    #[arg(long)]
+    /// Only review pull requests whose CI succeeded
+    pub ci_green: bool,
+    #[arg(long, short)]
+    /// Text of the review, required to request changes
+    pub body: Option<String>,
+    #[arg(long)]
+    /// Only show the pull requests that would be reviewed
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ReviewAction {
+    /// Approve the pull requests
+    Approve,
+    /// Request changes on the pull requests
+    RequestChanges,
+    /// Only comment on the pull requests
+    Comment,
+}
+
+impl ReviewAction {
+    fn event(&self) -> &'static str {
+        match self {
+            Self::Approve => "APPROVE",
+            Self::RequestChanges => "REQUEST_CHANGES",
+            Self::Comment => "COMMENT",
+        }
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Approve => "Approved",
+            Self::RequestChanges => "Requested changes on",
+            Self::Comment => "Commented on",
+        }
+    }
+}
+
+impl ReviewArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        if self.body.is_none() && self.action != ReviewAction::Approve {
+            bail!("You need to provide the text of the review with \"--body\"");
+        }
+
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, self.select(repo, &user_token)))
+            .collect();
+
+        let mut candidates = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(pulls) => candidates.extend(pulls.into_iter().map(|pr| (repo.clone(), pr))),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up pull requests:",
+                reports.len()
+            );
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if candidates.is_empty() {
+            println!("There is no pull requests to review");
+            return Ok(());
+        }
+
+        to_table(&candidates).printstd();
+
+        if self.dry_run {
+            println!("\n{} pull requests would be reviewed", candidates.len());
+            return Ok(());
+        }
+
+        if !confirm(self.action, candidates.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        for (repo, pr) in candidates {
+            let result = github::create_review(
+                &repo,
+                pr.number,
+                self.action.event(),
+                self.body.as_deref(),
+                &user_token,
+            );
+            match result {
+                Ok(_) => println!(
+                    "{} {}#{}",
+                    self.action.past_tense(),
+                    repo.full_name(),
+                    pr.number
+                ),
+                Err(e) => println!(
+                    "Failed to review {}#{} because {:?}",
+                    repo.full_name(),
+                    pr.number,
+                    e
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The open pull requests of a repository that match the criteria
+    fn select(&self, repo: &RemoteRepo, token: &str) -> Result<Vec<OpenPullRequest>> {
+        let mut pulls: Vec<_> = github::get_open_pull_requests(repo, token)?
+            .into_iter()
+            .filter(|pr| self.is_match(pr))
+            .collect();
+
+        if self.ci_green {
+            let mut green = vec![];
+            for pr in pulls {
+                if github::get_ci_state(repo, &pr.head.sha, token)? == CiState::Success {
+                    green.push(pr);
+                }
+            }
+            pulls = green;
+        }
+        Ok(pulls)
+    }
+
+    fn is_match(&self, pr: &OpenPullRequest) -> bool {
+        !pr.draft
+            && self
+                .author
+                .as_ref()
+                .is_none_or(|a| a.eq_ignore_ascii_case(&pr.user.login))
+            && self
+                .head
+                .as_ref()
+                .is_none_or(|h| h.is_match(&pr.head.branch))
+    }
+}
+
+fn to_table(candidates: &[(RemoteRepo, OpenPullRequest)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Author", "Branch"]);
+    for (repo, pr) in candidates {
+        table.add_row(row![
+            b -> repo.full_name(),
+            r -> pr.number,
+            pr.title,
+            pr.user.login,
+            pr.head.branch
+        ]);
+    }
+    table
+}
+
+fn confirm(action: ReviewAction, count: usize) -> Result<bool> {
+    let key = "YES";
+    let verb = match action {
+        ReviewAction::Approve => "approve",
+        ReviewAction::RequestChanges => "request changes on",
+        ReviewAction::Comment => "comment on",
+    };
+    common::confirm(
+        &format!(
+            "Are you sure you want to {} {} pull request(s)?\nEnter {} to continue",
+            verb, count, key
+        ),
+        key,
+    )
+}
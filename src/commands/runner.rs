@@ -0,0 +1,39 @@
+use super::runner_labels::*;
+use super::runner_list::*;
+use super::runner_token::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Manage self-hosted runners of an organisation
+pub struct RunnerArgs {
+    #[command(subcommand)]
+    command: RunnerCommand,
+}
+
+impl RunnerArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum RunnerCommand {
+    #[command(name = "labels")]
+    Labels(RunnerLabelsArgs),
+    #[command(name = "list", aliases = &["ls"])]
+    List(RunnerListArgs),
+    #[command(name = "token")]
+    Token(RunnerTokenArgs),
+}
+
+impl RunnerCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Labels(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+            Self::Token(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::case_insensitive_regex;
+use crate::github;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use regex::Regex;
+
+#[derive(Debug, Parser)]
+/// Add or remove custom labels of self-hosted runners that match a regex
+pub struct RunnerLabelsArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short, value_parser = case_insensitive_regex)]
+    /// Regex to filter runners by name
+    pub regex: Regex,
+    #[arg(long, short)]
+    /// Labels to add
+    pub add: Vec<String>,
+    #[arg(long, short = 'd')]
+    /// Labels to remove
+    pub remove: Vec<String>,
+}
+
+impl RunnerLabelsArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        if self.add.is_empty() && self.remove.is_empty() {
+            return Err(anyhow!(
+                "You need to provide at least one of \"--add\" and \"--remove\""
+            ));
+        }
+
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let runners: Vec<_> = github::get_org_runners(&organisation, &user_token)?
+            .into_iter()
+            .filter(|r| self.regex.is_match(&r.name))
+            .collect();
+
+        if runners.is_empty() {
+            println!(
+                "There is no runners in organisation {} that matches pattern {:?}",
+                organisation,
+                self.regex.as_str()
+            );
+            return Ok(());
+        }
+
+        for runner in runners {
+            if !self.add.is_empty() {
+                match github::add_runner_labels(&organisation, runner.id, &self.add, &user_token) {
+                    Ok(_) => println!(
+                        "Added labels {:?} to runner {} successfully",
+                        self.add, runner.name
+                    ),
+                    Err(e) => println!(
+                        "Failed to add labels to runner {} because {:?}",
+                        runner.name, e
+                    ),
+                }
+            }
+
+            for label in &self.remove {
+                match github::remove_runner_label(&organisation, runner.id, label, &user_token) {
+                    Ok(_) => println!(
+                        "Removed label {} from runner {} successfully",
+                        label, runner.name
+                    ),
+                    Err(e) => println!(
+                        "Failed to remove label {} from runner {} because {:?}",
+                        label, runner.name, e
+                    ),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
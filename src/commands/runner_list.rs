@@ -0,0 +1,70 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::case_insensitive_regex;
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{cell, format, row, Table};
+use regex::Regex;
+
+#[derive(Debug, Parser)]
+/// List self-hosted runners of an organisation with their status and labels
+pub struct RunnerListArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short, value_parser = case_insensitive_regex)]
+    /// Optional regex to filter runners by name
+    pub regex: Option<Regex>,
+}
+
+impl RunnerListArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let runners: Vec<_> = github::get_org_runners(&organisation, &user_token)?
+            .into_iter()
+            .filter(|r| self.regex.as_ref().is_none_or(|re| re.is_match(&r.name)))
+            .collect();
+
+        if runners.is_empty() {
+            println!(
+                "There is no runners in organisation {} that matches pattern {:?}",
+                organisation,
+                self.regex.as_ref().map(Regex::as_str)
+            );
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Id", "Name", "OS", "Status", "Activity", "Labels"]);
+        for runner in &runners {
+            let status = if runner.status == "online" {
+                cell!(Fg -> &runner.status)
+            } else {
+                cell!(Fr -> &runner.status)
+            };
+            let activity = if runner.busy {
+                cell!(Fy -> "busy")
+            } else {
+                cell!("idle")
+            };
+            let labels: Vec<_> = runner.labels.iter().map(|l| l.name.as_str()).collect();
+            table.add_row(row![
+                r -> runner.id,
+                b -> &runner.name,
+                runner.os,
+                status,
+                activity,
+                labels.join(", ")
+            ]);
+        }
+        table.printstd();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Generate a token to register a new self-hosted runner for an organisation
+pub struct RunnerTokenArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+}
+
+impl RunnerTokenArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let token = github::create_runner_registration_token(&organisation, &user_token)?;
+
+        println!("{}", token.token);
+        eprintln!(
+            "Use it with: ./config.sh --url https://github.com/{} --token {}\nThe token expires at {}",
+            organisation, token.token, token.expires_at
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use super::search_code::*;
+use super::search_issues::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Search code, issues and pull requests of organisations on GitHub, without cloning
+pub struct SearchArgs {
+    #[command(subcommand)]
+    command: SearchCommand,
+}
+
+impl SearchArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum SearchCommand {
+    #[command(name = "code")]
+    Code(SearchCodeArgs),
+    #[command(name = "issues")]
+    Issues(SearchIssuesArgs),
+}
+
+impl SearchCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Code(args) => args.run(common_args),
+            Self::Issues(args) => args.run(common_args),
+        }
+    }
+}
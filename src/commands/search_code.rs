@@ -0,0 +1,170 @@
+use super::common;
+use super::table::{split_full_name, GroupBy, OwnerTable};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, CodeSearchItem};
+use anyhow::Result;
+use clap::Parser;
+use prettytable::row;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Parser)]
+/// Search the code of the default branches of organisations on GitHub
+///
+/// The query uses the GitHub code search syntax, e.g. `analyse_word language:python`, and is
+/// limited to the organisations. GitHub gives no line numbers, so the lines that match are
+/// shown from the fragments it returns. At most 1000 files are found per organisation.
+pub struct SearchCodeArgs {
+    /// What to search for
+    pub query: String,
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Search every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, value_enum, default_value_t = GroupBy::Flat)]
+    /// Print a table per owner, or one table with an Owner column
+    pub group_by: GroupBy,
+}
+
+impl SearchCodeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let owners = common::owners(
+            self.organisation.as_deref(),
+            self.all_orgs,
+            self.regex.as_ref(),
+            &user_token,
+        )?;
+
+        let mut hits = vec![];
+        for owner in &owners {
+            let query = format!("{} org:{}", self.query, owner);
+            for item in github::search_code(&query, &user_token)? {
+                if let Some(regex) = &self.regex {
+                    if !regex.is_match_in(owner, &item.repository.name) {
+                        continue;
+                    }
+                }
+                hits.push(Hit::new(item));
+            }
+        }
+        hits.sort_by(|a, b| (&a.repo, &a.path).cmp(&(&b.repo, &b.path)));
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(hits));
+            return Ok(());
+        }
+
+        summarize(&hits, &self.query, self.group_by);
+
+        Ok(())
+    }
+}
+
+/// A file that matches the query
+#[derive(Debug, Serialize)]
+struct Hit {
+    repo: String,
+    path: String,
+    url: String,
+    lines: Vec<String>,
+}
+
+impl Hit {
+    fn new(item: CodeSearchItem) -> Hit {
+        Hit {
+            lines: matched_lines(&item),
+            repo: item.repository.full_name,
+            path: item.path,
+            url: item.html_url,
+        }
+    }
+}
+
+/// The lines of the fragments that contain a match, without duplicates
+fn matched_lines(item: &CodeSearchItem) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for text_match in item.text_matches.iter().filter(|m| m.property == "content") {
+        for line in text_match.fragment.lines() {
+            let line = line.trim();
+            let matches = text_match.matches.iter().any(|m| line.contains(&m.text));
+            if matches && !lines.iter().any(|l| l == line) {
+                lines.push(line.to_string());
+            }
+        }
+    }
+    lines
+}
+
+fn summarize(hits: &[Hit], query: &str, group_by: GroupBy) {
+    if hits.is_empty() {
+        println!("There is no code that matches {:?}", query);
+        return;
+    }
+
+    let mut table = OwnerTable::new(row!["Repo", "Path", "Line"]);
+    let mut previous = "";
+    for hit in hits {
+        let (owner, name) = split_full_name(&hit.repo);
+        let repo = if hit.repo == previous { "" } else { name };
+        previous = &hit.repo;
+        let first = hit.lines.first().map(|l| shorten(l)).unwrap_or_default();
+        table.add_row(owner, row![b -> repo, hit.path, first]);
+        for line in hit.lines.iter().skip(1) {
+            table.add_row(owner, row!["", "", shorten(line)]);
+        }
+    }
+    table.print(group_by);
+
+    let repos: BTreeSet<_> = hits.iter().map(|h| h.repo.as_str()).collect();
+    println!("\n{} files in {} repositories", hits.len(), repos.len());
+}
+
+/// Long lines make the table unreadable, e.g. minified files
+fn shorten(line: &str) -> String {
+    if line.chars().count() <= 100 {
+        return line.to_string();
+    }
+    let short: String = line.chars().take(99).collect();
+    format!("{}…", short)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{CodeSearchRepository, FragmentMatch, TextMatch};
+
+    #[test]
+    fn test_matched_lines() {
+        let text_match = |property: &str, fragment: &str, text: &str| TextMatch {
+            property: property.to_string(),
+            fragment: fragment.to_string(),
+            matches: vec![FragmentMatch {
+                text: text.to_string(),
+            }],
+        };
+        let item = CodeSearchItem {
+            path: "tools/analyse.py".to_string(),
+            html_url: String::new(),
+            repository: CodeSearchRepository {
+                name: "lang-sme".to_string(),
+                full_name: "giellalt/lang-sme".to_string(),
+            },
+            text_matches: vec![
+                text_match("content", "import os\n  old_analyse(word)\n", "old_analyse"),
+                text_match("content", "  old_analyse(word)\nprint()", "old_analyse"),
+                text_match("path", "tools/old_analyse.py", "old_analyse"),
+            ],
+        };
+        assert_eq!(matched_lines(&item), vec!["old_analyse(word)"]);
+    }
+}
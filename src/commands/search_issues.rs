@@ -0,0 +1,158 @@
+use super::common;
+use super::table::{split_full_name, GroupBy, OwnerTable};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use prettytable::{cell, row, Cell, Row};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Search issues and pull requests of organisations on GitHub
+///
+/// The query uses the GitHub issue search syntax, e.g. `label:bug "analyser crashes"`, and is
+/// limited to the organisations. At most 1000 results are found per organisation.
+pub struct SearchIssuesArgs {
+    /// What to search for
+    pub query: String,
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Search every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, value_enum)]
+    /// Only search issues or only pull requests
+    pub kind: Option<Kind>,
+    #[arg(long)]
+    /// Only search the open ones
+    pub open: bool,
+    #[arg(long, value_enum, default_value_t = GroupBy::Flat)]
+    /// Print a table per owner, or one table with an Owner column
+    pub group_by: GroupBy,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    #[value(name = "issue")]
+    Issue,
+    #[value(name = "pr")]
+    Pr,
+}
+
+impl SearchIssuesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let owners = common::owners(
+            self.organisation.as_deref(),
+            self.all_orgs,
+            self.regex.as_ref(),
+            &user_token,
+        )?;
+
+        let mut results = vec![];
+        for owner in &owners {
+            for item in github::search_issues(&self.search_query(owner), &user_token)? {
+                let repo = match item.repository() {
+                    Some((owner, name)) => {
+                        if let Some(regex) = &self.regex {
+                            if !regex.is_match_in(owner, name) {
+                                continue;
+                            }
+                        }
+                        format!("{}/{}", owner, name)
+                    }
+                    None => continue,
+                };
+                results.push(Found {
+                    repo,
+                    number: item.number,
+                    kind: if item.pull_request.is_some() {
+                        "PR"
+                    } else {
+                        "Issue"
+                    },
+                    state: item.state,
+                    title: item.title,
+                    url: item.html_url,
+                    created_at: item.created_at,
+                });
+            }
+        }
+        results.sort_by(|a, b| (&a.repo, a.number).cmp(&(&b.repo, b.number)));
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(results));
+            return Ok(());
+        }
+
+        summarize(&results, &self.query, self.group_by);
+
+        Ok(())
+    }
+
+    fn search_query(&self, owner: &str) -> String {
+        let mut query = format!("{} org:{}", self.query, owner);
+        match self.kind {
+            Some(Kind::Issue) => query.push_str(" is:issue"),
+            Some(Kind::Pr) => query.push_str(" is:pr"),
+            None => {}
+        }
+        if self.open {
+            query.push_str(" is:open");
+        }
+        query
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Found {
+    repo: String,
+    number: u64,
+    kind: &'static str,
+    state: String,
+    title: String,
+    url: String,
+    created_at: DateTime<Utc>,
+}
+
+impl Found {
+    fn to_row(&self) -> Row {
+        let state = match self.state.as_str() {
+            "open" => cell!(Fg -> "open"),
+            state => cell!(state),
+        };
+        Row::new(vec![
+            cell!(b -> split_full_name(&self.repo).1),
+            cell!(r -> format!("#{}", self.number)),
+            cell!(self.kind),
+            state,
+            cell!(self.title),
+            Cell::new(&self.created_at.format("%Y-%m-%d").to_string()),
+        ])
+    }
+}
+
+fn summarize(results: &[Found], query: &str, group_by: GroupBy) {
+    if results.is_empty() {
+        println!("There is no issue or pull request that matches {:?}", query);
+        return;
+    }
+
+    let mut table = OwnerTable::new(row!["Repo", "#", "Kind", "State", "Title", "Created"]);
+    for found in results {
+        table.add_row(split_full_name(&found.repo).0, found.to_row());
+    }
+    table.print(group_by);
+
+    let open = results.iter().filter(|f| f.state == "open").count();
+    println!("\n{} found, {} of them open", results.len(), open);
+}
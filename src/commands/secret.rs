@@ -0,0 +1,31 @@
+use super::secret_rotate::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Manage action secrets of repositories
+pub struct SecretArgs {
+    #[command(subcommand)]
+    command: SecretCommand,
+}
+
+impl SecretArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum SecretCommand {
+    #[command(name = "rotate")]
+    Rotate(RotateArgs),
+}
+
+impl SecretCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Rotate(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::set_secret::set_secret;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github;
+use crate::github::RemoteRepo;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::env;
+
+#[derive(Debug, Parser)]
+/// Rotate a secret of all repositories that match regex
+///
+/// The new value is the output of the given command. It is run once and the same value is
+/// set for every repository.
+pub struct RotateArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// The name of the secret
+    pub name: String,
+    #[arg(long)]
+    /// The command whose output is the new value of the secret
+    pub value_from_command: String,
+    #[arg(long)]
+    /// Workflow file name (or id) to dispatch after the secret is updated
+    ///
+    /// The workflow needs to have a workflow_dispatch trigger.
+    pub verify_workflow: Option<String>,
+    #[arg(long, default_value = "main")]
+    /// The branch or tag to run the verification workflow on
+    pub verify_ref: String,
+}
+
+impl RotateArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} matches pattern {:?}",
+                &organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let value = self.new_value()?;
+
+        progress::begin(filtered_repos.len());
+        let statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|r| progress::track(&r.full_name(), || self.rotate(r, &value, &user_token)))
+            .collect();
+
+        match common_args.format {
+            Some(OutputFormat::Json) => println!("{}", json!(statuses)),
+            _ => summarize(&statuses, &self.name),
+        }
+
+        Ok(())
+    }
+
+    fn new_value(&self) -> Result<String> {
+        let output = common::apply_script(&env::current_dir()?, &self.value_from_command)?;
+        let value = String::from_utf8(output.stdout)?.trim_end().to_string();
+        if value.is_empty() {
+            return Err(anyhow!(
+                "The command {:?} did not output any value",
+                self.value_from_command
+            ));
+        }
+        Ok(value)
+    }
+
+    fn rotate(&self, repo: &RemoteRepo, value: &str, token: &str) -> Status {
+        let result = set_secret(repo, value, &self.name, token);
+        let verification = match (&result, &self.verify_workflow) {
+            (Ok(_), Some(workflow)) => Some(
+                github::dispatch_workflow(repo, workflow, &self.verify_ref, token)
+                    .map_err(|e| format!("{:?}", e)),
+            ),
+            _ => None,
+        };
+        Status {
+            repo: repo.full_name(),
+            result: result.map_err(|e| format!("{:?}", e)),
+            verification,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    result: Result<(), String>,
+    verification: Option<Result<(), String>>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            self.status(),
+            self.verification(),
+        ])
+    }
+
+    fn status(&self) -> Cell {
+        match &self.result {
+            Ok(_) => cell!(Fgr -> "Updated"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn verification(&self) -> Cell {
+        match &self.verification {
+            None => cell!(r -> "-"),
+            Some(Ok(_)) => cell!(Fgr -> "Dispatched"),
+            Some(Err(e)) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err() || matches!(self.verification, Some(Err(_)))
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let msg = match (&self.result, &self.verification) {
+            (Err(e), _) => e.clone(),
+            (_, Some(Err(e))) => format!("Cannot dispatch verification workflow: {}", e),
+            _ => panic!("This should have an error here"),
+        };
+
+        ErrorReport::from_message(&self.repo, &msg)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Secret", "Verification"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status], name: &str) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let updated = statuses.iter().filter(|s| s.result.is_ok()).count();
+
+    if updated > 0 {
+        let msg = format!("\nRotated secret {} for {} repos!", name, updated);
+        println!("{}", msg.green());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when rotating secret:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
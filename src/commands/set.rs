@@ -1,5 +1,6 @@
 use super::set_default_organisation::*;
 use super::set_info::*;
+use super::set_org_defaults::*;
 use super::set_secret::*;
 use super::set_team_permission::*;
 use crate::cli::Args as CommonArgs;
@@ -24,6 +25,8 @@ pub enum SetCommand {
     Info(InfoArgs),
     #[command(name = "organisation")]
     Organisation(SetOrganisationArgs),
+    #[command(name = "org-defaults")]
+    OrgDefaults(SetOrgDefaultsArgs),
     #[command(name = "permission")]
     Permission(SetTeamPermissionArgs),
     #[command(name = "secret")]
@@ -35,6 +38,7 @@ impl SetCommand {
         match self {
             Self::Info(args) => args.run(common_args),
             Self::Organisation(args) => args.run(common_args),
+            Self::OrgDefaults(args) => args.run(common_args),
             Self::Permission(args) => args.set_permission(common_args),
             Self::Secret(args) => args.run(common_args),
         }
@@ -1,10 +1,14 @@
 use super::common;
+use super::models::template::TargetDelta;
 use super::models::Script;
 use crate::github;
 use crate::cli::Args as CommonArgs;
 
 use crate::github::RemoteRepo;
-use anyhow::{anyhow, Result};
+use crate::path;
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
 
 use crate::filter::Filter;
 use clap::Parser;
@@ -17,6 +21,10 @@ use clap::Parser;
 /// When it is provided --des-script will override --description
 ///
 /// Similar to --web-script and --website
+///
+/// --des-format and --web-format fill in the replacements of the template that a repository was
+/// generated from, e.g. "Finite state morphology for {__LANGNAME__}". They are read from the
+/// .gut/delta.toml of the local repository, and the underscores around a pattern can be left out.
 pub struct InfoArgs {
     #[arg(long, short)]
     /// Target organisation name
@@ -38,18 +46,25 @@ pub struct InfoArgs {
     #[arg(long)]
     /// The script that will produce a website
     pub web_script: Option<Script>,
+    #[arg(long, conflicts_with = "des_script")]
+    /// Description with {PATTERN} placeholders for the template replacements of each repository
+    pub des_format: Option<String>,
+    #[arg(long, conflicts_with = "web_script")]
+    /// Homepage with {PATTERN} placeholders for the template replacements of each repository
+    pub web_format: Option<String>,
 }
 
 impl InfoArgs {
     pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
         let user_token = common::user_token()?;
+        let root = common::root()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
 
         let filtered_repos =
             common::query_and_filter_repositories(&organisation, Some(&self.regex), &user_token)?;
 
         for repo in filtered_repos {
-            let result = set_info(&repo, self, &user_token);
+            let result = set_info(&repo, self, &root, &user_token);
             match result {
                 Ok(_) => println!("Set info for repo {} successfully", repo.name),
                 Err(e) => println!("Failed to set info for repo {} because {:?}", repo.name, e),
@@ -59,19 +74,31 @@ impl InfoArgs {
     }
 }
 
-fn set_info(repo: &RemoteRepo, args: &InfoArgs, token: &str) -> Result<()> {
-    let des = get_text(
-        repo,
-        args.description.as_deref(),
-        args.des_script.as_ref(),
-        "No description is provided",
-    );
-    let web = get_text(
-        repo,
-        args.website.as_deref(),
-        args.web_script.as_ref(),
-        "No website is provided",
-    );
+fn set_info(repo: &RemoteRepo, args: &InfoArgs, root: &str, token: &str) -> Result<()> {
+    let replacements = if args.des_format.is_some() || args.web_format.is_some() {
+        template_replacements(repo, root)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let des = match &args.des_format {
+        Some(format) => Ok(fill_placeholders(format, &replacements)?),
+        None => get_text(
+            repo,
+            args.description.as_deref(),
+            args.des_script.as_ref(),
+            "No description is provided",
+        ),
+    };
+    let web = match &args.web_format {
+        Some(format) => Ok(fill_placeholders(format, &replacements)?),
+        None => get_text(
+            repo,
+            args.website.as_deref(),
+            args.web_script.as_ref(),
+            "No website is provided",
+        ),
+    };
 
     github::set_repo_metadata(repo, des.ok().as_deref(), web.ok().as_deref(), token)?;
     Ok(())
@@ -92,3 +119,58 @@ fn get_text(
             .map(|s| s.to_string())
     }
 }
+
+/// The replacements of the template in the .gut/delta.toml of the local repository
+fn template_replacements(repo: &RemoteRepo, root: &str) -> Result<BTreeMap<String, String>> {
+    let delta = path::local_path_repo(&repo.owner, &repo.name, root).join(".gut/delta.toml");
+    let delta = TargetDelta::get(&delta)
+        .with_context(|| format!("Cannot read {:?}, is the repository cloned?", delta))?;
+    Ok(delta.replacements)
+}
+
+fn fill_placeholders(format: &str, replacements: &BTreeMap<String, String>) -> Result<String> {
+    let placeholder = Regex::new(r"\{([^{}]+)\}")?;
+    let mut missing = vec![];
+    let text = placeholder.replace_all(format, |captures: &regex::Captures| {
+        let key = &captures[1];
+        let value = replacements
+            .iter()
+            .find(|(pattern, _)| *pattern == key || pattern.trim_matches('_') == key)
+            .map(|(_, value)| value.clone());
+        value.unwrap_or_else(|| {
+            missing.push(key.to_string());
+            String::new()
+        })
+    });
+
+    if missing.is_empty() {
+        Ok(text.into_owned())
+    } else {
+        Err(anyhow!(
+            "No template replacement for {}",
+            missing.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_placeholders() {
+        let mut replacements = BTreeMap::new();
+        replacements.insert("__UND__".to_string(), "sme".to_string());
+        replacements.insert("__LANGNAME__".to_string(), "North Sami".to_string());
+
+        assert_eq!(
+            fill_placeholders(
+                "Finite state morphology for {LANGNAME} ({__UND__})",
+                &replacements
+            )
+            .unwrap(),
+            "Finite state morphology for North Sami (sme)"
+        );
+        assert!(fill_placeholders("Keyboard for {LANGCODE}", &replacements).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::github;
+use crate::org_settings::{Deviation, OrgSettings};
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{format, row, Table};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Set the settings of organisations to the values of a policy file
+///
+/// The policy file is the one of show org-settings. Only the settings that differ from the
+/// policy are changed. The 2FA requirement cannot be set through the api, so it is only
+/// reported.
+pub struct SetOrgDefaultsArgs {
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Set the settings of every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Policy file with the settings to set
+    pub policy: PathBuf,
+    #[arg(long)]
+    /// Only show the settings that would be changed
+    pub dry_run: bool,
+}
+
+impl SetOrgDefaultsArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let policy = OrgSettings::load(&self.policy)?;
+
+        let organisations = if self.all_orgs {
+            github::get_user_orgs(&user_token)?
+        } else {
+            vec![common::organisation(self.organisation.as_deref())?]
+        };
+
+        let mut changes = vec![];
+        for org in &organisations {
+            match github::get_org_settings(org, &user_token) {
+                Ok(current) => {
+                    let deviations = current.deviations(&policy);
+                    if !deviations.is_empty() {
+                        changes.push((org.as_str(), current, deviations));
+                    }
+                }
+                Err(e) => println!("Failed to read the settings of {} because {:?}", org, e),
+            }
+        }
+
+        if changes.is_empty() {
+            println!("The settings of the organisations already match the policy");
+            return Ok(());
+        }
+
+        to_table(&changes).printstd();
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        if !confirm(changes.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        for (org, current, deviations) in &changes {
+            if deviations
+                .iter()
+                .any(|d| d.setting == "two_factor_requirement_enabled")
+            {
+                println!(
+                    "The 2FA requirement of {} has to be changed in its settings on GitHub",
+                    org
+                );
+            }
+            match github::set_org_settings(org, &to_set(current, &policy), &user_token) {
+                Ok(_) => println!("Set the settings of {}", org),
+                Err(e) => println!("Failed to set the settings of {} because {:?}", org, e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The settings to change, the enabled repositories go along with the allowed actions
+fn to_set(current: &OrgSettings, policy: &OrgSettings) -> OrgSettings {
+    let differs = |wanted: &Option<String>, actual: &Option<String>| {
+        wanted.as_ref().filter(|_| wanted != actual).cloned()
+    };
+
+    let actions_allowed_actions = differs(
+        &policy.actions_allowed_actions,
+        &current.actions_allowed_actions,
+    );
+    let mut actions_enabled_repositories = differs(
+        &policy.actions_enabled_repositories,
+        &current.actions_enabled_repositories,
+    );
+    if actions_allowed_actions.is_some() && actions_enabled_repositories.is_none() {
+        actions_enabled_repositories = policy
+            .actions_enabled_repositories
+            .clone()
+            .or_else(|| current.actions_enabled_repositories.clone());
+    }
+    let members_can_create_repositories = policy
+        .members_can_create_repositories
+        .filter(|p| Some(*p) != current.members_can_create_repositories);
+
+    OrgSettings {
+        default_repository_permission: differs(
+            &policy.default_repository_permission,
+            &current.default_repository_permission,
+        ),
+        members_can_create_repositories,
+        two_factor_requirement_enabled: None,
+        actions_enabled_repositories,
+        actions_allowed_actions,
+        default_workflow_permissions: differs(
+            &policy.default_workflow_permissions,
+            &current.default_workflow_permissions,
+        ),
+    }
+}
+
+fn to_table(changes: &[(&str, OrgSettings, Vec<Deviation>)]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Organisation", "Setting", "Current", "New"]);
+    for (org, _, deviations) in changes {
+        for (i, deviation) in deviations.iter().enumerate() {
+            table.add_row(row![
+                b -> if i == 0 { *org } else { "" },
+                deviation.setting,
+                deviation.actual.as_deref().unwrap_or("unknown"),
+                Fg -> deviation.expected
+            ]);
+        }
+    }
+    table
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to change the settings of {} organisation(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_set() {
+        let current = OrgSettings {
+            default_repository_permission: Some("read".into()),
+            members_can_create_repositories: Some(true),
+            actions_enabled_repositories: Some("all".into()),
+            actions_allowed_actions: Some("all".into()),
+            default_workflow_permissions: Some("write".into()),
+            ..Default::default()
+        };
+        let policy = OrgSettings {
+            default_repository_permission: Some("read".into()),
+            members_can_create_repositories: Some(false),
+            actions_allowed_actions: Some("selected".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_set(&current, &policy),
+            OrgSettings {
+                members_can_create_repositories: Some(false),
+                actions_enabled_repositories: Some("all".into()),
+                actions_allowed_actions: Some("selected".into()),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -50,7 +50,7 @@ impl SecretArgs {
     }
 }
 
-fn set_secret(repo: &RemoteRepo, value: &str, name: &str, token: &str) -> Result<()> {
+pub fn set_secret(repo: &RemoteRepo, value: &str, name: &str, token: &str) -> Result<()> {
     let public_key = github::get_public_key(repo, token)?;
     let encrypted_value = encrypt(value, &public_key.key)?;
     github::set_secret(repo, name, &encrypted_value, &public_key.key_id, token)?;
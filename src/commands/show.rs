@@ -1,6 +1,16 @@
+use super::show_ci_config::*;
+use super::show_community_files::*;
 use super::show_config::*;
+use super::show_default_branches::*;
+use super::show_dependencies::*;
+use super::show_forks::*;
+use super::show_inbox::*;
+use super::show_org_settings::*;
 use super::show_repos::*;
+use super::show_size::*;
+use super::show_url::*;
 use super::show_users::*;
+use super::show_vulnerabilities::*;
 use anyhow::Result;
 use clap::Parser;
 use crate::cli::Args as CommonArgs;
@@ -19,21 +29,51 @@ impl ShowArgs {
 
 #[derive(Debug, Parser)]
 pub enum ShowCommand {
+    #[command(name = "ci-config")]
+    CiConfig(ShowCiConfigArgs),
+    #[command(name = "community-files")]
+    CommunityFiles(ShowCommunityFilesArgs),
     #[command(name = "config")]
     // Show current configuration
     Config,
+    #[command(name = "default-branches")]
+    DefaultBranches(ShowDefaultBranchesArgs),
+    #[command(name = "dependencies", aliases = &["deps"])]
+    Dependencies(ShowDependenciesArgs),
+    #[command(name = "forks")]
+    Forks(ShowForksArgs),
+    #[command(name = "inbox")]
+    Inbox(ShowInboxArgs),
+    #[command(name = "org-settings")]
+    OrgSettings(ShowOrgSettingsArgs),
     #[command(name = "repositories", aliases = &["repos"])]
     Repos(ShowReposArgs),
+    #[command(name = "size")]
+    Size(ShowSizeArgs),
+    #[command(name = "url", aliases = &["urls"])]
+    Url(ShowUrlArgs),
     #[command(name = "users")]
     Users(ShowUsersArgs),
+    #[command(name = "vulnerabilities", aliases = &["vulns"])]
+    Vulnerabilities(ShowVulnerabilitiesArgs),
 }
 
 impl ShowCommand {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         match self {
+            Self::CiConfig(args) => args.run(common_args),
+            Self::CommunityFiles(args) => args.run(common_args),
             Self::Config => show_config(common_args),
+            Self::DefaultBranches(args) => args.run(common_args),
+            Self::Dependencies(args) => args.run(common_args),
+            Self::Forks(args) => args.run(common_args),
+            Self::Inbox(args) => args.run(common_args),
+            Self::OrgSettings(args) => args.run(common_args),
             Self::Repos(args) => args.show(common_args),
+            Self::Size(args) => args.run(common_args),
+            Self::Url(args) => args.run(common_args),
             Self::Users(args) => args.run(common_args),
+            Self::Vulnerabilities(args) => args.run(common_args),
         }
     }
 }
@@ -0,0 +1,31 @@
+use super::show_ci_config_drift::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Check the CI configuration of repositories
+pub struct ShowCiConfigArgs {
+    #[command(subcommand)]
+    command: ShowCiConfigCommand,
+}
+
+impl ShowCiConfigArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum ShowCiConfigCommand {
+    #[command(name = "drift")]
+    Drift(ShowCiConfigDriftArgs),
+}
+
+impl ShowCiConfigCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Drift(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git::{self, GitCredential};
+use crate::github::{self, RemoteRepo};
+use crate::path::{self, dir_name, owner_name};
+use crate::user::User;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::build::TreeUpdateBuilder;
+use git2::{FileMode, ObjectType, Oid, Repository};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+static WORKFLOWS: &str = ".github/workflows";
+
+#[derive(Debug, Parser)]
+/// Compare the GitHub Actions workflows of repositories with their canonical versions
+///
+/// The canonical workflows are the .yml files in .github/workflows of the reference, or in the
+/// reference directory itself. A workflow of a repository is outdated when it is an earlier
+/// version of the canonical one from the history of the reference, and locally modified when
+/// it is not. The committed workflows on the current branch are compared.
+pub struct ShowCiConfigDriftArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Directory with the canonical workflows, or the name of a local repository of the
+    /// organisation
+    pub reference: String,
+    #[arg(long)]
+    /// Open pull requests that add the missing and update the outdated workflows
+    pub pr: bool,
+    #[arg(long, requires = "pr")]
+    /// Also replace locally modified workflows in the pull requests
+    pub overwrite: bool,
+    #[arg(long, short, default_value = "gut/ci-config")]
+    /// Name of the branch for the pull requests
+    pub branch: String,
+    #[arg(long, short, default_value = "Update CI workflows")]
+    /// Commit message and title of the pull requests
+    pub message: String,
+}
+
+impl ShowCiConfigDriftArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let reference = Reference::load(&self.reference_dir(&organisation, &root)?)?;
+        if reference.workflows.is_empty() {
+            bail!("There are no workflows in the reference {}", self.reference);
+        }
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| check(d, &organisation, &reference))
+            .collect();
+
+        let to_update = statuses
+            .iter()
+            .filter(|s| !s.updates(self.overwrite).is_empty())
+            .count();
+        if self.pr && to_update > 0 {
+            if !confirm(to_update)? {
+                println!("Command is aborted. Nothing change!");
+                return Ok(());
+            }
+            let user = common::user()?;
+            // Sequential, because it pushes and opens pull requests
+            for status in statuses.iter_mut() {
+                self.open_pr(status, &reference, &user);
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, reference.workflows.len());
+        }
+
+        Ok(())
+    }
+
+    fn reference_dir(&self, organisation: &str, root: &str) -> Result<PathBuf> {
+        let dir = PathBuf::from(&self.reference);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        let dir = path::local_path_repo(organisation, &self.reference, root);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        bail!(
+            "Cannot find the reference {}, clone it or give the directory of the workflows",
+            self.reference
+        )
+    }
+
+    fn open_pr(&self, status: &mut Status, reference: &Reference, user: &User) {
+        let updates = status.updates(self.overwrite);
+        if updates.is_empty() {
+            return;
+        }
+
+        let open = || -> Result<String> {
+            let git_repo = git::open(&status.dir)?;
+            if !git_repo.head()?.is_branch() {
+                bail!("HEAD is detached, check out a branch first");
+            }
+            let current_branch = git::head_shorthand(&git_repo)?;
+            let head = git_repo.head()?.peel_to_commit()?;
+
+            let mut update = TreeUpdateBuilder::new();
+            for (file, _) in &updates {
+                let blob = git_repo.blob(&reference.workflows[file])?;
+                update.upsert(format!("{}/{}", WORKFLOWS, file), blob, FileMode::Blob);
+            }
+            let tree = git_repo.find_tree(update.create_updated(&git_repo, &head.tree()?)?)?;
+            let sig = git_repo.signature()?;
+            let oid = git_repo.commit(None, &sig, &sig, &self.message, &tree, &[&head])?;
+            git_repo.branch(&self.branch, &git_repo.find_commit(oid)?, true)?;
+            git::push::push_branch(
+                &git_repo,
+                &self.branch,
+                "origin",
+                Some(GitCredential::from(user)),
+            )?;
+
+            let files: Vec<_> = updates
+                .iter()
+                .map(|(file, drift)| format!("- {} ({})", file, drift.describe()))
+                .collect();
+            let body = format!(
+                "This brings the workflows in line with the canonical versions of {}:\n\n{}",
+                reference.name,
+                files.join("\n")
+            );
+            let remote_repo = RemoteRepo {
+                name: status.repo.clone(),
+                owner: status.owner.clone(),
+                ssh_url: String::new(),
+                https_url: String::new(),
+            };
+            let pr = github::create_pull_request(
+                &remote_repo,
+                &self.branch,
+                &current_branch,
+                &self.message,
+                &body,
+                &user.token,
+            )?;
+            Ok(pr.html_url)
+        };
+
+        match open() {
+            Ok(url) => status.pull_request = Some(url),
+            Err(e) => status.result = Err(format!("{:?}", e)),
+        }
+    }
+}
+
+/// The canonical workflows and all earlier versions of them
+struct Reference {
+    name: String,
+    workflows: BTreeMap<String, Vec<u8>>,
+    /// Blob ids of the earlier versions of each workflow, from the history of the reference
+    history: BTreeMap<String, HashSet<Oid>>,
+}
+
+impl Reference {
+    fn load(dir: &PathBuf) -> Result<Reference> {
+        let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+        let nested = dir.join(WORKFLOWS);
+        let workflows_dir = if nested.is_dir() {
+            nested
+        } else {
+            dir.to_path_buf()
+        };
+
+        let mut workflows = BTreeMap::new();
+        for entry in std::fs::read_dir(&workflows_dir)
+            .with_context(|| format!("Cannot read {:?}", workflows_dir))?
+        {
+            let path = entry?.path();
+            let is_workflow = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("yml") | Some("yaml")
+            );
+            if let (true, Some(file)) = (is_workflow, path.file_name()) {
+                let content =
+                    std::fs::read(&path).with_context(|| format!("Cannot read {:?}", path))?;
+                workflows.insert(file.to_string_lossy().to_string(), content);
+            }
+        }
+
+        let history = match Repository::discover(&workflows_dir) {
+            Ok(git_repo) => earlier_versions(&git_repo, &workflows_dir, workflows.keys())?,
+            Err(_) => BTreeMap::new(),
+        };
+
+        Ok(Reference {
+            name,
+            workflows,
+            history,
+        })
+    }
+}
+
+/// Blob ids of every version of the files in the history of the repository of `dir`
+pub fn earlier_versions<'a>(
+    git_repo: &Repository,
+    dir: &Path,
+    files: impl Iterator<Item = &'a String>,
+) -> Result<BTreeMap<String, HashSet<Oid>>> {
+    let workdir = git_repo
+        .workdir()
+        .ok_or_else(|| anyhow!("The reference is a bare repository"))?
+        .canonicalize()?;
+    let relative = dir.canonicalize()?;
+    let relative = relative.strip_prefix(&workdir)?;
+
+    let mut revwalk = git_repo.revwalk()?;
+    if revwalk.push_head().is_err() {
+        return Ok(BTreeMap::new());
+    }
+    let trees = revwalk
+        .map(|oid| Ok(git_repo.find_commit(oid?)?.tree()?))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut history = BTreeMap::new();
+    for file in files {
+        let path = relative.join(file);
+        let versions: HashSet<_> = trees
+            .iter()
+            .filter_map(|tree| tree.get_path(&path).ok())
+            .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+            .map(|entry| entry.id())
+            .collect();
+        history.insert(file.clone(), versions);
+    }
+    Ok(history)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Drift {
+    UpToDate,
+    Missing,
+    /// An earlier version of the canonical file
+    Outdated,
+    /// Changed in the repository
+    Modified,
+}
+
+impl Drift {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Drift::UpToDate => "up to date",
+            Drift::Missing => "missing",
+            Drift::Outdated => "outdated",
+            Drift::Modified => "locally modified",
+        }
+    }
+}
+
+fn check(dir: &PathBuf, organisation: &str, reference: &Reference) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let check = || -> Result<BTreeMap<String, Drift>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let tree = git_repo.head()?.peel_to_tree()?;
+
+        let mut drifts = BTreeMap::new();
+        for (file, content) in &reference.workflows {
+            let path = format!("{}/{}", WORKFLOWS, file);
+            let drift = match tree.get_path(Path::new(&path)) {
+                Err(_) => Drift::Missing,
+                Ok(entry) if entry.id() == Oid::hash_object(ObjectType::Blob, content)? => {
+                    Drift::UpToDate
+                }
+                Ok(entry)
+                    if reference
+                        .history
+                        .get(file)
+                        .is_some_and(|versions| versions.contains(&entry.id())) =>
+                {
+                    Drift::Outdated
+                }
+                Ok(_) => Drift::Modified,
+            };
+            drifts.insert(file.clone(), drift);
+        }
+        Ok(drifts)
+    };
+
+    Status {
+        repo,
+        owner,
+        dir: dir.clone(),
+        result: check().map_err(|e| format!("{:?}", e)),
+        pull_request: None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    #[serde(skip)]
+    owner: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    result: Result<BTreeMap<String, Drift>, String>,
+    pull_request: Option<String>,
+}
+
+impl Status {
+    fn files(&self, drift: Drift) -> Vec<String> {
+        match &self.result {
+            Ok(drifts) => drifts
+                .iter()
+                .filter(|(_, d)| **d == drift)
+                .map(|(file, _)| file.clone())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// The workflows that a pull request changes
+    fn updates(&self, overwrite: bool) -> Vec<(String, Drift)> {
+        match &self.result {
+            Ok(drifts) => drifts
+                .iter()
+                .filter(|(_, d)| match d {
+                    Drift::Missing | Drift::Outdated => true,
+                    Drift::Modified => overwrite,
+                    Drift::UpToDate => false,
+                })
+                .map(|(file, d)| (file.clone(), *d))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn is_up_to_date(&self) -> bool {
+        matches!(&self.result, Ok(drifts) if drifts.values().all(|d| *d == Drift::UpToDate))
+    }
+
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(_) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(Fr -> self.files(Drift::Missing).join("\n")),
+                cell!(Fy -> self.files(Drift::Outdated).join("\n")),
+                cell!(Fm -> self.files(Drift::Modified).join("\n")),
+                self.pull_request_cell(),
+            ]),
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn pull_request_cell(&self) -> Cell {
+        match &self.pull_request {
+            Some(url) => cell!(Fg -> url),
+            None => cell!("-"),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status], workflows: usize) {
+    let up_to_date = statuses.iter().filter(|s| s.is_up_to_date()).count();
+    println!(
+        "{} of {} repositories have the {} canonical workflows",
+        up_to_date,
+        statuses.len(),
+        workflows
+    );
+
+    let rows: Vec<_> = statuses
+        .iter()
+        .filter(|s| !s.is_up_to_date())
+        .map(|s| s.to_row())
+        .collect();
+    if !rows.is_empty() {
+        let mut table = Table::init(rows);
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row![
+            "Repo",
+            "Missing",
+            "Outdated",
+            "Locally modified",
+            "Pull request"
+        ]);
+        table.printstd();
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when comparing workflows:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to open a pull request with the workflows in {} repo(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,459 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::show_ci_config_drift::{earlier_versions, Drift};
+use super::table::SortArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git::{self, GitCredential};
+use crate::github::{self, RemoteRepo};
+use crate::path::{self, dir_name, owner_name};
+use crate::user::User;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::build::TreeUpdateBuilder;
+use git2::{FileMode, ObjectType, Oid, Repository};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Community health files that GitHub recognises, in the root or in .github of a repository
+static HEALTH_FILES: &[&str] = &[
+    "CODE_OF_CONDUCT.md",
+    "CONTRIBUTING.md",
+    "FUNDING.yml",
+    "PULL_REQUEST_TEMPLATE.md",
+    "SECURITY.md",
+    "SUPPORT.md",
+];
+
+static ISSUE_TEMPLATES: &str = ".github/ISSUE_TEMPLATE";
+
+#[derive(Debug, Parser)]
+/// Compare the community health files of repositories with their canonical versions
+///
+/// The canonical files are the issue templates in .github/ISSUE_TEMPLATE and the
+/// PULL_REQUEST_TEMPLATE.md, CONTRIBUTING.md, CODE_OF_CONDUCT.md, SECURITY.md, SUPPORT.md and
+/// FUNDING.yml files in the root or .github of the source. They keep their path from the source
+/// in the repositories. A file is outdated when it is an earlier version of the canonical one
+/// from the history of the source, and locally modified when it is not. To use them as the
+/// defaults of a whole organisation, select its .github repository with -r '^\.github$'.
+pub struct ShowCommunityFilesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Directory with the canonical files, or the name of a local repository of the
+    /// organisation
+    pub source: String,
+    #[arg(long)]
+    /// Open pull requests that add the missing and update the outdated files
+    pub pr: bool,
+    #[arg(long, requires = "pr")]
+    /// Also replace locally modified files in the pull requests
+    pub overwrite: bool,
+    #[arg(long, short, default_value = "gut/community-files")]
+    /// Name of the branch for the pull requests
+    pub branch: String,
+    #[arg(long, short, default_value = "Update community health files")]
+    /// Commit message and title of the pull requests
+    pub message: String,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+impl ShowCommunityFilesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let source = Source::load(&self.source_dir(&organisation, &root)?)?;
+        if source.files.is_empty() {
+            bail!("There are no community health files in {}", self.source);
+        }
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| check(d, &organisation, &source))
+            .collect();
+        self.sort.sort(&mut statuses, &titles(), |s| s.to_row())?;
+
+        let to_update = statuses
+            .iter()
+            .filter(|s| !s.updates(self.overwrite).is_empty())
+            .count();
+        if self.pr && to_update > 0 {
+            if !confirm(to_update)? {
+                println!("Command is aborted. Nothing change!");
+                return Ok(());
+            }
+            let user = common::user()?;
+            // Sequential, because it pushes and opens pull requests
+            for status in statuses.iter_mut() {
+                self.open_pr(status, &source, &user);
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, source.files.len());
+        }
+
+        Ok(())
+    }
+
+    fn source_dir(&self, organisation: &str, root: &str) -> Result<PathBuf> {
+        let dir = PathBuf::from(&self.source);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        let dir = path::local_path_repo(organisation, &self.source, root);
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+        bail!(
+            "Cannot find the source {}, clone it or give the directory of the files",
+            self.source
+        )
+    }
+
+    fn open_pr(&self, status: &mut Status, source: &Source, user: &User) {
+        let updates = status.updates(self.overwrite);
+        if updates.is_empty() {
+            return;
+        }
+
+        let open = || -> Result<String> {
+            let git_repo = git::open(&status.dir)?;
+            if !git_repo.head()?.is_branch() {
+                bail!("HEAD is detached, check out a branch first");
+            }
+            let current_branch = git::head_shorthand(&git_repo)?;
+            let head = git_repo.head()?.peel_to_commit()?;
+
+            let mut update = TreeUpdateBuilder::new();
+            for (file, _) in &updates {
+                let blob = git_repo.blob(&source.files[file])?;
+                update.upsert(file.as_str(), blob, FileMode::Blob);
+            }
+            let tree = git_repo.find_tree(update.create_updated(&git_repo, &head.tree()?)?)?;
+            let sig = git_repo.signature()?;
+            let oid = git_repo.commit(None, &sig, &sig, &self.message, &tree, &[&head])?;
+            git_repo.branch(&self.branch, &git_repo.find_commit(oid)?, true)?;
+            git::push::push_branch(
+                &git_repo,
+                &self.branch,
+                "origin",
+                Some(GitCredential::from(user)),
+            )?;
+
+            let files: Vec<_> = updates
+                .iter()
+                .map(|(file, drift)| format!("- {} ({})", file, drift.describe()))
+                .collect();
+            let body = format!(
+                "This brings the community health files in line with the canonical versions of {}:\n\n{}",
+                source.name,
+                files.join("\n")
+            );
+            let remote_repo = RemoteRepo {
+                name: status.repo.clone(),
+                owner: status.owner.clone(),
+                ssh_url: String::new(),
+                https_url: String::new(),
+            };
+            let pr = github::create_pull_request(
+                &remote_repo,
+                &self.branch,
+                &current_branch,
+                &self.message,
+                &body,
+                &user.token,
+            )?;
+            Ok(pr.html_url)
+        };
+
+        match open() {
+            Ok(url) => status.pull_request = Some(url),
+            Err(e) => status.result = Err(format!("{:?}", e)),
+        }
+    }
+}
+
+/// The canonical files, by their path in the repositories, and all earlier versions of them
+struct Source {
+    name: String,
+    files: BTreeMap<String, Vec<u8>>,
+    history: BTreeMap<String, HashSet<Oid>>,
+}
+
+impl Source {
+    fn load(dir: &PathBuf) -> Result<Source> {
+        let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let mut files = BTreeMap::new();
+        for file in health_files(dir) {
+            let path = dir.join(&file);
+            let content =
+                std::fs::read(&path).with_context(|| format!("Cannot read {:?}", path))?;
+            files.insert(file, content);
+        }
+
+        let history = match Repository::discover(dir) {
+            Ok(git_repo) => earlier_versions(&git_repo, dir, files.keys())?,
+            Err(_) => BTreeMap::new(),
+        };
+
+        Ok(Source {
+            name,
+            files,
+            history,
+        })
+    }
+}
+
+/// Paths of the community health files in a directory, relative to it
+fn health_files(dir: &Path) -> Vec<String> {
+    let mut files = vec![];
+    for name in HEALTH_FILES {
+        for file in [name.to_string(), format!(".github/{}", name)] {
+            if dir.join(&file).is_file() {
+                files.push(file);
+            }
+        }
+    }
+
+    let templates = WalkDir::new(dir.join(ISSUE_TEMPLATES))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+    for entry in templates {
+        if let Ok(relative) = entry.path().strip_prefix(dir) {
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn check(dir: &PathBuf, organisation: &str, source: &Source) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let check = || -> Result<BTreeMap<String, Drift>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let tree = git_repo.head()?.peel_to_tree()?;
+
+        let mut drifts = BTreeMap::new();
+        for (file, content) in &source.files {
+            let drift = match tree.get_path(Path::new(file)) {
+                Err(_) => Drift::Missing,
+                Ok(entry) if entry.id() == Oid::hash_object(ObjectType::Blob, content)? => {
+                    Drift::UpToDate
+                }
+                Ok(entry)
+                    if source
+                        .history
+                        .get(file)
+                        .is_some_and(|versions| versions.contains(&entry.id())) =>
+                {
+                    Drift::Outdated
+                }
+                Ok(_) => Drift::Modified,
+            };
+            drifts.insert(file.clone(), drift);
+        }
+        Ok(drifts)
+    };
+
+    Status {
+        repo,
+        owner,
+        dir: dir.clone(),
+        result: check().map_err(|e| format!("{:?}", e)),
+        pull_request: None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    #[serde(skip)]
+    owner: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    result: Result<BTreeMap<String, Drift>, String>,
+    pull_request: Option<String>,
+}
+
+impl Status {
+    fn files(&self, drift: Drift) -> Vec<String> {
+        match &self.result {
+            Ok(drifts) => drifts
+                .iter()
+                .filter(|(_, d)| **d == drift)
+                .map(|(file, _)| file.clone())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// The files that a pull request changes
+    fn updates(&self, overwrite: bool) -> Vec<(String, Drift)> {
+        match &self.result {
+            Ok(drifts) => drifts
+                .iter()
+                .filter(|(_, d)| match d {
+                    Drift::Missing | Drift::Outdated => true,
+                    Drift::Modified => overwrite,
+                    Drift::UpToDate => false,
+                })
+                .map(|(file, d)| (file.clone(), *d))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn is_up_to_date(&self) -> bool {
+        matches!(&self.result, Ok(drifts) if drifts.values().all(|d| *d == Drift::UpToDate))
+    }
+
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(_) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(Fr -> self.files(Drift::Missing).join("\n")),
+                cell!(Fy -> self.files(Drift::Outdated).join("\n")),
+                cell!(Fm -> self.files(Drift::Modified).join("\n")),
+                self.pull_request_cell(),
+            ]),
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn pull_request_cell(&self) -> Cell {
+        match &self.pull_request {
+            Some(url) => cell!(Fg -> url),
+            None => cell!("-"),
+        }
+    }
+
+    fn to_error_report(&self) -> Option<ErrorReport> {
+        match &self.result {
+            Err(e) => Some(ErrorReport::from_message(&self.repo, e)),
+            Ok(_) => None,
+        }
+    }
+}
+
+fn titles() -> Row {
+    row![
+        "Repo",
+        "Missing",
+        "Outdated",
+        "Locally modified",
+        "Pull request"
+    ]
+}
+
+fn summarize(statuses: &[Status], files: usize) {
+    let up_to_date = statuses.iter().filter(|s| s.is_up_to_date()).count();
+    println!(
+        "{} of {} repositories have the {} canonical community health files",
+        up_to_date,
+        statuses.len(),
+        files
+    );
+
+    let rows: Vec<_> = statuses
+        .iter()
+        .filter(|s| !s.is_up_to_date())
+        .map(|s| s.to_row())
+        .collect();
+    if !rows.is_empty() {
+        let mut table = Table::init(rows);
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(titles());
+        table.printstd();
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| s.to_error_report())
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when comparing files:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to open a pull request with the community health files in {} repo(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |file: &str| {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "content").unwrap();
+        };
+        write("CONTRIBUTING.md");
+        write(".github/CODE_OF_CONDUCT.md");
+        write(".github/ISSUE_TEMPLATE/bug_report.md");
+        write(".github/ISSUE_TEMPLATE/config.yml");
+        write(".github/workflows/ci.yml");
+        write("README.md");
+
+        assert_eq!(
+            health_files(dir.path()),
+            vec![
+                ".github/CODE_OF_CONDUCT.md",
+                ".github/ISSUE_TEMPLATE/bug_report.md",
+                ".github/ISSUE_TEMPLATE/config.yml",
+                "CONTRIBUTING.md",
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use super::common;
 use crate::cli::Args as CommonArgs;
 use crate::config::Config;
+use crate::email::EmailSettings;
 
 pub fn show_config(_common_args: &CommonArgs) -> anyhow::Result<()> {
     let user = common::user()?;
@@ -19,5 +20,32 @@ pub fn show_config(_common_args: &CommonArgs) -> anyhow::Result<()> {
         "Default org: {}\nHttps? {}",
         organisation, use_https);
 
+    let config = Config::load()?;
+    if !config.defaults.is_empty() {
+        println!("Command defaults:\n{}", toml::to_string(&config.defaults)?);
+    }
+    if !config.alias.is_empty() {
+        println!("Aliases:\n{}", toml::to_string(&config.alias)?);
+    }
+    if !config.network.is_empty() {
+        println!("Network:\n{}", toml::to_string(&config.network)?);
+    }
+    if !config.mirrors.is_empty() {
+        println!("Mirrors:\n{}", toml::to_string(&config.mirrors)?);
+    }
+    if !config.version_files.is_empty() {
+        println!("Version files:");
+        for rule in &config.version_files {
+            println!("{}", toml::to_string(rule)?);
+        }
+    }
+    if let Some(email) = &config.email {
+        let email = EmailSettings {
+            password: email.password.as_ref().map(|_| "***".to_string()),
+            ..email.clone()
+        };
+        println!("Email:\n{}", toml::to_string(&email)?);
+    }
+
     Ok(())
 }
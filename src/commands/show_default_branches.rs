@@ -0,0 +1,201 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, RemoteRepo};
+use anyhow::Result;
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Show the default branch of all repositories that match a regex
+///
+/// The repositories are counted per default branch, main, master or another one, to follow the
+/// migration from master to main at a glance.
+pub struct ShowDefaultBranchesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Only print the counts, not the table of repositories
+    pub summary: bool,
+}
+
+impl ShowDefaultBranchesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| default_branch(repo, &user_token))
+            .collect();
+        let counts = Counts::of(&statuses);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!({ "repos": statuses, "counts": counts }));
+            return Ok(());
+        }
+
+        if !self.summary {
+            to_table(&statuses).printstd();
+            println!();
+        }
+        counts.print();
+
+        let reports: Vec<_> = statuses
+            .iter()
+            .filter_map(|s| s.to_error_report())
+            .collect();
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when getting default branches:",
+                reports.len()
+            );
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+}
+
+fn default_branch(repo: &RemoteRepo, token: &str) -> Status {
+    let result = github::get_default_branch(&repo.owner, &repo.name, token);
+    Status {
+        repo: repo.full_name(),
+        branch: result.map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    branch: Result<String, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let branch = match &self.branch {
+            Ok(branch) if branch == "main" => cell!(Fg -> branch),
+            Ok(branch) if branch == "master" => cell!(Fy -> branch),
+            Ok(branch) => cell!(branch),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![cell!(b -> &self.repo), branch])
+    }
+
+    fn to_error_report(&self) -> Option<ErrorReport> {
+        let e = self.branch.as_ref().err()?;
+        Some(ErrorReport::from_message(&self.repo, e))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Counts {
+    main: usize,
+    master: usize,
+    other: usize,
+    failed: usize,
+}
+
+impl Counts {
+    fn of(statuses: &[Status]) -> Counts {
+        let mut counts = Counts::default();
+        for status in statuses {
+            match status.branch.as_deref() {
+                Ok("main") => counts.main += 1,
+                Ok("master") => counts.master += 1,
+                Ok(_) => counts.other += 1,
+                Err(_) => counts.failed += 1,
+            }
+        }
+        counts
+    }
+
+    fn print(&self) {
+        let known = self.main + self.master + self.other;
+        let percent = |n: usize| {
+            if known == 0 {
+                0.0
+            } else {
+                n as f64 * 100.0 / known as f64
+            }
+        };
+
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Default branch", "Repos", "%"]);
+        for (branch, n) in [
+            (cell!(Fg -> "main"), self.main),
+            (cell!(Fy -> "master"), self.master),
+            (cell!("other"), self.other),
+        ] {
+            table.add_row(Row::new(vec![
+                branch,
+                cell!(r -> n),
+                cell!(r -> format!("{:.0}", percent(n))),
+            ]));
+        }
+        table.printstd();
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Default branch"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let status = |branch: Result<&str, &str>| Status {
+            repo: "giellalt/lang-sme".to_string(),
+            branch: branch.map(|b| b.to_string()).map_err(|e| e.to_string()),
+        };
+        let statuses = [
+            status(Ok("main")),
+            status(Ok("master")),
+            status(Ok("main")),
+            status(Ok("develop")),
+            status(Err("Unsuccessful request with status code: 404 Not Found")),
+        ];
+        assert_eq!(
+            Counts::of(&statuses),
+            Counts {
+                main: 2,
+                master: 1,
+                other: 1,
+                failed: 1,
+            }
+        );
+    }
+}
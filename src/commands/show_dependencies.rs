@@ -0,0 +1,448 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+#[derive(Debug, Parser)]
+/// List the dependencies that local repositories declare, with the versions and repositories
+///
+/// Cargo.toml, package.json and requirements*.txt files anywhere in the repositories are read,
+/// except in .git, node_modules and target directories.
+pub struct ShowDependenciesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Regex of the dependencies to show, the repositories that use them are listed
+    pub dependency: Option<Regex>,
+    #[arg(long)]
+    /// Write every dependency of every repository to a csv file
+    pub csv: Option<PathBuf>,
+}
+
+impl ShowDependenciesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let results: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| dependencies(d, &organisation))
+            .collect();
+
+        let mut all = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(dependencies) => all.extend(dependencies),
+                Err(e) => reports.push(ErrorReport::from_message(&repo, &format!("{:?}", e))),
+            }
+        }
+        if let Some(regex) = &self.dependency {
+            all.retain(|d| regex.is_match(&d.name));
+        }
+
+        if let Some(path) = &self.csv {
+            std::fs::write(path, to_csv(&all))
+                .with_context(|| format!("Cannot write the csv file {:?}", path))?;
+        }
+
+        let inventory = inventory(&all);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(inventory));
+            return Ok(());
+        }
+
+        summarize(&inventory, self.dependency.is_some(), &reports);
+        if let Some(path) = &self.csv {
+            println!("\nWrote {} dependencies to {:?}", all.len(), path);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Ecosystem {
+    Cargo,
+    Npm,
+    Pypi,
+}
+
+impl std::fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Pypi => "pypi",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One dependency in one manifest
+#[derive(Debug, PartialEq)]
+struct Dependency {
+    ecosystem: Ecosystem,
+    name: String,
+    /// The version requirement as written, or where the dependency comes from
+    version: String,
+    /// normal, dev, build, peer or optional
+    kind: String,
+    repo: String,
+    manifest: String,
+}
+
+/// The versions of a dependency, with the repositories that use each
+#[derive(Debug, Serialize)]
+struct Entry {
+    ecosystem: Ecosystem,
+    name: String,
+    versions: BTreeMap<String, BTreeSet<String>>,
+}
+
+fn dependencies(dir: &PathBuf, organisation: &str) -> (String, Result<Vec<Dependency>>) {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+    let repo = format!("{}/{}", owner, name);
+
+    let read = || -> Result<Vec<Dependency>> {
+        let mut dependencies = vec![];
+        let walker = WalkDir::new(dir).into_iter().filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir()
+                && (name == ".git" || name == "node_modules" || name == "target"))
+        });
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy();
+            let parse = match file_name.as_ref() {
+                "Cargo.toml" => parse_cargo,
+                "package.json" => parse_package_json,
+                n if n.starts_with("requirements") && n.ends_with(".txt") => parse_requirements,
+                _ => continue,
+            };
+
+            let path = entry.path();
+            let manifest = path
+                .strip_prefix(dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string();
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read {}", manifest))?;
+            let parsed = parse(&content).with_context(|| format!("Cannot parse {}", manifest))?;
+
+            dependencies.extend(parsed.into_iter().map(|(ecosystem, name, version, kind)| {
+                Dependency {
+                    ecosystem,
+                    name,
+                    version,
+                    kind: kind.to_string(),
+                    repo: repo.clone(),
+                    manifest: manifest.clone(),
+                }
+            }));
+        }
+        Ok(dependencies)
+    };
+
+    let result = read();
+    (repo, result)
+}
+
+type Parsed = Vec<(Ecosystem, String, String, &'static str)>;
+
+fn parse_cargo(content: &str) -> Result<Parsed> {
+    let manifest: toml::Table = toml::from_str(content)?;
+    let mut tables = vec![];
+    for (key, kind) in [
+        ("dependencies", "normal"),
+        ("dev-dependencies", "dev"),
+        ("build-dependencies", "build"),
+    ] {
+        tables.push((manifest.get(key), kind));
+        let workspace = manifest.get("workspace").and_then(|w| w.get(key));
+        tables.push((workspace, kind));
+        for target in manifest
+            .get("target")
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|t| t.values())
+        {
+            tables.push((target.get(key), kind));
+        }
+    }
+
+    let mut dependencies = vec![];
+    for (table, kind) in tables {
+        let table = match table.and_then(|t| t.as_table()) {
+            Some(table) => table,
+            None => continue,
+        };
+        for (name, spec) in table {
+            let (name, version) = match spec {
+                toml::Value::String(version) => (name.clone(), version.clone()),
+                toml::Value::Table(spec) => {
+                    let name = spec
+                        .get("package")
+                        .and_then(|p| p.as_str())
+                        .unwrap_or(name)
+                        .to_string();
+                    let version =
+                        if let Some(version) = spec.get("version").and_then(|v| v.as_str()) {
+                            version.to_string()
+                        } else if spec.contains_key("workspace") {
+                            "workspace".to_string()
+                        } else if spec.contains_key("git") {
+                            "git".to_string()
+                        } else if spec.contains_key("path") {
+                            "path".to_string()
+                        } else {
+                            "*".to_string()
+                        };
+                    (name, version)
+                }
+                _ => continue,
+            };
+            dependencies.push((Ecosystem::Cargo, name, version, kind));
+        }
+    }
+    Ok(dependencies)
+}
+
+fn parse_package_json(content: &str) -> Result<Parsed> {
+    let package: serde_json::Value = serde_json::from_str(content)?;
+    let mut dependencies = vec![];
+    for (key, kind) in [
+        ("dependencies", "normal"),
+        ("devDependencies", "dev"),
+        ("peerDependencies", "peer"),
+        ("optionalDependencies", "optional"),
+    ] {
+        let table = match package.get(key).and_then(|t| t.as_object()) {
+            Some(table) => table,
+            None => continue,
+        };
+        for (name, version) in table {
+            let version = version.as_str().unwrap_or("*").to_string();
+            dependencies.push((Ecosystem::Npm, name.clone(), version, kind));
+        }
+    }
+    Ok(dependencies)
+}
+
+/// `name[extras] <spec> ; <marker>` lines, options, urls and comments are left out
+fn parse_requirements(content: &str) -> Result<Parsed> {
+    let mut dependencies = vec![];
+    for line in content.lines() {
+        let line = match line.find(" #") {
+            Some(i) => &line[..i],
+            None => line,
+        };
+        let line = line.split(';').next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') || line.contains("://")
+        {
+            continue;
+        }
+
+        let end = line
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+            .unwrap_or(line.len());
+        let name = line[..end].to_lowercase().replace('_', "-");
+        let mut spec = line[end..].trim();
+        if spec.starts_with('[') {
+            spec = spec
+                .find(']')
+                .map(|i| spec[i + 1..].trim())
+                .unwrap_or_default();
+        }
+        let version = if spec.is_empty() { "*" } else { spec };
+        dependencies.push((Ecosystem::Pypi, name, version.to_string(), "normal"));
+    }
+    Ok(dependencies)
+}
+
+fn inventory(dependencies: &[Dependency]) -> Vec<Entry> {
+    let mut entries: BTreeMap<(Ecosystem, &str), BTreeMap<String, BTreeSet<String>>> =
+        BTreeMap::new();
+    for dependency in dependencies {
+        entries
+            .entry((dependency.ecosystem, &dependency.name))
+            .or_default()
+            .entry(dependency.version.clone())
+            .or_default()
+            .insert(dependency.repo.clone());
+    }
+
+    entries
+        .into_iter()
+        .map(|((ecosystem, name), versions)| Entry {
+            ecosystem,
+            name: name.to_string(),
+            versions,
+        })
+        .collect()
+}
+
+fn to_csv(dependencies: &[Dependency]) -> String {
+    let mut csv = String::from("ecosystem,name,version,kind,repo,manifest\n");
+    for d in dependencies {
+        let fields = [
+            d.ecosystem.to_string(),
+            d.name.clone(),
+            d.version.clone(),
+            d.kind.clone(),
+            d.repo.clone(),
+            d.manifest.clone(),
+        ];
+        let fields: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn summarize(inventory: &[Entry], list_repos: bool, reports: &[ErrorReport]) {
+    if inventory.is_empty() {
+        println!("There is no dependencies");
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Dependency", "Ecosystem", "Version", "Repos"]);
+        for entry in inventory {
+            for (i, (version, repos)) in entry.versions.iter().enumerate() {
+                let name = if i == 0 { entry.name.as_str() } else { "" };
+                let ecosystem = if i == 0 {
+                    entry.ecosystem.to_string()
+                } else {
+                    String::new()
+                };
+                let repos = if list_repos {
+                    repos.iter().cloned().collect::<Vec<_>>().join("\n")
+                } else {
+                    repos.len().to_string()
+                };
+                table.add_row(row![b -> name, ecosystem, version, r -> repos]);
+            }
+        }
+        table.printstd();
+
+        let several: Vec<_> = inventory.iter().filter(|e| e.versions.len() > 1).collect();
+        println!(
+            "\n{} dependencies, {} of them with several versions",
+            inventory.len(),
+            several.len()
+        );
+    }
+
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading manifests:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo() {
+        let content = r#"
+            [dependencies]
+            serde = "1.0"
+            git2 = { version = "0.18.0", features = ["vendored-openssl"] }
+            divvunspell = { git = "https://github.com/divvun/divvunspell" }
+
+            [dev-dependencies]
+            tempfile = "3.10.1"
+
+            [target.'cfg(windows)'.dependencies]
+            winapi = { version = "0.3", package = "winapi" }
+        "#;
+
+        let mut parsed = parse_cargo(content).unwrap();
+        parsed.sort();
+
+        assert_eq!(
+            parsed,
+            vec![
+                (
+                    Ecosystem::Cargo,
+                    "divvunspell".into(),
+                    "git".into(),
+                    "normal"
+                ),
+                (Ecosystem::Cargo, "git2".into(), "0.18.0".into(), "normal"),
+                (Ecosystem::Cargo, "serde".into(), "1.0".into(), "normal"),
+                (Ecosystem::Cargo, "tempfile".into(), "3.10.1".into(), "dev"),
+                (Ecosystem::Cargo, "winapi".into(), "0.3".into(), "normal"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let content = "# tools\n\
+                       PyYAML==6.0.1\n\
+                       requests[socks] >= 2.31 ; python_version > '3.8'\n\
+                       lxml  # unpinned\n\
+                       -r base.txt\n\
+                       git+https://github.com/divvun/foo.git\n";
+
+        assert_eq!(
+            parse_requirements(content).unwrap(),
+            vec![
+                (Ecosystem::Pypi, "pyyaml".into(), "==6.0.1".into(), "normal"),
+                (
+                    Ecosystem::Pypi,
+                    "requests".into(),
+                    ">= 2.31".into(),
+                    "normal"
+                ),
+                (Ecosystem::Pypi, "lxml".into(), "*".into(), "normal"),
+            ]
+        );
+    }
+}
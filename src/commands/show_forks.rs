@@ -0,0 +1,212 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, Fork, RemoteRepo};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+
+#[derive(Debug, Parser)]
+/// Show the forks of all repositories that match a regex
+///
+/// Every fork is compared with the default branch of its parent, so forks that carry work
+/// worth upstreaming stand out.
+pub struct ShowForksArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Only show forks that have commits which are not in their parent
+    pub ahead: bool,
+}
+
+impl ShowForksArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| forks(repo, &user_token))
+            .collect();
+
+        if self.ahead {
+            for status in &mut statuses {
+                if let Ok(forks) = &mut status.result {
+                    forks.retain(|f| matches!(f.comparison, Ok(Some((ahead, _))) if ahead > 0));
+                }
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn forks(repo: &RemoteRepo, token: &str) -> Status {
+    let result = (|| -> Result<Vec<ForkStatus>> {
+        let forks = github::get_forks(repo, token)?;
+        if forks.is_empty() {
+            return Ok(vec![]);
+        }
+        let default_branch = github::get_default_branch(&repo.owner, &repo.name, token)?;
+
+        let mut forks: Vec<_> = forks
+            .par_iter()
+            .map(|fork| compare(repo, &default_branch, fork, token))
+            .collect();
+        forks.sort_by_key(|f| Reverse(f.pushed_at));
+        Ok(forks)
+    })();
+
+    Status {
+        repo: repo.full_name(),
+        result: result.map_err(|e| format!("{:?}", e)),
+    }
+}
+
+fn compare(repo: &RemoteRepo, default_branch: &str, fork: &Fork, token: &str) -> ForkStatus {
+    let head = format!("{}:{}", fork.owner.login, fork.default_branch);
+    let comparison = github::compare_commits(&repo.owner, &repo.name, default_branch, &head, token)
+        .map(|c| c.map(|c| (c.ahead_by, c.behind_by)))
+        .map_err(|e| format!("{:?}", e));
+
+    ForkStatus {
+        fork: fork.full_name.clone(),
+        url: fork.html_url.clone(),
+        comparison,
+        pushed_at: fork.pushed_at,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ForkStatus {
+    fork: String,
+    url: String,
+    /// Commits that the fork is ahead and behind, None when the histories are unrelated
+    comparison: Result<Option<(usize, usize)>, String>,
+    pushed_at: Option<DateTime<Utc>>,
+}
+
+impl ForkStatus {
+    fn to_row(&self, repo: &str) -> Row {
+        let mut cells = vec![cell!(b -> repo), cell!(self.fork)];
+        match &self.comparison {
+            Ok(Some((ahead, behind))) => {
+                cells.push(if *ahead > 0 {
+                    cell!(Fgr -> ahead)
+                } else {
+                    cell!(r -> ahead)
+                });
+                cells.push(cell!(r -> behind));
+            }
+            Ok(None) => {
+                cells.push(cell!(Fy -> "Unrelated"));
+                cells.push(cell!(""));
+            }
+            Err(e) => {
+                cells.push(ErrorCategory::from_message(e).failed_cell());
+                cells.push(cell!(""));
+            }
+        }
+        cells.push(match self.pushed_at {
+            Some(pushed_at) => cell!(pushed_at.format("%Y-%m-%d")),
+            None => cell!("-"),
+        });
+        Row::new(cells)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<ForkStatus>, String>,
+}
+
+impl Status {
+    fn to_rows(&self) -> Vec<Row> {
+        match &self.result {
+            Ok(forks) => forks.iter().map(|f| f.to_row(&self.repo)).collect(),
+            Err(e) => vec![Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ])],
+        }
+    }
+
+    fn errors(&self) -> Vec<ErrorReport> {
+        match &self.result {
+            Ok(forks) => forks
+                .iter()
+                .filter_map(|f| match &f.comparison {
+                    Err(e) => Some(ErrorReport::from_message(&f.fork, e)),
+                    _ => None,
+                })
+                .collect(),
+            Err(e) => vec![ErrorReport::from_message(&self.repo, e)],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let rows: Vec<_> = statuses.iter().flat_map(|s| s.to_rows()).collect();
+
+    if rows.is_empty() {
+        println!("There is no forks of the matched repositories");
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Repo", "Fork", "Ahead", "Behind", "Last push"]);
+        for row in rows {
+            table.add_row(row);
+        }
+        table.printstd();
+
+        let count = statuses
+            .iter()
+            .filter_map(|s| s.result.as_ref().ok())
+            .flatten()
+            .count();
+        println!("\n{} forks in total", count);
+    }
+
+    let reports: Vec<_> = statuses.iter().flat_map(|s| s.errors()).collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when looking up forks:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
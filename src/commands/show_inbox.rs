@@ -0,0 +1,153 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use prettytable::{format, row, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Parser)]
+/// Show open issues and pull requests that need your attention in repositories that match a regex
+///
+/// This lists everything that is assigned to you, where your review is requested or where you
+/// are mentioned, oldest first.
+pub struct ShowInboxArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+/// Search qualifiers and the reason they are shown with
+static REASONS: &[(&str, &str)] = &[
+    ("assignee:@me", "assigned"),
+    ("review-requested:@me", "review"),
+    ("mentions:@me", "mentioned"),
+];
+
+impl ShowInboxArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let repos: BTreeSet<_> = filtered_repos
+            .iter()
+            .map(|r| r.full_name().to_lowercase())
+            .collect();
+        let owners: BTreeSet<_> = filtered_repos.iter().map(|r| r.owner.as_str()).collect();
+
+        // The same issue can turn up for several reasons, so they are collected by url
+        let mut entries: BTreeMap<String, Entry> = BTreeMap::new();
+        for owner in owners {
+            for (qualifier, reason) in REASONS {
+                let query = format!("is:open archived:false user:{} {}", owner, qualifier);
+                for item in github::search_issues(&query, &user_token)? {
+                    let repo = match item.repository() {
+                        Some((owner, name)) => format!("{}/{}", owner, name),
+                        None => continue,
+                    };
+                    if !repos.contains(&repo.to_lowercase()) {
+                        continue;
+                    }
+
+                    entries
+                        .entry(item.html_url.clone())
+                        .or_insert_with(|| Entry {
+                            repo,
+                            number: item.number,
+                            kind: if item.pull_request.is_some() {
+                                "PR"
+                            } else {
+                                "Issue"
+                            },
+                            title: item.title.clone(),
+                            url: item.html_url.clone(),
+                            created_at: item.created_at,
+                            reasons: vec![],
+                        })
+                        .reasons
+                        .push(reason);
+                }
+            }
+        }
+
+        let mut entries: Vec<_> = entries.into_values().collect();
+        entries.sort_by_key(|e| e.created_at);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(entries));
+            return Ok(());
+        }
+
+        summarize(&entries);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    repo: String,
+    number: u64,
+    kind: &'static str,
+    title: String,
+    url: String,
+    created_at: DateTime<Utc>,
+    reasons: Vec<&'static str>,
+}
+
+impl Entry {
+    fn to_row(&self, now: DateTime<Utc>) -> Row {
+        let age = (now - self.created_at).num_days();
+        row![
+            r -> format!("{}d", age),
+            b -> self.repo,
+            r -> format!("#{}", self.number),
+            self.kind,
+            self.reasons.join(", "),
+            self.title
+        ]
+    }
+}
+
+fn summarize(entries: &[Entry]) {
+    if entries.is_empty() {
+        println!("There is nothing that needs your attention");
+        return;
+    }
+
+    let now = Utc::now();
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Age", "Repo", "Number", "Kind", "Reason", "Title"]);
+    for entry in entries {
+        table.add_row(entry.to_row(now));
+    }
+    table.printstd();
+
+    let pulls = entries.iter().filter(|e| e.kind == "PR").count();
+    println!(
+        "\n{} open issues and {} open pull requests",
+        entries.len() - pulls,
+        pulls
+    );
+}
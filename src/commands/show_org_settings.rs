@@ -0,0 +1,151 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use super::table::{GroupBy, OwnerTable};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::github;
+use crate::org_settings::{Deviation, OrgSettings};
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, row, Row};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show the settings of organisations and where they differ from a policy file
+///
+/// The policy file is a toml table with the expected value of the settings to check, e.g.
+/// `default_repository_permission = "read"`. Settings that only owners can see are shown as
+/// unknown for other tokens. GitHub has no api for the default branch name of new
+/// repositories, so it is not shown.
+pub struct ShowOrgSettingsArgs {
+    #[arg(long, short, conflicts_with = "all_orgs")]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Show every organisation that the token is a member of
+    pub all_orgs: bool,
+    #[arg(long, short)]
+    /// Policy file with the expected settings
+    pub policy: Option<PathBuf>,
+    #[arg(long)]
+    /// Fail when a setting differs from the policy, to use it in CI
+    pub check: bool,
+    #[arg(long, value_enum, default_value_t = GroupBy::Flat)]
+    /// Print a table per owner, or one table with an Owner column
+    pub group_by: GroupBy,
+}
+
+impl ShowOrgSettingsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let policy = self
+            .policy
+            .as_deref()
+            .map(OrgSettings::load)
+            .transpose()?
+            .unwrap_or_default();
+
+        let organisations = if self.all_orgs {
+            github::get_user_orgs(&user_token)?
+        } else {
+            vec![common::organisation(self.organisation.as_deref())?]
+        };
+
+        let statuses: Vec<_> = organisations
+            .par_iter()
+            .map(|org| Status::new(org, &policy, &user_token))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, self.group_by);
+        }
+
+        let deviations: usize = statuses.iter().map(|s| s.deviations.len()).sum();
+        if self.check && deviations > 0 {
+            bail!("{} settings differ from the policy", deviations);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    organisation: String,
+    result: Result<OrgSettings, String>,
+    deviations: Vec<Deviation>,
+}
+
+impl Status {
+    fn new(org: &str, policy: &OrgSettings, token: &str) -> Status {
+        let result = github::get_org_settings(org, token).map_err(|e| format!("{:?}", e));
+        let deviations = match &result {
+            Ok(settings) => settings.deviations(policy),
+            Err(_) => vec![],
+        };
+        Status {
+            organisation: org.to_string(),
+            result,
+            deviations,
+        }
+    }
+
+    fn to_rows(&self) -> Vec<Row> {
+        let settings = match &self.result {
+            Ok(settings) => settings,
+            Err(_) => return vec![],
+        };
+        settings
+            .values()
+            .into_iter()
+            .map(|(setting, value)| {
+                let value = value.unwrap_or_else(|| "unknown".to_string());
+                match self.deviations.iter().find(|d| d.setting == setting) {
+                    Some(deviation) => Row::new(vec![
+                        cell!(setting),
+                        cell!(Fr -> value),
+                        cell!(Fr -> deviation.expected),
+                    ]),
+                    None => Row::new(vec![cell!(setting), cell!(value), cell!("")]),
+                }
+            })
+            .collect()
+    }
+}
+
+fn summarize(statuses: &[Status], group_by: GroupBy) {
+    let mut table = OwnerTable::new(row!["Setting", "Value", "Policy"]);
+    for status in statuses {
+        for row in status.to_rows() {
+            table.add_row(&status.organisation, row);
+        }
+    }
+    table.print(group_by);
+
+    let deviations: usize = statuses.iter().map(|s| s.deviations.len()).sum();
+    if deviations > 0 {
+        let msg = format!("\n{} settings differ from the policy", deviations);
+        println!("{}", msg.red());
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.organisation, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading settings:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
@@ -1,8 +1,11 @@
 use super::common;
+use super::table::SortArgs;
 
-use crate::filter::Filter;
 use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github::RemoteRepo;
 use clap::Parser;
+use prettytable::{format, row, Row, Table};
 
 #[derive(Debug, Parser)]
 // Show all repositories that match a pattern
@@ -15,6 +18,8 @@ pub struct ShowReposArgs {
     #[arg(long, short)]
     /// Optional regex to filter repositories
     pub regex: Option<Filter>,
+    #[command(flatten)]
+    pub sort: SortArgs,
 }
 
 impl ShowReposArgs {
@@ -22,8 +27,9 @@ impl ShowReposArgs {
         let user_token = common::user_token()?;
         let organisation = common::organisation(self.organisation.as_deref())?;
 
-        let filtered_repos =
+        let mut filtered_repos =
             common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+        self.sort.sort(&mut filtered_repos, &titles(), to_row)?;
 
         print_results(&filtered_repos);
 
@@ -31,8 +37,20 @@ impl ShowReposArgs {
     }
 }
 
-fn print_results<T: std::fmt::Debug>(repos: &[T]) {
+fn titles() -> Row {
+    row!["Repo", "Owner", "SSH url", "HTTPS url"]
+}
+
+fn to_row(repo: &RemoteRepo) -> Row {
+    row![b -> &repo.name, repo.owner, repo.ssh_url, repo.https_url]
+}
+
+fn print_results(repos: &[RemoteRepo]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles());
     for repo in repos {
-        println!("{:?}", repo);
+        table.add_row(to_row(repo));
     }
+    table.printstd();
 }
@@ -0,0 +1,375 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::table::SortArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{self, dir_name, owner_name};
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Parser)]
+/// Show how much disk space local repositories take and how much they grew
+///
+/// Sizes are compared with the baseline saved by an earlier run with --save-baseline, to find
+/// the repositories that are growing fast before they hit the GitHub limits.
+pub struct ShowSizeArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, default_value = "3")]
+    /// Number of largest blobs to show for every repository
+    pub blobs: usize,
+    #[arg(long, short)]
+    /// Save the sizes as the baseline for the next runs
+    pub save_baseline: bool,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+impl ShowSizeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut baseline = Baseline::load()?;
+
+        progress::begin(sub_dirs.len());
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || measure(d, &organisation, self.blobs)))
+            .collect();
+        statuses.sort_by_key(|s| Reverse(s.result.as_ref().map(|r| r.sizes.git).unwrap_or(0)));
+
+        for status in &mut statuses {
+            status.before = baseline.repos.get(&status.repo).cloned();
+        }
+        self.sort.sort(&mut statuses, &titles(), |s| s.to_row())?;
+
+        if self.save_baseline {
+            for status in &statuses {
+                if let Ok(report) = &status.result {
+                    baseline
+                        .repos
+                        .insert(status.repo.clone(), report.sizes.clone());
+                }
+            }
+            baseline.save()?;
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+        if self.save_baseline {
+            println!("\nSaved the sizes as the new baseline");
+        }
+
+        Ok(())
+    }
+}
+
+fn measure(dir: &PathBuf, organisation: &str, blobs: usize) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let measure = || -> Result<SizeReport> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let git_dir = git_repo.path();
+
+        let sizes = Sizes {
+            git: dir_size(git_dir, false),
+            worktree: git_repo.workdir().map(|d| dir_size(d, true)).unwrap_or(0),
+            lfs: dir_size(&git_dir.join("lfs").join("objects"), false),
+        };
+
+        Ok(SizeReport {
+            sizes,
+            largest: largest_blobs(&git_repo, blobs)?,
+        })
+    };
+
+    Status {
+        repo: format!("{}/{}", owner, name),
+        result: measure().map_err(|e| format!("{:?}", e)),
+        before: None,
+    }
+}
+
+/// Total size of the files in a directory, the .git directory left out of working trees
+fn dir_size(dir: &Path, skip_git: bool) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| !(skip_git && e.file_name() == ".git"))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// The largest blobs in the object database, with their path in HEAD when they are there
+fn largest_blobs(git_repo: &Repository, count: usize) -> Result<Vec<Blob>> {
+    if count == 0 {
+        return Ok(vec![]);
+    }
+
+    let odb = git_repo.odb()?;
+    let mut heap = BinaryHeap::new();
+    let mut error = None;
+    odb.foreach(|oid| {
+        match odb.read_header(*oid) {
+            Ok((size, ObjectType::Blob)) => {
+                heap.push(Reverse((size as u64, *oid)));
+                if heap.len() > count {
+                    heap.pop();
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error = Some(e);
+                return false;
+            }
+        }
+        true
+    })?;
+    if let Some(e) = error {
+        return Err(anyhow!("Cannot read the object database: {}", e));
+    }
+
+    let mut largest: Vec<(u64, Oid)> = heap.into_iter().map(|Reverse(b)| b).collect();
+    largest.sort_by_key(|(size, _)| Reverse(*size));
+
+    let mut paths: BTreeMap<Oid, String> = BTreeMap::new();
+    if let Ok(tree) = git_repo.head().and_then(|h| h.peel_to_tree()) {
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if largest.iter().any(|(_, oid)| *oid == entry.id()) {
+                let name = entry.name().unwrap_or_default();
+                paths
+                    .entry(entry.id())
+                    .or_insert_with(|| format!("{}{}", root, name));
+            }
+            TreeWalkResult::Ok
+        })?;
+    }
+
+    Ok(largest
+        .into_iter()
+        .map(|(size, oid)| Blob {
+            path: paths.get(&oid).cloned(),
+            oid: oid.to_string(),
+            size,
+        })
+        .collect())
+}
+
+/// Sizes in bytes, the LFS objects are also part of the .git size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sizes {
+    git: u64,
+    worktree: u64,
+    lfs: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Blob {
+    /// None when the blob is only in the history
+    path: Option<String>,
+    oid: String,
+    size: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SizeReport {
+    sizes: Sizes,
+    largest: Vec<Blob>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Baseline {
+    repos: BTreeMap<String, Sizes>,
+}
+
+impl Baseline {
+    fn path() -> Result<PathBuf> {
+        path::size_baseline_path().ok_or_else(|| anyhow!("Cannot find the config directory"))
+    }
+
+    fn load() -> Result<Baseline> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Baseline::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read the size baseline from {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Cannot read the size baseline from {:?}", path))
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Cannot save the size baseline to {:?}", path))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<SizeReport, String>,
+    /// The sizes in the baseline
+    before: Option<Sizes>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(report) => {
+                let before = self.before.as_ref();
+                let blobs: Vec<_> = report
+                    .largest
+                    .iter()
+                    .map(|b| {
+                        let path = b.path.as_deref().unwrap_or("(only in history)");
+                        format!("{} {}", human_size(b.size), path)
+                    })
+                    .collect();
+                Row::new(vec![
+                    cell!(b -> &self.repo),
+                    size_cell(report.sizes.git, before.map(|b| b.git)),
+                    size_cell(report.sizes.worktree, before.map(|b| b.worktree)),
+                    size_cell(report.sizes.lfs, before.map(|b| b.lfs)),
+                    cell!(blobs.join("\n")),
+                ])
+            }
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn size_cell(size: u64, before: Option<u64>) -> Cell {
+    match before {
+        Some(before) if size > before => {
+            cell!(Fyr -> format!("{} (+{})", human_size(size), human_size(size - before)))
+        }
+        Some(before) if size < before => {
+            cell!(Fgr -> format!("{} (-{})", human_size(size), human_size(before - size)))
+        }
+        _ => cell!(r -> human_size(size)),
+    }
+}
+
+pub fn human_size(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+fn titles() -> Row {
+    row!["Repo", ".git", "Working tree", "LFS", "Largest blobs"]
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles());
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let total: u64 = statuses
+        .iter()
+        .filter_map(|s| s.result.as_ref().ok())
+        .map(|r| r.sizes.git + r.sizes.worktree)
+        .sum();
+    println!("\n{} on disk in total", human_size(total));
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when measuring:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(1023), "1023 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(100 * 1024 * 1024), "100.0 MB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
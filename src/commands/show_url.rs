@@ -0,0 +1,140 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use prettytable::{format, row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show the ssh and https clone urls of all repositories that match a regex
+///
+/// Use --plain to get one url per line for other tools.
+pub struct ShowUrlArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Read the origin remotes of local repositories instead of asking GitHub
+    pub local: bool,
+    #[arg(long, short)]
+    /// Print only one url per line
+    pub plain: bool,
+    #[arg(long, short)]
+    /// Print https urls instead of ssh urls with --plain
+    ///
+    /// This defaults to the use https setting of the init command.
+    pub use_https: bool,
+}
+
+impl ShowUrlArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let urls = if self.local {
+            let root = common::root()?;
+            let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+            sub_dirs
+                .iter()
+                .map(RepoUrl::from_local)
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            let user_token = common::user_token()?;
+            let filtered_repos = common::query_and_filter_repositories(
+                &organisation,
+                self.regex.as_ref(),
+                &user_token,
+            )?;
+            filtered_repos
+                .into_iter()
+                .map(|r| RepoUrl {
+                    https_url: Some(format!("{}.git", r.https_url)),
+                    ssh_url: Some(r.ssh_url),
+                    owner: r.owner,
+                    repo: r.name,
+                })
+                .collect()
+        };
+
+        if urls.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        if self.plain {
+            let use_https = self.use_https || common::use_https()?;
+            for url in &urls {
+                let preferred = if use_https {
+                    url.https_url.as_ref().or(url.ssh_url.as_ref())
+                } else {
+                    url.ssh_url.as_ref().or(url.https_url.as_ref())
+                };
+                if let Some(u) = preferred {
+                    println!("{}", u);
+                }
+            }
+        } else if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(urls));
+        } else {
+            print_table(&urls);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RepoUrl {
+    owner: String,
+    repo: String,
+    ssh_url: Option<String>,
+    https_url: Option<String>,
+}
+
+impl RepoUrl {
+    fn from_local(dir: &PathBuf) -> Result<RepoUrl> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let origin = git_repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|r| r.url().map(str::to_string));
+        let (https_url, ssh_url) = match origin {
+            Some(u) if u.starts_with("https://") || u.starts_with("http://") => (Some(u), None),
+            u => (None, u),
+        };
+
+        Ok(RepoUrl {
+            owner: owner_name(dir)?,
+            repo: dir_name(dir)?,
+            ssh_url,
+            https_url,
+        })
+    }
+}
+
+fn print_table(urls: &[RepoUrl]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "SSH", "HTTPS"]);
+    for url in urls {
+        table.add_row(row![
+            b -> format!("{}/{}", url.owner, url.repo),
+            url.ssh_url.as_deref().unwrap_or("-"),
+            url.https_url.as_deref().unwrap_or("-")
+        ]);
+    }
+    table.printstd();
+}
@@ -0,0 +1,288 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, DependabotAlert, RemoteRepo};
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Parser)]
+/// Summarize the open Dependabot alerts of all repositories that match a regex
+///
+/// The alerts come from the dependency graph of GitHub, so Dependabot alerts have to be
+/// enabled for the repositories. With --fail-on the command fails when there are alerts of
+/// that severity or worse, to use it in CI.
+pub struct ShowVulnerabilitiesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, value_enum)]
+    /// Fail when there are open alerts of this severity or worse
+    pub fail_on: Option<Severity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn from_advisory(severity: &str) -> Severity {
+        match severity {
+            "critical" => Severity::Critical,
+            "high" => Severity::High,
+            "medium" | "moderate" => Severity::Medium,
+            _ => Severity::Low,
+        }
+    }
+
+    fn cell(&self) -> Cell {
+        match self {
+            Severity::Critical => cell!(Frb -> "critical"),
+            Severity::High => cell!(Fr -> "high"),
+            Severity::Medium => cell!(Fy -> "medium"),
+            Severity::Low => cell!("low"),
+        }
+    }
+
+    fn count_cell(&self, count: usize) -> Cell {
+        match (count, self) {
+            (0, _) => cell!(r -> "-"),
+            (_, Severity::Critical) => cell!(Frbr -> count),
+            (_, Severity::High) => cell!(Frr -> count),
+            (_, Severity::Medium) => cell!(Fyr -> count),
+            (_, Severity::Low) => cell!(r -> count),
+        }
+    }
+}
+
+impl ShowVulnerabilitiesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| alerts(repo, &user_token))
+            .collect();
+        statuses.sort_by_key(|s| Reverse(s.counts()));
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
+        }
+
+        if let Some(threshold) = self.fail_on {
+            let failing: usize = statuses
+                .iter()
+                .filter_map(|s| s.result.as_ref().ok())
+                .flatten()
+                .filter(|a| a.severity >= threshold)
+                .count();
+            if failing > 0 {
+                bail!(
+                    "There are {} open alerts of severity {:?} or worse",
+                    failing,
+                    threshold
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn alerts(repo: &RemoteRepo, token: &str) -> Status {
+    let result = github::get_dependabot_alerts(repo, token)
+        .map(|alerts| alerts.into_iter().map(Alert::from).collect())
+        .map_err(|e| format!("{:?}", e));
+
+    Status {
+        repo: repo.full_name(),
+        result,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Alert {
+    severity: Severity,
+    #[serde(flatten)]
+    alert: DependabotAlert,
+}
+
+impl From<DependabotAlert> for Alert {
+    fn from(alert: DependabotAlert) -> Self {
+        Alert {
+            severity: Severity::from_advisory(&alert.security_advisory.severity),
+            alert,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<Alert>, String>,
+}
+
+impl Status {
+    /// Number of alerts per severity, the worst first
+    fn counts(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+        if let Ok(alerts) = &self.result {
+            for alert in alerts {
+                counts[3 - alert.severity as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(_) => {
+                let mut cells = vec![cell!(b -> &self.repo)];
+                let severities = [
+                    Severity::Critical,
+                    Severity::High,
+                    Severity::Medium,
+                    Severity::Low,
+                ];
+                for (count, severity) in self.counts().iter().zip(severities) {
+                    cells.push(severity.count_cell(*count));
+                }
+                Row::new(cells)
+            }
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+/// The alerts of one package across repositories
+#[derive(Default)]
+struct PackageAlerts {
+    severity: Option<Severity>,
+    advisories: BTreeSet<String>,
+    repos: BTreeSet<String>,
+    patched: BTreeSet<String>,
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Critical", "High", "Medium", "Low"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let mut packages: BTreeMap<(String, String), PackageAlerts> = BTreeMap::new();
+    for status in statuses {
+        for alert in status.result.iter().flatten() {
+            let package = &alert.alert.dependency.package;
+            let entry = packages
+                .entry((package.name.clone(), package.ecosystem.clone()))
+                .or_default();
+            entry.severity = entry.severity.max(Some(alert.severity));
+            entry
+                .advisories
+                .insert(alert.alert.security_advisory.ghsa_id.clone());
+            entry.repos.insert(status.repo.clone());
+            if let Some(patched) = &alert.alert.security_vulnerability.first_patched_version {
+                entry.patched.insert(patched.identifier.clone());
+            }
+        }
+    }
+
+    if packages.is_empty() {
+        println!("\n{}", "There is no open Dependabot alert".green());
+    } else {
+        let mut packages: Vec<_> = packages.into_iter().collect();
+        packages.sort_by_key(|(_, p)| (Reverse(p.severity), Reverse(p.repos.len())));
+
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row![
+            "Package",
+            "Ecosystem",
+            "Severity",
+            "Advisories",
+            "Repos",
+            "Fixed in"
+        ]);
+        for ((name, ecosystem), package) in &packages {
+            let severity = package.severity.unwrap_or(Severity::Low);
+            table.add_row(Row::new(vec![
+                cell!(b -> name),
+                cell!(ecosystem),
+                severity.cell(),
+                cell!(r -> package.advisories.len()),
+                cell!(r -> package.repos.len()),
+                cell!(package
+                    .patched
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")),
+            ]));
+        }
+        println!();
+        table.printstd();
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when fetching alerts:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,35 @@
+use super::snapshot_checkout::*;
+use super::snapshot_create::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Pin the commits of repositories in a lock file and check them out again later
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    command: SnapshotCommand,
+}
+
+impl SnapshotArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum SnapshotCommand {
+    #[command(name = "checkout", aliases = &["restore"])]
+    Checkout(SnapshotCheckoutArgs),
+    #[command(name = "create", aliases = &["save"])]
+    Create(SnapshotCreateArgs),
+}
+
+impl SnapshotCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Checkout(args) => args.run(common_args),
+            Self::Create(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use super::snapshot_create::{Snapshot, SnapshotEntry};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::path::local_path_repo;
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::BranchType;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Check out the commits of a lock file written by snapshot create in the local repositories
+///
+/// A repository goes back to its recorded branch when that branch is still at the recorded
+/// commit, otherwise the commit is checked out in detached mode.
+pub struct SnapshotCheckoutArgs {
+    /// The lock file to check out
+    pub file: PathBuf,
+    #[arg(long, short)]
+    /// Optional regex to check out only some of the repositories in the lock file
+    pub regex: Option<Filter>,
+}
+
+impl SnapshotCheckoutArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let snapshot = Snapshot::load(&self.file)?;
+
+        let entries: Vec<_> = snapshot
+            .repos
+            .iter()
+            .filter_map(|(repo, entry)| {
+                let (owner, name) = repo.split_once('/')?;
+                let is_match = self
+                    .regex
+                    .as_ref()
+                    .map(|r| r.is_match_in(owner, name))
+                    .unwrap_or(true);
+                is_match.then_some((repo, owner, name, entry))
+            })
+            .collect();
+
+        if entries.is_empty() {
+            println!(
+                "There is no repositories in lock file {:?} that matches pattern {:?}",
+                self.file, self.regex
+            );
+            return Ok(());
+        }
+
+        progress::begin(entries.len());
+        let statuses: Vec<_> = entries
+            .par_iter()
+            .map(|(repo, owner, name, entry)| {
+                progress::track(repo, || {
+                    let dir = local_path_repo(owner, name, &root);
+                    let result = restore(&dir, entry, &user).map_err(|e| format!("{:?}", e));
+                    Status {
+                        repo: repo.to_string(),
+                        result,
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+/// Check out the commit of a lock file entry, on its branch when the branch is still there
+pub fn restore(dir: &PathBuf, entry: &SnapshotEntry, user: &User) -> Result<Restored> {
+    if !dir.exists() {
+        return Err(anyhow!("{:?} is not cloned, run gut clone first", dir));
+    }
+    let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+    let on_branch = entry.branch.as_deref().filter(|b| {
+        git_repo
+            .find_branch(b, BranchType::Local)
+            .ok()
+            .and_then(|branch| branch.get().target())
+            .map(|oid| oid.to_string() == entry.sha)
+            .unwrap_or(false)
+    });
+
+    let head = git_repo.head().ok();
+    let head_sha = head
+        .as_ref()
+        .and_then(|h| h.target())
+        .map(|h| h.to_string());
+    let head_branch = head
+        .as_ref()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand())
+        .map(|b| b.to_string());
+    if head_sha.as_deref() == Some(&entry.sha) && head_branch.as_deref() == on_branch {
+        return Ok(Restored::AlreadyThere);
+    }
+
+    match on_branch {
+        Some(branch) => {
+            git::checkout_local_branch(&git_repo, branch)?;
+            Ok(Restored::Branch(branch.to_string()))
+        }
+        None => {
+            let cred = GitCredential::from(user);
+            git::checkout_detached(&git_repo, &entry.sha, "origin", Some(cred))?;
+            Ok(Restored::Detached)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub enum Restored {
+    AlreadyThere,
+    Branch(String),
+    Detached,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Restored, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.result_cell()])
+    }
+
+    fn result_cell(&self) -> Cell {
+        match &self.result {
+            Ok(Restored::AlreadyThere) => cell!("Already there"),
+            Ok(Restored::Branch(branch)) => cell!(Fg -> format!("On branch {}", branch)),
+            Ok(Restored::Detached) => cell!(Fg -> "Detached"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!(
+            "There {} errors when checking out the lock file:",
+            errors.len()
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,217 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Parser)]
+/// Record the branch and commit of all local repositories that match a regex in a lock file
+///
+/// The lock file can be checked out again later with snapshot checkout or checkout --manifest,
+/// to reproduce a release build across repositories. Uncommitted changes are not part of it.
+pub struct SnapshotCreateArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// The lock file to write, for example release.lock
+    pub file: PathBuf,
+}
+
+impl SnapshotCreateArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| record(d, &organisation))
+            .collect();
+
+        let snapshot = Snapshot {
+            repos: statuses
+                .iter()
+                .filter_map(|s| s.result.as_ref().ok().map(|e| (s.repo.clone(), e.clone())))
+                .collect(),
+        };
+        snapshot.save(&self.file)?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+        println!(
+            "\nSaved {} repositories to {:?}",
+            snapshot.repos.len(),
+            self.file
+        );
+
+        Ok(())
+    }
+}
+
+/// The commits of a set of repositories, keyed by `owner/name`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub repos: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub sha: String,
+    /// The branch that was checked out, for information
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// There were uncommitted changes, which the snapshot does not have
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dirty: bool,
+}
+
+impl Snapshot {
+    pub fn load(path: &Path) -> Result<Snapshot> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read the snapshot from {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Cannot read the snapshot from {:?}", path))
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Cannot save the snapshot to {:?}", path))
+    }
+
+    /// The entry of a repository, the owner is compared without case like GitHub does
+    pub fn get(&self, owner: &str, name: &str) -> Option<&SnapshotEntry> {
+        self.repos
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&format!("{}/{}", owner, name)))
+            .map(|(_, entry)| entry)
+    }
+}
+
+fn record(dir: &PathBuf, organisation: &str) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let record = || -> Result<SnapshotEntry> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let head = git_repo.head()?;
+        let sha = head
+            .target()
+            .ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+        let branch = if head.is_branch() {
+            head.shorthand().map(|b| b.to_string())
+        } else {
+            None
+        };
+
+        Ok(SnapshotEntry {
+            sha: sha.to_string(),
+            branch,
+            dirty: git::status(&git_repo, true)?.is_dirty(),
+        })
+    };
+
+    Status {
+        repo: format!("{}/{}", owner, name),
+        result: record().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<SnapshotEntry, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(entry) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(entry.branch.as_deref().unwrap_or("(detached)")),
+                cell!(&entry.sha[..8]),
+                self.dirty_cell(entry),
+            ]),
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn dirty_cell(&self, entry: &SnapshotEntry) -> Cell {
+        if entry.dirty {
+            cell!(Fy -> "Uncommitted changes are left out")
+        } else {
+            cell!("")
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Branch", "Commit", ""]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!(
+            "There {} errors when reading the current commits, they are not in the snapshot:",
+            errors.len()
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,58 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Star all repositories that match a regex, or remove their stars
+pub struct StarArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Remove the stars instead
+    pub remove: bool,
+}
+
+impl StarArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let (action, done) = if self.remove {
+            ("unstar", "Unstarred")
+        } else {
+            ("star", "Starred")
+        };
+        for repo in filtered_repos {
+            let result = if self.remove {
+                github::unstar_repo(&repo, &user_token)
+            } else {
+                github::star_repo(&repo, &user_token)
+            };
+            match result {
+                Ok(_) => println!("{} repo {} successfully", done, repo.name),
+                Err(e) => println!("Failed to {} repo {} because {:?}", action, repo.name, e),
+            }
+        }
+        Ok(())
+    }
+}
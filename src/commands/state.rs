@@ -0,0 +1,31 @@
+use super::state_show::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Show what gut did last in local repositories
+pub struct StateArgs {
+    #[command(subcommand)]
+    command: StateCommand,
+}
+
+impl StateArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum StateCommand {
+    #[command(name = "show")]
+    Show(StateShowArgs),
+}
+
+impl StateCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Show(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::path;
+use crate::repo_state::RepoState;
+use anyhow::Result;
+use chrono::Local;
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Row, Table};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Show the last pull, the last applied template revision, the last campaign branch and the
+/// pending actions of all local repositories that match a regex
+pub struct StateShowArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// Only show repositories with pending actions
+    pub pending: bool,
+}
+
+impl StateShowArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .iter()
+            .map(load)
+            .filter(|s| {
+                !self.pending || matches!(&s.result, Ok(state) if !state.pending.is_empty())
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses);
+        }
+
+        Ok(())
+    }
+}
+
+fn load(dir: &PathBuf) -> Status {
+    let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    Status {
+        repo,
+        result: RepoState::load(dir).map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<RepoState, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(state) => row![
+                b -> &self.repo,
+                state
+                    .last_pull
+                    .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                r -> state
+                    .template_rev
+                    .map(|r| r.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                state.campaign_branch.as_deref().unwrap_or("-"),
+                Fy -> state.pending.join("\n")
+            ],
+            Err(e) => row![b -> &self.repo, Fr -> e, "", "", ""],
+        }
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row![
+        "Repo",
+        "Last pull",
+        "Template",
+        "Campaign branch",
+        "Pending"
+    ]);
+    table.printstd();
+
+    let pending = statuses
+        .iter()
+        .filter(|s| matches!(&s.result, Ok(state) if !state.pending.is_empty()))
+        .count();
+    if pending > 0 {
+        let msg = format!("\n{} repos have actions to finish by hand", pending);
+        println!("{}", msg.yellow());
+    }
+}
@@ -0,0 +1,35 @@
+use super::stats_activity::*;
+use super::stats_languages::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Report statistics about the repositories of organisations
+pub struct StatsArgs {
+    #[command(subcommand)]
+    command: StatsCommand,
+}
+
+impl StatsArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum StatsCommand {
+    #[command(name = "activity")]
+    Activity(StatsActivityArgs),
+    #[command(name = "languages", aliases = &["langs"])]
+    Languages(StatsLanguagesArgs),
+}
+
+impl StatsCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Activity(args) => args.run(common_args),
+            Self::Languages(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::path::{dir_name, owner_name};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Days, Local, NaiveDate};
+use clap::Parser;
+use colored::*;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Ranges longer than this are shown with a column per week instead of per day
+const MAX_DAY_COLUMNS: usize = 92;
+
+#[derive(Debug, Parser)]
+/// Report the number of commits per day of all local repositories that match a regex
+///
+/// The commits are counted on the current branch of the local clones, by author date. The
+/// result is printed as a heatmap, or exported as csv or json for reports.
+pub struct StatsActivityArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long)]
+    /// First day to count, as YYYY-MM-DD [default: 12 weeks before --until]
+    pub since: Option<NaiveDate>,
+    #[arg(long)]
+    /// Last day to count, as YYYY-MM-DD [default: today]
+    pub until: Option<NaiveDate>,
+    #[arg(long)]
+    /// Write the commits of every repository and day to a csv file
+    pub csv: Option<PathBuf>,
+}
+
+impl StatsActivityArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let until = self.until.unwrap_or_else(|| Local::now().date_naive());
+        let since = match self.since {
+            Some(since) => since,
+            None => until - Days::new(7 * 12 - 1),
+        };
+        if since > until {
+            bail!("--since {} is after --until {}", since, until);
+        }
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| activity(d, since, until))
+            .collect();
+
+        if let Some(path) = &self.csv {
+            std::fs::write(path, to_csv(&statuses, since, until))
+                .with_context(|| format!("Cannot write the csv file {:?}", path))?;
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!(
+                "{}",
+                json!({ "since": since, "until": until, "repos": statuses })
+            );
+            return Ok(());
+        }
+
+        summarize(&statuses, since, until);
+        if let Some(path) = &self.csv {
+            println!(
+                "\nWrote the commits of every repository and day to {:?}",
+                path
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn activity(dir: &PathBuf, since: NaiveDate, until: NaiveDate) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_default();
+
+    let activity = || -> Result<BTreeMap<NaiveDate, usize>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let times = git::author_times(&git_repo)?;
+        Ok(commits_per_day(&times, since, until))
+    };
+
+    Status {
+        owner,
+        repo,
+        result: activity().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+/// Number of commits per day in the range, days without commits are left out
+fn commits_per_day(
+    times: &[i64],
+    since: NaiveDate,
+    until: NaiveDate,
+) -> BTreeMap<NaiveDate, usize> {
+    let mut days = BTreeMap::new();
+    for time in times {
+        if let Some(day) = DateTime::from_timestamp(*time, 0).map(|t| t.date_naive()) {
+            if day >= since && day <= until {
+                *days.entry(day).or_default() += 1;
+            }
+        }
+    }
+    days
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    owner: String,
+    repo: String,
+    /// Commits per day
+    result: Result<BTreeMap<NaiveDate, usize>, String>,
+}
+
+impl Status {
+    fn total(&self) -> usize {
+        match &self.result {
+            Ok(days) => days.values().sum(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Commits per column of the heatmap, a column is one or more days
+    fn columns(&self, days: &[NaiveDate], per_column: usize) -> Vec<usize> {
+        let counts = match &self.result {
+            Ok(counts) => counts,
+            Err(_) => return vec![],
+        };
+        days.chunks(per_column)
+            .map(|chunk| chunk.iter().filter_map(|d| counts.get(d)).sum())
+            .collect()
+    }
+}
+
+fn days(since: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+    since.iter_days().take_while(|d| *d <= until).collect()
+}
+
+fn to_csv(statuses: &[Status], since: NaiveDate, until: NaiveDate) -> String {
+    let days = days(since, until);
+    let mut csv = String::from("owner,repo,date,commits\n");
+    for status in statuses {
+        if let Ok(counts) = &status.result {
+            for day in &days {
+                csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    status.owner,
+                    status.repo,
+                    day,
+                    counts.get(day).copied().unwrap_or(0)
+                ));
+            }
+        }
+    }
+    csv
+}
+
+/// One character for a number of commits, relative to the busiest column
+fn shade(count: usize, max: usize) -> char {
+    const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+    if count == 0 || max == 0 {
+        return '·';
+    }
+    SHADES[((count * SHADES.len()).div_ceil(max) - 1).min(SHADES.len() - 1)]
+}
+
+fn summarize(statuses: &[Status], since: NaiveDate, until: NaiveDate) {
+    let days = days(since, until);
+    let per_column = if days.len() > MAX_DAY_COLUMNS { 7 } else { 1 };
+
+    let ok: Vec<_> = statuses.iter().filter(|s| s.result.is_ok()).collect();
+    let columns: Vec<_> = ok.iter().map(|s| s.columns(&days, per_column)).collect();
+    let max = columns.iter().flatten().copied().max().unwrap_or(0);
+    let width = ok.iter().map(|s| s.repo.len()).max().unwrap_or(0);
+
+    let unit = if per_column == 7 { "week" } else { "day" };
+    println!(
+        "Commits per {} from {} to {}, █ is up to {} commits\n",
+        unit, since, until, max
+    );
+    for (status, columns) in ok.iter().zip(&columns) {
+        let row: String = columns.iter().map(|c| shade(*c, max)).collect();
+        let repo = format!("{:width$}", status.repo, width = width);
+        println!("{}  {}  {}", repo.bold(), row, status.total());
+    }
+
+    let total: usize = ok.iter().map(|s| s.total()).sum();
+    let active = ok.iter().filter(|s| s.total() > 0).count();
+    println!(
+        "\n{} commits in {} of {} repositories",
+        total,
+        active,
+        ok.len()
+    );
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when reading the history:", reports.len());
+        println!("\n{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commits_per_day() {
+        let since = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        // 2024-02-29 23:00, 2024-03-01 00:00 and 12:00, 2024-03-02 23:59, 2024-03-03 00:00
+        let times = [1709247600, 1709251200, 1709294400, 1709423940, 1709424000];
+
+        let days = commits_per_day(&times, since, until);
+        assert_eq!(days.get(&since), Some(&2));
+        assert_eq!(days.get(&until), Some(&1));
+        assert_eq!(days.len(), 2);
+    }
+
+    #[test]
+    fn test_shade() {
+        assert_eq!(shade(0, 8), '·');
+        assert_eq!(shade(1, 8), '░');
+        assert_eq!(shade(3, 8), '▒');
+        assert_eq!(shade(8, 8), '█');
+    }
+}
@@ -0,0 +1,276 @@
+use super::common;
+use super::errors::{self, ErrorReport};
+use super::show_size::human_size;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, RemoteRepo};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{format, row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Report the languages of all repositories that match a regex, per organisation
+///
+/// The bytes per language come from the language detection of GitHub, which leaves out
+/// vendored, generated and documentation files.
+pub struct StatsLanguagesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Also show the main languages of every repository
+    pub per_repo: bool,
+    #[arg(long)]
+    /// Write the bytes of every language of every repository to a csv file
+    pub csv: Option<PathBuf>,
+}
+
+impl StatsLanguagesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| languages(repo, &user_token))
+            .collect();
+        let owners = totals(&statuses);
+
+        if let Some(path) = &self.csv {
+            std::fs::write(path, to_csv(&statuses))
+                .with_context(|| format!("Cannot write the csv file {:?}", path))?;
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!({ "repos": statuses, "owners": owners }));
+            return Ok(());
+        }
+
+        summarize(&statuses, &owners, self.per_repo);
+        if let Some(path) = &self.csv {
+            println!("\nWrote the languages of every repository to {:?}", path);
+        }
+
+        Ok(())
+    }
+}
+
+fn languages(repo: &RemoteRepo, token: &str) -> Status {
+    Status {
+        owner: repo.owner.clone(),
+        repo: repo.full_name(),
+        result: github::get_languages(repo, token).map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    owner: String,
+    repo: String,
+    /// Bytes per language
+    result: Result<BTreeMap<String, u64>, String>,
+}
+
+impl Status {
+    /// The languages with their share of the repository, the largest first
+    fn shares(&self) -> Vec<Share> {
+        match &self.result {
+            Ok(languages) => shares(languages, |_| 1),
+            Err(_) => vec![],
+        }
+    }
+}
+
+/// What one language makes up of a repository or an organisation
+#[derive(Debug, Serialize, PartialEq)]
+struct Share {
+    language: String,
+    bytes: u64,
+    percent: f64,
+    repos: usize,
+}
+
+fn shares(languages: &BTreeMap<String, u64>, repos: impl Fn(&str) -> usize) -> Vec<Share> {
+    let total: u64 = languages.values().sum();
+    let mut shares: Vec<_> = languages
+        .iter()
+        .map(|(language, bytes)| Share {
+            language: language.clone(),
+            bytes: *bytes,
+            percent: if total == 0 {
+                0.0
+            } else {
+                *bytes as f64 * 100.0 / total as f64
+            },
+            repos: repos(language),
+        })
+        .collect();
+    shares.sort_by_key(|s| Reverse(s.bytes));
+    shares
+}
+
+/// The languages of every owner, summed over its repositories
+fn totals(statuses: &[Status]) -> BTreeMap<String, Vec<Share>> {
+    let mut bytes: BTreeMap<&str, BTreeMap<String, u64>> = BTreeMap::new();
+    let mut repos: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for status in statuses {
+        if let Ok(languages) = &status.result {
+            let owner = bytes.entry(status.owner.as_str()).or_default();
+            for (language, count) in languages {
+                *owner.entry(language.clone()).or_default() += count;
+                *repos
+                    .entry((status.owner.as_str(), language.as_str()))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    bytes
+        .into_iter()
+        .map(|(owner, languages)| {
+            let shares = shares(&languages, |l| repos.get(&(owner, l)).copied().unwrap_or(0));
+            (owner.to_string(), shares)
+        })
+        .collect()
+}
+
+fn to_csv(statuses: &[Status]) -> String {
+    let mut csv = String::from("owner,repo,language,bytes,percent\n");
+    for status in statuses {
+        for share in status.shares() {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.2}\n",
+                status.owner,
+                status.repo,
+                csv_field(&share.language),
+                share.bytes,
+                share.percent
+            ));
+        }
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn summarize(statuses: &[Status], owners: &BTreeMap<String, Vec<Share>>, per_repo: bool) {
+    if per_repo {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Repo", "Size", "Main languages"]);
+        for status in statuses.iter().filter(|s| s.result.is_ok()) {
+            let shares = status.shares();
+            let size: u64 = shares.iter().map(|s| s.bytes).sum();
+            let main: Vec<_> = shares
+                .iter()
+                .take(3)
+                .map(|s| format!("{} {:.1}%", s.language, s.percent))
+                .collect();
+            table.add_row(row![b -> &status.repo, r -> human_size(size), main.join(", ")]);
+        }
+        table.printstd();
+        println!();
+    }
+
+    for (owner, shares) in owners {
+        println!("{}", owner.bold());
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Language", "Size", "Share", "Repos"]);
+        for share in shares {
+            table.add_row(row![
+                share.language,
+                r -> human_size(share.bytes),
+                r -> format!("{:.1}%", share.percent),
+                r -> share.repos
+            ]);
+        }
+        table.printstd();
+        println!();
+    }
+
+    let reports: Vec<_> = statuses
+        .iter()
+        .filter_map(|s| match &s.result {
+            Err(e) => Some(ErrorReport::from_message(&s.repo, e)),
+            Ok(_) => None,
+        })
+        .collect();
+    if !reports.is_empty() {
+        let msg = format!("There {} errors when looking up languages:", reports.len());
+        println!("{}\n", msg.red());
+
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(owner: &str, name: &str, languages: &[(&str, u64)]) -> Status {
+        Status {
+            owner: owner.to_string(),
+            repo: format!("{}/{}", owner, name),
+            result: Ok(languages.iter().map(|(l, b)| (l.to_string(), *b)).collect()),
+        }
+    }
+
+    #[test]
+    fn test_totals() {
+        let statuses = vec![
+            status("giellalt", "lang-sme", &[("Makefile", 100), ("XML", 300)]),
+            status("giellalt", "lang-smj", &[("XML", 600)]),
+            status("divvun", "divvunspell", &[("Rust", 50)]),
+        ];
+
+        let totals = totals(&statuses);
+        assert_eq!(
+            totals["giellalt"],
+            vec![
+                Share {
+                    language: "XML".into(),
+                    bytes: 900,
+                    percent: 90.0,
+                    repos: 2,
+                },
+                Share {
+                    language: "Makefile".into(),
+                    bytes: 100,
+                    percent: 10.0,
+                    repos: 1,
+                },
+            ]
+        );
+        assert_eq!(totals["divvun"][0].percent, 100.0);
+    }
+}
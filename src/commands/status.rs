@@ -1,19 +1,23 @@
 use super::common;
+use super::table::SortArgs;
 use crate::cli::{OutputFormat, Args as CommonArgs};
 use crate::filter::Filter;
 use crate::git;
 use crate::git::GitStatus;
 use crate::path::dir_name;
 use anyhow::{Context, Result};
-use clap::Parser;
-use prettytable::{format, row, Row, Table};
+use clap::{Parser, ValueEnum};
+use prettytable::{format, row, Cell, Row, Table};
 use rayon::prelude::*;
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 /// Show git status of all repositories that match a pattern
+///
+/// The json output is a list with an object per repository, whose fields are documented in
+/// the README and only ever added to.
 pub struct StatusArgs {
     #[arg(long, short)]
     /// Target organisation name
@@ -29,6 +33,66 @@ pub struct StatusArgs {
     #[arg(long, short)]
     /// Option to omit repositories without changes
     pub quiet: bool,
+    #[arg(long = "path")]
+    /// Only show the files that match this glob, e.g. src/fst/**, can be repeated
+    pub paths: Vec<String>,
+    #[arg(long, value_enum, value_delimiter = ',')]
+    /// Only show these fields, e.g. name,branch,is_ahead
+    ///
+    /// The table then has these columns in this order, and a row per repository with the
+    /// number of files for the file lists.
+    pub fields: Vec<Field>,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+/// A field of the status of a repository, a column of the table and a key of the json output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    Name,
+    Branch,
+    New,
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Typechanges,
+    Conflicted,
+    #[value(name = "is_ahead")]
+    IsAhead,
+    #[value(name = "is_behind")]
+    IsBehind,
+}
+
+impl Field {
+    fn key(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Branch => "branch",
+            Field::New => "new",
+            Field::Added => "added",
+            Field::Modified => "modified",
+            Field::Deleted => "deleted",
+            Field::Renamed => "renamed",
+            Field::Typechanges => "typechanges",
+            Field::Conflicted => "conflicted",
+            Field::IsAhead => "is_ahead",
+            Field::IsBehind => "is_behind",
+        }
+    }
+
+    /// Whether the field is in the `status` object of the record instead of at its top
+    fn in_status(&self) -> bool {
+        !matches!(self, Field::Name | Field::Branch)
+    }
+
+    fn value<'a>(&self, record: &'a Value) -> &'a Value {
+        if self.in_status() {
+            &record["status"][self.key()]
+        } else {
+            &record[self.key()]
+        }
+    }
 }
 
 impl StatusArgs {
@@ -38,9 +102,9 @@ impl StatusArgs {
 
         let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
 
-        let statuses: Result<Vec<_>> = sub_dirs.iter().map(status).collect();
+        let statuses: Result<Vec<_>> = sub_dirs.iter().map(|d| status(d, &self.paths)).collect();
         let statuses: Vec<_> = statuses?;
-        let statuses: Vec<_> = statuses
+        let mut statuses: Vec<_> = statuses
             .into_iter()
             .filter(|status| {
                 !(self.quiet
@@ -50,8 +114,27 @@ impl StatusArgs {
             })
             .collect();
 
+        if self.fields.is_empty() {
+            self.sort
+                .sort(&mut statuses, &titles(), |s| s.to_repo_summarize().to_row())?;
+        } else {
+            self.sort
+                .sort(&mut statuses, &field_titles(&self.fields), |s| {
+                    field_row(s, &self.fields)
+                })?;
+        }
+
         if let Some(OutputFormat::Json) = common_args.format {
-            println!("{}", json!(statuses));
+            let records: Vec<_> = statuses
+                .iter()
+                .map(|s| select(s.to_record(), &self.fields))
+                .collect();
+            println!("{}", json!(records));
+            return Ok(());
+        }
+
+        if !self.fields.is_empty() {
+            to_field_table(&statuses, &self.fields).printstd();
             return Ok(());
         }
 
@@ -63,11 +146,11 @@ impl StatusArgs {
     }
 }
 
-fn status(dir: &PathBuf) -> Result<RepoStatus> {
+fn status(dir: &PathBuf, paths: &[String]) -> Result<RepoStatus> {
     let name = dir_name(dir)?;
     let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
 
-    let status = git::status(&git_repo, false)?;
+    let status = git::status_in(&git_repo, false, paths)?;
     let branch = git::head_shorthand(&git_repo)?;
     let repo_status = RepoStatus {
         name,
@@ -77,16 +160,67 @@ fn status(dir: &PathBuf) -> Result<RepoStatus> {
     Ok(repo_status)
 }
 
+/// The record of a repository with only the given fields, or all fields when none are given
+fn select(record: StatusRecord, fields: &[Field]) -> Value {
+    let value = json!(record);
+    if fields.is_empty() {
+        return value;
+    }
+    let mut selected = serde_json::Map::new();
+    let mut status = serde_json::Map::new();
+    for field in fields {
+        let key = field.key().to_string();
+        if field.in_status() {
+            status.insert(key, field.value(&value).clone());
+        } else {
+            selected.insert(key, field.value(&value).clone());
+        }
+    }
+    if !status.is_empty() {
+        selected.insert("status".to_string(), Value::Object(status));
+    }
+    Value::Object(selected)
+}
+
+fn to_field_table(statuses: &[RepoStatus], fields: &[Field]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(field_titles(fields));
+    for status in statuses {
+        table.add_row(field_row(status, fields));
+    }
+    table
+}
+
+fn field_titles(fields: &[Field]) -> Row {
+    Row::new(fields.iter().map(|f| Cell::new(f.key())).collect())
+}
+
+fn field_row(status: &RepoStatus, fields: &[Field]) -> Row {
+    let record = select(status.to_record(), fields);
+    let cells = fields
+        .iter()
+        .map(|f| match f.value(&record) {
+            Value::String(s) => Cell::new(s),
+            Value::Array(list) => Cell::new(&list.len().to_string()).style_spec("r"),
+            value => Cell::new(&value.to_string()).style_spec("r"),
+        })
+        .collect();
+    Row::new(cells)
+}
+
 fn to_table(statuses: &[StatusRow]) -> Table {
     let rows: Vec<_> = statuses.par_iter().map(|s| s.to_row()).collect();
     let mut table = Table::init(rows);
     table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
-    table.set_titles(
-        row!["Repo", "branch", r -> "±origin", r -> "U", r -> "D", r -> "M", r -> "C", r -> "A"],
-    );
+    table.set_titles(titles());
     table
 }
 
+fn titles() -> Row {
+    row!["Repo", "branch", r -> "±origin", r -> "U", r -> "D", r -> "M", r -> "C", r -> "A"]
+}
+
 fn to_rows(statuses: &[RepoStatus], verbose: bool) -> Vec<StatusRow> {
     let mut rows: Vec<_> = statuses.iter().flat_map(|s| s.to_rows(verbose)).collect();
     rows.append(&mut to_total_summarize(statuses));
@@ -132,14 +266,60 @@ fn to_total_summarize(statuses: &[RepoStatus]) -> Vec<StatusRow> {
     rows
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
 struct RepoStatus {
     name: String,
     branch: String,
     status: GitStatus,
 }
 
+/// A repository in the json output of `gut status`
+///
+/// This is the schema that scripts read, the shape that `gut status` always had. It is kept
+/// apart from `GitStatus` so that changes there do not change the output. Fields may be added,
+/// but not renamed, moved or removed.
+#[derive(Debug, Serialize)]
+struct StatusRecord<'a> {
+    name: &'a str,
+    branch: &'a str,
+    status: StatusChanges<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusChanges<'a> {
+    /// Files that are not added
+    new: &'a [String],
+    added: &'a [String],
+    modified: &'a [String],
+    deleted: &'a [String],
+    renamed: &'a [String],
+    typechanges: &'a [String],
+    conflicted: &'a [String],
+    /// Commits that origin does not have
+    is_ahead: usize,
+    /// Commits of origin that are not merged
+    is_behind: usize,
+}
+
 impl RepoStatus {
+    fn to_record(&self) -> StatusRecord<'_> {
+        StatusRecord {
+            name: &self.name,
+            branch: &self.branch,
+            status: StatusChanges {
+                new: &self.status.new,
+                added: &self.status.added,
+                modified: &self.status.modified,
+                deleted: &self.status.deleted,
+                renamed: &self.status.renamed,
+                typechanges: &self.status.typechanges,
+                conflicted: &self.status.conflicted,
+                is_ahead: self.status.is_ahead,
+                is_behind: self.status.is_behind,
+            },
+        }
+    }
+
     fn to_rows(&self, verbose: bool) -> Vec<StatusRow> {
         if verbose {
             self.to_repo_detail()
@@ -254,3 +434,67 @@ impl StatusRow {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_status() -> RepoStatus {
+        RepoStatus {
+            name: "lang-sme".to_string(),
+            branch: "main".to_string(),
+            status: GitStatus {
+                added: vec!["src/new.lexc".to_string()],
+                new: vec!["notes.txt".to_string()],
+                modified: vec!["README.md".to_string()],
+                deleted: vec![],
+                renamed: vec![],
+                typechanges: vec![],
+                conflicted: vec![],
+                is_ahead: 2,
+                is_behind: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_record_schema() {
+        assert_eq!(
+            json!(repo_status().to_record()),
+            json!({
+                "name": "lang-sme",
+                "branch": "main",
+                "status": {
+                    "new": ["notes.txt"],
+                    "added": ["src/new.lexc"],
+                    "modified": ["README.md"],
+                    "deleted": [],
+                    "renamed": [],
+                    "typechanges": [],
+                    "conflicted": [],
+                    "is_ahead": 2,
+                    "is_behind": 1,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_every_field_is_in_the_record() {
+        let record = json!(repo_status().to_record());
+        for field in Field::value_variants() {
+            assert!(!field.value(&record).is_null(), "{:?}", field);
+            let name = field.to_possible_value().unwrap();
+            assert_eq!(name.get_name(), field.key());
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let selected = select(repo_status().to_record(), &[Field::Branch, Field::Name]);
+        assert_eq!(selected, json!({"branch": "main", "name": "lang-sme"}));
+
+        let selected = select(repo_status().to_record(), &[Field::Name, Field::IsBehind]);
+        assert_eq!(selected, json!({"name": "lang-sme", "status": {"is_behind": 1}}));
+    }
+}
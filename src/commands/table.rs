@@ -0,0 +1,335 @@
+use anyhow::{anyhow, Result};
+use clap::{Args, ValueEnum};
+use colored::*;
+use prettytable::{cell, format, Row, Table};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// How a command orders the rows of its table
+#[derive(Debug, Clone, Args)]
+pub struct SortArgs {
+    #[arg(long, value_name = "COLUMN")]
+    /// Order the rows of the table by a column, e.g. repo, "pull status" or lfs
+    ///
+    /// Sizes, ages and counts are ordered by their value, the other columns alphabetically.
+    pub sort_by: Option<String>,
+    #[arg(long, requires = "sort_by")]
+    /// Order the rows from the largest to the smallest value
+    pub desc: bool,
+}
+
+impl SortArgs {
+    /// Order the items of a table by the cells of the --sort-by column in their rows
+    ///
+    /// Without --sort-by the order is kept. Items whose rows have the same value keep their
+    /// order too, so that an earlier order breaks ties.
+    pub fn sort<T>(&self, items: &mut [T], titles: &Row, to_row: impl Fn(&T) -> Row) -> Result<()> {
+        let Some(column) = &self.sort_by else {
+            return Ok(());
+        };
+        let index = column_index(titles, column)?;
+
+        let mut keyed: Vec<_> = items
+            .iter()
+            .map(|item| {
+                let row = to_row(item);
+                let text = row.get_cell(index).map(|c| c.get_content());
+                sort_key(text.as_deref().unwrap_or(""))
+            })
+            .enumerate()
+            .collect();
+        keyed.sort_by(|(_, a), (_, b)| {
+            let order = a.compare(b);
+            if self.desc {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        let order: Vec<_> = keyed.into_iter().map(|(i, _)| i).collect();
+        apply_order(items, order);
+        Ok(())
+    }
+}
+
+/// How the results of several owners are printed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// A section with its own table for every owner
+    Owner,
+    /// One table with an Owner column
+    Flat,
+}
+
+/// A table whose rows belong to owners, e.g. for commands that take --all-orgs
+pub struct OwnerTable {
+    titles: Row,
+    rows: Vec<(String, Row)>,
+}
+
+impl OwnerTable {
+    pub fn new(titles: Row) -> OwnerTable {
+        OwnerTable {
+            titles,
+            rows: vec![],
+        }
+    }
+
+    pub fn add_row(&mut self, owner: &str, row: Row) {
+        self.rows.push((owner.to_string(), row));
+    }
+
+    pub fn print(self, group_by: GroupBy) {
+        for (owner, table) in self.tables(group_by) {
+            if let Some(owner) = owner {
+                println!("\n{}", owner.bold());
+            }
+            table.printstd();
+        }
+    }
+
+    /// The tables to print, with the owner they belong to when grouped by owner
+    fn tables(self, group_by: GroupBy) -> Vec<(Option<String>, Table)> {
+        match group_by {
+            GroupBy::Flat => {
+                let mut titles = self.titles;
+                titles.insert_cell(0, cell!("Owner"));
+                let mut table = new_table(titles);
+                let mut previous = None;
+                for (owner, mut row) in self.rows {
+                    let shown = if previous.as_ref() == Some(&owner) {
+                        ""
+                    } else {
+                        owner.as_str()
+                    };
+                    row.insert_cell(0, cell!(b -> shown));
+                    table.add_row(row);
+                    previous = Some(owner);
+                }
+                vec![(None, table)]
+            }
+            GroupBy::Owner => {
+                let mut groups: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+                for (owner, row) in self.rows {
+                    groups.entry(owner).or_default().push(row);
+                }
+                groups
+                    .into_iter()
+                    .map(|(owner, rows)| {
+                        let mut table = new_table(self.titles.clone());
+                        for row in rows {
+                            table.add_row(row);
+                        }
+                        (Some(owner), table)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn new_table(titles: Row) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles);
+    table
+}
+
+/// The owner and the name in the full name of a repository, like `giellalt/lang-sme`
+pub fn split_full_name(full_name: &str) -> (&str, &str) {
+    full_name.split_once('/').unwrap_or(("", full_name))
+}
+
+/// The index of the column with this title, ignoring case, spaces and signs
+fn column_index(titles: &Row, column: &str) -> Result<usize> {
+    let wanted = normalize(column);
+    let names: Vec<_> = titles.iter().map(|c| c.get_content()).collect();
+    names
+        .iter()
+        .position(|name| normalize(name) == wanted)
+        .ok_or_else(|| {
+            anyhow!(
+                "There is no column {:?}, the columns are: {}",
+                column,
+                names.join(", ")
+            )
+        })
+}
+
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum SortKey {
+    Number(f64),
+    Text(String),
+}
+
+impl SortKey {
+    /// Numbers come before text
+    fn compare(&self, other: &SortKey) -> Ordering {
+        match (self, other) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Number(_), SortKey::Text(_)) => Ordering::Less,
+            (SortKey::Text(_), SortKey::Number(_)) => Ordering::Greater,
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+        }
+    }
+}
+
+/// The value of a cell, with sizes like `1.5 MB` in bytes and ages like `40d` in days
+fn sort_key(text: &str) -> SortKey {
+    let text = text.trim();
+    let mut words = text.split_whitespace();
+    let first = words.next().unwrap_or_default();
+    let end = first
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .unwrap_or(first.len());
+    let (number, unit) = first.split_at(end);
+    let Ok(number) = number.parse::<f64>() else {
+        return SortKey::Text(text.to_lowercase());
+    };
+    let unit = if unit.is_empty() {
+        words.next().unwrap_or_default()
+    } else {
+        unit
+    };
+    let factor = match unit {
+        "KB" => 1024f64,
+        "MB" => 1024f64.powi(2),
+        "GB" => 1024f64.powi(3),
+        "TB" => 1024f64.powi(4),
+        _ => 1.0,
+    };
+    SortKey::Number(number * factor)
+}
+
+/// Reorder the items so that the item at `order[i]` comes at position `i`
+fn apply_order<T>(items: &mut [T], order: Vec<usize>) {
+    let mut target = vec![0; order.len()];
+    for (new, old) in order.into_iter().enumerate() {
+        target[old] = new;
+    }
+    for i in 0..items.len() {
+        while target[i] != i {
+            let t = target[i];
+            items.swap(i, t);
+            target.swap(i, t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prettytable::row;
+
+    #[test]
+    fn test_sort_key() {
+        assert_eq!(sort_key("12"), SortKey::Number(12.0));
+        assert_eq!(sort_key("-3"), SortKey::Number(-3.0));
+        assert_eq!(sort_key("40d"), SortKey::Number(40.0));
+        assert_eq!(sort_key("1.5 KB (+512 B)"), SortKey::Number(1536.0));
+        assert_eq!(
+            sort_key("Failed (network)"),
+            SortKey::Text("failed (network)".to_string())
+        );
+        assert!(sort_key("2 MB").compare(&sort_key("900 KB")) == Ordering::Greater);
+        assert!(sort_key("7").compare(&sort_key("-")) == Ordering::Less);
+    }
+
+    #[test]
+    fn test_owner_table() {
+        let mut table = OwnerTable::new(row!["Repo", "Path"]);
+        table.add_row("giellalt", row!["lang-sme", "README.md"]);
+        table.add_row("giellalt", row!["lang-sma", "README.md"]);
+        table.add_row("divvun", row!["divvun-api", "src/main.rs"]);
+
+        let contents = |table: &Table| -> Vec<Vec<String>> {
+            table
+                .row_iter()
+                .map(|r| r.iter().map(|c| c.get_content()).collect())
+                .collect()
+        };
+
+        let flat = clone(&table).tables(GroupBy::Flat);
+        assert_eq!(flat.len(), 1);
+        assert_eq!(flat[0].0, None);
+        assert_eq!(
+            contents(&flat[0].1),
+            [
+                ["giellalt", "lang-sme", "README.md"],
+                ["", "lang-sma", "README.md"],
+                ["divvun", "divvun-api", "src/main.rs"],
+            ]
+        );
+
+        let grouped = table.tables(GroupBy::Owner);
+        let owners: Vec<_> = grouped.iter().map(|t| t.0.as_deref()).collect();
+        assert_eq!(owners, [Some("divvun"), Some("giellalt")]);
+        assert_eq!(
+            contents(&grouped[1].1),
+            [["lang-sme", "README.md"], ["lang-sma", "README.md"]]
+        );
+    }
+
+    fn clone(table: &OwnerTable) -> OwnerTable {
+        OwnerTable {
+            titles: table.titles.clone(),
+            rows: table.rows.clone(),
+        }
+    }
+
+    #[test]
+    fn test_split_full_name() {
+        assert_eq!(
+            split_full_name("giellalt/lang-sme"),
+            ("giellalt", "lang-sme")
+        );
+        assert_eq!(split_full_name("lang-sme"), ("", "lang-sme"));
+    }
+
+    #[test]
+    fn test_sort() {
+        let titles = row!["Repo", "Pull Status", "±origin"];
+        let mut items = [
+            ("lang-sme", "Success", 2),
+            ("lang-fit", "Failed", 10),
+            ("lang-sma", "Success", -1),
+        ];
+        let to_row = |i: &(&str, &str, i32)| row![i.0, i.1, i.2];
+
+        let sort = |column: &str, desc: bool| SortArgs {
+            sort_by: Some(column.to_string()),
+            desc,
+        };
+
+        sort("origin", false)
+            .sort(&mut items, &titles, to_row)
+            .unwrap();
+        let names: Vec<_> = items.iter().map(|i| i.0).collect();
+        assert_eq!(names, ["lang-sma", "lang-sme", "lang-fit"]);
+
+        sort("pull-status", true)
+            .sort(&mut items, &titles, to_row)
+            .unwrap();
+        let names: Vec<_> = items.iter().map(|i| i.0).collect();
+        assert_eq!(names, ["lang-sma", "lang-sme", "lang-fit"]);
+
+        sort("REPO", false)
+            .sort(&mut items, &titles, to_row)
+            .unwrap();
+        let names: Vec<_> = items.iter().map(|i| i.0).collect();
+        assert_eq!(names, ["lang-fit", "lang-sma", "lang-sme"]);
+
+        assert!(sort("size", false)
+            .sort(&mut items, &titles, to_row)
+            .is_err());
+    }
+}
@@ -1,17 +1,21 @@
 use super::patch_file::*;
 use crate::cli::Args as CommonArgs;
 use crate::commands::common;
+use crate::commands::errors::{self, ErrorCategory, ErrorReport};
+use crate::commands::guard::Guard;
 use crate::commands::models::template::*;
 use crate::commands::models::ExistDirectory;
 use crate::filter::Filter;
 use crate::git;
 use crate::path;
+use crate::repo_state::{RepoState, PENDING_TEMPLATE_APPLY};
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use colored::*;
 use git2::Repository;
+use prettytable::{cell, format, row, Cell, Row, Table};
 use std::fs::{create_dir_all, write, File};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
 use std::str;
 
 /// Apply changes from template to all prject that match the regex
@@ -32,18 +36,27 @@ pub struct ApplyArgs {
     /// Flag to abort apply changes process
     #[arg(long)]
     pub abort: bool,
+    /// Flag to show which repositories are in the middle of an apply changes process
+    #[arg(long)]
+    pub status: bool,
     /// Flag to include optional files
     #[arg(long)]
     pub optional: bool,
     /// Skip CI
     #[arg(long)]
     pub skip_ci: bool,
+    /// Check that CI passes on the default branch of every repository before applying
+    #[arg(long)]
+    pub check_ci: bool,
+    /// Apply even when the preflight checks fail
+    #[arg(long)]
+    pub force: bool,
 }
 
 impl ApplyArgs {
     pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
-        if self.finish && self.abort {
-            println!("You cannot provide both \"--continue\" and \"--abort\" at the same time");
+        if [self.finish, self.abort, self.status].iter().filter(|f| **f).count() > 1 {
+            println!("You can only provide one of \"--continue\", \"--abort\" and \"--status\" at the same time");
             return Ok(());
         }
 
@@ -52,12 +65,20 @@ impl ApplyArgs {
         let target_dirs =
             common::read_dirs_for_org(organisation.as_str(), &root, self.regex.as_ref())?;
 
-        if self.finish {
+        if self.status {
+            let statuses: Vec<_> = target_dirs.iter().map(apply_status).collect();
+            summarize(&statuses);
+        } else if self.finish {
             // finish apply process
             for dir in target_dirs {
+                let name = path::dir_name(&dir)?;
+                if !is_applying(&dir) {
+                    println!("Skip {} because there is no on going template apply changes", name);
+                    continue;
+                }
                 match continue_apply(&dir, self.skip_ci) {
-                    Ok(_) => println!("Apply changes finish successfully"),
-                    Err(e) => println!("Apply changes finish failed because {:?}", e),
+                    Ok(_) => println!("Apply changes finish successfully for {}", name),
+                    Err(e) => println!("Apply changes finish failed for {} because {:?}", name, e),
                 }
             }
         } else if self.abort {
@@ -70,6 +91,13 @@ impl ApplyArgs {
             }
         } else {
             // start apply process
+            let guard = Guard {
+                clean_worktree: true,
+                ci: self.check_ci,
+                requests_per_repo: 0,
+            };
+            guard.check(&target_dirs, self.force)?;
+
             let template_delta =
                 TemplateDelta::get(&self.template.path.join(".gut/template.toml"))?;
 
@@ -87,6 +115,143 @@ impl ApplyArgs {
     }
 }
 
+fn is_applying(target_dir: &Path) -> bool {
+    target_dir.join(".git/gut/template_apply/APPLYING").exists()
+}
+
+/// Files left behind when a hunk cannot be applied cleanly
+fn rejected_files(target_repo: &Repository) -> Result<Vec<String>> {
+    let status = git::status(target_repo, true)?;
+    let files = status
+        .new
+        .into_iter()
+        .chain(status.conflicted)
+        .filter(|f| f.ends_with(".rej") || f.ends_with(".orig"))
+        .collect();
+    Ok(files)
+}
+
+fn apply_status(target_dir: &PathBuf) -> Status {
+    let mut repo = "".to_string();
+    let mut state = || -> Result<ApplyState> {
+        repo = path::dir_name(target_dir)?;
+        if !is_applying(target_dir) {
+            return Ok(ApplyState::NotApplying);
+        }
+        let target_repo = git::open::open(target_dir)?;
+        let rejected = rejected_files(&target_repo)?;
+        if rejected.is_empty() {
+            Ok(ApplyState::Applying)
+        } else {
+            Ok(ApplyState::Conflicted(rejected))
+        }
+    };
+    let result = state();
+    Status { repo, result }
+}
+
+enum ApplyState {
+    NotApplying,
+    Applying,
+    Conflicted(Vec<String>),
+}
+
+struct Status {
+    repo: String,
+    result: Result<ApplyState>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![cell!(b -> &self.repo), self.state(), self.rejected()])
+    }
+
+    fn state(&self) -> Cell {
+        match &self.result {
+            Ok(ApplyState::NotApplying) => cell!(r -> "-"),
+            Ok(ApplyState::Applying) => cell!(Fgr -> "Applying"),
+            Ok(ApplyState::Conflicted(_)) => cell!(Fyr -> "Conflicted"),
+            Err(e) => ErrorCategory::of(e).failed_cell(),
+        }
+    }
+
+    fn rejected(&self) -> Cell {
+        match &self.result {
+            Ok(ApplyState::Conflicted(files)) => cell!(l -> files.join("\n")),
+            _ => cell!(r -> "-"),
+        }
+    }
+
+    fn is_applying(&self) -> bool {
+        matches!(self.result, Ok(ApplyState::Applying))
+    }
+
+    fn is_conflicted(&self) -> bool {
+        matches!(self.result, Ok(ApplyState::Conflicted(_)))
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::new(&self.repo, e)
+    }
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Apply Status", "Rejected files"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let applying = statuses.iter().filter(|s| s.is_applying()).count();
+    let conflicted = statuses.iter().filter(|s| s.is_conflicted()).count();
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+
+    if applying > 0 {
+        let msg = format!(
+            "\n{} repos are ready to finish with \"--continue\" once all changes are added",
+            applying
+        );
+        println!("{}", msg.green());
+    }
+
+    if conflicted > 0 {
+        let msg = format!(
+            "\n{} repos have rejected hunks that need to be resolved before continuing",
+            conflicted
+        );
+        println!("{}", msg.yellow());
+    }
+
+    if applying == 0 && conflicted == 0 {
+        println!("\nThere is no on going template apply changes");
+    }
+
+    if !errors.is_empty() {
+        let msg = format!("There are {} errors when reading apply status:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
 /// do clean -f and reset --hard
 /// Remove temp directory
 fn abort_apply(target_dir: &PathBuf) -> Result<()> {
@@ -96,6 +261,7 @@ fn abort_apply(target_dir: &PathBuf) -> Result<()> {
     if template_apply_dir.exists() {
         path::remove_path(template_apply_dir)?;
     }
+    RepoState::record(target_dir, |s| s.remove_pending(PENDING_TEMPLATE_APPLY));
     Ok(())
 }
 
@@ -140,6 +306,10 @@ fn continue_apply(target_dir: &PathBuf, skip_ci: bool) -> Result<()> {
 
     // remove temp dir
     path::remove_path(template_apply_dir)?;
+    RepoState::record(target_dir, |s| {
+        s.template_rev = Some(new_delta.rev_id);
+        s.remove_pending(PENDING_TEMPLATE_APPLY);
+    });
 
     Ok(())
 }
@@ -156,7 +326,6 @@ fn continue_apply(target_dir: &PathBuf, skip_ci: bool) -> Result<()> {
 /// - write patch file to .git/gut/template_appy/patch.diff
 /// - apply patch command in target repo
 /// - Done.
-
 fn start_apply(
     template_dir: &PathBuf,
     template_delta: &TemplateDelta,
@@ -188,6 +357,7 @@ fn start_apply(
     create_dir_all(template_apply_dir)?;
     // write status file to mark process as on going
     File::create(apply_status_path)?;
+    RepoState::record(target_dir, |s| s.add_pending(PENDING_TEMPLATE_APPLY));
 
     let template_repo = git::open::open(template_dir)?;
 
@@ -218,9 +388,18 @@ fn start_apply(
         .map(|p| p.apply_patterns(&target_delta.replacements));
     let target_patch_files: Result<Vec<_>> = target_patch_files.into_iter().collect();
 
+    let target_patch_files = target_patch_files?;
+
     let diff_path = &template_apply_dir.join("patch.diff");
-    write(diff_path, to_content(&target_patch_files?))?;
-    execute_patch(diff_path.to_str().unwrap(), target_dir)?;
+    write(diff_path, to_content(&target_patch_files))?;
+    let patches: Vec<_> = target_patch_files
+        .iter()
+        .map(|p| (p.new_file.clone(), p.to_content()))
+        .collect();
+    let rejected = git::apply_patches(&target_repo, &patches)?;
+    if !rejected.is_empty() {
+        return Err(anyhow!("patching failed for {}!", rejected.join(", ")));
+    }
 
     let update_target_delta = target_delta.update(template_delta.rev_id, temp_current_sha.as_str());
     update_target_delta.save(&template_apply_dir.join("temp_target_delta.toml"))?;
@@ -259,36 +438,7 @@ fn previous_template_sha(template_repo: &Repository, target_delta: &TargetDelta)
     Err(anyhow!("Cannot find the commit of previous rev_id"))
 }
 
-fn execute_patch(patch_file: &str, dir: &PathBuf) -> Result<Output> {
-    let output = Command::new("patch")
-        .arg("-p1")
-        .arg("-i")
-        .arg(patch_file)
-        .current_dir(dir)
-        .output()
-        .expect("failed to execute process");
-
-    log::debug!("Patch result {:?} at {:?}: {:?}", patch_file, dir, output);
-    match output.status.success() {
-        true => Ok(output),
-        false => Err(anyhow!("patching failed!"))
-    }
-}
-
 fn clean_git_dir(dir: &PathBuf) -> Result<()> {
-    Command::new("git")
-        .arg("clean")
-        .arg("-f")
-        .current_dir(dir)
-        .output()
-        .expect("failed to execute process");
-
-    Command::new("git")
-        .arg("reset")
-        .arg("--hard")
-        .current_dir(dir)
-        .output()
-        .expect("failed to execute process");
-
-    Ok(())
+    let repo = git::open::open(dir)?;
+    git::clean_and_reset(&repo)
 }
@@ -33,7 +33,7 @@ impl GenerateArgs {
         let target_dir = Path::new(&self.dir).to_path_buf();
         create_dir_all(&target_dir).context("Cannot create target directory")?;
 
-        match generate(template_dir, &target_dir, self.no_init) {
+        match generate(template_dir, &target_dir, self.no_init, &BTreeMap::new()) {
             Ok(_) => println!("Generate success at {:?}", target_dir),
             Err(e) => println!("Generate failed because {:?}", e),
         }
@@ -45,12 +45,19 @@ impl GenerateArgs {
 // init git repo
 // create delta files
 // commit all
-fn generate(template_dir: &PathBuf, target_dir: &PathBuf, no_init: bool) -> Result<()> {
+//
+// Patterns that are not in `known` are asked for
+pub fn generate(
+    template_dir: &PathBuf,
+    target_dir: &PathBuf,
+    no_init: bool,
+    known: &BTreeMap<String, String>,
+) -> Result<()> {
     let template_repo = git::open(template_dir)?;
     let current_sha = git::head_sha(&template_repo)?;
 
     let template_delta = TemplateDelta::get(&template_dir.join(".gut/template.toml"))?;
-    let target_info = get_target_info(&template_delta)?;
+    let target_info = get_target_info(&template_delta, known)?;
 
     // generate file paths
     let generate_files = path::all_files(template_dir);
@@ -97,11 +104,23 @@ struct TargetInfo {
     reps: BTreeMap<String, String>,
 }
 
-fn get_target_info(template_delta: &TemplateDelta) -> Result<TargetInfo> {
-    println!("Enter patterns:");
+fn get_target_info(
+    template_delta: &TemplateDelta,
+    known: &BTreeMap<String, String>,
+) -> Result<TargetInfo> {
     let mut reps = BTreeMap::new();
+    let mut asked = false;
     for pattern in &template_delta.patterns {
-        let key = common::ask_for(pattern)?;
+        let key = match known.get(pattern) {
+            Some(value) => value.clone(),
+            None => {
+                if !asked {
+                    println!("Enter patterns:");
+                    asked = true;
+                }
+                common::ask_for(pattern)?
+            }
+        };
         reps.insert(pattern.to_string(), key);
     }
 
@@ -1,5 +1,6 @@
 use crate::cli::Args as CommonArgs;
 use super::common;
+use super::topic_helper;
 use crate::filter::Filter;
 use crate::github;
 use anyhow::Result;
@@ -19,6 +20,9 @@ pub struct TopicAddArgs {
     #[arg(long, short)]
     /// All topics will be added
     pub topics: Vec<String>,
+    #[arg(long)]
+    /// Start with the first repository instead of resuming an interrupted run
+    pub restart: bool,
 }
 
 impl TopicAddArgs {
@@ -37,20 +41,18 @@ impl TopicAddArgs {
             return Ok(());
         }
 
-        for repo in filtered_repos {
-            let result = add_topics(&repo, &self.topics, &user_token);
-            match result {
-                Ok(topics) => {
-                    println!("Add topics for repo {} successfully", repo.name);
-                    println!("List of topics for {} is: {:?}", repo.name, topics);
-                }
-                Err(e) => println!(
-                    "Failed to add topics for repo {} because {:?}",
-                    repo.name, e
-                ),
-            }
-        }
-        Ok(())
+        let invocation = format!(
+            "topic add {} {} {:?}",
+            organisation,
+            self.regex
+                .as_ref()
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+            self.topics
+        );
+        topic_helper::change_topics(&invocation, &filtered_repos, self.restart, "Add", |repo| {
+            add_topics(repo, &self.topics, &user_token)
+        })
     }
 }
 
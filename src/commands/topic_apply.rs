@@ -5,6 +5,7 @@ use crate::cli::Args as CommonArgs;
 use crate::convert::try_from_one;
 use crate::filter::Filter;
 use crate::github::RemoteRepoWithTopics;
+use crate::journal::Journal;
 use crate::user::User;
 use anyhow::Result;
 use clap::Parser;
@@ -12,6 +13,9 @@ use std::process::Output;
 
 /// Apply a script to all repositories that has a topics that match a pattern
 /// Or to all repositories that has a specific topic
+///
+/// Repositories that the script was applied to are remembered, so that running the same
+/// command again after it was interrupted or failed continues with the remaining ones.
 #[derive(Debug, Parser)]
 pub struct TopicApplyArgs {
     #[arg(long, short)]
@@ -31,6 +35,9 @@ pub struct TopicApplyArgs {
     /// use https to clone repositories if needed
     #[arg(long, short)]
     pub use_https: bool,
+    #[arg(long)]
+    /// Start with the first repository instead of resuming an interrupted run
+    pub restart: bool,
 }
 
 impl TopicApplyArgs {
@@ -52,14 +59,48 @@ impl TopicApplyArgs {
 
         println!("repos {:?}", repos);
 
+        let invocation = format!(
+            "topic apply {} {:?} {} {}",
+            organisation,
+            self.topic,
+            self.regex
+                .as_ref()
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+            script_path
+        );
+        let mut journal = Journal::open("topics", &invocation)?;
+        if self.restart {
+            journal.done.clear();
+        }
+
+        let mut failed = 0;
         for repo in repos {
+            let name = repo.repo.full_name();
+            if journal.is_done(&name) {
+                println!("Skipping {}, it was applied in an earlier run", name);
+                continue;
+            }
             match apply(&repo, script_path, &user, self.use_https) {
-                Ok(_) => println!("Apply success"),
-                Err(e) => println!("Apply failed because {:?}", e),
+                Ok(_) => {
+                    println!("Apply success");
+                    journal.record(&name)?;
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!("Apply failed because {:?}", e);
+                }
             }
         }
 
-        Ok(())
+        if failed > 0 {
+            println!(
+                "\n{} repositories failed, run the same command again to retry only them",
+                failed
+            );
+            return Ok(());
+        }
+        journal.finish()
     }
 }
 
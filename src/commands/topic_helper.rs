@@ -1,6 +1,7 @@
 use crate::filter::{Filter, Filterable};
 use crate::github;
-use crate::github::{NoReposFound, RemoteRepoWithTopics, Unauthorized};
+use crate::github::{NoReposFound, Pacer, RemoteRepo, RemoteRepoWithTopics, Unauthorized};
+use crate::journal::Journal;
 use anyhow::{Context, Result};
 
 pub fn query_repositories_with_topics(org: &str, token: &str) -> Result<Vec<RemoteRepoWithTopics>> {
@@ -22,6 +23,66 @@ pub fn query_repositories_with_topics(org: &str, token: &str) -> Result<Vec<Remo
     Ok(repos)
 }
 
+/// Change the topics of repositories one after another, paced to keep below the rate limits
+///
+/// Finished repositories are recorded in a journal of the invocation, so that running the same
+/// command again after it was interrupted or some repositories failed continues with the
+/// remaining ones. `restart` forgets what was done before.
+pub fn change_topics(
+    invocation: &str,
+    repos: &[RemoteRepo],
+    restart: bool,
+    verb: &str,
+    change: impl Fn(&RemoteRepo) -> Result<Vec<String>>,
+) -> Result<()> {
+    let mut journal = Journal::open("topics", invocation)?;
+    if restart {
+        journal.done.clear();
+    }
+
+    let todo: Vec<_> = repos
+        .iter()
+        .filter(|r| !journal.is_done(&r.full_name()))
+        .collect();
+    if todo.len() < repos.len() {
+        println!(
+            "Resuming an earlier run, {} of {} repositories are done already",
+            repos.len() - todo.len(),
+            repos.len()
+        );
+    }
+
+    let mut pacer = Pacer::new();
+    let mut failed = 0;
+    for repo in todo {
+        match pacer.run(|| change(repo)) {
+            Ok(topics) => {
+                println!("{} topics for repo {} successfully", verb, repo.name);
+                println!("List of topics for {} is: {:?}", repo.name, topics);
+                journal.record(&repo.full_name())?;
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "Failed to {} topics for repo {} because {:?}",
+                    verb.to_lowercase(),
+                    repo.name,
+                    e
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        println!(
+            "\n{} repositories failed, run the same command again to retry only them",
+            failed
+        );
+        return Ok(());
+    }
+    journal.finish()
+}
+
 pub fn filter_repos(
     repos: &[RemoteRepoWithTopics],
     topic: Option<&String>,
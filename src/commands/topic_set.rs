@@ -1,4 +1,5 @@
 use super::common;
+use super::topic_helper;
 use crate::cli::Args as CommonArgs;
 use crate::filter::Filter;
 use crate::github;
@@ -19,6 +20,9 @@ pub struct TopicSetArgs {
     #[arg(long, short)]
     /// All topics will be set
     pub topics: Vec<String>,
+    #[arg(long)]
+    /// Start with the first repository instead of resuming an interrupted run
+    pub restart: bool,
 }
 
 impl TopicSetArgs {
@@ -37,19 +41,17 @@ impl TopicSetArgs {
             return Ok(());
         }
 
-        for repo in filtered_repos {
-            let result = github::set_topics(&repo, &self.topics, &user_token);
-            match result {
-                Ok(topics) => {
-                    println!("Set topics for repo {} successfully", repo.name);
-                    println!("List of topics for {} is: {:?}", repo.name, topics);
-                }
-                Err(e) => println!(
-                    "Failed to set topics for repo {} because {:?}",
-                    repo.name, e
-                ),
-            }
-        }
-        Ok(())
+        let invocation = format!(
+            "topic set {} {} {:?}",
+            organisation,
+            self.regex
+                .as_ref()
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+            self.topics
+        );
+        topic_helper::change_topics(&invocation, &filtered_repos, self.restart, "Set", |repo| {
+            github::set_topics(repo, &self.topics, &user_token)
+        })
     }
 }
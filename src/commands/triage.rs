@@ -0,0 +1,31 @@
+use super::triage_issues::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Triage the issues of many repositories
+pub struct TriageArgs {
+    #[command(subcommand)]
+    command: TriageCommand,
+}
+
+impl TriageArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum TriageCommand {
+    #[command(name = "issues")]
+    Issues(TriageIssuesArgs),
+}
+
+impl TriageCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Issues(args) => args.run(common_args),
+        }
+    }
+}
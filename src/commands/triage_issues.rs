@@ -0,0 +1,438 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, OpenIssue, Pacer, RemoteRepo};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Find the stale issues of all repositories that match a regex, mark them and close them
+///
+/// An issue is stale when it had no activity for a number of months. Stale issues can get a
+/// label and a comment, and are closed as not planned when they stay without activity for a
+/// grace period after that. Without --label, --comment or --close-after the stale issues are
+/// only listed.
+pub struct TriageIssuesArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(
+        long,
+        value_name = "MONTHS",
+        num_args = 0..=1,
+        default_missing_value = "6",
+        required = true
+    )]
+    /// Find the issues without activity for this many months
+    pub stale: Option<i64>,
+    #[arg(long)]
+    /// Add the stale label to the stale issues
+    pub label: bool,
+    #[arg(long, default_value = "stale")]
+    /// Name of the label that marks stale issues
+    pub stale_label: String,
+    #[arg(long, value_name = "FILE")]
+    /// Comment on the stale issues with the content of this markdown file
+    ///
+    /// {AUTHOR} is replaced with the login of the author of the issue, and {DAYS} with the
+    /// number of days without activity.
+    pub comment: Option<PathBuf>,
+    #[arg(long, value_name = "DAYS")]
+    /// Close stale issues that stay without activity for this many days after being marked
+    pub close_after: Option<i64>,
+    #[arg(long, value_delimiter = ',', default_value = "pinned,security")]
+    /// Issues with any of these labels are never stale
+    pub exempt: Vec<String>,
+    #[arg(long)]
+    /// Only show the stale issues and what would be done with them
+    pub dry_run: bool,
+}
+
+impl TriageIssuesArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let comment = match &self.comment {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Cannot read comment file {:?}", path))?,
+            ),
+            None => None,
+        };
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let results: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| (repo, github::get_open_issues(repo, &[], None, &user_token)))
+            .collect();
+
+        let mut stale = vec![];
+        let mut reports = vec![];
+        for (repo, result) in results {
+            match result {
+                Ok(issues) => stale.extend(issues.into_iter().filter_map(|issue| {
+                    let action = self.action(&issue, now);
+                    (action != Action::Keep).then(|| (repo.clone(), issue, action))
+                })),
+                Err(e) => reports.push(ErrorReport::from_message(
+                    &repo.full_name(),
+                    &format!("{:?}", e),
+                )),
+            }
+        }
+
+        if !reports.is_empty() {
+            let msg = format!("There {} errors when looking up issues:", reports.len());
+            println!("{}\n", msg.red());
+            errors::print_error_report(&reports);
+            println!();
+        }
+
+        if stale.is_empty() {
+            println!("There is no stale issues");
+            return Ok(());
+        }
+
+        let changes = stale.iter().filter(|(_, _, a)| self.changes(*a)).count();
+        if self.dry_run || changes == 0 {
+            if let Some(OutputFormat::Json) = common_args.format {
+                let stale: Vec<_> = stale
+                    .iter()
+                    .map(|(repo, issue, action)| Candidate::new(repo, issue, *action, now))
+                    .collect();
+                println!("{}", json!(stale));
+            } else {
+                to_candidate_table(&stale, now).printstd();
+                println!("\n{} stale issues", stale.len());
+            }
+            return Ok(());
+        }
+
+        if common_args.format != Some(OutputFormat::Json) {
+            to_candidate_table(&stale, now).printstd();
+        }
+        if !confirm(changes)? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        progress::begin(changes);
+        let mut pacer = Pacer::new();
+        let statuses: Vec<_> = stale
+            .iter()
+            .filter(|(_, _, action)| self.changes(*action))
+            .map(|(repo, issue, action)| {
+                let name = format!("{}#{}", repo.full_name(), issue.number);
+                let body = comment.as_deref().map(|c| fill_comment(c, issue, now));
+                progress::track(&name, || {
+                    let result = self.apply(
+                        repo,
+                        issue,
+                        *action,
+                        body.as_deref(),
+                        &mut pacer,
+                        &user_token,
+                    );
+                    Status {
+                        issue: name.clone(),
+                        action: *action,
+                        result: result.map_err(|e| format!("{:?}", e)),
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        println!();
+        summarize(&statuses);
+
+        Ok(())
+    }
+
+    /// What to do with an issue
+    fn action(&self, issue: &OpenIssue, now: DateTime<Utc>) -> Action {
+        let labels: Vec<_> = issue.labels.iter().map(|l| l.name.as_str()).collect();
+        if labels.iter().any(|l| self.exempt.iter().any(|e| e == l)) {
+            return Action::Keep;
+        }
+
+        let idle = (now - issue.updated_at).num_days();
+        let stale_days = self.stale.unwrap_or(6) * 30;
+        let marked = labels.contains(&self.stale_label.as_str());
+        if marked {
+            match self.close_after {
+                Some(grace) if idle >= grace => Action::Close,
+                _ => Action::Marked,
+            }
+        } else if idle >= stale_days {
+            Action::Mark
+        } else {
+            Action::Keep
+        }
+    }
+
+    /// Whether the action changes the issue with the given options
+    fn changes(&self, action: Action) -> bool {
+        match action {
+            Action::Mark => self.label || self.comment.is_some(),
+            Action::Close => true,
+            Action::Marked | Action::Keep => false,
+        }
+    }
+
+    fn apply(
+        &self,
+        repo: &RemoteRepo,
+        issue: &OpenIssue,
+        action: Action,
+        comment: Option<&str>,
+        pacer: &mut Pacer,
+        token: &str,
+    ) -> Result<()> {
+        match action {
+            Action::Mark => {
+                if self.label {
+                    let labels = [self.stale_label.clone()];
+                    pacer.run(|| github::add_issue_labels(repo, issue.number, &labels, token))?;
+                }
+                if let Some(body) = comment {
+                    pacer.run(|| github::comment_on_issue(repo, issue.number, body, token))?;
+                }
+                Ok(())
+            }
+            Action::Close => pacer.run(|| github::close_issue(repo, issue.number, token)),
+            Action::Marked | Action::Keep => Ok(()),
+        }
+    }
+}
+
+fn fill_comment(comment: &str, issue: &OpenIssue, now: DateTime<Utc>) -> String {
+    comment
+        .replace("{AUTHOR}", &issue.user.login)
+        .replace("{DAYS}", &(now - issue.updated_at).num_days().to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Keep,
+    /// Stale and not marked yet
+    Mark,
+    /// Marked and waiting for the grace period to pass
+    Marked,
+    Close,
+}
+
+impl Action {
+    fn to_cell(self) -> Cell {
+        match self {
+            Action::Keep => cell!(""),
+            Action::Mark => cell!(Fy -> "Stale"),
+            Action::Marked => cell!("Marked"),
+            Action::Close => cell!(Fr -> "Close"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Candidate {
+    repo: String,
+    number: u64,
+    title: String,
+    idle_days: i64,
+    action: Action,
+    url: String,
+}
+
+impl Candidate {
+    fn new(repo: &RemoteRepo, issue: &OpenIssue, action: Action, now: DateTime<Utc>) -> Candidate {
+        Candidate {
+            repo: repo.full_name(),
+            number: issue.number,
+            title: issue.title.clone(),
+            idle_days: (now - issue.updated_at).num_days(),
+            action,
+            url: issue.html_url.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    issue: String,
+    action: Action,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let result = match &self.result {
+            Ok(()) if self.action == Action::Close => cell!(Fgr -> "Closed"),
+            Ok(()) => cell!(Fgr -> "Marked"),
+            Err(e) => ErrorCategory::from_message(e).failed_cell(),
+        };
+        Row::new(vec![cell!(b -> &self.issue), result])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.issue, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn to_candidate_table(stale: &[(RemoteRepo, OpenIssue, Action)], now: DateTime<Utc>) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "#", "Title", "Idle", "Action"]);
+    for (repo, issue, action) in stale {
+        table.add_row(Row::new(vec![
+            cell!(b -> repo.full_name()),
+            cell!(r -> issue.number),
+            cell!(issue.title),
+            cell!(r -> format!("{}d", (now - issue.updated_at).num_days())),
+            action.to_cell(),
+        ]));
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Issue", "Result"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when triaging issues:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to mark or close {} issue(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{IssueLabel, PullRequestUser};
+    use chrono::Duration;
+
+    fn issue(idle_days: i64, labels: &[&str], now: DateTime<Utc>) -> OpenIssue {
+        OpenIssue {
+            number: 1,
+            node_id: "I_kwDOAbc".to_string(),
+            title: "Analyser crashes".to_string(),
+            html_url: "https://github.com/giellalt/lang-sme/issues/1".to_string(),
+            user: PullRequestUser {
+                login: "snomos".to_string(),
+            },
+            assignees: vec![],
+            labels: labels
+                .iter()
+                .map(|l| IssueLabel {
+                    name: l.to_string(),
+                })
+                .collect(),
+            created_at: now - Duration::days(1000),
+            updated_at: now - Duration::days(idle_days),
+            pull_request: None,
+        }
+    }
+
+    #[test]
+    fn test_action() {
+        let now = Utc::now();
+        let args = TriageIssuesArgs::parse_from(["issues", "--stale", "--close-after", "14"]);
+        assert_eq!(args.action(&issue(10, &[], now), now), Action::Keep);
+        assert_eq!(args.action(&issue(200, &[], now), now), Action::Mark);
+        assert_eq!(
+            args.action(&issue(200, &["pinned"], now), now),
+            Action::Keep
+        );
+        assert_eq!(args.action(&issue(3, &["stale"], now), now), Action::Marked);
+        assert_eq!(args.action(&issue(20, &["stale"], now), now), Action::Close);
+
+        let args = TriageIssuesArgs::parse_from(["issues", "--stale", "12"]);
+        assert_eq!(args.action(&issue(200, &[], now), now), Action::Keep);
+        assert_eq!(
+            args.action(&issue(400, &["stale"], now), now),
+            Action::Marked
+        );
+        assert!(!args.changes(Action::Mark));
+    }
+
+    #[test]
+    fn test_fill_comment() {
+        let now = Utc::now();
+        assert_eq!(
+            fill_comment(
+                "@{AUTHOR}, no activity for {DAYS} days",
+                &issue(200, &[], now),
+                now
+            ),
+            "@snomos, no activity for 200 days"
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use super::checkout::checkout_branch;
+use super::commit::{commit_paths, CommitResult};
+use super::common;
+use super::push::push_if_ahead;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::git::{self, GitCredential, GitStatus};
+use crate::path::{dir_name, owner_name};
+use crate::user::User;
+use anyhow::{Context, Result};
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use rayon::prelude::*;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+/// Interactive dashboard of all local repositories that match a regex
+///
+/// Shows the branch and status of every repository. Select repositories with space and pull,
+/// push, commit or checkout them from the keyboard, without selection the action works on the
+/// repository under the cursor.
+pub struct TuiArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl TuiArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut app = App::new(sub_dirs, &organisation, user);
+        let mut terminal = ratatui::try_init().context("Cannot start the terminal dashboard")?;
+        let result = app.run(&mut terminal);
+        ratatui::restore();
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Pull,
+    Push,
+    Commit(String),
+    Checkout(String),
+}
+
+impl Action {
+    fn apply(&self, dir: &PathBuf, user: &User) -> Result<String> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        match self {
+            Action::Pull => {
+                if git::status(&git_repo, false)?.is_dirty() {
+                    return Ok("Skipped, there are local changes".to_string());
+                }
+                let cred = GitCredential::from(user);
+                let status = git::pull(&git_repo, "origin", Some(cred), false)?;
+                Ok(format!("Pulled: {:?}", status))
+            }
+            Action::Push => {
+                let branch = git::head_shorthand(&git_repo)?;
+                if push_if_ahead(&git_repo, &branch, user, "origin")? {
+                    Ok(format!("Pushed {}", branch))
+                } else {
+                    Ok("Nothing to push".to_string())
+                }
+            }
+            Action::Commit(msg) => Ok(match commit_paths(&git_repo, msg, &[])? {
+                CommitResult::Conflict => "There are conflicts".to_string(),
+                CommitResult::NoChanges => "There is no changes".to_string(),
+                CommitResult::Success => "Committed".to_string(),
+            }),
+            Action::Checkout(branch) => {
+                checkout_branch(&git_repo, branch, user, "origin", true)?;
+                Ok(format!("Checked out {}", branch))
+            }
+        }
+    }
+}
+
+/// What the background work reports back to the dashboard
+enum Update {
+    Status(usize, Result<Summary, String>),
+    Done(usize, Result<String, String>),
+}
+
+struct Summary {
+    branch: String,
+    status: GitStatus,
+}
+
+fn summary(dir: &PathBuf) -> Result<Summary> {
+    let git_repo = git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+    Ok(Summary {
+        branch: git::head_shorthand(&git_repo).unwrap_or_else(|_| "(detached)".to_string()),
+        status: git::status(&git_repo, false)?,
+    })
+}
+
+struct Repo {
+    name: String,
+    dir: PathBuf,
+    summary: Option<Result<Summary, String>>,
+    selected: bool,
+    busy: bool,
+    message: Option<Result<String, String>>,
+}
+
+impl Repo {
+    fn to_row(&self) -> Row<'static> {
+        let mark = if self.selected { "*" } else { " " };
+        let (branch, changes, ahead_behind) = match &self.summary {
+            None => (Cell::from("..."), Cell::from(""), Cell::from("")),
+            Some(Ok(s)) => (
+                Cell::from(s.branch.clone()),
+                changes_cell(&s.status),
+                Cell::from(ahead_behind(&s.status)),
+            ),
+            Some(Err(e)) => (
+                Cell::from(e.lines().next().unwrap_or_default().to_string())
+                    .style(Style::default().fg(Color::Red)),
+                Cell::from(""),
+                Cell::from(""),
+            ),
+        };
+        let message = match (&self.message, self.busy) {
+            (_, true) => Cell::from("working..."),
+            (Some(Ok(m)), _) => Cell::from(m.clone()).style(Style::default().fg(Color::Green)),
+            (Some(Err(e)), _) => Cell::from(e.lines().next().unwrap_or_default().to_string())
+                .style(Style::default().fg(Color::Red)),
+            (None, _) => Cell::from(""),
+        };
+        Row::new(vec![
+            Cell::from(mark),
+            Cell::from(self.name.clone()),
+            branch,
+            changes,
+            ahead_behind,
+            message,
+        ])
+    }
+}
+
+fn changes_cell(status: &GitStatus) -> Cell<'static> {
+    if status.is_empty() {
+        return Cell::from("clean").style(Style::default().fg(Color::Green));
+    }
+    let counts = [
+        (
+            "M",
+            status.modified.len() + status.renamed.len() + status.typechanges.len(),
+        ),
+        ("A", status.added.len()),
+        ("D", status.deleted.len()),
+        ("?", status.new.len()),
+        ("C", status.conflicted.len()),
+    ];
+    let text: Vec<_> = counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(kind, count)| format!("{}{}", count, kind))
+        .collect();
+    let color = if status.conflicted.is_empty() {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+    Cell::from(text.join(" ")).style(Style::default().fg(color))
+}
+
+fn ahead_behind(status: &GitStatus) -> String {
+    match (status.is_ahead, status.is_behind) {
+        (0, 0) => "-".to_string(),
+        (ahead, 0) => format!("↑{}", ahead),
+        (0, behind) => format!("↓{}", behind),
+        (ahead, behind) => format!("↑{} ↓{}", ahead, behind),
+    }
+}
+
+enum Prompt {
+    Commit,
+    Checkout,
+}
+
+struct App {
+    repos: Vec<Repo>,
+    user: User,
+    table: TableState,
+    prompt: Option<(Prompt, String)>,
+    sender: Sender<Update>,
+    receiver: Receiver<Update>,
+}
+
+impl App {
+    fn new(dirs: Vec<PathBuf>, organisation: &str, user: User) -> App {
+        let repos = dirs
+            .into_iter()
+            .map(|dir| {
+                let name = dir_name(&dir).unwrap_or_else(|_| format!("{:?}", dir));
+                let owner = owner_name(&dir).unwrap_or_else(|_| organisation.to_string());
+                Repo {
+                    name: format!("{}/{}", owner, name),
+                    dir,
+                    summary: None,
+                    selected: false,
+                    busy: false,
+                    message: None,
+                }
+            })
+            .collect();
+        let (sender, receiver) = channel();
+        App {
+            repos,
+            user,
+            table: TableState::default().with_selected(0),
+            prompt: None,
+            sender,
+            receiver,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.refresh((0..self.repos.len()).collect());
+
+        loop {
+            while let Ok(update) = self.receiver.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if self.prompt.is_some() {
+                    self.on_prompt_key(key.code);
+                } else if !self.on_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Status(index, summary) => self.repos[index].summary = Some(summary),
+            Update::Done(index, result) => {
+                self.repos[index].busy = false;
+                self.repos[index].message = Some(result);
+            }
+        }
+    }
+
+    /// Handles a key, returns false to quit
+    fn on_key(&mut self, code: KeyCode) -> bool {
+        let current = self.table.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.table
+                    .select(Some((current + 1).min(self.repos.len() - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.table.select(Some(current.saturating_sub(1)));
+            }
+            KeyCode::Char(' ') => {
+                self.repos[current].selected = !self.repos[current].selected;
+                self.table
+                    .select(Some((current + 1).min(self.repos.len() - 1)));
+            }
+            KeyCode::Char('a') => {
+                let select = !self.repos.iter().all(|r| r.selected);
+                for repo in &mut self.repos {
+                    repo.selected = select;
+                }
+            }
+            KeyCode::Char('r') => self.refresh(self.targets()),
+            KeyCode::Char('p') => self.start(Action::Pull),
+            KeyCode::Char('P') => self.start(Action::Push),
+            KeyCode::Char('c') => self.prompt = Some((Prompt::Commit, String::new())),
+            KeyCode::Char('o') => self.prompt = Some((Prompt::Checkout, String::new())),
+            _ => {}
+        }
+        true
+    }
+
+    fn on_prompt_key(&mut self, code: KeyCode) {
+        let (prompt, input) = match &mut self.prompt {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter if !input.trim().is_empty() => {
+                let input = input.trim().to_string();
+                let action = match prompt {
+                    Prompt::Commit => Action::Commit(input),
+                    Prompt::Checkout => Action::Checkout(input),
+                };
+                self.prompt = None;
+                self.start(action);
+            }
+            _ => {}
+        }
+    }
+
+    /// The selected repositories, or the one under the cursor when none is selected
+    fn targets(&self) -> Vec<usize> {
+        let selected: Vec<_> = (0..self.repos.len())
+            .filter(|&i| self.repos[i].selected)
+            .collect();
+        if selected.is_empty() {
+            self.table.selected().into_iter().collect()
+        } else {
+            selected
+        }
+    }
+
+    fn refresh(&mut self, targets: Vec<usize>) {
+        let dirs: Vec<_> = targets
+            .into_iter()
+            .map(|i| (i, self.repos[i].dir.clone()))
+            .collect();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            dirs.par_iter().for_each_with(sender, |sender, (i, dir)| {
+                let _ = sender.send(Update::Status(*i, summary(dir).map_err(|e| e.to_string())));
+            });
+        });
+    }
+
+    fn start(&mut self, action: Action) {
+        let targets: Vec<_> = self
+            .targets()
+            .into_iter()
+            .filter(|&i| !self.repos[i].busy)
+            .collect();
+        let mut work = vec![];
+        for &i in &targets {
+            self.repos[i].busy = true;
+            work.push((i, self.repos[i].dir.clone()));
+        }
+
+        let sender = self.sender.clone();
+        let user = self.user.clone();
+        std::thread::spawn(move || {
+            work.par_iter().for_each_with(sender, |sender, (i, dir)| {
+                let result = action.apply(dir, &user).map_err(|e| format!("{:?}", e));
+                let _ = sender.send(Update::Done(*i, result));
+                let _ = sender.send(Update::Status(*i, summary(dir).map_err(|e| e.to_string())));
+            });
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list, footer] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+
+        let header = Row::new(vec!["", "Repo", "Branch", "Changes", "Remote", "Result"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows: Vec<_> = self.repos.iter().map(|r| r.to_row()).collect();
+        let selected = self.repos.iter().filter(|r| r.selected).count();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Percentage(30),
+                Constraint::Percentage(15),
+                Constraint::Length(16),
+                Constraint::Length(9),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " gut: {} repositories, {} selected ",
+            self.repos.len(),
+            selected
+        )));
+        frame.render_stateful_widget(table, list, &mut self.table);
+
+        let help = match &self.prompt {
+            Some((Prompt::Commit, input)) => format!("Commit message: {}▏", input),
+            Some((Prompt::Checkout, input)) => format!("Checkout branch: {}▏", input),
+            None => "↑↓ move  space select  a select all  p pull  P push  c commit  \
+                     o checkout  r refresh  q quit"
+                .to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(help).block(Block::default().borders(Borders::ALL)),
+            footer,
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::lock::{protection_cell, Lock, Locks};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, RemoteRepo};
+use anyhow::Result;
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Unfreeze the default branch of repositories that were locked with the lock command
+///
+/// The protection that the branch had before it was locked is put back, or removed when the
+/// branch was not protected.
+pub struct UnlockArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+impl UnlockArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        let mut locks = Locks::load()?;
+        let to_unlock: Vec<_> = filtered_repos
+            .into_iter()
+            .filter_map(|r| {
+                let lock = locks.repos.get(&r.full_name())?.clone();
+                Some((r, lock))
+            })
+            .collect();
+
+        if to_unlock.is_empty() {
+            println!(
+                "There is no locked repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        println!("The following repos will be unlocked:");
+        for (repo, _) in &to_unlock {
+            println!("{}", repo.full_name());
+        }
+
+        if !confirm(to_unlock.len())? {
+            println!("Command is aborted. Nothing change!");
+            return Ok(());
+        }
+
+        let statuses: Vec<_> = to_unlock
+            .par_iter()
+            .map(|(repo, lock)| unlock(repo, lock, &user_token))
+            .collect();
+
+        for status in statuses.iter().filter(|s| s.result.is_ok()) {
+            locks.repos.remove(&status.repo);
+        }
+        locks.save()?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn unlock(repo: &RemoteRepo, lock: &Lock, token: &str) -> Status {
+    let result = match &lock.protection {
+        Some(protection) => {
+            github::restore_branch_protection(repo, &lock.branch, protection, token)
+        }
+        None => github::set_unprotected_branch(repo, &lock.branch, token),
+    };
+
+    Status {
+        repo: repo.full_name(),
+        lock: lock.clone(),
+        result: result.map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    lock: Lock,
+    result: Result<(), String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        Row::new(vec![
+            cell!(b -> &self.repo),
+            cell!(self.lock.branch),
+            protection_cell(&self.lock.protection),
+            match &self.result {
+                Ok(_) => cell!(Fg -> "Unlocked"),
+                Err(e) => ErrorCategory::from_message(e).failed_cell(),
+            },
+        ])
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Branch", "Restored", "Status"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!(
+            "There {} errors when unlocking, these repos are still locked:",
+            errors.len()
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to unlock {} repo(s)?\nEnter {} to continue",
+            count, key
+        ),
+        key,
+    )
+}
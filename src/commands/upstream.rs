@@ -0,0 +1,31 @@
+use super::upstream_merge::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Keep forks in sync with the repositories they were forked from
+pub struct UpstreamArgs {
+    #[command(subcommand)]
+    command: UpstreamCommand,
+}
+
+impl UpstreamArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum UpstreamCommand {
+    #[command(name = "merge", aliases = &["sync"])]
+    Merge(UpstreamMergeArgs),
+}
+
+impl UpstreamCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Merge(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::{GitCredential, MergeStatus, RebaseStatus};
+use crate::path;
+use crate::repo_state::{RepoState, PENDING_UPSTREAM_MERGE};
+use crate::user::User;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::Repository;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Merge the default branch of the upstream remote into forks and push them to origin
+///
+/// Repositories without an upstream remote are skipped, as well as repositories with local
+/// changes or another branch checked out. When there is a conflict, nothing is changed and the
+/// repository has to be synced by hand.
+pub struct UpstreamMergeArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, default_value = "upstream")]
+    /// Name of the remote of the original repository
+    pub remote: String,
+    #[arg(long)]
+    /// Rebase the patches of the fork onto upstream instead of merging, and force-push
+    pub rebase: bool,
+    #[arg(long)]
+    /// Only update the local default branch, without pushing to origin
+    pub no_push: bool,
+}
+
+impl UpstreamMergeArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || sync(d, &user, self)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, &self.remote);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum Outcome {
+    NoUpstream,
+    Dirty,
+    /// Another branch than the default branch is checked out
+    OtherBranch(String),
+    UpToDate,
+    FastForward,
+    Merged,
+    Rebased,
+    /// Nothing was changed because upstream conflicts with the fork
+    Conflict,
+}
+
+impl Outcome {
+    fn changed(&self) -> bool {
+        matches!(
+            self,
+            Outcome::FastForward | Outcome::Merged | Outcome::Rebased
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Sync {
+    branch: Option<String>,
+    outcome: Outcome,
+    pushed: bool,
+}
+
+fn sync(dir: &PathBuf, user: &User, args: &UpstreamMergeArgs) -> Status {
+    let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+    let sync = || -> Result<Sync> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let skip = |outcome| Sync {
+            branch: None,
+            outcome,
+            pushed: false,
+        };
+
+        if git_repo.find_remote(&args.remote).is_err() {
+            return Ok(skip(Outcome::NoUpstream));
+        }
+        if git::status(&git_repo, false)?.is_dirty() {
+            return Ok(skip(Outcome::Dirty));
+        }
+        let current = git::head_shorthand(&git_repo)?;
+        let branch = git::remote_head(&git_repo, "origin").unwrap_or_else(|| current.clone());
+        if current != branch {
+            return Ok(skip(Outcome::OtherBranch(current)));
+        }
+
+        let cred = GitCredential::from(user);
+        git::fetch_quiet(&git_repo, &args.remote, Some(cred.clone()))
+            .with_context(|| format!("Cannot fetch {}", args.remote))?;
+
+        let outcome = update(&git_repo, &args.remote, &branch, args.rebase)?;
+        RepoState::record(dir, |s| match outcome {
+            Outcome::Conflict => s.add_pending(PENDING_UPSTREAM_MERGE),
+            _ => s.remove_pending(PENDING_UPSTREAM_MERGE),
+        });
+        let pushed = outcome.changed() && !args.no_push;
+        if pushed {
+            if args.rebase {
+                git::push::force_push_branch(&git_repo, &branch, "origin", Some(cred))?;
+            } else {
+                git::push::push_branch(&git_repo, &branch, "origin", Some(cred))?;
+            }
+        }
+
+        Ok(Sync {
+            branch: Some(branch),
+            outcome,
+            pushed,
+        })
+    };
+
+    Status {
+        repo,
+        result: sync().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+/// Merge or rebase the branch onto the same branch of upstream, or the branch that upstream's
+/// HEAD points to when upstream names it differently
+fn update(git_repo: &Repository, remote: &str, branch: &str, rebase: bool) -> Result<Outcome> {
+    let upstream_branch = if git_repo
+        .find_reference(&format!("refs/remotes/{}/{}", remote, branch))
+        .is_ok()
+    {
+        branch.to_string()
+    } else {
+        git::remote_head(git_repo, remote)
+            .ok_or_else(|| anyhow!("{} has no branch {}", remote, branch))?
+    };
+    let reference =
+        git_repo.find_reference(&format!("refs/remotes/{}/{}", remote, upstream_branch))?;
+    let upstream_commit = git_repo.reference_to_annotated_commit(&reference)?;
+
+    let outcome = if rebase {
+        match git::rebase_commit(git_repo, &upstream_commit, true)? {
+            RebaseStatus::NormalRebase => Outcome::Rebased,
+            RebaseStatus::Nothing => Outcome::UpToDate,
+            RebaseStatus::RebaseWithConflict | RebaseStatus::SkipByConflict => Outcome::Conflict,
+        }
+    } else {
+        let msg = format!(
+            "Merge branch '{}' of {} into {}",
+            upstream_branch, remote, branch
+        );
+        match git::merge_commit(git_repo, &upstream_commit, &msg, true)? {
+            MergeStatus::FastForward => Outcome::FastForward,
+            MergeStatus::NormalMerge => Outcome::Merged,
+            MergeStatus::Nothing => Outcome::UpToDate,
+            MergeStatus::MergeWithConflict | MergeStatus::SkipByConflict => Outcome::Conflict,
+        }
+    };
+    Ok(outcome)
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Sync, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(sync) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(sync.branch.as_deref().unwrap_or("")),
+                outcome_cell(&sync.outcome),
+                if sync.pushed {
+                    cell!(Fg -> "Pushed")
+                } else {
+                    cell!("-")
+                },
+            ]),
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!("-"),
+            ]),
+        }
+    }
+
+    fn is_conflict(&self) -> bool {
+        matches!(&self.result, Ok(s) if matches!(s.outcome, Outcome::Conflict))
+    }
+
+    fn is_changed(&self) -> bool {
+        matches!(&self.result, Ok(s) if s.outcome.changed())
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn outcome_cell(outcome: &Outcome) -> Cell {
+    match outcome {
+        Outcome::NoUpstream => cell!("no upstream remote"),
+        Outcome::Dirty => cell!(Fy -> "skipped, local changes"),
+        Outcome::OtherBranch(branch) => cell!(Fy -> format!("skipped, on {}", branch)),
+        Outcome::UpToDate => cell!("up to date"),
+        Outcome::FastForward => cell!(Fg -> "fast-forwarded"),
+        Outcome::Merged => cell!(Fg -> "merged"),
+        Outcome::Rebased => cell!(Fg -> "rebased"),
+        Outcome::Conflict => cell!(Fr -> "conflict"),
+    }
+}
+
+fn summarize(statuses: &[Status], remote: &str) {
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Branch", remote, "Origin"]);
+    table.printstd();
+
+    let changed = statuses.iter().filter(|s| s.is_changed()).count();
+    if changed > 0 {
+        let msg = format!("\nSynced {} repos with {}!", changed, remote);
+        println!("{}", msg.green());
+    }
+
+    let conflicts: Vec<_> = statuses.iter().filter(|s| s.is_conflict()).collect();
+    if !conflicts.is_empty() {
+        let names: Vec<_> = conflicts.iter().map(|s| s.repo.as_str()).collect();
+        let msg = format!(
+            "\nThere are {} repos with conflicts that need to be synced by hand: {}",
+            conflicts.len(),
+            names.join(", ")
+        );
+        println!("{}", msg.yellow());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!(
+            "There {} errors when syncing with {}:",
+            errors.len(),
+            remote
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
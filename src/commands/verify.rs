@@ -0,0 +1,39 @@
+use super::verify_clone_integrity::*;
+use super::verify_default_branch::*;
+use super::verify_tags_signed::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Verify local repositories against their remotes
+pub struct VerifyArgs {
+    #[command(subcommand)]
+    command: VerifyCommand,
+}
+
+impl VerifyArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum VerifyCommand {
+    #[command(name = "clone-integrity")]
+    CloneIntegrity(CloneIntegrityArgs),
+    #[command(name = "default-branch")]
+    DefaultBranch(DefaultBranchConsistencyArgs),
+    #[command(name = "tags-signed")]
+    TagsSigned(TagsSignedArgs),
+}
+
+impl VerifyCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::CloneIntegrity(args) => args.run(common_args),
+            Self::DefaultBranch(args) => args.run(common_args),
+            Self::TagsSigned(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::github;
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{Oid, Repository};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Compare the current branch and the default branch of local repositories with their remotes
+///
+/// This uses the GitHub api and does not fetch anything, so it is fast enough to run as a
+/// regular consistency check. Repositories that are behind, ahead or whose history has
+/// diverged from the remote (for example after a force-push upstream) are flagged.
+pub struct CloneIntegrityArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Option to omit repositories that are up to date
+    pub quiet: bool,
+}
+
+impl CloneIntegrityArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|dir| progress::track_dir(dir, || verify(dir, &organisation, &user_token)))
+            .filter(|s| !(self.quiet && s.is_up_to_date()))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+fn verify(dir: &PathBuf, organisation: &str, token: &str) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    // The directory can belong to another owner with an owner:pattern regex
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+    let owner = owner.as_str();
+    let check = || -> Result<Vec<BranchCheck>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let mut branches = vec![];
+        if git_repo.head()?.is_branch() {
+            branches.push(git::head_shorthand(&git_repo)?);
+        }
+
+        let default_branch = github::get_default_branch(owner, &repo, token)?;
+        if !branches.contains(&default_branch)
+            && git_repo
+                .find_reference(&format!("refs/heads/{}", default_branch))
+                .is_ok()
+        {
+            branches.push(default_branch);
+        }
+
+        branches
+            .iter()
+            .map(|b| check_branch(&git_repo, owner, &repo, b, token))
+            .collect()
+    };
+
+    Status {
+        repo: repo.clone(),
+        result: check().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+fn check_branch(
+    git_repo: &Repository,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    token: &str,
+) -> Result<BranchCheck> {
+    let local_oid = git_repo
+        .refname_to_id(&format!("refs/heads/{}", branch))
+        .with_context(|| format!("Cannot resolve local branch {}", branch))?;
+    let local = local_oid.to_string();
+
+    let remote = github::get_branch_sha(owner, repo, branch, token)?;
+
+    let state = match &remote {
+        None => Integrity::MissingOnRemote,
+        Some(sha) if *sha == local => Integrity::UpToDate,
+        Some(sha) => {
+            let remote_oid = Oid::from_str(sha)?;
+            if git_repo.find_commit(remote_oid).is_ok() {
+                // We already have the remote tip, so we can find out locally
+                match git_repo.graph_ahead_behind(local_oid, remote_oid)? {
+                    (ahead, 0) => Integrity::Ahead(ahead),
+                    (0, behind) => Integrity::Behind(behind),
+                    _ => Integrity::Diverged,
+                }
+            } else {
+                match github::compare_commits(owner, repo, &local, sha, token)? {
+                    Some(c) if c.status == "identical" => Integrity::UpToDate,
+                    Some(c) if c.status == "ahead" => Integrity::Behind(c.ahead_by),
+                    Some(c) if c.status == "behind" => Integrity::Ahead(c.behind_by),
+                    // Either both sides have new commits, or the local commit is unknown to
+                    // the remote while the remote has commits that we don't have
+                    _ => Integrity::Diverged,
+                }
+            }
+        }
+    };
+
+    Ok(BranchCheck {
+        branch: branch.to_string(),
+        local,
+        remote,
+        state,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct BranchCheck {
+    branch: String,
+    local: String,
+    remote: Option<String>,
+    state: Integrity,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+enum Integrity {
+    UpToDate,
+    Ahead(usize),
+    Behind(usize),
+    Diverged,
+    MissingOnRemote,
+}
+
+impl Integrity {
+    fn to_cell(&self) -> Cell {
+        match self {
+            Self::UpToDate => cell!(Fg -> "Up to date"),
+            Self::Ahead(n) => cell!(Fy -> format!("Ahead {}", n)),
+            Self::Behind(n) => cell!(Fy -> format!("Behind {}", n)),
+            Self::Diverged => cell!(Fr -> "Diverged"),
+            Self::MissingOnRemote => cell!(Fr -> "Missing on remote"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<BranchCheck>, String>,
+}
+
+impl Status {
+    fn to_rows(&self) -> Vec<Row> {
+        match &self.result {
+            Ok(checks) => checks
+                .iter()
+                .map(|c| {
+                    row![
+                        b -> &self.repo,
+                        c.branch,
+                        short(&c.local),
+                        c.remote.as_deref().map(short).unwrap_or("-"),
+                        c.state.to_cell()
+                    ]
+                })
+                .collect(),
+            Err(e) => vec![Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+            ])],
+        }
+    }
+
+    fn is_up_to_date(&self) -> bool {
+        match &self.result {
+            Ok(checks) => checks.iter().all(|c| c.state == Integrity::UpToDate),
+            Err(_) => false,
+        }
+    }
+
+    fn is_diverged(&self) -> bool {
+        match &self.result {
+            Ok(checks) => checks.iter().any(|c| c.state == Integrity::Diverged),
+            Err(_) => false,
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Branch", "Local", "Remote", "Status"]);
+    for status in statuses {
+        for row in status.to_rows() {
+            table.add_row(row);
+        }
+    }
+    table
+}
+
+fn summarize(statuses: &[Status]) {
+    let table = to_table(statuses);
+    table.printstd();
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    let inconsistent = statuses
+        .iter()
+        .filter(|s| !s.has_error() && !s.is_up_to_date())
+        .count();
+    let diverged = statuses.iter().filter(|s| s.is_diverged()).count();
+
+    if inconsistent == 0 {
+        println!(
+            "\n{}",
+            "All repositories are consistent with their remotes".green()
+        );
+    } else {
+        let msg = format!(
+            "{} repositories differ from their remotes, {} of them diverged",
+            inconsistent, diverged
+        );
+        println!("\n{}", msg.yellow());
+    }
+
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!("There {} errors when verifying:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
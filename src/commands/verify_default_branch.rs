@@ -0,0 +1,390 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::github::{self, RemoteRepo};
+use crate::path::{dir_name, owner_name};
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::{BranchType, Repository};
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Check that the default branch is named the same on GitHub and in local repositories
+///
+/// Three things are checked: the default branch on GitHub has to match the given policy, the
+/// local default branch has to have the same name and track it, and origin/HEAD has to point
+/// to it. Use --fix to rename or set the branch on GitHub and fix the local repositories.
+pub struct DefaultBranchConsistencyArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, default_value = "main")]
+    /// Name that the default branch should have
+    pub branch: String,
+    #[arg(long, short)]
+    /// Fix the repositories that do not match after confirmation
+    pub fix: bool,
+    #[arg(long, short)]
+    /// Option to omit repositories that are consistent
+    pub quiet: bool,
+}
+
+impl DefaultBranchConsistencyArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let user = common::user()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        progress::begin(sub_dirs.len());
+        let mut statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|dir| progress::track_dir(dir, || check(dir, &organisation, &user.token)))
+            .filter(|s| !(self.quiet && s.is_consistent(&self.branch)))
+            .collect();
+
+        if self.fix {
+            let to_fix: Vec<_> = statuses
+                .iter_mut()
+                .filter(|s| s.result.is_ok() && !s.is_consistent(&self.branch))
+                .collect();
+
+            if !to_fix.is_empty() {
+                println!("The following repos will be fixed:");
+                for status in &to_fix {
+                    println!("{}/{}", status.owner, status.repo);
+                }
+
+                if confirm(to_fix.len(), &self.branch)? {
+                    let cred = GitCredential::from(&user);
+                    for status in to_fix {
+                        status.fix(&self.branch, &user.token, &cred);
+                    }
+                } else {
+                    println!("Command is aborted. Nothing change!");
+                }
+            }
+        }
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses, &self.branch);
+
+        Ok(())
+    }
+}
+
+fn check(dir: &PathBuf, organisation: &str, token: &str) -> Status {
+    let repo = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    // The directory can belong to another owner with an owner:pattern regex
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let check = || -> Result<Check> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+        let github = github::get_default_branch(&owner, &repo, token)?;
+        let origin_head = git::remote_head(&git_repo, "origin");
+        let local = local_default_branch(&git_repo, &github, origin_head.as_deref());
+
+        Ok(Check {
+            github,
+            local,
+            origin_head,
+        })
+    };
+
+    Status {
+        repo: repo.clone(),
+        owner: owner.clone(),
+        dir: dir.clone(),
+        result: check().map_err(|e| format!("{:?}", e)),
+        fix: None,
+    }
+}
+
+/// The local branch that is meant to be the default branch: the one that tracks the default
+/// branch on GitHub, or else the one with its name, or else the one named after origin/HEAD
+fn local_default_branch(
+    git_repo: &Repository,
+    github: &str,
+    origin_head: Option<&str>,
+) -> Option<LocalBranch> {
+    let tracking = format!("origin/{}", github);
+    let names: Vec<String> = git_repo
+        .branches(Some(BranchType::Local))
+        .ok()?
+        .filter_map(|b| b.ok())
+        .filter_map(|(b, _)| b.name().ok().flatten().map(|n| n.to_string()))
+        .collect();
+
+    let to_local = |name: &String| LocalBranch {
+        name: name.clone(),
+        upstream: git::upstream_name(git_repo, name),
+    };
+    let branches: Vec<_> = names.iter().map(to_local).collect();
+
+    let found = branches
+        .iter()
+        .position(|b| b.upstream.as_deref() == Some(tracking.as_str()))
+        .or_else(|| branches.iter().position(|b| b.name == github))
+        .or_else(|| origin_head.and_then(|head| branches.iter().position(|b| b.name == head)))?;
+
+    branches.into_iter().nth(found)
+}
+
+#[derive(Debug, Serialize)]
+struct LocalBranch {
+    name: String,
+    upstream: Option<String>,
+}
+
+impl LocalBranch {
+    fn is_tracking(&self, branch: &str) -> bool {
+        self.name == branch && self.upstream.as_deref() == Some(&format!("origin/{}", branch))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    github: String,
+    local: Option<LocalBranch>,
+    origin_head: Option<String>,
+}
+
+impl Check {
+    fn is_github_ok(&self, policy: &str) -> bool {
+        self.github == policy
+    }
+
+    /// Repositories without any local default branch have nothing to fix locally
+    fn is_local_ok(&self) -> bool {
+        self.local
+            .as_ref()
+            .map(|l| l.is_tracking(&self.github))
+            .unwrap_or(true)
+    }
+
+    fn is_origin_head_ok(&self) -> bool {
+        self.origin_head.as_deref() == Some(self.github.as_str())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    owner: String,
+    #[serde(skip)]
+    dir: PathBuf,
+    result: Result<Check, String>,
+    fix: Option<Result<(), String>>,
+}
+
+impl Status {
+    fn is_consistent(&self, policy: &str) -> bool {
+        match &self.result {
+            Ok(c) => c.is_github_ok(policy) && c.is_local_ok() && c.is_origin_head_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn fix(&mut self, policy: &str, token: &str, cred: &GitCredential) {
+        let check = match &mut self.result {
+            Ok(check) => check,
+            Err(_) => return,
+        };
+        let remote_repo = RemoteRepo {
+            name: self.repo.clone(),
+            owner: self.owner.clone(),
+            ssh_url: String::new(),
+            https_url: String::new(),
+        };
+
+        let mut fix = || -> Result<()> {
+            if !check.is_github_ok(policy) {
+                fix_github(&remote_repo, &check.github, policy, token)?;
+                check.github = policy.to_string();
+            }
+
+            let git_repo = git::open(&self.dir)?;
+            if check.is_local_ok() && check.is_origin_head_ok() {
+                return Ok(());
+            }
+
+            git::fetch_branch(&git_repo, policy, "origin", Some(cred.clone()))
+                .with_context(|| format!("Cannot fetch branch {}", policy))?;
+
+            if let Some(local) = &check.local {
+                fix_local_branch(&git_repo, &local.name, policy)?;
+            }
+            check.local = local_default_branch(&git_repo, policy, Some(policy));
+
+            git::set_remote_head(&git_repo, "origin", policy)?;
+            check.origin_head = Some(policy.to_string());
+            Ok(())
+        };
+
+        self.fix = Some(fix().map_err(|e| format!("{:?}", e)));
+    }
+
+    fn to_row(&self, policy: &str) -> Row {
+        match &self.result {
+            Ok(check) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ok_cell(&check.github, check.is_github_ok(policy)),
+                match &check.local {
+                    Some(l) => {
+                        let upstream = l.upstream.as_deref().unwrap_or("no upstream");
+                        ok_cell(&format!("{} -> {}", l.name, upstream), check.is_local_ok())
+                    }
+                    None => cell!("-"),
+                },
+                ok_cell(
+                    check.origin_head.as_deref().unwrap_or("missing"),
+                    check.is_origin_head_ok(),
+                ),
+                self.fix_cell(),
+            ]),
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn fix_cell(&self) -> Cell {
+        match &self.fix {
+            None => cell!("-"),
+            Some(Ok(_)) => cell!(Fg -> "Fixed"),
+            Some(Err(e)) => ErrorCategory::from_message(e).failed_cell(),
+        }
+    }
+
+    fn error(&self) -> Option<&String> {
+        match (&self.result, &self.fix) {
+            (Err(e), _) | (_, Some(Err(e))) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.error().is_some()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = self.error().expect("This should have an error here");
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+/// Rename the default branch when the policy branch does not exist yet, so that open pull
+/// requests follow it. Otherwise only switch the default branch.
+fn fix_github(repo: &RemoteRepo, current: &str, policy: &str, token: &str) -> Result<()> {
+    if github::get_branch_sha(&repo.owner, &repo.name, policy, token)?.is_some() {
+        github::set_default_branch(repo, policy, token)
+    } else {
+        github::rename_branch(repo, current, policy, token)
+    }
+}
+
+fn fix_local_branch(git_repo: &Repository, name: &str, policy: &str) -> Result<()> {
+    if name != policy {
+        if git_repo.find_branch(policy, BranchType::Local).is_ok() {
+            return Err(anyhow!(
+                "Cannot rename local branch {} to {}, because {} already exists",
+                name,
+                policy,
+                policy
+            ));
+        }
+        git_repo
+            .find_branch(name, BranchType::Local)?
+            .rename(policy, false)?;
+    }
+
+    git_repo
+        .find_branch(policy, BranchType::Local)?
+        .set_upstream(Some(&format!("origin/{}", policy)))?;
+    Ok(())
+}
+
+fn ok_cell(text: &str, ok: bool) -> Cell {
+    if ok {
+        cell!(Fg -> text)
+    } else {
+        cell!(Fy -> text)
+    }
+}
+
+fn summarize(statuses: &[Status], policy: &str) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "GitHub", "Local branch", "origin/HEAD", "Fix"]);
+    for status in statuses {
+        table.add_row(status.to_row(policy));
+    }
+    table.printstd();
+
+    let inconsistent = statuses
+        .iter()
+        .filter(|s| s.result.is_ok() && !s.is_consistent(policy))
+        .count();
+    if inconsistent > 0 {
+        let msg = format!(
+            "{} repos do not use {} as default branch everywhere",
+            inconsistent, policy
+        );
+        println!("\n{}", msg.yellow());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if errors.is_empty() {
+        println!("\nThere is no error!");
+    } else {
+        let msg = format!(
+            "There {} errors when checking default branches:",
+            errors.len()
+        );
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+fn confirm(count: usize, policy: &str) -> Result<bool> {
+    let key = "YES";
+    common::confirm(
+        &format!(
+            "Are you sure you want to make {} the default branch of {} repo(s) on GitHub and locally?\nEnter {} to continue",
+            policy, count, key
+        ),
+        key,
+    )
+}
@@ -0,0 +1,284 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::git::{self, TagSignature};
+use crate::path::{dir_name, owner_name};
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+/// Check that the release tags of local repositories are annotated and signed by allowed keys
+///
+/// The allowed GPG fingerprints and SSH public keys are set per owner in the
+/// [allowed_signers] table of the config file. GPG signatures are checked with your keyring,
+/// so import the public keys first. Fetch the tags before running this.
+pub struct TagsSignedArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short, default_value = r"^v?\d")]
+    /// Regex of the tags that are releases
+    pub tags: Regex,
+    #[arg(long, short)]
+    /// Option to omit repositories whose release tags are all signed
+    pub quiet: bool,
+}
+
+impl TagsSignedArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let config = Config::load()?;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let signers = Signers::new(&config)?;
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| verify(d, &organisation, &self.tags, &signers))
+            .filter(|s| !(self.quiet && s.is_all_signed()))
+            .collect();
+        signers.remove_files();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+            return Ok(());
+        }
+
+        summarize(&statuses);
+
+        Ok(())
+    }
+}
+
+/// The allowed keys of every owner, with an allowed signers file for their SSH keys
+struct Signers {
+    keys: BTreeMap<String, Vec<String>>,
+    ssh_files: BTreeMap<String, PathBuf>,
+}
+
+impl Signers {
+    fn new(config: &Config) -> Result<Signers> {
+        let mut ssh_files = BTreeMap::new();
+        for (owner, keys) in &config.allowed_signers {
+            let lines: Vec<_> = keys
+                .iter()
+                .filter(|k| is_ssh_key(k))
+                .map(|k| format!("* {}\n", k.trim()))
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+            let path = std::env::temp_dir().join(format!(
+                "gut-allowed-signers-{}-{}",
+                owner.to_lowercase(),
+                std::process::id()
+            ));
+            std::fs::write(&path, lines.concat())
+                .with_context(|| format!("Cannot write the allowed signers file {:?}", path))?;
+            ssh_files.insert(owner.to_lowercase(), path);
+        }
+
+        let keys = config
+            .allowed_signers
+            .iter()
+            .map(|(owner, keys)| (owner.to_lowercase(), keys.clone()))
+            .collect();
+
+        Ok(Signers { keys, ssh_files })
+    }
+
+    fn keys(&self, owner: &str) -> &[String] {
+        self.keys
+            .get(&owner.to_lowercase())
+            .map(|k| k.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn ssh_file(&self, owner: &str) -> Option<&PathBuf> {
+        self.ssh_files.get(&owner.to_lowercase())
+    }
+
+    fn remove_files(&self) {
+        for path in self.ssh_files.values() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn is_ssh_key(key: &str) -> bool {
+    let key = key.trim();
+    key.starts_with("ssh-") || key.starts_with("ecdsa-") || key.starts_with("sk-")
+}
+
+fn verify(dir: &PathBuf, organisation: &str, pattern: &Regex, signers: &Signers) -> Status {
+    let name = dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+    let owner = owner_name(dir).unwrap_or_else(|_| organisation.to_string());
+
+    let verify = || -> Result<Vec<Tag>> {
+        let git_repo =
+            git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+
+        let names = git_repo.tag_names(None)?;
+        let mut tags = vec![];
+        for tag in names.iter().flatten().filter(|t| pattern.is_match(t)) {
+            let signature = git::tag_signature(
+                &git_repo,
+                tag,
+                signers.keys(&owner),
+                signers.ssh_file(&owner).map(|p| p.as_path()),
+            )?;
+            tags.push(Tag {
+                name: tag.to_string(),
+                signature,
+            });
+        }
+        Ok(tags)
+    };
+
+    Status {
+        repo: format!("{}/{}", owner, name),
+        result: verify().map_err(|e| format!("{:?}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Tag {
+    name: String,
+    signature: TagSignature,
+}
+
+impl Tag {
+    fn is_signed(&self) -> bool {
+        matches!(self.signature, TagSignature::Allowed(_))
+    }
+
+    fn describe(&self) -> String {
+        match &self.signature {
+            TagSignature::Lightweight => format!("{}: not annotated", self.name),
+            TagSignature::Unsigned => format!("{}: unsigned", self.name),
+            TagSignature::Allowed(key) => format!("{}: signed by {}", self.name, key),
+            TagSignature::UnknownSigner(key) => format!("{}: unknown signer {}", self.name, key),
+            TagSignature::Bad(e) => format!("{}: {}", self.name, e),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    result: Result<Vec<Tag>, String>,
+}
+
+impl Status {
+    fn is_all_signed(&self) -> bool {
+        match &self.result {
+            Ok(tags) => tags.iter().all(|t| t.is_signed()),
+            Err(_) => false,
+        }
+    }
+
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(tags) => {
+                let signed = tags.iter().filter(|t| t.is_signed()).count();
+                let problems: Vec<_> = tags
+                    .iter()
+                    .filter(|t| !t.is_signed())
+                    .map(|t| t.describe())
+                    .collect();
+                Row::new(vec![
+                    cell!(b -> &self.repo),
+                    cell!(r -> tags.len()),
+                    self.signed_cell(signed, tags.len()),
+                    cell!(Fr -> problems.join("\n")),
+                ])
+            }
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!(""),
+            ]),
+        }
+    }
+
+    fn signed_cell(&self, signed: usize, total: usize) -> Cell {
+        if signed == total {
+            cell!(Fgr -> signed)
+        } else {
+            cell!(Fyr -> signed)
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+fn summarize(statuses: &[Status]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Tags", "Signed", "Problems"]);
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table.printstd();
+
+    let unsigned: usize = statuses
+        .iter()
+        .filter_map(|s| s.result.as_ref().ok())
+        .map(|tags| tags.iter().filter(|t| !t.is_signed()).count())
+        .sum();
+    if unsigned == 0 {
+        println!(
+            "\n{}",
+            "Every release tag is signed by an allowed key".green()
+        );
+    } else {
+        let msg = format!("{} release tags are not signed by an allowed key", unsigned);
+        println!("\n{}", msg.red());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when checking tags:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
@@ -0,0 +1,31 @@
+use super::version_bump::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Bump the versions of repositories for coordinated releases
+pub struct VersionArgs {
+    #[command(subcommand)]
+    command: VersionCommand,
+}
+
+impl VersionArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum VersionCommand {
+    #[command(name = "bump")]
+    Bump(VersionBumpArgs),
+}
+
+impl VersionCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Bump(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::progress::{self, Tracked};
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::git;
+use crate::git::GitCredential;
+use crate::path;
+use crate::user::User;
+use crate::version_file::{self, VersionFile};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use git2::Status as FileStatus;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+/// Bump the version in the version files of repositories, then commit and tag it
+///
+/// The version is read from Cargo.toml, package.json, pyproject.toml or configure.ac, or from
+/// the files set in the [[version_files]] tables of the config file. All version files of a
+/// repository are set to the new version in one commit, which is tagged with the version.
+pub struct VersionBumpArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    /// major, minor, patch, or the new version of all repositories, e.g. 2.1.0
+    pub version: Bump,
+    #[arg(long, short, default_value = "Bump version to {version}")]
+    /// Commit message, {version} is replaced with the new version
+    pub message: String,
+    #[arg(long, default_value = "v")]
+    /// Prefix of the tag names
+    pub tag_prefix: String,
+    #[arg(long)]
+    /// Only commit, without tagging
+    pub no_tag: bool,
+    #[arg(long, conflicts_with = "no_tag")]
+    /// Sign the tags with the signing key of the git config
+    pub sign: bool,
+    #[arg(long)]
+    /// Push the branch and the tag to origin
+    pub push: bool,
+    #[arg(long)]
+    /// Show the new versions without changing anything
+    pub dry_run: bool,
+}
+
+impl VersionBumpArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let root = common::root()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let configured = Config::load()?.version_files;
+
+        let sub_dirs = common::read_dirs_for_org(&organisation, &root, self.regex.as_ref())?;
+
+        if sub_dirs.is_empty() {
+            println!(
+                "There is no local repositories in organisation {} matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        progress::begin(sub_dirs.len());
+        let statuses: Vec<_> = sub_dirs
+            .par_iter()
+            .map(|d| progress::track_dir(d, || self.bump(d, &user, &configured)))
+            .collect();
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(statuses));
+        } else {
+            summarize(&statuses, self.dry_run);
+        }
+
+        Ok(())
+    }
+
+    fn bump(&self, dir: &PathBuf, user: &User, configured: &[VersionFile]) -> Status {
+        let repo = path::dir_name(dir).unwrap_or_else(|_| format!("{:?}", dir));
+
+        let bump = || -> Result<Option<Release>> {
+            let git_repo =
+                git::open(dir).with_context(|| format!("{:?} is not a git directory.", dir))?;
+            let workdir = git_repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Cannot bump the version of a bare repository"))?
+                .to_path_buf();
+
+            let mut files = vec![];
+            for rule in version_file::rules_for(&repo, configured)? {
+                let content = match std::fs::read_to_string(workdir.join(&rule.file)) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                };
+                if let Some(range) = rule.find(&content)? {
+                    files.push((rule.file, content, range));
+                }
+            }
+            let (_, content, range) = match files.first() {
+                Some(first) => first,
+                None => return Ok(None),
+            };
+
+            let old = content[range.clone()].to_string();
+            let new = self.version.apply(&old)?;
+            let tag = (!self.no_tag).then(|| format!("{}{}", self.tag_prefix, new));
+            let mut release = Release {
+                files: files.iter().map(|(file, _, _)| file.clone()).collect(),
+                old,
+                new,
+                tag,
+                pushed: false,
+            };
+            if self.dry_run {
+                return Ok(Some(release));
+            }
+
+            if !git_repo.head()?.is_branch() {
+                bail!("HEAD is detached, check out a branch first");
+            }
+            if let Some(tag) = &release.tag {
+                if git::tag_exists(&git_repo, tag) {
+                    bail!("Tag {} already exists", tag);
+                }
+            }
+            let head = git_repo.head()?.peel_to_commit()?;
+            let staged = git_repo.diff_tree_to_index(Some(&head.tree()?), None, None)?;
+            if staged.deltas().len() > 0 {
+                bail!("There are staged changes, commit or unstage them first");
+            }
+            for (file, _, _) in &files {
+                match git_repo.status_file(Path::new(file)) {
+                    Ok(status) if status != FileStatus::CURRENT => {
+                        bail!(
+                            "{} has uncommitted changes, commit or discard them first",
+                            file
+                        )
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut index = git_repo.index()?;
+            for (file, content, range) in &files {
+                let mut content = content.clone();
+                content.replace_range(range.clone(), &release.new);
+                std::fs::write(workdir.join(file), content)?;
+                index.add_path(Path::new(file))?;
+            }
+            index.write()?;
+            let tree = git_repo.find_tree(index.write_tree()?)?;
+            let sig = git_repo.signature()?;
+            let message = self.message.replace("{version}", &release.new);
+            git_repo.commit(Some("HEAD"), &sig, &sig, &message, &tree, &[&head])?;
+
+            if let Some(tag) = &release.tag {
+                git::tag_head(
+                    &git_repo,
+                    tag,
+                    &format!("Version {}", release.new),
+                    self.sign,
+                )?;
+            }
+
+            if self.push {
+                let branch = git::head_shorthand(&git_repo)?;
+                let cred = GitCredential::from(user);
+                git::push::push_branch(&git_repo, &branch, "origin", Some(cred.clone()))?;
+                if let Some(tag) = &release.tag {
+                    git::push::push_tag(&git_repo, tag, "origin", Some(cred))?;
+                }
+                release.pushed = true;
+            }
+
+            Ok(Some(release))
+        };
+
+        let result = bump().map_err(|e| format!("{:?}", e));
+        Status { repo, result }
+    }
+}
+
+/// How the version changes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    To(String),
+}
+
+impl FromStr for Bump {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "major" => Ok(Bump::Major),
+            "minor" => Ok(Bump::Minor),
+            "patch" => Ok(Bump::Patch),
+            version if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                Ok(Bump::To(version.to_string()))
+            }
+            _ => Err(format!(
+                "{:?} is neither major, minor, patch nor a version",
+                s
+            )),
+        }
+    }
+}
+
+impl Bump {
+    /// The next version, any pre-release or build suffix of the current version is dropped
+    fn apply(&self, current: &str) -> Result<String> {
+        let level = match self {
+            Bump::To(version) => return Ok(version.clone()),
+            Bump::Major => 0,
+            Bump::Minor => 1,
+            Bump::Patch => 2,
+        };
+
+        let numbers = Regex::new(r"^(\d+)(?:\.(\d+))?(?:\.(\d+))?")?
+            .captures(current)
+            .ok_or_else(|| anyhow!("{:?} is not a version with numbers", current))?;
+        let mut parts: Vec<u64> = (1..=3)
+            .map(|i| numbers.get(i).map(|m| m.as_str().parse()).transpose())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|n| n.unwrap_or(0))
+            .collect();
+        parts[level] += 1;
+        for part in parts.iter_mut().skip(level + 1) {
+            *part = 0;
+        }
+
+        Ok(format!("{}.{}.{}", parts[0], parts[1], parts[2]))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Release {
+    files: Vec<String>,
+    old: String,
+    new: String,
+    tag: Option<String>,
+    pushed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    /// Nothing when the repository has no version file
+    result: Result<Option<Release>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        match &self.result {
+            Ok(Some(release)) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(release.files.join(", ")),
+                cell!(Fg -> format!("{} -> {}", release.old, release.new)),
+                cell!(release.tag.as_deref().unwrap_or("-")),
+                pushed_cell(release.pushed),
+            ]),
+            Ok(None) => row![b -> &self.repo, "no version file", "", "", "-"],
+            Err(e) => Row::new(vec![
+                cell!(b -> &self.repo),
+                cell!(""),
+                ErrorCategory::from_message(e).failed_cell(),
+                cell!(""),
+                cell!("-"),
+            ]),
+        }
+    }
+
+    fn is_bumped(&self) -> bool {
+        matches!(&self.result, Ok(Some(_)))
+    }
+
+    fn has_error(&self) -> bool {
+        self.result.is_err()
+    }
+
+    fn to_error_report(&self) -> ErrorReport {
+        let e = if let Err(e) = &self.result {
+            e
+        } else {
+            panic!("This should have an error here");
+        };
+
+        ErrorReport::from_message(&self.repo, e)
+    }
+}
+
+impl Tracked for Status {
+    fn error_report(&self) -> Option<ErrorReport> {
+        self.has_error().then(|| self.to_error_report())
+    }
+}
+
+fn pushed_cell(pushed: bool) -> Cell {
+    if pushed {
+        cell!(Fg -> "Pushed")
+    } else {
+        cell!("-")
+    }
+}
+
+fn summarize(statuses: &[Status], dry_run: bool) {
+    let rows: Vec<_> = statuses.iter().map(|s| s.to_row()).collect();
+    let mut table = Table::init(rows);
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(row!["Repo", "Files", "Version", "Tag", "Origin"]);
+    table.printstd();
+
+    let bumped = statuses.iter().filter(|s| s.is_bumped()).count();
+    if dry_run {
+        println!("\nDry run, {} repos would be bumped", bumped);
+    } else if bumped > 0 {
+        let msg = format!("\nBumped the version of {} repos!", bumped);
+        println!("{}", msg.green());
+    }
+
+    let errors: Vec<_> = statuses.iter().filter(|s| s.has_error()).collect();
+    if !errors.is_empty() {
+        let msg = format!("There {} errors when bumping the versions:", errors.len());
+        println!("\n{}\n", msg.red());
+
+        let reports: Vec<_> = errors.iter().map(|e| e.to_error_report()).collect();
+        errors::print_error_report(&reports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump() {
+        assert_eq!(Bump::Patch.apply("0.1.2").unwrap(), "0.1.3");
+        assert_eq!(Bump::Minor.apply("0.1.2").unwrap(), "0.2.0");
+        assert_eq!(Bump::Major.apply("1.0.0-beta.5").unwrap(), "2.0.0");
+        assert_eq!(Bump::Patch.apply("1.4").unwrap(), "1.4.1");
+        assert_eq!(
+            "2.1.0".parse::<Bump>().unwrap().apply("1.4").unwrap(),
+            "2.1.0"
+        );
+        assert!("latest".parse::<Bump>().is_err());
+        assert!(Bump::Patch.apply("unknown").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::filter::Filter;
+use crate::github;
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::fmt::Display;
+
+#[derive(Debug, Parser)]
+/// Watch, ignore or stop watching all repositories that match a regex
+///
+/// This sets the subscriptions of the user of the token, e.g. for a new team member to follow
+/// the repositories of the team. GitHub has no api for custom watching, like releases only,
+/// so that has to be set on the web page of a repository.
+pub struct WatchArgs {
+    #[arg(value_enum)]
+    pub subscription: Subscription,
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Subscription {
+    /// Be notified of all activity
+    #[value(name = "all")]
+    All,
+    /// Never be notified, not even when participating or mentioned
+    #[value(name = "ignore")]
+    Ignore,
+    /// Stop watching, and only be notified when participating or mentioned
+    #[value(name = "none")]
+    None,
+}
+
+impl Display for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Subscription::All => "watched",
+            Subscription::Ignore => "ignored",
+            Subscription::None => "not watched",
+        })
+    }
+}
+
+impl WatchArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        for repo in filtered_repos {
+            let result = match self.subscription {
+                Subscription::All => github::set_subscription(&repo, false, &user_token),
+                Subscription::Ignore => github::set_subscription(&repo, true, &user_token),
+                Subscription::None => github::delete_subscription(&repo, &user_token),
+            };
+            match result {
+                Ok(_) => println!("Repo {} is {}", repo.name, self.subscription),
+                Err(e) => println!(
+                    "Failed to set repo {} to {} because {:?}",
+                    repo.name, self.subscription, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use super::common;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::env_var;
+use crate::github;
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Show who the GitHub token belongs to and what it may do
+///
+/// This shows the user of the token that gut runs with, the type, scopes and expiry of the
+/// token, and the organisations that it can see, to check the identity before a bulk change.
+pub struct WhoamiArgs {}
+
+impl WhoamiArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user = common::user()?;
+        let source = if env_var("GUT_TOKEN").is_some() {
+            "GUT_TOKEN"
+        } else {
+            "config file"
+        };
+
+        let info = github::get_token_info(&user.token).context("Cannot look up the token")?;
+        let orgs = github::get_user_orgs(&user.token)
+            .context("Cannot get the organisations of the token")?;
+        let whoami = Whoami {
+            username: info.login.clone(),
+            name: info.name.clone(),
+            token_type: token_type(&user.token),
+            token_source: source,
+            scopes: info.scopes.clone(),
+            expires_at: info.expires_at.clone(),
+            organisations: orgs,
+        };
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(whoami));
+            return Ok(());
+        }
+
+        whoami.print();
+        if !user.username.eq_ignore_ascii_case(&info.login) {
+            let msg = format!(
+                "The token belongs to {}, but gut knows the user as {}",
+                info.login, user.username
+            );
+            println!("\n{}", msg.yellow());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Whoami {
+    username: String,
+    name: Option<String>,
+    token_type: &'static str,
+    token_source: &'static str,
+    scopes: Option<Vec<String>>,
+    expires_at: Option<String>,
+    organisations: Vec<String>,
+}
+
+impl Whoami {
+    fn print(&self) {
+        match &self.name {
+            Some(name) => println!("Username: {} ({})", self.username.bold(), name),
+            None => println!("Username: {}", self.username.bold()),
+        }
+        println!("Token: {} from {}", self.token_type, self.token_source);
+        match &self.scopes {
+            Some(scopes) if scopes.is_empty() => println!("Scopes: {}", "none".yellow()),
+            Some(scopes) => println!("Scopes: {}", scopes.join(", ")),
+            None => println!("Scopes: set per repository in the token settings"),
+        }
+        println!("Expires: {}", self.expires_at.as_deref().unwrap_or("never"));
+        if self.organisations.is_empty() {
+            println!("Organisations: {}", "none".yellow());
+        } else {
+            println!("Organisations: {}", self.organisations.join(", "));
+        }
+    }
+}
+
+/// The type of a token, from the prefix that GitHub gives every type
+fn token_type(token: &str) -> &'static str {
+    match token {
+        t if t.starts_with("github_pat_") => "fine-grained personal access token",
+        t if t.starts_with("ghp_") => "classic personal access token",
+        t if t.starts_with("gho_") => "OAuth app token",
+        t if t.starts_with("ghu_") => "GitHub App user token",
+        t if t.starts_with("ghs_") => "GitHub App installation token",
+        _ => "token",
+    }
+}
@@ -1,14 +1,16 @@
 use super::workflow_run::*;
+use super::workflow_status::*;
 use crate::cli::Args as CommonArgs;
 use anyhow::Result;
 use clap::Parser;
 
 #[derive(Debug, Parser)]
+/// Run workflows and show the latest workflow runs
 pub struct WorkflowArgs {
     #[command(subcommand)]
     command: WorkflowCommand,
 }
-/// Run a workflow
+
 impl WorkflowArgs {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         self.command.run(common_args)
@@ -19,12 +21,15 @@ impl WorkflowArgs {
 pub enum WorkflowCommand {
     #[command(name = "run")]
     Run(WorkflowRunArgs),
+    #[command(name = "status")]
+    Status(WorkflowStatusArgs),
 }
 
 impl WorkflowCommand {
     pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
         match self {
             Self::Run(args) => args.run(common_args),
+            Self::Status(args) => args.run(common_args),
         }
     }
 }
@@ -0,0 +1,307 @@
+use super::common;
+use super::errors::{self, ErrorCategory, ErrorReport};
+use super::table::SortArgs;
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::filter::Filter;
+use crate::github::{self, RemoteRepo, Workflow};
+use anyhow::Result;
+use chrono::Utc;
+use clap::Parser;
+use colored::*;
+use prettytable::{cell, format, row, Cell, Row, Table};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// Show the latest workflow run of all repositories that match a regex
+///
+/// The run is the most recent one on the default branch of every repository, or on the branch
+/// given with --branch. Repositories whose run failed are shown in red.
+pub struct WorkflowStatusArgs {
+    #[arg(long, short)]
+    /// Target organisation name
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Only look at the runs of this workflow file, e.g. build.yml
+    pub workflow: Option<String>,
+    #[arg(long, short)]
+    /// Look at the runs on this branch instead of the default branch
+    pub branch: Option<String>,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+impl WorkflowStatusArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let user_token = common::user_token()?;
+        let organisation = common::organisation(self.organisation.as_deref())?;
+
+        let filtered_repos =
+            common::query_and_filter_repositories(&organisation, self.regex.as_ref(), &user_token)?;
+
+        if filtered_repos.is_empty() {
+            println!(
+                "There is no repositories in organisation {} that matches pattern {:?}",
+                organisation, self.regex
+            );
+            return Ok(());
+        }
+
+        let mut statuses: Vec<_> = filtered_repos
+            .par_iter()
+            .map(|repo| Status {
+                repo: repo.full_name(),
+                run: self.latest_run(repo, &user_token),
+            })
+            .collect();
+        self.sort.sort(&mut statuses, &titles(), Status::to_row)?;
+        let counts = Counts::of(&statuses);
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!({ "repos": statuses, "counts": counts }));
+            return Ok(());
+        }
+
+        to_table(&statuses).printstd();
+        println!();
+        counts.print();
+
+        let reports: Vec<_> = statuses
+            .iter()
+            .filter_map(|s| s.to_error_report())
+            .collect();
+        if !reports.is_empty() {
+            let msg = format!(
+                "There {} errors when looking up workflow runs:",
+                reports.len()
+            );
+            println!("\n{}\n", msg.red());
+            errors::print_error_report(&reports);
+        }
+
+        Ok(())
+    }
+
+    fn latest_run(&self, repo: &RemoteRepo, token: &str) -> Result<Option<Run>, String> {
+        let find = || -> Result<Option<Run>> {
+            let branch = match &self.branch {
+                Some(branch) => branch.clone(),
+                None => github::get_default_branch(&repo.owner, &repo.name, token)?,
+            };
+            let run =
+                github::get_latest_workflow_run(repo, self.workflow.as_deref(), &branch, token)?;
+            Ok(run.map(Run::from))
+        };
+        find().map_err(|e| format!("{:?}", e))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Run {
+    workflow: String,
+    branch: String,
+    status: String,
+    conclusion: Option<String>,
+    duration_secs: Option<i64>,
+    url: String,
+}
+
+impl From<Workflow> for Run {
+    fn from(run: Workflow) -> Run {
+        // A run that has not completed yet is still running, so its duration is up to now
+        let end = if run.status == "completed" {
+            run.updated_at
+        } else {
+            Utc::now()
+        };
+        Run {
+            workflow: run.name.unwrap_or_default(),
+            branch: run.head_branch.unwrap_or_default(),
+            conclusion: run.conclusion,
+            duration_secs: run.run_started_at.map(|start| (end - start).num_seconds()),
+            status: run.status,
+            url: run.html_url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    Failure,
+    Pending,
+    Other,
+}
+
+impl Run {
+    fn outcome(&self) -> Outcome {
+        match self.conclusion.as_deref() {
+            Some("success") => Outcome::Success,
+            Some("failure" | "timed_out" | "startup_failure") => Outcome::Failure,
+            Some(_) => Outcome::Other,
+            None => Outcome::Pending,
+        }
+    }
+
+    fn conclusion_cell(&self) -> Cell {
+        let text = self.conclusion.as_deref().unwrap_or(&self.status);
+        match self.outcome() {
+            Outcome::Success => cell!(Fg -> text),
+            Outcome::Failure => cell!(Fr -> text),
+            Outcome::Pending => cell!(Fy -> text),
+            Outcome::Other => cell!(text),
+        }
+    }
+}
+
+/// Minutes and seconds, with the minutes first so that the column orders by its value
+fn format_duration(secs: i64) -> String {
+    format!("{}m {:02}s", secs / 60, secs % 60)
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    repo: String,
+    run: Result<Option<Run>, String>,
+}
+
+impl Status {
+    fn to_row(&self) -> Row {
+        let mut cells = vec![cell!(b -> &self.repo)];
+        match &self.run {
+            Ok(Some(run)) => cells.extend([
+                cell!(run.workflow),
+                cell!(run.branch),
+                run.conclusion_cell(),
+                cell!(r -> run.duration_secs.map(format_duration).unwrap_or_default()),
+                cell!(run.url),
+            ]),
+            Ok(None) => cells.push(cell!(H5 -> "No workflow runs")),
+            Err(e) => cells.push(ErrorCategory::from_message(e).failed_cell()),
+        }
+        Row::new(cells)
+    }
+
+    fn to_error_report(&self) -> Option<ErrorReport> {
+        let e = self.run.as_ref().err()?;
+        Some(ErrorReport::from_message(&self.repo, e))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Counts {
+    success: usize,
+    failure: usize,
+    pending: usize,
+    other: usize,
+    no_runs: usize,
+    failed: usize,
+}
+
+impl Counts {
+    fn of(statuses: &[Status]) -> Counts {
+        let mut counts = Counts::default();
+        for status in statuses {
+            match &status.run {
+                Ok(Some(run)) => match run.outcome() {
+                    Outcome::Success => counts.success += 1,
+                    Outcome::Failure => counts.failure += 1,
+                    Outcome::Pending => counts.pending += 1,
+                    Outcome::Other => counts.other += 1,
+                },
+                Ok(None) => counts.no_runs += 1,
+                Err(_) => counts.failed += 1,
+            }
+        }
+        counts
+    }
+
+    fn print(&self) {
+        println!(
+            "{} succeeded, {} failed, {} running, {} other, {} without runs",
+            self.success.to_string().green(),
+            self.failure.to_string().red(),
+            self.pending.to_string().yellow(),
+            self.other,
+            self.no_runs
+        );
+    }
+}
+
+fn titles() -> Row {
+    row![
+        "Repo",
+        "Workflow",
+        "Branch",
+        "Conclusion",
+        "Duration",
+        "Link"
+    ]
+}
+
+fn to_table(statuses: &[Status]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+    table.set_titles(titles());
+    for status in statuses {
+        table.add_row(status.to_row());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(status: &str, conclusion: Option<&str>) -> Run {
+        Run {
+            workflow: "CI".to_string(),
+            branch: "main".to_string(),
+            status: status.to_string(),
+            conclusion: conclusion.map(|c| c.to_string()),
+            duration_secs: Some(754),
+            url: "https://github.com/giellalt/lang-sme/actions/runs/1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(754), "12m 34s");
+        assert_eq!(format_duration(45), "0m 45s");
+        assert_eq!(format_duration(4205), "70m 05s");
+    }
+
+    #[test]
+    fn test_counts() {
+        let status = |run: Result<Option<Run>, &str>| Status {
+            repo: "giellalt/lang-sme".to_string(),
+            run: run.map_err(|e| e.to_string()),
+        };
+        let statuses = [
+            status(Ok(Some(run("completed", Some("success"))))),
+            status(Ok(Some(run("completed", Some("failure"))))),
+            status(Ok(Some(run("completed", Some("timed_out"))))),
+            status(Ok(Some(run("completed", Some("cancelled"))))),
+            status(Ok(Some(run("in_progress", None)))),
+            status(Ok(None)),
+            status(Err("Unsuccessful request with status code: 404 Not Found")),
+        ];
+        assert_eq!(
+            Counts::of(&statuses),
+            Counts {
+                success: 1,
+                failure: 2,
+                pending: 1,
+                other: 1,
+                no_runs: 1,
+                failed: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use super::workspace_create::*;
+use super::workspace_list::*;
+use super::workspace_use::*;
+use crate::cli::Args as CommonArgs;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Save named sets of repositories that other commands can select with --workspace
+pub struct WorkspaceArgs {
+    #[command(subcommand)]
+    command: WorkspaceCommand,
+}
+
+impl WorkspaceArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        self.command.run(common_args)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum WorkspaceCommand {
+    #[command(name = "create")]
+    Create(WorkspaceCreateArgs),
+    #[command(name = "list")]
+    List(WorkspaceListArgs),
+    #[command(name = "use")]
+    Use(WorkspaceUseArgs),
+}
+
+impl WorkspaceCommand {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        match self {
+            Self::Create(args) => args.run(common_args),
+            Self::List(args) => args.run(common_args),
+            Self::Use(args) => args.run(common_args),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::common;
+use crate::cli::Args as CommonArgs;
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::workspace::{Selection, Workspace};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Create a workspace, or add the repositories of another owner to a workspace
+///
+/// A repository is part of the workspace when it is listed with --repos, matches the regex
+/// or has the topic. Without any of them, all repositories of the owner are part of it.
+pub struct WorkspaceCreateArgs {
+    /// Name of the workspace
+    pub name: String,
+    #[arg(long, short)]
+    /// Owner of the repositories
+    ///
+    /// You can set a default organisation in the init or set organisation command.
+    pub organisation: Option<String>,
+    #[arg(long, short)]
+    /// Optional regex to filter repositories
+    pub regex: Option<Filter>,
+    #[arg(long, short)]
+    /// Optional topic to filter repositories
+    pub topic: Option<String>,
+    #[arg(long, num_args(1..))]
+    /// Names of repositories to add to the workspace
+    pub repos: Vec<String>,
+    #[arg(long, short)]
+    /// Add the selection to an existing workspace instead of creating a new one
+    pub add: bool,
+}
+
+impl WorkspaceCreateArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let organisation = common::organisation(self.organisation.as_deref())?;
+        let mut config = Config::from_file()?;
+
+        if !self.add && config.workspaces.contains_key(&self.name) {
+            anyhow::bail!(
+                "Workspace {} already exists. Use --add to add more repositories to it",
+                self.name
+            );
+        }
+
+        let workspace = config
+            .workspaces
+            .entry(self.name.clone())
+            .or_insert_with(Workspace::default);
+        workspace.selections.push(Selection {
+            owner: organisation.clone(),
+            regex: self.regex.as_ref().map(|r| r.to_arg()),
+            topic: self.topic.clone(),
+            repos: self.repos.clone(),
+        });
+        let count = workspace.selections.len();
+
+        config.save_config()?;
+
+        println!(
+            "Workspace {} selects repositories of {} owner(s), added {}",
+            self.name, count, organisation
+        );
+        Ok(())
+    }
+}
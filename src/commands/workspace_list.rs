@@ -0,0 +1,50 @@
+use crate::cli::{Args as CommonArgs, OutputFormat};
+use crate::config::Config;
+use anyhow::Result;
+use clap::Parser;
+use prettytable::{cell, format, row, Table};
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+/// List saved workspaces and the repositories they select
+pub struct WorkspaceListArgs {}
+
+impl WorkspaceListArgs {
+    pub fn run(&self, common_args: &CommonArgs) -> Result<()> {
+        let config = Config::from_file()?;
+
+        if let Some(OutputFormat::Json) = common_args.format {
+            println!("{}", json!(config.workspaces));
+            return Ok(());
+        }
+
+        if config.workspaces.is_empty() {
+            println!("There is no workspaces. Create one with gut workspace create");
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BORDERS_ONLY);
+        table.set_titles(row!["Workspace", "Owner", "Regex", "Topic", "Repos"]);
+        for (name, workspace) in &config.workspaces {
+            let name = if config.current_workspace.as_ref() == Some(name) {
+                cell!(Fgb -> format!("{} (in use)", name))
+            } else {
+                cell!(b -> name)
+            };
+            for (i, selection) in workspace.selections.iter().enumerate() {
+                let name = if i == 0 { name.clone() } else { cell!("") };
+                table.add_row(row![
+                    name,
+                    selection.owner,
+                    selection.regex.as_deref().unwrap_or("-"),
+                    selection.topic.as_deref().unwrap_or("-"),
+                    selection.repos.join("\n")
+                ]);
+            }
+        }
+        table.printstd();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::cli::Args as CommonArgs;
+use crate::config::Config;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+/// Make every command select the repositories of a workspace by default
+///
+/// The --workspace argument of a command still takes precedence.
+pub struct WorkspaceUseArgs {
+    #[arg(required_unless_present = "clear")]
+    /// Name of the workspace
+    pub name: Option<String>,
+    #[arg(long, short, conflicts_with = "name")]
+    /// Stop using a workspace by default
+    pub clear: bool,
+}
+
+impl WorkspaceUseArgs {
+    pub fn run(&self, _common_args: &CommonArgs) -> Result<()> {
+        let mut config = Config::from_file()?;
+
+        match &self.name {
+            Some(name) if !config.workspaces.contains_key(name) => {
+                anyhow::bail!(
+                    "There is no workspace {}. Create it with gut workspace create",
+                    name
+                );
+            }
+            Some(name) => println!("Commands will now use workspace {}", name),
+            None => println!("Commands will no longer use a workspace by default"),
+        }
+
+        config.current_workspace = self.name.clone();
+        config.save_config()
+    }
+}
@@ -0,0 +1,134 @@
+use crate::path;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// The names that shell completion offers, so that completing never waits for GitHub
+///
+/// It is kept in completion-cache.json in the config directory and filled by
+/// `gut cache refresh`, which `gut cache list` starts in the background when it is stale.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CompletionCache {
+    /// Organisations of the token
+    pub orgs: Vec<String>,
+    pub owners: BTreeMap<String, OwnerNames>,
+}
+
+/// The names of the repositories, topics and teams of an owner
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OwnerNames {
+    pub refreshed_at: DateTime<Utc>,
+    pub repos: Vec<String>,
+    pub topics: Vec<String>,
+    /// Slugs of the teams, empty when the token cannot see them
+    pub teams: Vec<String>,
+}
+
+/// What kind of names to complete
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    Orgs,
+    Repos,
+    Topics,
+    Teams,
+}
+
+impl CompletionCache {
+    fn path() -> Result<PathBuf> {
+        path::completion_cache_path().ok_or_else(|| anyhow!("Cannot find the config directory"))
+    }
+
+    pub fn load() -> Result<CompletionCache> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(CompletionCache::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read the completion cache from {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Cannot read the completion cache from {:?}", path))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Cannot save the completion cache to {:?}", path))
+    }
+
+    /// Whether the names of an owner are missing or older than `max_age`
+    pub fn is_stale(&self, owner: &str, max_age: Duration) -> bool {
+        match self.find(owner) {
+            Some(names) => Utc::now() - names.refreshed_at > max_age,
+            None => true,
+        }
+    }
+
+    fn find(&self, owner: &str) -> Option<&OwnerNames> {
+        self.owners
+            .iter()
+            .find(|(o, _)| o.eq_ignore_ascii_case(owner))
+            .map(|(_, names)| names)
+    }
+
+    /// The names of a kind, of one owner or of all owners in the cache, sorted and without
+    /// duplicates
+    pub fn names(&self, kind: Kind, owner: Option<&str>) -> Vec<String> {
+        let owners: Vec<&OwnerNames> = match owner {
+            Some(owner) => self.find(owner).into_iter().collect(),
+            None => self.owners.values().collect(),
+        };
+        let names: BTreeSet<&String> = match kind {
+            Kind::Orgs => self.orgs.iter().chain(self.owners.keys()).collect(),
+            Kind::Repos => owners.iter().flat_map(|o| &o.repos).collect(),
+            Kind::Topics => owners.iter().flat_map(|o| &o.topics).collect(),
+            Kind::Teams => owners.iter().flat_map(|o| &o.teams).collect(),
+        };
+        names.into_iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(repos: &[&str], topics: &[&str], days: i64) -> OwnerNames {
+        OwnerNames {
+            refreshed_at: Utc::now() - Duration::days(days),
+            repos: repos.iter().map(|r| r.to_string()).collect(),
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+            teams: vec![],
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        let mut cache = CompletionCache {
+            orgs: vec!["giellalt".to_string()],
+            owners: BTreeMap::new(),
+        };
+        cache.owners.insert(
+            "giellalt".to_string(),
+            names(&["lang-sme", "lang-sma"], &["lang", "langfam-sami"], 0),
+        );
+        cache
+            .owners
+            .insert("divvun".to_string(), names(&["divvun-api"], &["lang"], 3));
+
+        assert_eq!(
+            cache.names(Kind::Repos, Some("GiellaLT")),
+            ["lang-sma", "lang-sme"]
+        );
+        assert_eq!(cache.names(Kind::Topics, None), ["lang", "langfam-sami"]);
+        assert_eq!(cache.names(Kind::Orgs, None), ["divvun", "giellalt"]);
+        assert!(cache.names(Kind::Repos, Some("unknown")).is_empty());
+
+        assert!(!cache.is_stale("giellalt", Duration::days(1)));
+        assert!(cache.is_stale("divvun", Duration::days(1)));
+        assert!(cache.is_stale("unknown", Duration::days(1)));
+    }
+}
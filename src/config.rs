@@ -1,14 +1,63 @@
+use super::alias::Alias;
+use super::doc_check::DocsPolicy;
+use super::email::EmailSettings;
+use super::mirror::Mirror;
+use super::network::NetworkSettings;
 use super::path::config_path;
+use super::policy::HookPolicy;
 use super::toml::{read_file, write_to_file};
-use anyhow::Result;
+use super::version_file::VersionFile;
+use super::workspace::Workspace;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Config {
     pub root: String,
     pub default_org: Option<String>,
     pub use_https: bool,
+    /// Base url of the GitHub api, for GitHub Enterprise servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Workspace that commands use when no --workspace is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_workspace: Option<String>,
+    /// Default arguments per command, e.g. `[defaults.pull]` with `stash = true`
+    ///
+    /// Flags that are given on the command line take precedence over these.
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub defaults: toml::Table,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, Workspace>,
+    /// Rules of the git hooks installed by `gut hook install`, per owner
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hooks: BTreeMap<String, HookPolicy>,
+    /// Rules of `gut check docs`, per owner
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub docs: BTreeMap<String, DocsPolicy>,
+    /// GPG fingerprints and SSH public keys that may sign release tags, per owner
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub allowed_signers: BTreeMap<String, Vec<String>>,
+    /// Shortcuts for commands, e.g. `st = "status -q"`, expanded before the arguments are parsed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias: BTreeMap<String, Alias>,
+    /// Proxy and ssh command to reach the remotes, per owner
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub network: BTreeMap<String, NetworkSettings>,
+    /// Secondary remotes of `gut push --mirror-to`, per owner and name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mirrors: BTreeMap<String, BTreeMap<String, Mirror>>,
+    /// Files and patterns of the versions that `gut version bump` changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_files: Vec<VersionFile>,
+    /// Where `--email` sends the output of a run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailSettings>,
+    /// Refuse api writes, pushes and local changes, for accounts that should only observe
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
 }
 
 impl Config {
@@ -17,6 +66,19 @@ impl Config {
             root,
             default_org,
             use_https,
+            api_url: None,
+            current_workspace: None,
+            defaults: toml::Table::new(),
+            workspaces: BTreeMap::new(),
+            hooks: BTreeMap::new(),
+            docs: BTreeMap::new(),
+            allowed_signers: BTreeMap::new(),
+            alias: BTreeMap::new(),
+            network: BTreeMap::new(),
+            mirrors: BTreeMap::new(),
+            version_files: vec![],
+            email: None,
+            read_only: false,
         }
     }
 
@@ -28,8 +90,79 @@ impl Config {
         read_file(path())
     }
 
+    /// The config file with the `GUT_*` environment variables applied on top of it
+    ///
+    /// Without a config file, setting GUT_ROOT is enough to run gut.
+    pub fn load() -> Result<Config> {
+        let config = match Config::from_file() {
+            Ok(config) => config,
+            Err(e) => match env_var("GUT_ROOT") {
+                Some(root) => Config::new(root, None, false),
+                None => return Err(e),
+            },
+        };
+        config.with_env()
+    }
+
+    fn with_env(mut self) -> Result<Config> {
+        if let Some(root) = env_var("GUT_ROOT") {
+            self.root = root;
+        }
+        if let Some(org) = env_var("GUT_DEFAULT_OWNER") {
+            self.default_org = Some(org);
+        }
+        if let Some(use_https) = env_var("GUT_USE_HTTPS") {
+            self.use_https = parse_bool("GUT_USE_HTTPS", &use_https)?;
+        }
+        if let Some(api_url) = env_var("GUT_API_URL") {
+            self.api_url = Some(api_url);
+        }
+        if let Some(read_only) = env_var("GUT_READ_ONLY") {
+            self.read_only = parse_bool("GUT_READ_ONLY", &read_only)?;
+        }
+        Ok(self)
+    }
+
+    /// Whether GUT_READ_ONLY or read_only in the config file turns on read-only mode
+    ///
+    /// Unlike `load`, a value that cannot be parsed is an error instead of the default, so that
+    /// a typo never turns read-only mode off. Without a config file only GUT_READ_ONLY counts.
+    pub fn read_only() -> Result<bool> {
+        if let Some(read_only) = env_var("GUT_READ_ONLY") {
+            return parse_bool("GUT_READ_ONLY", &read_only);
+        }
+        let path = path();
+        if !path.exists() {
+            return Ok(false);
+        }
+        read_file(path).map(|c: Config| c.read_only)
+    }
+
     pub fn root() -> Result<String> {
-        Config::from_file().map(|c| c.root)
+        Config::load().map(|c| c.root)
+    }
+
+    pub fn api_url() -> String {
+        Config::load()
+            .ok()
+            .and_then(|c| c.api_url)
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+    }
+}
+
+pub static DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Value of an environment variable, unset and empty variables are treated the same
+pub fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => bail!("{} must be true or false, not {:?}", name, value),
     }
 }
 
@@ -27,8 +27,116 @@ pub fn try_from_one(repo: RemoteRepo, user: &User, use_https: bool) -> Result<Gi
     })
 }
 
+/// A remote url of a repository on GitHub, whatever protocol it is written with
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RemoteUrl {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+    pub protocol: Protocol,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Protocol {
+    Ssh,
+    Https,
+    /// The unauthenticated git:// protocol, which GitHub no longer serves
+    Git,
+}
+
+impl RemoteUrl {
+    /// Parse `git@host:owner/name`, `ssh://git@host/owner/name`, `https://host/owner/name`
+    /// and `git://host/owner/name`, with or without `.git` and a trailing slash
+    pub fn parse(url: &str) -> Option<RemoteUrl> {
+        let url = url.trim();
+        let (protocol, rest) = if let Some(rest) = url.strip_prefix("ssh://") {
+            (
+                Protocol::Ssh,
+                rest.split_once('@').map(|(_, r)| r).unwrap_or(rest),
+            )
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            (Protocol::Https, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (Protocol::Https, rest)
+        } else if let Some(rest) = url.strip_prefix("git://") {
+            (Protocol::Git, rest)
+        } else if let Some((_, rest)) = url.split_once('@') {
+            (Protocol::Ssh, rest)
+        } else {
+            return None;
+        };
+
+        let (host, path) = rest.split_once(['/', ':'])?;
+        let path = path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let (owner, name) = path.split_once('/')?;
+        if host.is_empty() || owner.is_empty() || name.is_empty() || name.contains('/') {
+            return None;
+        }
+
+        Some(RemoteUrl {
+            host: host.to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+            protocol,
+        })
+    }
+
+    /// The url in the normal form, git:// is turned into ssh or https
+    pub fn to_url(&self, use_https: bool) -> String {
+        let https = match self.protocol {
+            Protocol::Ssh => false,
+            Protocol::Https => true,
+            Protocol::Git => use_https,
+        };
+        if https {
+            format!("https://{}/{}/{}", self.host, self.owner, self.name)
+        } else {
+            format!("git@{}:{}/{}", self.host, self.owner, self.name)
+        }
+    }
+}
+
 //pub fn try_from(vec: Vec<RemoteRepo>, user: &User, use_https: bool) -> Result<Vec<GitRepo>> {
 //vec.into_par_iter()
 //.map(|repo| try_from_one(repo, user, use_https))
 //.collect()
 //}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_remote_url() {
+        let cases = [
+            (
+                "git@github.com:giellalt/lang-sme.git",
+                "git@github.com:giellalt/lang-sme",
+            ),
+            (
+                "ssh://git@github.com/giellalt/lang-sme/",
+                "git@github.com:giellalt/lang-sme",
+            ),
+            (
+                "https://github.com/divvun/gut.git",
+                "https://github.com/divvun/gut",
+            ),
+            (
+                "git://github.com/divvun/gut.git",
+                "git@github.com:divvun/gut",
+            ),
+        ];
+        for (url, normal) in cases {
+            assert_eq!(RemoteUrl::parse(url).unwrap().to_url(false), normal);
+        }
+
+        assert_eq!(
+            RemoteUrl::parse("git://github.com/divvun/gut")
+                .unwrap()
+                .to_url(true),
+            "https://github.com/divvun/gut"
+        );
+        assert_eq!(RemoteUrl::parse("/srv/git/gut.git"), None);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::config::Config;
+use crate::encoding::{self, EncodingProblem};
+use crate::markdown;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The rules that `gut check docs` applies to the documentation of an owner
+///
+/// They are configured per owner in the `[docs.<owner>]` tables of the config file:
+///
+/// ```toml
+/// [docs.giellalt]
+/// encoding = true
+/// skip_rules = ["trailing-whitespace"]
+///
+/// [docs.giellalt.terms]
+/// "Sámi" = ["Sami", "Saami"]
+/// "GiellaLT" = ["Giella LT"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DocsPolicy {
+    /// Lint markdown files
+    pub markdown: bool,
+    /// Check that all text files are UTF-8 without a BOM, with the line endings of .gitattributes
+    pub encoding: bool,
+    /// Rules that are not checked, e.g. `trailing-whitespace`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skip_rules: Vec<String>,
+    /// Preferred spelling of a term, with the spellings that should be replaced by it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub terms: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for DocsPolicy {
+    fn default() -> Self {
+        DocsPolicy {
+            markdown: true,
+            encoding: false,
+            skip_rules: vec![],
+            terms: BTreeMap::new(),
+        }
+    }
+}
+
+impl DocsPolicy {
+    pub fn for_owner(config: &Config, owner: &str) -> DocsPolicy {
+        config
+            .docs
+            .iter()
+            .find(|(o, _)| o.eq_ignore_ascii_case(owner))
+            .map(|(_, policy)| policy.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn skips(&self, rule: &str) -> bool {
+        self.skip_rules.iter().any(|r| r == rule)
+    }
+
+    /// The checkers that the policy turns on
+    pub fn checkers(&self) -> Result<Vec<Box<dyn Checker>>> {
+        let mut checkers: Vec<Box<dyn Checker>> = vec![];
+        if self.markdown {
+            checkers.push(Box::new(MarkdownLint));
+        }
+        if !self.terms.is_empty() {
+            checkers.push(Box::new(TermList::new(&self.terms)?));
+        }
+        if self.encoding {
+            checkers.push(Box::new(EncodingCheck));
+        }
+        Ok(checkers)
+    }
+}
+
+/// A problem in a file, found by a checker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// Line of the problem, starting at 1
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// A check of the files of one kind
+///
+/// A new kind of check implements this and is added to [`DocsPolicy::checkers`].
+pub trait Checker: Send + Sync {
+    /// Whether the checker looks at the file, by its path in the repository
+    fn applies_to(&self, path: &str) -> bool;
+
+    /// Check the text of a file that is valid UTF-8
+    fn check(&self, _content: &str) -> Vec<Violation> {
+        vec![]
+    }
+
+    /// Check the file as it is, by default its text when it is valid UTF-8
+    fn check_file(&self, file: &File) -> Vec<Violation> {
+        match std::str::from_utf8(file.bytes) {
+            Ok(content) => self.check(content),
+            Err(_) => vec![],
+        }
+    }
+}
+
+/// A file that is checked
+pub struct File<'a> {
+    pub bytes: &'a [u8],
+    /// Whether .gitattributes asks for LF line endings
+    pub wants_lf: bool,
+}
+
+fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    let path = path.to_lowercase();
+    extensions
+        .iter()
+        .any(|e| path.ends_with(&format!(".{}", e)))
+}
+
+/// The common markdown mistakes, outside of code blocks
+pub struct MarkdownLint;
+
+impl Checker for MarkdownLint {
+    fn applies_to(&self, path: &str) -> bool {
+        has_extension(path, &["md", "markdown"])
+    }
+
+    fn check(&self, content: &str) -> Vec<Violation> {
+        let mut violations = vec![];
+        let mut violation = |line: usize, rule, message: String| {
+            violations.push(Violation {
+                line: line + 1,
+                rule,
+                message,
+            })
+        };
+
+        let lines = markdown::code_free_lines(content);
+        let mut previous_level = 0;
+        let mut top_level = false;
+        let mut blank = 0;
+        for (i, line) in lines.iter().enumerate() {
+            let line = match line {
+                Some(line) => line,
+                None => {
+                    blank = 0;
+                    continue;
+                }
+            };
+
+            let trimmed = line.trim_end();
+            // Two spaces at the end of a line are a line break
+            let line_break = !trimmed.is_empty() && &line[trimmed.len()..] == "  ";
+            if trimmed.len() < line.len() && !line_break {
+                violation(i, "trailing-whitespace", "Trailing whitespace".to_string());
+            }
+            if line.contains('\t') {
+                violation(i, "hard-tab", "Tab character, use spaces".to_string());
+            }
+
+            if line.trim().is_empty() {
+                blank += 1;
+                if blank == 2 {
+                    violation(i, "blank-lines", "More than one blank line".to_string());
+                }
+                continue;
+            }
+            blank = 0;
+
+            let hashes = line.chars().take_while(|c| *c == '#').count();
+            if (1..=6).contains(&hashes) {
+                match line[hashes..].chars().next() {
+                    None | Some(' ') => {}
+                    Some(_) => violation(
+                        i,
+                        "heading-space",
+                        "No space after the # of the heading".to_string(),
+                    ),
+                }
+                if previous_level > 0 && hashes > previous_level + 1 {
+                    violation(
+                        i,
+                        "heading-increment",
+                        format!(
+                            "Heading level {} after level {}, skipping a level",
+                            hashes, previous_level
+                        ),
+                    );
+                }
+                if hashes == 1 {
+                    if top_level {
+                        violation(
+                            i,
+                            "single-h1",
+                            "More than one top level heading".to_string(),
+                        );
+                    }
+                    top_level = true;
+                }
+                previous_level = hashes;
+            }
+        }
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            violation(
+                lines.len().saturating_sub(1),
+                "final-newline",
+                "The file does not end with a newline".to_string(),
+            );
+        }
+        violations
+    }
+}
+
+/// Spellings of terms that should be written differently, e.g. Sami instead of Sámi
+pub struct TermList {
+    terms: Vec<(String, String, Regex)>,
+}
+
+impl TermList {
+    pub fn new(terms: &BTreeMap<String, Vec<String>>) -> Result<TermList> {
+        let mut list = vec![];
+        for (preferred, variants) in terms {
+            for variant in variants {
+                let regex = Regex::new(&format!(r"\b{}\b", regex::escape(variant)))
+                    .with_context(|| format!("Invalid term {:?}", variant))?;
+                list.push((preferred.clone(), variant.clone(), regex));
+            }
+        }
+        Ok(TermList { terms: list })
+    }
+}
+
+impl Checker for TermList {
+    fn applies_to(&self, path: &str) -> bool {
+        has_extension(path, &["md", "markdown", "txt", "rst"])
+    }
+
+    fn check(&self, content: &str) -> Vec<Violation> {
+        let mut violations = vec![];
+        for (i, line) in markdown::code_free_lines(content).into_iter().enumerate() {
+            let line = match line {
+                Some(line) => line,
+                None => continue,
+            };
+            for (preferred, variant, regex) in &self.terms {
+                if regex.is_match(&line) {
+                    violations.push(Violation {
+                        line: i + 1,
+                        rule: "term",
+                        message: format!("Write {:?} instead of {:?}", preferred, variant),
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Text files that are not UTF-8, start with a BOM or have CRLF where LF is wanted
+pub struct EncodingCheck;
+
+impl Checker for EncodingCheck {
+    fn applies_to(&self, _path: &str) -> bool {
+        true
+    }
+
+    fn check_file(&self, file: &File) -> Vec<Violation> {
+        if encoding::is_binary(file.bytes) {
+            return vec![];
+        }
+        encoding::check(file.bytes, file.wants_lf)
+            .into_iter()
+            .map(|problem| Violation {
+                line: problem.line(),
+                rule: match problem {
+                    EncodingProblem::NotUtf8 { .. } => "not-utf8",
+                    EncodingProblem::Bom => "bom",
+                    EncodingProblem::Crlf { .. } => "crlf",
+                },
+                message: problem.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(violations: &[Violation]) -> Vec<(usize, &str)> {
+        violations.iter().map(|v| (v.line, v.rule)).collect()
+    }
+
+    #[test]
+    fn test_markdown_lint() {
+        let content = "# Title \n\n\n### Usage\n\n```\n#not a heading\t\n```\n##Setup\n# Again";
+        assert_eq!(
+            rules(&MarkdownLint.check(content)),
+            vec![
+                (1, "trailing-whitespace"),
+                (3, "blank-lines"),
+                (4, "heading-increment"),
+                (9, "heading-space"),
+                (10, "single-h1"),
+                (10, "final-newline"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_term_list() {
+        let terms = BTreeMap::from([(
+            "Sámi".to_string(),
+            vec!["Sami".to_string(), "Saami".to_string()],
+        )]);
+        let list = TermList::new(&terms).unwrap();
+        let content = "North Sami and Sámi\nSamisk is fine\n`Saami` in code\n";
+        assert_eq!(rules(&list.check(content)), vec![(1, "term")]);
+    }
+}
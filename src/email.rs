@@ -0,0 +1,223 @@
+use crate::config::{env_var, Config};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Where `--email` sends the output of a gut run
+///
+/// It is set in the `[email]` table of the config file. The SMTP password can also be given
+/// with GUT_SMTP_PASSWORD, so that it does not have to be saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmailSettings {
+    pub from: String,
+    pub to: Vec<String>,
+    pub smtp_host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub security: Security,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+/// How the connection to the SMTP server is encrypted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// Upgrade a plain connection with STARTTLS, usually on port 587
+    #[default]
+    Starttls,
+    /// TLS from the start, usually on port 465
+    Tls,
+    /// No encryption, only for a relay on the same host
+    None,
+}
+
+impl EmailSettings {
+    fn port(&self) -> u16 {
+        self.smtp_port.unwrap_or(match self.security {
+            Security::Starttls => 587,
+            Security::Tls => 465,
+            Security::None => 25,
+        })
+    }
+
+    fn password(&self) -> Option<String> {
+        env_var("GUT_SMTP_PASSWORD").or_else(|| self.password.clone())
+    }
+}
+
+/// Set for the child process of `--email`, which then runs the command instead of sending
+/// another email, also when `email = true` is in the [defaults] of the config
+const CHILD_ENV: &str = "GUT_EMAIL_CHILD";
+
+/// Whether this process is the child of a run with `--email`
+pub fn is_child() -> bool {
+    std::env::var_os(CHILD_ENV).is_some_and(|v| v == "1")
+}
+
+/// Run a gut command line and send what it prints by email
+///
+/// The command runs in a child process without `--email`, its output goes to stdout and stderr
+/// as usual and is collected for the email, so that the errors of a failed run are reported
+/// too. The email is also sent when the command fails.
+pub fn run_and_send(args: &[OsString]) -> Result<()> {
+    let settings = Config::load()?
+        .email
+        .ok_or_else(|| anyhow!("There is no [email] table in the config file to send to"))?;
+
+    let args: Vec<_> = args[1..].iter().filter(|a| *a != "--email").collect();
+    let line = args
+        .iter()
+        .map(|a| a.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let started = Local::now();
+
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(&args)
+        .env(CHILD_ENV, "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Cannot run gut")?;
+    // stderr is read on its own thread, so that neither pipe fills up while the other is read
+    let errors = child.stderr.take().map(|stderr| {
+        thread::spawn(move || {
+            let mut errors = String::new();
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("{}", line);
+                errors.push_str(&line);
+                errors.push('\n');
+            }
+            errors
+        })
+    });
+    let mut output = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            let line = line?;
+            println!("{}", line);
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    let errors = errors
+        .map(|handle| handle.join().unwrap_or_default())
+        .unwrap_or_default();
+    let status = child.wait()?;
+
+    let result = if status.success() {
+        "succeeded".to_string()
+    } else {
+        format!("failed with {}", status)
+    };
+    let subject = format!("gut {}: {}", line, result);
+    let mut body = format!(
+        "gut {}\nStarted {} and {} at {}\n\n{}",
+        line,
+        started.format("%Y-%m-%d %H:%M"),
+        result,
+        Local::now().format("%Y-%m-%d %H:%M"),
+        output
+    );
+    if !errors.is_empty() {
+        body.push_str(&format!("\nErrors:\n\n{}", errors));
+    }
+    send(&settings, &subject, &body).context("Cannot send the email")?;
+    println!("Sent the output to {}", settings.to.join(", "));
+
+    if !status.success() {
+        bail!("gut {} {}", line, result);
+    }
+    Ok(())
+}
+
+/// Send a plain text email
+pub fn send(settings: &EmailSettings, subject: &str, body: &str) -> Result<()> {
+    let message = message(settings, subject, body)?;
+
+    let host = settings.smtp_host.as_str();
+    let tls = TlsParameters::new(host.to_string())?;
+    let mut transport = SmtpTransport::builder_dangerous(host)
+        .port(settings.port())
+        .timeout(Some(Duration::from_secs(60)))
+        .tls(match settings.security {
+            Security::Starttls => Tls::Required(tls),
+            Security::Tls => Tls::Wrapper(tls),
+            Security::None => Tls::None,
+        });
+    if let Some(username) = &settings.username {
+        let password = settings
+            .password()
+            .ok_or_else(|| anyhow!("Set the SMTP password with GUT_SMTP_PASSWORD"))?;
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    transport
+        .build()
+        .send(&message)
+        .with_context(|| format!("Cannot send through {}:{}", host, settings.port()))?;
+    Ok(())
+}
+
+fn message(settings: &EmailSettings, subject: &str, body: &str) -> Result<Message> {
+    let mut builder = Message::builder()
+        .from(
+            settings
+                .from
+                .parse()
+                .with_context(|| format!("{} is not an email address", settings.from))?,
+        )
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &settings.to {
+        builder = builder.to(to
+            .parse()
+            .with_context(|| format!("{} is not an email address", to))?);
+    }
+    Ok(builder.body(body.to_string())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(from: &str) -> EmailSettings {
+        EmailSettings {
+            from: from.to_string(),
+            to: vec!["a@divvun.no".to_string(), "b@divvun.no".to_string()],
+            smtp_host: "smtp.divvun.no".to_string(),
+            smtp_port: None,
+            security: Security::default(),
+            username: None,
+            password: None,
+        }
+    }
+
+    #[test]
+    fn test_message() {
+        let from_gut = settings("gut@divvun.no");
+        assert_eq!(from_gut.port(), 587);
+
+        let email = message(&from_gut, "gut pull: succeeded", "Pulled\n.hidden\n").unwrap();
+        let formatted = String::from_utf8(email.formatted()).unwrap();
+        assert!(formatted.contains("To: a@divvun.no, b@divvun.no\r\n"));
+        assert!(formatted.contains("Subject: gut pull: succeeded\r\n"));
+        assert!(formatted.contains("Pulled\r\n.hidden"));
+
+        assert!(message(&from_gut, "giella-sámi", "").is_ok());
+        assert!(message(&settings("gut"), "gut pull", "").is_err());
+    }
+}
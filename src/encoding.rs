@@ -0,0 +1,126 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// A problem with the encoding or the line endings of a text file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "problem", rename_all = "kebab-case")]
+pub enum EncodingProblem {
+    /// The file is not valid UTF-8, e.g. a Latin-1 lexicon
+    NotUtf8 { line: usize },
+    /// The file starts with a UTF-8 byte order mark
+    Bom,
+    /// .gitattributes asks for LF, but some lines end with CRLF
+    Crlf { first: usize, lines: usize },
+}
+
+impl EncodingProblem {
+    /// The line where the problem starts, starting at 1
+    pub fn line(&self) -> usize {
+        match self {
+            EncodingProblem::NotUtf8 { line } => *line,
+            EncodingProblem::Bom => 1,
+            EncodingProblem::Crlf { first, .. } => *first,
+        }
+    }
+}
+
+impl fmt::Display for EncodingProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingProblem::NotUtf8 { line } => {
+                write!(f, "Not UTF-8, the first invalid byte is on line {}", line)
+            }
+            EncodingProblem::Bom => write!(f, "Starts with a UTF-8 byte order mark"),
+            EncodingProblem::Crlf { lines, .. } => write!(
+                f,
+                "{} lines end with CRLF, but .gitattributes asks for LF",
+                lines
+            ),
+        }
+    }
+}
+
+/// Whether the content looks binary, with the heuristic of git: a NUL in the first 8000 bytes
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|b| *b == 0)
+}
+
+/// Whether .gitattributes says that the file is not text, e.g. with `binary` or `-text`
+pub fn is_marked_binary(repo: &Repository, path: &str) -> bool {
+    repo.get_attr(Path::new(path), "text", AttrCheckFlags::FILE_THEN_INDEX)
+        .map(|value| AttrValue::from_string(value) == AttrValue::False)
+        .unwrap_or(false)
+}
+
+/// Whether .gitattributes asks git to store the file with LF line endings, with `text`,
+/// `text=auto` or `eol`
+pub fn is_normalized(repo: &Repository, path: &str) -> bool {
+    let attr = |name| repo.get_attr(Path::new(path), name, AttrCheckFlags::FILE_THEN_INDEX);
+    match attr("text").map(AttrValue::from_string) {
+        Ok(AttrValue::True) | Ok(AttrValue::String("auto")) => true,
+        Ok(AttrValue::False) => false,
+        _ => matches!(attr("eol"), Ok(Some(_))),
+    }
+}
+
+/// Whether .gitattributes asks for LF line endings in the file, with `eol=lf`
+pub fn wants_lf(repo: &Repository, path: &str) -> bool {
+    repo.get_attr(Path::new(path), "eol", AttrCheckFlags::FILE_THEN_INDEX)
+        .map(|value| value == Some("lf"))
+        .unwrap_or(false)
+}
+
+/// The encoding problems of a text file
+pub fn check(bytes: &[u8], wants_lf: bool) -> Vec<EncodingProblem> {
+    let mut problems = vec![];
+    if let Err(e) = std::str::from_utf8(bytes) {
+        let line = bytes[..e.valid_up_to()]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1;
+        problems.push(EncodingProblem::NotUtf8 { line });
+    }
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        problems.push(EncodingProblem::Bom);
+    }
+    if wants_lf {
+        let crlf: Vec<_> = bytes
+            .split(|b| *b == b'\n')
+            .enumerate()
+            .filter(|(_, line)| line.ends_with(b"\r"))
+            .map(|(i, _)| i + 1)
+            .collect();
+        if let Some(first) = crlf.first() {
+            problems.push(EncodingProblem::Crlf {
+                first: *first,
+                lines: crlf.len(),
+            });
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check("Sámi\n".as_bytes(), true), vec![]);
+        assert_eq!(
+            check(b"ok\nS\xe1mi\n", false),
+            vec![EncodingProblem::NotUtf8 { line: 2 }]
+        );
+        assert_eq!(
+            check(b"\xef\xbb\xbfa\r\nb\nc\r\n", true),
+            vec![
+                EncodingProblem::Bom,
+                EncodingProblem::Crlf { first: 1, lines: 2 }
+            ]
+        );
+        assert_eq!(check(b"a\r\n", false), vec![]);
+    }
+}
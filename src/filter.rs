@@ -4,35 +4,131 @@ use regex::{Error as RegexError, Regex, RegexBuilder};
 use std::path::PathBuf;
 use std::{fmt, str::FromStr};
 
+/// A case insensitive regex on repository names
+///
+/// The regex can be scoped to an owner with `owner:pattern`, and the regex argument can be
+/// repeated (`-r giellalt:^lang-s -r divvun:^divvun-gramcheck$`) to select repositories of
+/// several owners at once. Patterns without an owner apply to the organisation of the command.
 #[derive(Debug, Clone)]
 pub struct Filter {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    owner: Option<String>,
     regex: Regex,
 }
 
+/// Separates the values of a repeated regex argument after they are joined into one
+///
+/// A command line argument cannot contain it, so a single value is never split.
+pub const SEPARATOR: char = '\0';
+
 impl FromStr for Filter {
     type Err = RegexError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        RegexBuilder::new(s)
+        let terms = s
+            .split(SEPARATOR)
+            .map(Term::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Filter { terms })
+    }
+}
+
+impl FromStr for Term {
+    type Err = RegexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (owner, pattern) = match s.split_once(':') {
+            Some((owner, pattern)) if is_owner(owner) && is_scoped_pattern(pattern) => {
+                (Some(owner.to_string()), pattern)
+            }
+            _ => (None, s),
+        };
+        RegexBuilder::new(pattern)
             .case_insensitive(true)
             .build()
-            .map(|regex| Filter { regex })
+            .map(|regex| Term { owner, regex })
     }
 }
 
+/// Only `owner:pattern` is an owner scope, not `foo: bar` or `owner:` on its own
+fn is_scoped_pattern(s: &str) -> bool {
+    !s.is_empty() && !s.starts_with(char::is_whitespace)
+}
+
+/// A case insensitive regex on anything else than repository names, like issue titles
+pub fn case_insensitive_regex(s: &str) -> Result<Regex, RegexError> {
+    RegexBuilder::new(s).case_insensitive(true).build()
+}
+
+/// GitHub user and organisation names only have alphanumerics and hyphens
+fn is_owner(s: &str) -> bool {
+    !s.is_empty() && !s.starts_with('-') && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
 impl Filter {
+    /// Whether any of the patterns matches, whatever its owner
     pub fn is_match(&self, pattern: &str) -> bool {
-        self.regex.is_match(pattern)
+        self.terms.iter().any(|t| t.regex.is_match(pattern))
+    }
+
+    /// Whether a pattern for this owner, or without an owner, matches
+    pub fn is_match_in(&self, owner: &str, pattern: &str) -> bool {
+        self.terms.iter().any(|t| {
+            t.owner
+                .as_ref()
+                .map(|o| o.eq_ignore_ascii_case(owner))
+                .unwrap_or(true)
+                && t.regex.is_match(pattern)
+        })
     }
 
     pub fn replace(&self, original_text: &str, pattern: &str) -> String {
-        self.regex.replace(original_text, pattern).to_string()
+        match self.terms.iter().find(|t| t.regex.is_match(original_text)) {
+            Some(t) => t.regex.replace(original_text, pattern).to_string(),
+            None => original_text.to_string(),
+        }
+    }
+
+    /// Owners named in the patterns
+    pub fn owners(&self) -> Vec<String> {
+        let mut owners: Vec<String> = vec![];
+        for owner in self.terms.iter().filter_map(|t| t.owner.as_ref()) {
+            if !owners.iter().any(|o| o.eq_ignore_ascii_case(owner)) {
+                owners.push(owner.clone());
+            }
+        }
+        owners
+    }
+
+    /// The patterns joined like a repeated regex argument, which parses to the same filter
+    pub fn to_arg(&self) -> String {
+        let terms: Vec<_> = self.terms.iter().map(Term::to_string).collect();
+        terms.join(&SEPARATOR.to_string())
+    }
+
+    /// Whether some patterns have no owner and apply to the organisation of the command
+    pub fn has_unscoped(&self) -> bool {
+        self.terms.iter().any(|t| t.owner.is_none())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.owner {
+            Some(owner) => write!(f, "{}:{}", owner, self.regex),
+            None => write!(f, "{}", self.regex),
+        }
     }
 }
 
 impl fmt::Display for Filter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.regex)
+        let terms: Vec<_> = self.terms.iter().map(Term::to_string).collect();
+        write!(f, "{}", terms.join(" "))
     }
 }
 
@@ -51,7 +147,7 @@ pub trait Filterable {
 
 impl Filterable for RemoteRepo {
     fn is_match(&self, filter: &Filter) -> bool {
-        filter.is_match(&self.name)
+        filter.is_match_in(&self.owner, &self.name)
     }
 }
 
@@ -63,9 +159,10 @@ impl Filterable for RemoteRepoWithTopics {
 
 impl Filterable for PathBuf {
     fn is_match(&self, filter: &Filter) -> bool {
-        match path::dir_name(self) {
-            Ok(v) => filter.is_match(v.as_str()),
-            Err(_) => false,
+        match (path::owner_name(self), path::dir_name(self)) {
+            (Ok(owner), Ok(v)) => filter.is_match_in(&owner, v.as_str()),
+            (Err(_), Ok(v)) => filter.is_match(v.as_str()),
+            _ => false,
         }
     }
 }
@@ -82,4 +179,40 @@ mod tests {
         assert!(!filter.is_match("template-lang-sma"));
         assert!(!filter.is_match("langCI-sma-old"))
     }
+    #[test]
+    fn test_owner_scoped() {
+        let filter = Filter::from_str("giellalt:^lang-s\0divvun:^divvun-gramcheck$").unwrap();
+        assert_eq!(filter.owners(), vec!["giellalt", "divvun"]);
+        assert!(!filter.has_unscoped());
+        assert!(filter.is_match_in("giellalt", "lang-sme"));
+        assert!(filter.is_match_in("Divvun", "divvun-gramcheck"));
+        assert!(!filter.is_match_in("divvun", "lang-sme"));
+        assert!(!filter.is_match_in("giellalt", "divvun-gramcheck"));
+
+        let filter = Filter::from_str("^lang-\0divvun:keyboard").unwrap();
+        assert!(filter.has_unscoped());
+        assert!(filter.is_match_in("giellalt", "lang-sma"));
+        assert!(filter.is_match_in("divvun", "keyboard-sme"));
+        assert!(!filter.is_match_in("giellalt", "keyboard-sme"));
+        assert_eq!(filter.to_string(), "^lang- divvun:keyboard");
+        assert_eq!(
+            Filter::from_str(&filter.to_arg()).unwrap().to_arg(),
+            filter.to_arg()
+        );
+    }
+
+    #[test]
+    fn test_single_term() {
+        // Whitespace is part of the regex, not a separator
+        let filter = Filter::from_str("(?x) ^lang- sm [ae] $").unwrap();
+        assert!(filter.is_match("lang-sma"));
+        assert!(!filter.is_match("lang-fit"));
+
+        // Only owner:pattern scopes to an owner
+        let filter = Filter::from_str("foo: bar").unwrap();
+        assert!(filter.owners().is_empty());
+        assert!(filter.is_match_in("giellalt", "foo: bar"));
+        let filter = Filter::from_str("giellalt:").unwrap();
+        assert!(filter.owners().is_empty());
+    }
 }
@@ -0,0 +1,94 @@
+use super::status;
+use anyhow::Result;
+use git2::{ApplyLocation, Diff, Repository, ResetType};
+use std::fs;
+
+/// Apply the patches of several files to the work directory, file by file
+///
+/// A file whose hunks do not apply is left unchanged and its patch is written next to it with a
+/// `.rej` extension, like `patch` does, to be resolved by hand. Returns the rejected files.
+pub fn apply_patches(repo: &Repository, patches: &[(String, String)]) -> Result<Vec<String>> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Cannot apply a patch to a bare repository"))?;
+
+    let mut rejected = vec![];
+    for (file, patch) in patches {
+        let diff = Diff::from_buffer(patch.as_bytes())?;
+        if let Err(e) = repo.apply(&diff, ApplyLocation::WorkDir, None) {
+            log::debug!("Cannot apply the patch of {}: {}", file, e);
+            let reject = workdir.join(format!("{}.rej", file));
+            if let Some(parent) = reject.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(reject, patch)?;
+            rejected.push(file.to_string());
+        }
+    }
+    Ok(rejected)
+}
+
+/// Remove the untracked files and reset the tracked ones to HEAD, like `git clean -f` and
+/// `git reset --hard`
+pub fn clean_and_reset(repo: &Repository) -> Result<()> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Cannot clean a bare repository"))?;
+
+    for file in status(repo, false)?.new {
+        let path = workdir.join(&file);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn commit_file(repo: &Repository, name: &str, content: &str) {
+        fs::write(repo.workdir().unwrap().join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new(name)).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("gut", "gut@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_file(&repo, "README.md", "# lang-sme\n\nText\n");
+
+        let patch = |from: &str, to: &str| {
+            format!(
+                "diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n\
+                 @@ -1,3 +1,3 @@\n-# {}\n+# {}\n \n Text\n",
+                from, to
+            )
+        };
+        let patches = [("README.md".to_string(), patch("lang-sme", "Hello lang-sme"))];
+        assert!(apply_patches(&repo, &patches).unwrap().is_empty());
+        let readme = dir.path().join("README.md");
+        assert_eq!(
+            fs::read_to_string(&readme).unwrap(),
+            "# Hello lang-sme\n\nText\n"
+        );
+
+        let patches = [("README.md".to_string(), patch("lang-fin", "Hello lang-fin"))];
+        assert_eq!(apply_patches(&repo, &patches).unwrap(), ["README.md"]);
+        assert!(dir.path().join("README.md.rej").exists());
+
+        clean_and_reset(&repo).unwrap();
+        assert_eq!(fs::read_to_string(&readme).unwrap(), "# lang-sme\n\nText\n");
+        assert!(!dir.path().join("README.md.rej").exists());
+    }
+}
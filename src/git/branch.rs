@@ -1,19 +1,58 @@
 use super::fetch;
 use super::models::GitCredential;
 use anyhow::{anyhow, Result};
-use git2::{Branch, BranchType, Error, Repository};
+use git2::{Branch, BranchType, Commit, Oid, Repository};
+use serde::Serialize;
 
+/// Create a branch from a local branch, a tag, a commit or a branch of the remote
+///
+/// A tag or commit that is not known locally is fetched from the remote first.
 pub fn create_branch<'a>(
     repo: &'a Repository,
     new_branch: &str,
-    base_branch: &str,
-) -> Result<Branch<'a>, Error> {
-    let base_branch = repo.find_branch(base_branch, BranchType::Local)?;
+    base: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<Branch<'a>> {
+    let commit = base_commit(repo, base, remote_name, cred)?;
+    Ok(repo.branch(new_branch, &commit, false)?)
+}
+
+fn base_commit<'a>(
+    repo: &'a Repository,
+    base: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<Commit<'a>> {
+    if let Ok(branch) = repo.find_branch(base, BranchType::Local) {
+        return Ok(branch.get().peel_to_commit()?);
+    }
+    if let Ok(object) = repo.revparse_single(base) {
+        return Ok(object.peel_to_commit()?);
+    }
+
+    let refspec = if is_sha(base) {
+        base.to_string()
+    } else {
+        format!("+refs/tags/{}:refs/tags/{}", base, base)
+    };
+    if fetch::fetch_branch(repo, &refspec, remote_name, cred.clone()).is_ok() {
+        if let Ok(object) = repo.revparse_single(base) {
+            return Ok(object.peel_to_commit()?);
+        }
+    }
 
-    // unwrap work here because I assume branch always has direct reference
-    let oid = base_branch.get().target().unwrap();
-    let commit = repo.find_commit(oid)?;
-    repo.branch(new_branch, &commit, false)
+    // A short commit id cannot be fetched by itself, and a branch may only be on the remote
+    fetch::fetch_quiet(repo, remote_name, cred)?;
+    let object = repo
+        .revparse_single(base)
+        .or_else(|_| repo.revparse_single(&format!("{}/{}", remote_name, base)))
+        .map_err(|_| anyhow!("There is no branch, tag or commit {}", base))?;
+    Ok(object.peel_to_commit()?)
+}
+
+fn is_sha(rev: &str) -> bool {
+    (7..=40).contains(&rev.len()) && rev.chars().all(|c| c.is_ascii_hexdigit())
 }
 
 pub fn head_shorthand(repo: &Repository) -> Result<String> {
@@ -56,3 +95,163 @@ pub fn checkout_remote_branch(
         }
     }
 }
+
+/// Check out a tag, commit or any other revision without a branch
+///
+/// When the revision is not known locally, the remote is fetched first.
+pub fn checkout_detached(
+    repo: &Repository,
+    rev: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<Oid> {
+    let object = match repo.revparse_single(rev) {
+        Ok(object) => object,
+        Err(_) => {
+            fetch::fetch(repo, remote_name, cred)?;
+            repo.revparse_single(rev)
+                .map_err(|_| anyhow!("There is no tag or commit {}", rev))?
+        }
+    };
+    let commit = object.peel_to_commit()?;
+
+    repo.checkout_tree(commit.as_object(), None)?;
+    repo.set_head_detached(commit.id())?;
+
+    Ok(commit.id())
+}
+
+/// The branch that `<remote>/HEAD` points to, which is the default branch at the time of cloning
+pub fn remote_head(repo: &Repository, remote_name: &str) -> Option<String> {
+    let prefix = format!("refs/remotes/{}/", remote_name);
+    let reference = repo.find_reference(&format!("{}HEAD", prefix)).ok()?;
+    reference
+        .symbolic_target()
+        .and_then(|t| t.strip_prefix(&prefix))
+        .map(|t| t.to_string())
+}
+
+/// Point `<remote>/HEAD` to the given branch, like `git remote set-head`
+pub fn set_remote_head(repo: &Repository, remote_name: &str, branch: &str) -> Result<()> {
+    let prefix = format!("refs/remotes/{}/", remote_name);
+    repo.reference_symbolic(
+        &format!("{}HEAD", prefix),
+        &format!("{}{}", prefix, branch),
+        true,
+        "gut: set remote head",
+    )?;
+    Ok(())
+}
+
+/// The shorthand name of the upstream of a local branch, like `origin/main`
+pub fn upstream_name(repo: &Repository, branch: &str) -> Option<String> {
+    let branch = repo.find_branch(branch, BranchType::Local).ok()?;
+    let upstream = branch.upstream().ok()?;
+    upstream.name().ok().flatten().map(|n| n.to_string())
+}
+
+/// What fast-forwarding a local branch to its upstream did
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FastForward {
+    UpToDate,
+    /// The branch has commits that its upstream does not have, and nothing else
+    Ahead {
+        commits: usize,
+    },
+    Forwarded {
+        commits: usize,
+        from: String,
+        to: String,
+    },
+}
+
+/// Move a local branch that is not checked out to its upstream, like `git fetch . @{u}:branch`
+///
+/// A branch that has diverged from its upstream is left alone.
+pub fn fast_forward_branch(repo: &Repository, branch: &str) -> Result<FastForward> {
+    let local = repo.find_branch(branch, BranchType::Local)?;
+    if local.is_head() {
+        return Err(anyhow!(
+            "Branch {} is checked out, pull it instead to update the working tree",
+            branch
+        ));
+    }
+    let upstream = local
+        .upstream()
+        .map_err(|_| anyhow!("Branch {} has no upstream", branch))?;
+
+    let old = local.get().peel_to_commit()?.id();
+    let new = upstream.get().peel_to_commit()?.id();
+    let (ahead, behind) = repo.graph_ahead_behind(old, new)?;
+    match (ahead, behind) {
+        (0, 0) => Ok(FastForward::UpToDate),
+        (ahead, 0) => Ok(FastForward::Ahead { commits: ahead }),
+        (0, behind) => {
+            let mut reference = local.into_reference();
+            reference.set_target(new, "gut: fast-forward to upstream")?;
+            Ok(FastForward::Forwarded {
+                commits: behind,
+                from: old.to_string(),
+                to: new.to_string(),
+            })
+        }
+        (ahead, behind) => Err(anyhow!(
+            "Branch {} has diverged from its upstream ({} ahead, {} behind), it cannot be fast-forwarded",
+            branch,
+            ahead,
+            behind
+        )),
+    }
+}
+
+/// The remote of the upstream of a local branch, like `origin`
+pub fn upstream_remote(repo: &Repository, branch: &str) -> Option<String> {
+    repo.branch_upstream_remote(&format!("refs/heads/{}", branch))
+        .ok()
+        .and_then(|r| r.as_str().map(|r| r.to_string()))
+}
+
+/// A local branch whose commits are all in another branch
+#[derive(Debug, Clone)]
+pub struct MergedBranch {
+    pub name: String,
+    /// Commit time of the tip, in seconds since the epoch
+    pub time: i64,
+    /// The shorthand name of its upstream, like `origin/fix-typo`
+    pub upstream: Option<String>,
+}
+
+/// The local branches that are merged into `target`, except the one that is checked out
+pub fn merged_branches(repo: &Repository, target: Oid) -> Result<Vec<MergedBranch>> {
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.shorthand().map(|s| s.to_string()));
+
+    let mut merged = vec![];
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let name = match branch.name()? {
+            Some(name) if Some(name) != head.as_deref() => name.to_string(),
+            _ => continue,
+        };
+        let tip = match branch.get().target() {
+            Some(tip) => tip,
+            None => continue,
+        };
+        if tip != target && !repo.graph_descendant_of(target, tip)? {
+            continue;
+        }
+        let upstream = branch
+            .upstream()
+            .ok()
+            .and_then(|u| u.name().ok().flatten().map(|n| n.to_string()));
+        merged.push(MergedBranch {
+            name,
+            time: repo.find_commit(tip)?.time().seconds(),
+            upstream,
+        });
+    }
+    Ok(merged)
+}
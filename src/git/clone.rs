@@ -1,5 +1,6 @@
 use super::common;
 use super::models::GitCredential;
+use crate::network::NetworkSettings;
 //use rayon::prelude::*;
 use std::path::Path;
 
@@ -31,6 +32,17 @@ pub fn clone(
     cred: Option<GitCredential>,
 ) -> Result<git2::Repository, CloneError> {
     log::debug!("Clone {:?} to {:?}", remote_url, local_path);
+    let settings = NetworkSettings::for_url(remote_url, None);
+
+    if let Some(ssh_command) = &settings.ssh_command {
+        return clone_with_ssh_command(remote_url, local_path, ssh_command).map_err(|s| {
+            CloneError {
+                source: s,
+                remote_url: remote_url.to_string(),
+            }
+        });
+    }
+
     let remote_callbacks = common::create_remote_callback(&cred).map_err(|s| CloneError {
         source: s,
         remote_url: remote_url.to_string(),
@@ -40,15 +52,55 @@ pub fn clone(
 
     fo.remote_callbacks(remote_callbacks)
         .download_tags(git2::AutotagOption::All)
-        .update_fetchhead(true);
+        .update_fetchhead(true)
+        .proxy_options(settings.proxy_options());
+
+    let mut builder = git2::build::RepoBuilder::new();
+    builder.fetch_options(fo);
+
+    // Windows limits paths to 260 characters unless core.longpaths is set, and it has to be
+    // set before the files are checked out
+    #[cfg(windows)]
+    {
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.dry_run();
+        builder.with_checkout(checkout);
+    }
 
-    git2::build::RepoBuilder::new()
-        .fetch_options(fo)
+    let repo = builder
         .clone(remote_url, local_path)
         .map_err(|s| CloneError {
-            source: s,
+            source: common::explain_not_found(Some(remote_url), s),
             remote_url: remote_url.to_string(),
-        })
+        })?;
+
+    #[cfg(windows)]
+    checkout_with_long_paths(&repo).map_err(|s| CloneError {
+        source: s,
+        remote_url: remote_url.to_string(),
+    })?;
+
+    Ok(repo)
+}
+
+fn clone_with_ssh_command(
+    remote_url: &str,
+    local_path: &Path,
+    ssh_command: &str,
+) -> Result<git2::Repository, git2::Error> {
+    let path = local_path.to_string_lossy();
+    let mut args = vec!["clone", remote_url, &path];
+    if cfg!(windows) {
+        args.extend(["--config", "core.longpaths=true"]);
+    }
+    common::git_with_ssh_command(Path::new("."), ssh_command, &args)?;
+    git2::Repository::open(local_path)
+}
+
+#[cfg(windows)]
+fn checkout_with_long_paths(repo: &git2::Repository) -> Result<(), git2::Error> {
+    repo.config()?.set_bool("core.longpaths", true)?;
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
 }
 
 /*
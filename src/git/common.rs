@@ -1,12 +1,17 @@
 use super::models::GitCredential;
-use git2::Error;
+use crate::convert::RemoteUrl;
+use crate::network::NetworkSettings;
+use anyhow::anyhow;
+use git2::{Error, ErrorClass, Repository};
 use git2_credentials::ui4dialoguer::CredentialUI4Dialoguer;
 use git2_credentials::CredentialHandler;
 use git2_credentials::CredentialUI;
+use std::path::Path;
+use std::process::Command;
 
 pub fn create_remote_callback(
     cred: &Option<GitCredential>,
-) -> Result<git2::RemoteCallbacks, Error> {
+) -> Result<git2::RemoteCallbacks<'_>, Error> {
     let mut cb = git2::RemoteCallbacks::new();
     let git_config = git2::Config::open_default()?;
 
@@ -23,6 +28,119 @@ pub fn create_remote_callback(
     Ok(cb)
 }
 
+/// A git command that runs in the working directory of the repository
+///
+/// For https remotes the credential is handed to git as an http header through the
+/// environment, so git neither prompts for a password nor shows the token in the process list.
+/// The proxy and ssh command of the owner are passed on as well.
+pub fn git_command(repo: &Repository, cred: Option<&GitCredential>) -> anyhow::Result<Command> {
+    let dir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot run git in a bare repository"))?;
+
+    let mut command = Command::new("git");
+    command.current_dir(dir).env("GIT_TERMINAL_PROMPT", "0");
+
+    let url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().map(|u| u.to_string()))
+        .unwrap_or_default();
+    let settings = NetworkSettings::for_url(&url, repo.config().ok().as_ref());
+
+    // The header is scoped to the host of origin, which is not github.com on GitHub Enterprise
+    let mut git_config = vec![];
+    if let (Some(host), Some(cred)) = (https_host(&url), cred) {
+        git_config.push((
+            format!("http.https://{}/.extraheader", host),
+            cred.basic_auth_header(),
+        ));
+    }
+    if let Some(proxy) = &settings.proxy {
+        git_config.push(("http.proxy".to_string(), proxy.clone()));
+    }
+    if !git_config.is_empty() {
+        command.env("GIT_CONFIG_COUNT", git_config.len().to_string());
+        for (i, (key, value)) in git_config.into_iter().enumerate() {
+            command
+                .env(format!("GIT_CONFIG_KEY_{}", i), key)
+                .env(format!("GIT_CONFIG_VALUE_{}", i), value);
+        }
+    }
+    if let Some(ssh_command) = &settings.ssh_command {
+        command.env("GIT_SSH_COMMAND", ssh_command);
+    }
+
+    Ok(command)
+}
+
+/// The host of an https url with its port, without the user
+fn https_host(url: &str) -> Option<&str> {
+    let authority = url.strip_prefix("https://")?.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+/// Run git for a clone, fetch or push over ssh with a custom ssh command
+///
+/// libgit2 talks to ssh remotes with its own ssh client and cannot run another command, so
+/// these operations are handed to git when an ssh command is set.
+pub fn git_with_ssh_command(dir: &Path, ssh_command: &str, args: &[&str]) -> Result<(), Error> {
+    log::debug!(
+        "git {:?} with ssh command {:?} in {:?}",
+        args,
+        ssh_command,
+        dir
+    );
+    let output = Command::new("git")
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", ssh_command)
+        .args(args)
+        .output()
+        .map_err(|e| Error::from_str(&format!("Cannot run git: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::from_str(
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ))
+    }
+}
+
+/// Name the owner of the remote in a 404 from an https remote
+///
+/// GitHub answers 404 instead of 403 for a repository that the token cannot see, which for a
+/// repository that exists is almost always a token that is not authorized for the SSO of the
+/// owner or lacks the repo scope.
+pub fn explain_not_found(url: Option<&str>, error: Error) -> Error {
+    if error.class() != ErrorClass::Http || !error.message().contains("status code: 404") {
+        return error;
+    }
+    match url.and_then(RemoteUrl::parse) {
+        Some(url) => Error::new(
+            error.code(),
+            error.class(),
+            format!(
+                "{}: authorize your token for SSO in {} at https://{}/settings/tokens, or check that it has the repo scope",
+                error.message(),
+                url.owner,
+                url.host
+            ),
+        ),
+        None => error,
+    }
+}
+
+/// The network settings of a remote of the repository
+pub fn network_settings(repo: &Repository, remote: &git2::Remote) -> NetworkSettings {
+    NetworkSettings::for_url(
+        remote.url().unwrap_or_default(),
+        repo.config().ok().as_ref(),
+    )
+}
+
 pub fn ref_by_branch(branch: &str) -> String {
     format!("refs/heads/{}:refs/heads/{}", branch, branch)
 }
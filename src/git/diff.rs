@@ -1,5 +1,9 @@
 use anyhow::Result;
-use git2::{Diff, DiffDelta, DiffFile, DiffHunk, DiffLine, DiffOptions, Error, Repository};
+use colored::*;
+use git2::{
+    Diff, DiffDelta, DiffFile, DiffFormat, DiffHunk, DiffLine, DiffOptions, Error, Repository,
+};
+use serde::Serialize;
 use std::str;
 
 pub fn diff_trees<'a>(repo: &'a Repository, old: &str, new: &str) -> Result<Diff<'a>, Error> {
@@ -13,6 +17,66 @@ pub fn diff_trees<'a>(repo: &'a Repository, old: &str, new: &str) -> Result<Diff
     repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut opts))
 }
 
+/// Diff between the trees of two revisions, such as branches, tags or `origin/main`, with renames
+/// detected like git does
+///
+/// When pathspecs are given, only the files that match one of them are compared.
+pub fn diff_revs<'a>(
+    repo: &'a Repository,
+    old: &str,
+    new: &str,
+    paths: &[String],
+) -> Result<Diff<'a>, Error> {
+    let old_tree = repo.revparse_single(old)?.peel_to_tree()?;
+    let new_tree = repo.revparse_single(new)?.peel_to_tree()?;
+
+    let mut opts = DiffOptions::new();
+    opts.old_prefix("a");
+    opts.new_prefix("b");
+    for path in paths {
+        opts.pathspec(path.as_str());
+    }
+
+    let mut diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut opts))?;
+    diff.find_similar(None)?;
+    Ok(diff)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+pub fn diff_stat(diff: &Diff) -> Result<DiffStat, Error> {
+    let stats = diff.stats()?;
+    Ok(DiffStat {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// Print a diff as a patch with the colors of `git diff`
+pub fn print_patch(diff: &Diff) -> Result<(), Error> {
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        let content = String::from_utf8_lossy(line.content());
+        let text = content.trim_end_matches('\n');
+        let text = match line.origin() {
+            '+' => format!("+{}", text).green(),
+            '-' => format!("-{}", text).red(),
+            ' ' => format!(" {}", text).normal(),
+            'F' => text.bold(),
+            'H' => text.cyan(),
+            _ => text.normal(),
+        };
+        let newline = if content.ends_with('\n') { "\n" } else { "" };
+        print!("{}{}", text, newline);
+        true
+    })
+}
+
 #[allow(dead_code)]
 fn print_stats(diff: &Diff) -> Result<()> {
     let stats = diff.stats()?;
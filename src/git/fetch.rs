@@ -1,6 +1,10 @@
 use super::common;
 use super::models::GitCredential;
-use git2::{AnnotatedCommit, AutotagOption, Error, FetchOptions, Repository};
+use git2::{
+    AnnotatedCommit, AutotagOption, Error, FetchOptions, Oid, ProxyOptions, Remote, Repository,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::str;
 
@@ -13,26 +17,114 @@ pub fn fetch_branch<'a>(
 ) -> Result<AnnotatedCommit<'a>, Error> {
     //log::info!("Fetching {} for repo", branch);
     let mut remote = repo.find_remote(remote_name)?;
+    let settings = common::network_settings(repo, &remote);
 
-    let remote_callbacks = common::create_remote_callback(&cred)?;
+    if let Some(ssh_command) = &settings.ssh_command {
+        let dir = repo.workdir().unwrap_or(repo.path());
+        common::git_with_ssh_command(dir, ssh_command, &["fetch", remote_name, branch])?;
+    } else {
+        let remote_callbacks = common::create_remote_callback(&cred)?;
 
-    let mut fo = git2::FetchOptions::new();
-    fo.remote_callbacks(remote_callbacks);
+        let mut fo = git2::FetchOptions::new();
+        fo.remote_callbacks(remote_callbacks)
+            .proxy_options(settings.proxy_options());
 
-    remote.fetch(&[branch], Some(&mut fo), None)?;
+        remote
+            .fetch(&[branch], Some(&mut fo), None)
+            .map_err(|e| common::explain_not_found(remote.url(), e))?;
+    }
 
     let fetch_head = repo.find_reference("FETCH_HEAD")?;
     repo.reference_to_annotated_commit(&fetch_head)
 }
 
+/// A remote-tracking branch that a fetch created or moved
+#[derive(Debug, Clone, Serialize)]
+pub struct RefUpdate {
+    pub branch: String,
+    /// The commit before the fetch, none for a new branch
+    pub old: Option<String>,
+    pub new: String,
+}
+
+/// Fetch all branches of a remote and show the progress
+///
+/// Returns the remote-tracking branches that were created or moved.
 pub fn fetch(
     repo: &Repository,
     remote_name: &str,
     cred: Option<GitCredential>,
-) -> Result<(), Error> {
+) -> Result<Vec<RefUpdate>, Error> {
+    fetch_with(repo, remote_name, cred, true)
+}
+
+/// Fetch all branches of a remote without printing anything, for commands that run on many
+/// repositories at once
+pub fn fetch_quiet(
+    repo: &Repository,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<Vec<RefUpdate>, Error> {
+    fetch_with(repo, remote_name, cred, false)
+}
+
+fn fetch_with(
+    repo: &Repository,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+    verbose: bool,
+) -> Result<Vec<RefUpdate>, Error> {
+    let before = remote_branches(repo, remote_name)?;
+
     let mut remote = repo.find_remote(remote_name)?;
+    let settings = common::network_settings(repo, &remote);
+
+    if let Some(ssh_command) = &settings.ssh_command {
+        let dir = repo.workdir().unwrap_or(repo.path());
+        common::git_with_ssh_command(dir, ssh_command, &["fetch", remote_name])?;
+    } else {
+        download(&mut remote, cred, settings.proxy_options(), verbose)
+            .map_err(|e| common::explain_not_found(remote.url(), e))?;
+    }
 
+    let updates = remote_branches(repo, remote_name)?
+        .into_iter()
+        .filter(|(branch, new)| before.get(branch) != Some(new))
+        .map(|(branch, new)| RefUpdate {
+            old: before.get(&branch).map(|o| o.to_string()),
+            new: new.to_string(),
+            branch,
+        })
+        .collect();
+    Ok(updates)
+}
+
+/// The remote-tracking branches of a remote with their commits
+fn remote_branches(repo: &Repository, remote_name: &str) -> Result<BTreeMap<String, Oid>, Error> {
+    let prefix = format!("refs/remotes/{}/", remote_name);
+    let mut branches = BTreeMap::new();
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+            branches.insert(name.trim_start_matches(&prefix).to_string(), oid);
+        }
+    }
+    Ok(branches)
+}
+
+fn download(
+    remote: &mut Remote,
+    cred: Option<GitCredential>,
+    proxy: ProxyOptions,
+    verbose: bool,
+) -> Result<(), Error> {
     let mut cb = common::create_remote_callback(&cred)?;
+    if !verbose {
+        let mut fo = FetchOptions::new();
+        fo.remote_callbacks(cb).proxy_options(proxy);
+        remote.fetch(&[] as &[&str], Some(&mut fo), None)?;
+        return Ok(());
+    }
 
     //let mut fo = git2::FetchOptions::new();
     //fo.remote_callbacks(remote_callbacks);
@@ -81,7 +173,7 @@ pub fn fetch(
     // received data and the indexer stats which lets you inform the user about
     // progress.
     let mut fo = FetchOptions::new();
-    fo.remote_callbacks(cb);
+    fo.remote_callbacks(cb).proxy_options(proxy);
     remote.download(&[] as &[&str], Some(&mut fo))?;
 
     {
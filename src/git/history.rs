@@ -0,0 +1,145 @@
+use git2::{Commit, DescribeFormatOptions, DescribeOptions, Error, Oid, Repository, Sort};
+use serde::Serialize;
+use std::path::Path;
+
+/// A commit of the history of a branch
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub sha: String,
+    pub summary: String,
+    pub body: String,
+    /// Commit time in seconds since the epoch
+    pub time: i64,
+    pub is_merge: bool,
+}
+
+/// The commits that `to` contains and `from` does not, newest first
+///
+/// Only the first parent of merge commits is followed, so a merged pull request shows up as its
+/// merge commit, like on the branch it was merged into.
+pub fn first_parent_log(
+    repo: &Repository,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Vec<LogEntry>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+    revwalk.simplify_first_parent()?;
+    revwalk.push(repo.revparse_single(to)?.peel_to_commit()?.id())?;
+    if let Some(from) = from {
+        revwalk.hide(repo.revparse_single(from)?.peel_to_commit()?.id())?;
+    }
+
+    let mut entries = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        entries.push(LogEntry {
+            sha: commit.id().to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            body: commit.body().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            is_merge: commit.parent_count() > 1,
+        });
+    }
+    Ok(entries)
+}
+
+/// Number of commits of local branches that no remote-tracking branch contains
+pub fn unpushed_count(repo: &Repository) -> Result<usize, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_glob("refs/heads/*")?;
+    revwalk.hide_glob("refs/remotes/*")?;
+
+    let mut count = 0;
+    for oid in revwalk {
+        oid?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Author times of all commits reachable from HEAD, in seconds since the epoch
+pub fn author_times(repo: &Repository) -> Result<Vec<i64>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+
+    let mut times = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        times.push(commit.author().when().seconds());
+    }
+    Ok(times)
+}
+
+/// The last commit that changed a file or directory
+#[derive(Debug, Clone, Serialize)]
+pub struct LastChange {
+    pub sha: String,
+    pub author: String,
+    pub email: String,
+    /// Commit time in seconds since the epoch
+    pub time: i64,
+    /// Id of the blob or tree at HEAD, which is the same for identical copies
+    pub content: String,
+}
+
+/// The most recent commit reachable from HEAD that changed the path, like `git log -1 -- path`
+///
+/// None when the path does not exist at HEAD. A merge only counts when the path differs from
+/// all of its parents, so changes show up with the commit that made them.
+pub fn last_change(repo: &Repository, path: &str) -> Result<Option<LastChange>, Error> {
+    let head = repo.head()?.peel_to_commit()?;
+    let content = match entry_id(&head, path)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push(head.id())?;
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let id = entry_id(&commit, path)?;
+        if id.is_none() {
+            continue;
+        }
+        let mut changed = true;
+        for parent in commit.parents() {
+            if entry_id(&parent, path)? == id {
+                changed = false;
+                break;
+            }
+        }
+        if changed {
+            let author = commit.author();
+            return Ok(Some(LastChange {
+                sha: commit.id().to_string(),
+                author: author.name().unwrap_or_default().to_string(),
+                email: author.email().unwrap_or_default().to_string(),
+                time: commit.time().seconds(),
+                content: content.to_string(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn entry_id(commit: &Commit, path: &str) -> Result<Option<Oid>, Error> {
+    match commit.tree()?.get_path(Path::new(path)) {
+        Ok(entry) => Ok(Some(entry.id())),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The most recent tag before `rev`, not counting a tag on `rev` itself
+pub fn previous_tag(repo: &Repository, rev: &str) -> Option<String> {
+    let parent = repo.revparse_single(&format!("{}^", rev)).ok()?;
+    let describe = parent
+        .describe(DescribeOptions::new().describe_tags())
+        .ok()?;
+    describe
+        .format(Some(DescribeFormatOptions::new().abbreviated_size(0)))
+        .ok()
+}
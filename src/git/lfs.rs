@@ -0,0 +1,76 @@
+use super::common;
+use super::models::GitCredential;
+use anyhow::{anyhow, Result};
+use git2::{Config, ConfigLevel, Repository};
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// Whether the repository tracks any files with Git LFS
+pub fn uses_lfs(repo_dir: &Path) -> bool {
+    read_to_string(repo_dir.join(".gitattributes"))
+        .map(|content| content.contains("filter=lfs"))
+        .unwrap_or(false)
+}
+
+/// Download and check out the LFS objects of the current branch with `git lfs pull`
+///
+/// libgit2 does not run the LFS smudge filter, so repositories cloned or pulled by gut only
+/// have pointer files until this runs.
+///
+/// Returns false when the repository does not use LFS.
+pub fn lfs_pull(repo: &Repository, cred: Option<&GitCredential>) -> Result<bool> {
+    let dir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot pull LFS objects in a bare repository"))?;
+
+    if !uses_lfs(dir) {
+        return Ok(false);
+    }
+
+    let output = common::git_command(repo, cred)?
+        .args(["lfs", "pull"])
+        .output()
+        .map_err(|e| anyhow!("Cannot run git lfs, is it installed? {}", e))?;
+
+    if output.status.success() {
+        Ok(true)
+    } else {
+        Err(anyhow!(
+            "git lfs pull failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Keep the LFS files of the repository as pointer files
+///
+/// This sets `lfs.fetchexclude` to `*` in the repository config, so that neither gut nor a
+/// plain `git pull` downloads LFS objects until `gut lfs fetch` is run for the repository.
+///
+/// Returns false when the repository does not use LFS.
+pub fn lfs_skip(repo: &Repository) -> Result<bool> {
+    let dir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot skip LFS objects in a bare repository"))?;
+
+    if !uses_lfs(dir) {
+        return Ok(false);
+    }
+
+    local_config(repo)?.set_str("lfs.fetchexclude", "*")?;
+    Ok(true)
+}
+
+/// Undo `lfs_skip` and download the LFS objects of the current branch
+pub fn lfs_fetch(repo: &Repository, cred: Option<&GitCredential>) -> Result<bool> {
+    match local_config(repo)?.remove("lfs.fetchexclude") {
+        Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    lfs_pull(repo, cred)
+}
+
+fn local_config(repo: &Repository) -> Result<Config> {
+    Ok(repo.config()?.open_level(ConfigLevel::Local)?)
+}
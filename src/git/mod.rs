@@ -1,30 +1,44 @@
+pub mod apply;
 pub mod branch;
 pub mod clone;
 pub mod commit;
 pub mod common;
 pub mod diff;
 pub mod fetch;
+pub mod history;
+pub mod lfs;
 pub mod merge;
 pub mod models;
 pub mod open;
 pub mod pull;
+pub mod purge;
 pub mod push;
 pub mod rebase;
+pub mod repair;
 pub mod sha;
+pub mod signature;
 pub mod stash;
 pub mod status;
+pub mod tag;
 pub mod tree;
 
+pub use apply::*;
 pub use branch::*;
 pub use clone::Clonable;
 pub use commit::*;
 pub use fetch::*;
+pub use history::*;
+pub use lfs::*;
 pub use merge::*;
 pub use models::*;
 pub use open::*;
 pub use pull::*;
+pub use purge::*;
 pub use rebase::*;
+pub use repair::*;
 pub use sha::*;
+pub use signature::*;
 pub use stash::*;
 pub use status::*;
+pub use tag::*;
 pub use tree::*;
@@ -40,6 +40,14 @@ impl GitCredential {
     pub fn new(username: String, password: String) -> GitCredential {
         GitCredential { username, password }
     }
+
+    /// Value of an "Authorization" http header for this credential
+    pub fn basic_auth_header(&self) -> String {
+        use base64::Engine;
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let encoded = b64.encode(format!("{}:{}", self.username, self.password));
+        format!("Authorization: Basic {}", encoded)
+    }
 }
 
 impl CredentialUI for GitCredential {
@@ -0,0 +1,138 @@
+use super::common;
+use super::models::GitCredential;
+use crate::read_only;
+use anyhow::{anyhow, Result};
+use git2::{Oid, Repository};
+use std::process::{Command, Output};
+
+/// Whether `git filter-repo` is installed
+pub fn filter_repo_available() -> bool {
+    Command::new("git")
+        .args(["filter-repo", "--version"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Whether any commit reachable from a local branch or tag touches one of the paths
+pub fn history_contains(repo: &Repository, paths: &[String]) -> Result<bool> {
+    let output = common::git_command(repo, None)?
+        .args(["rev-list", "--branches", "--tags", "-1", "--"])
+        .args(paths)
+        .output()?;
+    let output = check("rev-list", output)?;
+    Ok(!output.stdout.is_empty())
+}
+
+/// Whether the history of a commit or tag touches one of the paths
+pub fn rev_contains(repo: &Repository, rev: &str, paths: &[String]) -> Result<bool> {
+    let output = common::git_command(repo, None)?
+        .args(["rev-list", "-1", rev, "--"])
+        .args(paths)
+        .output()?;
+    let output = check("rev-list", output)?;
+    Ok(!output.stdout.is_empty())
+}
+
+/// The tags on origin with the object they point to
+pub fn remote_tags(repo: &Repository, cred: Option<&GitCredential>) -> Result<Vec<(String, Oid)>> {
+    let output = common::git_command(repo, cred)?
+        .args(["ls-remote", "--tags", "--refs", "origin"])
+        .output()?;
+    let output = check("ls-remote", output)?;
+
+    let mut tags = vec![];
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((oid, name)) = line.split_once('\t') else {
+            continue;
+        };
+        if let Some(name) = name.strip_prefix("refs/tags/") {
+            tags.push((name.to_string(), Oid::from_str(oid)?));
+        }
+    }
+    Ok(tags)
+}
+
+/// Remove the paths from every commit with `git filter-repo`
+///
+/// filter-repo removes the origin remote afterwards, so that the rewritten history is not
+/// pushed by accident. It is added back here, because pushing is the point.
+pub fn purge_paths(repo: &Repository, paths: &[String]) -> Result<()> {
+    let origin = repo
+        .find_remote("origin")?
+        .url()
+        .map(|u| u.to_string())
+        .ok_or_else(|| anyhow!("The url of origin is not valid utf-8"))?;
+
+    let mut command = common::git_command(repo, None)?;
+    command.args(["filter-repo", "--force", "--invert-paths"]);
+    for path in paths {
+        command.args(["--path", path]);
+    }
+    check("filter-repo", command.output()?)?;
+
+    if repo.find_remote("origin").is_err() {
+        repo.remote("origin", &origin)?;
+    }
+    Ok(())
+}
+
+/// Force-push branches and tags, but only if origin still has the objects that we rewrote
+///
+/// The expected objects are given explicitly, because the remote tracking branches are gone
+/// after a rewrite.
+pub fn force_push_with_lease(
+    repo: &Repository,
+    branches: &[(String, Oid)],
+    tags: &[(String, Oid)],
+    cred: Option<&GitCredential>,
+) -> Result<()> {
+    let refs: Vec<_> = branches
+        .iter()
+        .map(|(branch, oid)| (format!("refs/heads/{}", branch), oid))
+        .chain(
+            tags.iter()
+                .map(|(tag, oid)| (format!("refs/tags/{}", tag), oid)),
+        )
+        .collect();
+
+    let mut command = common::git_command(repo, cred)?;
+    command.args(["push", "--porcelain", "origin"]);
+    for (name, expected) in &refs {
+        command.arg(format!("--force-with-lease={}:{}", name, expected));
+    }
+    for (name, _) in &refs {
+        command.arg(format!("{0}:{0}", name));
+    }
+    check("push", command.output()?)?;
+    Ok(())
+}
+
+/// Delete a branch on origin, but only if it still points to the commit that was checked
+pub fn delete_with_lease(
+    repo: &Repository,
+    branch: &str,
+    expected: Oid,
+    cred: Option<&GitCredential>,
+) -> Result<()> {
+    read_only::ensure_writable(&format!("delete origin/{}", branch))?;
+    let name = format!("refs/heads/{}", branch);
+    let mut command = common::git_command(repo, cred)?;
+    command.args(["push", "--porcelain", "origin"]);
+    command.arg(format!("--force-with-lease={}:{}", name, expected));
+    command.arg(format!(":{}", name));
+    check("push", command.output()?)?;
+    Ok(())
+}
+
+fn check(name: &str, output: Output) -> Result<Output> {
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(anyhow!(
+            "git {} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
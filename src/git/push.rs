@@ -1,5 +1,7 @@
 use super::common;
 use super::models::GitCredential;
+use crate::network::NetworkSettings;
+use crate::read_only;
 use git2::{BranchType, Error, Remote, Repository};
 
 pub fn push_branch(
@@ -8,14 +10,140 @@ pub fn push_branch(
     remote_name: &str,
     cred: Option<GitCredential>,
 ) -> Result<(), Error> {
+    push_refspec(repo, &common::ref_by_branch(branch), remote_name, cred)
+}
+
+/// Push a branch even when it does not contain the remote branch, e.g. after a rebase
+pub fn force_push_branch(
+    repo: &Repository,
+    branch: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<(), Error> {
+    let refspec = format!("+{}", common::ref_by_branch(branch));
+    push_refspec(repo, &refspec, remote_name, cred)
+}
+
+pub fn push_tag(
+    repo: &Repository,
+    tag: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<(), Error> {
+    let refspec = format!("refs/tags/{}:refs/tags/{}", tag, tag);
+    push_refspec(repo, &refspec, remote_name, cred)
+}
+
+/// Delete a branch on the remote, like `git push <remote> --delete <branch>`
+pub fn delete_remote_branch(
+    repo: &Repository,
+    branch: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<(), Error> {
+    let refspec = format!(":refs/heads/{}", branch);
+    push_refspec(repo, &refspec, remote_name, cred)
+}
+
+/// Push all branches of a remote and all tags to another url, e.g. a mirror on another host
+///
+/// The branches are pushed from the remote-tracking branches, so that the mirror gets what the
+/// remote has and not the local work. Branches that were deleted on the remote stay on the mirror.
+/// The push fails when the mirror rejects any of them.
+pub fn push_mirror(
+    repo: &Repository,
+    remote_name: &str,
+    url: &str,
+    cred: Option<GitCredential>,
+) -> Result<(), Error> {
+    ensure_writable(&format!("push to {}", url))?;
+
+    let prefix = format!("refs/remotes/{}/", remote_name);
+    let mut refspecs = vec![];
+    for reference in repo.references()? {
+        let reference = reference?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if let Some(branch) = name.strip_prefix(&prefix) {
+            if branch != "HEAD" {
+                refspecs.push(format!("+{}:refs/heads/{}", name, branch));
+            }
+        } else if name.starts_with("refs/tags/") {
+            refspecs.push(format!("+{}:{}", name, name));
+        }
+    }
+    if refspecs.is_empty() {
+        return Err(Error::from_str(&format!(
+            "There is no branch of {} to push",
+            remote_name
+        )));
+    }
+
+    let settings = NetworkSettings::for_url(url, repo.config().ok().as_ref());
+    if let Some(ssh_command) = &settings.ssh_command {
+        let dir = repo.workdir().unwrap_or(repo.path());
+        let mut args = vec!["push", url];
+        args.extend(refspecs.iter().map(|r| r.as_str()));
+        return common::git_with_ssh_command(dir, ssh_command, &args);
+    }
+
+    // libgit2 reports refs that the mirror rejects, e.g. protected branches, only through this
+    // callback and the push itself succeeds
+    let mut rejected = vec![];
+    {
+        let mut remote_callbacks = common::create_remote_callback(&cred)?;
+        remote_callbacks.push_update_reference(|name, status| {
+            if let Some(status) = status {
+                rejected.push(format!("{} ({})", name, status));
+            }
+            Ok(())
+        });
+
+        let mut po = git2::PushOptions::new();
+        po.remote_callbacks(remote_callbacks)
+            .proxy_options(settings.proxy_options());
+
+        repo.remote_anonymous(url)?
+            .push(&refspecs, Some(&mut po))
+            .map_err(|e| common::explain_not_found(Some(url), e))?;
+    }
+
+    if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::from_str(&format!(
+            "{} rejected {}",
+            url,
+            rejected.join(", ")
+        )))
+    }
+}
+
+fn push_refspec(
+    repo: &Repository,
+    refspec: &str,
+    remote_name: &str,
+    cred: Option<GitCredential>,
+) -> Result<(), Error> {
+    ensure_writable(&format!("push {} to {}", refspec, remote_name))?;
     let mut origin = repo.find_remote(remote_name)?;
+    let settings = common::network_settings(repo, &origin);
+
+    if let Some(ssh_command) = &settings.ssh_command {
+        let dir = repo.workdir().unwrap_or(repo.path());
+        return common::git_with_ssh_command(dir, ssh_command, &["push", remote_name, refspec]);
+    }
 
     let remote_callbacks = common::create_remote_callback(&cred)?;
 
     let mut po = git2::PushOptions::new();
-    po.remote_callbacks(remote_callbacks);
+    po.remote_callbacks(remote_callbacks)
+        .proxy_options(settings.proxy_options());
 
-    origin.push(&[&common::ref_by_branch(branch)], Some(&mut po))?;
+    origin
+        .push(&[refspec], Some(&mut po))
+        .map_err(|e| common::explain_not_found(origin.url(), e))?;
 
     Ok(())
 }
@@ -25,10 +153,11 @@ pub fn push(
     remote: &mut Remote,
     cred: Option<GitCredential>,
 ) -> Result<(), Error> {
-    let remote_callbacks = common::create_remote_callback(&cred)?;
-
-    let mut po = git2::PushOptions::new();
-    po.remote_callbacks(remote_callbacks);
+    ensure_writable(&format!(
+        "push to {}",
+        remote.name().or(remote.url()).unwrap_or_default()
+    ))?;
+    let settings = common::network_settings(repo, remote);
 
     let branches: Vec<String> = repo
         .branches(Some(BranchType::Local))
@@ -41,7 +170,24 @@ pub fn push(
 
     let refs: Vec<String> = branches.iter().map(|a| common::ref_by_branch(a)).collect();
 
-    let result = remote.push(&refs, Some(&mut po));
+    let result = if let Some(ssh_command) = &settings.ssh_command {
+        let dir = repo.workdir().unwrap_or(repo.path());
+        let name = remote.name().or(remote.url()).unwrap_or_default();
+        let mut args = vec!["push", name];
+        args.extend(refs.iter().map(|r| r.as_str()));
+        common::git_with_ssh_command(dir, ssh_command, &args)
+    } else {
+        let remote_callbacks = common::create_remote_callback(&cred)?;
+
+        let mut po = git2::PushOptions::new();
+        po.remote_callbacks(remote_callbacks)
+            .proxy_options(settings.proxy_options());
+        remote.push(&refs, Some(&mut po))
+    };
     log::debug!("Push result {:?}", result);
     Ok(())
 }
+
+fn ensure_writable(action: &str) -> Result<(), Error> {
+    read_only::ensure_writable(action).map_err(|e| Error::from_str(&e.to_string()))
+}
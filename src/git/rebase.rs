@@ -29,8 +29,9 @@ fn normal_rebase(
     remote: &git2::AnnotatedCommit,
     abort_if_conflict: bool,
 ) -> Result<RebaseStatus, git2::Error> {
-    let mut operations = repo.rebase(Some(local), Some(remote), None, None)?;
+    // Without a signature the rebase could not commit and would be left half done
     let sig = repo.signature()?;
+    let mut operations = repo.rebase(Some(local), Some(remote), None, None)?;
     while let Some(operation) = operations.next() {
         let operation = operation?;
         match operation.kind() {
@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result};
+use git2::{Repository, RepositoryState};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// Name of the operation that the repository is in the middle of, if any
+pub fn operation_in_progress(repo: &Repository) -> Option<&'static str> {
+    match repo.state() {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailboxOrRebase => Some("rebase"),
+        RepositoryState::ApplyMailbox => Some("am"),
+    }
+}
+
+/// Abort the operation that the repository is in the middle of, like `git merge --abort`
+///
+/// This runs git itself, because libgit2 cannot restore the state from before the operation
+/// for all of them.
+pub fn abort_operation(repo: &Repository) -> Result<()> {
+    let args: &[&str] = match operation_in_progress(repo) {
+        None => return Ok(()),
+        Some("bisect") => &["bisect", "reset"],
+        Some(operation) => &[operation, "--abort"],
+    };
+    let dir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot abort an operation in a bare repository"))?;
+
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| anyhow!("Cannot run git, is it installed? {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Lock files that git leaves behind when it is killed, older than `max_age`
+pub fn stale_locks(repo: &Repository, max_age: Duration) -> Vec<PathBuf> {
+    let now = SystemTime::now();
+    ["index.lock", "HEAD.lock", "config.lock", "packed-refs.lock"]
+        .iter()
+        .map(|name| repo.path().join(name))
+        .filter(|path| {
+            path.metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .map(|age| age >= max_age)
+                .unwrap_or(false)
+        })
+        .collect()
+}
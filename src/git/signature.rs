@@ -0,0 +1,121 @@
+use super::common;
+use anyhow::Result;
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub enum TagSignature {
+    /// A tag that is only a ref, it cannot carry a signature
+    Lightweight,
+    Unsigned,
+    /// Signed by one of the allowed keys, with the fingerprint of the key
+    Allowed(String),
+    /// Signed by a key that is not allowed, or that cannot be looked up
+    UnknownSigner(String),
+    /// The signature does not match the tag
+    Bad(String),
+}
+
+/// Check the signature of a tag against a list of allowed GPG fingerprints and SSH keys
+///
+/// GPG signatures are checked with the keyring of the user, SSH signatures with an allowed
+/// signers file (see `gpg.ssh.allowedSignersFile` in git-config) made from the allowed keys.
+pub fn tag_signature(
+    repo: &Repository,
+    tag: &str,
+    allowed: &[String],
+    ssh_signers: Option<&Path>,
+) -> Result<TagSignature> {
+    let target = repo
+        .find_reference(&format!("refs/tags/{}", tag))?
+        .target()
+        .ok_or_else(|| anyhow::anyhow!("Tag {} is a symbolic reference", tag))?;
+    if repo.find_tag(target).is_err() {
+        return Ok(TagSignature::Lightweight);
+    }
+
+    let odb = repo.odb()?;
+    let object = odb.read(target)?;
+    let content = String::from_utf8_lossy(object.data());
+
+    if content.contains("-----BEGIN SSH SIGNATURE-----") {
+        verify_ssh(repo, tag, ssh_signers)
+    } else if content.contains("-----BEGIN PGP SIGNATURE-----") {
+        verify_gpg(repo, tag, allowed)
+    } else {
+        Ok(TagSignature::Unsigned)
+    }
+}
+
+fn verify_gpg(repo: &Repository, tag: &str, allowed: &[String]) -> Result<TagSignature> {
+    let output = common::git_command(repo, None)?
+        .args(["verify-tag", "--raw", tag])
+        .output()?;
+    let status = String::from_utf8_lossy(&output.stderr);
+
+    for line in status.lines() {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["[GNUPG:]", "VALIDSIG", fingerprint, .., primary] => {
+                let is_allowed = allowed
+                    .iter()
+                    .map(|k| k.replace(' ', "").to_uppercase())
+                    .filter(|k| !k.is_empty())
+                    .any(|k| fingerprint.ends_with(&k) || primary.ends_with(&k));
+                return Ok(if is_allowed {
+                    TagSignature::Allowed(primary.to_string())
+                } else {
+                    TagSignature::UnknownSigner(primary.to_string())
+                });
+            }
+            ["[GNUPG:]", "NO_PUBKEY", key_id, ..] => {
+                return Ok(TagSignature::UnknownSigner(format!(
+                    "{} (not in the keyring)",
+                    key_id
+                )));
+            }
+            ["[GNUPG:]", "BADSIG", key_id, ..] => {
+                return Ok(TagSignature::Bad(format!("Bad signature by {}", key_id)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(TagSignature::Bad(status.trim().to_string()))
+}
+
+fn verify_ssh(repo: &Repository, tag: &str, ssh_signers: Option<&Path>) -> Result<TagSignature> {
+    let ssh_signers = match ssh_signers {
+        Some(path) => path,
+        None => {
+            return Ok(TagSignature::UnknownSigner(
+                "SSH key, but no SSH keys are allowed".to_string(),
+            ))
+        }
+    };
+
+    let output = common::git_command(repo, None)?
+        .arg("-c")
+        .arg(format!(
+            "gpg.ssh.allowedSignersFile={}",
+            ssh_signers.display()
+        ))
+        .args(["verify-tag", tag])
+        .output()?;
+    let message = String::from_utf8_lossy(&output.stderr);
+    // `... with ED25519 key SHA256:...`
+    let key = message
+        .split_whitespace()
+        .find(|w| w.starts_with("SHA256:"))
+        .unwrap_or("unknown key")
+        .to_string();
+
+    if output.status.success() {
+        Ok(TagSignature::Allowed(key))
+    } else if message.contains("No principal matched") {
+        Ok(TagSignature::UnknownSigner(key))
+    } else {
+        Ok(TagSignature::Bad(message.trim().to_string()))
+    }
+}
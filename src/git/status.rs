@@ -69,11 +69,25 @@ impl GitStatus {
 }
 
 pub fn status(repo: &Repository, recurse_untracked_dirs: bool) -> Result<GitStatus, Error> {
+    status_in(repo, recurse_untracked_dirs, &[])
+}
+
+/// Status of only the files that match one of the pathspecs, e.g. `src/fst/**`
+///
+/// Without pathspecs this is the status of the whole repository.
+pub fn status_in(
+    repo: &Repository,
+    recurse_untracked_dirs: bool,
+    paths: &[String],
+) -> Result<GitStatus, Error> {
     let mut opts = StatusOptions::new();
     opts.include_ignored(false)
         .include_untracked(true)
         .recurse_untracked_dirs(recurse_untracked_dirs)
         .exclude_submodules(false);
+    for path in paths {
+        opts.pathspec(path.as_str());
+    }
 
     let git_statuses = repo.statuses(Some(&mut opts))?;
 
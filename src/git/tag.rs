@@ -0,0 +1,33 @@
+use super::common;
+use anyhow::{anyhow, Result};
+use git2::{ObjectType, Repository};
+
+/// Create an annotated tag of HEAD, signed with the signing key of the git config when `sign`
+/// is set
+///
+/// libgit2 cannot sign tags, so signed tags are made by git.
+pub fn tag_head(repo: &Repository, name: &str, message: &str, sign: bool) -> Result<()> {
+    if sign {
+        let output = common::git_command(repo, None)?
+            .args(["tag", "-s", "-m", message, name])
+            .output()?;
+        return if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Cannot sign tag {}: {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        };
+    }
+
+    let head = repo.head()?.peel(ObjectType::Commit)?;
+    let sig = repo.signature()?;
+    repo.tag(name, &head, &sig, message, false)?;
+    Ok(())
+}
+
+pub fn tag_exists(repo: &Repository, name: &str) -> bool {
+    repo.find_reference(&format!("refs/tags/{}", name)).is_ok()
+}
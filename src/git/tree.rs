@@ -6,3 +6,12 @@ pub fn tree_from_commit_sha<'a>(repo: &'a Repository, sha: &str) -> Result<Tree<
     let commit = repo.find_commit(oid)?;
     commit.tree()
 }
+
+/// Paths of the files in the index, which are the files that git tracks
+pub fn tracked_files(repo: &Repository) -> Result<Vec<String>, Error> {
+    let index = repo.index()?;
+    Ok(index
+        .iter()
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+        .collect())
+}
@@ -1,7 +1,8 @@
 use super::models::*;
+use crate::read_only;
 use graphql_client::{GraphQLQuery, Response};
 use reqwest::blocking as req;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[allow(clippy::upper_case_acronyms)]
 type URI = String;
@@ -50,7 +51,7 @@ struct OrganizationMembers;
 fn query<T: Serialize + ?Sized>(token: &str, body: &T) -> Result<req::Response, reqwest::Error> {
     let client = req::Client::new();
     client
-        .post("https://api.github.com/graphql")
+        .post(super::graphql_url())
         .bearer_auth(token)
         .header("User-Agent", super::USER_AGENT)
         .json(body)
@@ -118,8 +119,16 @@ fn get_org_members_rec(
         return Err(Unauthorized.into());
     }
 
+    if let Some(e) = access_error(&res, org) {
+        return Err(e);
+    }
+
     let response_body: Response<organization_members::ResponseData> = res.json()?;
 
+    if let Some(e) = graphql_access_error(response_body.errors.as_ref(), org) {
+        return Err(e);
+    }
+
     let org_data = response_body
         .data
         .as_ref()
@@ -169,8 +178,16 @@ fn list_org_repos_rec(
         return Err(Unauthorized.into());
     }
 
+    if let Some(e) = access_error(&res, org) {
+        return Err(e);
+    }
+
     let response_body: Response<organization_repositories::ResponseData> = res.json()?;
 
+    if let Some(e) = graphql_access_error(response_body.errors.as_ref(), org) {
+        return Err(e);
+    }
+
     let org_data = response_body
         .data
         .as_ref()
@@ -228,9 +245,17 @@ fn list_org_repos_with_topics_rec(
         return Err(Unauthorized.into());
     }
 
+    if let Some(e) = access_error(&res, org) {
+        return Err(e);
+    }
+
     let response_body: Response<organization_repositories_with_topics::ResponseData> =
         res.json()?;
 
+    if let Some(e) = graphql_access_error(response_body.errors.as_ref(), org) {
+        return Err(e);
+    }
+
     let org_data = response_body
         .data
         .as_ref()
@@ -300,6 +325,10 @@ pub fn default_branch(repo: &RemoteRepo, token: &str) -> anyhow::Result<String>
 
     let response_body: Response<repository_default_branch::ResponseData> = response.json()?;
 
+    if let Some(e) = graphql_access_error(response_body.errors.as_ref(), &repo.owner) {
+        return Err(e);
+    }
+
     log::debug!("Response body {:?}", response_body);
 
     let branch: &str = response_body
@@ -317,3 +346,177 @@ pub fn default_branch(repo: &RemoteRepo, token: &str) -> anyhow::Result<String>
     log::debug!("Default branch of repository {} is: {}", repo.name, branch);
     Ok(branch.to_string())
 }
+
+/// Enable auto-merge of a pull request, so GitHub merges it as soon as its checks pass
+///
+/// `merge_method` is MERGE, SQUASH or REBASE. The mutation is not in the schema of
+/// github.graphql, so the query is written out instead of derived.
+pub fn enable_auto_merge(
+    pull_request_id: &str,
+    merge_method: &str,
+    token: &str,
+) -> anyhow::Result<()> {
+    read_only::ensure_writable(&format!("enable auto-merge of {}", pull_request_id))?;
+
+    let body = serde_json::json!({
+        "query": "mutation($id: ID!, $method: PullRequestMergeMethod!) { \
+            enablePullRequestAutoMerge(input: {pullRequestId: $id, mergeMethod: $method}) { \
+            clientMutationId } }",
+        "variables": { "id": pull_request_id, "method": merge_method },
+    });
+
+    let response = query(token, &body)?;
+
+    let response_status = response.status();
+    if response_status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+
+    if !response_status.is_success() {
+        return Err(Unsuccessful(response_status).into());
+    }
+
+    let response_body: Response<serde_json::Value> = response.json()?;
+    match response_body.errors {
+        Some(errors) if !errors.is_empty() => {
+            let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+            anyhow::bail!("{}", messages.join("; "))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A Projects v2 board of an organisation
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub title: String,
+    pub fields: Vec<ProjectField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectField {
+    pub id: String,
+    pub name: String,
+    /// TEXT, NUMBER, DATE, SINGLE_SELECT, ITERATION, or one of the built in fields
+    #[serde(rename = "dataType")]
+    pub data_type: String,
+    /// The options of a single select field
+    #[serde(default)]
+    pub options: Vec<ProjectFieldOption>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectFieldOption {
+    pub id: String,
+    pub name: String,
+}
+
+/// The Projects v2 board with a number in an organisation, with its first 100 fields
+///
+/// The Projects v2 api is not in the schema of github.graphql, so the queries are written out.
+pub fn get_project(org: &str, number: u64, token: &str) -> anyhow::Result<Project> {
+    let body = serde_json::json!({
+        "query": "query($org: String!, $number: Int!) { organization(login: $org) { \
+            projectV2(number: $number) { id title fields(first: 100) { nodes { \
+            ... on ProjectV2FieldCommon { id name dataType } \
+            ... on ProjectV2SingleSelectField { options { id name } } } } } } }",
+        "variables": { "org": org, "number": number },
+    });
+
+    let data = run_query(&body, token)?;
+    let project = &data["organization"]["projectV2"];
+    if project.is_null() {
+        anyhow::bail!("There is no project {} in organisation {}", number, org);
+    }
+
+    let fields = project["fields"]["nodes"]
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter(|n| n.get("id").is_some())
+                .map(|n| serde_json::from_value(n.clone()))
+                .collect::<Result<Vec<ProjectField>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(Project {
+        id: project["id"].as_str().unwrap_or_default().to_string(),
+        title: project["title"].as_str().unwrap_or_default().to_string(),
+        fields,
+    })
+}
+
+/// Add an issue or a pull request to a project, and return the id of its item
+///
+/// Content that is already on the board keeps its item.
+pub fn add_project_item(project_id: &str, content_id: &str, token: &str) -> anyhow::Result<String> {
+    read_only::ensure_writable(&format!("add {} to project {}", content_id, project_id))?;
+
+    let body = serde_json::json!({
+        "query": "mutation($project: ID!, $content: ID!) { \
+            addProjectV2ItemById(input: {projectId: $project, contentId: $content}) { \
+            item { id } } }",
+        "variables": { "project": project_id, "content": content_id },
+    });
+
+    let data = run_query(&body, token)?;
+    data["addProjectV2ItemById"]["item"]["id"]
+        .as_str()
+        .map(|id| id.to_string())
+        .ok_or_else(|| InvalidRepoResponse.into())
+}
+
+/// Set the value of a field of a project item
+///
+/// `value` is the input of the field type, e.g. `{"text": "Campaign 2025"}` or
+/// `{"singleSelectOptionId": "..."}`.
+pub fn set_project_field(
+    project_id: &str,
+    item_id: &str,
+    field_id: &str,
+    value: &serde_json::Value,
+    token: &str,
+) -> anyhow::Result<()> {
+    read_only::ensure_writable(&format!("set a field of item {}", item_id))?;
+
+    let body = serde_json::json!({
+        "query": "mutation($project: ID!, $item: ID!, $field: ID!, \
+            $value: ProjectV2FieldValue!) { updateProjectV2ItemFieldValue(input: \
+            {projectId: $project, itemId: $item, fieldId: $field, value: $value}) { \
+            projectV2Item { id } } }",
+        "variables": {
+            "project": project_id,
+            "item": item_id,
+            "field": field_id,
+            "value": value,
+        },
+    });
+
+    run_query(&body, token).map(|_| ())
+}
+
+/// The data of a query that is written out, or its errors
+fn run_query<T: Serialize + ?Sized>(body: &T, token: &str) -> anyhow::Result<serde_json::Value> {
+    let response = query(token, body)?;
+
+    let response_status = response.status();
+    if response_status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized.into());
+    }
+
+    if !response_status.is_success() {
+        return Err(Unsuccessful(response_status).into());
+    }
+
+    let response_body: Response<serde_json::Value> = response.json()?;
+    match response_body.errors {
+        Some(errors) if !errors.is_empty() => {
+            let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+            anyhow::bail!("{}", messages.join("; "))
+        }
+        _ => response_body.data.ok_or_else(|| InvalidRepoResponse.into()),
+    }
+}
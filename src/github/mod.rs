@@ -1,9 +1,39 @@
 pub mod graphql;
 pub mod models;
+pub mod pacer;
 pub mod rest;
 
 pub use graphql::*;
 pub use models::*;
+pub use pacer::*;
 pub use rest::*;
 
+use crate::config::{Config, DEFAULT_API_URL};
+use std::sync::OnceLock;
+
 pub(crate) static USER_AGENT: &str = concat!("gut ", env!("CARGO_PKG_VERSION"));
+
+/// Base url of the GitHub api, from GUT_API_URL or the config file
+pub(crate) fn api_url() -> &'static str {
+    static API_URL: OnceLock<String> = OnceLock::new();
+    API_URL.get_or_init(Config::api_url)
+}
+
+/// GitHub Enterprise serves the REST api under /api/v3 and GraphQL under /api/graphql
+pub(crate) fn graphql_url() -> String {
+    let api_url = api_url();
+    match api_url.strip_suffix("/v3") {
+        Some(base) => format!("{}/graphql", base),
+        None => format!("{}/graphql", api_url),
+    }
+}
+
+/// Base url of the GitHub web pages, GitHub Enterprise serves them on the host of the api
+pub(crate) fn web_url() -> String {
+    let api_url = api_url();
+    if api_url == DEFAULT_API_URL {
+        "https://github.com".to_string()
+    } else {
+        api_url.trim_end_matches("/api/v3").to_string()
+    }
+}
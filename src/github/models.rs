@@ -1,3 +1,4 @@
+use reqwest::blocking as req;
 use reqwest::StatusCode;
 use std::cmp::Ord;
 use std::cmp::Ordering;
@@ -64,6 +65,11 @@ pub struct Unauthorized;
 #[error("Unsuccessful request with status code: {0}")]
 pub struct Unsuccessful(pub StatusCode);
 
+/// A request that was refused because of the primary or a secondary rate limit
+#[derive(thiserror::Error, Debug)]
+#[error("The rate limit is reached, retry in {} seconds", .0.as_secs())]
+pub struct RateLimited(pub std::time::Duration);
+
 #[derive(thiserror::Error, Debug)]
 #[error("invalid response when fetching repositories")]
 pub struct InvalidRepoResponse;
@@ -79,3 +85,111 @@ pub struct NoMembersFound;
 #[derive(thiserror::Error, Debug)]
 #[error("No default branch")]
 pub struct NoDefaultBranch;
+
+#[derive(thiserror::Error, Debug)]
+#[error("The organisation {org} enforces SAML single sign-on and your token is not authorized for it. Authorize the token for {org} under \"Configure SSO\" at https://github.com/settings/tokens{}", url.as_ref().map(|u| format!(" or visit {}", u)).unwrap_or_default())]
+pub struct SsoRequired {
+    pub org: String,
+    pub url: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Your token is missing the scope(s) \"{required}\" needed to access {org} (the token has \"{granted}\"). Add the scope(s) at https://github.com/settings/tokens and run `gut init` again if you create a new token")]
+pub struct MissingScope {
+    pub org: String,
+    pub required: String,
+    pub granted: String,
+}
+
+/// Detect the responses that fail because of SAML SSO enforcement or a token without the
+/// needed scopes. GitHub answers those with 403, or with 404 for private repositories.
+pub fn access_error(response: &req::Response, org: &str) -> Option<anyhow::Error> {
+    let status = response.status();
+    if status != StatusCode::FORBIDDEN && status != StatusCode::NOT_FOUND {
+        return None;
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+
+    if let Some(sso) = header("x-github-sso") {
+        if sso.starts_with("required") {
+            let url = sso.split("url=").nth(1).map(|u| u.trim().to_string());
+            return Some(
+                SsoRequired {
+                    org: org.to_string(),
+                    url,
+                }
+                .into(),
+            );
+        }
+    }
+
+    let granted = header("x-oauth-scopes")?;
+    let granted_scopes: Vec<_> = granted.split(',').map(|s| s.trim()).collect();
+    let accepted = header("x-accepted-oauth-scopes").unwrap_or_default();
+    let accepted_scopes: Vec<_> = accepted
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let has_scope = |scope: &str| {
+        granted_scopes
+            .iter()
+            .any(|g| *g == scope || scope.starts_with(&format!("{}:", g)))
+    };
+
+    let required = if !accepted_scopes.is_empty() && !accepted_scopes.iter().any(|s| has_scope(s)) {
+        accepted_scopes.join(", ")
+    } else if status == StatusCode::NOT_FOUND && !has_scope("repo") {
+        // Private repositories are reported as not found without the repo scope
+        "repo".to_string()
+    } else {
+        return None;
+    };
+
+    Some(
+        MissingScope {
+            org: org.to_string(),
+            required,
+            granted,
+        }
+        .into(),
+    )
+}
+
+/// Detect a GraphQL response whose data was withheld because of SAML SSO enforcement
+pub fn graphql_access_error(
+    errors: Option<&Vec<graphql_client::Error>>,
+    org: &str,
+) -> Option<anyhow::Error> {
+    errors?
+        .iter()
+        .any(|e| e.message.contains("SAML enforcement"))
+        .then(|| {
+            SsoRequired {
+                org: org.to_string(),
+                url: None,
+            }
+            .into()
+        })
+}
+
+/// The organisation or user that a GitHub api url is about
+pub fn owner_from_url(url: &reqwest::Url) -> String {
+    let mut segments = url.path_segments().into_iter().flatten();
+    while let Some(segment) = segments.next() {
+        if ["repos", "orgs", "users"].contains(&segment) {
+            if let Some(owner) = segments.next() {
+                return owner.to_string();
+            }
+        }
+    }
+    "the organisation".to_string()
+}
@@ -0,0 +1,124 @@
+use super::models::RateLimited;
+use anyhow::{anyhow, Result};
+use std::time::{Duration, Instant};
+
+const MIN_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+const MAX_ATTEMPTS: usize = 8;
+
+/// Spaces the write requests of a bulk command so that it keeps below the secondary rate limits
+///
+/// GitHub asks for write requests to be made one after another and at least a second apart.
+/// Whenever a request is refused because of a rate limit, the pacer waits as long as GitHub
+/// asks, doubles the delay between requests and repeats the request. After successful
+/// requests the delay shrinks back towards a second.
+pub struct Pacer {
+    delay: Duration,
+    min_delay: Duration,
+    last: Option<Instant>,
+}
+
+impl Pacer {
+    pub fn new() -> Pacer {
+        Pacer::with_min_delay(MIN_DELAY)
+    }
+
+    fn with_min_delay(min_delay: Duration) -> Pacer {
+        Pacer {
+            delay: min_delay,
+            min_delay,
+            last: None,
+        }
+    }
+
+    /// Make a request when its turn has come, repeating it while it is rate limited
+    pub fn run<T>(&mut self, mut request: impl FnMut() -> Result<T>) -> Result<T> {
+        for _ in 0..MAX_ATTEMPTS {
+            self.wait_turn();
+            match request() {
+                Ok(value) => {
+                    self.speed_up();
+                    return Ok(value);
+                }
+                Err(e) => match e.downcast_ref::<RateLimited>() {
+                    Some(RateLimited(wait)) => {
+                        self.slow_down();
+                        eprintln!(
+                            "The rate limit is reached, waiting {} seconds",
+                            wait.as_secs()
+                        );
+                        std::thread::sleep(*wait);
+                    }
+                    None => return Err(e),
+                },
+            }
+        }
+        Err(anyhow!(
+            "The rate limit is still reached after {} attempts",
+            MAX_ATTEMPTS
+        ))
+    }
+
+    fn wait_turn(&mut self) {
+        if let Some(last) = self.last {
+            let elapsed = last.elapsed();
+            if elapsed < self.delay {
+                std::thread::sleep(self.delay - elapsed);
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+
+    fn slow_down(&mut self) {
+        self.delay = (self.delay * 2).clamp(self.min_delay, MAX_DELAY);
+    }
+
+    fn speed_up(&mut self) {
+        self.delay = self.delay.mul_f64(0.8).max(self.min_delay);
+    }
+}
+
+impl Default for Pacer {
+    fn default() -> Pacer {
+        Pacer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer_retries_rate_limited_requests() {
+        let mut pacer = Pacer::with_min_delay(Duration::ZERO);
+        let mut attempts = 0;
+        let result = pacer.run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(RateLimited(Duration::ZERO).into())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let result: Result<()> = pacer.run(|| Err(anyhow!("Not found")));
+        assert_eq!(result.unwrap_err().to_string(), "Not found");
+    }
+
+    #[test]
+    fn test_pacer_delay() {
+        let mut pacer = Pacer::new();
+        pacer.slow_down();
+        pacer.slow_down();
+        assert_eq!(pacer.delay, Duration::from_secs(4));
+        for _ in 0..10 {
+            pacer.slow_down();
+        }
+        assert_eq!(pacer.delay, MAX_DELAY);
+        for _ in 0..100 {
+            pacer.speed_up();
+        }
+        assert_eq!(pacer.delay, MIN_DELAY);
+    }
+}
@@ -1,23 +1,26 @@
 use super::models;
 use super::models::RemoteRepo;
+use crate::org_settings::OrgSettings;
+use crate::read_only;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use reqwest::{blocking as req, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-fn patch<T: Serialize + ?Sized>(
-    url: &str,
-    body: &T,
-    token: &str,
-) -> Result<req::Response, reqwest::Error> {
+fn patch<T: Serialize + ?Sized>(url: &str, body: &T, token: &str) -> Result<req::Response> {
     log::debug!("Patch: {}", url);
+    read_only::ensure_writable(&format!("PATCH {}", url))?;
     let client = req::Client::new();
-    client
+    let response = client
         .patch(url)
         .bearer_auth(token)
         .header("User-Agent", super::USER_AGENT)
         .header("Accept", "application/vnd.github.v3+json")
         .json(body)
-        .send()
+        .send()?;
+    Ok(response)
 }
 
 fn get(url: &str, token: &str, accept: Option<&str>) -> Result<req::Response, reqwest::Error> {
@@ -37,44 +40,46 @@ fn put<T: Serialize + ?Sized>(
     body: &T,
     token: &str,
     accept: Option<&str>,
-) -> Result<req::Response, reqwest::Error> {
+) -> Result<req::Response> {
     let client = req::Client::new();
     let accept = accept.unwrap_or("application/vnd.github.v3+json");
     log::debug!("PUT: {} with accept: {}", url, accept);
-    client
+    read_only::ensure_writable(&format!("PUT {}", url))?;
+    let response = client
         .put(url)
         .bearer_auth(token)
         .header("User-Agent", super::USER_AGENT)
         .header("Accept", accept)
         .json(body)
-        .send()
+        .send()?;
+    Ok(response)
 }
 
-fn post<T: Serialize + ?Sized>(
-    url: &str,
-    body: &T,
-    token: &str,
-) -> Result<req::Response, reqwest::Error> {
+fn post<T: Serialize + ?Sized>(url: &str, body: &T, token: &str) -> Result<req::Response> {
     log::debug!("POST: {}", url);
+    read_only::ensure_writable(&format!("POST {}", url))?;
     let client = req::Client::new();
-    client
+    let response = client
         .post(url)
         .bearer_auth(token)
         .header("User-Agent", super::USER_AGENT)
         .header("Accept", "application/vnd.github.v3+json")
         .json(body)
-        .send()
+        .send()?;
+    Ok(response)
 }
 
-fn delete(url: &str, token: &str) -> Result<req::Response, reqwest::Error> {
+fn delete(url: &str, token: &str) -> Result<req::Response> {
     log::debug!("DELETE: {}", url);
+    read_only::ensure_writable(&format!("DELETE {}", url))?;
     let client = req::Client::new();
-    client
+    let response = client
         .delete(url)
         .bearer_auth(token)
         .header("User-Agent", super::USER_AGENT)
         .header("Accept", "application/vnd.github.v3+json")
-        .send()
+        .send()?;
+    Ok(response)
 }
 
 #[derive(Serialize, Debug)]
@@ -134,15 +139,37 @@ impl UpdateRepoBody {
 }
 
 pub fn set_default_branch(repo: &RemoteRepo, branch: &str, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/repos/{}/{}", repo.owner, repo.name);
+    let url = format!("{}/repos/{}/{}", super::api_url(), repo.owner, repo.name);
     let body = UpdateRepoBody::default_branch(branch);
     let response = patch(&url, &body, token)?;
 
     process_response(&response).map(|_| ())
 }
 
+/// Rename a branch, GitHub retargets open pull requests and updates the default branch
+pub fn rename_branch(repo: &RemoteRepo, branch: &str, new_name: &str, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/branches/{}/rename",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
+    );
+    let body = RenameBranchBody {
+        new_name: new_name.to_string(),
+    };
+    let response = post(&url, &body, token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+#[derive(Serialize, Debug)]
+struct RenameBranchBody {
+    new_name: String,
+}
+
 pub fn set_repo_visibility(repo: &RemoteRepo, is_private: bool, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/repos/{}/{}", repo.owner, repo.name);
+    let url = format!("{}/repos/{}/{}", super::api_url(), repo.owner, repo.name);
     let body = UpdateRepoBody::repo_visibility(is_private);
     let response = patch(&url, &body, token)?;
 
@@ -150,7 +177,7 @@ pub fn set_repo_visibility(repo: &RemoteRepo, is_private: bool, token: &str) ->
 }
 
 pub fn set_repo_name(repo: &RemoteRepo, name: &str, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/repos/{}/{}", repo.owner, repo.name);
+    let url = format!("{}/repos/{}/{}", super::api_url(), repo.owner, repo.name);
     let body = UpdateRepoBody::name(name);
     let response = patch(&url, &body, token)?;
 
@@ -163,7 +190,7 @@ pub fn set_repo_metadata(
     homepage: Option<&str>,
     token: &str,
 ) -> Result<()> {
-    let url = format!("https://api.github.com/repos/{}/{}", repo.owner, repo.name);
+    let url = format!("{}/repos/{}/{}", super::api_url(), repo.owner, repo.name);
     let body = UpdateRepoBody::metadata(des, homepage);
     let response = patch(&url, &body, token)?;
 
@@ -203,8 +230,11 @@ struct Restrictions {
 
 pub fn set_protected_branch(repo: &RemoteRepo, branch: &str, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/branches/{}/protection",
-        repo.owner, repo.name, branch
+        "{}/repos/{}/{}/branches/{}/protection",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
     );
     let body = ProtectedBranch {
         required_status_checks: None,
@@ -230,8 +260,11 @@ pub fn set_protected_branch(repo: &RemoteRepo, branch: &str, token: &str) -> Res
 
 pub fn set_unprotected_branch(repo: &RemoteRepo, branch: &str, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/branches/{}/protection",
-        repo.owner, repo.name, branch
+        "{}/repos/{}/{}/branches/{}/protection",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
     );
 
     let response = delete(&url, token)?;
@@ -239,6 +272,177 @@ pub fn set_unprotected_branch(repo: &RemoteRepo, branch: &str, token: &str) -> R
     process_response(&response).map(|_| ())
 }
 
+/// Returns None when the branch is not protected
+pub fn get_branch_protection(
+    repo: &RemoteRepo,
+    branch: &str,
+    token: &str,
+) -> Result<Option<serde_json::Value>> {
+    let url = format!(
+        "{}/repos/{}/{}/branches/{}/protection",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
+    );
+
+    let response = get(&url, token, None)?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map(Some).map_err(Into::into)
+}
+
+#[derive(Serialize, Debug)]
+struct LockedBranch {
+    required_status_checks: Option<RequiredStatusCheck>,
+    enforce_admins: bool,
+    required_pull_request_reviews: Option<RequiredPullRequestReviews>,
+    restrictions: Option<Restrictions>,
+    lock_branch: bool,
+    allow_force_pushes: bool,
+    allow_deletions: bool,
+}
+
+/// Make a branch read-only for everyone, including admins
+pub fn set_locked_branch(repo: &RemoteRepo, branch: &str, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/branches/{}/protection",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
+    );
+    let body = LockedBranch {
+        required_status_checks: None,
+        enforce_admins: true,
+        required_pull_request_reviews: None,
+        restrictions: None,
+        lock_branch: true,
+        allow_force_pushes: false,
+        allow_deletions: false,
+    };
+
+    let response = put(&url, &body, token, None)?;
+
+    process_response(&response).map(|_| ())
+}
+
+/// Put back a protection as it was returned by `get_branch_protection`
+pub fn restore_branch_protection(
+    repo: &RemoteRepo,
+    branch: &str,
+    protection: &serde_json::Value,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/branches/{}/protection",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        branch
+    );
+    let body = protection_update_body(protection);
+
+    log::debug!("Body {}", body);
+
+    let response = put(&url, &body, token, None)?;
+    process_response(&response).map(|_| ())?;
+
+    // Signed commits have their own endpoint and are not part of the body above
+    let signatures_url = format!("{}/required_signatures", url);
+    let response = if protection["required_signatures"]["enabled"].as_bool() == Some(true) {
+        post(&signatures_url, &serde_json::json!({}), token)?
+    } else {
+        delete(&signatures_url, token)?
+    };
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    process_response(&response).map(|_| ())
+}
+
+/// The protection that GitHub returns has a different shape than the one it accepts
+fn protection_update_body(protection: &serde_json::Value) -> serde_json::Value {
+    use serde_json::{json, Value};
+
+    let enabled = |key: &str| protection[key]["enabled"].as_bool().unwrap_or(false);
+    let names = |list: &Value, field: &str| -> Vec<Value> {
+        list.as_array()
+            .map(|l| l.iter().map(|i| i[field].clone()).collect())
+            .unwrap_or_default()
+    };
+    let restrictions = |r: &Value| -> Value {
+        if r.is_object() {
+            json!({
+                "users": names(&r["users"], "login"),
+                "teams": names(&r["teams"], "slug"),
+                "apps": names(&r["apps"], "slug"),
+            })
+        } else {
+            Value::Null
+        }
+    };
+
+    let checks = &protection["required_status_checks"];
+    let required_status_checks = if checks.is_object() {
+        let list: Vec<Value> = match checks["checks"].as_array() {
+            Some(list) => list
+                .iter()
+                .map(|c| match c["app_id"].as_i64() {
+                    Some(app_id) => json!({ "context": c["context"], "app_id": app_id }),
+                    None => json!({ "context": c["context"] }),
+                })
+                .collect(),
+            None => checks["contexts"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|c| json!({ "context": c }))
+                .collect(),
+        };
+        json!({ "strict": checks["strict"].as_bool().unwrap_or(false), "checks": list })
+    } else {
+        Value::Null
+    };
+
+    let reviews = &protection["required_pull_request_reviews"];
+    let required_pull_request_reviews = if reviews.is_object() {
+        let mut body = json!({
+            "dismiss_stale_reviews": reviews["dismiss_stale_reviews"].as_bool().unwrap_or(false),
+            "require_code_owner_reviews": reviews["require_code_owner_reviews"].as_bool().unwrap_or(false),
+            "required_approving_review_count": reviews["required_approving_review_count"].as_u64().unwrap_or(0),
+            "require_last_push_approval": reviews["require_last_push_approval"].as_bool().unwrap_or(false),
+        });
+        for key in ["dismissal_restrictions", "bypass_pull_request_allowances"] {
+            if reviews[key].is_object() {
+                body[key] = restrictions(&reviews[key]);
+            }
+        }
+        body
+    } else {
+        Value::Null
+    };
+
+    json!({
+        "required_status_checks": required_status_checks,
+        "enforce_admins": enabled("enforce_admins"),
+        "required_pull_request_reviews": required_pull_request_reviews,
+        "restrictions": restrictions(&protection["restrictions"]),
+        "required_linear_history": enabled("required_linear_history"),
+        "allow_force_pushes": enabled("allow_force_pushes"),
+        "allow_deletions": enabled("allow_deletions"),
+        "block_creations": enabled("block_creations"),
+        "required_conversation_resolution": enabled("required_conversation_resolution"),
+        "lock_branch": enabled("lock_branch"),
+        "allow_fork_syncing": enabled("allow_fork_syncing"),
+    })
+}
+
 pub fn create_team(
     org: &str,
     team: &str,
@@ -247,7 +451,7 @@ pub fn create_team(
     is_secret: bool,
     token: &str,
 ) -> Result<CreateTeamResponse> {
-    let url = format!("https://api.github.com/orgs/{}/teams", org);
+    let url = format!("{}/orgs/{}/teams", super::api_url(), org);
     let privacy = if is_secret {
         "secret".to_string()
     } else {
@@ -291,7 +495,7 @@ pub struct CreateTeamResponse {
 }
 
 pub fn remove_user_from_org(org: &str, user: &str, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/orgs/{}/memberships/{}", org, user);
+    let url = format!("{}/orgs/{}/memberships/{}", super::api_url(), org, user);
 
     let response = delete(&url, token)?;
 
@@ -300,8 +504,11 @@ pub fn remove_user_from_org(org: &str, user: &str, token: &str) -> Result<()> {
 
 pub fn remove_user_from_team(org: &str, team: &str, user: &str, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/orgs/{}/teams/{}/memberships/{}",
-        org, team, user
+        "{}/orgs/{}/teams/{}/memberships/{}",
+        super::api_url(),
+        org,
+        team,
+        user
     );
 
     let response = delete(&url, token)?;
@@ -312,8 +519,11 @@ pub fn remove_user_from_team(org: &str, team: &str, user: &str, token: &str) ->
 // https://developer.github.com/v3/teams/members/#add-or-update-team-membership
 pub fn add_user_to_team(org: &str, team: &str, role: &str, user: &str, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/orgs/{}/teams/{}/memberships/{}",
-        org, team, user
+        "{}/orgs/{}/teams/{}/memberships/{}",
+        super::api_url(),
+        org,
+        team,
+        user
     );
 
     let body = AddUserToOrgBody {
@@ -326,7 +536,7 @@ pub fn add_user_to_team(org: &str, team: &str, role: &str, user: &str, token: &s
 }
 
 pub fn get_teams(org: &str, token: &str) -> Result<Vec<Team>> {
-    let url = format!("https://api.github.com/orgs/{}/teams", org);
+    let url = format!("{}/orgs/{}/teams", super::api_url(), org);
 
     let response = get(&url, token, None)?;
 
@@ -335,6 +545,35 @@ pub fn get_teams(org: &str, token: &str) -> Result<Vec<Team>> {
     response.json().map_err(Into::into)
 }
 
+/// Logins of the members of a team, also of its child teams
+pub fn get_team_members(org: &str, team_slug: &str, token: &str) -> Result<Vec<String>> {
+    let mut members = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/orgs/{}/teams/{}/members?per_page=100&page={}",
+            super::api_url(),
+            org,
+            team_slug,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<PullRequestUser> = response.json()?;
+        let is_last_page = body.len() < 100;
+        members.extend(body.into_iter().map(|u| u.login));
+
+        if is_last_page {
+            return Ok(members);
+        }
+        page += 1;
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
 pub struct Team {
@@ -350,7 +589,7 @@ pub fn invite_user_to_org(
     token: &str,
     teams: &[i32],
 ) -> Result<()> {
-    let url = format!("https://api.github.com/orgs/{}/invitations", org);
+    let url = format!("{}/orgs/{}/invitations", super::api_url(), org);
 
     let body = InviteUserToOrgBody {
         email: email.to_string(),
@@ -371,7 +610,7 @@ struct InviteUserToOrgBody {
 }
 
 pub fn add_user_to_org(org: &str, role: &str, user: &str, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/orgs/{}/memberships/{}", org, user);
+    let url = format!("{}/orgs/{}/memberships/{}", super::api_url(), org, user);
 
     let body = AddUserToOrgBody {
         role: role.to_string(),
@@ -396,8 +635,10 @@ pub fn create_discusstion(
     token: &str,
 ) -> Result<CreateDiscussionResponse> {
     let url = format!(
-        "https://api.github.com/orgs/{}/teams/{}/discussions",
-        org, team
+        "{}/orgs/{}/teams/{}/discussions",
+        super::api_url(),
+        org,
+        team
     );
 
     let body = CreateDiscussionBody {
@@ -444,8 +685,12 @@ pub fn set_team_permission(
     token: &str,
 ) -> Result<()> {
     let url = format!(
-        "https://api.github.com/orgs/{}/teams/{}/repos/{}/{}",
-        org, team, owner, repo
+        "{}/orgs/{}/teams/{}/repos/{}/{}",
+        super::api_url(),
+        org,
+        team,
+        owner,
+        repo
     );
 
     let body = SetTeamPermissionBody {
@@ -468,7 +713,7 @@ pub fn create_org_repo(
     public: bool,
     token: &str,
 ) -> Result<CreateRepoResponse> {
-    let url = format!("https://api.github.com/orgs/{}/repos", org);
+    let url = format!("{}/orgs/{}/repos", super::api_url(), org);
 
     let body = CreateRepoBody {
         name: name.to_string(),
@@ -507,7 +752,7 @@ pub struct CreateRepoResponse {
 }
 
 pub fn delete_repo(owner: &str, repo: &str, token: &str) -> Result<()> {
-    let url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+    let url = format!("{}/repos/{}/{}", super::api_url(), owner, repo);
 
     let response = delete(&url, token)?;
 
@@ -517,8 +762,10 @@ pub fn delete_repo(owner: &str, repo: &str, token: &str) -> Result<()> {
 // https://developer.github.com/v3/repos/#replace-all-repository-topics
 pub fn set_topics(repo: &RemoteRepo, topics: &[String], token: &str) -> Result<Vec<String>> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/topics",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/topics",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let body = SetTopicsBody {
@@ -532,6 +779,10 @@ pub fn set_topics(repo: &RemoteRepo, topics: &[String], token: &str) -> Result<V
         Some("application/vnd.github.mercy-preview+json"),
     )?;
 
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
     let status = response.status();
 
     if status == StatusCode::UNAUTHORIZED {
@@ -548,8 +799,10 @@ pub fn set_topics(repo: &RemoteRepo, topics: &[String], token: &str) -> Result<V
 
 pub fn get_topics(repo: &RemoteRepo, token: &str) -> Result<Vec<String>> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/topics",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/topics",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let response = get(
@@ -558,6 +811,10 @@ pub fn get_topics(repo: &RemoteRepo, token: &str) -> Result<Vec<String>> {
         Some("application/vnd.github.mercy-preview+json"),
     )?;
 
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
     let status = response.status();
 
     if status == StatusCode::UNAUTHORIZED {
@@ -584,8 +841,10 @@ pub struct TopicsResponse {
 
 pub fn transfer_repo(repo: &RemoteRepo, new_owner: &str, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/transfer",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/transfer",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
     let body = TransferBody {
         new_owner: new_owner.to_string(),
@@ -601,8 +860,10 @@ struct TransferBody {
 
 pub fn get_public_key(repo: &RemoteRepo, token: &str) -> Result<PublicKey> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/actions/secrets/public-key",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/actions/secrets/public-key",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let response = get(&url, token, None)?;
@@ -635,8 +896,11 @@ pub fn set_secret(
     token: &str,
 ) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/actions/secrets/{}",
-        repo.owner, repo.name, name
+        "{}/repos/{}/{}/actions/secrets/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        name
     );
 
     let body = SetSecretBody {
@@ -656,8 +920,10 @@ struct SetSecretBody {
 
 pub fn get_hooks(repo: &RemoteRepo, token: &str) -> Result<Vec<usize>> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/hooks",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/hooks",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let response = get(&url, token, None)?;
@@ -684,8 +950,11 @@ struct HookResponse {
 
 pub fn delete_hook(repo: &RemoteRepo, id: usize, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/hooks/{}",
-        repo.owner, repo.name, id,
+        "{}/repos/{}/{}/hooks/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        id,
     );
 
     let response = delete(&url, token)?;
@@ -701,8 +970,10 @@ pub fn create_hook(
     token: &str,
 ) -> Result<CreateHookResponse> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/hooks",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/hooks",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let config = CreateHookConfig {
@@ -759,8 +1030,12 @@ pub fn add_repo_to_team(
     token: &str,
 ) -> Result<()> {
     let url = format!(
-        "https://api.github.com/orgs/{}/teams/{}/repos/{}/{}",
-        repo.owner, team, repo.owner, repo.name
+        "{}/orgs/{}/teams/{}/repos/{}/{}",
+        super::api_url(),
+        repo.owner,
+        team,
+        repo.owner,
+        repo.name
     );
     let body = SetRepoToTeamBody {
         permission: permission.to_string(),
@@ -778,8 +1053,10 @@ struct SetRepoToTeamBody {
 
 pub fn get_repo_workflow_runs(repo: &RemoteRepo, token: &str) -> Result<Vec<Workflow>> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/actions/runs",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/actions/runs",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     let response = get(&url, token, None)?;
@@ -801,8 +1078,11 @@ pub fn get_repo_workflow_runs(repo: &RemoteRepo, token: &str) -> Result<Vec<Work
 
 pub fn get_workflow_runs(repo: &RemoteRepo, workflow: &str, token: &str) -> Result<Vec<Workflow>> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/actions/workflows/{}/runs",
-        repo.owner, repo.name, workflow
+        "{}/repos/{}/{}/actions/workflows/{}/runs",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        workflow
     );
 
     let response = get(&url, token, None)?;
@@ -824,6 +1104,7 @@ pub fn get_workflow_runs(repo: &RemoteRepo, workflow: &str, token: &str) -> Resu
 
 #[derive(Deserialize, Debug)]
 struct WorkflowResponse {
+    #[serde(rename = "total_count")]
     _total_count: usize,
     workflow_runs: Vec<Workflow>,
 }
@@ -832,17 +1113,58 @@ struct WorkflowResponse {
 #[allow(dead_code)]
 pub struct Workflow {
     pub id: usize,
+    pub name: Option<String>,
+    pub head_branch: Option<String>,
     pub html_url: String,
     pub status: String,
+    pub conclusion: Option<String>,
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The most recent run on a branch of one workflow, or of any workflow of the repository
+pub fn get_latest_workflow_run(
+    repo: &RemoteRepo,
+    workflow: Option<&str>,
+    branch: &str,
+    token: &str,
+) -> Result<Option<Workflow>> {
+    let runs = match workflow {
+        Some(wf) => format!("workflows/{}/runs", wf),
+        None => "runs".to_string(),
+    };
+    let url = format!(
+        "{}/repos/{}/{}/actions/{}?branch={}&per_page=1",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        runs,
+        branch
+    );
+
+    let response = get(&url, token, None)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    let body: WorkflowResponse = response.json()?;
+    Ok(body.workflow_runs.into_iter().next())
 }
 
 pub fn rerun_a_workflow(repo: &RemoteRepo, id: usize, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/actions/runs/{}/rerun",
-        repo.owner, repo.name, id
+        "{}/repos/{}/{}/actions/runs/{}/rerun",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        id
     );
 
     println!("url {}", url);
+    read_only::ensure_writable(&format!("POST {}", url))?;
 
     let client = req::Client::new();
     let response = client
@@ -858,8 +1180,10 @@ pub fn rerun_a_workflow(repo: &RemoteRepo, id: usize, token: &str) -> Result<()>
 
 pub fn send_a_dispatch(repo: &RemoteRepo, token: &str) -> Result<()> {
     let url = format!(
-        "https://api.github.com/repos/{}/{}/dispatches",
-        repo.owner, repo.name
+        "{}/repos/{}/{}/dispatches",
+        super::api_url(),
+        repo.owner,
+        repo.name
     );
 
     println!("url {}", url);
@@ -878,16 +1202,2029 @@ struct DispatchBody {
     event_type: String,
 }
 
-fn process_response(response: &req::Response) -> Result<&req::Response> {
-    let status = response.status();
+pub fn dispatch_workflow(
+    repo: &RemoteRepo,
+    workflow: &str,
+    git_ref: &str,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/actions/workflows/{}/dispatches",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        workflow
+    );
 
-    if status == StatusCode::UNAUTHORIZED {
-        return Err(models::Unauthorized.into());
+    let body = WorkflowDispatchBody {
+        git_ref: git_ref.to_string(),
+    };
+
+    let response = post(&url, &body, token)?;
+    process_response(&response).map(|_| ())
+}
+
+#[derive(Serialize, Debug)]
+struct WorkflowDispatchBody {
+    #[serde(rename = "ref")]
+    git_ref: String,
+}
+
+pub fn get_user_id(username: &str, token: &str) -> Result<i64> {
+    let url = format!("{}/users/{}", super::api_url(), username);
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let account: Account = response.json()?;
+    Ok(account.id)
+}
+
+pub fn get_team_id(org: &str, team_slug: &str, token: &str) -> Result<i64> {
+    let url = format!("{}/orgs/{}/teams/{}", super::api_url(), org, team_slug);
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let account: Account = response.json()?;
+    Ok(account.id)
+}
+
+#[derive(Deserialize, Debug)]
+struct Account {
+    id: i64,
+}
+
+pub fn set_environment(
+    repo: &RemoteRepo,
+    name: &str,
+    wait_timer: Option<u32>,
+    reviewers: Option<&[EnvironmentReviewer]>,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/environments/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        name
+    );
+
+    let body = EnvironmentBody {
+        wait_timer,
+        reviewers: reviewers.map(|r| r.to_vec()),
+    };
+
+    let response = put(&url, &body, token, None)?;
+    process_response(&response).map(|_| ())
+}
+
+#[derive(Serialize, Debug)]
+struct EnvironmentBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wait_timer: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reviewers: Option<Vec<EnvironmentReviewer>>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EnvironmentReviewer {
+    #[serde(rename = "type")]
+    pub reviewer_type: String,
+    pub id: i64,
+}
+
+pub fn get_environments(repo: &RemoteRepo, token: &str) -> Result<Vec<Environment>> {
+    let url = format!(
+        "{}/repos/{}/{}/environments",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let body: EnvironmentsResponse = response.json()?;
+    Ok(body.environments)
+}
+
+#[derive(Deserialize, Debug)]
+struct EnvironmentsResponse {
+    environments: Vec<Environment>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Environment {
+    pub name: String,
+    #[serde(default)]
+    pub protection_rules: Vec<ProtectionRule>,
+}
+
+impl Environment {
+    pub fn wait_timer(&self) -> Option<u32> {
+        self.protection_rules.iter().find_map(|r| r.wait_timer)
     }
 
-    if !status.is_success() {
-        return Err(models::Unsuccessful(status).into());
+    pub fn reviewers(&self) -> Vec<String> {
+        self.protection_rules
+            .iter()
+            .flat_map(|r| r.reviewers.iter())
+            .filter_map(|r| r.reviewer.login.clone().or_else(|| r.reviewer.slug.clone()))
+            .collect()
     }
+}
 
-    Ok(response)
+#[derive(Deserialize, Debug)]
+pub struct ProtectionRule {
+    pub wait_timer: Option<u32>,
+    #[serde(default)]
+    pub reviewers: Vec<ProtectionRuleReviewer>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProtectionRuleReviewer {
+    pub reviewer: ReviewerAccount,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReviewerAccount {
+    pub login: Option<String>,
+    pub slug: Option<String>,
+}
+
+pub fn create_pages(
+    repo: &RemoteRepo,
+    build_type: Option<&str>,
+    source: Option<PagesSource>,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pages",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let body = PagesBody {
+        build_type: build_type.map(|b| b.to_string()),
+        source,
+    };
+
+    let response = post(&url, &body, token)?;
+    process_response(&response).map(|_| ())
+}
+
+pub fn update_pages(
+    repo: &RemoteRepo,
+    build_type: Option<&str>,
+    source: Option<PagesSource>,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pages",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let body = PagesBody {
+        build_type: build_type.map(|b| b.to_string()),
+        source,
+    };
+
+    let response = put(&url, &body, token, None)?;
+    process_response(&response).map(|_| ())
+}
+
+pub fn delete_pages(repo: &RemoteRepo, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pages",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = delete(&url, token)?;
+    process_response(&response).map(|_| ())
+}
+
+/// Returns None when GitHub Pages is not enabled for the repository
+pub fn get_pages(repo: &RemoteRepo, token: &str) -> Result<Option<Pages>> {
+    let url = format!(
+        "{}/repos/{}/{}/pages",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = get(&url, token, None)?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    let pages: Pages = response.json()?;
+    Ok(Some(pages))
+}
+
+#[derive(Serialize, Debug)]
+struct PagesBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<PagesSource>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PagesSource {
+    pub branch: String,
+    pub path: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Pages {
+    pub html_url: Option<String>,
+    pub status: Option<String>,
+    pub build_type: Option<String>,
+    pub source: Option<PagesSource>,
+}
+
+pub fn get_org_packages(org: &str, package_type: &str, token: &str) -> Result<Vec<Package>> {
+    let mut packages = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/orgs/{}/packages?package_type={}&per_page=100&page={}",
+            super::api_url(),
+            org,
+            package_type,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<Package> = response.json()?;
+        let is_last_page = body.len() < 100;
+        packages.extend(body);
+
+        if is_last_page {
+            return Ok(packages);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Package {
+    pub name: String,
+    pub visibility: String,
+    #[serde(default)]
+    pub version_count: usize,
+    pub repository: Option<PackageRepository>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PackageRepository {
+    pub name: String,
+}
+
+/// Versions are returned newest first
+pub fn get_package_versions(
+    org: &str,
+    package_type: &str,
+    package_name: &str,
+    token: &str,
+) -> Result<Vec<PackageVersion>> {
+    let mut versions = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/orgs/{}/packages/{}/{}/versions?per_page=100&page={}",
+            super::api_url(),
+            org,
+            package_type,
+            package_name.replace('/', "%2F"),
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<PackageVersion> = response.json()?;
+        let is_last_page = body.len() < 100;
+        versions.extend(body);
+
+        if is_last_page {
+            return Ok(versions);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PackageVersion {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn delete_package_version(
+    org: &str,
+    package_type: &str,
+    package_name: &str,
+    version_id: i64,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/orgs/{}/packages/{}/{}/versions/{}",
+        super::api_url(),
+        org,
+        package_type,
+        package_name.replace('/', "%2F"),
+        version_id
+    );
+
+    let response = delete(&url, token)?;
+    process_response(&response).map(|_| ())
+}
+
+pub fn get_org_runners(org: &str, token: &str) -> Result<Vec<Runner>> {
+    let mut runners = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/orgs/{}/actions/runners?per_page=100&page={}",
+            super::api_url(),
+            org,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: RunnersResponse = response.json()?;
+        let is_last_page = body.runners.len() < 100;
+        runners.extend(body.runners);
+
+        if is_last_page {
+            return Ok(runners);
+        }
+        page += 1;
+    }
+}
+
+pub fn get_forks(repo: &RemoteRepo, token: &str) -> Result<Vec<Fork>> {
+    let mut forks = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/repos/{}/{}/forks?sort=newest&per_page=100&page={}",
+            super::api_url(),
+            repo.owner,
+            repo.name,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<Fork> = response.json()?;
+        let is_last_page = body.len() < 100;
+        forks.extend(body);
+
+        if is_last_page {
+            return Ok(forks);
+        }
+        page += 1;
+    }
+}
+
+/// Bytes of code per language of a repository, as detected by GitHub linguist
+pub fn get_languages(repo: &RemoteRepo, token: &str) -> Result<BTreeMap<String, u64>> {
+    let url = format!(
+        "{}/repos/{}/{}/languages",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    Ok(response.json()?)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Fork {
+    pub full_name: String,
+    pub owner: ForkOwner,
+    pub default_branch: String,
+    pub html_url: String,
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ForkOwner {
+    pub login: String,
+}
+
+/// A repository and the repository it is forked from, None when it does not exist
+pub fn get_fork_info(owner: &str, name: &str, token: &str) -> Result<Option<ForkInfo>> {
+    let url = format!("{}/repos/{}/{}", super::api_url(), owner, name);
+
+    let response = get(&url, token, None)?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map(Some).map_err(Into::into)
+}
+
+/// Fork a repository into an organisation
+///
+/// GitHub makes the fork in the background, so it can take a moment before it can be cloned.
+pub fn create_fork(repo: &RemoteRepo, org: &str, token: &str) -> Result<ForkInfo> {
+    let url = format!(
+        "{}/repos/{}/{}/forks",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+    let body = serde_json::json!({ "organization": org });
+
+    let response = post(&url, &body, token)?;
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ForkInfo {
+    pub name: String,
+    pub owner: ForkOwner,
+    pub ssh_url: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub fork: bool,
+    pub parent: Option<ForkParent>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ForkParent {
+    pub full_name: String,
+}
+
+impl ForkInfo {
+    pub fn remote_repo(&self) -> RemoteRepo {
+        RemoteRepo {
+            name: self.name.clone(),
+            owner: self.owner.login.clone(),
+            ssh_url: self.ssh_url.clone(),
+            https_url: self.html_url.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct RunnersResponse {
+    runners: Vec<Runner>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Runner {
+    pub id: i64,
+    pub name: String,
+    pub os: String,
+    pub status: String,
+    pub busy: bool,
+    pub labels: Vec<RunnerLabel>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RunnerLabel {
+    pub name: String,
+}
+
+pub fn add_runner_labels(org: &str, runner_id: i64, labels: &[String], token: &str) -> Result<()> {
+    let url = format!(
+        "{}/orgs/{}/actions/runners/{}/labels",
+        super::api_url(),
+        org,
+        runner_id
+    );
+
+    let body = RunnerLabelsBody {
+        labels: labels.to_vec(),
+    };
+
+    let response = post(&url, &body, token)?;
+    process_response(&response).map(|_| ())
+}
+
+#[derive(Serialize, Debug)]
+struct RunnerLabelsBody {
+    labels: Vec<String>,
+}
+
+pub fn remove_runner_label(org: &str, runner_id: i64, label: &str, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/orgs/{}/actions/runners/{}/labels/{}",
+        super::api_url(),
+        org,
+        runner_id,
+        label
+    );
+
+    let response = delete(&url, token)?;
+    process_response(&response).map(|_| ())
+}
+
+pub fn create_runner_registration_token(org: &str, token: &str) -> Result<RegistrationToken> {
+    let url = format!(
+        "{}/orgs/{}/actions/runners/registration-token",
+        super::api_url(),
+        org
+    );
+
+    let response = post(&url, &serde_json::json!({}), token)?;
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegistrationToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn get_default_branch(owner: &str, repo: &str, token: &str) -> Result<String> {
+    let url = format!("{}/repos/{}/{}", super::api_url(), owner, repo);
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let body: RepoResponse = response.json()?;
+    Ok(body.default_branch)
+}
+
+#[derive(Deserialize, Debug)]
+struct RepoResponse {
+    default_branch: String,
+}
+
+/// How CI went for a commit, from both the commit statuses and the check runs
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiState {
+    Success,
+    Pending,
+    Failure,
+    /// There is no CI for the commit
+    Unknown,
+}
+
+// https://docs.github.com/en/rest/commits/statuses#get-the-combined-status-for-a-specific-reference
+// https://docs.github.com/en/rest/checks/runs#list-check-runs-for-a-git-reference
+pub fn get_ci_state(repo: &RemoteRepo, git_ref: &str, token: &str) -> Result<CiState> {
+    let base = format!(
+        "{}/repos/{}/{}/commits/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        git_ref
+    );
+
+    let response = get(&format!("{}/status", base), token, None)?;
+    process_response(&response).map(|_| ())?;
+    let combined: CombinedStatus = response.json()?;
+
+    let response = get(&format!("{}/check-runs", base), token, None)?;
+    process_response(&response).map(|_| ())?;
+    let checks: CheckRuns = response.json()?;
+
+    let mut states = vec![];
+    if combined.total_count > 0 {
+        states.push(match combined.state.as_str() {
+            "success" => CiState::Success,
+            "pending" => CiState::Pending,
+            _ => CiState::Failure,
+        });
+    }
+    for run in checks.check_runs {
+        states.push(match (run.status.as_str(), run.conclusion.as_deref()) {
+            ("completed", Some("success" | "neutral" | "skipped")) => CiState::Success,
+            ("completed", _) => CiState::Failure,
+            _ => CiState::Pending,
+        });
+    }
+
+    Ok(if states.contains(&CiState::Failure) {
+        CiState::Failure
+    } else if states.contains(&CiState::Pending) {
+        CiState::Pending
+    } else if states.is_empty() {
+        CiState::Unknown
+    } else {
+        CiState::Success
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct CombinedStatus {
+    state: String,
+    total_count: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RateLimit {
+    pub limit: usize,
+    pub remaining: usize,
+    /// When the limit is reset, in seconds since the epoch
+    pub reset: i64,
+}
+
+// https://docs.github.com/en/rest/rate-limit/rate-limit#get-rate-limit-status-for-the-authenticated-user
+pub fn get_rate_limit(token: &str) -> Result<RateLimit> {
+    let url = format!("{}/rate_limit", super::api_url());
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let body: RateLimitResponse = response.json()?;
+    Ok(body.resources.core)
+}
+
+#[derive(Deserialize, Debug)]
+struct RateLimitResponse {
+    resources: RateLimitResources,
+}
+
+#[derive(Deserialize, Debug)]
+struct RateLimitResources {
+    core: RateLimit,
+}
+
+/// Who a token belongs to and what it may do
+#[derive(Serialize, Debug, Clone)]
+pub struct TokenInfo {
+    pub login: String,
+    pub name: Option<String>,
+    /// Scopes of a classic token, fine-grained tokens have none
+    pub scopes: Option<Vec<String>>,
+    /// When the token expires, for tokens with an expiry
+    pub expires_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AuthenticatedUser {
+    login: String,
+    name: Option<String>,
+}
+
+// https://docs.github.com/en/rest/users/users#get-the-authenticated-user
+pub fn get_token_info(token: &str) -> Result<TokenInfo> {
+    let url = format!("{}/user", super::api_url());
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+    let scopes = header("x-oauth-scopes").map(|scopes| {
+        scopes
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    });
+    let expires_at = header("github-authentication-token-expiration");
+
+    let user: AuthenticatedUser = response.json()?;
+    Ok(TokenInfo {
+        login: user.login,
+        name: user.name,
+        scopes,
+        expires_at,
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct Organisation {
+    login: String,
+}
+
+// https://docs.github.com/en/rest/orgs/orgs#list-organizations-for-the-authenticated-user
+pub fn get_user_orgs(token: &str) -> Result<Vec<String>> {
+    let mut orgs = vec![];
+    let mut url = Some(format!("{}/user/orgs?per_page=100", super::api_url()));
+
+    while let Some(current) = url {
+        let response = get(&current, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        url = next_link(&response);
+        let body: Vec<Organisation> = response.json()?;
+        orgs.extend(body.into_iter().map(|o| o.login));
+    }
+
+    Ok(orgs)
+}
+
+/// The settings of an organisation, from the organisation and its Actions permissions
+///
+/// Settings that only owners can see are None for other tokens.
+pub fn get_org_settings(org: &str, token: &str) -> Result<OrgSettings> {
+    let base = format!("{}/orgs/{}", super::api_url(), org);
+
+    let response = get(&base, token, None)?;
+    process_response(&response).map(|_| ())?;
+    let organisation: OrgResponse = response.json()?;
+
+    let response = get(&format!("{}/actions/permissions", base), token, None)?;
+    let actions: Option<ActionsPermissions> = match response.status() {
+        StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => None,
+        _ => {
+            process_response(&response).map(|_| ())?;
+            Some(response.json()?)
+        }
+    };
+
+    let response = get(&format!("{}/actions/permissions/workflow", base), token, None)?;
+    let workflow: Option<WorkflowPermissions> = match response.status() {
+        StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => None,
+        _ => {
+            process_response(&response).map(|_| ())?;
+            Some(response.json()?)
+        }
+    };
+
+    Ok(OrgSettings {
+        default_repository_permission: organisation.default_repository_permission,
+        members_can_create_repositories: organisation.members_can_create_repositories,
+        two_factor_requirement_enabled: organisation.two_factor_requirement_enabled,
+        actions_enabled_repositories: actions.as_ref().map(|a| a.enabled_repositories.clone()),
+        actions_allowed_actions: actions.and_then(|a| a.allowed_actions),
+        default_workflow_permissions: workflow.map(|w| w.default_workflow_permissions),
+    })
+}
+
+/// Change the settings of an organisation that are set in `settings`, the others are kept
+///
+/// The 2FA requirement cannot be changed through the api. Changing the allowed actions needs
+/// actions_enabled_repositories too.
+pub fn set_org_settings(org: &str, settings: &OrgSettings, token: &str) -> Result<()> {
+    let base = format!("{}/orgs/{}", super::api_url(), org);
+
+    if settings.default_repository_permission.is_some()
+        || settings.members_can_create_repositories.is_some()
+    {
+        let body = UpdateOrgBody {
+            default_repository_permission: settings.default_repository_permission.clone(),
+            members_can_create_repositories: settings.members_can_create_repositories,
+        };
+        let response = patch(&base, &body, token)?;
+        process_response(&response).map(|_| ())?;
+    }
+
+    if let Some(enabled_repositories) = &settings.actions_enabled_repositories {
+        let body = ActionsPermissions {
+            enabled_repositories: enabled_repositories.clone(),
+            allowed_actions: settings.actions_allowed_actions.clone(),
+        };
+        let response = put(&format!("{}/actions/permissions", base), &body, token, None)?;
+        process_response(&response).map(|_| ())?;
+    } else if settings.actions_allowed_actions.is_some() {
+        anyhow::bail!("actions_enabled_repositories is needed to change actions_allowed_actions");
+    }
+
+    if let Some(permissions) = &settings.default_workflow_permissions {
+        let body = WorkflowPermissions {
+            default_workflow_permissions: permissions.clone(),
+        };
+        let url = format!("{}/actions/permissions/workflow", base);
+        let response = put(&url, &body, token, None)?;
+        process_response(&response).map(|_| ())?;
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Debug)]
+struct UpdateOrgBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_repository_permission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    members_can_create_repositories: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OrgResponse {
+    default_repository_permission: Option<String>,
+    members_can_create_repositories: Option<bool>,
+    two_factor_requirement_enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ActionsPermissions {
+    enabled_repositories: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_actions: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct WorkflowPermissions {
+    default_workflow_permissions: String,
+}
+
+/// The current `owner/name` of a repository, GitHub redirects after a transfer or a rename
+///
+/// Returns None when the repository does not exist anymore
+pub fn get_current_full_name(owner: &str, repo: &str, token: &str) -> Result<Option<String>> {
+    let url = format!("{}/repos/{}/{}", super::api_url(), owner, repo);
+
+    let response = get(&url, token, None)?;
+
+    if response.status() == StatusCode::NOT_FOUND
+        && response.headers().get("x-github-sso").is_none()
+    {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    let body: FullNameResponse = response.json()?;
+    Ok(Some(body.full_name))
+}
+
+#[derive(Deserialize, Debug)]
+struct FullNameResponse {
+    full_name: String,
+}
+
+/// Returns None when the branch does not exist on the remote
+pub fn get_branch_sha(
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    token: &str,
+) -> Result<Option<String>> {
+    let url = format!(
+        "{}/repos/{}/{}/commits/{}",
+        super::api_url(),
+        owner,
+        repo,
+        branch
+    );
+
+    let response = get(&url, token, Some("application/vnd.github.sha"))?;
+
+    if response.status() == StatusCode::NOT_FOUND
+        || response.status() == StatusCode::UNPROCESSABLE_ENTITY
+    {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    Ok(Some(response.text()?.trim().to_string()))
+}
+
+/// Returns None when one of the commits is unknown to the remote
+pub fn compare_commits(
+    owner: &str,
+    repo: &str,
+    base: &str,
+    head: &str,
+    token: &str,
+) -> Result<Option<Comparison>> {
+    let url = format!(
+        "{}/repos/{}/{}/compare/{}...{}?per_page=1",
+        super::api_url(),
+        owner,
+        repo,
+        base,
+        head
+    );
+
+    let response = get(&url, token, None)?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map(Some).map_err(Into::into)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Comparison {
+    /// One of "ahead", "behind", "identical" or "diverged", seen from head
+    pub status: String,
+    pub ahead_by: usize,
+    pub behind_by: usize,
+}
+
+pub fn create_pull_request(
+    repo: &RemoteRepo,
+    head: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+    token: &str,
+) -> Result<PullRequest> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+    let body = CreatePullRequestBody {
+        title: title.to_string(),
+        head: head.to_string(),
+        base: base.to_string(),
+        body: body.to_string(),
+    };
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+#[derive(Serialize, Debug)]
+struct CreatePullRequestBody {
+    title: String,
+    head: String,
+    base: String,
+    body: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PullRequest {
+    pub html_url: String,
+}
+
+pub fn create_issue(
+    repo: &RemoteRepo,
+    title: &str,
+    body: &str,
+    labels: &[String],
+    assignees: &[String],
+    token: &str,
+) -> Result<Issue> {
+    let url = format!(
+        "{}/repos/{}/{}/issues",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+    let body = CreateIssueBody {
+        title: title.to_string(),
+        body: body.to_string(),
+        labels: labels.to_vec(),
+        assignees: assignees.to_vec(),
+    };
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+#[derive(Serialize, Debug)]
+struct CreateIssueBody {
+    title: String,
+    body: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    assignees: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Issue {
+    pub html_url: String,
+}
+
+/// The open issues of a repository, without pull requests
+///
+/// The issues have all of the `labels`, and are assigned to `assignee` when it is given.
+/// GitHub takes `none` and `*` as assignee for unassigned and assigned issues.
+pub fn get_open_issues(
+    repo: &RemoteRepo,
+    labels: &[String],
+    assignee: Option<&str>,
+    token: &str,
+) -> Result<Vec<OpenIssue>> {
+    let mut issues = vec![];
+    let mut page = 1;
+
+    loop {
+        let mut params = vec![
+            ("state", "open".to_string()),
+            ("per_page", "100".to_string()),
+            ("page", page.to_string()),
+        ];
+        if !labels.is_empty() {
+            params.push(("labels", labels.join(",")));
+        }
+        if let Some(assignee) = assignee {
+            params.push(("assignee", assignee.to_string()));
+        }
+        let url = reqwest::Url::parse_with_params(
+            &format!(
+                "{}/repos/{}/{}/issues",
+                super::api_url(),
+                repo.owner,
+                repo.name
+            ),
+            &params,
+        )?;
+
+        let response = get(url.as_str(), token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<OpenIssue> = response.json()?;
+        let is_last_page = body.len() < 100;
+        issues.extend(body.into_iter().filter(|i| i.pull_request.is_none()));
+
+        if is_last_page {
+            return Ok(issues);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OpenIssue {
+    pub number: u64,
+    /// Id of the issue in the GraphQL api
+    pub node_id: String,
+    pub title: String,
+    pub html_url: String,
+    pub user: PullRequestUser,
+    #[serde(default)]
+    pub assignees: Vec<PullRequestUser>,
+    #[serde(default)]
+    pub labels: Vec<IssueLabel>,
+    pub created_at: DateTime<Utc>,
+    /// Last activity, comments and label changes count
+    pub updated_at: DateTime<Utc>,
+    /// Only present for pull requests
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueLabel {
+    pub name: String,
+}
+
+fn issue_url(repo: &RemoteRepo, number: u64) -> String {
+    format!(
+        "{}/repos/{}/{}/issues/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    )
+}
+
+/// Add labels to an issue or a pull request, keeping the labels it has
+pub fn add_issue_labels(
+    repo: &RemoteRepo,
+    number: u64,
+    labels: &[String],
+    token: &str,
+) -> Result<()> {
+    let url = format!("{}/labels", issue_url(repo, number));
+    let body = serde_json::json!({ "labels": labels });
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+pub fn comment_on_issue(repo: &RemoteRepo, number: u64, body: &str, token: &str) -> Result<()> {
+    let url = format!("{}/comments", issue_url(repo, number));
+    let body = serde_json::json!({ "body": body });
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+/// The releases of a repository, newest first, drafts included for users with push access
+pub fn get_releases(repo: &RemoteRepo, token: &str) -> Result<Vec<Release>> {
+    let mut releases = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/repos/{}/{}/releases?per_page=100&page={}",
+            super::api_url(),
+            repo.owner,
+            repo.name,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<Release> = response.json()?;
+        let is_last_page = body.len() < 100;
+        releases.extend(body);
+
+        if is_last_page {
+            return Ok(releases);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Release {
+    pub id: u64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub html_url: String,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Url template to upload assets to, ending with `{?name,label}`
+    #[serde(skip_serializing)]
+    pub upload_url: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    pub download_count: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NewRelease {
+    pub tag_name: String,
+    /// Branch or commit that a new tag is made on, the default branch when it is left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_commitish: Option<String>,
+    pub name: String,
+    pub body: String,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+/// Create a release, and its tag when the tag does not exist
+pub fn create_release(repo: &RemoteRepo, release: &NewRelease, token: &str) -> Result<Release> {
+    let url = format!(
+        "{}/repos/{}/{}/releases",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = post(&url, release, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+/// Upload a file as an asset of a release, named after the file
+pub fn upload_release_asset(release: &Release, path: &std::path::Path, token: &str) -> Result<()> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("{:?} is not a file name", path))?;
+    let base = release
+        .upload_url
+        .split('{')
+        .next()
+        .unwrap_or(&release.upload_url);
+    let url = reqwest::Url::parse_with_params(base, &[("name", name)])?;
+    let content = std::fs::read(path)?;
+
+    log::debug!("POST: {}", url);
+    read_only::ensure_writable(&format!("POST {}", url))?;
+    let client = req::Client::new();
+    let response = client
+        .post(url)
+        .bearer_auth(token)
+        .header("User-Agent", super::USER_AGENT)
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("Content-Type", "application/octet-stream")
+        .body(content)
+        .send()?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+pub fn delete_release(repo: &RemoteRepo, id: u64, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/releases/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        id
+    );
+
+    let response = delete(&url, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+pub fn delete_tag(repo: &RemoteRepo, tag: &str, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/git/refs/tags/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        tag
+    );
+
+    let response = delete(&url, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+/// Close an issue as not planned
+pub fn close_issue(repo: &RemoteRepo, number: u64, token: &str) -> Result<()> {
+    let body = serde_json::json!({
+        "state": "closed",
+        "state_reason": "not_planned",
+    });
+
+    let response = patch(&issue_url(repo, number), &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+pub fn get_open_pull_requests(repo: &RemoteRepo, token: &str) -> Result<Vec<OpenPullRequest>> {
+    let mut pulls = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = format!(
+            "{}/repos/{}/{}/pulls?state=open&per_page=100&page={}",
+            super::api_url(),
+            repo.owner,
+            repo.name,
+            page
+        );
+
+        let response = get(&url, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: Vec<OpenPullRequest> = response.json()?;
+        let is_last_page = body.len() < 100;
+        pulls.extend(body);
+
+        if is_last_page {
+            return Ok(pulls);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OpenPullRequest {
+    pub number: u64,
+    /// Id of the pull request in the GraphQL api
+    pub node_id: String,
+    pub title: String,
+    pub html_url: String,
+    pub user: PullRequestUser,
+    pub head: PullRequestHead,
+    #[serde(default)]
+    pub draft: bool,
+    pub created_at: DateTime<Utc>,
+    /// Reviewers that are asked to review and have not reviewed yet
+    #[serde(default)]
+    pub requested_reviewers: Vec<PullRequestUser>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullRequestUser {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullRequestHead {
+    #[serde(rename = "ref")]
+    pub branch: String,
+    pub sha: String,
+}
+
+pub fn get_pull_request(repo: &RemoteRepo, number: u64, token: &str) -> Result<PullRequestDetails> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    );
+
+    let response = get(&url, token, None)?;
+
+    process_response(&response).map(|_| ())?;
+
+    response.json().map_err(Into::into)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullRequestDetails {
+    /// None while GitHub is still computing it
+    pub mergeable: Option<bool>,
+    /// clean, unstable, blocked, behind, dirty, draft, has_hooks or unknown
+    pub mergeable_state: String,
+}
+
+/// Merge a pull request if its head is still `sha`, `method` is merge, squash or rebase
+pub fn merge_pull_request(
+    repo: &RemoteRepo,
+    number: u64,
+    sha: &str,
+    method: &str,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}/merge",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    );
+    let body = serde_json::json!({
+        "sha": sha,
+        "merge_method": method,
+    });
+
+    let response = put(&url, &body, token, None)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+/// Ask users to review a pull request, keeping the reviewers it has
+pub fn request_reviewers(
+    repo: &RemoteRepo,
+    number: u64,
+    reviewers: &[String],
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}/requested_reviewers",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    );
+    let body = serde_json::json!({ "reviewers": reviewers });
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+/// Assign users to an issue or a pull request, keeping the assignees it has
+pub fn add_assignees(
+    repo: &RemoteRepo,
+    number: u64,
+    assignees: &[String],
+    token: &str,
+) -> Result<()> {
+    let url = format!("{}/assignees", issue_url(repo, number));
+    let body = serde_json::json!({ "assignees": assignees });
+
+    let response = post(&url, &body, token)?;
+
+    if let Some(wait) = rate_limit_wait(&response) {
+        return Err(models::RateLimited(wait).into());
+    }
+
+    process_response(&response).map(|_| ())
+}
+
+/// The reviews of a pull request, oldest first
+pub fn get_pull_request_reviews(
+    repo: &RemoteRepo,
+    number: u64,
+    token: &str,
+) -> Result<Vec<PullRequestReview>> {
+    let mut reviews = vec![];
+    let mut url = Some(format!(
+        "{}/repos/{}/{}/pulls/{}/reviews?per_page=100",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    ));
+
+    while let Some(current) = url {
+        let response = get(&current, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        url = next_link(&response);
+        let body: Vec<PullRequestReview> = response.json()?;
+        reviews.extend(body);
+    }
+
+    Ok(reviews)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PullRequestReview {
+    /// None for reviewers whose account was deleted
+    pub user: Option<PullRequestUser>,
+    /// APPROVED, CHANGES_REQUESTED, COMMENTED, DISMISSED or PENDING
+    pub state: String,
+}
+
+/// Review a pull request, `event` is APPROVE, REQUEST_CHANGES or COMMENT
+pub fn create_review(
+    repo: &RemoteRepo,
+    number: u64,
+    event: &str,
+    body: Option<&str>,
+    token: &str,
+) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}/reviews",
+        super::api_url(),
+        repo.owner,
+        repo.name,
+        number
+    );
+    let body = CreateReviewBody {
+        event: event.to_string(),
+        body: body.map(|b| b.to_string()),
+    };
+
+    let response = post(&url, &body, token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+#[derive(Serialize, Debug)]
+struct CreateReviewBody {
+    event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+/// Search issues and pull requests, GitHub returns at most 1000 results for a query
+pub fn search_issues(query: &str, token: &str) -> Result<Vec<IssueSearchItem>> {
+    let mut items = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/search/issues", super::api_url()),
+            &[
+                ("q", query),
+                ("sort", "created"),
+                ("order", "asc"),
+                ("per_page", "100"),
+                ("page", &page.to_string()),
+            ],
+        )?;
+
+        let response = get_search(url.as_str(), token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: IssueSearchResponse = response.json()?;
+        let is_last_page = body.items.len() < 100 || page == 10;
+        items.extend(body.items);
+
+        if is_last_page {
+            return Ok(items);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct IssueSearchResponse {
+    items: Vec<IssueSearchItem>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IssueSearchItem {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    /// open or closed
+    pub state: String,
+    /// Api url of the repository, ending with owner/name
+    pub repository_url: String,
+    pub created_at: DateTime<Utc>,
+    /// Only present for pull requests
+    pub pull_request: Option<serde_json::Value>,
+}
+
+impl IssueSearchItem {
+    pub fn repository(&self) -> Option<(&str, &str)> {
+        let mut parts = self.repository_url.rsplit('/');
+        let name = parts.next()?;
+        let owner = parts.next()?;
+        Some((owner, name))
+    }
+}
+
+/// Search code on the default branches, GitHub returns at most 1000 results for a query
+///
+/// The results come with the fragments of the files that match, GitHub gives no line numbers.
+pub fn search_code(query: &str, token: &str) -> Result<Vec<CodeSearchItem>> {
+    let mut items = vec![];
+    let mut page = 1;
+
+    loop {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/search/code", super::api_url()),
+            &[
+                ("q", query),
+                ("per_page", "100"),
+                ("page", &page.to_string()),
+            ],
+        )?;
+
+        let response = get_search(
+            url.as_str(),
+            token,
+            Some("application/vnd.github.text-match+json"),
+        )?;
+
+        process_response(&response).map(|_| ())?;
+
+        let body: CodeSearchResponse = response.json()?;
+        let is_last_page = body.items.len() < 100 || page == 10;
+        items.extend(body.items);
+
+        if is_last_page {
+            return Ok(items);
+        }
+        page += 1;
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CodeSearchResponse {
+    items: Vec<CodeSearchItem>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CodeSearchItem {
+    pub path: String,
+    pub html_url: String,
+    pub repository: CodeSearchRepository,
+    #[serde(default)]
+    pub text_matches: Vec<TextMatch>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CodeSearchRepository {
+    pub name: String,
+    pub full_name: String,
+}
+
+/// A fragment of a file with the parts that match the query
+#[derive(Deserialize, Debug)]
+pub struct TextMatch {
+    /// content or path
+    pub property: String,
+    pub fragment: String,
+    #[serde(default)]
+    pub matches: Vec<FragmentMatch>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FragmentMatch {
+    pub text: String,
+}
+
+/// Get a url of the search api, waiting when the rate limit is reached
+///
+/// Searching allows only 10 to 30 requests a minute, so searching many organisations or pages
+/// runs into the limit.
+fn get_search(url: &str, token: &str, accept: Option<&str>) -> Result<req::Response> {
+    for _ in 0..5 {
+        let response = get(url, token, accept)?;
+        match rate_limit_wait(&response) {
+            Some(wait) => {
+                eprintln!(
+                    "The search rate limit is reached, waiting {} seconds",
+                    wait.as_secs()
+                );
+                std::thread::sleep(wait);
+            }
+            None => return Ok(response),
+        }
+    }
+    Ok(get(url, token, accept)?)
+}
+
+/// How long to wait before a request that is refused because of the rate limit can be repeated
+fn rate_limit_wait(response: &req::Response) -> Option<Duration> {
+    if !matches!(
+        response.status(),
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ) {
+        return None;
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)?
+            .to_str()
+            .ok()?
+            .parse::<i64>()
+            .ok()
+    };
+    let seconds = match (header("retry-after"), header("x-ratelimit-remaining")) {
+        (Some(seconds), _) => seconds,
+        (None, Some(0)) => header("x-ratelimit-reset")? - Utc::now().timestamp() + 1,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds.clamp(1, 60 * 60) as u64))
+}
+
+/// Open Dependabot alerts of a repository
+///
+/// This endpoint pages with cursors, so the next page is taken from the Link header.
+pub fn get_dependabot_alerts(repo: &RemoteRepo, token: &str) -> Result<Vec<DependabotAlert>> {
+    let mut alerts = vec![];
+    let mut url = Some(format!(
+        "{}/repos/{}/{}/dependabot/alerts?state=open&per_page=100",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    ));
+
+    while let Some(current) = url {
+        let response = get(&current, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        url = next_link(&response);
+        let body: Vec<DependabotAlert> = response.json()?;
+        alerts.extend(body);
+    }
+
+    Ok(alerts)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DependabotAlert {
+    pub number: u64,
+    pub html_url: String,
+    pub dependency: AlertDependency,
+    pub security_advisory: SecurityAdvisory,
+    pub security_vulnerability: SecurityVulnerability,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertDependency {
+    pub package: AlertPackage,
+    pub manifest_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertPackage {
+    pub ecosystem: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecurityAdvisory {
+    pub ghsa_id: String,
+    pub summary: String,
+    pub severity: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecurityVulnerability {
+    pub first_patched_version: Option<PatchedVersion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatchedVersion {
+    pub identifier: String,
+}
+
+/// The url with `rel="next"` in the Link header of a response
+// https://docs.github.com/en/rest/activity/notifications#list-notifications-for-the-authenticated-user
+/// Unread notifications of the authenticated user, newest first
+pub fn get_notifications(token: &str) -> Result<Vec<Notification>> {
+    let mut notifications = vec![];
+    let mut url = Some(format!("{}/notifications?per_page=50", super::api_url()));
+
+    while let Some(current) = url {
+        let response = get(&current, token, None)?;
+
+        process_response(&response).map(|_| ())?;
+
+        url = next_link(&response);
+        let body: Vec<Notification> = response.json()?;
+        notifications.extend(body);
+    }
+
+    Ok(notifications)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Notification {
+    /// Id of the thread
+    pub id: String,
+    /// Why the user is notified, e.g. review_requested, mention or subscribed
+    pub reason: String,
+    pub updated_at: DateTime<Utc>,
+    pub subject: NotificationSubject,
+    pub repository: NotificationRepository,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotificationSubject {
+    pub title: String,
+    /// Issue, PullRequest, Release, CheckSuite...
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotificationRepository {
+    pub name: String,
+    pub full_name: String,
+    pub owner: NotificationOwner,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotificationOwner {
+    pub login: String,
+}
+
+// https://docs.github.com/en/rest/activity/notifications#mark-a-thread-as-read
+pub fn mark_notification_read(id: &str, token: &str) -> Result<()> {
+    let url = format!("{}/notifications/threads/{}", super::api_url(), id);
+
+    let response = patch(&url, &serde_json::json!({}), token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+// https://docs.github.com/en/rest/activity/notifications#mark-a-thread-as-done
+pub fn mark_notification_done(id: &str, token: &str) -> Result<()> {
+    let url = format!("{}/notifications/threads/{}", super::api_url(), id);
+
+    let response = delete(&url, token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+// https://docs.github.com/en/rest/activity/watching#set-a-repository-subscription
+/// Watch all activity of a repository, or ignore it with `ignored`
+pub fn set_subscription(repo: &RemoteRepo, ignored: bool, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/subscription",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+    let body = serde_json::json!({ "subscribed": !ignored, "ignored": ignored });
+
+    let response = put(&url, &body, token, None)?;
+
+    process_response(&response).map(|_| ())
+}
+
+// https://docs.github.com/en/rest/activity/watching#delete-a-repository-subscription
+pub fn delete_subscription(repo: &RemoteRepo, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/repos/{}/{}/subscription",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = delete(&url, token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+// https://docs.github.com/en/rest/activity/starring#star-a-repository-for-the-authenticated-user
+pub fn star_repo(repo: &RemoteRepo, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/user/starred/{}/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+    read_only::ensure_writable(&format!("PUT {}", url))?;
+
+    // This endpoint wants an empty body
+    let client = req::Client::new();
+    let response = client
+        .put(&url)
+        .bearer_auth(token)
+        .header("User-Agent", super::USER_AGENT)
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("Content-Length", "0")
+        .send()?;
+
+    process_response(&response).map(|_| ())
+}
+
+// https://docs.github.com/en/rest/activity/starring#unstar-a-repository-for-the-authenticated-user
+pub fn unstar_repo(repo: &RemoteRepo, token: &str) -> Result<()> {
+    let url = format!(
+        "{}/user/starred/{}/{}",
+        super::api_url(),
+        repo.owner,
+        repo.name
+    );
+
+    let response = delete(&url, token)?;
+
+    process_response(&response).map(|_| ())
+}
+
+fn next_link(response: &req::Response) -> Option<String> {
+    let link = response.headers().get("link")?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, rel) = part.split_once(';')?;
+        rel.contains("rel=\"next\"").then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+    })
+}
+
+fn process_response(response: &req::Response) -> Result<&req::Response> {
+    let status = response.status();
+
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(models::Unauthorized.into());
+    }
+
+    if !status.is_success() {
+        let org = models::owner_from_url(response.url());
+        if let Some(e) = models::access_error(response, &org) {
+            return Err(e);
+        }
+        return Err(models::Unsuccessful(status).into());
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_protection_update_body() {
+        let protection = json!({
+            "url": "https://api.github.com/repos/divvun/gut/branches/main/protection",
+            "required_status_checks": {
+                "strict": true,
+                "contexts": ["build"],
+                "checks": [{ "context": "build", "app_id": 15368 }]
+            },
+            "required_pull_request_reviews": {
+                "dismissal_restrictions": {
+                    "users": [{ "login": "snomos" }],
+                    "teams": [{ "slug": "core" }],
+                    "apps": []
+                },
+                "dismiss_stale_reviews": true,
+                "require_code_owner_reviews": false,
+                "required_approving_review_count": 2,
+                "require_last_push_approval": false
+            },
+            "required_signatures": { "enabled": true },
+            "enforce_admins": { "enabled": true },
+            "required_linear_history": { "enabled": true },
+            "allow_force_pushes": { "enabled": false },
+            "allow_deletions": { "enabled": false },
+            "required_conversation_resolution": { "enabled": true },
+            "lock_branch": { "enabled": false }
+        });
+
+        let body = protection_update_body(&protection);
+
+        assert_eq!(
+            body["required_status_checks"],
+            json!({ "strict": true, "checks": [{ "context": "build", "app_id": 15368 }] })
+        );
+        assert_eq!(
+            body["required_pull_request_reviews"]["dismissal_restrictions"],
+            json!({ "users": ["snomos"], "teams": ["core"], "apps": [] })
+        );
+        assert_eq!(
+            body["required_pull_request_reviews"]["required_approving_review_count"],
+            2
+        );
+        assert_eq!(body["enforce_admins"], true);
+        assert_eq!(body["restrictions"], serde_json::Value::Null);
+        assert_eq!(body["required_linear_history"], true);
+        assert_eq!(body["required_conversation_resolution"], true);
+        assert_eq!(body["allow_force_pushes"], false);
+        assert_eq!(body["block_creations"], false);
+    }
 }
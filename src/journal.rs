@@ -0,0 +1,90 @@
+use crate::path;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// The repositories that a bulk command has finished, so that an interrupted run can be resumed
+///
+/// A journal belongs to one invocation, e.g. setting the same topics on the repositories of
+/// the same organisation and regex. It is kept in the journals directory of the config
+/// directory, written after every repository and removed when the run is complete.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Journal {
+    #[serde(skip)]
+    path: PathBuf,
+    /// The invocation this journal belongs to
+    pub invocation: String,
+    pub done: BTreeSet<String>,
+}
+
+impl Journal {
+    /// The journal of an invocation of a command, empty when it has not been started before
+    pub fn open(command: &str, invocation: &str) -> Result<Journal> {
+        let dir =
+            path::journals_dir().ok_or_else(|| anyhow!("Cannot find the config directory"))?;
+        let path = dir.join(file_name(command, invocation));
+        if !path.exists() {
+            return Ok(Journal {
+                path,
+                invocation: invocation.to_string(),
+                done: BTreeSet::new(),
+            });
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read the journal {:?}", path))?;
+        let journal: Journal = serde_json::from_str(&content)
+            .with_context(|| format!("Cannot read the journal {:?}", path))?;
+        Ok(Journal { path, ..journal })
+    }
+
+    pub fn is_done(&self, repo: &str) -> bool {
+        self.done.contains(repo)
+    }
+
+    /// Remember that a repository is finished
+    pub fn record(&mut self, repo: &str) -> Result<()> {
+        self.done.insert(repo.to_string());
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Cannot write the journal {:?}", self.path))
+    }
+
+    /// Remove the journal of a run that is complete
+    pub fn finish(self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)
+                .with_context(|| format!("Cannot remove the journal {:?}", self.path))?;
+        }
+        Ok(())
+    }
+}
+
+/// A file name that is the same for every run of an invocation, e.g. `topic-set-1f0c…json`
+fn file_name(command: &str, invocation: &str) -> String {
+    // FNV-1a, which unlike the hasher of std is the same for every build of gut
+    let hash = invocation
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{}-{:016x}.json", command.replace(' ', "-"), hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        let name = file_name("topic set", "giellalt lang- [\"lang\"]");
+        assert!(name.starts_with("topic-set-"));
+        assert!(name.ends_with(".json"));
+        assert_eq!(name, file_name("topic set", "giellalt lang- [\"lang\"]"));
+        assert_ne!(
+            name,
+            file_name("topic set", "giellalt lang- [\"keyboard\"]")
+        );
+    }
+}
@@ -1,17 +1,37 @@
+mod alias;
+mod branch_map;
 mod cli;
 mod commands;
+mod completion_cache;
 mod config;
 mod convert;
+mod doc_check;
+mod email;
+mod encoding;
 mod filter;
 mod git;
 mod github;
+mod journal;
+mod markdown;
+mod mirror;
+mod network;
+mod org_settings;
 mod path;
+mod policy;
+mod read_only;
+mod repo_state;
+mod run_lock;
+mod syntax;
 mod toml;
 mod user;
+mod version_file;
+mod workspace;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
 use cli::{Args, Commands};
+use config::Config;
+use run_lock::RunLock;
+use std::path::Path;
 
 fn main() -> Result<()> {
     color_backtrace::install();
@@ -21,35 +41,130 @@ fn main() -> Result<()> {
         .filter(Some("gut"), log::LevelFilter::Debug)
         .init();
 
-    let common_args = Args::parse();
+    let (defaults, aliases) = Config::load()
+        .map(|c| (c.defaults, c.alias))
+        .unwrap_or_default();
+
+    let lines = alias::expand(std::env::args_os().collect(), &aliases)?;
+    let is_macro = lines.len() > 1;
+    for args in lines {
+        if is_macro {
+            let line = args[1..].join(std::ffi::OsStr::new(" "));
+            log::info!("Running gut {}", line.to_string_lossy());
+        }
+        let common_args = Args::parse_with_defaults(args.clone(), &defaults);
+        if common_args.email && !email::is_child() {
+            email::run_and_send(&args)?;
+        } else {
+            run(common_args)?;
+        }
+    }
+    Ok(())
+}
+
+fn run(common_args: Args) -> Result<()> {
     log::debug!("Arguments: {:?}", common_args);
 
+    let read_only = common_args.read_only || Config::read_only()?;
+    read_only::init(read_only);
+    if read_only && !common_args.command.allowed_read_only() {
+        bail!("This command changes local repositories and cannot run in read-only mode");
+    }
+
+    workspace::activate(common_args.workspace.as_deref(), common_args.selects_repos)?;
+    commands::progress::init(common_args.progress);
+    let _lock = lock(&common_args)?;
+
     match &common_args.command {
         Commands::Add(args) => args.run(&common_args),
+        Commands::Affected(args) => args.run(&common_args),
+        Commands::Amend(args) => args.run(&common_args),
         Commands::Apply(args) => args.run(&common_args),
+        Commands::BlameFile(args) => args.run(&common_args),
         Commands::Branch(args) => args.run(&common_args),
+        Commands::Cache(args) => args.run(&common_args),
+        Commands::Changelog(args) => args.run(&common_args),
+        Commands::Check(args) => args.run(&common_args),
         Commands::Checkout(args) => args.run(&common_args),
         Commands::Ci(args) => args.run(&common_args),
         Commands::Clone(args) => args.run(&common_args),
         Commands::Clean(args) => args.run(&common_args),
         Commands::Commit(args) => args.run(&common_args),
+        Commands::Convert(args) => args.run(&common_args),
         Commands::Create(args) => args.run(&common_args),
+        Commands::Deps(args) => args.run(&common_args),
+        Commands::Diff(args) => args.run(&common_args),
+        Commands::Encode(args) => args.run(&common_args),
+        Commands::Environment(args) => args.run(&common_args),
         Commands::Fetch(args) => args.run(&common_args),
+        Commands::Guard(args) => args.run(&common_args),
         Commands::Hook(args) => args.run(&common_args),
         Commands::Init(args) => args.save_config(&common_args),
         Commands::Invite(args) => args.run(&common_args),
+        Commands::Issue(args) => args.run(&common_args),
+        Commands::Lfs(args) => args.run(&common_args),
+        Commands::Lock(args) => args.run(&common_args),
+        Commands::Mailmap(args) => args.run(&common_args),
         Commands::Merge(args) => args.run(&common_args),
         Commands::Make(args) => args.run(&common_args),
+        Commands::Normalize(args) => args.run(&common_args),
+        Commands::Notifications(args) => args.run(&common_args),
+        Commands::Open(args) => args.run(&common_args),
+        Commands::Packages(args) => args.run(&common_args),
+        Commands::Pages(args) => args.run(&common_args),
+        Commands::Pr(args) => args.run(&common_args),
+        Commands::Project(args) => args.run(&common_args),
+        Commands::PruneBranches(args) => args.run(&common_args),
         Commands::Pull(args) => args.run(&common_args),
+        Commands::PurgeFile(args) => args.run(&common_args),
         Commands::Push(args) => args.run(&common_args),
+        Commands::Release(args) => args.run(&common_args),
         Commands::Remove(args) => args.run(&common_args),
         Commands::Rename(args) => args.run(&common_args),
+        Commands::Repair(args) => args.run(&common_args),
+        Commands::Review(args) => args.run(&common_args),
+        Commands::Runner(args) => args.run(&common_args),
+        Commands::Search(args) => args.run(&common_args),
+        Commands::Secret(args) => args.run(&common_args),
         Commands::Set(args) => args.run(&common_args),
         Commands::Show(args) => args.run(&common_args),
+        Commands::Snapshot(args) => args.run(&common_args),
+        Commands::Star(args) => args.run(&common_args),
+        Commands::State(args) => args.run(&common_args),
+        Commands::Stats(args) => args.run(&common_args),
         Commands::Status(args) => args.run(&common_args),
         Commands::Template(args) => args.run(&common_args),
         Commands::Topic(args) => args.run(&common_args),
         Commands::Transfer(args) => args.run(&common_args),
+        Commands::Triage(args) => args.run(&common_args),
+        #[cfg(feature = "tui")]
+        Commands::Tui(args) => args.run(&common_args),
+        Commands::Unlock(args) => args.run(&common_args),
+        Commands::Upstream(args) => args.run(&common_args),
+        Commands::Verify(args) => args.run(&common_args),
+        Commands::Version(args) => args.run(&common_args),
+        Commands::Watch(args) => args.run(&common_args),
+        Commands::Whoami(args) => args.run(&common_args),
         Commands::Workflow(args) => args.run(&common_args),
+        Commands::Workspace(args) => args.run(&common_args),
     }
 }
+
+/// Take the lock of the root directory for commands that change local repositories
+fn lock(common_args: &Args) -> Result<Option<RunLock>> {
+    if common_args.no_lock
+        || !common_args.command.changes_local_repos()
+        || run_lock::held_by_parent()
+    {
+        return Ok(None);
+    }
+    let root = match Config::root() {
+        Ok(root) => root,
+        Err(_) => return Ok(None),
+    };
+
+    let command: Vec<_> = std::env::args().skip(1).collect();
+    let lock = RunLock::acquire(Path::new(&root), &command.join(" "), common_args.wait)?;
+    std::env::set_var(run_lock::LOCK_ENV, std::process::id().to_string());
+    Ok(Some(lock))
+}
@@ -0,0 +1,118 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// A link in a markdown document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Link {
+    /// Line of the link, starting at 1
+    pub line: usize,
+    pub target: String,
+}
+
+/// The inline links, images, autolinks and link reference definitions of a markdown document
+///
+/// Links in fenced code blocks and code spans are left out, since they are examples rather than
+/// references.
+pub fn links(content: &str) -> Vec<Link> {
+    static PATTERNS: OnceLock<[Regex; 3]> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            // [text](target "title"), ![alt](<target with spaces>) and targets with (parens)
+            Regex::new(
+                r#"\]\(\s*(?:<([^>]+)>|((?:[^()\s]|\([^()\s]*\))+))(?:\s+["'(][^)]*)?\s*\)"#,
+            )
+            .unwrap(),
+            // <https://example.com>
+            Regex::new(r"<(https?://[^>\s]+)>").unwrap(),
+            // [label]: target "title"
+            Regex::new(r"^ {0,3}\[[^\]]+\]:\s*<?([^\s>]+)>?").unwrap(),
+        ]
+    });
+
+    let mut links = vec![];
+    for (i, line) in code_free_lines(content).into_iter().enumerate() {
+        let line = match line {
+            Some(line) => line,
+            None => continue,
+        };
+        for pattern in patterns {
+            for captures in pattern.captures_iter(&line) {
+                if let Some(target) = captures.iter().skip(1).flatten().next() {
+                    links.push(Link {
+                        line: i + 1,
+                        target: target.as_str().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// The lines of the document with code spans removed, None for the lines of fenced code blocks
+pub fn code_free_lines(content: &str) -> Vec<Option<String>> {
+    let mut fence: Option<&str> = None;
+    content
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+            match (fence, marker) {
+                (None, Some(marker)) => {
+                    fence = Some(marker);
+                    None
+                }
+                (Some(open), Some(marker)) if open == marker => {
+                    fence = None;
+                    None
+                }
+                (Some(_), _) => None,
+                (None, None) => Some(without_code_spans(line)),
+            }
+        })
+        .collect()
+}
+
+fn without_code_spans(line: &str) -> String {
+    line.split('`')
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 0)
+        .map(|(_, part)| part)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        let content = "\
+# Title
+
+See [the docs](docs/README.md#setup) and ![logo](<img/logo 1.png> \"Logo\").
+Mail <https://giellalt.github.io>, read [Sami](https://en.wikipedia.org/wiki/Sami_(people)), not `[code](ignored.md)`.
+
+```
+[example](ignored.md)
+```
+
+[ref]: https://example.com/page \"Page\"
+";
+        let targets: Vec<_> = links(content)
+            .into_iter()
+            .map(|l| (l.line, l.target))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                (3, "docs/README.md#setup".to_string()),
+                (3, "img/logo 1.png".to_string()),
+                (4, "https://en.wikipedia.org/wiki/Sami_(people)".to_string()),
+                (4, "https://giellalt.github.io".to_string()),
+                (10, "https://example.com/page".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::config::{env_var, Config};
+use crate::git::GitCredential;
+use crate::read_only;
+use anyhow::{anyhow, bail, Result};
+use reqwest::{blocking as req, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A secondary remote that `gut push --mirror-to` copies all branches and tags to
+///
+/// Mirrors are configured per owner and name in the `[mirrors.<owner>.<name>]` tables of the
+/// config file, `{name}` in the url is replaced by the name of the repository:
+///
+/// ```toml
+/// [mirrors.giellalt.uit]
+/// url = "https://git.uit.no/giellalt-mirror/{name}.git"
+/// gitlab_api = "https://git.uit.no/api/v4"
+/// token_env = "UIT_GITLAB_TOKEN"
+/// ```
+///
+/// With a GitLab api and a token, projects that are missing on the mirror are created as
+/// private projects in the group of the url.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Mirror {
+    pub url: String,
+    /// Base url of the GitLab api, e.g. `https://gitlab.com/api/v4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gitlab_api: Option<String>,
+    /// Environment variable with the access token of the mirror, so that it is not saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+impl Mirror {
+    pub fn for_owner(config: &Config, owner: &str, name: &str) -> Result<Mirror> {
+        config
+            .mirrors
+            .iter()
+            .find(|(o, _)| o.eq_ignore_ascii_case(owner))
+            .and_then(|(_, mirrors)| mirrors.get(name))
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no mirror {} for owner {} in [mirrors.{}.{}] of the config file",
+                    name,
+                    owner,
+                    owner,
+                    name
+                )
+            })
+    }
+
+    /// The remote url of a repository on the mirror
+    pub fn url_for(&self, repo_name: &str) -> String {
+        self.url.replace("{name}", repo_name)
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.token_env.as_deref().and_then(env_var)
+    }
+
+    /// Credential to push over https, GitLab accepts a token with any user name
+    pub fn credential(&self) -> Option<GitCredential> {
+        self.token()
+            .map(|token| GitCredential::new("oauth2".to_string(), token))
+    }
+
+    /// Create the project of a repository on the mirror when it does not exist yet
+    ///
+    /// Returns whether the project was created. Without a GitLab api or a token nothing is
+    /// created, and pushing to a missing project fails.
+    pub fn ensure_project(&self, repo_name: &str) -> Result<bool> {
+        let (Some(api), Some(token)) = (&self.gitlab_api, self.token()) else {
+            return Ok(false);
+        };
+        let api = api.trim_end_matches('/');
+        let url = self.url_for(repo_name);
+        let path = project_path(&url)
+            .ok_or_else(|| anyhow!("Cannot find the project path in the mirror url {}", url))?;
+        let (group, name) = path
+            .rsplit_once('/')
+            .ok_or_else(|| anyhow!("The mirror url {} has no group", url))?;
+
+        let client = req::Client::new();
+        // https://docs.gitlab.com/ee/api/projects.html#get-a-single-project
+        let response = client
+            .get(format!("{}/projects/{}", api, encode_path(&path)))
+            .header("PRIVATE-TOKEN", &token)
+            .send()?;
+        match response.status() {
+            StatusCode::NOT_FOUND => {}
+            status if status.is_success() => return Ok(false),
+            status => bail!("GitLab answered {} for project {}", status, path),
+        }
+
+        // https://docs.gitlab.com/ee/api/namespaces.html#get-details-of-a-namespace
+        let response = client
+            .get(format!("{}/namespaces/{}", api, encode_path(group)))
+            .header("PRIVATE-TOKEN", &token)
+            .send()?;
+        if !response.status().is_success() {
+            bail!("GitLab answered {} for group {}", response.status(), group);
+        }
+        let namespace: Namespace = response.json()?;
+
+        // https://docs.gitlab.com/ee/api/projects.html#create-a-project
+        let url = format!("{}/projects", api);
+        read_only::ensure_writable(&format!("POST {}", url))?;
+        let response = client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &token)
+            .json(&json!({
+                "name": name,
+                "path": name,
+                "namespace_id": namespace.id,
+                "visibility": "private",
+            }))
+            .send()?;
+        if !response.status().is_success() {
+            bail!(
+                "GitLab answered {} when creating project {}",
+                response.status(),
+                path
+            );
+        }
+        Ok(true)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Namespace {
+    id: u64,
+}
+
+/// The path of the project in a remote url, e.g. `giellalt-mirror/lang-sme`
+fn project_path(url: &str) -> Option<String> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+fn encode_path(path: &str) -> String {
+    path.replace('/', "%2F")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_path() {
+        assert_eq!(
+            project_path("https://git.uit.no/giellalt-mirror/lang-sme.git"),
+            Some("giellalt-mirror/lang-sme".to_string())
+        );
+        assert_eq!(
+            project_path("git@gitlab.com:uit/giellalt/lang-sme.git"),
+            Some("uit/giellalt/lang-sme".to_string())
+        );
+        assert_eq!(
+            project_path("ssh://git@gitlab.com:2222/uit/lang-sme"),
+            Some("uit/lang-sme".to_string())
+        );
+        assert_eq!(project_path("https://git.uit.no/"), None);
+    }
+
+    #[test]
+    fn test_url_for() {
+        let mirror = Mirror {
+            url: "git@git.uit.no:giellalt-mirror/{name}.git".to_string(),
+            gitlab_api: None,
+            token_env: None,
+        };
+        assert_eq!(
+            mirror.url_for("lang-sme"),
+            "git@git.uit.no:giellalt-mirror/lang-sme.git"
+        );
+    }
+}
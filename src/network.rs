@@ -0,0 +1,98 @@
+use crate::config::{env_var, Config};
+use crate::convert::{Protocol, RemoteUrl};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// How the remotes of an owner are reached
+///
+/// They are configured per owner in the `[network.<owner>]` tables of the config file. What is
+/// not set there comes from the environment and the git config, like git itself reads it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Proxy for https remotes, e.g. `http://proxy.example.com:8080`
+    ///
+    /// Without it libgit2 uses `http.proxy` of the git config and the `https_proxy` and
+    /// `http_proxy` environment variables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Command that runs instead of ssh for ssh remotes, e.g. `ssh -i ~/.ssh/work -p 2222`
+    ///
+    /// Without it `GIT_SSH_COMMAND` and `core.sshCommand` of the git config are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_command: Option<String>,
+}
+
+impl NetworkSettings {
+    /// The settings for a remote url, with the git config of the repository when there is one
+    pub fn for_url(url: &str, git_config: Option<&git2::Config>) -> NetworkSettings {
+        let remote = RemoteUrl::parse(url);
+        let configured = remote
+            .as_ref()
+            .and_then(|r| {
+                configured()
+                    .iter()
+                    .find(|(owner, _)| owner.eq_ignore_ascii_case(&r.owner))
+            })
+            .map(|(_, settings)| settings.clone())
+            .unwrap_or_default();
+
+        let is_ssh = match &remote {
+            Some(remote) => remote.protocol == Protocol::Ssh,
+            None => url.starts_with("ssh://") || (!url.contains("://") && url.contains('@')),
+        };
+        let ssh_command = if is_ssh {
+            configured
+                .ssh_command
+                .or_else(|| env_var("GIT_SSH_COMMAND"))
+                .or_else(|| ssh_command_from_git_config(git_config))
+        } else {
+            None
+        };
+
+        NetworkSettings {
+            proxy: configured.proxy,
+            ssh_command,
+        }
+    }
+
+    pub fn proxy_options(&self) -> git2::ProxyOptions<'static> {
+        let mut proxy = git2::ProxyOptions::new();
+        match &self.proxy {
+            Some(url) => proxy.url(url),
+            None => proxy.auto(),
+        };
+        proxy
+    }
+}
+
+fn ssh_command_from_git_config(git_config: Option<&git2::Config>) -> Option<String> {
+    let value = match git_config {
+        Some(config) => config.get_string("core.sshCommand"),
+        None => git2::Config::open_default().and_then(|c| c.get_string("core.sshCommand")),
+    };
+    value.ok().filter(|v| !v.is_empty())
+}
+
+fn configured() -> &'static BTreeMap<String, NetworkSettings> {
+    static NETWORK: OnceLock<BTreeMap<String, NetworkSettings>> = OnceLock::new();
+    NETWORK.get_or_init(|| Config::load().map(|c| c.network).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_command_only_for_ssh_remotes() {
+        std::env::set_var("GIT_SSH_COMMAND", "ssh -i ~/.ssh/work");
+        let ssh = NetworkSettings::for_url("git@github.com:giellalt/lang-sme.git", None);
+        assert_eq!(ssh.ssh_command.as_deref(), Some("ssh -i ~/.ssh/work"));
+        let ssh = NetworkSettings::for_url("ssh://git@example.com/group/sub/repo", None);
+        assert_eq!(ssh.ssh_command.as_deref(), Some("ssh -i ~/.ssh/work"));
+        let https = NetworkSettings::for_url("https://github.com/giellalt/lang-sme.git", None);
+        assert_eq!(https.ssh_command, None);
+        std::env::remove_var("GIT_SSH_COMMAND");
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The settings of an organisation that `gut show org-settings` audits
+///
+/// The same table is the policy file, where every setting that is left out is not checked:
+///
+/// ```toml
+/// default_repository_permission = "read"
+/// members_can_create_repositories = false
+/// two_factor_requirement_enabled = true
+/// actions_allowed_actions = "selected"
+/// default_workflow_permissions = "read"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OrgSettings {
+    /// Permission of members on the repositories: none, read, write or admin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_repository_permission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members_can_create_repositories: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_factor_requirement_enabled: Option<bool>,
+    /// Repositories that can run GitHub Actions: all, none or selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions_enabled_repositories: Option<String>,
+    /// Actions that workflows can use: all, local_only or selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions_allowed_actions: Option<String>,
+    /// Permissions of the GITHUB_TOKEN in workflows: read or write
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_workflow_permissions: Option<String>,
+}
+
+/// A setting of an organisation that differs from the policy
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Deviation {
+    pub setting: &'static str,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+impl OrgSettings {
+    pub fn load(path: &Path) -> Result<OrgSettings> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read the policy file {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Invalid policy file {:?}", path))
+    }
+
+    /// Every setting with its value, None when it is not set or not visible to the token
+    pub fn values(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            (
+                "default_repository_permission",
+                self.default_repository_permission.clone(),
+            ),
+            (
+                "members_can_create_repositories",
+                self.members_can_create_repositories.map(|b| b.to_string()),
+            ),
+            (
+                "two_factor_requirement_enabled",
+                self.two_factor_requirement_enabled.map(|b| b.to_string()),
+            ),
+            (
+                "actions_enabled_repositories",
+                self.actions_enabled_repositories.clone(),
+            ),
+            (
+                "actions_allowed_actions",
+                self.actions_allowed_actions.clone(),
+            ),
+            (
+                "default_workflow_permissions",
+                self.default_workflow_permissions.clone(),
+            ),
+        ]
+    }
+
+    /// The settings that the policy sets to another value
+    pub fn deviations(&self, policy: &OrgSettings) -> Vec<Deviation> {
+        self.values()
+            .into_iter()
+            .zip(policy.values())
+            .filter_map(|((setting, actual), (_, expected))| match expected {
+                Some(expected) if actual.as_ref() != Some(&expected) => Some(Deviation {
+                    setting,
+                    expected,
+                    actual,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviations() {
+        let policy: OrgSettings = toml::from_str(
+            r#"
+            default_repository_permission = "read"
+            two_factor_requirement_enabled = true
+            default_workflow_permissions = "read"
+            "#,
+        )
+        .unwrap();
+        let actual = OrgSettings {
+            default_repository_permission: Some("write".into()),
+            members_can_create_repositories: Some(true),
+            two_factor_requirement_enabled: None,
+            default_workflow_permissions: Some("read".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            actual.deviations(&policy),
+            vec![
+                Deviation {
+                    setting: "default_repository_permission",
+                    expected: "read".into(),
+                    actual: Some("write".into()),
+                },
+                Deviation {
+                    setting: "two_factor_requirement_enabled",
+                    expected: "true".into(),
+                    actual: None,
+                },
+            ]
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use crate::config::Config;
 use anyhow::{anyhow, Context};
 use std::fs;
 use std::fs::{create_dir_all, write};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use walkdir::WalkDir;
 
 fn config_dir() -> Option<PathBuf> {
@@ -22,9 +24,62 @@ pub fn user_path() -> Option<PathBuf> {
     Some(config)
 }
 
+pub fn locks_path() -> Option<PathBuf> {
+    let dir = config_dir()?;
+    let locks = dir.join("locks.json");
+    Some(locks)
+}
+
+pub fn size_baseline_path() -> Option<PathBuf> {
+    let dir = config_dir()?;
+    let baseline = dir.join("size-baseline.json");
+    Some(baseline)
+}
+
+pub fn completion_cache_path() -> Option<PathBuf> {
+    let dir = config_dir()?;
+    let cache = dir.join("completion-cache.json");
+    Some(cache)
+}
+
+/// Directory of the journals of bulk commands that can be resumed
+pub fn journals_dir() -> Option<PathBuf> {
+    let dir = config_dir()?.join("journals");
+    dir.ensure_dir_exists().ok()
+}
+
+/// Where a repository is cloned, either directly in the organisation directory or in one of
+/// its group directories when it was cloned with `--org-structure topics`
 pub fn local_path_repo(organisation: &str, name: &str, root: &str) -> PathBuf {
     let root_dir = Path::new(&root);
-    root_dir.join(organisation).join(name)
+    let org_dir = root_dir.join(organisation);
+    let flat = org_dir.join(name);
+    if flat.exists() {
+        return flat;
+    }
+
+    org_dir
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| is_group_dir(p))
+        .map(|p| p.join(name))
+        .find(|p| p.exists())
+        .unwrap_or(flat)
+}
+
+/// A directory inside an organisation directory that holds repositories instead of being one
+pub fn is_group_dir(path: &Path) -> bool {
+    path.is_dir()
+        && !path.join(".git").exists()
+        && path
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .any(|e| e.path().join(".git").exists())
 }
 
 pub fn local_path_org(organisation: &str, root: &str) -> anyhow::Result<PathBuf> {
@@ -51,7 +106,8 @@ impl EnsureDirExists for std::path::PathBuf {
     }
 }
 
-pub fn remove_path(path: &PathBuf) -> std::io::Result<()> {
+pub fn remove_path(path: &Path) -> std::io::Result<()> {
+    let path = long_path(path);
     if path.is_file() {
         std::fs::remove_file(path)
     } else {
@@ -59,6 +115,32 @@ pub fn remove_path(path: &PathBuf) -> std::io::Result<()> {
     }
 }
 
+/// A path that the file functions of Windows accept beyond 260 characters
+///
+/// On Windows this is the absolute path with the `\\?\` prefix, elsewhere the path itself.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    let absolute = match std::path::absolute(path) {
+        Ok(absolute) => absolute,
+        Err(_) => return path.to_path_buf(),
+    };
+    let text = absolute.to_string_lossy();
+    if text.starts_with(r"\\?\") {
+        absolute
+    } else if let Some(share) = text.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", share))
+    } else {
+        let mut long = std::ffi::OsString::from(r"\\?\");
+        long.push(absolute.as_os_str());
+        PathBuf::from(long)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
 pub fn dir_name(path: &PathBuf) -> anyhow::Result<String> {
     let dir_name = path
         .file_name()
@@ -69,6 +151,29 @@ pub fn dir_name(path: &PathBuf) -> anyhow::Result<String> {
     Ok(dir_name)
 }
 
+/// Name of the owner directory that a repository directory is in
+///
+/// This is the first directory below the root, so that repositories in group directories
+/// belong to the right owner.
+pub fn owner_name(path: &Path) -> anyhow::Result<String> {
+    let owner = root_dir()
+        .and_then(|root| path.strip_prefix(root).ok())
+        .filter(|relative| relative.components().count() > 1)
+        .and_then(|relative| relative.components().next())
+        .map(|c| c.as_os_str())
+        .or_else(|| path.parent().and_then(|p| p.file_name()))
+        .and_then(|n| n.to_str())
+        .with_context(|| format!("{:?}, there is no owner directory for this path", path))?
+        .to_string();
+    Ok(owner)
+}
+
+fn root_dir() -> Option<&'static PathBuf> {
+    static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    ROOT.get_or_init(|| Config::root().ok().map(PathBuf::from))
+        .as_ref()
+}
+
 pub fn parrent(path: &PathBuf) -> anyhow::Result<String> {
     let parrent = path
         .parent()
@@ -82,32 +187,33 @@ pub fn parrent(path: &PathBuf) -> anyhow::Result<String> {
 
 pub fn write_content(file_path: &PathBuf, content: &str) -> anyhow::Result<()> {
     let parrent = parrent(file_path)?;
-    create_dir_all(parrent)?;
-    write(file_path, content)?;
+    create_dir_all(long_path(Path::new(&parrent)))?;
+    write(long_path(file_path), content)?;
     Ok(())
 }
 
-pub fn all_files(dir: &PathBuf) -> Vec<String> {
-    let len = if let Some(dir_str) = dir.to_str() {
-        dir_str.len() + 1
-    } else {
-        return vec![];
-    };
-
-    let walk_dirs = WalkDir::new(dir);
+/// All files in a directory outside of `.git`, relative to the directory and with `/` as the
+/// separator, like git writes paths
+pub fn all_files(dir: &Path) -> Vec<String> {
+    let dir = long_path(dir);
+    let walk_dirs = WalkDir::new(&dir);
     let mut files = vec![];
     for entry in walk_dirs
         .into_iter()
-        //.filter_entry(|de| de.file_type().is_file())
+        .filter_entry(|de| de.file_name() != ".git")
         .filter_map(|e| e.ok())
     {
         if entry.file_type().is_file() {
-            if let Some(str) = entry.into_path().to_str() {
-                let (_a, b) = str.split_at(len);
-                if !b.starts_with(".git/") {
-                    //println!("File: {}", b);
-                    files.push(b.to_string());
-                }
+            let relative = match entry.path().strip_prefix(&dir) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            let parts: Option<Vec<_>> = relative
+                .components()
+                .map(|c| c.as_os_str().to_str())
+                .collect();
+            if let Some(parts) = parts {
+                files.push(parts.join("/"));
             }
         }
     }
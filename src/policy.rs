@@ -0,0 +1,188 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use unicode_normalization::is_nfc;
+
+/// The rules that the git hooks installed by `gut hook install` enforce
+///
+/// They are configured per owner in the `[hooks.<owner>]` tables of the config file, owners
+/// without a table get the defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HookPolicy {
+    /// Largest file that can be committed, in megabytes, 0 turns the check off
+    pub max_file_size_mb: u64,
+    /// Refuse file names that are not in Unicode NFC, as macOS writes decomposed names
+    pub block_nfd: bool,
+    /// Regex that the first line of every commit message has to start with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_prefix: Option<String>,
+}
+
+impl Default for HookPolicy {
+    fn default() -> Self {
+        HookPolicy {
+            max_file_size_mb: 50,
+            block_nfd: true,
+            message_prefix: None,
+        }
+    }
+}
+
+impl HookPolicy {
+    pub fn for_owner(config: &Config, owner: &str) -> HookPolicy {
+        config
+            .hooks
+            .iter()
+            .find(|(o, _)| o.eq_ignore_ascii_case(owner))
+            .map(|(_, policy)| policy.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn check_file(&self, path: &str, size: u64) -> Vec<Violation> {
+        let mut violations = vec![];
+        if self.max_file_size_mb > 0 && size > self.max_file_size_mb * 1024 * 1024 {
+            violations.push(Violation::LargeFile {
+                path: path.to_string(),
+                size,
+                limit_mb: self.max_file_size_mb,
+            });
+        }
+        if self.block_nfd && !is_nfc(path) {
+            violations.push(Violation::NfdName {
+                path: path.to_string(),
+            });
+        }
+        violations
+    }
+
+    pub fn message_regex(&self) -> Result<Option<Regex>> {
+        self.message_prefix
+            .as_deref()
+            .map(|p| {
+                Regex::new(&format!("^(?:{})", p))
+                    .with_context(|| format!("Invalid message_prefix {:?} in the hook policy", p))
+            })
+            .transpose()
+    }
+
+    pub fn check_message(regex: &Regex, commit: &str, message: &str) -> Option<Violation> {
+        let summary = message.lines().next().unwrap_or_default();
+        if regex.is_match(summary) {
+            None
+        } else {
+            Some(Violation::MessagePrefix {
+                commit: commit.to_string(),
+                summary: summary.to_string(),
+                prefix: regex.as_str().to_string(),
+            })
+        }
+    }
+
+    /// One line summary of the rules, for tables
+    pub fn describe(&self) -> String {
+        let mut rules = vec![];
+        if self.max_file_size_mb > 0 {
+            rules.push(format!("files up to {} MB", self.max_file_size_mb));
+        }
+        if self.block_nfd {
+            rules.push("NFC file names".to_string());
+        }
+        if let Some(prefix) = &self.message_prefix {
+            rules.push(format!("messages match {}", prefix));
+        }
+        if rules.is_empty() {
+            "no rules".to_string()
+        } else {
+            rules.join(", ")
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Violation {
+    LargeFile {
+        path: String,
+        size: u64,
+        limit_mb: u64,
+    },
+    NfdName {
+        path: String,
+    },
+    MessagePrefix {
+        commit: String,
+        summary: String,
+        prefix: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::LargeFile {
+                path,
+                size,
+                limit_mb,
+            } => write!(
+                f,
+                "{} is {:.1} MB, larger than the limit of {} MB (use git lfs)",
+                path,
+                *size as f64 / 1024.0 / 1024.0,
+                limit_mb
+            ),
+            Violation::NfdName { path } => write!(
+                f,
+                "{:?} is not in Unicode NFC, rename it with precomposed letters",
+                path
+            ),
+            Violation::MessagePrefix {
+                commit,
+                summary,
+                prefix,
+            } => write!(
+                f,
+                "commit {} {:?} does not match {}",
+                &commit[..commit.len().min(8)],
+                summary,
+                prefix
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_file() {
+        let policy = HookPolicy {
+            max_file_size_mb: 1,
+            ..Default::default()
+        };
+
+        assert!(policy.check_file("src/sme.lexc", 1024 * 1024).is_empty());
+        assert!(policy.check_file("s\u{00e1}mi.txt", 10).is_empty());
+        assert_eq!(
+            policy.check_file("sa\u{0301}mi.txt", 2 * 1024 * 1024).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_check_message() {
+        let policy = HookPolicy {
+            message_prefix: Some(r"\[[A-Z]+-\d+\]|Merge".to_string()),
+            ..Default::default()
+        };
+        let regex = policy.message_regex().unwrap().unwrap();
+
+        assert_eq!(
+            HookPolicy::check_message(&regex, "abc", "[GL-12] Fix twolc\n\nDetails"),
+            None
+        );
+        assert!(HookPolicy::check_message(&regex, "abc", "Fix twolc [GL-12]").is_some());
+    }
+}
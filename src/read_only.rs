@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// An action that read-only mode refuses
+#[derive(thiserror::Error, Debug)]
+#[error(
+    "Refusing to {0} in read-only mode (--read-only, read_only in the config or GUT_READ_ONLY)"
+)]
+pub struct ReadOnly(pub String);
+
+/// Turn read-only mode on for this run, from `--read-only` or the config
+pub fn init(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Fail when read-only mode is on, for everything that changes repositories or the organisation
+///
+/// `action` completes "Refusing to ...", e.g. `"POST https://api.github.com/..."`.
+pub fn ensure_writable(action: &str) -> Result<(), ReadOnly> {
+    if is_enabled() {
+        Err(ReadOnly(action.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_writable() {
+        init(false);
+        assert!(ensure_writable("push").is_ok());
+
+        init(true);
+        let error = ensure_writable("push to origin").unwrap_err();
+        assert!(error.to_string().starts_with("Refusing to push to origin"));
+        init(false);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::toml::{read_file, write_to_file};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+/// Pending action of a template apply that is waiting for --continue or --abort
+pub static PENDING_TEMPLATE_APPLY: &str = "template apply";
+/// Pending action of an upstream merge that stopped at a conflict
+pub static PENDING_UPSTREAM_MERGE: &str = "upstream merge";
+
+/// What gut did last in a local repository
+///
+/// It is kept in .git/gut/state.toml, next to the files of an ongoing template apply, so that
+/// it never shows up as a change of the repository.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct RepoState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_pull: Option<DateTime<Utc>>,
+    /// Revision of the template that was applied last
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_rev: Option<usize>,
+    /// Branch that was created last by create branch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub campaign_branch: Option<String>,
+    /// Actions that were started and have to be finished by hand
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+}
+
+impl RepoState {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join(".git/gut/state.toml")
+    }
+
+    /// The state of a repository, which is empty when gut has not recorded anything yet
+    pub fn load(dir: &Path) -> Result<RepoState> {
+        let path = RepoState::path(dir);
+        if !path.exists() {
+            return Ok(RepoState::default());
+        }
+        read_file(path)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = RepoState::path(dir);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        write_to_file(path, self)
+    }
+
+    /// Change the state of a repository
+    ///
+    /// The work of the command is done by then, so a state that cannot be written is only logged.
+    pub fn record(dir: &Path, change: impl FnOnce(&mut RepoState)) {
+        let result = RepoState::load(dir).and_then(|mut state| {
+            change(&mut state);
+            state.save(dir)
+        });
+        if let Err(e) = result {
+            log::warn!("Cannot record the state of {:?}: {:?}", dir, e);
+        }
+    }
+
+    pub fn add_pending(&mut self, action: &str) {
+        if !self.pending.iter().any(|a| a == action) {
+            self.pending.push(action.to_string());
+        }
+    }
+
+    pub fn remove_pending(&mut self, action: &str) {
+        self.pending.retain(|a| a != action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record() {
+        let dir = tempdir().unwrap();
+        assert_eq!(RepoState::load(dir.path()).unwrap(), RepoState::default());
+
+        RepoState::record(dir.path(), |s| {
+            s.campaign_branch = Some("bump-version".to_string());
+            s.add_pending(PENDING_TEMPLATE_APPLY);
+            s.add_pending(PENDING_TEMPLATE_APPLY);
+        });
+        RepoState::record(dir.path(), |s| s.template_rev = Some(3));
+
+        let state = RepoState::load(dir.path()).unwrap();
+        assert_eq!(state.campaign_branch.as_deref(), Some("bump-version"));
+        assert_eq!(state.template_rev, Some(3));
+        assert_eq!(state.pending, vec![PENDING_TEMPLATE_APPLY.to_string()]);
+    }
+}
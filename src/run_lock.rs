@@ -0,0 +1,224 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+/// Name of the lock file in the root directory
+static LOCK_FILE: &str = ".gut.lock";
+
+/// Pid of the gut run that holds the lock, set for the processes it starts, e.g. the scripts of
+/// gut apply, so that the gut commands they run do not wait for their parent
+pub static LOCK_ENV: &str = "GUT_LOCK_HELD";
+
+/// Whether this process was started by a gut run that holds the lock
+pub fn held_by_parent() -> bool {
+    std::env::var(LOCK_ENV)
+        .map(|pid| pid != std::process::id().to_string())
+        .unwrap_or(false)
+}
+
+/// A lock file that lets only one gut run at a time change the repositories of a root directory
+///
+/// The lock is released when it is dropped. A lock whose process is not running anymore is
+/// stale and is taken over.
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+}
+
+/// Who holds a lock
+#[derive(Serialize, Deserialize, Debug)]
+struct Holder {
+    pid: u32,
+    host: String,
+    started: DateTime<Utc>,
+    command: String,
+}
+
+impl Holder {
+    fn current(command: &str) -> Holder {
+        Holder {
+            pid: std::process::id(),
+            host: host(),
+            started: Utc::now(),
+            command: command.to_string(),
+        }
+    }
+
+    /// Only the processes of this host can be checked, a lock of another host is never stale
+    fn is_stale(&self) -> bool {
+        self.host == host() && !is_running(self.pid)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "gut {} (pid {} on {}, started {})",
+            self.command,
+            self.pid,
+            self.host,
+            self.started.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+impl RunLock {
+    /// Take the lock of a root directory
+    ///
+    /// When another run holds it, this waits for it with `wait`, and stops with an error
+    /// otherwise.
+    pub fn acquire(root: &Path, command: &str, wait: bool) -> Result<RunLock> {
+        let path = root.join(LOCK_FILE);
+        let mut waiting = false;
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let holder = toml::to_string(&Holder::current(command))?;
+                    file.write_all(holder.as_bytes())
+                        .with_context(|| format!("Cannot write the lock file {:?}", path))?;
+                    return Ok(RunLock { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Cannot create the lock file {:?}", path))
+                }
+            }
+
+            match read_holder(&path) {
+                Some(holder) if holder.is_stale() => {
+                    log::warn!("Removing the stale lock of {}", holder.describe());
+                    remove(&path)?;
+                    continue;
+                }
+                // A lock that cannot be read is being written, unless it is old
+                None if is_old(&path) => {
+                    log::warn!("Removing the unreadable lock file {:?}", path);
+                    remove(&path)?;
+                    continue;
+                }
+                Some(holder) if !wait => bail!(
+                    "{} is running in {}. Run again with --wait to wait for it to finish",
+                    holder.describe(),
+                    root.display()
+                ),
+                Some(holder) if !waiting => {
+                    println!("Waiting for {} to finish...", holder.describe());
+                    waiting = true;
+                }
+                _ => {}
+            }
+
+            sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("Cannot remove the lock file {:?}: {:?}", self.path, e);
+        }
+    }
+}
+
+fn read_holder(path: &Path) -> Option<Holder> {
+    let content = fs::read_to_string(path).ok()?;
+    toml::from_str(&content).ok()
+}
+
+fn remove(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Cannot remove the lock file {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_old(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|age| age > Duration::from_secs(60))
+        .unwrap_or(false)
+}
+
+fn host() -> String {
+    Command::new("hostname")
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        return proc.join(pid.to_string()).exists();
+    }
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(true)
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    // Without a match, tasklist prints an info line instead of the quoted pid
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .stderr(Stdio::null())
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(true)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock() {
+        let root = tempdir().unwrap();
+
+        let lock = RunLock::acquire(root.path(), "pull", false).unwrap();
+        let held = RunLock::acquire(root.path(), "push", false).unwrap_err();
+        assert!(format!("{}", held).starts_with("gut pull (pid"));
+        drop(lock);
+        assert!(!root.path().join(LOCK_FILE).exists());
+    }
+
+    #[test]
+    #[cfg(any(unix, windows))]
+    fn test_stale_lock() {
+        let root = tempdir().unwrap();
+        let stale = Holder {
+            pid: u32::MAX,
+            ..Holder::current("pull")
+        };
+        fs::write(
+            root.path().join(LOCK_FILE),
+            toml::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+        assert!(RunLock::acquire(root.path(), "push", false).is_ok());
+    }
+}
@@ -0,0 +1,159 @@
+use clap::ValueEnum;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A file format whose syntax `gut check syntax` validates
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Syntax {
+    /// XML, e.g. pipespecs and xsl files
+    Xml,
+    /// YAML, e.g. GitHub workflows
+    Yaml,
+    /// TOML, e.g. metadata and manifests
+    Toml,
+}
+
+impl Syntax {
+    /// The format of a file, by the extension of its path
+    pub fn of(path: &str) -> Option<Syntax> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "xml" | "xsl" | "xslt" | "xsd" => Some(Syntax::Xml),
+            "yml" | "yaml" => Some(Syntax::Yaml),
+            "toml" => Some(Syntax::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Syntax::Xml => "xml",
+            Syntax::Yaml => "yaml",
+            Syntax::Toml => "toml",
+        })
+    }
+}
+
+/// The first syntax error of a file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseError {
+    /// Line of the error, starting at 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn at(content: &str, offset: usize, message: impl Into<String>) -> ParseError {
+        let offset = offset.min(content.len());
+        let line = content.as_bytes()[..offset]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1;
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// The first syntax error of the content, None when it parses
+pub fn check(syntax: Syntax, content: &str) -> Option<ParseError> {
+    match syntax {
+        Syntax::Xml => check_xml(content),
+        Syntax::Yaml => check_yaml(content),
+        Syntax::Toml => check_toml(content),
+    }
+}
+
+fn check_xml(content: &str) -> Option<ParseError> {
+    let mut reader = Reader::from_str(content);
+    // The elements that are open, with where they start
+    let mut open: Vec<(String, usize)> = vec![];
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if let Some(Err(error)) = e.attributes().find(|a| a.is_err()) {
+                    return Some(ParseError::at(content, start, error.to_string()));
+                }
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                open.push((name, start));
+            }
+            Ok(Event::Empty(e)) => {
+                if let Some(Err(error)) = e.attributes().find(|a| a.is_err()) {
+                    return Some(ParseError::at(content, start, error.to_string()));
+                }
+            }
+            Ok(Event::End(_)) => {
+                open.pop();
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                let position = reader.error_position() as usize;
+                return Some(ParseError::at(content, position, e.to_string()));
+            }
+        }
+    }
+
+    open.pop().map(|(name, start)| {
+        ParseError::at(
+            content,
+            start,
+            format!("The element <{}> is not closed", name),
+        )
+    })
+}
+
+fn check_yaml(content: &str) -> Option<ParseError> {
+    // A file can hold several documents, separated by ---
+    for document in serde_yaml::Deserializer::from_str(content) {
+        if let Err(e) = serde_yaml::Value::deserialize(document) {
+            let line = e.location().map(|l| l.line()).unwrap_or(1);
+            return Some(ParseError {
+                line,
+                message: e.to_string(),
+            });
+        }
+    }
+    None
+}
+
+fn check_toml(content: &str) -> Option<ParseError> {
+    let e = content.parse::<toml::Table>().err()?;
+    let offset = e.span().map(|s| s.start).unwrap_or(0);
+    Some(ParseError::at(content, offset, e.message().trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(syntax: Syntax, content: &str) -> Option<usize> {
+        check(syntax, content).map(|e| e.line)
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(Syntax::of("src/pipespec.xml"), Some(Syntax::Xml));
+        assert_eq!(Syntax::of(".github/workflows/ci.YML"), Some(Syntax::Yaml));
+        assert_eq!(Syntax::of("Makefile"), None);
+
+        let xml = "<?xml version=\"1.0\"?>\n<pipespec>\n  <pipeline name=\"x\"/>\n</pipespec>\n";
+        assert_eq!(line(Syntax::Xml, xml), None);
+        assert_eq!(line(Syntax::Xml, "<a>\n  <b>\n</a>\n"), Some(3));
+        assert_eq!(line(Syntax::Xml, "<a>\n  <b/>\n"), Some(1));
+
+        assert_eq!(line(Syntax::Yaml, "on: push\n---\njobs: {}\n"), None);
+        assert!(line(Syntax::Yaml, "on: push\njobs: [build\n").is_some());
+
+        assert_eq!(line(Syntax::Toml, "[package]\nname = \"sme\"\n"), None);
+        assert_eq!(line(Syntax::Toml, "[package]\nname = \n"), Some(2));
+    }
+}
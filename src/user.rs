@@ -1,3 +1,4 @@
+use super::config::env_var;
 use super::github;
 use super::path::user_path;
 use super::toml::{read_file, write_to_file};
@@ -30,7 +31,25 @@ impl User {
         read_file(path().ok_or_else(|| anyhow::anyhow!("No user path found"))?)
     }
 
+    /// The saved user, unless GUT_TOKEN is set
+    ///
+    /// The username for GUT_TOKEN is taken from GUT_USERNAME, or looked up on GitHub.
+    pub fn load() -> Result<User> {
+        let token = match env_var("GUT_TOKEN") {
+            Some(token) => token,
+            None => return User::from_config(),
+        };
+        let username = match env_var("GUT_USERNAME") {
+            Some(username) => username,
+            None => github::is_valid_token(&token)?,
+        };
+        Ok(User { token, username })
+    }
+
     pub fn token() -> Result<String> {
+        if let Some(token) = env_var("GUT_TOKEN") {
+            return Ok(token);
+        }
         let user = User::from_config()?;
         Ok(user.token)
     }
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A file that holds the version of a repository, and where the version is in it
+///
+/// Rules are set in the `[[version_files]]` tables of the config file. They come before the
+/// built-in rules for Cargo.toml, package.json, pyproject.toml and configure.ac, so a rule for
+/// the same file replaces the built-in one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VersionFile {
+    /// Regex of the names of the repositories the rule is for, all repositories when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repos: Option<String>,
+    /// Path of the file in the repository
+    pub file: String,
+    /// Regex with one capture group around the version, only the first match is changed
+    pub pattern: String,
+}
+
+impl VersionFile {
+    fn builtin(file: &str, pattern: &str) -> VersionFile {
+        VersionFile {
+            repos: None,
+            file: file.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    pub fn applies_to(&self, repo: &str) -> Result<bool> {
+        match &self.repos {
+            Some(repos) => Ok(Regex::new(repos)
+                .with_context(|| format!("Invalid repos regex of {}", self.file))?
+                .is_match(repo)),
+            None => Ok(true),
+        }
+    }
+
+    /// Where the version is in the content of the file
+    pub fn find(&self, content: &str) -> Result<Option<Range<usize>>> {
+        let pattern = Regex::new(&self.pattern)
+            .with_context(|| format!("Invalid version pattern of {}", self.file))?;
+        Ok(pattern
+            .captures(content)
+            .and_then(|c| c.get(1))
+            .map(|m| m.range()))
+    }
+}
+
+fn builtins() -> Vec<VersionFile> {
+    vec![
+        VersionFile::builtin("Cargo.toml", r#"(?m)^version\s*=\s*"([^"]+)""#),
+        VersionFile::builtin("package.json", r#""version"\s*:\s*"([^"]+)""#),
+        VersionFile::builtin("pyproject.toml", r#"(?m)^version\s*=\s*"([^"]+)""#),
+        VersionFile::builtin(
+            "configure.ac",
+            r"AC_INIT\(\s*\[?[^\],]*\]?\s*,\s*\[?([0-9][^\],)\s]*)",
+        ),
+    ]
+}
+
+/// The rules for a repository, at most one per file
+pub fn rules_for(repo: &str, configured: &[VersionFile]) -> Result<Vec<VersionFile>> {
+    let mut rules: Vec<VersionFile> = vec![];
+    for rule in configured.iter().cloned().chain(builtins()) {
+        if rule.applies_to(repo)? && !rules.iter().any(|r| r.file == rule.file) {
+            rules.push(rule);
+        }
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version_in(file: &str, content: &str) -> Option<String> {
+        let rules = rules_for("lang-sme", &[]).unwrap();
+        let rule = rules.iter().find(|r| r.file == file).unwrap();
+        rule.find(content).unwrap().map(|r| content[r].to_string())
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        assert_eq!(
+            version_in(
+                "configure.ac",
+                "AC_INIT([giella-sme], [0.1.2], [feedback@divvun.no])"
+            ),
+            Some("0.1.2".to_string())
+        );
+        assert_eq!(
+            version_in(
+                "Cargo.toml",
+                "[package]\nname = \"divvunspell\"\nversion = \"1.0.0-beta.5\"\n"
+            ),
+            Some("1.0.0-beta.5".to_string())
+        );
+        assert_eq!(
+            version_in(
+                "package.json",
+                "{\n  \"name\": \"x\",\n  \"version\": \"2.3.4\"\n}"
+            ),
+            Some("2.3.4".to_string())
+        );
+        assert_eq!(
+            version_in("pyproject.toml", "[project]\nname = \"x\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_configured_rules_replace_builtins() {
+        let configured = vec![
+            VersionFile {
+                repos: Some("^keyboard-".to_string()),
+                file: "project.yaml".to_string(),
+                pattern: r"version:\s*(\S+)".to_string(),
+            },
+            VersionFile {
+                repos: Some("^lang-".to_string()),
+                file: "configure.ac".to_string(),
+                pattern: r"GIELLA_VERSION=(\S+)".to_string(),
+            },
+        ];
+
+        let rules = rules_for("lang-sme", &configured).unwrap();
+        assert!(!rules.iter().any(|r| r.file == "project.yaml"));
+        let configure = rules.iter().find(|r| r.file == "configure.ac").unwrap();
+        assert_eq!(configure.pattern, r"GIELLA_VERSION=(\S+)");
+
+        let rules = rules_for("keyboard-sme", &configured).unwrap();
+        assert!(rules.iter().any(|r| r.file == "project.yaml"));
+    }
+}
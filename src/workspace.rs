@@ -0,0 +1,125 @@
+use crate::config::Config;
+use crate::filter::Filter;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// A named set of repositories that can span several owners
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Workspace {
+    #[serde(default)]
+    pub selections: Vec<Selection>,
+}
+
+/// The repositories of one owner that belong to a workspace
+///
+/// A repository is selected when it is listed in `repos`, matches `regex` or has `topic`.
+/// A selection without any of them selects every repository of the owner.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repos: Vec<String>,
+}
+
+impl Selection {
+    pub fn filter(&self) -> Result<Option<Filter>> {
+        self.regex
+            .as_deref()
+            .map(|r| {
+                r.parse()
+                    .with_context(|| format!("Invalid regex {:?} for owner {}", r, self.owner))
+            })
+            .transpose()
+    }
+
+    pub fn is_match(&self, name: &str, topics: &[String], filter: Option<&Filter>) -> bool {
+        if filter.is_none() && self.topic.is_none() && self.repos.is_empty() {
+            return true;
+        }
+        self.repos.iter().any(|r| r.eq_ignore_ascii_case(name))
+            || filter.map(|f| f.is_match(name)).unwrap_or(false)
+            || self
+                .topic
+                .as_ref()
+                .map(|t| topics.contains(t))
+                .unwrap_or(false)
+    }
+}
+
+static ACTIVE: OnceLock<Option<(String, Workspace)>> = OnceLock::new();
+
+/// Select the workspace that the repository selection of this run uses
+///
+/// Without a name, the workspace chosen with `gut workspace use` is used, if there is one,
+/// unless the organisation or the regex of the command is given.
+pub fn activate(name: Option<&str>, selects_repos: bool) -> Result<()> {
+    let config = match (name, Config::load()) {
+        (_, Ok(config)) => config,
+        (None, Err(_)) => return Ok(()),
+        (Some(_), Err(e)) => return Err(e),
+    };
+
+    let current = match config.current_workspace {
+        Some(current) if name.is_none() && selects_repos => {
+            log::info!(
+                "Using the organisation and regex of the command instead of the current workspace {}",
+                current
+            );
+            None
+        }
+        current => current,
+    };
+
+    let active = match name.map(str::to_string).or(current) {
+        Some(name) => {
+            let workspace = config.workspaces.get(&name).cloned().ok_or_else(|| {
+                anyhow!(
+                    "There is no workspace {}. Create it with gut workspace create",
+                    name
+                )
+            })?;
+            log::info!("Using workspace {}", name);
+            Some((name, workspace))
+        }
+        None => None,
+    };
+
+    let _ = ACTIVE.set(active);
+    Ok(())
+}
+
+pub fn active() -> Option<&'static (String, Workspace)> {
+    ACTIVE.get().and_then(|a| a.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(regex: Option<&str>, topic: Option<&str>, repos: &[&str]) -> Selection {
+        Selection {
+            owner: "giellalt".to_string(),
+            regex: regex.map(str::to_string),
+            topic: topic.map(str::to_string),
+            repos: repos.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_selection_is_match() {
+        let all = selection(None, None, &[]);
+        assert!(all.is_match("lang-sme", &[], None));
+
+        let s = selection(Some("^lang-s"), Some("keyboard"), &["divvun-gramcheck"]);
+        let filter = s.filter().unwrap();
+        assert!(s.is_match("lang-sma", &[], filter.as_ref()));
+        assert!(s.is_match("Divvun-Gramcheck", &[], filter.as_ref()));
+        assert!(s.is_match("keyboard-sme", &["keyboard".to_string()], filter.as_ref()));
+        assert!(!s.is_match("lang-fin", &[], filter.as_ref()));
+    }
+}